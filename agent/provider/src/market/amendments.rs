@@ -0,0 +1,182 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::Duration;
+use url::Url;
+
+use ya_client::cli::ApiOpts;
+use ya_client::web::rest_api_url;
+use ya_client_model::market::{Agreement, Reason};
+
+use super::negotiator::AmendmentChanges;
+
+const MARKET_URL_ENV_VAR: &str = "YAGNA_MARKET_URL";
+const MARKET_API_SUFFIX: &str = "market-api/v1/";
+
+/// Changes to approved Agreement, as returned by market REST API.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Amendment {
+    pub amendment_id: String,
+    pub agreement_id: String,
+    pub demand_properties: Option<Value>,
+    pub offer_properties: Option<Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AmendmentIssuer {
+    Provider,
+    Requestor,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub enum AmendmentEventType {
+    Proposed,
+    Approved,
+    Rejected,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmendmentEvent {
+    pub amendment_id: String,
+    pub agreement_id: String,
+    pub event_date: DateTime<Utc>,
+    pub event_type: AmendmentEventType,
+    pub issuer: AmendmentIssuer,
+}
+
+/// Agreement Amendments endpoints of market REST API. They aren't available
+/// in `MarketProviderApi` yet.
+pub struct AmendmentApi {
+    client: awc::Client,
+    url: Url,
+}
+
+impl AmendmentApi {
+    pub fn try_new(opts: &ApiOpts) -> Result<AmendmentApi> {
+        let url = match std::env::var(MARKET_URL_ENV_VAR) {
+            Ok(url) => Url::parse(&url)?,
+            Err(_) => rest_api_url().join(MARKET_API_SUFFIX)?,
+        };
+        let client = awc::Client::builder()
+            .bearer_auth(&opts.app_key)
+            .timeout(Duration::from_secs(60 * 5))
+            .finish();
+        Ok(AmendmentApi { client, url })
+    }
+
+    pub async fn collect_events(
+        &self,
+        timeout: f32,
+        after_timestamp: &DateTime<Utc>,
+        max_events: i32,
+        session_id: &str,
+    ) -> Result<Vec<AmendmentEvent>> {
+        let mut url = self.url.join("agreementAmendmentEvents")?;
+        url.query_pairs_mut()
+            .append_pair("timeout", &timeout.to_string())
+            .append_pair("afterTimestamp", &after_timestamp.to_rfc3339())
+            .append_pair("maxEvents", &max_events.to_string())
+            .append_pair("appSessionId", session_id);
+        self.send(self.client.get(url.as_str()), None).await
+    }
+
+    pub async fn get_amendment(&self, agreement_id: &str, amendment_id: &str) -> Result<Amendment> {
+        let url = self.amendment_url(agreement_id, amendment_id, "")?;
+        self.send(self.client.get(url.as_str()), None).await
+    }
+
+    pub async fn approve_amendment(&self, agreement_id: &str, amendment_id: &str) -> Result<()> {
+        let url = self.amendment_url(agreement_id, amendment_id, "/approve")?;
+        self.send::<Value>(self.client.post(url.as_str()), None)
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn reject_amendment(
+        &self,
+        agreement_id: &str,
+        amendment_id: &str,
+        reason: &Option<Reason>,
+    ) -> Result<()> {
+        let url = self.amendment_url(agreement_id, amendment_id, "/reject")?;
+        let body = serde_json::to_value(reason)?;
+        self.send::<Value>(self.client.post(url.as_str()), Some(body))
+            .await
+            .map(|_| ())
+    }
+
+    fn amendment_url(&self, agreement_id: &str, amendment_id: &str, action: &str) -> Result<Url> {
+        Ok(self.url.join(&format!(
+            "agreements/{}/amendments/{}{}",
+            agreement_id, amendment_id, action
+        ))?)
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: awc::ClientRequest,
+        body: Option<Value>,
+    ) -> Result<T> {
+        let url = request.get_uri().to_string();
+        let mut response = match body {
+            Some(body) => request.send_json(&body).await,
+            None => request.send().await,
+        }
+        .map_err(|e| anyhow!("Request to [{}] failed. Error: {}", url, e))?;
+
+        if !response.status().is_success() {
+            let body = response.body().await.unwrap_or_default();
+            return Err(anyhow!(
+                "Request to [{}] failed with status {}: {}",
+                url,
+                response.status(),
+                String::from_utf8_lossy(&body)
+            ));
+        }
+        Ok(response.json().await?)
+    }
+}
+
+/// Builds Agreement with Amendment changes applied, for evaluation by negotiator.
+pub fn apply_amendment(agreement: &Agreement, amendment: &Amendment) -> Agreement {
+    let mut agreement = agreement.clone();
+    if let Some(changes) = &amendment.demand_properties {
+        apply_properties(&mut agreement.demand.properties, changes);
+    }
+    if let Some(changes) = &amendment.offer_properties {
+        apply_properties(&mut agreement.offer.properties, changes);
+    }
+    agreement
+}
+
+/// Lists properties, which Amendment changes in comparison to Agreement.
+pub fn amendment_changes(agreement: &Agreement, amendment: &Amendment) -> AmendmentChanges {
+    AmendmentChanges {
+        demand: changed_properties(&agreement.demand.properties, &amendment.demand_properties),
+        offer: changed_properties(&agreement.offer.properties, &amendment.offer_properties),
+    }
+}
+
+fn changed_properties(properties: &Value, changes: &Option<Value>) -> Vec<String> {
+    let changes = match changes {
+        Some(changes) => ya_agreement_utils::agreement::flatten(changes.clone()),
+        None => return vec![],
+    };
+    let current = ya_agreement_utils::agreement::flatten(properties.clone());
+    changes
+        .into_iter()
+        .filter(|(name, value)| current.get(name) != Some(value))
+        .map(|(name, _)| name)
+        .collect()
+}
+
+fn apply_properties(properties: &mut Value, changes: &Value) {
+    let mut flat = ya_agreement_utils::agreement::flatten(properties.clone());
+    flat.extend(ya_agreement_utils::agreement::flatten(changes.clone()));
+    *properties = Value::Object(flat);
+}
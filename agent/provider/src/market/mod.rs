@@ -1,3 +1,4 @@
+pub mod amendments;
 pub mod config;
 pub mod negotiator;
 pub mod presets;
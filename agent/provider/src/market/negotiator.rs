@@ -9,7 +9,10 @@ pub use accept_all::AcceptAllNegotiator;
 pub use composite::CompositeNegotiator;
 
 pub use common::{
-    AgreementResponse, AgreementResult, Negotiator, NegotiatorAddr, ProposalResponse,
+    AgreementResponse, AgreementResult, AmendmentResponse, Negotiator, NegotiatorAddr,
+    ProposalResponse,
 };
 
-pub use component::{NegotiationResult, NegotiatorComponent, NegotiatorsPack, ProposalView};
+pub use component::{
+    AmendmentChanges, AmendmentResult, NegotiationResult, NegotiatorComponent, NegotiatorsPack, ProposalView,
+};
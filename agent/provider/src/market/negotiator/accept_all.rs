@@ -1,11 +1,11 @@
 use actix::{Actor, Context, Handler};
 
-use ya_client_model::market::NewOffer;
+use ya_client_model::market::{NewOffer, Reason};
 
 use super::common::offer_definition_to_offer;
-use super::common::{AgreementResponse, Negotiator, ProposalResponse};
+use super::common::{AgreementResponse, AmendmentResponse, Negotiator, ProposalResponse};
 use crate::market::negotiator::common::{
-    AgreementFinalized, CreateOffer, ReactToAgreement, ReactToProposal,
+    AgreementFinalized, CreateOffer, ReactToAgreement, ReactToAmendment, ReactToProposal,
 };
 
 #[derive(Debug)]
@@ -41,6 +41,23 @@ impl Handler<ReactToAgreement> for AcceptAllNegotiator {
     }
 }
 
+impl Handler<ReactToAmendment> for AcceptAllNegotiator {
+    type Result = anyhow::Result<AmendmentResponse>;
+
+    fn handle(&mut self, msg: ReactToAmendment, _: &mut Context<Self>) -> Self::Result {
+        // Even this negotiator can't let Requestor change our Offer (for example pricing).
+        Ok(match msg.changes.offer.first() {
+            Some(property) => AmendmentResponse::RejectAmendment {
+                reason: Some(Reason::new(format!(
+                    "Amendment can't change Offer property '{}'.",
+                    property
+                ))),
+            },
+            None => AmendmentResponse::ApproveAmendment,
+        })
+    }
+}
+
 impl Handler<AgreementFinalized> for AcceptAllNegotiator {
     type Result = anyhow::Result<()>;
 
@@ -7,7 +7,8 @@ use ya_client_model::market::Reason;
 use crate::display::EnableDisplay;
 use crate::market::negotiator::factory::AgreementExpirationNegotiatorConfig;
 use crate::market::negotiator::{
-    AgreementResult, NegotiationResult, NegotiatorComponent, ProposalView,
+    AgreementResult, AmendmentChanges, AmendmentResult, NegotiationResult, NegotiatorComponent,
+    ProposalView,
 };
 
 /// Negotiator that can reject Requestors, that request too long Agreement
@@ -33,8 +34,6 @@ pub static AGREEMENT_EXPIRATION_PROPERTY: &'static str = "/golem/srv/comp/expira
 pub static DEBIT_NOTE_ACCEPT_TIMEOUT_PROPERTY_FLAT: &'static str =
     "golem.com.payment.debit-notes.accept-timeout?";
 
-// Note: Tests are using this.
-#[allow(dead_code)]
 pub static AGREEMENT_EXPIRATION_PROPERTY_FLAT: &'static str = "golem.srv.comp.expiration";
 
 impl LimitExpiration {
@@ -58,6 +57,35 @@ impl LimitExpiration {
 
         Ok(component)
     }
+
+    /// Returns rejection reason, if Requestor's expiration doesn't fit in our limits.
+    fn check_expiration(
+        &self,
+        demand: &ProposalView,
+        req_deadline: &Option<Duration>,
+    ) -> Result<Option<Reason>> {
+        let req_expiration = proposal_expiration_from(&demand)?;
+
+        // Let's check if Requestor is able to accept DebitNotes.
+        let max_expiration_delta = match req_deadline {
+            Some(_) => self.max_expiration,
+            None => self.max_expiration_without_deadline,
+        };
+
+        let now = Utc::now();
+        let max_expiration = now + max_expiration_delta;
+        let min_expiration = now + self.min_expiration;
+
+        if req_expiration > max_expiration || req_expiration < min_expiration {
+            return Ok(Some(Reason::new(format!(
+                "Proposal expires at: {} which is less than {} or more than {} from now",
+                req_expiration,
+                self.min_expiration.display(),
+                max_expiration_delta.display()
+            ))));
+        }
+        Ok(None)
+    }
 }
 
 fn proposal_expiration_from(proposal: &ProposalView) -> Result<DateTime<Utc>> {
@@ -89,32 +117,16 @@ impl NegotiatorComponent for LimitExpiration {
     ) -> anyhow::Result<NegotiationResult> {
         let req_deadline = debit_deadline_from(demand)?;
         let our_deadline = debit_deadline_from(&offer)?;
-        let req_expiration = proposal_expiration_from(&demand)?;
-
-        // Let's check if Requestor is able to accept DebitNotes.
-        let max_expiration_delta = match &req_deadline {
-            Some(_) => self.max_expiration,
-            None => self.max_expiration_without_deadline,
-        };
 
-        let now = Utc::now();
-        let max_expiration = now + max_expiration_delta;
-        let min_expiration = now + self.min_expiration;
-
-        if req_expiration > max_expiration || req_expiration < min_expiration {
+        if let Some(reason) = self.check_expiration(demand, &req_deadline)? {
             log::info!(
                 "Negotiator: Reject proposal [{}] due to expiration limits.",
                 demand.agreement_id
             );
             return Ok(NegotiationResult::Reject {
-                reason: Some(Reason::new(format!(
-                    "Proposal expires at: {} which is less than {} or more than {} from now",
-                    req_expiration,
-                    self.min_expiration.display(),
-                    max_expiration_delta.display()
-                ))),
+                reason: Some(reason),
             });
-        };
+        }
 
         // Maybe we negotiated different deadline in previous negotiation iteration?
         Ok(match (req_deadline, our_deadline) {
@@ -173,6 +185,52 @@ impl NegotiatorComponent for LimitExpiration {
         Ok(template)
     }
 
+    /// Requestor can extend Agreement expiration, but new value must be within
+    /// the same limits as during negotiations. DebitNotes deadline can't be changed.
+    fn negotiate_amendment(
+        &mut self,
+        demand: &ProposalView,
+        _offer: &ProposalView,
+        changes: &AmendmentChanges,
+    ) -> anyhow::Result<AmendmentResult> {
+        let deadline_changed = changes
+            .demand
+            .iter()
+            .chain(changes.offer.iter())
+            .any(|property| property == DEBIT_NOTE_ACCEPT_TIMEOUT_PROPERTY_FLAT);
+        if deadline_changed {
+            return Ok(AmendmentResult::Reject {
+                reason: Some(Reason::new(
+                    "DebitNote acceptance deadline can't be changed by Amendment.",
+                )),
+            });
+        }
+
+        // Amendment can change other properties, for example pricing.
+        // We shouldn't validate expiration, which remains unchanged.
+        if !changes
+            .demand
+            .iter()
+            .any(|property| property == AGREEMENT_EXPIRATION_PROPERTY_FLAT)
+        {
+            return Ok(AmendmentResult::Approve { validated: vec![] });
+        }
+
+        let req_deadline = debit_deadline_from(demand)?;
+        Ok(match self.check_expiration(demand, &req_deadline)? {
+            Some(reason) => {
+                log::info!(
+                    "Negotiator: Reject Amendment of Agreement [{}] due to expiration limits.",
+                    demand.agreement_id
+                );
+                AmendmentResult::Reject {
+                    reason: Some(reason),
+                }
+            }
+            None => AmendmentResult::Approve { validated: vec![] },
+        })
+    }
+
     fn on_agreement_terminated(
         &mut self,
         _agreement_id: &str,
@@ -189,6 +247,7 @@ impl NegotiatorComponent for LimitExpiration {
 #[cfg(test)]
 mod test_expiration_negotiator {
     use super::*;
+    use crate::market::negotiator::NegotiatorsPack;
     use ya_agreement_utils::agreement::expand;
     use ya_agreement_utils::{InfNodeInfo, NodeInfo, OfferTemplate, ServiceInfo};
 
@@ -381,4 +440,144 @@ mod test_expiration_negotiator {
             result => panic!("Expected NegotiationResult::Negotiating. Got: {:?}", result),
         }
     }
+
+    fn expiration_changed() -> AmendmentChanges {
+        AmendmentChanges {
+            demand: vec![AGREEMENT_EXPIRATION_PROPERTY_FLAT.to_string()],
+            offer: vec![],
+        }
+    }
+
+    /// Requestor extends Agreement within expiration limits. Amendment is approved.
+    #[test]
+    fn test_amendment_extension_within_limits() {
+        let config = expiration_config();
+        let mut negotiator = LimitExpiration::new(&config).unwrap();
+
+        let demand = properties_to_proposal(serde_json::json!({
+            AGREEMENT_EXPIRATION_PROPERTY_FLAT: (Utc::now() + Duration::minutes(25)).timestamp_millis(),
+            DEBIT_NOTE_ACCEPT_TIMEOUT_PROPERTY_FLAT: 120,
+        }));
+        let offer = properties_to_proposal(serde_json::json!({
+            DEBIT_NOTE_ACCEPT_TIMEOUT_PROPERTY_FLAT: 120,
+        }));
+
+        match negotiator
+            .negotiate_amendment(&demand, &offer, &expiration_changed())
+            .unwrap()
+        {
+            AmendmentResult::Approve { .. } => (),
+            result => panic!("Expected AmendmentResult::Approve. Got: {:?}", result),
+        }
+    }
+
+    /// Extension beyond `max_agreement_expiration` is rejected.
+    #[test]
+    fn test_amendment_extension_too_long() {
+        let config = expiration_config();
+        let mut negotiator = LimitExpiration::new(&config).unwrap();
+
+        let demand = properties_to_proposal(serde_json::json!({
+            AGREEMENT_EXPIRATION_PROPERTY_FLAT: (Utc::now() + Duration::minutes(45)).timestamp_millis(),
+            DEBIT_NOTE_ACCEPT_TIMEOUT_PROPERTY_FLAT: 120,
+        }));
+        let offer = properties_to_proposal(serde_json::json!({
+            DEBIT_NOTE_ACCEPT_TIMEOUT_PROPERTY_FLAT: 120,
+        }));
+
+        match negotiator
+            .negotiate_amendment(&demand, &offer, &expiration_changed())
+            .unwrap()
+        {
+            AmendmentResult::Reject { reason } => {
+                assert!(reason.unwrap().message.contains("Proposal expires at"))
+            }
+            result => panic!("Expected AmendmentResult::Reject. Got: {:?}", result),
+        }
+    }
+
+    /// Amendment, which doesn't touch expiration, isn't rejected because of
+    /// expiration, which is already close.
+    #[test]
+    fn test_amendment_without_expiration_change() {
+        let config = expiration_config();
+        let mut negotiator = LimitExpiration::new(&config).unwrap();
+
+        let demand = properties_to_proposal(serde_json::json!({
+            AGREEMENT_EXPIRATION_PROPERTY_FLAT: (Utc::now() + Duration::minutes(1)).timestamp_millis(),
+            DEBIT_NOTE_ACCEPT_TIMEOUT_PROPERTY_FLAT: 120,
+        }));
+        let offer = properties_to_proposal(serde_json::json!({
+            DEBIT_NOTE_ACCEPT_TIMEOUT_PROPERTY_FLAT: 120,
+        }));
+        let changes = AmendmentChanges {
+            demand: vec!["golem.srv.custom.property".to_string()],
+            offer: vec![],
+        };
+
+        match negotiator
+            .negotiate_amendment(&demand, &offer, &changes)
+            .unwrap()
+        {
+            AmendmentResult::Approve { .. } => (),
+            result => panic!("Expected AmendmentResult::Approve. Got: {:?}", result),
+        }
+    }
+
+    /// Amendment can't change DebitNotes deadline agreed during negotiations.
+    #[test]
+    fn test_amendment_changes_deadline() {
+        let config = expiration_config();
+        let mut negotiator = LimitExpiration::new(&config).unwrap();
+
+        let demand = properties_to_proposal(serde_json::json!({
+            AGREEMENT_EXPIRATION_PROPERTY_FLAT: (Utc::now() + Duration::minutes(7)).timestamp_millis(),
+        }));
+        let offer = properties_to_proposal(serde_json::json!({
+            DEBIT_NOTE_ACCEPT_TIMEOUT_PROPERTY_FLAT: 120,
+        }));
+        let changes = AmendmentChanges {
+            demand: vec![DEBIT_NOTE_ACCEPT_TIMEOUT_PROPERTY_FLAT.to_string()],
+            offer: vec![],
+        };
+
+        match negotiator
+            .negotiate_amendment(&demand, &offer, &changes)
+            .unwrap()
+        {
+            AmendmentResult::Reject { reason } => {
+                assert!(reason.unwrap().message.contains("can't be changed"))
+            }
+            result => panic!("Expected AmendmentResult::Reject. Got: {:?}", result),
+        }
+    }
+
+    /// Requestor can't lower Provider's price. No component validates pricing changes.
+    #[test]
+    fn test_pack_rejects_offer_changes() {
+        let config = expiration_config();
+        let mut pack = NegotiatorsPack::new().add_component(
+            "LimitExpiration",
+            Box::new(LimitExpiration::new(&config).unwrap()),
+        );
+
+        let demand = properties_to_proposal(serde_json::json!({
+            AGREEMENT_EXPIRATION_PROPERTY_FLAT: (Utc::now() + Duration::minutes(7)).timestamp_millis(),
+        }));
+        let offer = properties_to_proposal(serde_json::json!({
+            "golem.com.pricing.model.linear.coeffs": [0.0, 0.0, 0.0],
+        }));
+        let changes = AmendmentChanges {
+            demand: vec![],
+            offer: vec!["golem.com.pricing.model.linear.coeffs".to_string()],
+        };
+
+        match pack.negotiate_amendment(&demand, &offer, &changes).unwrap() {
+            AmendmentResult::Reject { reason } => assert!(reason
+                .unwrap()
+                .message
+                .contains("golem.com.pricing.model.linear.coeffs")),
+            result => panic!("Expected AmendmentResult::Reject. Got: {:?}", result),
+        }
+    }
 }
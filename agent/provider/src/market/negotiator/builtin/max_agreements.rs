@@ -6,7 +6,8 @@ use ya_client_model::market::Reason;
 
 use crate::market::negotiator::factory::LimitAgreementsNegotiatorConfig;
 use crate::market::negotiator::{
-    AgreementResult, NegotiationResult, NegotiatorComponent, ProposalView,
+    AgreementResult, AmendmentChanges, AmendmentResult, NegotiationResult, NegotiatorComponent,
+    ProposalView,
 };
 
 /// Negotiator that can limit number of running agreements.
@@ -57,6 +58,16 @@ impl NegotiatorComponent for MaxAgreements {
         Ok(offer_template)
     }
 
+    fn negotiate_amendment(
+        &mut self,
+        _demand: &ProposalView,
+        _offer: &ProposalView,
+        _changes: &AmendmentChanges,
+    ) -> anyhow::Result<AmendmentResult> {
+        // Amendment doesn't change number of running Agreements.
+        Ok(AmendmentResult::Approve { validated: vec![] })
+    }
+
     fn on_agreement_terminated(
         &mut self,
         agreement_id: &str,
@@ -7,6 +7,7 @@ use ya_agreement_utils::{AgreementView, OfferDefinition};
 use ya_client::model::market::Reason;
 use ya_client_model::market::{NewOffer, Proposal};
 
+use super::AmendmentChanges;
 use crate::market::termination_reason::BreakReason;
 
 /// Response for requestor proposals.
@@ -43,6 +44,19 @@ pub enum AgreementResponse {
     },
 }
 
+/// Response for Agreement Amendments proposed by Requestor.
+#[derive(Debug, Display)]
+pub enum AmendmentResponse {
+    ApproveAmendment,
+    #[display(
+        fmt = "RejectAmendment{}",
+        "reason.as_ref().map(|r| format!(\" (reason: {})\", r)).unwrap_or(\"\".into())"
+    )]
+    RejectAmendment {
+        reason: Option<Reason>,
+    },
+}
+
 /// Result of agreement execution.
 #[derive(Clone)]
 pub enum AgreementResult {
@@ -85,6 +99,15 @@ pub struct ReactToAgreement {
     pub agreement: AgreementView,
 }
 
+/// Reactions to Amendments of already approved Agreements.
+/// `agreement` contains Agreement with proposed changes applied.
+#[derive(Message)]
+#[rtype(result = "Result<AmendmentResponse>")]
+pub struct ReactToAmendment {
+    pub agreement: AgreementView,
+    pub changes: AmendmentChanges,
+}
+
 /// Agreement finished notifications. Negotiator can adjust his strategy based on it.
 #[derive(Message)]
 #[rtype(result = "Result<()>")]
@@ -115,6 +138,7 @@ pub trait Negotiator:
     + Handler<AgreementFinalized, Result = <AgreementFinalized as Message>::Result>
     + Handler<ReactToProposal, Result = <ReactToProposal as Message>::Result>
     + Handler<ReactToAgreement, Result = <ReactToAgreement as Message>::Result>
+    + Handler<ReactToAmendment, Result = <ReactToAmendment as Message>::Result>
 {
 }
 
@@ -129,6 +153,7 @@ pub struct NegotiatorAddr {
     pub on_finalized: Recipient<AgreementFinalized>,
     pub on_proposal: Recipient<ReactToProposal>,
     pub on_agreement: Recipient<ReactToAgreement>,
+    pub on_amendment: Recipient<ReactToAmendment>,
 }
 
 impl NegotiatorAddr {
@@ -164,6 +189,19 @@ impl NegotiatorAddr {
             .await?
     }
 
+    pub async fn react_to_amendment(
+        &self,
+        amended_agreement: &AgreementView,
+        changes: &AmendmentChanges,
+    ) -> Result<AmendmentResponse> {
+        self.on_amendment
+            .send(ReactToAmendment {
+                agreement: amended_agreement.clone(),
+                changes: changes.clone(),
+            })
+            .await?
+    }
+
     pub async fn agreement_finalized(
         &self,
         agreement_id: &str,
@@ -183,7 +221,8 @@ impl NegotiatorAddr {
             on_create: addr.clone().recipient(),
             on_finalized: addr.clone().recipient(),
             on_proposal: addr.clone().recipient(),
-            on_agreement: addr.recipient(),
+            on_agreement: addr.clone().recipient(),
+            on_amendment: addr.recipient(),
         }
     }
 }
@@ -213,4 +252,19 @@ mod tests {
         assert_eq!(reason.to_string(), "RejectAgreement (reason: 'lato')");
         assert_eq!(no_reason.to_string(), "RejectAgreement");
     }
+
+    #[test]
+    fn test_amendment_response_display() {
+        let reason = AmendmentResponse::RejectAmendment {
+            reason: Some("jesień".into()),
+        };
+        let no_reason = AmendmentResponse::RejectAmendment { reason: None };
+
+        assert_eq!(reason.to_string(), "RejectAmendment (reason: 'jesień')");
+        assert_eq!(no_reason.to_string(), "RejectAmendment");
+        assert_eq!(
+            AmendmentResponse::ApproveAmendment.to_string(),
+            "ApproveAmendment"
+        );
+    }
 }
@@ -9,6 +9,13 @@ use crate::market::negotiator::AgreementResult;
 
 pub type ProposalView = AgreementView;
 
+/// Names of properties (in flat form) changed by Agreement Amendment.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AmendmentChanges {
+    pub demand: Vec<String>,
+    pub offer: Vec<String>,
+}
+
 /// Result returned by `NegotiatorComponent` during Proposals evaluation.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum NegotiationResult {
@@ -24,6 +31,19 @@ pub enum NegotiationResult {
     Reject { reason: Option<Reason> },
 }
 
+/// Result returned by `NegotiatorComponent` during evaluation of changes
+/// proposed to already approved Agreement.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum AmendmentResult {
+    /// `NegotiatorComponent` accepts changes in his part of Agreement specification.
+    /// `validated` lists changed Offer properties, that component checked and
+    /// agrees to. Offer changes not validated by any component are rejected.
+    Approve { validated: Vec<String> },
+    /// Changes are not acceptable and Amendment should be rejected.
+    /// Agreement remains unchanged.
+    Reject { reason: Option<Reason> },
+}
+
 /// `NegotiatorComponent` implements negotiation logic for part of Agreement
 /// specification. Components should be as granular as possible to allow composition
 /// with other Components.
@@ -47,6 +67,16 @@ pub trait NegotiatorComponent {
     fn fill_template(&mut self, offer_template: OfferDefinition)
         -> anyhow::Result<OfferDefinition>;
 
+    /// Called when Requestor proposed changes to already approved Agreement.
+    /// `demand` and `offer` contain Agreement properties with changes applied,
+    /// `changes` lists properties which were modified.
+    fn negotiate_amendment(
+        &mut self,
+        demand: &ProposalView,
+        offer: &ProposalView,
+        changes: &AmendmentChanges,
+    ) -> anyhow::Result<AmendmentResult>;
+
     /// Called when Agreement was finished. `NegotiatorComponent` can use termination
     /// result to adjust his future negotiation strategy.
     fn on_agreement_terminated(
@@ -131,6 +161,51 @@ impl NegotiatorComponent for NegotiatorsPack {
         Ok(offer_template)
     }
 
+    fn negotiate_amendment(
+        &mut self,
+        demand: &ProposalView,
+        offer: &ProposalView,
+        changes: &AmendmentChanges,
+    ) -> anyhow::Result<AmendmentResult> {
+        let mut all_validated = Vec::new();
+        for (name, component) in &mut self.components {
+            match component.negotiate_amendment(demand, offer, changes)? {
+                AmendmentResult::Approve { validated } => all_validated.extend(validated),
+                AmendmentResult::Reject { reason } => {
+                    log::info!(
+                        "Negotiator component '{}' rejected Amendment of Agreement [{}].",
+                        name,
+                        demand.agreement_id
+                    );
+                    return Ok(AmendmentResult::Reject { reason });
+                }
+            }
+        }
+
+        // Requestor can't change our part of Agreement (for example pricing),
+        // unless some component checked, that this exact change is acceptable.
+        if let Some(property) = changes
+            .offer
+            .iter()
+            .find(|property| !all_validated.contains(property))
+        {
+            log::info!(
+                "Negotiator: Reject Amendment of Agreement [{}] changing Offer property '{}'.",
+                demand.agreement_id,
+                property
+            );
+            return Ok(AmendmentResult::Reject {
+                reason: Some(Reason::new(format!(
+                    "Amendment can't change Offer property '{}'.",
+                    property
+                ))),
+            });
+        }
+        Ok(AmendmentResult::Approve {
+            validated: all_validated,
+        })
+    }
+
     fn on_agreement_terminated(
         &mut self,
         agreement_id: &str,
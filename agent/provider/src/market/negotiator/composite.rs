@@ -7,10 +7,12 @@ use ya_agreement_utils::AgreementView;
 use ya_client_model::market::{NewOffer, Reason};
 
 use super::builtin::{LimitExpiration, MaxAgreements};
-use super::common::{offer_definition_to_offer, AgreementResponse, Negotiator, ProposalResponse};
-use super::{NegotiationResult, NegotiatorsPack};
+use super::common::{
+    offer_definition_to_offer, AgreementResponse, AmendmentResponse, Negotiator, ProposalResponse,
+};
+use super::{AmendmentResult, NegotiationResult, NegotiatorsPack};
 use crate::market::negotiator::common::{
-    AgreementFinalized, CreateOffer, ReactToAgreement, ReactToProposal,
+    AgreementFinalized, CreateOffer, ReactToAgreement, ReactToAmendment, ReactToProposal,
 };
 use crate::market::negotiator::factory::CompositeNegotiatorConfig;
 use crate::market::negotiator::{NegotiatorComponent, ProposalView};
@@ -143,6 +145,28 @@ impl Handler<ReactToAgreement> for CompositeNegotiator {
     }
 }
 
+impl Handler<ReactToAmendment> for CompositeNegotiator {
+    type Result = anyhow::Result<AmendmentResponse>;
+
+    fn handle(&mut self, msg: ReactToAmendment, _: &mut Context<Self>) -> Self::Result {
+        let (demand_proposal, offer_proposal) = to_proposal_views(msg.agreement).map_err(|e| {
+            anyhow!(
+                "Negotiator failed to extract Proposals from amended Agreement. {}",
+                e
+            )
+        })?;
+
+        match self.components.negotiate_amendment(
+            &demand_proposal,
+            &offer_proposal,
+            &msg.changes,
+        )? {
+            AmendmentResult::Approve { .. } => Ok(AmendmentResponse::ApproveAmendment),
+            AmendmentResult::Reject { reason } => Ok(AmendmentResponse::RejectAmendment { reason }),
+        }
+    }
+}
+
 impl Handler<AgreementFinalized> for CompositeNegotiator {
     type Result = anyhow::Result<()>;
 
@@ -26,8 +26,13 @@ use ya_utils_actix::{
     actix_signal_handler, forward_actix_handler,
 };
 
+use super::amendments::{
+    amendment_changes, apply_amendment, AmendmentApi, AmendmentEventType, AmendmentIssuer,
+};
 use super::negotiator::factory;
-use super::negotiator::{AgreementResponse, AgreementResult, NegotiatorAddr, ProposalResponse};
+use super::negotiator::{
+    AgreementResponse, AgreementResult, AmendmentResponse, NegotiatorAddr, ProposalResponse,
+};
 use super::Preset;
 use crate::display::EnableDisplay;
 use crate::market::config::MarketConfig;
@@ -69,6 +74,14 @@ pub struct NewAgreement {
     pub agreement: AgreementView,
 }
 
+/// Emitted after we approved Amendment. Carries Agreement with Amendment
+/// changes applied.
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "Result<()>")]
+pub struct AgreementAmended {
+    pub agreement: AgreementView,
+}
+
 // =========================================== //
 // Internal messages
 // =========================================== //
@@ -107,11 +120,13 @@ struct OnAgreementTerminated {
 pub struct ProviderMarket {
     negotiator: Arc<NegotiatorAddr>,
     api: Arc<MarketProviderApi>,
+    amendment_api: Arc<AmendmentApi>,
     subscriptions: HashMap<String, Subscription>,
     config: Arc<MarketConfig>,
 
     /// External actors can listen on this signal.
     pub agreement_signed_signal: SignalSlot<NewAgreement>,
+    pub agreement_amended_signal: SignalSlot<AgreementAmended>,
     pub agreement_terminated_signal: SignalSlot<CloseAgreement>,
 
     /// Infinite tasks requiring to be killed on shutdown.
@@ -123,6 +138,7 @@ struct AsyncCtx {
     market: Addr<ProviderMarket>,
     config: Arc<MarketConfig>,
     api: Arc<MarketProviderApi>,
+    amendment_api: Arc<AmendmentApi>,
     negotiator: Arc<NegotiatorAddr>,
 }
 
@@ -131,13 +147,19 @@ impl ProviderMarket {
     // Initialization
     // =========================================== //
 
-    pub fn new(api: MarketProviderApi, config: MarketConfig) -> ProviderMarket {
+    pub fn new(
+        api: MarketProviderApi,
+        amendment_api: AmendmentApi,
+        config: MarketConfig,
+    ) -> ProviderMarket {
         return ProviderMarket {
             api: Arc::new(api),
+            amendment_api: Arc::new(amendment_api),
             negotiator: Arc::new(NegotiatorAddr::default()),
             config: Arc::new(config),
             subscriptions: HashMap::new(),
             agreement_signed_signal: SignalSlot::<NewAgreement>::new(),
+            agreement_amended_signal: SignalSlot::<AgreementAmended>::new(),
             agreement_terminated_signal: SignalSlot::<CloseAgreement>::new(),
             handles: HashMap::new(),
         };
@@ -147,6 +169,7 @@ impl ProviderMarket {
         AsyncCtx {
             config: self.config.clone(),
             api: self.api.clone(),
+            amendment_api: self.amendment_api.clone(),
             market: ctx.address(),
             negotiator: self.negotiator.clone(),
         }
@@ -177,6 +200,15 @@ impl ProviderMarket {
         // At this moment we only forward agreement to outside world.
         self.agreement_signed_signal.send_signal(msg)
     }
+
+    fn on_agreement_amended(
+        &mut self,
+        msg: AgreementAmended,
+        _ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        log::info!("Agreement [{}] amended.", msg.agreement.agreement_id);
+        self.agreement_amended_signal.send_signal(msg)
+    }
 }
 
 async fn subscribe(
@@ -459,6 +491,107 @@ async fn collect_agreement_events(ctx: AsyncCtx) {
     }
 }
 
+async fn process_amendment(ctx: AsyncCtx, agreement_id: &str, amendment_id: &str) -> Result<()> {
+    log::info!(
+        "Got Amendment [{}] for agreement [{}] from Requestor.",
+        amendment_id,
+        agreement_id
+    );
+
+    let amendment = ctx
+        .amendment_api
+        .get_amendment(agreement_id, amendment_id)
+        .await?;
+    let agreement = ctx.api.get_agreement(agreement_id).await?;
+    let changes = amendment_changes(&agreement, &amendment);
+    let amended = AgreementView::try_from(&apply_amendment(&agreement, &amendment))
+        .map_err(|e| anyhow!("Invalid amended agreement. Error: {}", e))?;
+
+    let action = ctx
+        .negotiator
+        .react_to_amendment(&amended, &changes)
+        .await
+        .map_err(|e| {
+            anyhow!(
+                "Negotiator error while processing Amendment [{}] for agreement [{}]. Error: {}",
+                amendment_id,
+                agreement_id,
+                e
+            )
+        })?;
+
+    log::info!(
+        "Decided to {} [{}] for agreement [{}].",
+        action,
+        amendment_id,
+        agreement_id
+    );
+
+    match action {
+        AmendmentResponse::ApproveAmendment => {
+            ctx.amendment_api
+                .approve_amendment(agreement_id, amendment_id)
+                .await?;
+            // Agreement changes, like extended expiration, must be applied by other modules.
+            ctx.market
+                .send(AgreementAmended { agreement: amended })
+                .await??
+        }
+        AmendmentResponse::RejectAmendment { reason } => {
+            ctx.amendment_api
+                .reject_amendment(agreement_id, amendment_id, &reason)
+                .await?
+        }
+    };
+    Ok(())
+}
+
+async fn collect_amendment_events(ctx: AsyncCtx) {
+    let session = ctx.config.session_id.clone();
+    let timeout = ctx.config.agreement_events_interval;
+    let mut last_timestamp = Utc::now();
+
+    loop {
+        let events = match ctx
+            .amendment_api
+            .collect_events(timeout, &last_timestamp, 15, &session)
+            .await
+        {
+            Err(e) => {
+                log::warn!("Can't query amendment events. Error: {}", e);
+                tokio::time::delay_for(std::time::Duration::from_secs_f32(timeout)).await;
+                continue;
+            }
+            Ok(events) => events,
+        };
+
+        for event in events {
+            last_timestamp = event.event_date;
+
+            // We react only to changes proposed by Requestor. Decisions about
+            // our own Amendments don't require any action.
+            if event.event_type != AmendmentEventType::Proposed
+                || event.issuer != AmendmentIssuer::Requestor
+            {
+                log::trace!("Got: {:?}", event);
+                continue;
+            }
+
+            process_amendment(ctx.clone(), &event.agreement_id, &event.amendment_id)
+                .await
+                .map_err(|e| {
+                    log::warn!(
+                        "Failed to process Amendment [{}] for agreement [{}]. Error: {}",
+                        event.amendment_id,
+                        event.agreement_id,
+                        e
+                    )
+                })
+                .ok();
+        }
+    }
+}
+
 async fn collect_negotiation_events(ctx: AsyncCtx, subscription: Subscription) {
     let ctx = ctx.clone();
     let id = subscription.id.clone();
@@ -529,7 +662,11 @@ impl Actor for ProviderMarket {
         // Note: There will be no collision with subscription ids stored normally here.
         self.handles.insert(
             "collect-agreement-events".to_string(),
-            ctx.spawn(collect_agreement_events(actx).into_actor(self)),
+            ctx.spawn(collect_agreement_events(actx.clone()).into_actor(self)),
+        );
+        self.handles.insert(
+            "collect-amendment-events".to_string(),
+            ctx.spawn(collect_amendment_events(actx).into_actor(self)),
         );
 
         self.negotiator = factory::create_negotiator(ctx.address(), &self.config);
@@ -819,8 +956,10 @@ impl Handler<Unsubscribe> for ProviderMarket {
 
 forward_actix_handler!(ProviderMarket, Subscription, on_subscription);
 forward_actix_handler!(ProviderMarket, NewAgreement, on_agreement_approved);
+forward_actix_handler!(ProviderMarket, AgreementAmended, on_agreement_amended);
 actix_signal_handler!(ProviderMarket, CloseAgreement, agreement_terminated_signal);
 actix_signal_handler!(ProviderMarket, NewAgreement, agreement_signed_signal);
+actix_signal_handler!(ProviderMarket, AgreementAmended, agreement_amended_signal);
 
 fn get_backoff() -> backoff::ExponentialBackoff {
     // TODO: We could have config for Market actor to be able to set at least initial interval.
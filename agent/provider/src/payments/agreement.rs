@@ -99,6 +99,16 @@ impl AgreementPayment {
         })
     }
 
+    /// Refreshes payment terms from Agreement changed by Amendment.
+    /// Activities and their costs are kept.
+    pub fn update(&mut self, agreement: &AgreementView) -> Result<()> {
+        let payment_description = PaymentDescription::new(agreement)?;
+        self.update_interval = payment_description.get_update_interval()?;
+        self.payment_deadline = payment_description.get_debit_note_deadline()?;
+        self.payment_model = PaymentModelFactory::create(&payment_description)?;
+        Ok(())
+    }
+
     pub fn add_created_activity(&mut self, activity_id: &str) {
         let activity = ActivityPayment::Running {
             activity_id: activity_id.to_string(),
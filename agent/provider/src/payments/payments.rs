@@ -14,7 +14,7 @@ use super::agreement::{compute_cost, ActivityPayment, AgreementPayment, CostInfo
 use super::model::PaymentModel;
 use super::payment_checker::{DeadlineChecker, DeadlineElapsed, StopTracking, TrackDeadline};
 use crate::execution::{ActivityCreated, ActivityDestroyed};
-use crate::market::provider_market::NewAgreement;
+use crate::market::provider_market::{AgreementAmended, NewAgreement};
 use crate::market::termination_reason::BreakReason;
 use crate::tasks::{AgreementBroken, AgreementClosed, BreakAgreement};
 
@@ -190,6 +190,24 @@ impl Payments {
             }
         }
     }
+
+    pub fn on_agreement_amended(
+        &mut self,
+        msg: AgreementAmended,
+        _ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        let agreement_id = &msg.agreement.agreement_id;
+        match self.agreements.get_mut(agreement_id) {
+            Some(agreement) => agreement.update(&msg.agreement).map_err(|error| {
+                anyhow!(
+                    "Failed to update payment model for amended agreement [{}]. Error: {}",
+                    agreement_id,
+                    error
+                )
+            }),
+            None => Err(anyhow!("Amended agreement [{}] not found.", agreement_id)),
+        }
+    }
 }
 
 async fn send_debit_note(
@@ -384,6 +402,7 @@ async fn compute_cost_and_send_debit_note(
 }
 
 forward_actix_handler!(Payments, NewAgreement, on_signed_agreement);
+forward_actix_handler!(Payments, AgreementAmended, on_agreement_amended);
 
 impl Handler<ActivityCreated> for Payments {
     type Result = anyhow::Result<()>;
@@ -1,3 +1,4 @@
+mod expiration;
 mod task_info;
 pub mod task_manager;
 mod task_state;
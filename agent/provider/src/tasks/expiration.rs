@@ -0,0 +1,140 @@
+use actix::prelude::*;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Keeps single scheduled expiration callback per Agreement, so it can be
+/// moved, when Agreement expiration is changed by Amendment.
+#[derive(Default)]
+pub struct ExpirationSchedule {
+    handles: HashMap<String, SpawnHandle>,
+}
+
+impl ExpirationSchedule {
+    /// Schedules `on_expired` at `expiration`, replacing callback
+    /// scheduled previously for this Agreement.
+    pub fn schedule<A, F>(
+        &mut self,
+        agreement_id: &str,
+        expiration: DateTime<Utc>,
+        ctx: &mut Context<A>,
+        on_expired: F,
+    ) -> Result<()>
+    where
+        A: Actor<Context = Context<A>>,
+        F: FnOnce(&mut A, &mut Context<A>) + 'static,
+    {
+        if Utc::now() > expiration {
+            bail!("Agreement expired. Expiration {:#?}", expiration);
+        }
+
+        self.cancel(agreement_id, ctx);
+
+        let duration = (expiration - Utc::now()).to_std()?;
+        let handle = ctx.run_later(duration, on_expired);
+        self.handles.insert(agreement_id.to_string(), handle);
+        Ok(())
+    }
+
+    pub fn cancel<A>(&mut self, agreement_id: &str, ctx: &mut Context<A>)
+    where
+        A: Actor<Context = Context<A>>,
+    {
+        if let Some(handle) = self.handles.remove(agreement_id) {
+            ctx.cancel_future(handle);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::Duration;
+
+    struct Expirations {
+        schedule: ExpirationSchedule,
+        expired: Vec<String>,
+    }
+
+    impl Actor for Expirations {
+        type Context = Context<Self>;
+    }
+
+    #[derive(Message)]
+    #[rtype(result = "Result<()>")]
+    struct Schedule(String, DateTime<Utc>);
+
+    #[derive(Message)]
+    #[rtype(result = "Vec<String>")]
+    struct Collect;
+
+    impl Handler<Schedule> for Expirations {
+        type Result = Result<()>;
+
+        fn handle(&mut self, msg: Schedule, ctx: &mut Context<Self>) -> Self::Result {
+            let agreement_id = msg.0.clone();
+            self.schedule
+                .schedule(&msg.0, msg.1, ctx, move |myself, _| {
+                    myself.expired.push(agreement_id)
+                })
+        }
+    }
+
+    impl Handler<Collect> for Expirations {
+        type Result = MessageResult<Collect>;
+
+        fn handle(&mut self, _msg: Collect, _ctx: &mut Context<Self>) -> Self::Result {
+            MessageResult(self.expired.drain(..).collect())
+        }
+    }
+
+    async fn sleep(millis: u64) {
+        tokio::time::delay_for(std::time::Duration::from_millis(millis)).await;
+    }
+
+    #[cfg_attr(not(feature = "time-dependent-tests"), ignore)]
+    #[actix_rt::test]
+    async fn test_extended_expiration_replaces_previous() {
+        let expirations = Expirations {
+            schedule: ExpirationSchedule::default(),
+            expired: vec![],
+        }
+        .start();
+
+        let now = Utc::now();
+        let schedule = |id: &str, millis: i64| {
+            expirations.send(Schedule(
+                id.to_string(),
+                now + Duration::milliseconds(millis),
+            ))
+        };
+        schedule("agreement-1", 300).await.unwrap().unwrap();
+        schedule("agreement-2", 300).await.unwrap().unwrap();
+        // Amendment extends expiration of first Agreement.
+        schedule("agreement-1", 1000).await.unwrap().unwrap();
+
+        sleep(600).await;
+        let expired = expirations.send(Collect).await.unwrap();
+        assert_eq!(expired, vec!["agreement-2".to_string()]);
+
+        sleep(700).await;
+        let expired = expirations.send(Collect).await.unwrap();
+        assert_eq!(expired, vec!["agreement-1".to_string()]);
+    }
+
+    #[actix_rt::test]
+    async fn test_past_expiration_rejected() {
+        let expirations = Expirations {
+            schedule: ExpirationSchedule::default(),
+            expired: vec![],
+        }
+        .start();
+
+        let expired = Utc::now() - Duration::seconds(1);
+        let result = expirations
+            .send(Schedule("agreement-1".to_string(), expired))
+            .await
+            .unwrap();
+        assert!(result.is_err());
+    }
+}
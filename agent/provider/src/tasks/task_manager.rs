@@ -1,6 +1,6 @@
 use actix::prelude::*;
 use anyhow::{anyhow, bail, Error, Result};
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use futures::future::TryFutureExt;
 use std::collections::HashMap;
 
@@ -8,10 +8,11 @@ use ya_utils_actix::actix_handler::ResultTypeGetter;
 use ya_utils_actix::actix_signal::Subscribe;
 use ya_utils_actix::forward_actix_handler;
 
+use super::expiration::ExpirationSchedule;
 use super::task_info::TaskInfo;
 use super::task_state::{AgreementState, TasksStates};
 use crate::execution::{ActivityCreated, ActivityDestroyed, TaskRunner};
-use crate::market::provider_market::{NewAgreement, ProviderMarket};
+use crate::market::provider_market::{AgreementAmended, NewAgreement, ProviderMarket};
 use crate::market::termination_reason::BreakReason;
 use crate::payments::Payments;
 
@@ -21,6 +22,7 @@ use crate::payments::Payments;
 
 /// These events can be sent to TaskManager:
 /// - AgreementApproved
+/// - AgreementAmended
 /// - ActivityCreated
 /// - ActivityDestroyed
 /// - BreakAgreement
@@ -121,6 +123,7 @@ pub struct TaskManager {
 
     tasks: TasksStates,
     tasks_props: HashMap<String, TaskInfo>,
+    expirations: ExpirationSchedule,
 }
 
 impl TaskManager {
@@ -135,6 +138,7 @@ impl TaskManager {
             payments,
             tasks: TasksStates::new(),
             tasks_props: HashMap::new(),
+            expirations: ExpirationSchedule::default(),
         })
     }
 
@@ -154,16 +158,7 @@ impl TaskManager {
         }
 
         // Schedule agreement termination after expiration time.
-        let duration = (expiration - Utc::now()).to_std()?;
-        let agr_id = agreement_id.clone();
-        ctx.run_later(duration, move |myself, ctx| {
-            if !myself.tasks.is_agreement_finalized(&agr_id) {
-                ctx.address().do_send(BreakAgreement {
-                    agreement_id: agr_id,
-                    reason: BreakReason::Expired(expiration),
-                });
-            }
-        });
+        self.schedule_agreement_expiration(&agreement_id, expiration, ctx)?;
 
         // Schedule agreement termination when there is no activity created within 90s.
         let s90 = Duration::seconds(90).to_std()?;
@@ -178,6 +173,71 @@ impl TaskManager {
         Ok(())
     }
 
+    /// Replaces expiration scheduled previously, when Agreement was amended.
+    fn schedule_agreement_expiration(
+        &mut self,
+        agreement_id: &str,
+        expiration: DateTime<Utc>,
+        ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        let agr_id = agreement_id.to_string();
+        self.expirations
+            .schedule(agreement_id, expiration, ctx, move |myself, ctx| {
+                if !myself.tasks.is_agreement_finalized(&agr_id) {
+                    ctx.address().do_send(BreakAgreement {
+                        agreement_id: agr_id,
+                        reason: BreakReason::Expired(expiration),
+                    });
+                }
+            })
+    }
+
+    fn on_agreement_amended(
+        &mut self,
+        msg: AgreementAmended,
+        ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        let agreement_id = msg.agreement.agreement_id.clone();
+        if self.tasks.is_agreement_finalized(&agreement_id) {
+            return Ok(());
+        }
+
+        let previous = self
+            .tasks_props
+            .get(&agreement_id)
+            .map(|props| props.expiration)
+            .ok_or_else(|| anyhow!("Amended agreement [{}] not found.", agreement_id))?;
+        let props = TaskInfo::from(&msg.agreement)
+            .map_err(|e| anyhow!("Failed to create TaskInfo from amended Agreement. {}", e))?;
+
+        if props.expiration != previous {
+            log::info!(
+                "Agreement [{}] expiration changed by Amendment from {} to {}.",
+                agreement_id,
+                previous,
+                props.expiration
+            );
+            if let Err(error) =
+                self.schedule_agreement_expiration(&agreement_id, props.expiration, ctx)
+            {
+                log::warn!(
+                    "Can't reschedule agreement [{}] expiration. {}",
+                    agreement_id,
+                    error
+                );
+                ctx.address().do_send(BreakAgreement {
+                    agreement_id: agreement_id.clone(),
+                    reason: BreakReason::Expired(props.expiration),
+                });
+            }
+        }
+        self.tasks_props.insert(agreement_id, props);
+
+        // Payments keep their own view of Agreement.
+        self.payments.do_send(msg);
+        Ok(())
+    }
+
     fn start_update_agreement_state(
         &mut self,
         msg: StartUpdateState,
@@ -227,6 +287,7 @@ impl Actor for TaskManager {
 }
 
 forward_actix_handler!(TaskManager, ScheduleExpiration, schedule_expiration);
+forward_actix_handler!(TaskManager, AgreementAmended, on_agreement_amended);
 forward_actix_handler!(TaskManager, StartUpdateState, start_update_agreement_state);
 forward_actix_handler!(
     TaskManager,
@@ -245,6 +306,10 @@ impl Handler<InitializeTaskManager> for TaskManager {
             let msg = Subscribe::<NewAgreement>(actx.myself.clone().recipient());
             actx.market.send(msg).await?;
 
+            // Listen to Agreement changed by approved Amendment.
+            let msg = Subscribe::<AgreementAmended>(actx.myself.clone().recipient());
+            actx.market.send(msg).await?;
+
             // Listen to Agreement terminated event from market.
             let msg = Subscribe::<CloseAgreement>(actx.myself.clone().recipient());
             actx.market.send(msg).await?;
@@ -0,0 +1,19 @@
+DROP INDEX idx_activity_owner_id;
+
+CREATE TABLE activity_migrate (
+	id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	natural_id VARCHAR(255) NOT NULL,
+	agreement_id VARCHAR(255) NOT NULL,
+	state_id INTEGER NOT NULL,
+	usage_id INTEGER NOT NULL,
+    FOREIGN KEY(state_id) REFERENCES activity_state (id),
+    FOREIGN KEY(usage_id) REFERENCES activity_usage (id),
+    UNIQUE(natural_id)
+);
+
+INSERT INTO activity_migrate(id, natural_id, agreement_id, state_id, usage_id)
+SELECT id, natural_id, agreement_id, state_id, usage_id
+FROM activity;
+
+DROP TABLE activity;
+ALTER TABLE activity_migrate RENAME TO activity;
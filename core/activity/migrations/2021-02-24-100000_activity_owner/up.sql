@@ -0,0 +1,25 @@
+ALTER TABLE activity ADD COLUMN owner_id VARCHAR(50);
+ALTER TABLE activity ADD COLUMN role VARCHAR(1);
+ALTER TABLE activity ADD COLUMN created_date DATETIME;
+
+-- Provider's activities can be recovered from their CreateActivity events.
+-- Owners of requestor's activities, created before this migration, are recovered
+-- from their agreements, which are kept in market's database, when service starts.
+UPDATE activity
+SET owner_id = (
+        SELECT e.identity_id FROM activity_event e
+        WHERE e.activity_id = activity.id AND e.event_type_id = 1
+        ORDER BY e.id LIMIT 1
+    ),
+    role = 'P',
+    created_date = (
+        SELECT e.event_date FROM activity_event e
+        WHERE e.activity_id = activity.id AND e.event_type_id = 1
+        ORDER BY e.id LIMIT 1
+    )
+WHERE EXISTS (
+    SELECT 1 FROM activity_event e
+    WHERE e.activity_id = activity.id AND e.event_type_id = 1
+);
+
+CREATE INDEX idx_activity_owner_id ON activity(owner_id);
@@ -0,0 +1,10 @@
+CREATE TABLE activity_usage_sample (
+	id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	activity_id INTEGER NOT NULL,
+	vector_json TEXT NOT NULL,
+	timestamp DATETIME NOT NULL,
+    FOREIGN KEY(activity_id) REFERENCES activity (id)
+);
+
+CREATE INDEX idx_activity_usage_sample_activity_id
+ON activity_usage_sample(activity_id, timestamp);
@@ -22,23 +22,40 @@ mod common {
 
     use crate::common::{
         agreement_provider_service, authorize_activity_executor, authorize_activity_initiator,
-        get_activity_agreement, get_persisted_state, get_persisted_usage, set_persisted_state,
-        set_persisted_usage, PathActivity, QueryTimeout,
+        get_activity_agreement, get_persisted_state, get_persisted_usage,
+        get_persisted_usage_history, list_activities, set_persisted_state, set_persisted_usage,
+        PathActivity, QueryActivities, QueryTimeout, QueryUsageHistory,
     };
 
     pub fn extend_web_scope(scope: actix_web::Scope) -> actix_web::Scope {
         scope
-            // .service(get_activities_web)
+            .service(get_activities_web)
             .service(get_activity_state_web)
             .service(get_activity_usage_web)
+            .service(get_activity_usage_history_web)
+    }
+
+    /// Lists activities of the caller, both as a Provider and a Requestor.
+    #[actix_web::get("/activity")]
+    async fn get_activities_web(
+        db: web::Data<DbExecutor>,
+        query: web::Query<QueryActivities>,
+        id: Identity,
+    ) -> impl Responder {
+        log::debug!("get_activities_web");
+        let query = query.into_inner();
+        let msg = activity::local::ListActivities {
+            identity: id.identity,
+            agreement_id: query.agreement_id,
+            state: query.state,
+            created_after: query.created_after,
+            role: query.role,
+            offset: query.offset,
+            limit: query.limit,
+        };
+        list_activities(&db, msg).await.map(web::Json)
     }
 
-    // TODO this endpoint needs authorization via Identity, otherwise is vulnerable for attacks.
-    // #[actix_web::get("/activity")]
-    // async fn get_activities_web(db: web::Data<DbExecutor>) -> impl Responder {
-    //     log::debug!("get_activities_web");
-    //     get_activities(&db).await.map(web::Json)
-    // }
     #[actix_web::get("/activity/{activity_id}/state")]
     async fn get_activity_state_web(
         db: web::Data<DbExecutor>,
@@ -131,4 +148,39 @@ mod common {
             .await
             .map(web::Json)
     }
+
+    /// Usage samples are recorded by the Provider, so Requestors get them from there.
+    #[actix_web::get("/activity/{activity_id}/usage/history")]
+    async fn get_activity_usage_history_web(
+        db: web::Data<DbExecutor>,
+        path: web::Path<PathActivity>,
+        query: web::Query<QueryUsageHistory>,
+        id: Identity,
+    ) -> impl Responder {
+        // check if caller is the Provider
+        if authorize_activity_executor(&db, id.identity, &path.activity_id, Role::Provider)
+            .await
+            .is_ok()
+        {
+            return get_persisted_usage_history(&db, &path.activity_id, query.after_timestamp)
+                .await
+                .map(web::Json);
+        }
+
+        // check if caller is the Requestor
+        authorize_activity_initiator(&db, id.identity, &path.activity_id, Role::Requestor).await?;
+
+        let agreement = get_activity_agreement(&db, &path.activity_id, Role::Requestor).await?;
+        let provider_service = agreement_provider_service(&id, &agreement)?;
+        let history = provider_service
+            .send(activity::GetUsageHistory {
+                activity_id: path.activity_id.to_string(),
+                since: query.after_timestamp,
+                timeout: query.timeout.clone(),
+            })
+            .timeout(query.timeout)
+            .await???;
+
+        Ok(web::Json(history))
+    }
 }
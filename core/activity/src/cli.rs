@@ -1,8 +1,12 @@
+use chrono::{DateTime, Utc};
 use structopt::StructOpt;
+use ya_client_model::activity::State;
+use ya_client_model::NodeId;
 use ya_core_model::activity::local as acm;
 use ya_core_model::identity as idm;
 use ya_core_model::identity::IdentityInfo;
-use ya_service_api::{CliCtx, CommandOutput};
+use ya_core_model::Role;
+use ya_service_api::{CliCtx, CommandOutput, ResponseTable};
 use ya_service_bus::{typed as bus, RpcEndpoint};
 
 /// Activity management.
@@ -12,6 +16,37 @@ pub enum ActivityCli {
         #[structopt(long)]
         id: Option<String>,
     },
+    /// List activities, newest first
+    List {
+        #[structopt(long)]
+        id: Option<String>,
+        #[structopt(long)]
+        agreement_id: Option<String>,
+        /// e.g. Ready, Terminated
+        #[structopt(long, parse(try_from_str = parse_state))]
+        state: Option<State>,
+        /// RFC 3339 timestamp, e.g. 2021-01-01T00:00:00Z
+        #[structopt(long)]
+        created_after: Option<DateTime<Utc>>,
+        #[structopt(long, possible_values = &["provider", "requestor"], parse(try_from_str = parse_role))]
+        role: Option<Role>,
+        #[structopt(long)]
+        offset: Option<u32>,
+        #[structopt(long)]
+        limit: Option<u32>,
+    },
+}
+
+fn parse_state(s: &str) -> anyhow::Result<State> {
+    Ok(serde_json::from_value(serde_json::Value::String(s.into()))?)
+}
+
+fn parse_role(s: &str) -> anyhow::Result<Role> {
+    match s {
+        "provider" => Ok(Role::Provider),
+        "requestor" => Ok(Role::Requestor),
+        _ => anyhow::bail!("Invalid role: {}", s),
+    }
 }
 
 impl ActivityCli {
@@ -24,27 +59,79 @@ impl ActivityCli {
             .ok_or(anyhow::Error::msg("Identity not found"))
     }
 
-    pub async fn run_command(self, _ctx: &CliCtx) -> anyhow::Result<CommandOutput> {
+    async fn resolve_identity(id: Option<String>) -> anyhow::Result<NodeId> {
+        Ok(match id {
+            Some(id) => {
+                if id.starts_with("0x") {
+                    id.parse()?
+                } else {
+                    Self::get_identity(idm::Get::ByAlias(id.into()))
+                        .await?
+                        .node_id
+                }
+            }
+            None => Self::get_identity(idm::Get::ByDefault).await?.node_id,
+        })
+    }
+
+    pub async fn run_command(self, ctx: &CliCtx) -> anyhow::Result<CommandOutput> {
         match self {
             ActivityCli::Status { id } => {
-                let identity = match id {
-                    Some(id) => {
-                        if id.starts_with("0x") {
-                            id.parse()?
-                        } else {
-                            Self::get_identity(idm::Get::ByAlias(id.into()))
-                                .await?
-                                .node_id
-                        }
-                    }
-                    None => Self::get_identity(idm::Get::ByDefault).await?.node_id,
-                };
+                let identity = Self::resolve_identity(id).await?;
                 let result = bus::service(acm::BUS_ID)
                     .send(acm::Stats { identity })
                     .await??;
 
                 CommandOutput::object(result)
             }
+            ActivityCli::List {
+                id,
+                agreement_id,
+                state,
+                created_after,
+                role,
+                offset,
+                limit,
+            } => {
+                let identity = Self::resolve_identity(id).await?;
+                let activities = bus::service(acm::BUS_ID)
+                    .send(acm::ListActivities {
+                        identity,
+                        agreement_id,
+                        state,
+                        created_after,
+                        role,
+                        offset,
+                        limit,
+                    })
+                    .await??;
+                if ctx.json_output {
+                    return CommandOutput::object(activities);
+                }
+
+                Ok(ResponseTable {
+                    columns: vec![
+                        "activity".to_owned(),
+                        "agreement".to_owned(),
+                        "role".to_owned(),
+                        "state".to_owned(),
+                        "created".to_owned(),
+                    ],
+                    values: activities
+                        .into_iter()
+                        .map(|activity| {
+                            serde_json::json! {[
+                                activity.activity_id,
+                                activity.agreement_id,
+                                activity.role.to_string(),
+                                format!("{:?}", activity.state.state.0),
+                                activity.created_date.map(|d| d.to_rfc3339()).unwrap_or_default(),
+                            ]}
+                        })
+                        .collect(),
+                }
+                .into())
+            }
         }
     }
 }
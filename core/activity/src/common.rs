@@ -5,7 +5,7 @@ use serde::Deserialize;
 use uuid::Uuid;
 
 use ya_client_model::{
-    activity::{ActivityState, ActivityUsage},
+    activity::{ActivityState, ActivityUsage, State},
     market::Agreement,
     NodeId,
 };
@@ -38,6 +38,29 @@ pub struct QueryTimeoutCommandIndex {
     pub command_index: Option<usize>,
 }
 
+#[derive(Deserialize)]
+pub struct QueryUsageHistory {
+    #[serde(rename = "timeout", default = "default_query_timeout")]
+    pub timeout: Option<f32>,
+    /// select usage samples past the specified point in time
+    #[serde(rename = "afterTimestamp")]
+    pub after_timestamp: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct QueryActivities {
+    #[serde(rename = "agreementId")]
+    pub agreement_id: Option<String>,
+    pub state: Option<State>,
+    /// select activities created past the specified point in time
+    #[serde(rename = "createdAfter")]
+    pub created_after: Option<DateTime<Utc>>,
+    pub role: Option<Role>,
+    pub offset: Option<u32>,
+    /// maximum count of activities to return
+    pub limit: Option<u32>,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct QueryEvents {
     /// application session identifier
@@ -65,8 +88,39 @@ pub(crate) fn generate_id() -> String {
     Uuid::new_v4().to_simple().to_string()
 }
 
-pub(crate) async fn _get_activities(db: &DbExecutor) -> Result<Vec<String>, Error> {
-    Ok(db.as_dao::<ActivityDao>()._get_activity_ids().await?)
+pub(crate) async fn list_activities(
+    db: &DbExecutor,
+    msg: activity::local::ListActivities,
+) -> Result<Vec<activity::local::ActivityInfo>, Error> {
+    Ok(db.as_dao::<ActivityDao>().list(msg).await?)
+}
+
+/// Owners of requestor's activities, created by older versions, weren't stored, so
+/// they aren't listed. They are recovered from agreements, which are kept by the market.
+pub(crate) async fn recover_activity_owners(db: DbExecutor) {
+    let dao = db.as_dao::<ActivityDao>();
+    let activities = match dao.list_without_owner().await {
+        Ok(activities) => activities,
+        Err(e) => {
+            log::warn!("Failed to list activities without owner: {}", e);
+            return;
+        }
+    };
+    for (activity_id, agreement_id) in activities {
+        let agreement = match get_agreement(&agreement_id, Role::Requestor).await {
+            Ok(agreement) => agreement,
+            Err(e) => {
+                log::debug!("Owner of activity [{}] is unknown: {}", activity_id, e);
+                continue;
+            }
+        };
+        if let Err(e) = dao
+            .set_owner(&activity_id, agreement.requestor_id(), Role::Requestor)
+            .await
+        {
+            log::warn!("Failed to set owner of activity [{}]: {}", activity_id, e);
+        }
+    }
 }
 
 pub(crate) async fn get_persisted_state(
@@ -103,6 +157,17 @@ pub(crate) async fn get_persisted_usage(
     Ok(db.as_dao::<ActivityUsageDao>().get(&activity_id).await?)
 }
 
+pub(crate) async fn get_persisted_usage_history(
+    db: &DbExecutor,
+    activity_id: &str,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<ActivityUsage>, Error> {
+    Ok(db
+        .as_dao::<ActivityUsageDao>()
+        .get_history(&activity_id, since)
+        .await?)
+}
+
 pub(crate) async fn set_persisted_usage(
     db: &DbExecutor,
     activity_id: &str,
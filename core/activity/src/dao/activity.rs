@@ -1,14 +1,35 @@
-use chrono::Utc;
+use chrono::{NaiveDateTime, TimeZone, Utc};
 use diesel::prelude::*;
 use serde_json;
+use std::convert::TryInto;
 
 use ya_client_model::activity::{State, StatePair};
-use ya_persistence::executor::{do_with_transaction, AsDao, PoolType};
+use ya_client_model::NodeId;
+use ya_core_model::activity::local::{ActivityInfo, ListActivities};
+use ya_core_model::Role;
+use ya_persistence::executor::{do_with_transaction, readonly_transaction, AsDao, PoolType};
+use ya_persistence::types::Role as DbRole;
 
 use crate::dao::{last_insert_rowid, DaoError, Result};
-use crate::db::schema;
+use crate::db::{models::ActivityState as DbActivityState, schema};
 use diesel::dsl::exists;
 
+pub const MAX_ACTIVITIES: u32 = 100;
+
+fn to_db_role(role: Role) -> DbRole {
+    match role {
+        Role::Provider => DbRole::Provider,
+        Role::Requestor => DbRole::Requestor,
+    }
+}
+
+fn from_db_role(role: DbRole) -> Role {
+    match role {
+        DbRole::Provider => Role::Provider,
+        DbRole::Requestor => Role::Requestor,
+    }
+}
+
 pub struct ActivityDao<'c> {
     pool: &'c PoolType,
 }
@@ -40,7 +61,13 @@ impl<'c> ActivityDao<'c> {
         .await
     }
 
-    pub async fn create(&self, activity_id: &str, agreement_id: &str) -> Result<()> {
+    pub async fn create(
+        &self,
+        activity_id: &str,
+        agreement_id: &str,
+        owner_id: &NodeId,
+        role: Role,
+    ) -> Result<()> {
         use schema::activity::dsl;
         use schema::activity_state::dsl as dsl_state;
         use schema::activity_usage::dsl as dsl_usage;
@@ -53,6 +80,8 @@ impl<'c> ActivityDao<'c> {
 
         let activity_id = activity_id.to_owned();
         let agreement_id = agreement_id.to_owned();
+        let owner_id = owner_id.to_string();
+        let role = to_db_role(role);
 
         do_with_transaction(self.pool, move |conn| {
             diesel::insert_into(dsl_state::activity_state)
@@ -81,6 +110,9 @@ impl<'c> ActivityDao<'c> {
                     dsl::agreement_id.eq(agreement_id),
                     dsl::state_id.eq(state_id),
                     dsl::usage_id.eq(usage_id),
+                    dsl::owner_id.eq(owner_id),
+                    dsl::role.eq(role),
+                    dsl::created_date.eq(now),
                 ))
                 .execute(conn)?;
 
@@ -89,8 +121,17 @@ impl<'c> ActivityDao<'c> {
         .await
     }
 
-    pub async fn create_if_not_exists(&self, activity_id: &str, agreement_id: &str) -> Result<()> {
-        if let Err(e) = self.create(&activity_id, &agreement_id).await {
+    pub async fn create_if_not_exists(
+        &self,
+        activity_id: &str,
+        agreement_id: &str,
+        owner_id: &NodeId,
+        role: Role,
+    ) -> Result<()> {
+        if let Err(e) = self
+            .create(&activity_id, &agreement_id, owner_id, role)
+            .await
+        {
             if !self.exists(activity_id, agreement_id).await? {
                 return Err(e);
             }
@@ -115,14 +156,287 @@ impl<'c> ActivityDao<'c> {
         .await
     }
 
-    pub async fn _get_activity_ids(&self) -> Result<Vec<String>> {
+    /// Activities created before their owners were stored, with their agreement ids.
+    pub async fn list_without_owner(&self) -> Result<Vec<(String, String)>> {
         use schema::activity::dsl;
-        do_with_transaction(self.pool, |conn| {
-            dsl::activity
-                .select(dsl::natural_id)
-                .get_results(conn)
-                .map_err(|e| e.into())
+
+        readonly_transaction(self.pool, move |conn| {
+            Ok(dsl::activity
+                .select((dsl::natural_id, dsl::agreement_id))
+                .filter(dsl::owner_id.is_null())
+                .order(dsl::id.asc())
+                .load(conn)?)
+        })
+        .await
+    }
+
+    /// Sets recovered owner of the activity, unless it's already known.
+    pub async fn set_owner(&self, activity_id: &str, owner_id: &NodeId, role: Role) -> Result<()> {
+        use schema::activity::dsl;
+
+        let activity_id = activity_id.to_owned();
+        let owner_id = owner_id.to_string();
+        let role = to_db_role(role);
+
+        do_with_transaction(self.pool, move |conn| {
+            diesel::update(
+                dsl::activity
+                    .filter(dsl::natural_id.eq(activity_id))
+                    .filter(dsl::owner_id.is_null()),
+            )
+            .set((dsl::owner_id.eq(owner_id), dsl::role.eq(role)))
+            .execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn list(&self, msg: ListActivities) -> Result<Vec<ActivityInfo>> {
+        use schema::activity::dsl;
+        use schema::activity_state::dsl as dsl_state;
+
+        let state_prefix = match &msg.state {
+            // States are persisted as JSON encoded `StatePair`
+            Some(state) => Some(format!("[{},%", serde_json::to_string(state)?)),
+            None => None,
+        };
+        let limit = msg.limit.unwrap_or(MAX_ACTIVITIES).min(MAX_ACTIVITIES);
+
+        readonly_transaction(self.pool, move |conn| {
+            let mut query = dsl::activity
+                .inner_join(schema::activity_state::table)
+                .select((
+                    dsl::natural_id,
+                    dsl::agreement_id,
+                    dsl::role,
+                    dsl::created_date,
+                    schema::activity_state::all_columns,
+                ))
+                .filter(dsl::owner_id.eq(msg.identity.to_string()))
+                .into_boxed();
+            if let Some(agreement_id) = msg.agreement_id {
+                query = query.filter(dsl::agreement_id.eq(agreement_id));
+            }
+            if let Some(state_prefix) = state_prefix {
+                query = query.filter(dsl_state::name.like(state_prefix));
+            }
+            if let Some(created_after) = msg.created_after {
+                query = query.filter(dsl::created_date.gt(created_after.naive_utc()));
+            }
+            if let Some(role) = msg.role {
+                query = query.filter(dsl::role.eq(to_db_role(role)));
+            }
+
+            let rows: Vec<(
+                String,
+                String,
+                Option<DbRole>,
+                Option<NaiveDateTime>,
+                DbActivityState,
+            )> = query
+                .order(dsl::id.desc())
+                .offset(msg.offset.unwrap_or(0) as i64)
+                .limit(limit as i64)
+                .load(conn)?;
+
+            rows.into_iter()
+                .map(|(activity_id, agreement_id, role, created_date, state)| {
+                    Ok(ActivityInfo {
+                        activity_id,
+                        agreement_id,
+                        // Owned activities always have the role set
+                        role: role.map(from_db_role).unwrap_or(Role::Provider),
+                        state: state.try_into()?,
+                        created_date: created_date.map(|d| Utc.from_utc_datetime(&d)),
+                    })
+                })
+                .collect()
         })
         .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use tempdir::TempDir;
+    use ya_client_model::activity::ActivityState;
+    use ya_persistence::executor::DbExecutor;
+
+    use crate::dao::ActivityStateDao;
+
+    fn owner() -> NodeId {
+        "0xbabe000000000000000000000000000000000000"
+            .parse()
+            .unwrap()
+    }
+
+    fn other() -> NodeId {
+        "0xcafe000000000000000000000000000000000000"
+            .parse()
+            .unwrap()
+    }
+
+    /// Activities `a1`..`a4` of `owner`, newer ones first, and `b1` of `other`.
+    async fn init_db(dir: &TempDir) -> DbExecutor {
+        let db = DbExecutor::from_data_dir(dir.path(), "activity").unwrap();
+        db.apply_migration(crate::db::migrations::run_with_output)
+            .unwrap();
+        let dao = db.as_dao::<ActivityDao>();
+        let activities = vec![
+            ("a1", "agreement-1", owner(), Role::Provider),
+            ("a2", "agreement-1", owner(), Role::Provider),
+            ("b1", "agreement-2", other(), Role::Provider),
+            ("a3", "agreement-2", owner(), Role::Requestor),
+            ("a4", "agreement-3", owner(), Role::Requestor),
+        ];
+        for (activity_id, agreement_id, owner_id, role) in activities {
+            dao.create(activity_id, agreement_id, &owner_id, role)
+                .await
+                .unwrap();
+        }
+        db
+    }
+
+    fn list(identity: NodeId) -> ListActivities {
+        ListActivities {
+            identity,
+            agreement_id: None,
+            state: None,
+            created_after: None,
+            role: None,
+            offset: None,
+            limit: None,
+        }
+    }
+
+    async fn ids(db: &DbExecutor, msg: ListActivities) -> Vec<String> {
+        db.as_dao::<ActivityDao>()
+            .list(msg)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|info| info.activity_id)
+            .collect()
+    }
+
+    #[actix_rt::test]
+    async fn test_list_filters() {
+        let dir = TempDir::new("activity-dao").unwrap();
+        let db = init_db(&dir).await;
+
+        assert_eq!(ids(&db, list(owner())).await, vec!["a4", "a3", "a2", "a1"]);
+        assert_eq!(ids(&db, list(other())).await, vec!["b1"]);
+
+        let msg = ListActivities {
+            agreement_id: Some("agreement-1".to_string()),
+            ..list(owner())
+        };
+        assert_eq!(ids(&db, msg).await, vec!["a2", "a1"]);
+
+        let msg = ListActivities {
+            role: Some(Role::Requestor),
+            ..list(owner())
+        };
+        let activities = db.as_dao::<ActivityDao>().list(msg).await.unwrap();
+        assert_eq!(activities.len(), 2);
+        assert!(activities.iter().all(|a| a.role == Role::Requestor));
+
+        let ready = ActivityState {
+            state: StatePair(State::Ready, None),
+            reason: None,
+            error_message: None,
+        };
+        db.as_dao::<ActivityStateDao>()
+            .set("a2", ready)
+            .await
+            .unwrap();
+        let msg = ListActivities {
+            state: Some(State::Ready),
+            ..list(owner())
+        };
+        assert_eq!(ids(&db, msg).await, vec!["a2"]);
+        let msg = ListActivities {
+            state: Some(State::New),
+            ..list(owner())
+        };
+        assert_eq!(ids(&db, msg).await, vec!["a4", "a3", "a1"]);
+    }
+
+    #[actix_rt::test]
+    async fn test_list_created_after() {
+        use schema::activity::dsl;
+
+        let dir = TempDir::new("activity-dao").unwrap();
+        let db = init_db(&dir).await;
+        let old = (Utc::now() - Duration::days(1)).naive_utc();
+        diesel::update(dsl::activity.filter(dsl::natural_id.eq_any(vec!["a1", "a2"])))
+            .set(dsl::created_date.eq(old))
+            .execute(&db.conn().unwrap())
+            .unwrap();
+
+        let msg = ListActivities {
+            created_after: Some(Utc::now() - Duration::hours(1)),
+            ..list(owner())
+        };
+        assert_eq!(ids(&db, msg).await, vec!["a4", "a3"]);
+    }
+
+    #[actix_rt::test]
+    async fn test_list_pages() {
+        let dir = TempDir::new("activity-dao").unwrap();
+        let db = init_db(&dir).await;
+
+        let page = |offset, limit| ListActivities {
+            offset: Some(offset),
+            limit: Some(limit),
+            ..list(owner())
+        };
+        assert_eq!(ids(&db, page(0, 3)).await, vec!["a4", "a3", "a2"]);
+        assert_eq!(ids(&db, page(3, 3)).await, vec!["a1"]);
+        assert_eq!(ids(&db, page(1, 2)).await, vec!["a3", "a2"]);
+        assert!(ids(&db, page(4, 3)).await.is_empty());
+        // Limit is capped
+        assert_eq!(ids(&db, page(0, MAX_ACTIVITIES + 1)).await.len(), 4);
+    }
+
+    #[actix_rt::test]
+    async fn test_recovered_owner_listed() {
+        use schema::activity::dsl;
+
+        let dir = TempDir::new("activity-dao").unwrap();
+        let db = init_db(&dir).await;
+        // As left by migration for requestor's activities of older versions.
+        diesel::update(dsl::activity.filter(dsl::natural_id.eq("a4")))
+            .set((
+                dsl::owner_id.eq(None::<String>),
+                dsl::role.eq(None::<DbRole>),
+                dsl::created_date.eq(None::<NaiveDateTime>),
+            ))
+            .execute(&db.conn().unwrap())
+            .unwrap();
+
+        let dao = db.as_dao::<ActivityDao>();
+        assert_eq!(ids(&db, list(owner())).await, vec!["a3", "a2", "a1"]);
+        assert_eq!(
+            dao.list_without_owner().await.unwrap(),
+            vec![("a4".to_string(), "agreement-3".to_string())]
+        );
+
+        dao.set_owner("a4", &owner(), Role::Requestor)
+            .await
+            .unwrap();
+        assert!(dao.list_without_owner().await.unwrap().is_empty());
+        let activities = dao.list(list(owner())).await.unwrap();
+        assert_eq!(activities[0].activity_id, "a4");
+        assert_eq!(activities[0].role, Role::Requestor);
+        assert_eq!(activities[0].created_date, None);
+
+        // Known owner isn't overwritten.
+        dao.set_owner("a3", &other(), Role::Requestor)
+            .await
+            .unwrap();
+        assert_eq!(ids(&db, list(other())).await, vec!["b1"]);
+    }
+}
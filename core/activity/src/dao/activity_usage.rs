@@ -1,15 +1,25 @@
-use chrono::Utc;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use diesel::dsl::{count_star, max, min};
 use diesel::expression::dsl::exists;
 use diesel::prelude::*;
 use serde_json;
 use std::convert::TryInto;
 
 use ya_client_model::activity::activity_usage::ActivityUsage;
-use ya_persistence::executor::{do_with_transaction, AsDao, PoolType};
+use ya_persistence::executor::{
+    do_with_transaction, readonly_transaction, AsDao, ConnType, PoolType,
+};
 
 use crate::dao::{DaoError, Result};
 use crate::db::{models::ActivityUsage as DbActivityUsage, schema};
 
+/// Minimal interval between stored usage samples.
+pub const USAGE_SAMPLE_INTERVAL_SECONDS: i64 = 10;
+/// Once reached, every other sample of the activity is dropped. Afterwards samples
+/// are stored at the average interval of the remaining ones, so the history of long
+/// activities stays evenly spaced.
+pub const MAX_USAGE_SAMPLES: i64 = 720;
+
 pub struct ActivityUsageDao<'c> {
     pool: &'c PoolType,
 }
@@ -49,11 +59,19 @@ impl<'c> ActivityUsageDao<'c> {
         use schema::activity_usage::dsl as dsl_usage;
 
         let vector = serde_json::to_string(&usage.current_usage)?;
+        let sample = match &usage.current_usage {
+            Some(current_usage) => Some(serde_json::to_string(current_usage)?),
+            None => None,
+        };
         let now = Utc::now().naive_utc();
 
         let activity_id = activity_id.to_owned();
 
         do_with_transaction(self.pool, move |conn| {
+            if let Some(sample) = sample {
+                add_sample(&activity_id, sample, now, conn)?;
+            }
+
             diesel::update(
                 dsl_usage::activity_usage.filter(exists(
                     dsl::activity
@@ -71,4 +89,226 @@ impl<'c> ActivityUsageDao<'c> {
         })
         .await
     }
+
+    /// Returns usage samples stored after `since`, oldest first.
+    pub async fn get_history(
+        &self,
+        activity_id: &str,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<ActivityUsage>> {
+        use schema::activity::dsl;
+        use schema::activity_usage_sample::dsl as dsl_sample;
+
+        let activity_id = activity_id.to_owned();
+
+        readonly_transaction(self.pool, move |conn| {
+            let mut query = dsl_sample::activity_usage_sample
+                .inner_join(dsl::activity)
+                .select((dsl_sample::vector_json, dsl_sample::timestamp))
+                .filter(dsl::natural_id.eq(&activity_id))
+                .into_boxed();
+            if let Some(since) = since {
+                query = query.filter(dsl_sample::timestamp.gt(since.naive_utc()));
+            }
+            let samples: Vec<(String, NaiveDateTime)> = query
+                .order((dsl_sample::timestamp.asc(), dsl_sample::id.asc()))
+                .load(conn)?;
+
+            samples
+                .into_iter()
+                .map(|(vector_json, timestamp)| {
+                    Ok(ActivityUsage {
+                        current_usage: Some(serde_json::from_str(&vector_json)?),
+                        timestamp: timestamp.timestamp(),
+                    })
+                })
+                .collect()
+        })
+        .await
+    }
+}
+
+fn add_sample(
+    activity_id: &str,
+    vector_json: String,
+    now: NaiveDateTime,
+    conn: &ConnType,
+) -> Result<()> {
+    use schema::activity::dsl;
+    use schema::activity_usage_sample::dsl as dsl_sample;
+
+    // Samples are returned with whole seconds, which clients pass back as `afterTimestamp`.
+    let now = NaiveDateTime::from_timestamp(now.timestamp(), 0);
+
+    let id: i32 = match dsl::activity
+        .select(dsl::id)
+        .filter(dsl::natural_id.eq(activity_id))
+        .first(conn)
+        .optional()?
+    {
+        Some(id) => id,
+        None => return Ok(()),
+    };
+
+    let (count, first, last): (i64, Option<NaiveDateTime>, Option<NaiveDateTime>) =
+        dsl_sample::activity_usage_sample
+            .select((
+                count_star(),
+                min(dsl_sample::timestamp),
+                max(dsl_sample::timestamp),
+            ))
+            .filter(dsl_sample::activity_id.eq(id))
+            .first(conn)?;
+
+    if let (Some(first), Some(last)) = (first, last) {
+        let average = match count {
+            1 => 0,
+            _ => (last - first).num_seconds() / (count - 1),
+        };
+        if (now - last).num_seconds() < USAGE_SAMPLE_INTERVAL_SECONDS.max(average) {
+            return Ok(());
+        }
+    }
+
+    diesel::insert_into(dsl_sample::activity_usage_sample)
+        .values((
+            dsl_sample::activity_id.eq(id),
+            dsl_sample::vector_json.eq(vector_json),
+            dsl_sample::timestamp.eq(now),
+        ))
+        .execute(conn)?;
+
+    if count + 1 >= MAX_USAGE_SAMPLES {
+        let ids: Vec<i32> = dsl_sample::activity_usage_sample
+            .select(dsl_sample::id)
+            .filter(dsl_sample::activity_id.eq(id))
+            .order((dsl_sample::timestamp.asc(), dsl_sample::id.asc()))
+            .load(conn)?;
+        // Keep the first and the latest sample
+        let dropped: Vec<i32> = ids[..ids.len() - 1]
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .cloned()
+            .collect();
+        diesel::delete(dsl_sample::activity_usage_sample.filter(dsl_sample::id.eq_any(dropped)))
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use tempdir::TempDir;
+    use ya_client_model::NodeId;
+    use ya_core_model::Role;
+    use ya_persistence::executor::DbExecutor;
+
+    use crate::dao::ActivityDao;
+
+    const ACTIVITY_ID: &str = "activity-1";
+
+    async fn init_db(dir: &TempDir) -> DbExecutor {
+        let db = DbExecutor::from_data_dir(dir.path(), "activity").unwrap();
+        db.apply_migration(crate::db::migrations::run_with_output)
+            .unwrap();
+        let owner_id: NodeId = "0xbabe000000000000000000000000000000000000"
+            .parse()
+            .unwrap();
+        db.as_dao::<ActivityDao>()
+            .create(ACTIVITY_ID, "agreement-1", &owner_id, Role::Provider)
+            .await
+            .unwrap();
+        db
+    }
+
+    fn start() -> NaiveDateTime {
+        NaiveDateTime::from_timestamp(1_600_000_000, 0)
+    }
+
+    fn add_samples(db: &DbExecutor, times: impl IntoIterator<Item = NaiveDateTime>) {
+        let conn = db.conn().unwrap();
+        conn.transaction::<_, DaoError, _>(|| {
+            for time in times {
+                add_sample(ACTIVITY_ID, "[1.0]".to_string(), time, &conn)?;
+            }
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    fn sample_times(db: &DbExecutor) -> Vec<NaiveDateTime> {
+        use schema::activity_usage_sample::dsl as dsl_sample;
+
+        dsl_sample::activity_usage_sample
+            .select(dsl_sample::timestamp)
+            .order(dsl_sample::timestamp.asc())
+            .load(&db.conn().unwrap())
+            .unwrap()
+    }
+
+    #[actix_rt::test]
+    async fn test_sample_interval() {
+        let dir = TempDir::new("activity-usage").unwrap();
+        let db = init_db(&dir).await;
+
+        let seconds = [0, 5, 10, 15, 19, 20];
+        add_samples(&db, seconds.iter().map(|s| start() + Duration::seconds(*s)));
+        let expected: Vec<_> = [0, 10, 20]
+            .iter()
+            .map(|s| start() + Duration::seconds(*s))
+            .collect();
+        assert_eq!(sample_times(&db), expected);
+    }
+
+    #[actix_rt::test]
+    async fn test_samples_halved_at_limit() {
+        let dir = TempDir::new("activity-usage").unwrap();
+        let db = init_db(&dir).await;
+        let interval = USAGE_SAMPLE_INTERVAL_SECONDS;
+
+        add_samples(
+            &db,
+            (0..MAX_USAGE_SAMPLES - 1).map(|n| start() + Duration::seconds(n * interval)),
+        );
+        assert_eq!(sample_times(&db).len() as i64, MAX_USAGE_SAMPLES - 1);
+
+        let last = start() + Duration::seconds((MAX_USAGE_SAMPLES - 1) * interval);
+        add_samples(&db, vec![last]);
+        let times = sample_times(&db);
+        assert_eq!(times.len() as i64, MAX_USAGE_SAMPLES / 2 + 1);
+        assert_eq!(times.first(), Some(&start()));
+        assert_eq!(times.last(), Some(&last));
+        assert_eq!(times[1] - times[0], Duration::seconds(2 * interval));
+
+        // Remaining samples are twice as sparse, so are the next ones.
+        add_samples(
+            &db,
+            vec![
+                last + Duration::seconds(interval),
+                last + Duration::seconds(2 * interval - 2),
+            ],
+        );
+        assert_eq!(sample_times(&db).len(), times.len());
+        add_samples(&db, vec![last + Duration::seconds(2 * interval)]);
+        assert_eq!(sample_times(&db).len(), times.len() + 1);
+    }
+
+    #[actix_rt::test]
+    async fn test_history_after_returned_timestamp() {
+        let dir = TempDir::new("activity-usage").unwrap();
+        let db = init_db(&dir).await;
+        add_samples(&db, vec![start() + Duration::milliseconds(1500)]);
+
+        let dao = db.as_dao::<ActivityUsageDao>();
+        let history = dao.get_history(ACTIVITY_ID, None).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].timestamp, start().timestamp() + 1);
+
+        let after = Utc.timestamp(history[0].timestamp, 0);
+        let history = dao.get_history(ACTIVITY_ID, Some(after)).await.unwrap();
+        assert!(history.is_empty());
+    }
 }
@@ -8,6 +8,7 @@ use diesel::serialize::Output;
 use diesel::sql_types::Integer;
 use diesel::types::{FromSql, ToSql};
 use std::convert::TryFrom;
+use ya_persistence::types::Role;
 
 #[derive(Queryable, Debug, Identifiable)]
 #[table_name = "activity"]
@@ -17,6 +18,9 @@ pub struct Activity {
     pub agreement_id: String,
     pub state_id: i32,
     pub usage_id: i32,
+    pub owner_id: Option<String>,
+    pub role: Option<Role>,
+    pub created_date: Option<NaiveDateTime>,
 }
 
 #[derive(Queryable, Debug, Identifiable)]
@@ -5,6 +5,9 @@ table! {
         agreement_id -> Text,
         state_id -> Integer,
         usage_id -> Integer,
+        owner_id -> Nullable<Text>,
+        role -> Nullable<Text>,
+        created_date -> Nullable<Timestamp>,
     }
 }
 
@@ -52,6 +55,15 @@ table! {
     }
 }
 
+table! {
+    activity_usage_sample (id) {
+        id -> Integer,
+        activity_id -> Integer,
+        vector_json -> Text,
+        timestamp -> Timestamp,
+    }
+}
+
 table! {
     runtime_event (id) {
         id -> Integer,
@@ -77,6 +89,7 @@ joinable!(activity -> activity_state (state_id));
 joinable!(activity -> activity_usage (usage_id));
 joinable!(activity_event -> activity (activity_id));
 joinable!(activity_event -> activity_event_type (event_type_id));
+joinable!(activity_usage_sample -> activity (activity_id));
 joinable!(runtime_event -> activity (activity_id));
 joinable!(runtime_event -> runtime_event_type (type_id));
 
@@ -87,6 +100,7 @@ allow_tables_to_appear_in_same_query!(
     activity_event_type,
     activity_state,
     activity_usage,
+    activity_usage_sample,
     runtime_event,
     runtime_event_type,
 );
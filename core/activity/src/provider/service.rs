@@ -19,7 +19,7 @@ use ya_service_bus::{timeout::*, typed::ServiceBinder};
 use crate::common::{
     authorize_activity_initiator, authorize_agreement_initiator, generate_id,
     get_activity_agreement, get_agreement, get_persisted_state, get_persisted_usage,
-    set_persisted_state, RpcMessageResult,
+    get_persisted_usage_history, list_activities, set_persisted_state, RpcMessageResult,
 };
 use crate::dao::*;
 use crate::db::models::ActivityEventType;
@@ -63,7 +63,8 @@ pub fn bind_gsb(db: &DbExecutor) {
         .bind(create_activity_gsb)
         .bind(destroy_activity_gsb)
         .bind(get_activity_state_gsb)
-        .bind(get_activity_usage_gsb);
+        .bind(get_activity_usage_gsb)
+        .bind(get_activity_usage_history_gsb);
 
     // Initialize counters to 0 value. Otherwise they won't appear on metrics endpoint
     // until first change to value will be made.
@@ -104,7 +105,12 @@ async fn create_activity_gsb(
     let provider_id = agreement.provider_id().clone();
 
     db.as_dao::<ActivityDao>()
-        .create_if_not_exists(&activity_id, &msg.agreement_id)
+        .create_if_not_exists(
+            &activity_id,
+            &msg.agreement_id,
+            &provider_id,
+            Role::Provider,
+        )
         .await
         .map_err(Error::from)?;
 
@@ -259,6 +265,16 @@ async fn get_activity_usage_gsb(
     Ok(get_persisted_usage(&db, &msg.activity_id).await?)
 }
 
+async fn get_activity_usage_history_gsb(
+    db: DbExecutor,
+    caller: String,
+    msg: activity::GetUsageHistory,
+) -> RpcMessageResult<activity::GetUsageHistory> {
+    authorize_activity_initiator(&db, caller, &msg.activity_id, Role::Provider).await?;
+
+    Ok(get_persisted_usage_history(&db, &msg.activity_id, msg.since).await?)
+}
+
 async fn get_activity_progress(
     db: &DbExecutor,
     activity_id: &str,
@@ -367,6 +383,7 @@ mod local {
             .bind(set_activity_state_gsb)
             .bind(set_activity_usage_gsb)
             .bind(get_agreement_id_gsb)
+            .bind(list_activities_gsb)
             .bind(activity_status);
     }
 
@@ -394,6 +411,14 @@ mod local {
         })
     }
 
+    async fn list_activities_gsb(
+        db: DbExecutor,
+        _caller: String,
+        msg: activity::local::ListActivities,
+    ) -> RpcMessageResult<activity::local::ListActivities> {
+        Ok(list_activities(&db, msg).await?)
+    }
+
     /// Pass activity state (which may include error details).
     /// Called by ExeUnits.
     ///
@@ -3,10 +3,10 @@ use actix_web::http::header;
 use actix_web::{web, Either, HttpRequest, HttpResponse, Responder};
 use bytes::{BufMut, Bytes, BytesMut};
 use futures::{FutureExt, StreamExt, TryFutureExt};
-use metrics::counter;
+use metrics::{counter, timing};
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use ya_client_model::activity::{
     ActivityState, CreateActivityRequest, CreateActivityResult, Credentials, ExeScriptCommand,
@@ -29,6 +29,7 @@ pub fn extend_web_scope(scope: actix_web::Scope) -> actix_web::Scope {
         .service(destroy_activity)
         .service(exec)
         .service(get_batch_results)
+        .service(send_command_input)
         .service(encrypted)
 }
 
@@ -92,16 +93,23 @@ async fn create_activity(
         requestor_pub_key: body.pub_key()?,
     };
 
+    let start = Instant::now();
     let create_resp = net::from(id.identity)
         .to(provider_id)
         .service(activity::BUS_ID)
         .send(msg)
         .timeout(query.timeout)
         .await???;
+    timing!("activity.requestor.create.time", start, Instant::now());
 
     log::debug!("activity created: {}, inserting", create_resp.activity_id());
     db.as_dao::<ActivityDao>()
-        .create_if_not_exists(&create_resp.activity_id(), agreement_id)
+        .create_if_not_exists(
+            &create_resp.activity_id(),
+            agreement_id,
+            &id.identity,
+            Role::Requestor,
+        )
         .await?;
 
     counter!("activity.requestor.created", 1);
@@ -175,12 +183,14 @@ async fn exec(
         timeout: query.timeout.clone(),
     };
 
+    let start = Instant::now();
     ya_net::from(id.identity)
         .to(agreement.provider_id().clone())
         .service(&activity::exeunit::bus_id(&path.activity_id))
         .send(msg)
         .timeout(query.timeout)
         .await???;
+    timing!("activity.requestor.exec.time", start, Instant::now());
 
     counter!("activity.requestor.run-exescript", 1);
     Ok::<_, Error>(web::Json(batch_id))
@@ -302,6 +312,40 @@ fn map_event_result<T: Serialize>(
     Ok(bytes.freeze())
 }
 
+/// Sends stdin data or a terminal resize to a running command of the batch.
+/// ExeUnits accept input only if the Provider enabled interactive commands.
+#[actix_web::post("/activity/{activity_id}/exec/{batch_id}/input")]
+async fn send_command_input(
+    db: web::Data<DbExecutor>,
+    path: web::Path<PathActivityBatch>,
+    query: web::Query<QueryTimeoutCommandIndex>,
+    body: web::Json<activity::CommandInput>,
+    id: Identity,
+) -> impl Responder {
+    authorize_activity_initiator(&db, id.identity, &path.activity_id, Role::Requestor).await?;
+
+    let command_index = query
+        .command_index
+        .ok_or_else(|| Error::BadRequest("missing commandIndex".to_string()))?;
+    let agreement = get_activity_agreement(&db, &path.activity_id, Role::Requestor).await?;
+    let msg = activity::SendCommandInput {
+        activity_id: path.activity_id.clone(),
+        batch_id: path.batch_id.clone(),
+        command_index,
+        input: body.into_inner(),
+        timeout: query.timeout,
+    };
+
+    ya_net::from(id.identity)
+        .to(agreement.provider_id().clone())
+        .service(&activity::exeunit::bus_id(&path.activity_id))
+        .send(msg)
+        .timeout(query.timeout)
+        .await???;
+
+    Ok::<_, Error>(web::Json(()))
+}
+
 /// Forwards an encrypted ExeUnit call.
 #[actix_web::post("/activity/{activity_id}/encrypted")]
 async fn encrypted(
@@ -1,7 +1,8 @@
+use actix_rt::Arbiter;
 use ya_persistence::executor::DbExecutor;
 use ya_service_api_interfaces::{Provider, Service};
 
-use crate::{api, db::migrations, provider};
+use crate::{api, common::recover_activity_owners, db::migrations, provider};
 
 pub struct Activity;
 
@@ -14,6 +15,7 @@ impl Activity {
         let db: DbExecutor = ctx.component();
         db.apply_migration(migrations::run_with_output)?;
         provider::service::bind_gsb(&db);
+        Arbiter::spawn(recover_activity_owners(db.clone()));
         Ok(())
     }
 
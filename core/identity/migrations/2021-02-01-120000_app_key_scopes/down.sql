@@ -0,0 +1,24 @@
+-- HACK: removing columns 'scopes', 'expires_date' and 'last_used_date'
+
+PRAGMA foreign_keys=off;
+
+CREATE TABLE "app_key_tmp"(
+	"id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	"role_id" INTEGER NOT NULL,
+	"name" VARCHAR(255) NOT NULL,
+	"key" VARCHAR(255) NOT NULL,
+	"identity_id" VARCHAR(255) NOT NULL,
+	"created_date" DATETIME NOT NULL,
+    FOREIGN KEY("role_id") REFERENCES "role" ("id"),
+    FOREIGN KEY (identity_id) REFERENCES identity(identity_id),
+    UNIQUE("name")
+);
+
+INSERT INTO "app_key_tmp"(id, role_id, name, key, identity_id, created_date)
+SELECT id, role_id, name, key, identity_id, created_date FROM "app_key";
+
+DROP TABLE "app_key";
+
+ALTER TABLE "app_key_tmp" RENAME TO "app_key";
+
+PRAGMA foreign_keys=on;
@@ -0,0 +1,4 @@
+-- Space separated list of scopes, NULL grants access to all APIs
+ALTER TABLE "app_key" ADD COLUMN "scopes" TEXT NULL;
+ALTER TABLE "app_key" ADD COLUMN "expires_date" DATETIME NULL;
+ALTER TABLE "app_key" ADD COLUMN "last_used_date" DATETIME NULL;
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use chrono::{DateTime, NaiveDateTime, Utc};
 use structopt::*;
 
 use ya_core_model::appkey as model;
@@ -17,6 +18,13 @@ pub enum AppKeyCommand {
         role: String,
         #[structopt(long)]
         id: Option<String>,
+        /// Restricts key to given API scope, e.g. `market:read`, `activity:exec`
+        /// or `payment:*`. Can be repeated. Key without scopes has full access.
+        #[structopt(long = "scope", number_of_values = 1)]
+        scopes: Vec<String>,
+        /// Key lifetime (e.g. `30days`) or expiration date in RFC 3339 format.
+        #[structopt(long, parse(try_from_str = parse_expires))]
+        expires: Option<NaiveDateTime>,
     },
     Drop {
         name: String,
@@ -33,6 +41,15 @@ pub enum AppKeyCommand {
     },
 }
 
+fn parse_expires(s: &str) -> Result<NaiveDateTime> {
+    if let Ok(lifetime) = humantime::parse_duration(s) {
+        return Ok(Utc::now().naive_utc() + chrono::Duration::from_std(lifetime)?);
+    }
+    Ok(DateTime::parse_from_rfc3339(s)
+        .map_err(|_| anyhow::anyhow!("expected duration or RFC 3339 date, got: {}", s))?
+        .naive_utc())
+}
+
 impl AppKeyCommand {
     async fn get_identity(get_by: idm::Get) -> anyhow::Result<IdentityInfo> {
         bus::service(idm::BUS_ID)
@@ -45,7 +62,13 @@ impl AppKeyCommand {
 
     pub async fn run_command(&self, _ctx: &CliCtx) -> Result<CommandOutput> {
         match &self {
-            AppKeyCommand::Create { name, role, id } => {
+            AppKeyCommand::Create {
+                name,
+                role,
+                id,
+                scopes,
+                expires,
+            } => {
                 let identity = match id {
                     Some(id) => {
                         if id.starts_with("0x") {
@@ -62,12 +85,16 @@ impl AppKeyCommand {
                     name: name.clone(),
                     role: role.clone(),
                     identity,
+                    scopes: match scopes.is_empty() {
+                        true => None,
+                        false => Some(scopes.clone()),
+                    },
+                    expires_date: *expires,
                 };
                 let key = bus::service(model::BUS_ID)
                     .send(create)
                     .await
-                    .map_err(anyhow::Error::msg)?
-                    .unwrap();
+                    .map_err(anyhow::Error::msg)??;
                 Ok(CommandOutput::Object(serde_json::to_value(key)?))
             }
             AppKeyCommand::Drop { name, id } => {
@@ -101,6 +128,9 @@ impl AppKeyCommand {
                         "id".into(),
                         "role".into(),
                         "created".into(),
+                        "scopes".into(),
+                        "expires".into(),
+                        "last used".into(),
                     ],
                     values: result
                         .0
@@ -108,7 +138,9 @@ impl AppKeyCommand {
                         .map(|app_key| {
                             serde_json::json! {[
                                 app_key.name, app_key.key, app_key.identity,
-                                app_key.role, app_key.created_date
+                                app_key.role, app_key.created_date,
+                                app_key.scopes.map(|scopes| scopes.join(" ")),
+                                app_key.expires_date, app_key.last_used_date
                             ]}
                         })
                         .collect(),
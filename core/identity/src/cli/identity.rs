@@ -133,6 +133,34 @@ pub enum IdentityCommand {
         node_or_alias: NodeOrAlias,
     },
 
+    /// Imports identity from Ethereum JSON keystore or BIP-39 mnemonic
+    Import {
+        /// Alias of imported identity
+        #[structopt(long)]
+        alias: Option<String>,
+
+        /// Keystore file to import
+        #[structopt(long, required_unless = "mnemonic", conflicts_with = "mnemonic")]
+        keystore: Option<PathBuf>,
+
+        /// Derive key from mnemonic phrase (read from tty)
+        #[structopt(long)]
+        mnemonic: bool,
+
+        /// Derivation path of the key
+        #[structopt(long, requires = "mnemonic", default_value = identity::DEFAULT_DERIVATION_PATH)]
+        derivation_path: String,
+
+        /// Store key derived from mnemonic without password
+        #[structopt(long = "no-password", requires = "mnemonic")]
+        no_password: bool,
+    },
+
+    /// Changes password of given identity
+    ChangePassword {
+        node_or_alias: Option<NodeOrAlias>,
+    },
+
     /// Exports given identity to a file | stdout
     Export {
         /// Identity alias to export
@@ -144,6 +172,23 @@ pub enum IdentityCommand {
     },
 }
 
+fn read_new_password(no_password: bool) -> Result<Protected> {
+    if no_password {
+        return Ok(Protected::from(""));
+    }
+    let password: Protected = rpassword::read_password_from_tty(Some("Password: "))?.into();
+    let password2: Protected =
+        rpassword::read_password_from_tty(Some("Confirm password: "))?.into();
+    if password.as_ref() != password2.as_ref() {
+        anyhow::bail!("Password and confirmation do not match.")
+    }
+    Ok(password)
+}
+
+fn protected_to_string(password: Protected) -> Result<String> {
+    Ok(String::from_utf8(password.as_ref().to_vec())?)
+}
+
 impl IdentityCommand {
     pub async fn run_command(&self, _ctx: &CliCtx) -> Result<CommandOutput> {
         match self {
@@ -209,19 +254,7 @@ impl IdentityCommand {
                 let key_file = if let Some(keystore) = from_keystore {
                     std::fs::read_to_string(keystore)?
                 } else {
-                    let password = if *no_password {
-                        Protected::from("")
-                    } else {
-                        let password: Protected =
-                            rpassword::read_password_from_tty(Some("Password: "))?.into();
-                        let password2: Protected =
-                            rpassword::read_password_from_tty(Some("Confirm password: "))?.into();
-                        if password.as_ref() != password2.as_ref() {
-                            anyhow::bail!("Password and confirmation do not match.")
-                        }
-                        password
-                    };
-                    crate::id_key::generate_new_keyfile(password)?
+                    crate::id_key::generate_new_keyfile(read_new_password(*no_password)?)?
                 };
 
                 let id = bus::service(identity::BUS_ID)
@@ -274,6 +307,57 @@ impl IdentityCommand {
                         .map_err(|e| anyhow::Error::msg(e))?,
                 )
             }
+            IdentityCommand::Import {
+                alias,
+                keystore,
+                mnemonic: _,
+                derivation_path,
+                no_password,
+            } => {
+                let (source, password) = match keystore {
+                    Some(keystore) => (
+                        identity::KeySource::KeyStore(std::fs::read_to_string(keystore)?),
+                        rpassword::read_password_from_tty(Some("Keystore password: "))?,
+                    ),
+                    None => {
+                        let phrase = rpassword::read_password_from_tty(Some("Mnemonic: "))?;
+                        let password = read_new_password(*no_password)?;
+                        (
+                            identity::KeySource::Mnemonic {
+                                phrase,
+                                derivation_path: Some(derivation_path.clone()),
+                            },
+                            protected_to_string(password)?,
+                        )
+                    }
+                };
+
+                CommandOutput::object(
+                    bus::service(identity::BUS_ID)
+                        .send(identity::Import {
+                            alias: alias.clone(),
+                            source,
+                            password,
+                        })
+                        .await
+                        .map_err(|e| anyhow::Error::msg(e))?,
+                )
+            }
+            IdentityCommand::ChangePassword { node_or_alias } => {
+                let node_id = node_or_alias.clone().unwrap_or_default().resolve().await?;
+                let old_password = rpassword::read_password_from_tty(Some("Current password: "))?;
+                let new_password = protected_to_string(read_new_password(false)?)?;
+                CommandOutput::object(
+                    bus::service(identity::BUS_ID)
+                        .send(identity::ChangePassword {
+                            node_id,
+                            old_password,
+                            new_password,
+                        })
+                        .await
+                        .map_err(|e| anyhow::Error::msg(e))?,
+                )
+            }
             IdentityCommand::Export {
                 node_or_alias,
                 file_path,
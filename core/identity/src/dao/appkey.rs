@@ -1,6 +1,6 @@
 pub use crate::dao::Error as DaoError;
 pub use crate::db::models::{AppKey, Role};
-use chrono::Utc;
+use chrono::{NaiveDateTime, Utc};
 use diesel::prelude::*;
 
 use diesel::{Connection, ExpressionMethods, RunQueryDsl};
@@ -48,6 +48,8 @@ impl<'c> AppKeyDao<'c> {
         name: String,
         role: String,
         identity: NodeId,
+        scopes: Option<Vec<String>>,
+        expires_date: Option<NaiveDateTime>,
     ) -> Result<()> {
         use crate::db::schema::app_key as app_key_dsl;
         use crate::db::schema::role as role_dsl;
@@ -64,6 +66,8 @@ impl<'c> AppKeyDao<'c> {
                     app_key_dsl::key.eq(key),
                     app_key_dsl::identity_id.eq(identity),
                     app_key_dsl::created_date.eq(Utc::now().naive_utc()),
+                    app_key_dsl::scopes.eq(scopes.map(|scopes| scopes.join(" "))),
+                    app_key_dsl::expires_date.eq(expires_date),
                 ))
                 .execute(conn)?;
 
@@ -87,6 +91,27 @@ impl<'c> AppKeyDao<'c> {
         .await
     }
 
+    /// Skips the write, when stored last use time is more recent
+    /// than `min_interval`.
+    pub async fn mark_used(&self, key: String, min_interval: chrono::Duration) -> Result<()> {
+        use crate::db::schema::app_key as app_key_dsl;
+
+        do_with_transaction(self.pool, move |conn| {
+            let now = Utc::now().naive_utc();
+            diesel::update(
+                app_key_dsl::table.filter(app_key_dsl::key.eq(key)).filter(
+                    app_key_dsl::last_used_date
+                        .is_null()
+                        .or(app_key_dsl::last_used_date.lt(now - min_interval)),
+                ),
+            )
+            .set(app_key_dsl::last_used_date.eq(now))
+            .execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
+
     pub async fn get_for_id(&self, identity_id: String) -> Result<(AppKey, Role)> {
         use crate::db::schema::app_key as app_key_dsl;
         use crate::db::schema::role as role_dsl;
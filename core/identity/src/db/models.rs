@@ -29,6 +29,9 @@ pub struct AppKey {
     pub key: String,
     pub identity_id: NodeId,
     pub created_date: NaiveDateTime,
+    pub scopes: Option<String>,
+    pub expires_date: Option<NaiveDateTime>,
+    pub last_used_date: Option<NaiveDateTime>,
 }
 
 #[derive(Queryable, Debug, Identifiable)]
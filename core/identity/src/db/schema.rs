@@ -6,6 +6,9 @@ table! {
         key -> Text,
         identity_id -> Text,
         created_date -> Timestamp,
+        scopes -> Nullable<Text>,
+        expires_date -> Nullable<Timestamp>,
+        last_used_date -> Nullable<Timestamp>,
     }
 }
 
@@ -8,6 +8,7 @@ use ethsign::{KeyFile, Protected, SecretKey};
 use rand::Rng;
 use std::convert::TryFrom;
 use ya_client_model::NodeId;
+use ya_core_model::identity as model;
 
 pub struct IdentityKey {
     id: NodeId,
@@ -69,6 +70,34 @@ impl IdentityKey {
     pub fn lock(&mut self) {
         self.secret = None;
     }
+
+    /// Returns key file re-encrypted with `new_password`, without changing
+    /// this key. Returns `None`, when `old_password` is invalid.
+    pub fn reencrypt(
+        &self,
+        old_password: Protected,
+        new_password: Protected,
+    ) -> Result<Option<KeyFile>, Error> {
+        let secret = match self.key_file.to_secret_key(&old_password) {
+            Ok(secret) => secret,
+            Err(ethsign::Error::InvalidPassword) => return Ok(None),
+            Err(e) => return Err(Error::internal(e)),
+        };
+        // KeyFile isn't `Clone`, so it's copied through its JSON form.
+        let mut key_file: KeyFile = self
+            .to_key_file()
+            .and_then(|json| serde_json::from_str(&json))
+            .map_err(Error::internal)?;
+        key_file.crypto = secret
+            .to_crypto(&new_password, KEY_ITERATIONS)
+            .map_err(Error::internal)?;
+        Ok(Some(key_file))
+    }
+
+    /// Should be called only after new key file is stored.
+    pub fn replace_key_file(&mut self, key_file: KeyFile) {
+        self.key_file = key_file;
+    }
 }
 
 impl TryFrom<Identity> for IdentityKey {
@@ -114,8 +143,105 @@ fn generate_new_secret(password: Protected) -> (KeyFile, SecretKey) {
     (key_file, secret)
 }
 
+fn to_key_file(secret: &SecretKey, password: &Protected) -> Result<KeyFile, model::Error> {
+    Ok(KeyFile {
+        id: format!("{}", uuid::Uuid::new_v4()),
+        version: KEYSTORE_VERSION,
+        crypto: secret
+            .to_crypto(password, KEY_ITERATIONS)
+            .map_err(model::Error::new_err_msg)?,
+        address: Some(Bytes(secret.public().address().to_vec())),
+    })
+}
+
+/// Checks, that keystore can be decrypted with `password`. Keystores without
+/// address get it filled in.
+pub fn import_key_file(
+    key_file_json: &str,
+    password: &Protected,
+) -> Result<(NodeId, KeyFile), model::Error> {
+    let mut key_file: KeyFile =
+        serde_json::from_str(key_file_json).map_err(model::Error::keystore_format)?;
+    if key_file.version != KEYSTORE_VERSION {
+        return Err(model::Error::keystore_format(format!(
+            "unsupported version {}",
+            key_file.version
+        )));
+    }
+    let secret = match key_file.to_secret_key(password) {
+        Ok(secret) => secret,
+        Err(ethsign::Error::InvalidPassword) => return Err(model::Error::InvalidPassword),
+        Err(e) => return Err(model::Error::keystore_format(e)),
+    };
+
+    let address = secret.public().address().to_vec();
+    match &key_file.address {
+        Some(declared) if declared.0 != address => {
+            return Err(model::Error::keystore_format(
+                "address doesn't match the key",
+            ))
+        }
+        Some(_) => (),
+        None => key_file.address = Some(Bytes(address.clone())),
+    }
+    Ok((NodeId::from(address.as_slice()), key_file))
+}
+
+/// Derives key from BIP-39 mnemonic the same way, as Ethereum wallets do.
+pub fn key_file_from_mnemonic(
+    phrase: &str,
+    derivation_path: Option<&str>,
+    password: &Protected,
+) -> Result<(NodeId, KeyFile), model::Error> {
+    let mnemonic = bip39::Mnemonic::from_phrase(phrase.trim(), bip39::Language::English)
+        .map_err(|e| model::Error::keystore_format(format!("invalid mnemonic: {}", e)))?;
+    let seed = bip39::Seed::new(&mnemonic, "");
+    let path = derivation_path.unwrap_or(model::DEFAULT_DERIVATION_PATH);
+    let derived = tiny_hderive::bip32::ExtendedPrivKey::derive(seed.as_bytes(), path)
+        .map_err(|e| model::Error::keystore_format(format!("invalid derivation path: {:?}", e)))?;
+    let secret = SecretKey::from_raw(&derived.secret()).map_err(model::Error::new_err_msg)?;
+
+    let node_id = NodeId::from(secret.public().address().as_ref());
+    Ok((node_id, to_key_file(&secret, password)?))
+}
+
 pub fn generate_new_keyfile(password: Protected) -> anyhow::Result<String> {
     let (key_file, _) = generate_new_secret(password);
 
     Ok(serde_json::to_string(&key_file).context("serialize keyfile")?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_mnemonic_derives_wallet_address() {
+        let password = Protected::from("secret");
+        let (node_id, key_file) = key_file_from_mnemonic(MNEMONIC, None, &password).unwrap();
+        assert_eq!(
+            node_id,
+            "0x9858effd232b4033e47d90003d41ec34ecaeda94"
+                .parse()
+                .unwrap()
+        );
+
+        // Derived key round trips through keystore import.
+        let key_file_json = serde_json::to_string(&key_file).unwrap();
+        let (imported, _) = import_key_file(&key_file_json, &password).unwrap();
+        assert_eq!(imported, node_id);
+        assert!(matches!(
+            import_key_file(&key_file_json, &Protected::from("wrong")),
+            Err(model::Error::InvalidPassword)
+        ));
+    }
+
+    #[test]
+    fn test_invalid_mnemonic() {
+        let password = Protected::from("");
+        assert!(key_file_from_mnemonic("abandon about", None, &password).is_err());
+        assert!(key_file_from_mnemonic(MNEMONIC, Some("m/x"), &password).is_err());
+    }
+}
@@ -4,8 +4,10 @@
 extern crate diesel;
 
 pub mod cli;
+pub mod rest;
 pub mod service;
 
 pub mod dao;
 mod db;
 mod id_key;
+mod signature;
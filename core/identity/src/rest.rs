@@ -0,0 +1,110 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+
+use ya_client_model::{ErrorMessage, NodeId};
+use ya_core_model::identity as model;
+use ya_service_bus::{typed as bus, RpcEndpoint};
+
+pub const IDENTITY_API_PATH: &str = "/identity-api/v1";
+
+pub fn web_scope() -> actix_web::Scope {
+    actix_web::web::scope(IDENTITY_API_PATH)
+        .service(verify)
+        .service(recover)
+}
+
+/// Binary fields are hex encoded, with optional `0x` prefix.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum SignerBody {
+    NodeId(NodeId),
+    PublicKey(String),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VerifyBody {
+    signer: SignerBody,
+    payload: String,
+    signature: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RecoverBody {
+    payload: String,
+    signature: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VerifyResult {
+    valid: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RecoverResult {
+    node_id: NodeId,
+}
+
+fn decode_hex(field: &str, value: &str) -> Result<Vec<u8>, HttpResponse> {
+    hex::decode(value.trim_start_matches("0x")).map_err(|e| {
+        HttpResponse::BadRequest().json(ErrorMessage::new(format!("Invalid {}: {}", field, e)))
+    })
+}
+
+fn error_response(e: model::Error) -> HttpResponse {
+    match e {
+        model::Error::InvalidSignature(_) => {
+            HttpResponse::BadRequest().json(ErrorMessage::new(e.to_string()))
+        }
+        e => HttpResponse::InternalServerError().json(ErrorMessage::new(e.to_string())),
+    }
+}
+
+#[actix_web::post("/verify")]
+async fn verify(body: web::Json<VerifyBody>) -> impl Responder {
+    let body = body.into_inner();
+    let signer = match body.signer {
+        SignerBody::NodeId(node_id) => model::Signer::NodeId(node_id),
+        SignerBody::PublicKey(key) => match decode_hex("publicKey", &key) {
+            Ok(key) => model::Signer::PublicKey(key),
+            Err(response) => return response,
+        },
+    };
+    let (payload, signature) = match (
+        decode_hex("payload", &body.payload),
+        decode_hex("signature", &body.signature),
+    ) {
+        (Ok(payload), Ok(signature)) => (payload, signature),
+        (Err(response), _) | (_, Err(response)) => return response,
+    };
+    let msg = model::Verify {
+        signer,
+        payload,
+        signature,
+    };
+    match bus::service(model::BUS_ID).send(msg).await {
+        Ok(Ok(valid)) => HttpResponse::Ok().json(VerifyResult { valid }),
+        Ok(Err(e)) => error_response(e),
+        Err(e) => HttpResponse::InternalServerError().json(ErrorMessage::new(e.to_string())),
+    }
+}
+
+#[actix_web::post("/recover")]
+async fn recover(body: web::Json<RecoverBody>) -> impl Responder {
+    let (payload, signature) = match (
+        decode_hex("payload", &body.payload),
+        decode_hex("signature", &body.signature),
+    ) {
+        (Ok(payload), Ok(signature)) => (payload, signature),
+        (Err(response), _) | (_, Err(response)) => return response,
+    };
+    let msg = model::Recover { payload, signature };
+    match bus::service(model::BUS_ID).send(msg).await {
+        Ok(Ok(node_id)) => HttpResponse::Ok().json(RecoverResult { node_id }),
+        Ok(Err(e)) => error_response(e),
+        Err(e) => HttpResponse::InternalServerError().json(ErrorMessage::new(e.to_string())),
+    }
+}
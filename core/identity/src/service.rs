@@ -28,4 +28,8 @@ impl Identity {
         appkey::activate(&db).await?;
         Ok(())
     }
+
+    pub fn rest<Context>(_: &Context) -> actix_web::Scope {
+        crate::rest::web_scope()
+    }
 }
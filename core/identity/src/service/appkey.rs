@@ -1,3 +1,4 @@
+use chrono::Utc;
 use uuid::Uuid;
 
 use futures::prelude::*;
@@ -5,6 +6,7 @@ use ya_core_model::appkey as model;
 use ya_persistence::executor::DbExecutor;
 use ya_service_bus::typed as bus;
 
+use crate::dao::appkey::{AppKey, Role};
 use crate::dao::AppKeyDao;
 use actix_rt::Arbiter;
 use std::cell::{Ref, RefCell};
@@ -42,6 +44,36 @@ fn send_events(s: Ref<Subscription>, event: model::event::Event) -> impl Future<
     }
 }
 
+fn to_model(app_key: AppKey, role: Role) -> model::AppKey {
+    model::AppKey {
+        name: app_key.name,
+        key: app_key.key,
+        role: role.name,
+        identity: app_key.identity_id,
+        created_date: app_key.created_date,
+        scopes: app_key
+            .scopes
+            .map(|scopes| scopes.split_whitespace().map(str::to_string).collect()),
+        expires_date: app_key.expires_date,
+        last_used_date: app_key.last_used_date,
+    }
+}
+
+fn validate(create: &model::Create) -> Result<(), model::Error> {
+    for scope in create.scopes.iter().flatten() {
+        scope.parse::<model::Scope>()?;
+    }
+    if create.scopes.as_ref().map(Vec::is_empty).unwrap_or(false) {
+        return Err(model::Error::bad_request("at least one scope required"));
+    }
+    match create.expires_date {
+        Some(expires) if expires <= Utc::now().naive_utc() => Err(model::Error::bad_request(
+            format!("expiration date {} is in the past", expires),
+        )),
+        _ => Ok(()),
+    }
+}
+
 pub async fn activate(db: &DbExecutor) -> anyhow::Result<()> {
     let dbx = db.clone();
     let (tx, rx) = futures::channel::mpsc::unbounded();
@@ -70,9 +102,17 @@ pub async fn activate(db: &DbExecutor) -> anyhow::Result<()> {
         let mut create_tx = create_tx.clone();
         let identity = create.identity.clone();
         async move {
+            validate(&create)?;
             let result = db
                 .as_dao::<AppKeyDao>()
-                .create(key.clone(), create.name, create.role, create.identity)
+                .create(
+                    key.clone(),
+                    create.name,
+                    create.role,
+                    create.identity,
+                    create.scopes,
+                    create.expires_date,
+                )
                 .await
                 .map_err(|e| model::Error::internal(e))
                 .map(|_| key)?;
@@ -90,17 +130,24 @@ pub async fn activate(db: &DbExecutor) -> anyhow::Result<()> {
         async move {
             let (appkey, role) = db
                 .as_dao::<AppKeyDao>()
-                .get(get.key)
+                .get(get.key.clone())
                 .await
                 .map_err(|e| model::Error::internal(e.to_string()))?;
 
-            Ok(model::AppKey {
-                name: appkey.name,
-                key: appkey.key,
-                role: role.name,
-                identity: appkey.identity_id,
-                created_date: appkey.created_date,
-            })
+            Ok(to_model(appkey, role))
+        }
+    });
+
+    let dbx = db.clone();
+    let _ = bus::bind(&model::BUS_ID, move |used: model::MarkUsed| {
+        let db = dbx.clone();
+        async move {
+            let min_interval = chrono::Duration::from_std(model::LAST_USED_INTERVAL)
+                .map_err(model::Error::internal)?;
+            db.as_dao::<AppKeyDao>()
+                .mark_used(used.key, min_interval)
+                .await
+                .map_err(model::Error::internal)
         }
     });
 
@@ -118,13 +165,7 @@ pub async fn activate(db: &DbExecutor) -> anyhow::Result<()> {
             let keys = result
                 .0
                 .into_iter()
-                .map(|(app_key, role)| model::AppKey {
-                    name: app_key.name,
-                    key: app_key.key,
-                    role: role.name,
-                    identity: app_key.identity_id,
-                    created_date: app_key.created_date,
-                })
+                .map(|(app_key, role)| to_model(app_key, role))
                 .collect();
 
             Ok((keys, result.1))
@@ -12,7 +12,8 @@ use ya_service_bus::typed as bus;
 
 use crate::dao::identity::Identity;
 use crate::dao::{Error as DaoError, IdentityDao};
-use crate::id_key::{generate_new, IdentityKey};
+use crate::id_key::{generate_new, import_key_file, key_file_from_mnemonic, IdentityKey};
+use crate::signature;
 use actix_rt::Arbiter;
 use futures::prelude::*;
 use std::cell::{Ref, RefCell};
@@ -200,12 +201,29 @@ impl IdentityService {
         Ok(output)
     }
 
+    /// Imported keys can't replace existing identity nor take its alias.
+    fn check_collision(
+        &self,
+        alias: &Option<String>,
+        node_id: &NodeId,
+    ) -> Result<(), model::Error> {
+        let alias_taken = alias
+            .as_ref()
+            .map(|alias| self.alias_to_id.contains_key(alias))
+            .unwrap_or(false);
+        if alias_taken || self.ids.contains_key(node_id) {
+            return Err(model::Error::AlreadyExists);
+        }
+        Ok(())
+    }
+
     pub async fn create_from_keystore(
         &mut self,
         alias: Option<String>,
         identity_id: NodeId,
         key_file: KeyFile,
     ) -> Result<model::IdentityInfo, model::Error> {
+        self.check_collision(&alias, &identity_id)?;
         let key_file_json = serde_json::to_string(&key_file).map_err(model::Error::new_err_msg)?;
 
         let new_identity = Identity {
@@ -234,6 +252,57 @@ impl IdentityService {
         Ok(output)
     }
 
+    pub async fn import(
+        &mut self,
+        import: model::Import,
+    ) -> Result<model::IdentityInfo, model::Error> {
+        let password = Protected::from(import.password);
+        let (node_id, key_file) = match import.source {
+            model::KeySource::KeyStore(key_file_json) => {
+                import_key_file(&key_file_json, &password)?
+            }
+            model::KeySource::Mnemonic {
+                phrase,
+                derivation_path,
+            } => key_file_from_mnemonic(&phrase, derivation_path.as_deref(), &password)?,
+        };
+        self.create_from_keystore(import.alias, node_id, key_file)
+            .await
+    }
+
+    pub async fn change_password(
+        &mut self,
+        change: model::ChangePassword,
+    ) -> Result<model::IdentityInfo, model::Error> {
+        let node_id = change.node_id;
+        let default_key = self.default_key;
+        let key_file = self
+            .get_key_by_id(&node_id)?
+            .reencrypt(change.old_password.into(), change.new_password.into())
+            .map_err(model::Error::new_err_msg)?
+            .ok_or(model::Error::InvalidPassword)?;
+        let key_file_json =
+            serde_json::to_string_pretty(&key_file).map_err(model::Error::new_err_msg)?;
+
+        // Key in memory is replaced only after it's stored, so both use the same password.
+        self.db
+            .with_transaction(move |conn| {
+                use crate::db::schema::identity::dsl;
+                use diesel::prelude::*;
+
+                diesel::update(dsl::identity.filter(dsl::identity_id.eq(&node_id)))
+                    .set(dsl::key_file_json.eq(key_file_json))
+                    .execute(conn)?;
+                Ok::<_, DaoError>(())
+            })
+            .await
+            .map_err(model::Error::new_err_msg)?;
+
+        let key = self.get_key_by_id(&node_id)?;
+        key.replace_key_file(key_file);
+        Ok(to_info(&default_key, key))
+    }
+
     fn get_key_by_id(&mut self, node_id: &NodeId) -> Result<&mut IdentityKey, model::Error> {
         Ok(match self.ids.get_mut(node_id) {
             Some(v) => v,
@@ -387,6 +456,17 @@ impl IdentityService {
             }
         });
 
+        let this = me.clone();
+        let _ = bus::bind(model::BUS_ID, move |import: model::Import| {
+            let this = this.clone();
+            async move { this.lock().await.import(import).await }
+        });
+        let this = me.clone();
+        let _ = bus::bind(model::BUS_ID, move |change: model::ChangePassword| {
+            let this = this.clone();
+            async move { this.lock().await.change_password(change).await }
+        });
+
         let this = me.clone();
         let _ = bus::bind(model::BUS_ID, move |update: model::Update| {
             let this = this.clone();
@@ -446,5 +526,12 @@ impl IdentityService {
             let this = this.clone();
             async move { this.lock().await.get_key_file(node_id).await }
         });
+        // Stateless, so no need to take the service lock.
+        let _ = bus::bind(model::BUS_ID, move |verify: model::Verify| async move {
+            signature::verify(&verify.signer, &verify.payload, &verify.signature)
+        });
+        let _ = bus::bind(model::BUS_ID, move |recover: model::Recover| async move {
+            signature::recover(&recover.payload, &recover.signature)
+        });
     }
 }
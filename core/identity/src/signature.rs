@@ -0,0 +1,109 @@
+/// Verification of signatures made by identities and exe-units
+use sha3::Digest;
+use ya_client_model::NodeId;
+use ya_core_model::identity as model;
+
+const RECOVERABLE_SIGNATURE_LEN: usize = 65;
+
+fn to_ethsign_signature(signature: &[u8]) -> Result<ethsign::Signature, model::Error> {
+    if signature.len() != RECOVERABLE_SIGNATURE_LEN {
+        return Err(model::Error::InvalidSignature(format!(
+            "expected {} bytes, got {}",
+            RECOVERABLE_SIGNATURE_LEN,
+            signature.len()
+        )));
+    }
+    let mut r = [0u8; 32];
+    let mut s = [0u8; 32];
+    r.copy_from_slice(&signature[1..33]);
+    s.copy_from_slice(&signature[33..65]);
+    Ok(ethsign::Signature {
+        v: signature[0],
+        r,
+        s,
+    })
+}
+
+/// Inverse of `IdentityKey::sign`.
+pub fn recover(payload: &[u8], signature: &[u8]) -> Result<NodeId, model::Error> {
+    let public = to_ethsign_signature(signature)?
+        .recover(payload)
+        .map_err(|e| model::Error::InvalidSignature(e.to_string()))?;
+    Ok(NodeId::from(public.address().as_ref()))
+}
+
+pub fn verify(
+    signer: &model::Signer,
+    payload: &[u8],
+    signature: &[u8],
+) -> Result<bool, model::Error> {
+    match signer {
+        model::Signer::NodeId(node_id) => Ok(recover(payload, signature)? == *node_id),
+        model::Signer::PublicKey(public_key) => verify_der(public_key, payload, signature),
+    }
+}
+
+/// Scheme used by exe-unit `Crypto::sign`.
+fn verify_der(public_key: &[u8], payload: &[u8], signature: &[u8]) -> Result<bool, model::Error> {
+    use secp256k1::{Message, PublicKey, Secp256k1, Signature};
+
+    let invalid = |e: secp256k1::Error| model::Error::InvalidSignature(e.to_string());
+    let public_key = PublicKey::from_slice(public_key).map_err(invalid)?;
+    let signature = Signature::from_der(signature).map_err(invalid)?;
+    let digest = sha3::Sha3_256::digest(payload);
+    let message = Message::from_slice(digest.as_slice()).map_err(invalid)?;
+
+    Ok(Secp256k1::verification_only()
+        .verify(&message, &signature, &public_key)
+        .is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    fn sign(secret: &ethsign::SecretKey, payload: &[u8]) -> Vec<u8> {
+        let signature = secret.sign(payload).unwrap();
+        [&[signature.v][..], &signature.r[..], &signature.s[..]].concat()
+    }
+
+    fn identity() -> (ethsign::SecretKey, NodeId) {
+        let secret = ethsign::SecretKey::from_raw(&rand::thread_rng().gen::<[u8; 32]>()).unwrap();
+        let node_id = NodeId::from(secret.public().address().as_ref());
+        (secret, node_id)
+    }
+
+    #[test]
+    fn test_recover_node_id() {
+        let (secret, node_id) = identity();
+        let payload = [7u8; 32];
+        let signature = sign(&secret, &payload);
+
+        assert_eq!(recover(&payload, &signature).unwrap(), node_id);
+        assert!(verify(&model::Signer::NodeId(node_id), &payload, &signature).unwrap());
+
+        let (_, other) = identity();
+        assert!(!verify(&model::Signer::NodeId(other), &payload, &signature).unwrap());
+        assert!(!verify(&model::Signer::NodeId(node_id), &[8u8; 32], &signature).unwrap());
+        assert!(recover(&payload, &signature[1..]).is_err());
+    }
+
+    #[test]
+    fn test_verify_exe_unit_signature() {
+        use secp256k1::{Message, Secp256k1};
+
+        // Same steps as exe-unit `Crypto::sign`.
+        let ec = Secp256k1::new();
+        let (secret, public) = ec.generate_keypair(&mut rand::thread_rng());
+        let payload = br#"{"script":[],"results":[],"digest":"sha3"}"#;
+        let digest = sha3::Sha3_256::digest(payload);
+        let message = Message::from_slice(digest.as_slice()).unwrap();
+        let signature = ec.sign(&message, &secret).serialize_der().to_vec();
+
+        let signer = model::Signer::PublicKey(public.serialize().to_vec());
+        assert!(verify(&signer, payload, &signature).unwrap());
+        assert!(!verify(&signer, b"tampered", &signature).unwrap());
+        assert!(verify(&signer, payload, &signature[1..]).is_err());
+    }
+}
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE market_agreement_amendment_event;
+DROP TABLE market_agreement_amendment;
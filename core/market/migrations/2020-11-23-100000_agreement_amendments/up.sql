@@ -0,0 +1,37 @@
+-- Amendments proposed to already approved Agreements.
+
+CREATE TABLE market_agreement_amendment(
+    id VARCHAR(100) NOT NULL,
+    agreement_id VARCHAR(100) NOT NULL,
+    issuer VARCHAR(1) NOT NULL,
+
+    demand_properties TEXT,
+    offer_properties TEXT,
+
+    state VARCHAR(10) NOT NULL,
+    reason TEXT,
+
+    creation_ts DATETIME NOT NULL,
+    valid_to DATETIME NOT NULL,
+    decision_ts DATETIME,
+
+    PRIMARY KEY(id, agreement_id),
+    FOREIGN KEY(agreement_id) REFERENCES market_agreement (id),
+    CHECK (state in ('Pending', 'Approved', 'Rejected', 'Expired'))
+    CHECK (issuer in ('P', 'R'))
+);
+
+CREATE TABLE market_agreement_amendment_event(
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    amendment_id VARCHAR(100) NOT NULL,
+    agreement_id VARCHAR(100) NOT NULL,
+    event_type VARCHAR(10) NOT NULL,
+    timestamp DATETIME NOT NULL DEFAULT(STRFTIME('%Y-%m-%d %H:%M:%f', 'NOW')),
+    issuer VARCHAR(1) NOT NULL,
+    reason TEXT,
+
+    FOREIGN KEY(agreement_id) REFERENCES market_agreement (id),
+    UNIQUE(amendment_id, agreement_id, event_type)
+    CHECK (event_type in ('Proposed', 'Approved', 'Rejected'))
+    CHECK (issuer in ('P', 'R'))
+);
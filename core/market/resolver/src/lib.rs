@@ -6,7 +6,8 @@ pub mod resolver;
 
 use resolver::error::MatchError as InternalMatchErorr;
 
-use crate::resolver::properties::PropertyRef;
+use crate::resolver::expression::ResolveResult;
+use crate::resolver::properties::{PropertyRef, PropertySet};
 use flatten::{flatten_properties, FlattenError};
 use resolver::error::PrepareError;
 pub use resolver::matching::{match_weak, MatchResult};
@@ -60,6 +61,24 @@ pub fn match_demand_offer(
     }
 }
 
+/// Checks one-sided match: whether `properties` satisfy `constraints`.
+/// Unlike `match_demand_offer` it doesn't require constraints of the properties'
+/// owner to be fulfilled. Undefined result is treated as mismatch.
+pub fn match_constraints(properties: &str, constraints: &str) -> Result<bool, MatchError> {
+    let demand = Demand::from("{}", constraints)?;
+    let prep_demand = PreparedDemand::from(&demand)?;
+    let flat_props = flatten_properties(properties)?;
+    let property_set = PropertySet::from_flat_props(&flat_props);
+
+    match prep_demand.constraints.resolve(&property_set) {
+        ResolveResult::True => Ok(true),
+        ResolveResult::False(..) | ResolveResult::Undefined(..) => Ok(false),
+        ResolveResult::Err(e) => {
+            Err(InternalMatchErorr::new(&format!("Error resolving constraints: {}", e)).into())
+        }
+    }
+}
+
 fn extract_names(props_vec: &Vec<&PropertyRef>) -> Vec<String> {
     props_vec
         .iter()
@@ -0,0 +1,299 @@
+use std::str;
+
+use super::error::ExpressionError;
+use super::properties::{parse_prop_ref, Property, PropertyRef, PropertySet, PropertyValue};
+
+// Comparison operator used between two arithmetic expressions
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison {
+    Equal,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+impl Comparison {
+    pub fn from_operator(operator: &str) -> Result<Comparison, ExpressionError> {
+        match operator.trim() {
+            "=" => Ok(Comparison::Equal),
+            "<" => Ok(Comparison::Less),
+            "<=" => Ok(Comparison::LessEqual),
+            ">" => Ok(Comparison::Greater),
+            ">=" => Ok(Comparison::GreaterEqual),
+            _ => Err(ExpressionError::new(&format!(
+                "Unknown comparison operator {}",
+                operator
+            ))),
+        }
+    }
+
+    pub fn compare(&self, left: f64, right: f64) -> bool {
+        match self {
+            Comparison::Equal => left == right,
+            Comparison::Less => left < right,
+            Comparison::LessEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterEqual => left >= right,
+        }
+    }
+}
+
+// Arithmetic expression over numeric properties and number literals
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArithmeticExpr {
+    Number(f64),
+    Property(PropertyRef),
+    Add(Box<ArithmeticExpr>, Box<ArithmeticExpr>),
+    Sub(Box<ArithmeticExpr>, Box<ArithmeticExpr>),
+    Mul(Box<ArithmeticExpr>, Box<ArithmeticExpr>),
+    Div(Box<ArithmeticExpr>, Box<ArithmeticExpr>),
+}
+
+impl ArithmeticExpr {
+    // Parse arithmetic expression text, eg. "golem.com.price * (golem.srv.duration + 60)".
+    // Supported operators are +, -, * and / with usual precedence. Operands are either
+    // number literals or property references.
+    pub fn parse(input: &str) -> Result<ArithmeticExpr, ExpressionError> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+        };
+
+        let expr = parser.parse_sum()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(ExpressionError::new(&format!(
+                "Unexpected token {:?} in arithmetic expression '{}'",
+                token, input
+            ))),
+        }
+    }
+
+    // Evaluate expression value with given PropertySet.
+    // Returns list of property references which couldn't be resolved to numbers on failure.
+    pub fn evaluate<'a>(
+        &'a self,
+        property_set: &'a PropertySet,
+    ) -> Result<f64, Vec<&'a PropertyRef>> {
+        match self {
+            ArithmeticExpr::Number(value) => Ok(*value),
+            ArithmeticExpr::Property(prop_ref) => evaluate_property(prop_ref, property_set),
+            ArithmeticExpr::Add(left, right) => {
+                evaluate_pair(left, right, property_set).map(|(l, r)| l + r)
+            }
+            ArithmeticExpr::Sub(left, right) => {
+                evaluate_pair(left, right, property_set).map(|(l, r)| l - r)
+            }
+            ArithmeticExpr::Mul(left, right) => {
+                evaluate_pair(left, right, property_set).map(|(l, r)| l * r)
+            }
+            ArithmeticExpr::Div(left, right) => {
+                evaluate_pair(left, right, property_set).map(|(l, r)| l / r)
+            }
+        }
+    }
+
+    // Fetch all property references from the expression
+    pub fn property_refs(&self) -> Vec<&PropertyRef> {
+        match self {
+            ArithmeticExpr::Number(_) => vec![],
+            ArithmeticExpr::Property(prop_ref) => vec![prop_ref],
+            ArithmeticExpr::Add(left, right)
+            | ArithmeticExpr::Sub(left, right)
+            | ArithmeticExpr::Mul(left, right)
+            | ArithmeticExpr::Div(left, right) => left
+                .property_refs()
+                .into_iter()
+                .chain(right.property_refs())
+                .collect(),
+        }
+    }
+}
+
+fn evaluate_pair<'a>(
+    left: &'a ArithmeticExpr,
+    right: &'a ArithmeticExpr,
+    property_set: &'a PropertySet,
+) -> Result<(f64, f64), Vec<&'a PropertyRef>> {
+    match (left.evaluate(property_set), right.evaluate(property_set)) {
+        (Ok(l), Ok(r)) => Ok((l, r)),
+        (l, r) => {
+            let mut un_props = l.err().unwrap_or_default();
+            un_props.extend(r.err().unwrap_or_default());
+            Err(un_props)
+        }
+    }
+}
+
+fn evaluate_property<'a>(
+    prop_ref: &'a PropertyRef,
+    property_set: &'a PropertySet,
+) -> Result<f64, Vec<&'a PropertyRef>> {
+    // only property values can be used in arithmetic, aspects are not numeric
+    let name = match prop_ref {
+        PropertyRef::Value(name, _) => name,
+        PropertyRef::Aspect(..) => return Err(vec![prop_ref]),
+    };
+
+    match property_set.properties.get(&name[..]) {
+        Some(Property::Explicit(_name, value, _aspects)) => match value {
+            PropertyValue::Number(value) => Ok(*value),
+            PropertyValue::Decimal(value) => {
+                value.to_string().parse::<f64>().map_err(|_| vec![prop_ref])
+            }
+            _ => Err(vec![prop_ref]),
+        },
+        _ => Err(vec![prop_ref]),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token<'a> {
+    Number(f64),
+    Property(&'a str),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    LeftParen,
+    RightParen,
+}
+
+fn is_operator(chr: char) -> bool {
+    chr == '+' || chr == '-' || chr == '*' || chr == '/' || chr == '(' || chr == ')'
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, ExpressionError> {
+    let mut tokens = vec![];
+    let mut rest = input.trim_start();
+
+    while let Some(chr) = rest.chars().next() {
+        let token = match chr {
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '*' => Token::Star,
+            '/' => Token::Slash,
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            _ => {
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || is_operator(c))
+                    .unwrap_or(rest.len());
+                let word = &rest[..end];
+                rest = rest[end..].trim_start();
+
+                if chr.is_ascii_digit() || chr == '.' {
+                    tokens.push(Token::Number(word.parse::<f64>().map_err(|_| {
+                        ExpressionError::new(&format!("Error parsing number {}", word))
+                    })?));
+                } else {
+                    tokens.push(Token::Property(word));
+                }
+                continue;
+            }
+        };
+        tokens.push(token);
+        rest = rest[1..].trim_start();
+    }
+    Ok(tokens)
+}
+
+// Recursive descent parser for arithmetic expressions:
+// sum     := product (('+' | '-') product)*
+// product := unary (('*' | '/') unary)*
+// unary   := '-' unary | operand
+// operand := number | property | '(' sum ')'
+struct Parser<'a> {
+    tokens: &'a [Token<'a>],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token<'a>> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&'a Token<'a>> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn parse_sum(&mut self) -> Result<ArithmeticExpr, ExpressionError> {
+        let mut expr = self.parse_product()?;
+        loop {
+            match self.peek() {
+                Some(Token::Plus) => {
+                    self.next();
+                    expr = ArithmeticExpr::Add(Box::new(expr), Box::new(self.parse_product()?));
+                }
+                Some(Token::Minus) => {
+                    self.next();
+                    expr = ArithmeticExpr::Sub(Box::new(expr), Box::new(self.parse_product()?));
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    fn parse_product(&mut self) -> Result<ArithmeticExpr, ExpressionError> {
+        let mut expr = self.parse_unary()?;
+        loop {
+            match self.peek() {
+                Some(Token::Star) => {
+                    self.next();
+                    expr = ArithmeticExpr::Mul(Box::new(expr), Box::new(self.parse_unary()?));
+                }
+                Some(Token::Slash) => {
+                    self.next();
+                    expr = ArithmeticExpr::Div(Box::new(expr), Box::new(self.parse_unary()?));
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<ArithmeticExpr, ExpressionError> {
+        match self.peek() {
+            Some(Token::Minus) => {
+                self.next();
+                Ok(ArithmeticExpr::Sub(
+                    Box::new(ArithmeticExpr::Number(0.0)),
+                    Box::new(self.parse_unary()?),
+                ))
+            }
+            _ => self.parse_operand(),
+        }
+    }
+
+    fn parse_operand(&mut self) -> Result<ArithmeticExpr, ExpressionError> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(ArithmeticExpr::Number(*value)),
+            Some(Token::Property(name)) => match parse_prop_ref(name) {
+                Ok(prop_ref) => Ok(ArithmeticExpr::Property(prop_ref)),
+                Err(prop_err) => Err(ExpressionError::new(&format!(
+                    "Error parsing property reference {}: {}",
+                    name, prop_err
+                ))),
+            },
+            Some(Token::LeftParen) => {
+                let expr = self.parse_sum()?;
+                match self.next() {
+                    Some(Token::RightParen) => Ok(expr),
+                    _ => Err(ExpressionError::new(
+                        "Missing closing parenthesis in arithmetic expression",
+                    )),
+                }
+            }
+            Some(token) => Err(ExpressionError::new(&format!(
+                "Unexpected token {:?} in arithmetic expression",
+                token
+            ))),
+            None => Err(ExpressionError::new(
+                "Unexpected end of arithmetic expression",
+            )),
+        }
+    }
+}
@@ -1,10 +1,15 @@
 use std::str;
 
 use asnom::structures::{ExplicitTag, OctetString, Tag};
+use semver::VersionReq;
 
+use super::arithmetic::{ArithmeticExpr, Comparison};
 use super::error::{ExpressionError, ResolveError};
 use super::ldap_parser;
-use super::properties::{parse_prop_ref, Property, PropertyRef, PropertySet, PropertyValue};
+use super::prop_parser;
+use super::properties::{
+    parse_prop_ref, MatchRegex, Property, PropertyRef, PropertySet, PropertyValue,
+};
 
 // Expression resolution result enum
 #[derive(Debug, Clone, PartialEq)]
@@ -24,6 +29,13 @@ pub enum Expression {
     Less(PropertyRef, String),         // property ref, value
     LessEqual(PropertyRef, String),    // property ref, value
     Present(PropertyRef),              // property ref
+    Regex(PropertyRef, MatchRegex),    // property ref, compiled regular expression
+    InRange(PropertyRef, String),      // property ref, [min,max] list
+    In(PropertyRef, String),           // property ref, [item1,item2,...] list
+    ContainsAll(PropertyRef, String),  // property ref, [item1,item2,...] list
+    ContainsAny(PropertyRef, String),  // property ref, [item1,item2,...] list
+    VersionMatch(PropertyRef, String), // property ref, semver requirement
+    Compare(ArithmeticExpr, Comparison, ArithmeticExpr), // left operand, operator, right operand
     Or(Vec<Box<Expression>>),          // operands
     And(Vec<Box<Expression>>),         // operands
     Not(Box<Expression>),              // operand
@@ -71,7 +83,18 @@ impl Expression {
             | Expression::GreaterEqual(prop, _)
             | Expression::Less(prop, _)
             | Expression::LessEqual(prop, _)
+            | Expression::Regex(prop, _)
+            | Expression::InRange(prop, _)
+            | Expression::In(prop, _)
+            | Expression::ContainsAll(prop, _)
+            | Expression::ContainsAny(prop, _)
+            | Expression::VersionMatch(prop, _)
             | Expression::Present(prop) => vec![prop],
+            Expression::Compare(left, _, right) => left
+                .property_refs()
+                .into_iter()
+                .chain(right.property_refs())
+                .collect(),
             Expression::And(exprs) | Expression::Or(exprs) => {
                 exprs.iter().flat_map(|expr| expr.property_refs()).collect()
             }
@@ -133,6 +156,45 @@ impl Expression {
                 property_set,
                 |prop_value: &PropertyValue, val: &str| -> bool { prop_value.greater_equal(val) },
             ),
+            Expression::Regex(attr, regex) => self.resolve_with_function(
+                attr,
+                regex.as_str(),
+                property_set,
+                |prop_value: &PropertyValue, _: &str| -> bool { prop_value.matches_regex(regex) },
+            ),
+            Expression::InRange(attr, val) => self.resolve_with_function(
+                attr,
+                val,
+                property_set,
+                |prop_value: &PropertyValue, val: &str| -> bool { prop_value.in_range(val) },
+            ),
+            Expression::In(attr, val) => self.resolve_with_function(
+                attr,
+                val,
+                property_set,
+                |prop_value: &PropertyValue, val: &str| -> bool { prop_value.is_in(val) },
+            ),
+            Expression::ContainsAll(attr, val) => self.resolve_with_function(
+                attr,
+                val,
+                property_set,
+                |prop_value: &PropertyValue, val: &str| -> bool { prop_value.contains_all(val) },
+            ),
+            Expression::ContainsAny(attr, val) => self.resolve_with_function(
+                attr,
+                val,
+                property_set,
+                |prop_value: &PropertyValue, val: &str| -> bool { prop_value.contains_any(val) },
+            ),
+            Expression::VersionMatch(attr, val) => self.resolve_with_function(
+                attr,
+                val,
+                property_set,
+                |prop_value: &PropertyValue, val: &str| -> bool { prop_value.matches_version(val) },
+            ),
+            Expression::Compare(left, operator, right) => {
+                self.resolve_compare(left, operator, right, property_set)
+            }
             // other binary operators here if needed...
             Expression::And(inner_expressions) => self.resolve_and(inner_expressions, property_set),
             Expression::Or(inner_expressions) => self.resolve_or(inner_expressions, property_set),
@@ -343,6 +405,31 @@ impl Expression {
         }
     }
 
+    // Resolve comparison of arithmetic expressions.
+    // Undefined if any of referred properties is missing or isn't a number.
+    fn resolve_compare<'a>(
+        &'a self,
+        left: &'a ArithmeticExpr,
+        operator: &Comparison,
+        right: &'a ArithmeticExpr,
+        property_set: &'a PropertySet,
+    ) -> ResolveResult<'a> {
+        match (left.evaluate(property_set), right.evaluate(property_set)) {
+            (Ok(left_value), Ok(right_value)) => {
+                if operator.compare(left_value, right_value) {
+                    ResolveResult::True
+                } else {
+                    ResolveResult::False(vec![], Expression::Empty(false))
+                }
+            }
+            (left_result, right_result) => {
+                let mut un_props = left_result.err().unwrap_or_default();
+                un_props.extend(right_result.err().unwrap_or_default());
+                ResolveResult::Undefined(un_props, self.clone())
+            }
+        }
+    }
+
     // Resolve property/aspect presence
     fn resolve_present<'a>(
         &self,
@@ -393,6 +480,8 @@ pub fn build_expression(root: &Tag) -> Result<Expression, ExpressionError> {
             | ldap_parser::TAG_LESS_EQUAL
             | ldap_parser::TAG_GREATER
             | ldap_parser::TAG_GREATER_EQUAL => build_simple_expression(seq.id, &seq.inner),
+            ldap_parser::TAG_EXTENSIBLE => build_extensible_expression(&seq.inner),
+            ldap_parser::TAG_ARITHMETIC => build_arithmetic_expression(&seq.inner),
             _ => Err(ExpressionError::new(&format!(
                 "Unknown sequence type {}",
                 seq.id
@@ -506,6 +595,68 @@ fn build_simple_expression(
     }
 }
 
+fn build_extensible_expression(sequence: &Vec<Tag>) -> Result<Expression, ExpressionError> {
+    let (attr, rule, value) = extract_three_octet_strings(sequence)?;
+    let prop_ref = match parse_prop_ref(attr) {
+        Ok(prop_ref) => prop_ref,
+        Err(prop_err) => {
+            return Err(ExpressionError::new(&format!(
+                "Error parsing property reference {}: {}",
+                attr, prop_err
+            )))
+        }
+    };
+    let value = String::from(value);
+
+    match rule {
+        "regex" => match MatchRegex::new(&value) {
+            Ok(regex) => Ok(Expression::Regex(prop_ref, regex)),
+            Err(error) => Err(ExpressionError::new(&format!(
+                "Invalid regular expression {}: {}",
+                value, error
+            ))),
+        },
+        "range" => match prop_parser::parse_prop_ref_as_list(&value) {
+            Ok(bounds) if bounds.len() == 2 => Ok(Expression::InRange(prop_ref, value)),
+            _ => Err(ExpressionError::new(&format!(
+                "Invalid range {}, expected [min,max]",
+                value
+            ))),
+        },
+        "in" | "all" | "any" => match prop_parser::parse_prop_ref_as_list(&value) {
+            Ok(_) => Ok(match rule {
+                "in" => Expression::In(prop_ref, value),
+                "all" => Expression::ContainsAll(prop_ref, value),
+                _ => Expression::ContainsAny(prop_ref, value),
+            }),
+            Err(error) => Err(ExpressionError::new(&format!(
+                "Invalid list {}: {}",
+                value, error
+            ))),
+        },
+        "semver" => match VersionReq::parse(value.trim()) {
+            Ok(_) => Ok(Expression::VersionMatch(prop_ref, value)),
+            Err(error) => Err(ExpressionError::new(&format!(
+                "Invalid version requirement {}: {}",
+                value, error
+            ))),
+        },
+        _ => Err(ExpressionError::new(&format!(
+            "Unknown matching rule {}",
+            rule
+        ))),
+    }
+}
+
+fn build_arithmetic_expression(sequence: &Vec<Tag>) -> Result<Expression, ExpressionError> {
+    let (left, operator, right) = extract_three_octet_strings(sequence)?;
+    Ok(Expression::Compare(
+        ArithmeticExpr::parse(left)?,
+        Comparison::from_operator(operator)?,
+        ArithmeticExpr::parse(right)?,
+    ))
+}
+
 fn extract_str_from_octet_string<'a>(tag: &'a Tag) -> Result<&'a str, ExpressionError> {
     match tag {
         Tag::OctetString(oct) => match str::from_utf8(&oct.inner) {
@@ -548,4 +699,21 @@ fn extract_two_octet_strings<'a>(
     }
 }
 
+fn extract_three_octet_strings<'a>(
+    sequence: &'a Vec<Tag>,
+) -> Result<(&'a str, &'a str, &'a str), ExpressionError> {
+    if sequence.len() >= 3 {
+        Ok((
+            extract_str_from_octet_string(&sequence[0])?,
+            extract_str_from_octet_string(&sequence[1])?,
+            extract_str_from_octet_string(&sequence[2])?,
+        ))
+    } else {
+        Err(ExpressionError::new(&format!(
+            "Expected 3 tags, got {} tags",
+            sequence.len()
+        )))
+    }
+}
+
 // #endregion
@@ -1,6 +1,6 @@
 use std::default::Default;
 
-use nom::IResult;
+use nom::{IResult, Needed};
 
 use asnom::common::TagClass;
 use asnom::structures::{ExplicitTag, Null, OctetString, Sequence, Tag};
@@ -17,6 +17,8 @@ pub const TAG_GREATER: u64 = 8;
 pub const TAG_GREATER_EQUAL: u64 = 9;
 pub const TAG_LESS: u64 = 10;
 pub const TAG_LESS_EQUAL: u64 = 11;
+pub const TAG_EXTENSIBLE: u64 = 12;
+pub const TAG_ARITHMETIC: u64 = 13;
 
 // Parse function
 
@@ -33,7 +35,7 @@ named!(
     alt!(match_empty | ws!(delimited!(char!('('), content, char!(')'))))
 );
 named!(filterlist<Vec<Tag>>, many1!(filter));
-named!(content<Tag>, alt!(and | or | not | match_f));
+named!(content<Tag>, alt!(and | or | not | arithmetic | match_f));
 
 named!(
     and<Tag>,
@@ -83,7 +85,7 @@ named!(
     })
 );
 
-named!(match_f<Tag>, alt!(present | simple));
+named!(match_f<Tag>, alt!(present | extensible | simple));
 
 named!(
     present<Tag>,
@@ -121,6 +123,102 @@ named!(
     )
 );
 
+// Extensible match in the form of (<attr>:<rule>:=<value>), eg. (golem.inf.mem.gib:range:=[1,8])
+named!(
+    extensible<Tag>,
+    do_parse!(
+        attr: take_till!(is_extensible_delimiter)
+            >> char!(':')
+            >> rule: take_until!(":=")
+            >> tag!(":=")
+            >> value: extensible_value
+            >> (Tag::Sequence(Sequence {
+                class: TagClass::Context,
+                id: TAG_EXTENSIBLE,
+                inner: vec![
+                    Tag::OctetString(OctetString {
+                        inner: attr.to_vec(),
+                        ..Default::default()
+                    }),
+                    Tag::OctetString(OctetString {
+                        inner: rule.to_vec(),
+                        ..Default::default()
+                    }),
+                    Tag::OctetString(OctetString {
+                        inner: value.to_vec(),
+                        ..Default::default()
+                    })
+                ]
+            }))
+    )
+);
+
+// Value of extensible match is either double-quoted, where only `\"` is unescaped,
+// eg. (golem.node.id.name:regex:="^(prov|req)-\d+$"), or taken as is till closing
+// parenthesis, which can be escaped with backslash, eg. (golem.node.id.name:regex:=^\(a\)$).
+fn extensible_value(input: &[u8]) -> IResult<&[u8], Vec<u8>> {
+    if input.first() == Some(&b'"') {
+        let mut value = Vec::new();
+        let mut chars = input.iter().enumerate().skip(1);
+        while let Some((i, &chr)) = chars.next() {
+            match chr {
+                b'"' => return IResult::Done(&input[i + 1..], value),
+                b'\\' => match chars.next() {
+                    Some((_, b'"')) => value.push(b'"'),
+                    Some((_, &escaped)) => value.extend_from_slice(&[chr, escaped]),
+                    None => break,
+                },
+                _ => value.push(chr),
+            }
+        }
+    } else {
+        let mut escaped = false;
+        for (i, &chr) in input.iter().enumerate() {
+            match chr {
+                b')' if !escaped => return IResult::Done(&input[i..], input[..i].to_vec()),
+                b'\\' => escaped = !escaped,
+                _ => escaped = false,
+            }
+        }
+    }
+    IResult::Incomplete(Needed::Unknown)
+}
+
+// Comparison of arithmetic expressions in the form of ({<arithmetic>}<op><value>),
+// where value is either arithmetic expression in braces or a number,
+// eg. ({golem.com.price * golem.srv.duration}<{golem.com.budget})
+named!(
+    arithmetic<Tag>,
+    do_parse!(
+        left: ws!(braced)
+            >> operator: ws!(recognize!(filtertype))
+            >> right: alt!(ws!(braced) | take_until!(")"))
+            >> (Tag::Sequence(Sequence {
+                class: TagClass::Context,
+                id: TAG_ARITHMETIC,
+                inner: vec![
+                    Tag::OctetString(OctetString {
+                        inner: left.to_vec(),
+                        ..Default::default()
+                    }),
+                    Tag::OctetString(OctetString {
+                        inner: operator.to_vec(),
+                        ..Default::default()
+                    }),
+                    Tag::OctetString(OctetString {
+                        inner: right.to_vec(),
+                        ..Default::default()
+                    })
+                ]
+            }))
+    )
+);
+
+named!(
+    braced<&[u8]>,
+    delimited!(char!('{'), take_until!("}"), char!('}'))
+);
+
 //named!(filtertype <u64>, call!(equal));
 
 named!(
@@ -144,3 +242,7 @@ named!(
 pub fn is_delimiter(chr: u8) -> bool {
     chr == '=' as u8 || chr == '<' as u8 || chr == '>' as u8 || chr == '~' as u8
 }
+
+pub fn is_extensible_delimiter(chr: u8) -> bool {
+    is_delimiter(chr) || chr == ':' as u8
+}
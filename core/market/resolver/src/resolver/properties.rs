@@ -3,7 +3,7 @@ use std::str;
 use bigdecimal::BigDecimal;
 use chrono::{DateTime, Utc};
 use regex::Regex;
-use semver::Version;
+use semver::{Version, VersionReq};
 use std::collections::HashMap;
 
 use super::error::ParseError;
@@ -159,6 +159,86 @@ impl<'a> PropertyValue<'a> {
         }
     }
 
+    // Regular expression match. For List - true if any of the items matches.
+    pub fn matches_regex(&self, regex: &MatchRegex) -> bool {
+        match self {
+            PropertyValue::Str(value) => regex.0.is_match(value),
+            PropertyValue::List(items) => items.iter().any(|item| item.matches_regex(regex)),
+            _ => false, // operator meaningless for non-string types
+        }
+    }
+
+    // Range check, where val is [min,max] list (inclusive on both ends)
+    pub fn in_range(&self, val: &str) -> bool {
+        match prop_parser::parse_prop_ref_as_list(val) {
+            Ok(bounds) if bounds.len() == 2 => {
+                self.greater_equal(bounds[0].trim()) && self.less_equal(bounds[1].trim())
+            }
+            _ => false, // ignore parsing error, assume false
+        }
+    }
+
+    // Set membership, where val is [item1,item2,...] list.
+    // For List - true if all of the items belong to the set.
+    pub fn is_in(&self, val: &str) -> bool {
+        match prop_parser::parse_prop_ref_as_list(val) {
+            Ok(set) => match self {
+                PropertyValue::List(items) => items.iter().all(|item| item.is_in_set(&set)),
+                _ => self.is_in_set(&set),
+            },
+            _ => false, // ignore parsing error, assume false
+        }
+    }
+
+    // Set inclusion, where val is [item1,item2,...] list.
+    // For List - true if all of the set values are in the List.
+    pub fn contains_all(&self, val: &str) -> bool {
+        match prop_parser::parse_prop_ref_as_list(val) {
+            Ok(set) => set
+                .iter()
+                .all(|set_item| self.contains_item(set_item.trim())),
+            _ => false, // ignore parsing error, assume false
+        }
+    }
+
+    // Set intersection, where val is [item1,item2,...] list.
+    // For List - true if any of the set values is in the List.
+    pub fn contains_any(&self, val: &str) -> bool {
+        match prop_parser::parse_prop_ref_as_list(val) {
+            Ok(set) => set
+                .iter()
+                .any(|set_item| self.contains_item(set_item.trim())),
+            _ => false, // ignore parsing error, assume false
+        }
+    }
+
+    // Semantic version requirement match, eg. "^1.2", ">=0.4, <1.0"
+    pub fn matches_version(&self, val: &str) -> bool {
+        let requirement = match VersionReq::parse(val.trim()) {
+            Ok(requirement) => requirement,
+            _ => return false, // ignore parsing error, assume false
+        };
+        match self {
+            PropertyValue::Version(value) => requirement.matches(value),
+            PropertyValue::Str(value) => match Version::parse(value) {
+                Ok(parsed_value) => requirement.matches(&parsed_value),
+                _ => false,
+            }, // ignore parsing error, assume false
+            _ => false, // operator meaningless for other types
+        }
+    }
+
+    fn is_in_set(&self, set: &Vec<&str>) -> bool {
+        set.iter().any(|set_item| self.equals(set_item.trim()))
+    }
+
+    fn contains_item(&self, val: &str) -> bool {
+        match self {
+            PropertyValue::List(items) => items.iter().any(|item| item.equals(val)),
+            _ => self.equals(val),
+        }
+    }
+
     // Implement string equality with * wildcard
     // Note: Only str1 may contain wildcard
     // TODO my be sensible to move the Regex building to the point where property is parsed...
@@ -448,6 +528,26 @@ pub enum PropertyRefType {
     DateTime,
 }
 
+// Regular expression of `regex` matching rule, compiled once, when expression is built
+#[derive(Debug, Clone)]
+pub struct MatchRegex(Regex);
+
+impl MatchRegex {
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(MatchRegex(Regex::new(pattern)?))
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl PartialEq for MatchRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
 pub fn parse_prop_ref(flat_prop: &str) -> Result<PropertyRef, ParseError> {
     // TODO parse the flat_prop using prop_parser and repack to PropertyRef
     match prop_parser::parse_prop_ref_with_aspect(flat_prop) {
@@ -1,3 +1,4 @@
+use ya_market_resolver::resolver::arithmetic::*;
 use ya_market_resolver::resolver::expression::*;
 use ya_market_resolver::resolver::ldap_parser::parse;
 use ya_market_resolver::resolver::properties::*;
@@ -92,3 +93,56 @@ fn build_expression_and() {
 
     assert_eq!(build_expression(&parse(f).unwrap()), Ok(expression));
 }
+
+#[test]
+fn build_expression_extensible() {
+    let f = "(golem.inf.mem.gib:range:=[1,8])";
+
+    let expression = Expression::InRange(
+        PropertyRef::Value(String::from("golem.inf.mem.gib"), PropertyRefType::Any),
+        String::from("[1,8]"),
+    );
+
+    assert_eq!(build_expression(&parse(f).unwrap()), Ok(expression));
+}
+
+#[test]
+fn build_expression_extensible_errors() {
+    assert!(build_expression(&parse("(a:unknown:=b)").unwrap()).is_err());
+    assert!(build_expression(&parse("(a:regex:=[a-)").unwrap()).is_err());
+    assert!(build_expression(&parse("(a:range:=[1,2,3])").unwrap()).is_err());
+    assert!(build_expression(&parse("(a:in:=abc)").unwrap()).is_err());
+    assert!(build_expression(&parse("(a:semver:=not-a-version)").unwrap()).is_err());
+}
+
+#[test]
+fn build_expression_arithmetic() {
+    let f = "({a * b + 1} <= 10)";
+
+    let expression = Expression::Compare(
+        ArithmeticExpr::Add(
+            Box::new(ArithmeticExpr::Mul(
+                Box::new(ArithmeticExpr::Property(PropertyRef::Value(
+                    String::from("a"),
+                    PropertyRefType::Any,
+                ))),
+                Box::new(ArithmeticExpr::Property(PropertyRef::Value(
+                    String::from("b"),
+                    PropertyRefType::Any,
+                ))),
+            )),
+            Box::new(ArithmeticExpr::Number(1.0)),
+        ),
+        Comparison::LessEqual,
+        ArithmeticExpr::Number(10.0),
+    );
+
+    assert_eq!(build_expression(&parse(f).unwrap()), Ok(expression));
+}
+
+#[test]
+fn build_expression_arithmetic_errors() {
+    assert!(build_expression(&parse("({a * } < 10)").unwrap()).is_err());
+    assert!(build_expression(&parse("({(a + b} < 10)").unwrap()).is_err());
+    assert!(build_expression(&parse("({a b} < 10)").unwrap()).is_err());
+}
@@ -662,3 +662,309 @@ fn resolve_complex_and_undefined_reduced() {
         ResolveResult::False(vec![], Expression::Empty(false)),
     );
 }
+
+#[test]
+fn resolve_regex() {
+    let f = "(golem.node.id.name:regex:=^prov-[0-9]+$)";
+
+    // test positive
+
+    run_resolve_test(
+        f,
+        &vec![r#"golem.node.id.name="prov-12""#],
+        ResolveResult::True,
+    );
+
+    // test negative
+
+    run_resolve_test(
+        f,
+        &vec![r#"golem.node.id.name="req-12""#],
+        ResolveResult::False(vec![], Expression::Empty(false)),
+    );
+
+    // test list - any item should match
+
+    run_resolve_test(
+        f,
+        &vec![r#"golem.node.id.name=["req-1", "prov-2"]"#],
+        ResolveResult::True,
+    );
+
+    // test undefined
+
+    run_resolve_test(
+        f,
+        &vec![r#"golem.node.id.alias="prov-12""#],
+        ResolveResult::Undefined(
+            vec![&PropertyRef::Value(
+                String::from("golem.node.id.name"),
+                PropertyRefType::Any,
+            )],
+            Expression::Regex(
+                PropertyRef::Value(String::from("golem.node.id.name"), PropertyRefType::Any),
+                MatchRegex::new("^prov-[0-9]+$").unwrap(),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn resolve_regex_with_parentheses() {
+    let f =
+        r#"(&(golem.node.id.name:regex:="^(prov|req)-[0-9]+$")(golem.inf.mem.gib:regex:=^\(8\)$))"#;
+
+    run_resolve_test(
+        f,
+        &vec![
+            r#"golem.node.id.name="req-12""#,
+            r#"golem.inf.mem.gib="(8)""#,
+        ],
+        ResolveResult::True,
+    );
+    run_resolve_test(
+        f,
+        &vec![
+            r#"golem.node.id.name="other-12""#,
+            r#"golem.inf.mem.gib="(8)""#,
+        ],
+        ResolveResult::False(vec![], Expression::Empty(false)),
+    );
+}
+
+#[test]
+fn resolve_range() {
+    let f = "(golem.inf.mem.gib:range:=[1,8])";
+
+    // test positive (range is inclusive)
+
+    run_resolve_test(f, &vec!["golem.inf.mem.gib=1"], ResolveResult::True);
+    run_resolve_test(f, &vec!["golem.inf.mem.gib=4.5"], ResolveResult::True);
+    run_resolve_test(f, &vec!["golem.inf.mem.gib=8"], ResolveResult::True);
+
+    // test negative
+
+    run_resolve_test(
+        f,
+        &vec!["golem.inf.mem.gib=0.5"],
+        ResolveResult::False(vec![], Expression::Empty(false)),
+    );
+    run_resolve_test(
+        f,
+        &vec!["golem.inf.mem.gib=16"],
+        ResolveResult::False(vec![], Expression::Empty(false)),
+    );
+}
+
+#[test]
+fn resolve_range_version() {
+    let f = "(golem.runtime.version$v:range:=[0.1.0,0.3.0])";
+
+    run_resolve_test(
+        f,
+        &vec![r#"golem.runtime.version="0.2.1""#],
+        ResolveResult::True,
+    );
+    run_resolve_test(
+        f,
+        &vec![r#"golem.runtime.version="1.0.0""#],
+        ResolveResult::False(vec![], Expression::Empty(false)),
+    );
+}
+
+#[test]
+fn resolve_in() {
+    let f = "(golem.runtime.name:in:=[wasmtime,vm])";
+
+    // test positive
+
+    run_resolve_test(f, &vec![r#"golem.runtime.name="vm""#], ResolveResult::True);
+
+    // test negative
+
+    run_resolve_test(
+        f,
+        &vec![r#"golem.runtime.name="emscripten""#],
+        ResolveResult::False(vec![], Expression::Empty(false)),
+    );
+
+    // test list - all items must belong to set
+
+    run_resolve_test(
+        f,
+        &vec![r#"golem.runtime.name=["vm", "wasmtime"]"#],
+        ResolveResult::True,
+    );
+    run_resolve_test(
+        f,
+        &vec![r#"golem.runtime.name=["vm", "emscripten"]"#],
+        ResolveResult::False(vec![], Expression::Empty(false)),
+    );
+}
+
+#[test]
+fn resolve_contains_all() {
+    let f = "(golem.runtime.capabilities:all:=[vpn, gpu])";
+
+    // test positive
+
+    run_resolve_test(
+        f,
+        &vec![r#"golem.runtime.capabilities=["gpu", "sgx", "vpn"]"#],
+        ResolveResult::True,
+    );
+
+    // test negative
+
+    run_resolve_test(
+        f,
+        &vec![r#"golem.runtime.capabilities=["gpu", "sgx"]"#],
+        ResolveResult::False(vec![], Expression::Empty(false)),
+    );
+}
+
+#[test]
+fn resolve_contains_any() {
+    let f = "(golem.runtime.capabilities:any:=[vpn, gpu])";
+
+    // test positive
+
+    run_resolve_test(
+        f,
+        &vec![r#"golem.runtime.capabilities=["sgx", "vpn"]"#],
+        ResolveResult::True,
+    );
+
+    // test negative
+
+    run_resolve_test(
+        f,
+        &vec![r#"golem.runtime.capabilities=["sgx"]"#],
+        ResolveResult::False(vec![], Expression::Empty(false)),
+    );
+}
+
+#[test]
+fn resolve_semver() {
+    let f = "(golem.runtime.version:semver:=>=0.2.0, <0.4.0)";
+
+    // test positive
+
+    run_resolve_test(
+        f,
+        &vec![r#"golem.runtime.version=v"0.3.1""#],
+        ResolveResult::True,
+    );
+    run_resolve_test(
+        f,
+        &vec![r#"golem.runtime.version="0.2.0""#],
+        ResolveResult::True,
+    );
+
+    // test negative
+
+    run_resolve_test(
+        f,
+        &vec![r#"golem.runtime.version=v"0.4.0""#],
+        ResolveResult::False(vec![], Expression::Empty(false)),
+    );
+
+    // test caret requirement
+
+    run_resolve_test(
+        "(golem.runtime.version:semver:=^1.2)",
+        &vec![r#"golem.runtime.version=v"1.9.0""#],
+        ResolveResult::True,
+    );
+}
+
+#[test]
+fn resolve_arithmetic_compare() {
+    let f = "({golem.com.price * golem.srv.duration}<{golem.com.budget})";
+
+    // test positive
+
+    run_resolve_test(
+        f,
+        &vec![
+            "golem.com.price=0.5",
+            "golem.srv.duration=100",
+            "golem.com.budget=60",
+        ],
+        ResolveResult::True,
+    );
+
+    // test negative
+
+    run_resolve_test(
+        f,
+        &vec![
+            "golem.com.price=0.5",
+            "golem.srv.duration=100",
+            "golem.com.budget=40",
+        ],
+        ResolveResult::False(vec![], Expression::Empty(false)),
+    );
+}
+
+#[test]
+fn resolve_arithmetic_compare_with_literal() {
+    let f = "({(golem.inf.mem.gib + 2) * 2 - golem.inf.cpu.cores / 4}>=10)";
+
+    run_resolve_test(
+        f,
+        &vec!["golem.inf.mem.gib=4", "golem.inf.cpu.cores=8"],
+        ResolveResult::True,
+    );
+    run_resolve_test(
+        f,
+        &vec!["golem.inf.mem.gib=2", "golem.inf.cpu.cores=8"],
+        ResolveResult::False(vec![], Expression::Empty(false)),
+    );
+}
+
+#[test]
+fn resolve_arithmetic_compare_undefined() {
+    let f = "({a * b}<{c})";
+
+    // missing and non-numeric properties can't be resolved
+
+    let expression = build_expression(&parse(f).unwrap()).unwrap();
+    let properties = vec![String::from("a=2"), String::from(r#"b="x""#)];
+    let property_set = PropertySet::from_flat_props(&properties);
+
+    assert_eq!(
+        expression.resolve(&property_set),
+        ResolveResult::Undefined(
+            vec![
+                &PropertyRef::Value(String::from("b"), PropertyRefType::Any),
+                &PropertyRef::Value(String::from("c"), PropertyRefType::Any),
+            ],
+            expression.clone(),
+        )
+    );
+}
+
+#[test]
+fn resolve_extended_operators_in_complex_expression() {
+    let f = "(&(golem.runtime.name:in:=[vm,wasmtime])(!(golem.node.id.name:regex:=^test))({golem.inf.mem.gib * 2}<=16))";
+
+    run_resolve_test(
+        f,
+        &vec![
+            r#"golem.runtime.name="vm""#,
+            r#"golem.node.id.name="prov-1""#,
+            "golem.inf.mem.gib=8",
+        ],
+        ResolveResult::True,
+    );
+    run_resolve_test(
+        f,
+        &vec![
+            r#"golem.runtime.name="vm""#,
+            r#"golem.node.id.name="test-1""#,
+            "golem.inf.mem.gib=8",
+        ],
+        ResolveResult::False(vec![], Expression::Empty(false)),
+    );
+}
@@ -302,3 +302,129 @@ fn and_whitespace() {
 
     assert_eq!(parse(f), Ok(tag));
 }
+
+#[test]
+fn extensible() {
+    let f = "(cn:regex:=^Babs)";
+
+    let tag = Tag::Sequence(Sequence {
+        class: TagClass::Context,
+        id: TAG_EXTENSIBLE,
+        inner: vec![
+            Tag::OctetString(OctetString {
+                inner: b"cn".to_vec(),
+                ..Default::default()
+            }),
+            Tag::OctetString(OctetString {
+                inner: b"regex".to_vec(),
+                ..Default::default()
+            }),
+            Tag::OctetString(OctetString {
+                inner: b"^Babs".to_vec(),
+                ..Default::default()
+            }),
+        ],
+    });
+
+    assert_eq!(parse(f), Ok(tag));
+}
+
+fn extensible_tag(attr: &str, rule: &str, value: &str) -> Tag {
+    Tag::Sequence(Sequence {
+        class: TagClass::Context,
+        id: TAG_EXTENSIBLE,
+        inner: vec![attr, rule, value]
+            .into_iter()
+            .map(|s| {
+                Tag::OctetString(OctetString {
+                    inner: s.as_bytes().to_vec(),
+                    ..Default::default()
+                })
+            })
+            .collect(),
+    })
+}
+
+#[test]
+fn extensible_quoted() {
+    assert_eq!(
+        parse(r#"(cn:regex:="^(Babs|Tim) J\w+$")"#),
+        Ok(extensible_tag("cn", "regex", r#"^(Babs|Tim) J\w+$"#))
+    );
+    assert_eq!(
+        parse(r#"(cn:regex:="^\"(a)\"$")"#),
+        Ok(extensible_tag("cn", "regex", r#"^"(a)"$"#))
+    );
+    assert_eq!(
+        parse(r#"(&(cn:regex:="(a))")(sn=b))"#).map(|tag| match tag {
+            Tag::Sequence(seq) => seq.inner.len(),
+            _ => 0,
+        }),
+        Ok(2)
+    );
+    assert!(parse(r#"(cn:regex:="(a))"#).is_err());
+}
+
+#[test]
+fn extensible_escaped() {
+    assert_eq!(
+        parse(r#"(cn:regex:=^\(a\)$)"#),
+        Ok(extensible_tag("cn", "regex", r#"^\(a\)$"#))
+    );
+    assert_eq!(
+        parse(r#"(cn:regex:=a\\)"#),
+        Ok(extensible_tag("cn", "regex", r#"a\\"#))
+    );
+}
+
+#[test]
+fn arithmetic() {
+    let f = "({a * b} <= {c + 1})";
+
+    let tag = Tag::Sequence(Sequence {
+        class: TagClass::Context,
+        id: TAG_ARITHMETIC,
+        inner: vec![
+            Tag::OctetString(OctetString {
+                inner: b"a * b".to_vec(),
+                ..Default::default()
+            }),
+            Tag::OctetString(OctetString {
+                inner: b"<=".to_vec(),
+                ..Default::default()
+            }),
+            Tag::OctetString(OctetString {
+                inner: b"c + 1".to_vec(),
+                ..Default::default()
+            }),
+        ],
+    });
+
+    assert_eq!(parse(f), Ok(tag));
+}
+
+#[test]
+fn arithmetic_number_operand() {
+    let f = "({a * b}>10)";
+
+    let tag = Tag::Sequence(Sequence {
+        class: TagClass::Context,
+        id: TAG_ARITHMETIC,
+        inner: vec![
+            Tag::OctetString(OctetString {
+                inner: b"a * b".to_vec(),
+                ..Default::default()
+            }),
+            Tag::OctetString(OctetString {
+                inner: b">".to_vec(),
+                ..Default::default()
+            }),
+            Tag::OctetString(OctetString {
+                inner: b"10".to_vec(),
+                ..Default::default()
+            }),
+        ],
+    });
+
+    assert_eq!(parse(f), Ok(tag));
+}
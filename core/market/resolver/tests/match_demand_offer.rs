@@ -1,4 +1,4 @@
-use ya_market_resolver::{match_demand_offer, Match, MatchError};
+use ya_market_resolver::{match_constraints, match_demand_offer, Match, MatchError};
 
 mod sample;
 
@@ -119,3 +119,32 @@ fn match_poc_offer_demand_samples_deep() {
         Match::Yes
     );
 }
+
+#[test]
+fn match_constraints_ignores_offer_constraints() {
+    assert_eq!(
+        match_constraints(POC_OFFER_PROPERTIES_JSON, POC_DEMAND_CONSTRAINTS).unwrap(),
+        true
+    );
+}
+
+#[test]
+fn match_constraints_wrong_property_value_should_not_match() {
+    assert_eq!(
+        match_constraints("{\"foo\": \"bar\"}", "(foo=baz)").unwrap(),
+        false
+    );
+}
+
+#[test]
+fn match_constraints_undefined_should_not_match() {
+    assert_eq!(match_constraints("{}", "(foo=bar)").unwrap(), false);
+}
+
+#[test]
+fn match_constraints_invalid_constraints_should_fail() {
+    match match_constraints("{}", "werwer(foo=bar)").unwrap_err() {
+        MatchError::PrepareError(_) => (),
+        e => panic!("Prepare error expected, but got: {}", e),
+    }
+}
@@ -1,29 +1,447 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use std::time::Duration;
+use structopt::StructOpt;
+use thiserror::Error;
 
-/// TODO: Decide where should this config be loaded from.
-///  We could deserialize it from .json file or use structopt and
-///  configure market through env variables.
-#[derive(Default)]
+use ya_core_model::market::MarketSettings;
+
+/// Agreements are needed to settle payments, so they are never removed earlier.
+pub const MIN_AGREEMENTS_RETENTION_DAYS: i64 = 30;
+
+/// Replaced by `YAGNA_MARKET_AGREEMENTS_RETENTION`.
+const DEPRECATED_AGREEMENT_STORE_DAYS: &str = "YAGNA_MARKET_AGREEMENT_STORE_DAYS";
+/// Replaced by `YAGNA_MARKET_NEGOTIATION_EVENTS_RETENTION`.
+const DEPRECATED_EVENT_STORE_DAYS: &str = "YAGNA_MARKET_EVENT_STORE_DAYS";
+
+/// Market configuration. Values are taken from command line arguments or
+/// environment variables of `yagna service run`. Optionally they can be
+/// overridden by json file (see `MarketConfigOpts`).
+#[derive(StructOpt, Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
 pub struct Config {
+    #[structopt(flatten)]
     pub discovery: DiscoveryConfig,
+    #[structopt(flatten)]
     pub subscription: SubscriptionConfig,
+    #[structopt(flatten)]
     pub events: EventsConfig,
+    #[structopt(flatten)]
+    pub scan: ScanConfig,
+    #[structopt(flatten)]
+    pub retention: RetentionConfig,
 }
 
+#[derive(StructOpt, Serialize, Deserialize, Clone, Debug)]
+#[serde(default, rename_all = "camelCase")]
 pub struct DiscoveryConfig {
+    /// Maximum number of Offers broadcasted in single cyclic broadcast
+    #[structopt(
+        long = "market-max-bcasted-offers",
+        env = "YAGNA_MARKET_MAX_BCASTED_OFFERS",
+        default_value = "200"
+    )]
     pub max_bcasted_offers: u32,
+    /// Maximum number of Offer unsubscribes broadcasted in single cyclic broadcast
+    #[structopt(
+        long = "market-max-bcasted-unsubscribes",
+        env = "YAGNA_MARKET_MAX_BCASTED_UNSUBSCRIBES",
+        default_value = "200"
+    )]
     pub max_bcasted_unsubscribes: u32,
+    /// Mean interval between cyclic Offers broadcasts
+    #[structopt(
+        long = "market-mean-cyclic-bcast-interval",
+        env = "YAGNA_MARKET_MEAN_CYCLIC_BCAST_INTERVAL",
+        parse(try_from_str = humantime::parse_duration),
+        default_value = "60s"
+    )]
+    #[serde(with = "humantime_serde")]
     pub mean_cyclic_bcast_interval: Duration,
+    /// Mean interval between cyclic Offer unsubscribes broadcasts
+    #[structopt(
+        long = "market-mean-cyclic-unsubscribes-interval",
+        env = "YAGNA_MARKET_MEAN_CYCLIC_UNSUBSCRIBES_INTERVAL",
+        parse(try_from_str = humantime::parse_duration),
+        default_value = "60s"
+    )]
+    #[serde(with = "humantime_serde")]
     pub mean_cyclic_unsubscribes_interval: Duration,
 }
 
+#[derive(StructOpt, Serialize, Deserialize, Clone, Debug)]
+#[serde(default, rename_all = "camelCase")]
 pub struct SubscriptionConfig {
+    /// Time to live of Offers and Demands
+    #[structopt(
+        long = "market-subscription-ttl",
+        env = "YAGNA_MARKET_SUBSCRIPTION_TTL",
+        parse(try_from_str = parse_chrono_duration),
+        default_value = "1h"
+    )]
+    #[serde(with = "humantime_serde::chrono_duration")]
     pub default_ttl: chrono::Duration,
 }
 
+#[derive(StructOpt, Serialize, Deserialize, Clone, Debug)]
+#[serde(default, rename_all = "camelCase")]
 pub struct EventsConfig {
+    /// Number of events returned by single events query, if not specified by caller
+    #[structopt(
+        long = "market-max-events-default",
+        env = "YAGNA_MARKET_MAX_EVENTS_DEFAULT",
+        default_value = "20"
+    )]
     pub max_events_default: i32,
+    /// Maximum number of events, that can be returned by single events query
+    #[structopt(
+        long = "market-max-events-max",
+        env = "YAGNA_MARKET_MAX_EVENTS_MAX",
+        default_value = "100"
+    )]
     pub max_events_max: i32,
+    /// Interval of keep-alive messages sent on idle event streams
+    #[structopt(
+        long = "market-events-stream-heartbeat",
+        env = "YAGNA_MARKET_EVENTS_STREAM_HEARTBEAT",
+        parse(try_from_str = humantime::parse_duration),
+        default_value = "15s"
+    )]
+    #[serde(with = "humantime_serde")]
+    pub stream_heartbeat: Duration,
+}
+
+#[derive(StructOpt, Serialize, Deserialize, Clone, Debug)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ScanConfig {
+    /// Number of Offers returned by single scan query, if not specified by caller
+    #[structopt(
+        long = "market-max-offers-default",
+        env = "YAGNA_MARKET_MAX_OFFERS_DEFAULT",
+        default_value = "50"
+    )]
+    pub max_offers_default: i32,
+    /// Maximum number of Offers, that can be returned by single scan query
+    #[structopt(
+        long = "market-max-offers-max",
+        env = "YAGNA_MARKET_MAX_OFFERS_MAX",
+        default_value = "500"
+    )]
+    pub max_offers_max: i32,
+}
+
+/// How long outdated entries are kept in market database.
+#[derive(StructOpt, Serialize, Deserialize, Clone, Debug)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RetentionConfig {
+    /// Interval between market database cleanups
+    #[structopt(
+        long = "market-cleaner-interval",
+        env = "YAGNA_MARKET_CLEANER_INTERVAL",
+        parse(try_from_str = humantime::parse_duration),
+        default_value = "1day"
+    )]
+    #[serde(with = "humantime_serde")]
+    pub cleaner_interval: Duration,
+    /// Time of keeping Offers after their expiration
+    #[structopt(
+        long = "market-offers-retention",
+        env = "YAGNA_MARKET_OFFERS_RETENTION",
+        parse(try_from_str = parse_chrono_duration),
+        default_value = "0s"
+    )]
+    #[serde(with = "humantime_serde::chrono_duration")]
+    pub offers: chrono::Duration,
+    /// Time of keeping Demands after their expiration
+    #[structopt(
+        long = "market-demands-retention",
+        env = "YAGNA_MARKET_DEMANDS_RETENTION",
+        parse(try_from_str = parse_chrono_duration),
+        default_value = "0s"
+    )]
+    #[serde(with = "humantime_serde::chrono_duration")]
+    pub demands: chrono::Duration,
+    /// Time of keeping negotiations after all their Proposals expired
+    #[structopt(
+        long = "market-proposals-retention",
+        env = "YAGNA_MARKET_PROPOSALS_RETENTION",
+        parse(try_from_str = parse_chrono_duration),
+        default_value = "0s"
+    )]
+    #[serde(with = "humantime_serde::chrono_duration")]
+    pub proposals: chrono::Duration,
+    /// Time of keeping negotiation events
+    #[structopt(
+        long = "market-negotiation-events-retention",
+        env = "YAGNA_MARKET_NEGOTIATION_EVENTS_RETENTION",
+        parse(try_from_str = parse_chrono_duration),
+        default_value = "1day"
+    )]
+    #[serde(with = "humantime_serde::chrono_duration")]
+    pub negotiation_events: chrono::Duration,
+    /// Time of keeping Agreements after end of their validity period
+    #[structopt(
+        long = "market-agreements-retention",
+        env = "YAGNA_MARKET_AGREEMENTS_RETENTION",
+        parse(try_from_str = parse_chrono_duration),
+        default_value = "90days"
+    )]
+    #[serde(with = "humantime_serde::chrono_duration")]
+    pub agreements: chrono::Duration,
+    /// Directory, where Agreements are exported as json before removing them from database
+    #[structopt(
+        long = "market-agreements-archive-dir",
+        env = "YAGNA_MARKET_AGREEMENTS_ARCHIVE_DIR"
+    )]
+    pub agreements_archive_dir: Option<PathBuf>,
+}
+
+/// Market options for `yagna service run`.
+#[derive(StructOpt, Clone, Debug)]
+pub struct MarketConfigOpts {
+    /// Json file with market settings. Values found in file take
+    /// precedence over command line arguments and environment variables.
+    #[structopt(long, env = "YAGNA_MARKET_CONFIG")]
+    pub market_config: Option<PathBuf>,
+
+    #[structopt(flatten)]
+    pub config: Config,
+}
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read market config file [{0}]. Error: {1}.")]
+    Read(PathBuf, std::io::Error),
+    #[error("Failed to parse market config file [{0}]. Error: {1}.")]
+    Parse(PathBuf, serde_json::Error),
+    #[error("Invalid market config: {0}.")]
+    Invalid(String),
+}
+
+impl MarketConfigOpts {
+    /// Builds validated market `Config`.
+    pub fn load(&self) -> Result<Config, ConfigError> {
+        let mut base = self.config.clone();
+        base.retention.apply_deprecated_env()?;
+
+        let mut config = match &self.market_config {
+            Some(path) => Config::from_file(path, &base)?,
+            None => base,
+        };
+        config.retention.enforce_min_agreements();
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+impl Config {
+    /// Loads config from json file. Settings missing in file are taken from `base`.
+    pub fn from_file(path: &Path, base: &Config) -> Result<Config, ConfigError> {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| ConfigError::Read(path.to_owned(), e))?;
+        let overrides: serde_json::Value =
+            serde_json::from_str(&content).map_err(|e| ConfigError::Parse(path.to_owned(), e))?;
+
+        let mut config =
+            serde_json::to_value(base).map_err(|e| ConfigError::Parse(path.to_owned(), e))?;
+        merge_json(&mut config, overrides);
+
+        serde_json::from_value(config).map_err(|e| ConfigError::Parse(path.to_owned(), e))
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.discovery.validate()?;
+        self.subscription.validate()?;
+        self.events.validate()?;
+        validate_interval("market cleaner interval", self.retention.cleaner_interval)?;
+        validate_page_size(
+            "scanned Offers",
+            self.scan.max_offers_default,
+            self.scan.max_offers_max,
+        )
+    }
+
+    pub fn to_settings(&self) -> MarketSettings {
+        MarketSettings {
+            max_bcasted_offers: self.discovery.max_bcasted_offers,
+            max_bcasted_unsubscribes: self.discovery.max_bcasted_unsubscribes,
+            mean_cyclic_bcast_interval_ms: self.discovery.mean_cyclic_bcast_interval.as_millis()
+                as u64,
+            mean_cyclic_unsubscribes_interval_ms: self
+                .discovery
+                .mean_cyclic_unsubscribes_interval
+                .as_millis() as u64,
+            subscription_ttl_secs: self.subscription.default_ttl.num_seconds(),
+            max_events_default: self.events.max_events_default,
+            max_events_max: self.events.max_events_max,
+            max_offers_default: self.scan.max_offers_default,
+            max_offers_max: self.scan.max_offers_max,
+        }
+    }
+}
+
+impl DiscoveryConfig {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        validate_interval(
+            "mean cyclic broadcast interval",
+            self.mean_cyclic_bcast_interval,
+        )?;
+        validate_interval(
+            "mean cyclic unsubscribes interval",
+            self.mean_cyclic_unsubscribes_interval,
+        )
+    }
+}
+
+impl EventsConfig {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        validate_page_size("events", self.max_events_default, self.max_events_max)?;
+        validate_interval("events stream heartbeat", self.stream_heartbeat)
+    }
+}
+
+impl RetentionConfig {
+    /// Takes settings from environment variables used by previous versions,
+    /// unless their replacements are set.
+    pub fn apply_deprecated_env(&mut self) -> Result<(), ConfigError> {
+        if let Some(days) = deprecated_days(
+            DEPRECATED_AGREEMENT_STORE_DAYS,
+            "YAGNA_MARKET_AGREEMENTS_RETENTION",
+        )? {
+            self.agreements = chrono::Duration::days(days);
+        }
+        if let Some(days) = deprecated_days(
+            DEPRECATED_EVENT_STORE_DAYS,
+            "YAGNA_MARKET_NEGOTIATION_EVENTS_RETENTION",
+        )? {
+            self.negotiation_events = chrono::Duration::days(days);
+        }
+        Ok(())
+    }
+
+    pub fn enforce_min_agreements(&mut self) {
+        let min = chrono::Duration::days(MIN_AGREEMENTS_RETENTION_DAYS);
+        if self.agreements < min {
+            log::warn!(
+                "Agreements retention {} is lower than minimum {} days. Using minimum.",
+                self.agreements,
+                MIN_AGREEMENTS_RETENTION_DAYS
+            );
+            self.agreements = min;
+        }
+    }
+}
+
+fn deprecated_days(name: &str, replacement: &str) -> Result<Option<i64>, ConfigError> {
+    let value = match std::env::var(name) {
+        Ok(value) => value,
+        Err(_) => return Ok(None),
+    };
+    if std::env::var(replacement).is_ok() {
+        log::warn!(
+            "{} is deprecated and ignored, because {} is set.",
+            name,
+            replacement
+        );
+        return Ok(None);
+    }
+
+    let days = value
+        .trim()
+        .parse::<u32>()
+        .map_err(|e| ConfigError::Invalid(format!("{}={}: {}", name, value, e)))?;
+    log::warn!(
+        "{} is deprecated. Use {}={}days instead.",
+        name,
+        replacement,
+        days
+    );
+    Ok(Some(days as i64))
+}
+
+impl SubscriptionConfig {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.default_ttl <= chrono::Duration::zero() {
+            return Err(ConfigError::Invalid(format!(
+                "subscription ttl must be positive, got {}",
+                self.default_ttl
+            )));
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn validate_interval(name: &str, interval: Duration) -> Result<(), ConfigError> {
+    if interval == Duration::from_secs(0) {
+        return Err(ConfigError::Invalid(format!("{} can't be zero", name)));
+    }
+    Ok(())
+}
+
+fn validate_page_size(name: &str, default: i32, max: i32) -> Result<(), ConfigError> {
+    if max <= 0 {
+        return Err(ConfigError::Invalid(format!(
+            "maximum number of {} must be positive, got {}",
+            name, max
+        )));
+    }
+    if default <= 0 || default > max {
+        return Err(ConfigError::Invalid(format!(
+            "default number of {} must be in range [1, {}], got {}",
+            name, max, default
+        )));
+    }
+    Ok(())
+}
+
+fn parse_chrono_duration(s: &str) -> Result<chrono::Duration, anyhow::Error> {
+    Ok(chrono::Duration::from_std(humantime::parse_duration(s)?)?)
+}
+
+fn merge_json(base: &mut serde_json::Value, overrides: serde_json::Value) {
+    match (base, overrides) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                merge_json(base.entry(key).or_insert(serde_json::Value::Null), value);
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
+/// Serializes durations as human readable strings, eg. "1m 30s".
+mod humantime_serde {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&humantime::format_duration(*duration).to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        humantime::parse_duration(&s).map_err(D::Error::custom)
+    }
+
+    pub mod chrono_duration {
+        use serde::{de::Error, ser, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(
+            duration: &chrono::Duration,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            let duration = duration
+                .to_std()
+                .map_err(<S::Error as ser::Error>::custom)?;
+            super::serialize(&duration, serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<chrono::Duration, D::Error> {
+            chrono::Duration::from_std(super::deserialize(deserializer)?).map_err(D::Error::custom)
+        }
+    }
 }
 
 impl Default for DiscoveryConfig {
@@ -50,6 +468,98 @@ impl Default for EventsConfig {
         EventsConfig {
             max_events_default: 20,
             max_events_max: 100,
+            stream_heartbeat: Duration::from_secs(15),
         }
     }
 }
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        ScanConfig {
+            max_offers_default: 50,
+            max_offers_max: 500,
+        }
+    }
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        RetentionConfig {
+            cleaner_interval: Duration::from_secs(24 * 3600),
+            offers: chrono::Duration::zero(),
+            demands: chrono::Duration::zero(),
+            proposals: chrono::Duration::zero(),
+            negotiation_events: chrono::Duration::days(1),
+            agreements: chrono::Duration::days(90),
+            agreements_archive_dir: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_default_values_match_structopt_defaults() {
+        let from_args = MarketConfigOpts::from_iter_safe(vec!["yagna"]).unwrap();
+        assert_eq!(
+            serde_json::to_value(&from_args.config).unwrap(),
+            serde_json::to_value(&Config::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_partial_json_overrides() {
+        let mut base = serde_json::to_value(&Config::default()).unwrap();
+        merge_json(
+            &mut base,
+            serde_json::json!({"discovery": {"meanCyclicBcastInterval": "5s"}}),
+        );
+        let config: Config = serde_json::from_value(base).unwrap();
+
+        assert_eq!(
+            config.discovery.mean_cyclic_bcast_interval,
+            Duration::from_secs(5)
+        );
+        assert_eq!(config.discovery.max_bcasted_offers, 200);
+        assert_eq!(config.subscription.default_ttl, chrono::Duration::hours(1));
+        assert_eq!(config.retention.agreements, chrono::Duration::days(90));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Config::default().validate().is_ok());
+
+        let mut config = Config::default();
+        config.events.max_events_default = 101;
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.discovery.mean_cyclic_unsubscribes_interval = Duration::from_secs(0);
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.events.stream_heartbeat = Duration::from_secs(0);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_retention_deprecated_env_and_minimum() {
+        std::env::set_var(DEPRECATED_AGREEMENT_STORE_DAYS, "10");
+        std::env::set_var(DEPRECATED_EVENT_STORE_DAYS, "3");
+        let mut retention = RetentionConfig::default();
+        retention.apply_deprecated_env().unwrap();
+        std::env::remove_var(DEPRECATED_AGREEMENT_STORE_DAYS);
+        std::env::remove_var(DEPRECATED_EVENT_STORE_DAYS);
+
+        assert_eq!(retention.agreements, chrono::Duration::days(10));
+        assert_eq!(retention.negotiation_events, chrono::Duration::days(3));
+
+        retention.enforce_min_agreements();
+        assert_eq!(
+            retention.agreements,
+            chrono::Duration::days(MIN_AGREEMENTS_RETENTION_DAYS)
+        );
+    }
+}
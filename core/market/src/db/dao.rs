@@ -1,11 +1,11 @@
 mod agreement;
 mod agreement_events;
+mod amendment;
 pub mod cleaner;
 mod demand;
 mod negotiation_events;
 pub mod sql_functions {
     use diesel::sql_types;
-    diesel::sql_function!(fn datetime(timestring:sql_types::Text, modifier:sql_types::Text) -> sql_types::Timestamp);
     diesel::sql_function!(
         #[sql_name = "coalesce"]
         fn coalesce_id(column: sql_types::Nullable<sql_types::Text>, default: sql_types::Text) -> sql_types::Text
@@ -16,6 +16,7 @@ mod proposal;
 
 pub use agreement::{AgreementDao, AgreementDaoError, SaveAgreementError};
 pub use agreement_events::AgreementEventsDao;
+pub use amendment::{AmendmentDao, AmendmentDaoError};
 pub use demand::DemandDao;
 pub use negotiation_events::{NegotiationEventsDao, TakeEventsError};
 pub use offer::{OfferDao, OfferState};
@@ -1,28 +1,27 @@
 use chrono::NaiveDateTime;
 use diesel::prelude::*;
+use std::collections::HashMap;
 
 use ya_client::model::NodeId;
-use ya_persistence::executor::{do_with_transaction, AsDao, ConnType, PoolType};
+use ya_persistence::executor::{
+    do_with_transaction, readonly_transaction, AsDao, ConnType, PoolType,
+};
 
 use crate::db::dao::agreement_events::create_event;
 use crate::db::dao::proposal::{has_counter_proposal, update_proposal_state};
-use crate::db::dao::sql_functions::datetime;
 use crate::db::model::{
-    check_transition, Agreement, AgreementId, AgreementState, AppSessionId, Owner, ProposalId,
-    ProposalIdParseError, ProposalState,
+    check_transition, Agreement, AgreementEvent, AgreementId, AgreementState, AppSessionId, Owner,
+    ProposalId, ProposalIdParseError, ProposalState,
 };
 use crate::db::schema::market_agreement::dsl as agreement;
 use crate::db::schema::market_agreement::dsl::market_agreement;
+use crate::db::schema::market_agreement_amendment::dsl as amendment;
+use crate::db::schema::market_agreement_amendment::dsl::market_agreement_amendment;
+use crate::db::schema::market_agreement_amendment_event::dsl as amendment_event;
+use crate::db::schema::market_agreement_amendment_event::dsl::market_agreement_amendment_event;
 use crate::db::schema::market_agreement_event::dsl as event;
 use crate::db::schema::market_agreement_event::dsl::market_agreement_event;
 use crate::db::{DbError, DbResult};
-use crate::market::EnvConfig;
-
-const AGREEMENT_STORE_DAYS: EnvConfig<'static, u64> = EnvConfig {
-    name: "YAGNA_MARKET_AGREEMENT_STORE_DAYS",
-    default: 90, // days
-    min: 30,     // days
-};
 
 #[derive(thiserror::Error, Debug)]
 pub enum SaveAgreementError {
@@ -283,31 +282,91 @@ impl<'c> AgreementDao<'c> {
         .await
     }
 
-    pub async fn clean(&self) -> DbResult<()> {
-        // FIXME use grace time from config file when #460 is merged
+    /// Agreements, that will be removed by `clean` with the same `threshold`,
+    /// together with their events.
+    pub async fn select_to_clean(
+        &self,
+        threshold: NaiveDateTime,
+    ) -> DbResult<Vec<(Agreement, Vec<AgreementEvent>)>> {
+        readonly_transaction(self.pool, move |conn| {
+            let agreements = market_agreement
+                .filter(agreement::valid_to.lt(threshold))
+                .load::<Agreement>(conn)?;
+            let events = market_agreement_event
+                .filter(
+                    event::agreement_id.eq_any(
+                        market_agreement
+                            .filter(agreement::valid_to.lt(threshold))
+                            .select(agreement::id),
+                    ),
+                )
+                .order_by(event::timestamp.asc())
+                .load::<AgreementEvent>(conn)?;
+
+            let mut events_map: HashMap<AgreementId, Vec<AgreementEvent>> = HashMap::new();
+            for event in events {
+                events_map
+                    .entry(event.agreement_id.clone())
+                    .or_default()
+                    .push(event);
+            }
+
+            Ok(agreements
+                .into_iter()
+                .map(|agreement| {
+                    let events = events_map.remove(&agreement.id).unwrap_or_default();
+                    (agreement, events)
+                })
+                .collect())
+        })
+        .await
+    }
+
+    /// Removes Agreements, which validity ended before `threshold`, with all
+    /// related events and amendments. In `dry_run` mode only counts them.
+    /// Returns number of (Agreements, Agreement events).
+    pub async fn clean(&self, threshold: NaiveDateTime, dry_run: bool) -> DbResult<(u64, u64)> {
         log::trace!("Clean market agreements: start");
-        let interval_days = AGREEMENT_STORE_DAYS.get_value();
         let (num_agreements, num_events) = do_with_transaction(self.pool, move |conn| {
-            let agreements_to_clean = market_agreement.filter(
-                agreement::valid_to.lt(datetime("NOW", format!("-{} days", interval_days))),
-            );
+            let agreements_to_clean = market_agreement.filter(agreement::valid_to.lt(threshold));
 
             let related_events = market_agreement_event.filter(
                 event::agreement_id.eq_any(agreements_to_clean.clone().select(agreement::id)),
             );
 
-            let num_agreements = diesel::delete(agreements_to_clean).execute(conn)?;
+            if dry_run {
+                let num_agreements = agreements_to_clean.count().get_result::<i64>(conn)?;
+                let num_events = related_events.count().get_result::<i64>(conn)?;
+                return Result::<(u64, u64), DbError>::Ok((
+                    num_agreements as u64,
+                    num_events as u64,
+                ));
+            }
+
+            let related_amendments = market_agreement_amendment.filter(
+                amendment::agreement_id.eq_any(agreements_to_clean.clone().select(agreement::id)),
+            );
+            let related_amendment_events = market_agreement_amendment_event.filter(
+                amendment_event::agreement_id
+                    .eq_any(agreements_to_clean.clone().select(agreement::id)),
+            );
+
+            diesel::delete(related_amendment_events).execute(conn)?;
+            diesel::delete(related_amendments).execute(conn)?;
+            // Events must be removed first, because they are selected by
+            // ids of Agreements, that wouldn't exist anymore.
             let num_events = diesel::delete(related_events).execute(conn)?;
-            Result::<(usize, usize), DbError>::Ok((num_agreements, num_events))
+            let num_agreements = diesel::delete(agreements_to_clean).execute(conn)?;
+            Result::<(u64, u64), DbError>::Ok((num_agreements as u64, num_events as u64))
         })
         .await?;
 
-        if num_agreements > 0 {
+        if num_agreements > 0 && !dry_run {
             log::info!("Cleaned {} market agreements", num_agreements);
             log::info!("Cleaned {} market agreement events", num_events);
         }
         log::trace!("Clean market agreements: done");
-        Ok(())
+        Ok((num_agreements, num_events))
     }
 }
 
@@ -0,0 +1,420 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use ya_client::model::NodeId;
+use ya_persistence::executor::{do_with_transaction, readonly_transaction};
+use ya_persistence::executor::{AsDao, ConnType, PoolType};
+
+use crate::db::model::{
+    apply_properties, Agreement, AgreementId, AgreementState, Amendment, AmendmentEvent,
+    AmendmentEventType, AmendmentId, AmendmentState, AppSessionId, NewAmendmentEvent, Owner,
+};
+use crate::db::schema::market_agreement::dsl as agreement;
+use crate::db::schema::market_agreement::dsl::market_agreement;
+use crate::db::schema::market_agreement_amendment::dsl as amendment;
+use crate::db::schema::market_agreement_amendment::dsl::market_agreement_amendment;
+use crate::db::schema::market_agreement_amendment_event::dsl as event;
+use crate::db::schema::market_agreement_amendment_event::dsl::market_agreement_amendment_event;
+use crate::db::{DbError, DbResult};
+
+#[derive(thiserror::Error, Debug)]
+pub enum AmendmentDaoError {
+    #[error("Agreement [{0}] not found.")]
+    AgreementNotFound(AgreementId),
+    #[error("Agreement [{0}] is in state {1}. Only Approved Agreements can be amended.")]
+    AgreementState(AgreementId, AgreementState),
+    #[error("Agreement [{0}] has already pending Amendment [{1}].")]
+    PendingExists(AgreementId, AmendmentId),
+    #[error("Amendment [{0}] not found.")]
+    NotFound(AmendmentId),
+    #[error("Amendment [{0}] expired.")]
+    Expired(AmendmentId),
+    #[error("Amendment [{0}] was already {1}.")]
+    AlreadyDecided(AmendmentId, AmendmentState),
+    #[error("Can't approve nor reject own Amendment [{0}].")]
+    OwnAmendment(AmendmentId),
+    #[error("Failed to apply Amendment [{0}] to Agreement. Error: {1}")]
+    Apply(AmendmentId, String),
+    #[error("Amendment database error: {0}")]
+    DbError(DbError),
+}
+
+pub struct AmendmentDao<'c> {
+    pool: &'c PoolType,
+}
+
+impl<'a> AsDao<'a> for AmendmentDao<'a> {
+    fn as_dao(pool: &'a PoolType) -> Self {
+        Self { pool }
+    }
+}
+
+impl<'c> AmendmentDao<'c> {
+    /// Saves new Amendment and creates `Proposed` event.
+    /// Agreement can have only one pending Amendment at the same time.
+    pub async fn save(
+        &self,
+        new_amendment: Amendment,
+        validation_ts: NaiveDateTime,
+    ) -> Result<(), AmendmentDaoError> {
+        do_with_transaction(self.pool, move |conn| {
+            let agreement = get_approved_agreement(conn, &new_amendment.agreement_id)?;
+
+            let pending = market_agreement_amendment
+                .filter(amendment::agreement_id.eq(&agreement.id))
+                .filter(amendment::state.eq(AmendmentState::Pending))
+                .load::<Amendment>(conn)?;
+
+            for mut pending in pending.into_iter() {
+                if !expire_if_needed(conn, &mut pending, validation_ts)? {
+                    return Err(AmendmentDaoError::PendingExists(
+                        agreement.id.clone(),
+                        pending.id,
+                    ));
+                }
+            }
+
+            diesel::insert_into(market_agreement_amendment)
+                .values(&new_amendment)
+                .execute(conn)?;
+            create_event(
+                conn,
+                &new_amendment,
+                AmendmentEventType::Proposed,
+                new_amendment.issuer,
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Removes Amendment together with its events. Used when Amendment
+    /// couldn't be delivered to other party.
+    pub async fn remove(
+        &self,
+        agreement_id: &AgreementId,
+        amendment_id: &AmendmentId,
+    ) -> DbResult<()> {
+        let agreement_id = agreement_id.clone();
+        let amendment_id = amendment_id.clone();
+        do_with_transaction(self.pool, move |conn| {
+            diesel::delete(
+                market_agreement_amendment_event
+                    .filter(event::amendment_id.eq(&amendment_id))
+                    .filter(event::agreement_id.eq(&agreement_id)),
+            )
+            .execute(conn)?;
+            diesel::delete(
+                market_agreement_amendment
+                    .filter(amendment::id.eq(&amendment_id))
+                    .filter(amendment::agreement_id.eq(&agreement_id)),
+            )
+            .execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn select(
+        &self,
+        agreement_id: &AgreementId,
+        amendment_id: &AmendmentId,
+        validation_ts: NaiveDateTime,
+    ) -> Result<Option<Amendment>, AmendmentDaoError> {
+        let agreement_id = agreement_id.clone();
+        let amendment_id = amendment_id.clone();
+        do_with_transaction(self.pool, move |conn| {
+            let mut amendment = match find_amendment(conn, &agreement_id, &amendment_id)? {
+                Some(amendment) => amendment,
+                None => return Ok(None),
+            };
+
+            expire_if_needed(conn, &mut amendment, validation_ts)?;
+            Ok(Some(amendment))
+        })
+        .await
+    }
+
+    pub async fn list(
+        &self,
+        agreement_id: &AgreementId,
+        validation_ts: NaiveDateTime,
+    ) -> Result<Vec<Amendment>, AmendmentDaoError> {
+        let agreement_id = agreement_id.clone();
+        do_with_transaction(self.pool, move |conn| {
+            let mut amendments = market_agreement_amendment
+                .filter(amendment::agreement_id.eq(&agreement_id))
+                .order_by(amendment::creation_ts.asc())
+                .load::<Amendment>(conn)?;
+
+            for amendment in amendments.iter_mut() {
+                expire_if_needed(conn, amendment, validation_ts)?;
+            }
+            Ok(amendments)
+        })
+        .await
+    }
+
+    /// Approves pending Amendment and applies it's changes to Agreement.
+    pub async fn approve(
+        &self,
+        agreement_id: &AgreementId,
+        amendment_id: &AmendmentId,
+        approver: Owner,
+        approval_ts: NaiveDateTime,
+    ) -> Result<Amendment, AmendmentDaoError> {
+        let agreement_id = agreement_id.clone();
+        let amendment_id = amendment_id.clone();
+        do_with_transaction(self.pool, move |conn| {
+            let mut amendment =
+                get_pending_amendment(conn, &agreement_id, &amendment_id, approver, approval_ts)?;
+            let agreement = get_approved_agreement(conn, &agreement_id)?;
+
+            let demand_properties =
+                apply_properties(&agreement.demand_properties, &amendment.demand_properties)
+                    .map_err(|e| AmendmentDaoError::Apply(amendment_id.clone(), e))?;
+            let offer_properties =
+                apply_properties(&agreement.offer_properties, &amendment.offer_properties)
+                    .map_err(|e| AmendmentDaoError::Apply(amendment_id.clone(), e))?;
+
+            diesel::update(market_agreement.find(&agreement_id))
+                .set((
+                    agreement::demand_properties.eq(&demand_properties),
+                    agreement::offer_properties.eq(&offer_properties),
+                ))
+                .execute(conn)?;
+
+            update_decision(conn, &mut amendment, AmendmentState::Approved, approval_ts)?;
+            create_event(conn, &amendment, AmendmentEventType::Approved, approver)?;
+            Ok(amendment)
+        })
+        .await
+    }
+
+    /// Reverts approval or rejection, which couldn't be delivered to other party.
+    /// Amendment becomes pending again and Agreement gets back properties of
+    /// `agreement`, as they were before the decision.
+    pub async fn undo_decision(
+        &self,
+        agreement: &Agreement,
+        amendment_id: &AmendmentId,
+    ) -> DbResult<()> {
+        let agreement_id = agreement.id.clone();
+        let demand_properties = agreement.demand_properties.clone();
+        let offer_properties = agreement.offer_properties.clone();
+        let amendment_id = amendment_id.clone();
+        do_with_transaction(self.pool, move |conn| {
+            diesel::update(market_agreement.find(&agreement_id))
+                .set((
+                    agreement::demand_properties.eq(&demand_properties),
+                    agreement::offer_properties.eq(&offer_properties),
+                ))
+                .execute(conn)?;
+            diesel::update(
+                market_agreement_amendment
+                    .filter(amendment::id.eq(&amendment_id))
+                    .filter(amendment::agreement_id.eq(&agreement_id)),
+            )
+            .set((
+                amendment::state.eq(AmendmentState::Pending),
+                amendment::reason.eq(None::<String>),
+                amendment::decision_ts.eq(None::<NaiveDateTime>),
+            ))
+            .execute(conn)?;
+            diesel::delete(
+                market_agreement_amendment_event
+                    .filter(event::amendment_id.eq(&amendment_id))
+                    .filter(event::agreement_id.eq(&agreement_id))
+                    .filter(event::event_type.ne(AmendmentEventType::Proposed)),
+            )
+            .execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn reject(
+        &self,
+        agreement_id: &AgreementId,
+        amendment_id: &AmendmentId,
+        rejector: Owner,
+        reason: Option<String>,
+        rejection_ts: NaiveDateTime,
+    ) -> Result<Amendment, AmendmentDaoError> {
+        let agreement_id = agreement_id.clone();
+        let amendment_id = amendment_id.clone();
+        do_with_transaction(self.pool, move |conn| {
+            let mut amendment =
+                get_pending_amendment(conn, &agreement_id, &amendment_id, rejector, rejection_ts)?;
+
+            diesel::update(
+                market_agreement_amendment
+                    .filter(amendment::id.eq(&amendment_id))
+                    .filter(amendment::agreement_id.eq(&agreement_id)),
+            )
+            .set(amendment::reason.eq(&reason))
+            .execute(conn)?;
+            amendment.reason = reason;
+
+            update_decision(conn, &mut amendment, AmendmentState::Rejected, rejection_ts)?;
+            create_event(conn, &amendment, AmendmentEventType::Rejected, rejector)?;
+            Ok(amendment)
+        })
+        .await
+    }
+
+    pub async fn select_events(
+        &self,
+        node_id: &NodeId,
+        session_id: &AppSessionId,
+        max_events: i32,
+        after_timestamp: NaiveDateTime,
+    ) -> DbResult<Vec<AmendmentEvent>> {
+        let session_id = session_id.clone();
+        let node_id = node_id.clone();
+        readonly_transaction(self.pool, move |conn| {
+            let filter_my_agreements = agreement::provider_id
+                .eq(node_id)
+                .or(agreement::requestor_id.eq(node_id));
+
+            let mut select_corresponding_agreement = market_agreement
+                .select(agreement::id)
+                .filter(filter_my_agreements)
+                .into_boxed();
+
+            // Optionally filter by `AppSessionId`.
+            if let Some(session_id) = session_id {
+                select_corresponding_agreement =
+                    select_corresponding_agreement.filter(agreement::session_id.eq(session_id));
+            };
+
+            Ok(market_agreement_amendment_event
+                .filter(event::agreement_id.eq_any(select_corresponding_agreement))
+                .filter(event::timestamp.gt(after_timestamp))
+                .order_by(event::timestamp.asc())
+                .limit(max_events as i64)
+                .load::<AmendmentEvent>(conn)?)
+        })
+        .await
+    }
+}
+
+fn find_amendment(
+    conn: &ConnType,
+    agreement_id: &AgreementId,
+    amendment_id: &AmendmentId,
+) -> DbResult<Option<Amendment>> {
+    Ok(market_agreement_amendment
+        .filter(amendment::id.eq(amendment_id))
+        .filter(amendment::agreement_id.eq(agreement_id))
+        .first::<Amendment>(conn)
+        .optional()?)
+}
+
+fn get_approved_agreement(
+    conn: &ConnType,
+    agreement_id: &AgreementId,
+) -> Result<Agreement, AmendmentDaoError> {
+    let agreement = market_agreement
+        .filter(agreement::id.eq(agreement_id))
+        .first::<Agreement>(conn)
+        .optional()?
+        .ok_or(AmendmentDaoError::AgreementNotFound(agreement_id.clone()))?;
+
+    match agreement.state {
+        AgreementState::Approved => Ok(agreement),
+        state => Err(AmendmentDaoError::AgreementState(agreement.id, state)),
+    }
+}
+
+fn get_pending_amendment(
+    conn: &ConnType,
+    agreement_id: &AgreementId,
+    amendment_id: &AmendmentId,
+    decider: Owner,
+    validation_ts: NaiveDateTime,
+) -> Result<Amendment, AmendmentDaoError> {
+    let mut amendment = find_amendment(conn, agreement_id, amendment_id)?
+        .ok_or(AmendmentDaoError::NotFound(amendment_id.clone()))?;
+
+    if amendment.issuer == decider {
+        return Err(AmendmentDaoError::OwnAmendment(amendment_id.clone()));
+    }
+
+    if expire_if_needed(conn, &mut amendment, validation_ts)? {
+        return Err(AmendmentDaoError::Expired(amendment_id.clone()));
+    }
+
+    match amendment.state {
+        AmendmentState::Pending => Ok(amendment),
+        AmendmentState::Expired => Err(AmendmentDaoError::Expired(amendment_id.clone())),
+        state => Err(AmendmentDaoError::AlreadyDecided(
+            amendment_id.clone(),
+            state,
+        )),
+    }
+}
+
+/// Returns true if Amendment expired during this call.
+fn expire_if_needed(
+    conn: &ConnType,
+    amendment: &mut Amendment,
+    validation_ts: NaiveDateTime,
+) -> DbResult<bool> {
+    if amendment.state != AmendmentState::Pending || amendment.valid_to >= validation_ts {
+        return Ok(false);
+    }
+
+    update_state(conn, amendment, AmendmentState::Expired)?;
+    Ok(true)
+}
+
+fn update_state(conn: &ConnType, amendment: &mut Amendment, state: AmendmentState) -> DbResult<()> {
+    diesel::update(
+        market_agreement_amendment
+            .filter(amendment::id.eq(&amendment.id))
+            .filter(amendment::agreement_id.eq(&amendment.agreement_id)),
+    )
+    .set(amendment::state.eq(&state))
+    .execute(conn)?;
+
+    amendment.state = state;
+    Ok(())
+}
+
+fn update_decision(
+    conn: &ConnType,
+    amendment: &mut Amendment,
+    state: AmendmentState,
+    decision_ts: NaiveDateTime,
+) -> DbResult<()> {
+    diesel::update(
+        market_agreement_amendment
+            .filter(amendment::id.eq(&amendment.id))
+            .filter(amendment::agreement_id.eq(&amendment.agreement_id)),
+    )
+    .set(amendment::decision_ts.eq(Some(decision_ts)))
+    .execute(conn)?;
+
+    amendment.decision_ts = Some(decision_ts);
+    update_state(conn, amendment, state)
+}
+
+fn create_event(
+    conn: &ConnType,
+    amendment: &Amendment,
+    event_type: AmendmentEventType,
+    issuer: Owner,
+) -> DbResult<()> {
+    let event = NewAmendmentEvent::new(amendment, &amendment.agreement_id, event_type, issuer);
+    diesel::insert_into(market_agreement_amendment_event)
+        .values(&event)
+        .execute(conn)?;
+    Ok(())
+}
+
+impl<ErrorType: Into<DbError>> From<ErrorType> for AmendmentDaoError {
+    fn from(err: ErrorType) -> Self {
+        AmendmentDaoError::DbError(err.into())
+    }
+}
@@ -1,40 +1,160 @@
-use crate::db::dao::{AgreementDao, DemandDao, NegotiationEventsDao, OfferDao, ProposalDao};
+use chrono::{NaiveDateTime, Utc};
 use futures::join;
-use std::time::Duration;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tokio::time;
+
+use ya_client::model::market::AgreementOperationEvent as ClientEvent;
+use ya_core_model::market::CleanReport;
 use ya_persistence::executor::DbExecutor;
 
-pub async fn clean(db: DbExecutor) {
+use crate::config::{Config, RetentionConfig, MIN_AGREEMENTS_RETENTION_DAYS};
+use crate::db::dao::{AgreementDao, DemandDao, NegotiationEventsDao, OfferDao, ProposalDao};
+use crate::db::model::Agreement;
+use crate::db::DbError;
+
+#[derive(thiserror::Error, Debug)]
+pub enum CleanerError {
+    #[error("Market database cleaner error: {0}")]
+    Db(#[from] DbError),
+    #[error("Failed to archive Agreements to [{0}]: {1}")]
+    Archive(PathBuf, String),
+}
+
+/// Entry of Agreements archive file.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArchivedAgreement {
+    agreement: Agreement,
+    events: Vec<ClientEvent>,
+}
+
+/// Removes entries older than their retention time. Agreements are exported
+/// to `archive_dir` before removal. If archiving fails, Agreements are left
+/// untouched, to avoid losing them.
+pub async fn clean(
+    db: DbExecutor,
+    retention: &RetentionConfig,
+    dry_run: bool,
+    archive_dir: Option<PathBuf>,
+) -> Result<CleanReport, CleanerError> {
+    let now = Utc::now().naive_utc();
+
     let demand_db = db.clone();
     let events_db = db.clone();
     let offer_db = db.clone();
-    let agreement_db = db.clone();
     let proposal_db = db.clone();
 
     let results = join!(
-        async move { demand_db.as_dao::<DemandDao>().clean().await },
-        async move { offer_db.as_dao::<OfferDao>().clean().await },
-        async move { agreement_db.as_dao::<AgreementDao>().clean().await },
-        async move { proposal_db.as_dao::<ProposalDao>().clean().await },
-        async move { events_db.as_dao::<NegotiationEventsDao>().clean().await },
+        async move {
+            demand_db
+                .as_dao::<DemandDao>()
+                .clean(now - retention.demands, dry_run)
+                .await
+        },
+        async move {
+            let dao = offer_db.as_dao::<OfferDao>();
+            let offers = dao.clean(now - retention.offers, dry_run).await?;
+            let unsubscribes = dao
+                .clean_unsubscribes(now - retention.offers, dry_run)
+                .await?;
+            Result::<_, DbError>::Ok((offers, unsubscribes))
+        },
+        async move {
+            proposal_db
+                .as_dao::<ProposalDao>()
+                .clean(now - retention.proposals, dry_run)
+                .await
+        },
+        async move {
+            events_db
+                .as_dao::<NegotiationEventsDao>()
+                .clean(now - retention.negotiation_events, dry_run)
+                .await
+        },
     );
-    let v_results = vec![results.0, results.1, results.2, results.3, results.4];
-    for db_result in v_results.into_iter() {
-        match db_result {
-            Err(e) => log::error!("Market database cleaner error: {}", e),
-            _ => (),
-        }
+
+    // Minimum is enforced when loading config, but `RetentionConfig` can be built directly too.
+    let agreements_retention = retention
+        .agreements
+        .max(chrono::Duration::days(MIN_AGREEMENTS_RETENTION_DAYS));
+    let agreements_threshold = now - agreements_retention;
+    let agreements_archive = match (dry_run, archive_dir) {
+        (false, Some(dir)) => archive_agreements(&db, agreements_threshold, &dir).await?,
+        _ => None,
+    };
+    let (agreements, agreement_events) = db
+        .as_dao::<AgreementDao>()
+        .clean(agreements_threshold, dry_run)
+        .await?;
+
+    let (offers, offer_unsubscribes) = results.1?;
+    let (proposals, negotiations) = results.2?;
+    Ok(CleanReport {
+        dry_run,
+        demands: results.0?,
+        offers,
+        offer_unsubscribes,
+        negotiations,
+        proposals,
+        negotiation_events: results.3?,
+        agreements,
+        agreement_events,
+        agreements_archive,
+    })
+}
+
+async fn archive_agreements(
+    db: &DbExecutor,
+    threshold: NaiveDateTime,
+    archive_dir: &Path,
+) -> Result<Option<PathBuf>, CleanerError> {
+    let archive: Vec<ArchivedAgreement> = db
+        .as_dao::<AgreementDao>()
+        .select_to_clean(threshold)
+        .await?
+        .into_iter()
+        .map(|(agreement, events)| ArchivedAgreement {
+            agreement,
+            events: events
+                .into_iter()
+                .map(|event| event.into_client())
+                .collect(),
+        })
+        .collect();
+    if archive.is_empty() {
+        return Ok(None);
     }
+
+    let path = archive_dir.join(format!(
+        "market-agreements-{}.json",
+        Utc::now().format("%Y%m%dT%H%M%S%.3f")
+    ));
+    let archive_err = |e: String| CleanerError::Archive(path.clone(), e);
+
+    std::fs::create_dir_all(archive_dir).map_err(|e| archive_err(e.to_string()))?;
+    let content = serde_json::to_vec_pretty(&archive).map_err(|e| archive_err(e.to_string()))?;
+    std::fs::write(&path, content).map_err(|e| archive_err(e.to_string()))?;
+
+    log::info!(
+        "Archived {} market agreements to [{}]",
+        archive.len(),
+        path.display()
+    );
+    Ok(Some(path))
 }
 
-pub async fn clean_forever(db: DbExecutor) {
-    // TODO: Use value from market config once #460 is merged
-    let mut interval = time::interval(Duration::from_secs(3600 * 24));
+pub async fn clean_forever(db: DbExecutor, config: Arc<Config>) {
+    let retention = &config.retention;
+    let mut interval = time::interval(retention.cleaner_interval);
     loop {
         interval.tick().await;
         log::debug!("Market database cleaner job started");
-        let db = db.clone();
-        clean(db).await;
+        let archive_dir = retention.agreements_archive_dir.clone();
+        if let Err(e) = clean(db.clone(), retention, false, archive_dir).await {
+            log::error!("{}", e);
+        }
         log::debug!("Market database cleaner job done");
     }
 }
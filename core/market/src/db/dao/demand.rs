@@ -1,5 +1,4 @@
 use chrono::{NaiveDateTime, Utc};
-use diesel::expression::dsl::now as sql_now;
 use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
 
 use ya_client::model::NodeId;
@@ -93,19 +92,24 @@ impl<'c> DemandDao<'c> {
         .await
     }
 
-    pub async fn clean(&self) -> DbResult<()> {
+    /// Removes Demands expired before `threshold`. In `dry_run` mode only
+    /// counts Demands, that would be removed.
+    pub async fn clean(&self, threshold: NaiveDateTime, dry_run: bool) -> DbResult<u64> {
         log::debug!("Clean market demands: start");
         let num_deleted = do_with_transaction(self.pool, move |conn| {
-            let nd = diesel::delete(dsl::market_demand.filter(dsl::expiration_ts.lt(sql_now)))
-                .execute(conn)?;
-            Result::<usize, DbError>::Ok(nd)
+            let to_clean = dsl::market_demand.filter(dsl::expiration_ts.lt(threshold));
+            let nd = match dry_run {
+                true => to_clean.count().get_result::<i64>(conn)? as u64,
+                false => diesel::delete(to_clean).execute(conn)? as u64,
+            };
+            Result::<u64, DbError>::Ok(nd)
         })
         .await?;
-        if num_deleted > 0 {
+        if num_deleted > 0 && !dry_run {
             log::info!("Clean market demands: {} cleaned", num_deleted);
         }
         log::debug!("Clean market demands: done");
-        Ok(())
+        Ok(num_deleted)
     }
 }
 
@@ -1,4 +1,4 @@
-use chrono::Utc;
+use chrono::{NaiveDateTime, Utc};
 use diesel::{sql_types, ExpressionMethods, QueryDsl, RunQueryDsl};
 use thiserror::Error;
 
@@ -7,19 +7,11 @@ use ya_persistence::executor::{do_with_transaction, AsDao, PoolType};
 
 use crate::db::dao::demand::{demand_status, DemandState};
 use crate::db::dao::offer::{query_state, OfferState};
-use crate::db::dao::sql_functions::datetime;
 use crate::db::model::{Agreement, EventType, MarketEvent, Owner, Proposal, SubscriptionId};
 use crate::db::schema::market_negotiation_event::dsl;
 use crate::db::{DbError, DbResult};
-use crate::market::EnvConfig;
 use diesel::dsl::sql;
 
-const EVENT_STORE_DAYS: EnvConfig<'static, u64> = EnvConfig {
-    name: "YAGNA_MARKET_EVENT_STORE_DAYS",
-    default: 1, // days
-    min: 1,     // days
-};
-
 #[derive(Error, Debug)]
 pub enum TakeEventsError {
     #[error("Subscription [{0}] not found. Could be unsubscribed.")]
@@ -141,23 +133,24 @@ impl<'c> NegotiationEventsDao<'c> {
         .await
     }
 
-    pub async fn clean(&self) -> DbResult<()> {
+    /// Removes events created before `threshold`. In `dry_run` mode only
+    /// counts events, that would be removed.
+    pub async fn clean(&self, threshold: NaiveDateTime, dry_run: bool) -> DbResult<u64> {
         log::debug!("Clean market events: start");
-        let interval_days = EVENT_STORE_DAYS.get_value();
         let num_deleted = do_with_transaction(self.pool, move |conn| {
-            let nd = diesel::delete(
-                dsl::market_negotiation_event
-                    .filter(dsl::timestamp.lt(datetime("NOW", format!("-{} days", interval_days)))),
-            )
-            .execute(conn)?;
-            Result::<usize, DbError>::Ok(nd)
+            let to_clean = dsl::market_negotiation_event.filter(dsl::timestamp.lt(threshold));
+            let nd = match dry_run {
+                true => to_clean.count().get_result::<i64>(conn)? as u64,
+                false => diesel::delete(to_clean).execute(conn)? as u64,
+            };
+            Result::<u64, DbError>::Ok(nd)
         })
         .await?;
-        if num_deleted > 0 {
+        if num_deleted > 0 && !dry_run {
             log::info!("Clean market events: {} cleaned", num_deleted);
         }
         log::debug!("Clean market events: done");
-        Ok(())
+        Ok(num_deleted)
     }
 }
 
@@ -1,5 +1,4 @@
 use chrono::NaiveDateTime;
-use diesel::expression::dsl::now as sql_now;
 use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
 
 use ya_client::model::NodeId;
@@ -235,37 +234,49 @@ impl<'c> OfferDao<'c> {
         .await
     }
 
-    pub async fn clean(&self) -> DbResult<()> {
+    /// Removes Offers expired before `threshold`. In `dry_run` mode only
+    /// counts Offers, that would be removed.
+    pub async fn clean(&self, threshold: NaiveDateTime, dry_run: bool) -> DbResult<u64> {
         log::debug!("Clean market offers: start");
         let num_deleted = do_with_transaction(self.pool, move |conn| {
-            let nd = diesel::delete(market_offer.filter(offer::expiration_ts.lt(sql_now)))
-                .execute(conn)?;
-            Result::<usize, DbError>::Ok(nd)
+            let to_clean = market_offer.filter(offer::expiration_ts.lt(threshold));
+            let nd = match dry_run {
+                true => to_clean.count().get_result::<i64>(conn)? as u64,
+                false => diesel::delete(to_clean).execute(conn)? as u64,
+            };
+            Result::<u64, DbError>::Ok(nd)
         })
         .await?;
-        if num_deleted > 0 {
+        if num_deleted > 0 && !dry_run {
             log::info!("Clean market offers: {} cleaned", num_deleted);
         }
-        self.clean_unsubscribes().await?;
         log::debug!("Clean market offers: done");
-        Ok(())
+        Ok(num_deleted)
     }
 
-    pub async fn clean_unsubscribes(&self) -> DbResult<()> {
+    /// Unsubscribe markers are needed only until Offer expiration, because
+    /// expired Offers are rejected anyway.
+    pub async fn clean_unsubscribes(
+        &self,
+        threshold: NaiveDateTime,
+        dry_run: bool,
+    ) -> DbResult<u64> {
         log::debug!("Clean market offers unsubscribes: start");
         let num_deleted = do_with_transaction(self.pool, move |conn| {
-            let nd = diesel::delete(
-                market_offer_unsubscribed.filter(unsubscribed::expiration_ts.lt(sql_now)),
-            )
-            .execute(conn)?;
-            Result::<usize, DbError>::Ok(nd)
+            let to_clean =
+                market_offer_unsubscribed.filter(unsubscribed::expiration_ts.lt(threshold));
+            let nd = match dry_run {
+                true => to_clean.count().get_result::<i64>(conn)? as u64,
+                false => diesel::delete(to_clean).execute(conn)? as u64,
+            };
+            Result::<u64, DbError>::Ok(nd)
         })
         .await?;
-        if num_deleted > 0 {
+        if num_deleted > 0 && !dry_run {
             log::info!("Clean market offers unsubscribes: {} cleaned", num_deleted);
         }
         log::debug!("Clean market offers unsubscribes: done");
-        Ok(())
+        Ok(num_deleted)
     }
 }
 
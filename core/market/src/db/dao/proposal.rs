@@ -1,4 +1,4 @@
-use diesel::expression::dsl::now as sql_now;
+use chrono::NaiveDateTime;
 use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
 use serde::{Deserialize, Serialize};
 
@@ -119,9 +119,13 @@ impl<'c> ProposalDao<'c> {
         .await
     }
 
-    pub async fn clean(&self) -> DbResult<()> {
-        // FIXME clean negotiations also
+    /// Removes negotiations with all Proposals expired before `threshold`
+    /// together with their Proposals. In `dry_run` mode only counts them.
+    /// Returns number of (Proposals, negotiations).
+    pub async fn clean(&self, threshold: NaiveDateTime, dry_run: bool) -> DbResult<(u64, u64)> {
         log::debug!("Clean market proposals: start");
+        let mut num_proposals = 0;
+        let mut num_negotiations = 0;
         loop {
             let (num_deleted_p, num_deleted_n) = do_with_transaction(self.pool, move |conn| {
                 // diesel forbids the same table appearing more than once in a query
@@ -131,13 +135,25 @@ impl<'c> ProposalDao<'c> {
                     .filter(
                         dsl_negotiation::id.ne_all(
                             dsl::market_proposal
-                                .filter(dsl::expiration_ts.gt(sql_now))
+                                .filter(dsl::expiration_ts.gt(threshold))
                                 .select(dsl::negotiation_id),
                         ),
                     )
-                    .limit(500)
-                    .select(dsl_negotiation::id)
-                    .load::<String>(conn)?;
+                    .select(dsl_negotiation::id);
+
+                if dry_run {
+                    let expired_negotiations = expired_negotiations.load::<String>(conn)?;
+                    let ndp = dsl::market_proposal
+                        .filter(dsl::negotiation_id.eq_any(expired_negotiations.clone()))
+                        .count()
+                        .get_result::<i64>(conn)?;
+                    return Result::<(u64, u64), DbError>::Ok((
+                        ndp as u64,
+                        expired_negotiations.len() as u64,
+                    ));
+                }
+
+                let expired_negotiations = expired_negotiations.limit(500).load::<String>(conn)?;
                 let ndp = diesel::delete(
                     dsl::market_proposal
                         .filter(dsl::negotiation_id.eq_any(expired_negotiations.clone())),
@@ -148,21 +164,23 @@ impl<'c> ProposalDao<'c> {
                         .filter(dsl_negotiation::id.eq_any(expired_negotiations)),
                 )
                 .execute(conn)?;
-                Result::<(usize, usize), DbError>::Ok((ndp, ndn))
+                Result::<(u64, u64), DbError>::Ok((ndp as u64, ndn as u64))
             })
             .await?;
-            if (num_deleted_p > 0) || (num_deleted_n > 0) {
-                log::info!(
-                    "Clean market proposals: {}({} negotiations) cleaned",
-                    num_deleted_p,
-                    num_deleted_n
-                );
-            } else {
+
+            num_proposals += num_deleted_p;
+            num_negotiations += num_deleted_n;
+            if dry_run || (num_deleted_p == 0 && num_deleted_n == 0) {
                 break;
             }
+            log::info!(
+                "Clean market proposals: {}({} negotiations) cleaned",
+                num_deleted_p,
+                num_deleted_n
+            );
         }
         log::debug!("Clean market proposals: done");
-        Ok(())
+        Ok((num_proposals, num_negotiations))
     }
 }
 
@@ -1,5 +1,6 @@
 mod agreement;
 mod agreement_events;
+mod amendment;
 mod demand;
 mod negotiation_events;
 mod offer;
@@ -9,6 +10,10 @@ mod subscription_id;
 
 pub use agreement::{check_transition, Agreement, AgreementId, AgreementState, AppSessionId};
 pub use agreement_events::{AgreementEvent, AgreementEventType, NewAgreementEvent};
+pub use amendment::{
+    apply_properties, Amendment, AmendmentEvent, AmendmentEventType, AmendmentId, AmendmentIssuer,
+    AmendmentOperationEvent, AmendmentState, ClientAmendment, NewAmendment, NewAmendmentEvent,
+};
 pub use demand::Demand;
 pub use negotiation_events::{EventError, EventType, MarketEvent};
 pub use offer::{Offer, OfferUnsubscribed};
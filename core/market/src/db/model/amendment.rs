@@ -0,0 +1,302 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use diesel::sql_types::Text;
+use serde::{Deserialize, Serialize};
+
+use ya_client::model::market::Reason;
+use ya_client::model::ErrorMessage;
+use ya_diesel_utils::DbTextField;
+
+use crate::db::model::{AgreementId, Owner};
+use crate::db::schema::{market_agreement_amendment, market_agreement_amendment_event};
+
+pub type AmendmentId = String;
+
+#[derive(
+    strum_macros::EnumString,
+    DbTextField,
+    derive_more::Display,
+    AsExpression,
+    FromSqlRow,
+    PartialEq,
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+)]
+#[sql_type = "Text"]
+pub enum AmendmentState {
+    /// Proposed by one side and waiting for decision of the other side.
+    Pending,
+    /// Approved by the other side. Changes were applied to Agreement.
+    Approved,
+    /// Rejected by the other side.
+    Rejected,
+    /// Not approved nor rejected within validity period.
+    Expired,
+}
+
+/// Party that proposed Amendment.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AmendmentIssuer {
+    Provider,
+    Requestor,
+}
+
+/// Changes to Approved Agreement proposed by one of the parties.
+/// Properties are stored in flattened form, the same as in Agreement.
+#[derive(Clone, Debug, Identifiable, Insertable, Queryable, Serialize, Deserialize)]
+#[table_name = "market_agreement_amendment"]
+#[primary_key(id, agreement_id)]
+pub struct Amendment {
+    pub id: AmendmentId,
+    pub agreement_id: AgreementId,
+    pub issuer: Owner,
+
+    pub demand_properties: Option<String>,
+    pub offer_properties: Option<String>,
+
+    pub state: AmendmentState,
+    pub reason: Option<String>,
+
+    pub creation_ts: NaiveDateTime,
+    /// Amendment needs to be approved or rejected before this date; otherwise will expire.
+    pub valid_to: NaiveDateTime,
+    pub decision_ts: Option<NaiveDateTime>,
+}
+
+#[derive(
+    DbTextField,
+    strum_macros::EnumString,
+    derive_more::Display,
+    AsExpression,
+    FromSqlRow,
+    PartialEq,
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+)]
+#[sql_type = "Text"]
+pub enum AmendmentEventType {
+    Proposed,
+    Approved,
+    Rejected,
+}
+
+#[derive(Clone, Debug, Queryable)]
+pub struct AmendmentEvent {
+    pub id: i32,
+    pub amendment_id: AmendmentId,
+    pub agreement_id: AgreementId,
+    pub event_type: AmendmentEventType,
+    pub timestamp: NaiveDateTime,
+    pub issuer: Owner,
+    pub reason: Option<String>,
+}
+
+#[derive(Clone, Debug, Insertable)]
+#[table_name = "market_agreement_amendment_event"]
+pub struct NewAmendmentEvent {
+    pub amendment_id: AmendmentId,
+    pub agreement_id: AgreementId,
+    pub event_type: AmendmentEventType,
+    pub timestamp: NaiveDateTime,
+    pub issuer: Owner,
+    pub reason: Option<String>,
+}
+
+/// Amendment proposed by Agent through REST API.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewAmendment {
+    /// Demand properties to add or replace in Agreement.
+    pub demand_properties: Option<serde_json::Value>,
+    /// Offer properties to add or replace in Agreement.
+    pub offer_properties: Option<serde_json::Value>,
+    /// Amendment must be approved or rejected by other party before this date.
+    pub valid_to: DateTime<Utc>,
+}
+
+/// Amendment as seen by Agents.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientAmendment {
+    pub amendment_id: AmendmentId,
+    pub agreement_id: String,
+    pub issuer: AmendmentIssuer,
+    pub demand_properties: Option<serde_json::Value>,
+    pub offer_properties: Option<serde_json::Value>,
+    pub state: AmendmentState,
+    pub reason: Option<Reason>,
+    pub creation_date: DateTime<Utc>,
+    pub valid_to: DateTime<Utc>,
+    pub decision_date: Option<DateTime<Utc>>,
+}
+
+/// Event notifying Agents about changes of Agreement Amendments.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmendmentOperationEvent {
+    pub amendment_id: AmendmentId,
+    pub agreement_id: String,
+    pub event_date: DateTime<Utc>,
+    pub event_type: AmendmentEventType,
+    pub issuer: AmendmentIssuer,
+    pub reason: Option<Reason>,
+}
+
+impl Amendment {
+    pub fn new(
+        agreement_id: &AgreementId,
+        amendment: &NewAmendment,
+    ) -> Result<Amendment, ErrorMessage> {
+        Ok(Amendment {
+            id: uuid::Uuid::new_v4().to_simple().to_string(),
+            agreement_id: agreement_id.clone(),
+            issuer: agreement_id.owner(),
+            demand_properties: flatten_properties(&amendment.demand_properties)?,
+            offer_properties: flatten_properties(&amendment.offer_properties)?,
+            state: AmendmentState::Pending,
+            reason: None,
+            creation_ts: Utc::now().naive_utc(),
+            valid_to: amendment.valid_to.naive_utc(),
+            decision_ts: None,
+        })
+    }
+
+    pub fn into_client(self) -> Result<ClientAmendment, ErrorMessage> {
+        Ok(ClientAmendment {
+            amendment_id: self.id,
+            agreement_id: self.agreement_id.into_client(),
+            issuer: owner_to_client(self.issuer),
+            demand_properties: parse_properties(&self.demand_properties)?,
+            offer_properties: parse_properties(&self.offer_properties)?,
+            state: self.state,
+            reason: parse_reason(self.reason),
+            creation_date: DateTime::<Utc>::from_utc(self.creation_ts, Utc),
+            valid_to: DateTime::<Utc>::from_utc(self.valid_to, Utc),
+            decision_date: self
+                .decision_ts
+                .map(|ts| DateTime::<Utc>::from_utc(ts, Utc)),
+        })
+    }
+}
+
+impl NewAmendmentEvent {
+    pub fn new(
+        amendment: &Amendment,
+        agreement_id: &AgreementId,
+        event_type: AmendmentEventType,
+        issuer: Owner,
+    ) -> NewAmendmentEvent {
+        NewAmendmentEvent {
+            amendment_id: amendment.id.clone(),
+            agreement_id: agreement_id.clone(),
+            event_type,
+            timestamp: Utc::now().naive_utc(),
+            issuer,
+            reason: amendment.reason.clone(),
+        }
+    }
+}
+
+impl AmendmentEvent {
+    pub fn into_client(self) -> AmendmentOperationEvent {
+        AmendmentOperationEvent {
+            amendment_id: self.amendment_id,
+            agreement_id: self.agreement_id.into_client(),
+            event_date: DateTime::<Utc>::from_utc(self.timestamp, Utc),
+            event_type: self.event_type,
+            issuer: owner_to_client(self.issuer),
+            reason: parse_reason(self.reason),
+        }
+    }
+}
+
+/// Adds or replaces Agreement properties with properties from Amendment.
+pub fn apply_properties(
+    agreement_properties: &str,
+    amendment_properties: &Option<String>,
+) -> Result<String, String> {
+    let amendment_properties = match amendment_properties {
+        Some(properties) => properties,
+        None => return Ok(agreement_properties.to_string()),
+    };
+
+    let mut properties: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(agreement_properties)
+            .map_err(|e| format!("Can't deserialize Agreement properties. Error: {}", e))?;
+    let changes: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(amendment_properties)
+            .map_err(|e| format!("Can't deserialize Amendment properties. Error: {}", e))?;
+
+    properties.extend(changes);
+    serde_json::to_string(&properties)
+        .map_err(|e| format!("Can't serialize Agreement properties. Error: {}", e))
+}
+
+fn flatten_properties(properties: &Option<serde_json::Value>) -> Result<Option<String>, String> {
+    properties
+        .as_ref()
+        .map(|properties| {
+            serde_json::to_string(&ya_agreement_utils::agreement::flatten(properties.clone()))
+                .map_err(|e| format!("Can't serialize Amendment properties. Error: {}", e))
+        })
+        .transpose()
+}
+
+fn parse_properties(properties: &Option<String>) -> Result<Option<serde_json::Value>, String> {
+    properties
+        .as_ref()
+        .map(|properties| {
+            serde_json::from_str(properties)
+                .map_err(|e| format!("Can't deserialize Amendment properties. Error: {}", e))
+        })
+        .transpose()
+}
+
+fn parse_reason(reason: Option<String>) -> Option<Reason> {
+    reason
+        .map(|reason| serde_json::from_str::<Reason>(&reason))
+        .map(|result| {
+            result
+                .map_err(|e| {
+                    log::warn!(
+                        "Amendment with not parsable Reason in database. Error: {}. Shouldn't happen \
+                         because market is responsible for rejecting invalid Reasons.",
+                        e
+                    )
+                })
+                .ok()
+        })
+        .flatten()
+}
+
+fn owner_to_client(owner: Owner) -> AmendmentIssuer {
+    match owner {
+        Owner::Provider => AmendmentIssuer::Provider,
+        Owner::Requestor => AmendmentIssuer::Requestor,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_properties() {
+        let agreement = r#"{"golem.srv.comp.expiration":1000,"golem.inf.mem.gib":4}"#;
+        let amendment = Some(r#"{"golem.srv.comp.expiration":2000}"#.to_string());
+
+        let result: serde_json::Value =
+            serde_json::from_str(&apply_properties(agreement, &amendment).unwrap()).unwrap();
+        assert_eq!(
+            result,
+            serde_json::json!({"golem.srv.comp.expiration": 2000, "golem.inf.mem.gib": 4})
+        );
+        assert_eq!(apply_properties(agreement, &None).unwrap(), agreement);
+    }
+}
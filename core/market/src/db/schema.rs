@@ -121,10 +121,44 @@ table! {
     }
 }
 
+table! {
+    market_agreement_amendment (id, agreement_id) {
+        id -> Text,
+        agreement_id -> Text,
+        issuer -> Text,
+
+        demand_properties -> Nullable<Text>,
+        offer_properties -> Nullable<Text>,
+
+        state -> Text,
+        reason -> Nullable<Text>,
+
+        creation_ts -> Timestamp,
+        valid_to -> Timestamp,
+        decision_ts -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    market_agreement_amendment_event (id) {
+        id -> Integer,
+        amendment_id -> Text,
+        agreement_id -> Text,
+        event_type -> Text,
+        timestamp -> Timestamp,
+        issuer -> Text,
+        reason -> Nullable<Text>,
+    }
+}
+
 allow_tables_to_appear_in_same_query!(market_demand, market_offer, market_offer_unsubscribed);
 allow_tables_to_appear_in_same_query!(market_proposal, market_negotiation);
 allow_tables_to_appear_in_same_query!(market_agreement, market_agreement_event);
+allow_tables_to_appear_in_same_query!(market_agreement, market_agreement_amendment);
+allow_tables_to_appear_in_same_query!(market_agreement, market_agreement_amendment_event);
 
+joinable!(market_agreement_amendment -> market_agreement (agreement_id));
+joinable!(market_agreement_amendment_event -> market_agreement (agreement_id));
 joinable!(market_agreement_event -> market_agreement (agreement_id));
 joinable!(market_negotiation -> market_agreement (agreement_id));
 joinable!(market_offer -> market_offer_unsubscribed (id));
@@ -14,4 +14,5 @@ mod utils;
 #[cfg(feature = "testing")]
 pub mod testing;
 
+pub use config::{Config as MarketConfig, ConfigError as MarketConfigError, MarketConfigOpts};
 pub use market::MarketService;
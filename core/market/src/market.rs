@@ -2,19 +2,23 @@ use chrono::{DateTime, Utc};
 use lazy_static::lazy_static;
 use metrics::counter;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use thiserror::Error;
 
-use crate::config::Config;
+use crate::config::{Config, ConfigError};
 use crate::db::dao::AgreementDao;
-use crate::db::model::{AgreementId, AppSessionId, SubscriptionId};
+use crate::db::model::{
+    AgreementId, AmendmentId, AmendmentOperationEvent, AppSessionId, ClientAmendment, NewAmendment,
+    SubscriptionId,
+};
 use crate::identity::{IdentityApi, IdentityGSB};
 use crate::matcher::error::{
     DemandError, MatcherError, MatcherInitError, QueryDemandsError, QueryOfferError,
     QueryOffersError,
 };
-use crate::matcher::{store::SubscriptionStore, Matcher};
+use crate::matcher::{store::SubscriptionStore, Matcher, OfferScanPage};
 use crate::negotiation::error::{
-    AgreementError, AgreementEventsError, NegotiationError, NegotiationInitError,
+    AgreementError, AgreementEventsError, AmendmentError, NegotiationError, NegotiationInitError,
 };
 use crate::negotiation::{EventNotifier, ProviderBroker, RequestorBroker};
 use crate::rest_api;
@@ -23,7 +27,7 @@ use ya_client::model::market::{
     Agreement, AgreementOperationEvent as ClientAgreementEvent, Demand, NewDemand, NewOffer, Offer,
     Reason,
 };
-use ya_core_model::market::{local, BUS_ID};
+use ya_core_model::market::{local, MarketSettings, BUS_ID};
 use ya_persistence::executor::DbExecutor;
 use ya_service_api_interfaces::{Provider, Service};
 use ya_service_api_web::middleware::Identity;
@@ -31,21 +35,9 @@ use ya_service_api_web::middleware::Identity;
 use ya_service_api_web::scope::ExtendableScope;
 
 pub mod agreement;
-
-pub struct EnvConfig<'a, T> {
-    pub name: &'a str,
-    pub default: T,
-    pub min: T,
-}
-
-impl<'a> EnvConfig<'a, u64> {
-    pub fn get_value(&self) -> u64 {
-        std::env::var(self.name)
-            .and_then(|v| v.parse::<u64>().map_err(|_| std::env::VarError::NotPresent))
-            .unwrap_or(self.default)
-            .max(self.min)
-    }
-}
+pub mod cleaner;
+pub mod cli;
+pub mod settings;
 
 #[derive(Error, Debug)]
 pub enum MarketError {
@@ -71,6 +63,8 @@ pub enum MarketInitError {
     Negotiation(#[from] NegotiationInitError),
     #[error("Failed to migrate market database. Error: {0}.")]
     Migration(#[from] anyhow::Error),
+    #[error("Failed to load market config. Error: {0}.")]
+    Config(#[from] ConfigError),
 }
 
 /// Structure connecting all market objects.
@@ -79,6 +73,7 @@ pub struct MarketService {
     pub matcher: Matcher,
     pub provider_engine: ProviderBroker,
     pub requestor_engine: RequestorBroker,
+    pub(crate) config: Arc<Config>,
 }
 
 impl MarketService {
@@ -87,6 +82,7 @@ impl MarketService {
         identity_api: Arc<dyn IdentityApi>,
         config: Arc<Config>,
     ) -> Result<Self, MarketInitError> {
+        config.validate()?;
         db.apply_migration(crate::db::migrations::run_with_output)?;
 
         let store = SubscriptionStore::new(db.clone(), config.clone());
@@ -110,8 +106,9 @@ impl MarketService {
             config.clone(),
         )?;
         let cleaner_db = db.clone();
+        let cleaner_config = config.clone();
         tokio::spawn(async move {
-            crate::db::dao::cleaner::clean_forever(cleaner_db).await;
+            crate::db::dao::cleaner::clean_forever(cleaner_db, cleaner_config).await;
         });
 
         Ok(MarketService {
@@ -119,6 +116,7 @@ impl MarketService {
             matcher,
             provider_engine,
             requestor_engine,
+            config,
         })
     }
 
@@ -135,6 +133,14 @@ impl MarketService {
             .bind_gsb(public_prefix, local_prefix)
             .await?;
         agreement::bind_gsb(self.db.clone(), public_prefix, local_prefix).await;
+        settings::bind_gsb(self.matcher.clone(), public_prefix, local_prefix).await;
+        cleaner::bind_gsb(
+            self.db.clone(),
+            self.config.clone(),
+            public_prefix,
+            local_prefix,
+        )
+        .await;
 
         counter!("market.offers.subscribed", 0);
         counter!("market.offers.unsubscribed", 0);
@@ -143,13 +149,25 @@ impl MarketService {
         Ok(())
     }
 
-    pub async fn gsb<Context: Provider<Self, DbExecutor>>(ctx: &Context) -> anyhow::Result<()> {
-        let market = MARKET.get_or_init_market(&ctx.component())?;
+    pub async fn gsb<Context>(ctx: &Context) -> anyhow::Result<()>
+    where
+        Context: Provider<Self, DbExecutor> + Provider<Self, Arc<Config>>,
+    {
+        let market = MARKET.get_or_init_market(
+            &Provider::<Self, DbExecutor>::component(ctx),
+            Provider::<Self, Arc<Config>>::component(ctx),
+        )?;
         Ok(market.bind_gsb(BUS_ID, local::BUS_ID).await?)
     }
 
-    pub fn rest<Context: Provider<Self, DbExecutor>>(ctx: &Context) -> actix_web::Scope {
-        match MARKET.get_or_init_market(&ctx.component()) {
+    pub fn rest<Context>(ctx: &Context) -> actix_web::Scope
+    where
+        Context: Provider<Self, DbExecutor> + Provider<Self, Arc<Config>>,
+    {
+        match MARKET.get_or_init_market(
+            &Provider::<Self, DbExecutor>::component(ctx),
+            Provider::<Self, Arc<Config>>::component(ctx),
+        ) {
             Ok(market) => MarketService::bind_rest(market),
             Err(e) => {
                 log::error!("REST API initialization failed: {}", e);
@@ -166,6 +184,7 @@ impl MarketService {
             .extend(rest_api::common::register_endpoints)
             .extend(rest_api::provider::register_endpoints)
             .extend(rest_api::requestor::register_endpoints)
+            .extend(rest_api::stream::register_endpoints)
     }
 
     // TODO: (re)move this
@@ -185,6 +204,28 @@ impl MarketService {
             .await?)
     }
 
+    pub async fn scan_offers(
+        &self,
+        constraints: &str,
+        offset: usize,
+        limit: Option<i32>,
+    ) -> Result<OfferScanPage, MarketError> {
+        Ok(self.matcher.scan_offers(constraints, offset, limit).await?)
+    }
+
+    pub fn settings(&self) -> MarketSettings {
+        self.matcher.settings()
+    }
+
+    pub fn set_bcast_intervals(
+        &self,
+        offers: Option<Duration>,
+        unsubscribes: Option<Duration>,
+    ) -> Result<MarketSettings, ConfigError> {
+        self.matcher.set_bcast_intervals(offers, unsubscribes)?;
+        Ok(self.matcher.settings())
+    }
+
     pub async fn subscribe_offer(
         &self,
         offer: &NewOffer,
@@ -285,10 +326,103 @@ impl MarketService {
             .terminate_agreement(id, client_agreement_id, reason)
             .await
     }
+
+    pub async fn propose_amendment(
+        &self,
+        id: &Identity,
+        client_agreement_id: String,
+        amendment: NewAmendment,
+    ) -> Result<ClientAmendment, AmendmentError> {
+        self.requestor_engine
+            .common
+            .propose_amendment(id, client_agreement_id, amendment)
+            .await?
+            .into_client()
+            .map_err(|e| AmendmentError::Internal(e.to_string()))
+    }
+
+    pub async fn get_amendment(
+        &self,
+        id: &Identity,
+        client_agreement_id: String,
+        amendment_id: &AmendmentId,
+    ) -> Result<ClientAmendment, AmendmentError> {
+        self.requestor_engine
+            .common
+            .get_amendment(id, client_agreement_id, amendment_id)
+            .await?
+            .into_client()
+            .map_err(|e| AmendmentError::Internal(e.to_string()))
+    }
+
+    pub async fn list_amendments(
+        &self,
+        id: &Identity,
+        client_agreement_id: String,
+    ) -> Result<Vec<ClientAmendment>, AmendmentError> {
+        self.requestor_engine
+            .common
+            .list_amendments(id, client_agreement_id)
+            .await?
+            .into_iter()
+            .map(|amendment| {
+                amendment
+                    .into_client()
+                    .map_err(|e| AmendmentError::Internal(e.to_string()))
+            })
+            .collect()
+    }
+
+    pub async fn approve_amendment(
+        &self,
+        id: &Identity,
+        client_agreement_id: String,
+        amendment_id: &AmendmentId,
+    ) -> Result<ClientAmendment, AmendmentError> {
+        self.requestor_engine
+            .common
+            .approve_amendment(id, client_agreement_id, amendment_id)
+            .await?
+            .into_client()
+            .map_err(|e| AmendmentError::Internal(e.to_string()))
+    }
+
+    pub async fn reject_amendment(
+        &self,
+        id: &Identity,
+        client_agreement_id: String,
+        amendment_id: &AmendmentId,
+        reason: Option<Reason>,
+    ) -> Result<ClientAmendment, AmendmentError> {
+        self.requestor_engine
+            .common
+            .reject_amendment(id, client_agreement_id, amendment_id, reason)
+            .await?
+            .into_client()
+            .map_err(|e| AmendmentError::Internal(e.to_string()))
+    }
+
+    pub async fn query_amendment_events(
+        &self,
+        session_id: &AppSessionId,
+        timeout: f32,
+        max_events: Option<i32>,
+        after_timestamp: DateTime<Utc>,
+        id: &Identity,
+    ) -> Result<Vec<AmendmentOperationEvent>, AgreementEventsError> {
+        Ok(self
+            .requestor_engine
+            .common
+            .query_amendment_events(session_id, timeout, max_events, after_timestamp, id)
+            .await?
+            .into_iter()
+            .map(|event| event.into_client())
+            .collect())
+    }
 }
 
 impl Service for MarketService {
-    type Cli = ();
+    type Cli = cli::MarketCli;
 }
 
 // =========================================== //
@@ -310,13 +444,13 @@ impl StaticMarket {
     pub fn get_or_init_market(
         &self,
         db: &DbExecutor,
+        config: Arc<Config>,
     ) -> Result<Arc<MarketService>, MarketInitError> {
         let mut guarded_market = self.locked_market.lock().unwrap();
         if let Some(market) = &*guarded_market {
             Ok(market.clone())
         } else {
             let identity_api = IdentityGSB::new();
-            let config = Arc::new(Config::default());
             let market = Arc::new(MarketService::new(db, identity_api, config)?);
             *guarded_market = Some(market.clone());
             Ok(market)
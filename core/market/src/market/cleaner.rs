@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use ya_core_model::market::{CleanDatabase, CleanReport, RpcMessageError};
+use ya_persistence::executor::DbExecutor;
+use ya_service_bus::typed::ServiceBinder;
+
+use crate::config::Config;
+use crate::db::dao::cleaner::{clean, CleanerError};
+
+pub async fn bind_gsb(
+    db: DbExecutor,
+    config: Arc<Config>,
+    _public_prefix: &str,
+    local_prefix: &str,
+) {
+    log::trace!("Binding market cleaner local service to service bus");
+    ServiceBinder::new(local_prefix, &db, config).bind_with_processor(clean_database);
+    log::debug!("Successfully bound market cleaner local service to service bus");
+}
+
+async fn clean_database(
+    db: DbExecutor,
+    config: Arc<Config>,
+    _caller: String,
+    msg: CleanDatabase,
+) -> Result<CleanReport, RpcMessageError> {
+    let archive_dir = msg
+        .archive_dir
+        .or_else(|| config.retention.agreements_archive_dir.clone());
+
+    clean(db, &config.retention, msg.dry_run, archive_dir)
+        .await
+        .map_err(|e| match e {
+            CleanerError::Archive(..) => RpcMessageError::BadRequest(e.to_string()),
+            CleanerError::Db(_) => RpcMessageError::Service(e.to_string()),
+        })
+}
@@ -0,0 +1,66 @@
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+use ya_core_model::market::{local, CleanDatabase};
+use ya_service_api::{CliCtx, CommandOutput, ResponseTable};
+use ya_service_bus::{typed as bus, RpcEndpoint};
+
+/// Market management.
+#[derive(StructOpt, Debug)]
+pub enum MarketCli {
+    /// Removes outdated entries from market database according to retention settings.
+    Clean {
+        /// Only report number of entries, that would be removed.
+        #[structopt(long)]
+        dry_run: bool,
+        /// Directory, where removed Agreements are archived as json.
+        /// Overrides `--market-agreements-archive-dir` service setting.
+        #[structopt(long)]
+        archive_dir: Option<PathBuf>,
+    },
+}
+
+impl MarketCli {
+    pub async fn run_command(self, ctx: &CliCtx) -> anyhow::Result<CommandOutput> {
+        match self {
+            MarketCli::Clean {
+                dry_run,
+                archive_dir,
+            } => {
+                let report = bus::service(local::BUS_ID)
+                    .send(CleanDatabase {
+                        dry_run,
+                        archive_dir,
+                    })
+                    .await??;
+                if ctx.json_output {
+                    return CommandOutput::object(report);
+                }
+
+                let header = match report.dry_run {
+                    true => "to remove",
+                    false => "removed",
+                };
+                let mut values = vec![
+                    serde_json::json! {["demands", report.demands]},
+                    serde_json::json! {["offers", report.offers]},
+                    serde_json::json! {["offer unsubscribes", report.offer_unsubscribes]},
+                    serde_json::json! {["negotiations", report.negotiations]},
+                    serde_json::json! {["proposals", report.proposals]},
+                    serde_json::json! {["negotiation events", report.negotiation_events]},
+                    serde_json::json! {["agreements", report.agreements]},
+                    serde_json::json! {["agreement events", report.agreement_events]},
+                ];
+                if let Some(archive) = report.agreements_archive {
+                    values.push(serde_json::json! {["agreements archive", archive]});
+                }
+
+                Ok(ResponseTable {
+                    columns: vec!["entity".to_owned(), header.to_owned()],
+                    values,
+                }
+                .into())
+            }
+        }
+    }
+}
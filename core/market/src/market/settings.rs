@@ -0,0 +1,29 @@
+use std::time::Duration;
+
+use ya_core_model::market::{GetSettings, RpcMessageError, SetBcastIntervals};
+use ya_service_bus::typed::ServiceBinder;
+
+use crate::matcher::Matcher;
+
+pub async fn bind_gsb(matcher: Matcher, _public_prefix: &str, local_prefix: &str) {
+    log::trace!("Binding market settings local service to service bus");
+    ServiceBinder::new(local_prefix, &(), matcher)
+        .bind_with_processor(move |_, matcher, _caller: String, _msg: GetSettings| {
+            let matcher = matcher.clone();
+            async move { Ok(matcher.settings()) }
+        })
+        .bind_with_processor(move |_, matcher, _caller: String, msg: SetBcastIntervals| {
+            let matcher = matcher.clone();
+            async move {
+                matcher
+                    .set_bcast_intervals(
+                        msg.mean_cyclic_bcast_interval_ms.map(Duration::from_millis),
+                        msg.mean_cyclic_unsubscribes_interval_ms
+                            .map(Duration::from_millis),
+                    )
+                    .map_err(|e| RpcMessageError::BadRequest(e.to_string()))?;
+                Ok(matcher.settings())
+            }
+        });
+    log::debug!("Successfully bound market settings local service to service bus");
+}
@@ -1,11 +1,14 @@
 use metrics::counter;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
 
-use ya_client::model::market::{NewDemand, NewOffer};
+use ya_client::model::market::{NewDemand, NewOffer, Offer as ClientOffer};
+use ya_core_model::market::MarketSettings;
 use ya_service_api_web::middleware::Identity;
 
-use crate::config::Config;
+use crate::config::{Config, ConfigError};
 use crate::db::model::{Demand, Offer, SubscriptionId};
 use crate::identity::IdentityApi;
 use crate::protocol::discovery::{builder::DiscoveryBuilder, Discovery};
@@ -16,6 +19,8 @@ pub(crate) mod handlers;
 pub(crate) mod resolver;
 pub(crate) mod store;
 
+pub use cyclic::BcastIntervals;
+use cyclic::BcastIntervalsWatch;
 use error::{MatcherError, MatcherInitError, QueryOffersError};
 use resolver::Resolver;
 use store::SubscriptionStore;
@@ -27,6 +32,16 @@ pub struct RawProposal {
     pub demand: Demand,
 }
 
+/// Page of Offers matching constraints of a scan query.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OfferScanPage {
+    /// Number of all known Offers matching constraints.
+    pub total: usize,
+    pub offset: usize,
+    pub offers: Vec<ClientOffer>,
+}
+
 /// Receivers for events, that can be emitted from Matcher.
 pub struct EventsListeners {
     pub proposal_receiver: UnboundedReceiver<RawProposal>,
@@ -40,6 +55,7 @@ pub struct Matcher {
     discovery: Discovery,
     identity: Arc<dyn IdentityApi>,
     config: Arc<Config>,
+    bcast_intervals: BcastIntervalsWatch,
 }
 
 impl Matcher {
@@ -59,12 +75,14 @@ impl Matcher {
             .add_data_handler(handlers::receive_remote_offers)
             .add_data_handler(handlers::get_local_offers)
             .add_data_handler(handlers::receive_remote_offer_unsubscribes)
+            .add_data_handler(handlers::find_offers_missing_in_digest)
             .build();
 
         let matcher = Matcher {
             store,
             resolver,
             discovery,
+            bcast_intervals: BcastIntervalsWatch::new(&config.discovery),
             config,
             identity: identity_api,
         };
@@ -95,6 +113,36 @@ impl Matcher {
         Ok(())
     }
 
+    /// Settings used by this Matcher including current broadcast intervals.
+    pub fn settings(&self) -> MarketSettings {
+        let intervals = self.bcast_intervals();
+        MarketSettings {
+            mean_cyclic_bcast_interval_ms: intervals.offers.as_millis() as u64,
+            mean_cyclic_unsubscribes_interval_ms: intervals.unsubscribes.as_millis() as u64,
+            ..self.config.to_settings()
+        }
+    }
+
+    pub fn bcast_intervals(&self) -> BcastIntervals {
+        self.bcast_intervals.current()
+    }
+
+    /// Changes mean intervals of cyclic broadcasts. Broadcasts waiting
+    /// for next cycle are rescheduled using new intervals.
+    pub fn set_bcast_intervals(
+        &self,
+        offers: Option<Duration>,
+        unsubscribes: Option<Duration>,
+    ) -> Result<BcastIntervals, ConfigError> {
+        let intervals = self.bcast_intervals.set(offers, unsubscribes)?;
+        log::info!(
+            "Cyclic broadcast intervals changed. Offers: {:?}, unsubscribes: {:?}.",
+            intervals.offers,
+            intervals.unsubscribes
+        );
+        Ok(intervals)
+    }
+
     // =========================================== //
     // Offer/Demand subscription
     // =========================================== //
@@ -193,6 +241,65 @@ impl Matcher {
         Ok(())
     }
 
+    /// Evaluates `constraints` against all active Offers known to this Node.
+    /// This is read-only operation: no Proposals nor negotiation events are
+    /// created, so it can be used to check how many Providers would match Demand.
+    pub async fn scan_offers(
+        &self,
+        constraints: &str,
+        offset: usize,
+        limit: Option<i32>,
+    ) -> Result<OfferScanPage, QueryOffersError> {
+        let limit = limit.unwrap_or(self.config.scan.max_offers_default);
+        if limit <= 0 || limit > self.config.scan.max_offers_max {
+            Err(QueryOffersError::InvalidLimit(
+                limit,
+                self.config.scan.max_offers_max,
+            ))?
+        }
+
+        // Validate constraints upfront, so errors caused by malformed Offers
+        // can be distinguished from caller errors.
+        ya_market_resolver::match_constraints("{}", constraints)
+            .map_err(|e| QueryOffersError::InvalidConstraints(e.to_string()))?;
+
+        let matching = self
+            .store
+            .get_active_offers()
+            .await?
+            .into_iter()
+            .filter(
+                |offer| match resolver::matches_constraints(offer, constraints) {
+                    Ok(matches) => matches,
+                    Err(e) => {
+                        log::warn!("Scanning Offer [{}] error: {}", offer.id, e);
+                        false
+                    }
+                },
+            )
+            .collect::<Vec<_>>();
+
+        let total = matching.len();
+        let offers = matching
+            .into_iter()
+            .skip(offset)
+            .take(limit as usize)
+            .filter_map(|o| match o.into_client_offer() {
+                Err(e) => {
+                    log::error!("Skipping Offer because of: {}", e);
+                    None
+                }
+                Ok(o) => Some(o),
+            })
+            .collect();
+
+        Ok(OfferScanPage {
+            total,
+            offset,
+            offers,
+        })
+    }
+
     pub async fn get_our_active_offer_ids(&self) -> Result<Vec<SubscriptionId>, QueryOffersError> {
         let our_node_ids = self.identity.list().await?;
         Ok(self.store.get_active_offer_ids(Some(our_node_ids)).await?)
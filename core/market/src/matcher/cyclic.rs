@@ -1,43 +1,105 @@
 //! Cyclic methods for Matcher spawned after binding to GSB
+use futures::future::{self, Either};
 use metrics::{counter, timing};
 use rand::seq::IteratorRandom;
 use rand::Rng;
 use std::collections::HashSet;
 use std::hash::Hash;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
 
 use super::Matcher;
+use crate::config::{validate_interval, ConfigError, DiscoveryConfig};
+use crate::protocol::discovery::digest::OfferIdsDigest;
 use std::time::Instant;
 
+/// Mean intervals of cyclic broadcasts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BcastIntervals {
+    pub offers: Duration,
+    pub unsubscribes: Duration,
+}
+
+/// Shares broadcast intervals between Matcher and cyclic broadcasts,
+/// so they can be changed without restarting market.
+#[derive(Clone)]
+pub(crate) struct BcastIntervalsWatch {
+    sender: Arc<watch::Sender<BcastIntervals>>,
+    receiver: watch::Receiver<BcastIntervals>,
+}
+
+impl BcastIntervalsWatch {
+    pub fn new(config: &DiscoveryConfig) -> Self {
+        let (sender, receiver) = watch::channel(BcastIntervals {
+            offers: config.mean_cyclic_bcast_interval,
+            unsubscribes: config.mean_cyclic_unsubscribes_interval,
+        });
+        BcastIntervalsWatch {
+            sender: Arc::new(sender),
+            receiver,
+        }
+    }
+
+    pub fn current(&self) -> BcastIntervals {
+        *self.receiver.borrow()
+    }
+
+    pub fn set(
+        &self,
+        offers: Option<Duration>,
+        unsubscribes: Option<Duration>,
+    ) -> Result<BcastIntervals, ConfigError> {
+        let mut intervals = self.current();
+        if let Some(offers) = offers {
+            validate_interval("mean cyclic broadcast interval", offers)?;
+            intervals.offers = offers;
+        }
+        if let Some(unsubscribes) = unsubscribes {
+            validate_interval("mean cyclic unsubscribes interval", unsubscribes)?;
+            intervals.unsubscribes = unsubscribes;
+        }
+
+        // We keep our own receiver, so broadcast can't fail.
+        self.sender.broadcast(intervals).ok();
+        Ok(intervals)
+    }
+}
+
 pub(super) async fn bcast_offers(matcher: Matcher) {
     if matcher.config.discovery.max_bcasted_offers <= 0 {
         return;
     }
 
-    let bcast_interval = matcher.config.discovery.mean_cyclic_bcast_interval;
+    let mut intervals = matcher.bcast_intervals.receiver.clone();
     loop {
         let matcher = matcher.clone();
+        wait_random_interval(&mut intervals, |intervals| intervals.offers).await;
         async move {
-            wait_random_interval(bcast_interval).await;
-
             let start = Instant::now();
 
-            // We always broadcast our own Offers.
-            let our_ids = matcher.get_our_active_offer_ids().await?;
-
-            // Add some random subset of Offers to broadcast.
-            let num_our_offers = our_ids.len();
-            let num_to_bcast = matcher.config.discovery.max_bcasted_offers;
-
+            // Other Nodes will respond with Offers missing in our digest,
+            // so we don't need to flood network with random Offer ids.
             let all_ids = matcher.store.get_active_offer_ids(None).await?;
-            let our_and_random_ids = randomize_ids(our_ids, all_ids, num_to_bcast as usize);
+            let digest = OfferIdsDigest::new(&all_ids);
+            matcher.discovery.bcast_offers_digest(digest).await?;
+
+            // Older Nodes don't understand digests. We still broadcast our own Offers
+            // ids, so they can get them. Other Offers will reach them through
+            // broadcasts from their owners.
+            let our_ids = matcher.get_our_active_offer_ids().await?;
+            let num_to_bcast = matcher.config.discovery.max_bcasted_offers as usize;
+            let our_ids = randomize_ids(our_ids, vec![], num_to_bcast);
 
             log::trace!(
-                "Broadcasted {} Offers including {} ours.",
-                our_and_random_ids.len(),
-                num_our_offers
+                "Broadcasted digest of {} Offers and {} our Offers ids.",
+                all_ids.len(),
+                our_ids.len()
             );
 
-            matcher.discovery.bcast_offers(our_and_random_ids).await?;
+            if !our_ids.is_empty() {
+                matcher.discovery.bcast_offers(our_ids).await?;
+            }
 
             let end = Instant::now();
             counter!("market.offers.broadcasts", 1);
@@ -56,12 +118,11 @@ pub(super) async fn bcast_unsubscribes(matcher: Matcher) {
         return;
     }
 
-    let bcast_interval = matcher.config.discovery.mean_cyclic_unsubscribes_interval;
+    let mut intervals = matcher.bcast_intervals.receiver.clone();
     loop {
         let matcher = matcher.clone();
+        wait_random_interval(&mut intervals, |intervals| intervals.unsubscribes).await;
         async move {
-            wait_random_interval(bcast_interval).await;
-
             let start = Instant::now();
 
             // We always broadcast our own Offer unsubscribes.
@@ -129,9 +190,30 @@ fn randomize_interval(mean_interval: std::time::Duration) -> std::time::Duration
     (2 * mean_interval).mul_f64(rng.gen::<f64>())
 }
 
-async fn wait_random_interval(mean_interval: std::time::Duration) {
-    let random_interval = randomize_interval(mean_interval);
-    tokio::time::delay_for(random_interval).await;
+/// Waits random time around mean interval selected from `intervals`.
+/// If interval is changed in the meantime, waiting starts over with new value.
+async fn wait_random_interval(
+    intervals: &mut watch::Receiver<BcastIntervals>,
+    select: impl Fn(&BcastIntervals) -> Duration,
+) {
+    loop {
+        let mean_interval = select(&*intervals.borrow());
+        let delay = tokio::time::delay_for(randomize_interval(mean_interval));
+        let changed = async {
+            while let Some(new_intervals) = intervals.recv().await {
+                if select(&new_intervals) != mean_interval {
+                    return;
+                }
+            }
+            // Sender was dropped, so interval won't change anymore.
+            future::pending::<()>().await
+        };
+
+        match future::select(Box::pin(delay), Box::pin(changed)).await {
+            Either::Left(_) => return,
+            Either::Right(_) => log::debug!("Cyclic broadcast interval changed."),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -196,4 +278,19 @@ mod test {
         assert!(offers.contains(&sub3));
         assert_eq!(offers.len(), 3);
     }
+
+    #[test]
+    fn test_set_bcast_intervals() {
+        let intervals = BcastIntervalsWatch::new(&DiscoveryConfig::default());
+
+        let updated = intervals
+            .set(Some(Duration::from_millis(100)), None)
+            .unwrap();
+        assert_eq!(updated.offers, Duration::from_millis(100));
+        assert_eq!(updated.unsubscribes, Duration::from_secs(60));
+        assert_eq!(intervals.clone().current(), updated);
+
+        assert!(intervals.set(None, Some(Duration::from_secs(0))).is_err());
+        assert_eq!(intervals.current(), updated);
+    }
 }
@@ -25,6 +25,10 @@ pub enum QueryOffersError {
     DbError(#[from] DbError),
     #[error("Failed to list Offers based on identity. Error: {0}.")]
     IdentityError(#[from] IdentityError),
+    #[error("Invalid constraints. Error: {0}.")]
+    InvalidConstraints(String),
+    #[error("Invalid limit '{0}', should be between 1 and {1}.")]
+    InvalidLimit(i32, i32),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -6,7 +6,9 @@ use crate::db::model::{Offer, SubscriptionId};
 use crate::matcher::error::ModifyOfferError;
 use crate::protocol::discovery::{
     error::DiscoveryRemoteError,
-    message::{OffersBcast, OffersRetrieved, RetrieveOffers, UnsubscribedOffersBcast},
+    message::{
+        OffersBcast, OffersDigestBcast, OffersRetrieved, RetrieveOffers, UnsubscribedOffersBcast,
+    },
 };
 
 use super::{resolver::Resolver, store::SubscriptionStore};
@@ -26,6 +28,18 @@ pub(super) async fn filter_out_known_offer_ids(
         .map_err(|e| log::warn!("Error filtering Offers. Error: {}", e))?)
 }
 
+/// Returns ids of our active offers, that are missing in digest sent by caller.
+pub(super) async fn find_offers_missing_in_digest(
+    store: SubscriptionStore,
+    _caller: String,
+    msg: OffersDigestBcast,
+) -> Result<Vec<SubscriptionId>, ()> {
+    Ok(store
+        .get_offer_ids_missing_in(&msg.digest)
+        .await
+        .map_err(|e| log::warn!("Error comparing Offers with digest. Error: {}", e))?)
+}
+
 /// Returns only ids of those from input offers, that was successfully stored locally.
 /// Also triggers Resolver to match newly stored Offers against local Demands.
 pub(super) async fn receive_remote_offers(
@@ -1,6 +1,6 @@
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 
-use ya_market_resolver::{match_demand_offer, Match};
+use ya_market_resolver::{match_constraints, match_demand_offer, Match, MatchError};
 
 use super::{error::ResolverError, RawProposal, SubscriptionStore};
 use crate::db::model::{Demand, Offer, SubscriptionId};
@@ -132,13 +132,24 @@ fn matches(offer: &Offer, demand: &Demand) -> bool {
     }
 }
 
+/// Checks if Offer properties fulfill given constraints.
+/// Offer constraints are not taken into account.
+pub(crate) fn matches_constraints(offer: &Offer, constraints: &str) -> Result<bool, MatchError> {
+    match_constraints(&offer.properties, constraints)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::matcher::resolver::matches;
+    use crate::matcher::resolver::{matches, matches_constraints};
     use crate::testing::mock_offer::{sample_demand, sample_offer};
 
     #[test]
     fn matches_empty() {
         assert!(matches(&sample_offer(), &sample_demand()))
     }
+
+    #[test]
+    fn matches_constraints_empty() {
+        assert!(matches_constraints(&sample_offer(), "()").unwrap())
+    }
 }
@@ -1,4 +1,5 @@
 use chrono::{NaiveDateTime, Utc};
+use rand::seq::IteratorRandom;
 use std::sync::Arc;
 
 use ya_client::model::market::{Demand as ClientDemand, NewDemand, NewOffer, Offer as ClientOffer};
@@ -13,6 +14,7 @@ use crate::matcher::error::{
     DemandError, ModifyOfferError, QueryDemandsError, QueryOfferError, QueryOffersError,
     SaveOfferError,
 };
+use crate::protocol::discovery::digest::OfferIdsDigest;
 use std::collections::HashSet;
 
 #[derive(Clone)]
@@ -94,6 +96,21 @@ impl SubscriptionStore {
             .map_err(QueryOffersError::from)?)
     }
 
+    /// Returns random subset of active Offer ids, that aren't included in `digest`.
+    /// Number of returned ids is limited the same way as cyclic broadcasts.
+    pub async fn get_offer_ids_missing_in(
+        &self,
+        digest: &OfferIdsDigest,
+    ) -> Result<Vec<SubscriptionId>, QueryOffersError> {
+        let max_ids = self.config.discovery.max_bcasted_offers as usize;
+        Ok(self
+            .get_active_offer_ids(None)
+            .await?
+            .into_iter()
+            .filter(|offer_id| !digest.contains(offer_id))
+            .choose_multiple(&mut rand::thread_rng(), max_ids))
+    }
+
     pub async fn get_client_offers(
         &self,
         node_id: Option<NodeId>,
@@ -132,6 +149,15 @@ impl SubscriptionStore {
             .map_err(QueryOffersError::from)?)
     }
 
+    pub async fn get_active_offers(&self) -> Result<Vec<Offer>, QueryOffersError> {
+        Ok(self
+            .db
+            .as_dao::<OfferDao>()
+            .get_offers(None, None, None, Utc::now().naive_utc())
+            .await
+            .map_err(QueryOffersError::from)?)
+    }
+
     pub async fn get_offers_before(
         &self,
         inserted_before_ts: NaiveDateTime,
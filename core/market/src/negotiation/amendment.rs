@@ -0,0 +1,553 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use metrics::counter;
+use std::time::{Duration, Instant};
+
+use ya_client::model::market::Reason;
+use ya_client::model::NodeId;
+use ya_service_api_web::middleware::Identity;
+
+use crate::db::dao::{AgreementDao, AmendmentDao, AmendmentDaoError};
+use crate::db::model::{
+    Agreement, AgreementId, AgreementState, Amendment, AmendmentEvent, AmendmentId, AmendmentState,
+    AppSessionId, NewAmendment, Owner,
+};
+use crate::negotiation::error::{AgreementEventsError, AmendmentError};
+use crate::negotiation::notifier::NotifierError;
+use crate::protocol::negotiation::amendment::AmendmentApi;
+use crate::protocol::negotiation::error::{AmendAgreementError, RemoteAmendmentError};
+use crate::protocol::negotiation::messages::{
+    AmendmentApproved, AmendmentProposed, AmendmentRejected,
+};
+use crate::utils::display::EnableDisplay;
+
+use super::common::CommonBroker;
+
+impl CommonBroker {
+    // Called locally via REST
+    pub async fn propose_amendment(
+        &self,
+        id: &Identity,
+        client_agreement_id: String,
+        new_amendment: NewAmendment,
+    ) -> Result<Amendment, AmendmentError> {
+        let agreement = self.get_agreement_by_node(id, &client_agreement_id).await?;
+        if agreement.state != AgreementState::Approved {
+            Err(AmendmentDaoError::AgreementState(
+                agreement.id.clone(),
+                agreement.state,
+            ))?
+        }
+
+        if new_amendment.demand_properties.is_none() && new_amendment.offer_properties.is_none() {
+            return Err(AmendmentError::Invalid(
+                "Amendment should change Demand or Offer properties.".to_string(),
+            ));
+        }
+
+        if new_amendment.valid_to <= Utc::now() {
+            return Err(AmendmentError::Invalid(format!(
+                "Amendment validity [{}] is in the past.",
+                new_amendment.valid_to
+            )));
+        }
+
+        let amendment = Amendment::new(&agreement.id, &new_amendment)
+            .map_err(|e| AmendmentError::Invalid(e.to_string()))?;
+
+        {
+            // Amendment must be saved under lock to avoid races with other party
+            // proposing it's own Amendment at the same time.
+            let _hold = self.agreement_lock.lock(&agreement.id).await;
+            let dao = self.db.as_dao::<AmendmentDao>();
+
+            // Save before sending, so other party's decision can't arrive for Amendment
+            // we don't know yet. Saving fails if there is already pending Amendment.
+            dao.save(amendment.clone(), Utc::now().naive_utc()).await?;
+
+            if let Err(error) = AmendmentApi::propose_amendment(&agreement, &amendment).await {
+                // Other party doesn't know about this Amendment, so we can forget it.
+                dao.remove(&agreement.id, &amendment.id)
+                    .await
+                    .map_err(|e| {
+                        log::warn!(
+                            "Failed to remove not delivered Amendment [{}] for Agreement [{}]. Error: {}",
+                            &amendment.id,
+                            &agreement.id,
+                            e
+                        )
+                    })
+                    .ok();
+                return Err(error.into());
+            }
+        }
+
+        self.notify_agreement(&agreement).await;
+
+        counter!("market.agreements.amendments.proposed", 1);
+        log::info!(
+            "{:?} {} proposed Amendment [{}] for Agreement [{}].",
+            agreement.id.owner(),
+            id.display(),
+            &amendment.id,
+            &agreement.id,
+        );
+        Ok(amendment)
+    }
+
+    pub async fn get_amendment(
+        &self,
+        id: &Identity,
+        client_agreement_id: String,
+        amendment_id: &AmendmentId,
+    ) -> Result<Amendment, AmendmentError> {
+        let agreement = self.get_agreement_by_node(id, &client_agreement_id).await?;
+        self.db
+            .as_dao::<AmendmentDao>()
+            .select(&agreement.id, amendment_id, Utc::now().naive_utc())
+            .await?
+            .ok_or(AmendmentError::NotFound(amendment_id.clone()))
+    }
+
+    pub async fn list_amendments(
+        &self,
+        id: &Identity,
+        client_agreement_id: String,
+    ) -> Result<Vec<Amendment>, AmendmentError> {
+        let agreement = self.get_agreement_by_node(id, &client_agreement_id).await?;
+        Ok(self
+            .db
+            .as_dao::<AmendmentDao>()
+            .list(&agreement.id, Utc::now().naive_utc())
+            .await?)
+    }
+
+    // Called locally via REST
+    pub async fn approve_amendment(
+        &self,
+        id: &Identity,
+        client_agreement_id: String,
+        amendment_id: &AmendmentId,
+    ) -> Result<Amendment, AmendmentError> {
+        let agreement = self.get_agreement_by_node(id, &client_agreement_id).await?;
+        let dao = self.db.as_dao::<AmendmentDao>();
+
+        let amendment = {
+            let _hold = self.agreement_lock.lock(&agreement.id).await;
+
+            let approved_ts = Utc::now().naive_utc();
+            self.get_decidable_amendment(&agreement, amendment_id, approved_ts)
+                .await?;
+
+            // Apply approval locally before notifying other party, so it can't end up
+            // with Agreement we failed to change. Approval not delivered is reverted.
+            let amendment = dao
+                .approve(
+                    &agreement.id,
+                    amendment_id,
+                    agreement.id.owner(),
+                    approved_ts,
+                )
+                .await?;
+            if let Err(error) =
+                AmendmentApi::approve_amendment(&agreement, &amendment, approved_ts).await
+            {
+                self.undo_decision(&agreement, &amendment).await;
+                return Err(error.into());
+            }
+            amendment
+        };
+
+        self.notify_agreement(&agreement).await;
+
+        counter!("market.agreements.amendments.approved", 1);
+        log::info!(
+            "{:?} {} approved Amendment [{}] for Agreement [{}].",
+            agreement.id.owner(),
+            id.display(),
+            &amendment.id,
+            &agreement.id,
+        );
+        Ok(amendment)
+    }
+
+    // Called locally via REST
+    pub async fn reject_amendment(
+        &self,
+        id: &Identity,
+        client_agreement_id: String,
+        amendment_id: &AmendmentId,
+        reason: Option<Reason>,
+    ) -> Result<Amendment, AmendmentError> {
+        let agreement = self.get_agreement_by_node(id, &client_agreement_id).await?;
+        let dao = self.db.as_dao::<AmendmentDao>();
+
+        let amendment = {
+            let _hold = self.agreement_lock.lock(&agreement.id).await;
+
+            let rejected_ts = Utc::now().naive_utc();
+            self.get_decidable_amendment(&agreement, amendment_id, rejected_ts)
+                .await?;
+
+            let amendment = dao
+                .reject(
+                    &agreement.id,
+                    amendment_id,
+                    agreement.id.owner(),
+                    CommonBroker::reason2string(&reason),
+                    rejected_ts,
+                )
+                .await?;
+            if let Err(error) =
+                AmendmentApi::reject_amendment(&agreement, &amendment, reason.clone(), rejected_ts)
+                    .await
+            {
+                self.undo_decision(&agreement, &amendment).await;
+                return Err(error.into());
+            }
+            amendment
+        };
+
+        self.notify_agreement(&agreement).await;
+
+        counter!("market.agreements.amendments.rejected", 1);
+        log::info!(
+            "{:?} {} rejected Amendment [{}] for Agreement [{}]. Reason: {}",
+            agreement.id.owner(),
+            id.display(),
+            &amendment.id,
+            &agreement.id,
+            reason.display(),
+        );
+        Ok(amendment)
+    }
+
+    pub async fn query_amendment_events(
+        &self,
+        session_id: &AppSessionId,
+        timeout: f32,
+        max_events: Option<i32>,
+        after_timestamp: DateTime<Utc>,
+        id: &Identity,
+    ) -> Result<Vec<AmendmentEvent>, AgreementEventsError> {
+        let mut timeout = Duration::from_secs_f32(timeout.max(0.0));
+        let stop_time = Instant::now() + timeout;
+        let max_events = max_events.unwrap_or(self.config.events.max_events_default);
+
+        if max_events <= 0 || max_events > self.config.events.max_events_max {
+            Err(AgreementEventsError::InvalidMaxEvents(
+                max_events,
+                self.config.events.max_events_max,
+            ))?
+        }
+
+        // Amendment events are bound to Agreements, so we can use the same notifier.
+        let mut agreement_notifier = self.session_notifier.listen(session_id);
+        loop {
+            let events = self
+                .db
+                .as_dao::<AmendmentDao>()
+                .select_events(
+                    &id.identity,
+                    session_id,
+                    max_events,
+                    after_timestamp.naive_utc(),
+                )
+                .await
+                .map_err(|e| AgreementEventsError::Internal(e.to_string()))?;
+
+            if events.len() > 0 {
+                return Ok(events);
+            }
+            // Solves panic 'supplied instant is later than self'.
+            if stop_time < Instant::now() {
+                return Ok(vec![]);
+            }
+            timeout = stop_time - Instant::now();
+
+            if let Err(error) = agreement_notifier
+                .wait_for_event_with_timeout(timeout)
+                .await
+            {
+                return match error {
+                    NotifierError::Timeout(_) => Ok(vec![]),
+                    NotifierError::ChannelClosed(_) => {
+                        Err(AgreementEventsError::Internal(error.to_string()))
+                    }
+                    NotifierError::Unsubscribed(_) => Err(AgreementEventsError::Internal(format!(
+                        "Code logic error. Shouldn't get Unsubscribe in Amendment events notifier."
+                    ))),
+                };
+            }
+        }
+    }
+
+    // Called remotely via GSB
+    pub async fn on_amendment_proposed(
+        self,
+        msg: AmendmentProposed,
+        caller: String,
+        caller_role: Owner,
+    ) -> Result<(), AmendAgreementError> {
+        let caller_id = CommonBroker::parse_caller(&caller)?;
+        let agreement_id = msg.agreement_id.clone();
+        let amendment_id = msg.amendment.amendment_id.clone();
+
+        let agreement = {
+            let _hold = self.agreement_lock.lock(&agreement_id).await;
+            let agreement = self
+                .get_agreement_for_caller(&agreement_id, caller_id, caller_role)
+                .await?;
+
+            let amendment = Amendment {
+                id: amendment_id.clone(),
+                agreement_id: agreement_id.clone(),
+                issuer: caller_role,
+                demand_properties: msg.amendment.demand_properties,
+                offer_properties: msg.amendment.offer_properties,
+                state: AmendmentState::Pending,
+                reason: None,
+                creation_ts: msg.amendment.creation_ts,
+                valid_to: msg.amendment.valid_to,
+                decision_ts: None,
+            };
+
+            self.db
+                .as_dao::<AmendmentDao>()
+                .save(amendment, Utc::now().naive_utc())
+                .await
+                .map_err(|e| remote_amendment_error(&agreement_id, &amendment_id, e))?;
+            agreement
+        };
+
+        self.notify_agreement(&agreement).await;
+
+        counter!("market.agreements.amendments.received", 1);
+        log::info!(
+            "Received Amendment [{}] for Agreement [{}] from [{}].",
+            &amendment_id,
+            &agreement_id,
+            &caller_id,
+        );
+        Ok(())
+    }
+
+    // Called remotely via GSB
+    pub async fn on_amendment_approved(
+        self,
+        msg: AmendmentApproved,
+        caller: String,
+        caller_role: Owner,
+    ) -> Result<(), AmendAgreementError> {
+        let caller_id = CommonBroker::parse_caller(&caller)?;
+        let agreement_id = msg.agreement_id.clone();
+
+        let agreement = {
+            let _hold = self.agreement_lock.lock(&agreement_id).await;
+            let agreement = self
+                .get_agreement_for_caller(&agreement_id, caller_id, caller_role)
+                .await?;
+
+            self.db
+                .as_dao::<AmendmentDao>()
+                .approve(
+                    &agreement_id,
+                    &msg.amendment_id,
+                    caller_role,
+                    msg.approved_ts,
+                )
+                .await
+                .map_err(|e| remote_amendment_error(&agreement_id, &msg.amendment_id, e))?;
+            agreement
+        };
+
+        self.notify_agreement(&agreement).await;
+
+        log::info!(
+            "Amendment [{}] for Agreement [{}] approved by [{}].",
+            &msg.amendment_id,
+            &agreement_id,
+            &caller_id,
+        );
+        Ok(())
+    }
+
+    // Called remotely via GSB
+    pub async fn on_amendment_rejected(
+        self,
+        msg: AmendmentRejected,
+        caller: String,
+        caller_role: Owner,
+    ) -> Result<(), AmendAgreementError> {
+        let caller_id = CommonBroker::parse_caller(&caller)?;
+        let agreement_id = msg.agreement_id.clone();
+
+        let agreement = {
+            let _hold = self.agreement_lock.lock(&agreement_id).await;
+            let agreement = self
+                .get_agreement_for_caller(&agreement_id, caller_id, caller_role)
+                .await?;
+
+            self.db
+                .as_dao::<AmendmentDao>()
+                .reject(
+                    &agreement_id,
+                    &msg.amendment_id,
+                    caller_role,
+                    CommonBroker::reason2string(&msg.reason),
+                    msg.rejected_ts,
+                )
+                .await
+                .map_err(|e| remote_amendment_error(&agreement_id, &msg.amendment_id, e))?;
+            agreement
+        };
+
+        self.notify_agreement(&agreement).await;
+
+        log::info!(
+            "Amendment [{}] for Agreement [{}] rejected by [{}]. Reason: {}",
+            &msg.amendment_id,
+            &agreement_id,
+            &caller_id,
+            msg.reason.display(),
+        );
+        Ok(())
+    }
+
+    async fn get_agreement_by_node(
+        &self,
+        id: &Identity,
+        client_agreement_id: &str,
+    ) -> Result<Agreement, AmendmentError> {
+        self.db
+            .as_dao::<AgreementDao>()
+            .select_by_node(
+                client_agreement_id,
+                id.identity.clone(),
+                Utc::now().naive_utc(),
+            )
+            .await
+            .map_err(|e| AmendmentError::GetAgreement(client_agreement_id.to_string(), e))?
+            .ok_or(AmendmentError::AgreementNotFound(
+                client_agreement_id.to_string(),
+            ))
+    }
+
+    /// Makes Amendment pending again, when other party didn't get our decision.
+    /// `agreement` must be taken before the decision was applied.
+    async fn undo_decision(&self, agreement: &Agreement, amendment: &Amendment) {
+        self.db
+            .as_dao::<AmendmentDao>()
+            .undo_decision(agreement, &amendment.id)
+            .await
+            .map_err(|e| {
+                log::warn!(
+                    "Failed to revert not delivered decision about Amendment [{}] for Agreement [{}]. Error: {}",
+                    &amendment.id,
+                    &agreement.id,
+                    e
+                )
+            })
+            .ok();
+    }
+
+    /// Checks if our Agent can approve or reject Amendment, before notifying other party.
+    async fn get_decidable_amendment(
+        &self,
+        agreement: &Agreement,
+        amendment_id: &AmendmentId,
+        validation_ts: NaiveDateTime,
+    ) -> Result<Amendment, AmendmentError> {
+        let amendment = self
+            .db
+            .as_dao::<AmendmentDao>()
+            .select(&agreement.id, amendment_id, validation_ts)
+            .await?
+            .ok_or(AmendmentError::NotFound(amendment_id.clone()))?;
+
+        if amendment.issuer == agreement.id.owner() {
+            Err(AmendmentDaoError::OwnAmendment(amendment_id.clone()))?
+        }
+
+        match amendment.state {
+            AmendmentState::Pending => Ok(amendment),
+            AmendmentState::Expired => Err(AmendmentDaoError::Expired(amendment_id.clone()).into()),
+            state => Err(AmendmentDaoError::AlreadyDecided(amendment_id.clone(), state).into()),
+        }
+    }
+
+    async fn get_agreement_for_caller(
+        &self,
+        agreement_id: &AgreementId,
+        caller_id: NodeId,
+        caller_role: Owner,
+    ) -> Result<Agreement, RemoteAmendmentError> {
+        let agreement = self
+            .db
+            .as_dao::<AgreementDao>()
+            .select(agreement_id, None, Utc::now().naive_utc())
+            .await
+            .map_err(|_e| RemoteAmendmentError::NotFound(agreement_id.clone()))?
+            .ok_or(RemoteAmendmentError::NotFound(agreement_id.clone()))?;
+
+        let auth_id = match caller_role {
+            Owner::Provider => agreement.provider_id,
+            Owner::Requestor => agreement.requestor_id,
+        };
+
+        if auth_id != caller_id {
+            // Don't reveal, that we know this Agreement id.
+            Err(RemoteAmendmentError::NotFound(agreement_id.clone()))?
+        }
+        Ok(agreement)
+    }
+}
+
+/// Creates `AmendmentApi` handling messages sent by `other_party`.
+pub(super) fn amendment_api(broker: &CommonBroker, other_party: Owner) -> AmendmentApi {
+    let broker_proposed = broker.clone();
+    let broker_approved = broker.clone();
+    let broker_rejected = broker.clone();
+
+    AmendmentApi::new(
+        move |caller: String, msg: AmendmentProposed| {
+            broker_proposed
+                .clone()
+                .on_amendment_proposed(msg, caller, other_party)
+        },
+        move |caller: String, msg: AmendmentApproved| {
+            broker_approved
+                .clone()
+                .on_amendment_approved(msg, caller, other_party)
+        },
+        move |caller: String, msg: AmendmentRejected| {
+            broker_rejected
+                .clone()
+                .on_amendment_rejected(msg, caller, other_party)
+        },
+    )
+}
+
+fn remote_amendment_error(
+    agreement_id: &AgreementId,
+    amendment_id: &AmendmentId,
+    error: AmendmentDaoError,
+) -> RemoteAmendmentError {
+    match error {
+        AmendmentDaoError::AgreementNotFound(_) => {
+            RemoteAmendmentError::NotFound(agreement_id.clone())
+        }
+        AmendmentDaoError::NotFound(_) => {
+            RemoteAmendmentError::AmendmentNotFound(agreement_id.clone(), amendment_id.clone())
+        }
+        AmendmentDaoError::DbError(_) | AmendmentDaoError::Apply(..) => {
+            log::warn!(
+                "Failed to process Amendment [{}] for Agreement [{}]. Error: {}",
+                amendment_id,
+                agreement_id,
+                error
+            );
+            RemoteAmendmentError::InternalError(agreement_id.clone())
+        }
+        _ => RemoteAmendmentError::Invalid(amendment_id.clone(), error.to_string()),
+    }
+}
@@ -406,7 +406,7 @@ impl CommonBroker {
         Ok(())
     }
 
-    fn reason2string(reason: &Option<Reason>) -> Option<String> {
+    pub(super) fn reason2string(reason: &Option<Reason>) -> Option<String> {
         reason.as_ref().map(|reason| {
             serde_json::to_string::<Reason>(reason).unwrap_or(reason.message.to_string())
         })
@@ -3,9 +3,10 @@ use thiserror::Error;
 
 use ya_client::model::NodeId;
 
-use crate::db::dao::AgreementDaoError;
+use crate::db::dao::{AgreementDaoError, AmendmentDaoError};
 use crate::db::model::{
-    AgreementId, ProposalId, ProposalIdParseError, SubscriptionId, SubscriptionParseError,
+    AgreementId, AmendmentId, ProposalId, ProposalIdParseError, SubscriptionId,
+    SubscriptionParseError,
 };
 use crate::db::{
     dao::TakeEventsError,
@@ -14,9 +15,9 @@ use crate::db::{
 };
 use crate::matcher::error::{DemandError, QueryOfferError};
 use crate::protocol::negotiation::error::{
-    ApproveAgreementError, CommitAgreementError, CounterProposalError as ProtocolProposalError,
-    GsbAgreementError, NegotiationApiInitError, ProposeAgreementError, RejectProposalError,
-    TerminateAgreementError,
+    AmendAgreementError, ApproveAgreementError, CommitAgreementError,
+    CounterProposalError as ProtocolProposalError, GsbAgreementError, NegotiationApiInitError,
+    ProposeAgreementError, RejectProposalError, TerminateAgreementError,
 };
 
 #[derive(Error, Debug)]
@@ -82,6 +83,24 @@ pub enum AgreementError {
     Internal(String),
 }
 
+#[derive(Error, Debug)]
+pub enum AmendmentError {
+    #[error("Agreement [{0}] not found.")]
+    AgreementNotFound(String),
+    #[error("Amendment [{0}] not found.")]
+    NotFound(AmendmentId),
+    #[error("Failed to get Agreement [{0}]. Error: {1}")]
+    GetAgreement(String, AgreementDaoError),
+    #[error("Invalid Amendment. {0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Dao(#[from] AmendmentDaoError),
+    #[error("Protocol error while amending: {0}")]
+    Protocol(#[from] AmendAgreementError),
+    #[error("Internal error: {0}")]
+    Internal(String),
+}
+
 #[derive(Error, Debug)]
 pub enum WaitForApprovalError {
     #[error("Agreement [{0}] not found.")]
@@ -1,6 +1,6 @@
 use chrono::Utc;
 use futures::stream::StreamExt;
-use metrics::counter;
+use metrics::{counter, timing};
 use std::sync::Arc;
 
 use ya_client::model::market::{event::ProviderEvent, NewProposal, Reason};
@@ -15,8 +15,10 @@ use crate::db::{
     model::{Issuer, Offer, Owner, Proposal, ProposalId, SubscriptionId},
 };
 use crate::matcher::store::SubscriptionStore;
+use crate::protocol::negotiation::amendment::AmendmentApi;
 use crate::protocol::negotiation::{error::*, messages::*, provider::NegotiationApi};
 
+use super::amendment::amendment_api;
 use super::common::CommonBroker;
 use super::error::*;
 use super::notifier::EventNotifier;
@@ -40,6 +42,7 @@ pub enum ApprovalResult {
 pub struct ProviderBroker {
     pub(crate) common: CommonBroker,
     api: NegotiationApi,
+    amendment_api: AmendmentApi,
 }
 
 impl ProviderBroker {
@@ -103,8 +106,11 @@ impl ProviderBroker {
         counter!("market.proposals.provider.rejected.by-them", 0);
         counter!("market.proposals.provider.rejected.by-us", 0);
 
+        let amendment_api = amendment_api(&broker, Owner::Requestor);
+
         Ok(ProviderBroker {
             api,
+            amendment_api,
             common: broker,
         })
     }
@@ -114,7 +120,11 @@ impl ProviderBroker {
         public_prefix: &str,
         local_prefix: &str,
     ) -> Result<(), NegotiationInitError> {
-        Ok(self.api.bind_gsb(public_prefix, local_prefix).await?)
+        self.api.bind_gsb(public_prefix, local_prefix).await?;
+        self.amendment_api
+            .bind_gsb(public_prefix, Owner::Provider)
+            .await?;
+        Ok(())
     }
 
     pub async fn subscribe_offer(&self, _offer: &Offer) -> Result<(), NegotiationError> {
@@ -270,8 +280,13 @@ impl ProviderBroker {
         //
         // Note: There's reason, that it CAN'T be done under lock. If we hold lock whole time
         // Requestor won't be able to cancel his Agreement proposal and he is allowed to do it.
+        let start = Instant::now();
         match self.api.approve_agreement(&agreement, timeout).await {
-            Ok(_) => (),
+            Ok(_) => timing!(
+                "market.agreements.provider.approve.time",
+                start,
+                Instant::now()
+            ),
             // It can turn out, that we are in `Cancelled` state since we weren't under
             // lock during `self.api.approve_agreement` execution. In such a case,
             // we shouldn't return error from here.
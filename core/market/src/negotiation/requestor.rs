@@ -1,8 +1,8 @@
 use chrono::{DateTime, Utc};
 use futures::stream::StreamExt;
-use metrics::counter;
+use metrics::{counter, timing};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc::UnboundedReceiver;
 
 use ya_client::model::market::{event::RequestorEvent, NewProposal, Reason};
@@ -17,8 +17,10 @@ use crate::db::{
     model::{Demand, Issuer, Owner, ProposalId, SubscriptionId},
 };
 use crate::matcher::{store::SubscriptionStore, RawProposal};
+use crate::protocol::negotiation::amendment::AmendmentApi;
 use crate::protocol::negotiation::{error::*, messages::*, requestor::NegotiationApi};
 
+use super::amendment::amendment_api;
 use super::{common::*, error::*, notifier::NotifierError, EventNotifier};
 use crate::config::Config;
 use crate::utils::display::EnableDisplay;
@@ -37,6 +39,7 @@ pub enum ApprovalStatus {
 pub struct RequestorBroker {
     pub(crate) common: CommonBroker,
     api: NegotiationApi,
+    amendment_api: AmendmentApi,
 }
 
 impl RequestorBroker {
@@ -79,14 +82,21 @@ impl RequestorBroker {
             },
         );
 
+        let amendment_api = amendment_api(&broker, Owner::Provider);
+
         let engine = RequestorBroker {
             api,
+            amendment_api,
             common: broker.clone(),
         };
 
         // Initialize counters to 0 value. Otherwise they won't appear on metrics endpoint
         // until first change to value will be made.
         counter!("market.agreements.events.queried", 0);
+        counter!("market.agreements.amendments.proposed", 0);
+        counter!("market.agreements.amendments.received", 0);
+        counter!("market.agreements.amendments.approved", 0);
+        counter!("market.agreements.amendments.rejected", 0);
         counter!("market.agreements.requestor.approved", 0);
         counter!("market.agreements.requestor.cancelled", 0);
         counter!("market.agreements.requestor.confirmed", 0);
@@ -115,6 +125,9 @@ impl RequestorBroker {
         local_prefix: &str,
     ) -> Result<(), NegotiationInitError> {
         self.api.bind_gsb(public_prefix, local_prefix).await?;
+        self.amendment_api
+            .bind_gsb(public_prefix, Owner::Requestor)
+            .await?;
         Ok(())
     }
 
@@ -380,7 +393,13 @@ impl RequestorBroker {
             let signature = "NoSignature".to_string();
             agreement.proposed_signature = Some(signature.clone());
 
+            let start = Instant::now();
             self.api.propose_agreement(&agreement).await?;
+            timing!(
+                "market.agreements.requestor.propose.time",
+                start,
+                Instant::now()
+            );
             dao.confirm(agreement_id, &app_session_id, &signature)
                 .await
                 .map_err(|e| AgreementError::UpdateState(agreement_id.clone(), e))?;
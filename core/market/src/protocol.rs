@@ -7,6 +7,16 @@ macro_rules! PROTOCOL_VERSION {
     };
 }
 
+/// Version of discovery protocol extensions. Nodes supporting it still
+/// understand `PROTOCOL_VERSION` discovery messages, so they can cooperate
+/// with older nodes in the same network.
+#[macro_export]
+macro_rules! DISCOVERY_PROTOCOL_VERSION {
+    () => {
+        "mk2"
+    };
+}
+
 pub mod callback;
 pub mod discovery;
 pub mod negotiation;
@@ -16,10 +16,12 @@ use crate::db::model::{Offer as ModelOffer, SubscriptionId};
 use crate::identity::{IdentityApi, IdentityError};
 
 pub mod builder;
+pub mod digest;
 pub mod error;
 pub mod message;
 
-use crate::PROTOCOL_VERSION;
+use crate::{DISCOVERY_PROTOCOL_VERSION, PROTOCOL_VERSION};
+use digest::OfferIdsDigest;
 use error::*;
 use message::*;
 
@@ -41,6 +43,7 @@ pub struct DiscoveryImpl {
     offer_handlers: Mutex<OfferHandlers>,
     get_local_offers_handler: HandlerSlot<RetrieveOffers>,
     offer_unsubscribe_handler: HandlerSlot<UnsubscribedOffersBcast>,
+    offers_digest_handler: HandlerSlot<OffersDigestBcast>,
 }
 
 impl Discovery {
@@ -57,6 +60,34 @@ impl Discovery {
         Ok(())
     }
 
+    /// Broadcasts digest of Offers known to us. Nodes, that have Offers
+    /// not included in digest, will respond with `MissingOffers`.
+    pub async fn bcast_offers_digest(&self, digest: OfferIdsDigest) -> Result<(), DiscoveryError> {
+        let default_id = self.default_identity().await?;
+        let bcast_msg = SendBroadcastMessage::new(OffersDigestBcast { digest });
+
+        let _ = bus::service(local_net::BUS_ID)
+            .send_as(default_id, bcast_msg)
+            .await?;
+        Ok(())
+    }
+
+    /// Tells remote Node about Offers, that were missing in its digest.
+    pub async fn send_missing_offers(
+        &self,
+        target_node_id: String,
+        offer_ids: Vec<SubscriptionId>,
+    ) -> Result<(), DiscoveryError> {
+        let target_node = NodeId::from_str(&target_node_id)
+            .map_err(|e| DiscoveryError::InternalError(e.to_string()))?;
+
+        Ok(net::from(self.default_identity().await?)
+            .to(target_node)
+            .service(&missing_offers_addr(BUS_ID))
+            .send(MissingOffers { offer_ids })
+            .await??)
+    }
+
     /// Ask remote Node for specified Offers.
     pub async fn get_remote_offers(
         &self,
@@ -93,8 +124,43 @@ impl Discovery {
         public_prefix: &str,
         local_prefix: &str,
     ) -> Result<(), DiscoveryInitError> {
-        log::info!("Discovery protocol version: {}", PROTOCOL_VERSION!());
+        log::info!(
+            "Discovery protocol version: {} (compatible with {})",
+            DISCOVERY_PROTOCOL_VERSION!(),
+            PROTOCOL_VERSION!()
+        );
+
+        self.bind_gsb_legacy(public_prefix, local_prefix).await?;
 
+        let myself = self.clone();
+        // /local/market/market-protocol-discovery-mk2-offers-digest
+        let bcast_address = format!("{}/{}", local_prefix, OffersDigestBcast::TOPIC);
+        ya_net::bind_broadcast_with_caller(
+            &bcast_address,
+            move |caller, msg: SendBroadcastMessage<OffersDigestBcast>| {
+                let myself = myself.clone();
+                myself.on_bcast_offers_digest(caller, msg.body().to_owned())
+            },
+        )
+        .await
+        .map_err(|e| DiscoveryInitError::from_pair(bcast_address, e))?;
+
+        ServiceBinder::new(&missing_offers_addr(public_prefix), &(), self.clone())
+            .bind_with_processor(move |_, myself, caller: String, msg: MissingOffers| {
+                let myself = myself.clone();
+                myself.on_missing_offers(caller, msg)
+            });
+
+        Ok(())
+    }
+
+    /// Binds only handlers of `PROTOCOL_VERSION` messages. Node bound this way
+    /// doesn't understand Offers digests, so it can be used to emulate older Nodes.
+    pub async fn bind_gsb_legacy(
+        &self,
+        public_prefix: &str,
+        local_prefix: &str,
+    ) -> Result<(), DiscoveryInitError> {
         let myself = self.clone();
         // /local/market/market-protocol-mk1-offer
         let bcast_address = format!("{}/{}", local_prefix, OffersBcast::TOPIC);
@@ -137,36 +203,7 @@ impl Discovery {
             log::trace!("Received {} Offers from [{}].", num_ids_received, &caller);
         }
 
-        // We should do filtering and getting Offers in single transaction. Otherwise multiple
-        // broadcasts can overlap and we will ask other nodes for the same Offers more than once.
-        // Note that it wouldn't cause incorrect behavior, because we will add Offers only once.
-        // Other attempts to add them will end with error and we will filter all Offers, that already
-        // occurred and re-broadcast only new ones.
-        // But still it is worth to limit network traffic.
-        let new_offer_ids = {
-            let offer_handlers = self.inner.offer_handlers.lock().await;
-            let filter_out_known_ids = offer_handlers.filter_out_known_ids.clone();
-            let receive_remote_offers = offer_handlers.receive_remote_offers.clone();
-
-            let unknown_offer_ids = filter_out_known_ids.call(caller.clone(), msg).await?;
-
-            if !unknown_offer_ids.is_empty() {
-                let offers = self
-                    .get_remote_offers(caller.clone(), unknown_offer_ids)
-                    .await
-                    .map_err(|e| {
-                        log::debug!("Can't get Offers from [{}]. Error: {}", &caller, e)
-                    })?;
-
-                // We still could fail to add some Offers to database. If we fail to add them, we don't
-                // want to propagate subscription further.
-                receive_remote_offers
-                    .call(caller.clone(), OffersRetrieved { offers })
-                    .await?
-            } else {
-                vec![]
-            }
-        };
+        let new_offer_ids = self.retrieve_unknown_offers(caller.clone(), msg).await?;
 
         if !new_offer_ids.is_empty() {
             log::debug!(
@@ -186,6 +223,93 @@ impl Discovery {
         Ok(())
     }
 
+    /// Returns ids of Offers, that were retrieved from `caller` and saved locally.
+    async fn retrieve_unknown_offers(
+        &self,
+        caller: String,
+        msg: OffersBcast,
+    ) -> Result<Vec<SubscriptionId>, ()> {
+        // We should do filtering and getting Offers in single transaction. Otherwise multiple
+        // broadcasts can overlap and we will ask other nodes for the same Offers more than once.
+        // Note that it wouldn't cause incorrect behavior, because we will add Offers only once.
+        // Other attempts to add them will end with error and we will filter all Offers, that already
+        // occurred and re-broadcast only new ones.
+        // But still it is worth to limit network traffic.
+        let offer_handlers = self.inner.offer_handlers.lock().await;
+        let filter_out_known_ids = offer_handlers.filter_out_known_ids.clone();
+        let receive_remote_offers = offer_handlers.receive_remote_offers.clone();
+
+        let unknown_offer_ids = filter_out_known_ids.call(caller.clone(), msg).await?;
+        if unknown_offer_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let offers = self
+            .get_remote_offers(caller.clone(), unknown_offer_ids)
+            .await
+            .map_err(|e| log::debug!("Can't get Offers from [{}]. Error: {}", &caller, e))?;
+
+        // We still could fail to add some Offers to database. If we fail to add them, we don't
+        // want to propagate subscription further.
+        receive_remote_offers
+            .call(caller.clone(), OffersRetrieved { offers })
+            .await
+    }
+
+    async fn on_bcast_offers_digest(
+        self,
+        caller: String,
+        msg: OffersDigestBcast,
+    ) -> Result<(), ()> {
+        if !msg.digest.is_valid() {
+            log::debug!("Received invalid Offers digest from [{}].", &caller);
+            return Ok(());
+        }
+
+        let offers_digest_handler = self.inner.offers_digest_handler.clone();
+        let missing_offer_ids = offers_digest_handler.call(caller.clone(), msg).await?;
+
+        if !missing_offer_ids.is_empty() {
+            log::trace!(
+                "Sending {} Offers missing in digest to [{}].",
+                missing_offer_ids.len(),
+                &caller
+            );
+
+            self.send_missing_offers(caller.clone(), missing_offer_ids)
+                .await
+                .map_err(|e| {
+                    log::debug!("Can't send missing Offers to [{}]. Error: {}", &caller, e)
+                })?;
+        }
+        Ok(())
+    }
+
+    async fn on_missing_offers(
+        self,
+        caller: String,
+        msg: MissingOffers,
+    ) -> Result<(), DiscoveryRemoteError> {
+        log::trace!(
+            "[{}] has {} Offers missing in our digest.",
+            &caller,
+            msg.offer_ids.len()
+        );
+
+        // Sender shouldn't wait until we retrieve Offers from him.
+        // We don't propagate these Offers, since other Nodes will ask
+        // for them using their own digests.
+        tokio::task::spawn_local(async move {
+            let offers_bcast = OffersBcast {
+                offer_ids: msg.offer_ids,
+            };
+            self.retrieve_unknown_offers(caller, offers_bcast)
+                .await
+                .ok();
+        });
+        Ok(())
+    }
+
     async fn on_get_remote_offers(
         self,
         caller: String,
@@ -68,6 +68,7 @@ impl DiscoveryBuilder {
                 offer_handlers,
                 get_local_offers_handler: self.get_handler(),
                 offer_unsubscribe_handler: self.get_handler(),
+                offers_digest_handler: self.get_handler(),
             }),
         }
     }
@@ -134,13 +135,14 @@ mod test {
     }
 
     #[test]
-    fn build_from_with_four_handlers_should_pass() {
+    fn build_from_with_five_handlers_should_pass() {
         DiscoveryBuilder::default()
             .add_data(MockIdentity::new("test") as Arc<dyn IdentityApi>)
             .add_handler(|_, _: OffersRetrieved| async { Ok(vec![]) })
             .add_handler(|_, _: UnsubscribedOffersBcast| async { Ok(vec![]) })
             .add_handler(|_, _: OffersBcast| async { Ok(vec![]) })
             .add_handler(|_, _: RetrieveOffers| async { Ok(vec![]) })
+            .add_handler(|_, _: OffersDigestBcast| async { Ok(vec![]) })
             .build();
     }
 
@@ -153,6 +155,7 @@ mod test {
             .add_data_handler(|_: &str, _, _: UnsubscribedOffersBcast| async { Ok(vec![]) })
             .add_handler(|_, _: OffersBcast| async { Ok(vec![]) })
             .add_data_handler(|_: &str, _, _: RetrieveOffers| async { Ok(vec![]) })
+            .add_data_handler(|_: &str, _, _: OffersDigestBcast| async { Ok(vec![]) })
             .build();
     }
 
@@ -178,6 +181,7 @@ mod test {
                 }
             })
             .add_handler(|_, _: OffersBcast| async { Ok(vec![]) })
+            .add_handler(|_, _: OffersDigestBcast| async { Ok(vec![]) })
             .build();
 
         assert_eq!(0, counter.load(SeqCst));
@@ -0,0 +1,146 @@
+//! Compact representation of set of Offer ids known to Node.
+use digest::Digest;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha3::Sha3_256;
+use std::convert::TryInto;
+
+use crate::db::model::SubscriptionId;
+
+/// Expected ratio of ids, that will be falsely reported as contained in digest.
+/// Falsely reported Offers won't be synced in this round, but each digest uses
+/// different seed, so they will be synced in next rounds.
+const FALSE_POSITIVE_RATE: f64 = 0.01;
+const MIN_NUM_BITS: usize = 64;
+/// Limits computations we are willing to make for digest received from remote Node.
+pub const MAX_NUM_HASHES: u32 = 16;
+/// Digests are built from all active Offers known to Node. We don't expect more
+/// Offers in the network, so larger digests received from remote Nodes are rejected.
+pub const MAX_NUM_IDS: usize = 100_000;
+
+/// Bloom filter of Offer ids.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OfferIdsDigest {
+    seed: u64,
+    num_hashes: u32,
+    bits: Vec<u64>,
+}
+
+impl OfferIdsDigest {
+    pub fn new(offer_ids: &[SubscriptionId]) -> OfferIdsDigest {
+        let num_ids = offer_ids.len().max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+
+        let num_bits = num_bits(offer_ids.len());
+        let num_hashes = ((num_bits as f64 / num_ids) * ln2).round() as u32;
+
+        let mut digest = OfferIdsDigest {
+            seed: rand::thread_rng().gen(),
+            num_hashes: num_hashes.max(1).min(MAX_NUM_HASHES),
+            bits: vec![0; num_words(num_bits)],
+        };
+
+        for offer_id in offer_ids {
+            for position in digest.positions(offer_id) {
+                digest.bits[position / 64] |= 1 << (position % 64);
+            }
+        }
+        digest
+    }
+
+    /// Can return true for ids, that weren't inserted into digest, but never
+    /// returns false for inserted ids.
+    pub fn contains(&self, offer_id: &SubscriptionId) -> bool {
+        self.positions(offer_id)
+            .all(|position| self.bits[position / 64] & (1 << (position % 64)) != 0)
+    }
+
+    /// Digest received from other Node can be malformed.
+    pub fn is_valid(&self) -> bool {
+        !self.bits.is_empty()
+            && self.bits.len() <= num_words(num_bits(MAX_NUM_IDS))
+            && self.num_hashes > 0
+            && self.num_hashes <= MAX_NUM_HASHES
+    }
+
+    fn positions(&self, offer_id: &SubscriptionId) -> impl Iterator<Item = usize> {
+        let hash = Sha3_256::new()
+            .chain(self.seed.to_le_bytes())
+            .chain(offer_id.to_string().as_bytes())
+            .result();
+
+        // Double hashing: i-th hash function is computed as h1 + i * h2.
+        let h1 = u64::from_le_bytes(hash[0..8].try_into().unwrap());
+        let h2 = u64::from_le_bytes(hash[8..16].try_into().unwrap()) | 1;
+        let num_bits = (self.bits.len() * 64) as u64;
+
+        (0..self.num_hashes as u64)
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % num_bits) as usize)
+    }
+}
+
+/// Size of filter giving expected `FALSE_POSITIVE_RATE` for given number of ids.
+fn num_bits(num_ids: usize) -> usize {
+    let num_ids = num_ids.max(1) as f64;
+    let ln2 = std::f64::consts::LN_2;
+
+    let num_bits = (-num_ids * FALSE_POSITIVE_RATE.ln() / (ln2 * ln2)).ceil() as usize;
+    num_bits.max(MIN_NUM_BITS)
+}
+
+fn num_words(num_bits: usize) -> usize {
+    (num_bits + 63) / 64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_offer::generate_offer_ids;
+
+    #[test]
+    fn test_digest_contains_all_inserted_ids() {
+        let ids = generate_offer_ids(500);
+        let digest = OfferIdsDigest::new(&ids);
+
+        assert!(digest.is_valid());
+        assert!(ids.iter().all(|id| digest.contains(id)));
+    }
+
+    #[test]
+    fn test_digest_false_positive_rate() {
+        let digest = OfferIdsDigest::new(&generate_offer_ids(1000));
+        let false_positives = generate_offer_ids(1000)
+            .iter()
+            .filter(|id| digest.contains(id))
+            .count();
+
+        // Expected value is 10. Leave some margin to avoid flaky test.
+        assert!(false_positives < 50, "{} false positives", false_positives);
+    }
+
+    #[test]
+    fn test_empty_digest() {
+        let digest = OfferIdsDigest::new(&[]);
+
+        assert!(digest.is_valid());
+        assert!(!generate_offer_ids(10).iter().any(|id| digest.contains(id)));
+    }
+
+    #[test]
+    fn test_malformed_digest_is_invalid() {
+        let mut digest = OfferIdsDigest::new(&generate_offer_ids(10));
+        digest.num_hashes = MAX_NUM_HASHES + 1;
+        assert!(!digest.is_valid());
+
+        let mut digest = OfferIdsDigest::new(&generate_offer_ids(10));
+        digest.bits = vec![];
+        assert!(!digest.is_valid());
+
+        let mut digest = OfferIdsDigest::new(&generate_offer_ids(10));
+        digest.bits = vec![0; num_words(num_bits(MAX_NUM_IDS)) + 1];
+        assert!(!digest.is_valid());
+        digest.bits = vec![0; num_words(num_bits(MAX_NUM_IDS))];
+        assert!(digest.is_valid());
+    }
+}
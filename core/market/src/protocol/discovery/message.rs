@@ -6,6 +6,7 @@ use ya_service_bus::RpcMessage;
 use crate::db::model::{Offer as ModelOffer, SubscriptionId};
 
 use super::super::callback::CallbackMessage;
+use super::digest::OfferIdsDigest;
 use super::DiscoveryRemoteError;
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -46,6 +47,51 @@ impl RpcMessage for RetrieveOffers {
     type Error = DiscoveryRemoteError;
 }
 
+/// Digest of all active Offers known to sender. Replaces broadcasting
+/// random samples of Offer ids in cyclic broadcasts.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OffersDigestBcast {
+    pub digest: OfferIdsDigest,
+}
+
+/// Local handler will return ids of known Offers, that are missing in digest.
+/// Those will be sent directly to the bcast sender.
+impl CallbackMessage for OffersDigestBcast {
+    type Ok = Vec<SubscriptionId>;
+    type Error = ();
+}
+
+impl BroadcastMessage for OffersDigestBcast {
+    const TOPIC: &'static str = concat!(
+        "market-protocol-discovery-",
+        DISCOVERY_PROTOCOL_VERSION!(),
+        "-offers-digest"
+    );
+}
+
+pub(super) fn missing_offers_addr(prefix: &str) -> String {
+    format!(
+        "{}/protocol/{}/discovery/missing-offers",
+        prefix,
+        DISCOVERY_PROTOCOL_VERSION!()
+    )
+}
+
+/// Response to `OffersDigestBcast` with ids of Offers, that digest sender doesn't know.
+/// Receiver retrieves them the same way as Offers from `OffersBcast`.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingOffers {
+    pub offer_ids: Vec<SubscriptionId>,
+}
+
+impl RpcMessage for MissingOffers {
+    const ID: &'static str = "Missing";
+    type Item = ();
+    type Error = DiscoveryRemoteError;
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OffersRetrieved {
@@ -0,0 +1,197 @@
+use std::sync::Arc;
+
+use chrono::NaiveDateTime;
+
+use ya_client::model::market::Reason;
+use ya_core_model::market::BUS_ID;
+use ya_net::{self as net, RemoteEndpoint};
+use ya_service_bus::{typed::ServiceBinder, RpcEndpoint, RpcMessage};
+
+use crate::db::model::{Agreement, Amendment, Owner};
+
+use super::super::callback::{CallbackHandler, HandlerSlot};
+use super::error::{AmendAgreementError, GsbAgreementError, NegotiationApiInitError};
+use super::messages::{
+    provider, requestor, AmendmentApproved, AmendmentContent, AmendmentProposed, AmendmentRejected,
+};
+
+/// Responsible for communication with markets on other nodes
+/// while amending already approved Agreements.
+/// Unlike negotiation, both Provider and Requestor can propose changes,
+/// so the same api is used on both sides.
+#[derive(Clone)]
+pub struct AmendmentApi {
+    inner: Arc<AmendmentImpl>,
+}
+
+struct AmendmentImpl {
+    amendment_proposed: HandlerSlot<AmendmentProposed>,
+    amendment_approved: HandlerSlot<AmendmentApproved>,
+    amendment_rejected: HandlerSlot<AmendmentRejected>,
+}
+
+impl AmendmentApi {
+    pub fn new(
+        amendment_proposed: impl CallbackHandler<AmendmentProposed>,
+        amendment_approved: impl CallbackHandler<AmendmentApproved>,
+        amendment_rejected: impl CallbackHandler<AmendmentRejected>,
+    ) -> AmendmentApi {
+        let amendment_impl = AmendmentImpl {
+            amendment_proposed: HandlerSlot::new(amendment_proposed),
+            amendment_approved: HandlerSlot::new(amendment_approved),
+            amendment_rejected: HandlerSlot::new(amendment_rejected),
+        };
+        AmendmentApi {
+            inner: Arc::new(amendment_impl),
+        }
+    }
+
+    /// Sends Amendment proposed by our Agent to the other party.
+    pub async fn propose_amendment(
+        agreement: &Agreement,
+        amendment: &Amendment,
+    ) -> Result<(), AmendAgreementError> {
+        let msg = AmendmentProposed {
+            agreement_id: agreement.id.clone().swap_owner(),
+            amendment: AmendmentContent::from(amendment),
+        };
+        send_to_other_party(agreement, msg).await
+    }
+
+    pub async fn approve_amendment(
+        agreement: &Agreement,
+        amendment: &Amendment,
+        approved_ts: NaiveDateTime,
+    ) -> Result<(), AmendAgreementError> {
+        let msg = AmendmentApproved {
+            agreement_id: agreement.id.clone().swap_owner(),
+            amendment_id: amendment.id.clone(),
+            approved_ts,
+        };
+        send_to_other_party(agreement, msg).await
+    }
+
+    pub async fn reject_amendment(
+        agreement: &Agreement,
+        amendment: &Amendment,
+        reason: Option<Reason>,
+        rejected_ts: NaiveDateTime,
+    ) -> Result<(), AmendAgreementError> {
+        let msg = AmendmentRejected {
+            agreement_id: agreement.id.clone().swap_owner(),
+            amendment_id: amendment.id.clone(),
+            reason,
+            rejected_ts,
+        };
+        send_to_other_party(agreement, msg).await
+    }
+
+    async fn on_amendment_proposed(
+        self,
+        caller: String,
+        msg: AmendmentProposed,
+        owner: Owner,
+    ) -> Result<(), AmendAgreementError> {
+        log::debug!(
+            "Amendment API: Amendment [{}] for Agreement [{}] proposed by [{}].",
+            &msg.amendment.amendment_id,
+            &msg.agreement_id,
+            &caller
+        );
+        self.inner
+            .amendment_proposed
+            .call(caller, msg.translate(owner))
+            .await
+    }
+
+    async fn on_amendment_approved(
+        self,
+        caller: String,
+        msg: AmendmentApproved,
+        owner: Owner,
+    ) -> Result<(), AmendAgreementError> {
+        log::debug!(
+            "Amendment API: Amendment [{}] for Agreement [{}] approved by [{}].",
+            &msg.amendment_id,
+            &msg.agreement_id,
+            &caller
+        );
+        self.inner
+            .amendment_approved
+            .call(caller, msg.translate(owner))
+            .await
+    }
+
+    async fn on_amendment_rejected(
+        self,
+        caller: String,
+        msg: AmendmentRejected,
+        owner: Owner,
+    ) -> Result<(), AmendAgreementError> {
+        log::debug!(
+            "Amendment API: Amendment [{}] for Agreement [{}] rejected by [{}].",
+            &msg.amendment_id,
+            &msg.agreement_id,
+            &caller
+        );
+        self.inner
+            .amendment_rejected
+            .call(caller, msg.translate(owner))
+            .await
+    }
+
+    /// Binds handlers on address of `owner` side of negotiations.
+    pub async fn bind_gsb(
+        &self,
+        public_prefix: &str,
+        owner: Owner,
+    ) -> Result<(), NegotiationApiInitError> {
+        let addr = match owner {
+            Owner::Provider => provider::amendment_addr(public_prefix),
+            Owner::Requestor => requestor::amendment_addr(public_prefix),
+        };
+
+        ServiceBinder::new(&addr, &(), self.clone())
+            .bind_with_processor(move |_, myself, caller: String, msg: AmendmentProposed| {
+                let myself = myself.clone();
+                myself.on_amendment_proposed(caller, msg, owner)
+            })
+            .bind_with_processor(move |_, myself, caller: String, msg: AmendmentApproved| {
+                let myself = myself.clone();
+                myself.on_amendment_approved(caller, msg, owner)
+            })
+            .bind_with_processor(move |_, myself, caller: String, msg: AmendmentRejected| {
+                let myself = myself.clone();
+                myself.on_amendment_rejected(caller, msg, owner)
+            });
+        Ok(())
+    }
+}
+
+async fn send_to_other_party<Msg>(
+    agreement: &Agreement,
+    msg: Msg,
+) -> Result<(), AmendAgreementError>
+where
+    Msg: RpcMessage<Item = (), Error = AmendAgreementError> + Unpin,
+{
+    let (service, sender, receiver) = match agreement.id.owner() {
+        Owner::Requestor => (
+            provider::amendment_addr(BUS_ID),
+            agreement.requestor_id,
+            agreement.provider_id,
+        ),
+        Owner::Provider => (
+            requestor::amendment_addr(BUS_ID),
+            agreement.provider_id,
+            agreement.requestor_id,
+        ),
+    };
+    net::from(sender)
+        .to(receiver)
+        .service(&service)
+        .send(msg)
+        .await
+        .map_err(|e| GsbAgreementError(e.to_string(), agreement.id.clone()))??;
+    Ok(())
+}
@@ -150,6 +150,29 @@ pub enum RemoteAgreementError {
     InternalError(AgreementId),
 }
 
+#[derive(Error, Debug, Serialize, Deserialize)]
+pub enum AmendAgreementError {
+    #[error("Amend {0}.")]
+    Gsb(#[from] GsbAgreementError),
+    #[error("Remote Amend: {0}")]
+    Remote(#[from] RemoteAmendmentError),
+    #[error(transparent)]
+    CallerParse(#[from] CallerParseError),
+}
+
+#[derive(Error, Debug, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum RemoteAmendmentError {
+    #[error("Agreement [{0}] not found.")]
+    NotFound(AgreementId),
+    #[error("Amendment [{1}] for Agreement [{0}] not found.")]
+    AmendmentNotFound(AgreementId, String),
+    #[error("Amendment [{0}] rejected. {1}")]
+    Invalid(String, String),
+    #[error("Can't process Amendment for Agreement [{0}] due to internal error.")]
+    InternalError(AgreementId),
+}
+
 #[derive(Error, Debug, Serialize, Deserialize)]
 pub enum CommitAgreementError {
     #[error("Commit Agreement {0}.")]
@@ -4,14 +4,17 @@ use serde::{Deserialize, Serialize};
 use ya_client::model::market::Reason;
 use ya_service_bus::RpcMessage;
 
-use crate::db::model::{AgreementId, DbProposal, Owner, Proposal, ProposalId, SubscriptionId};
+use crate::db::model::{
+    AgreementId, Amendment, AmendmentId, DbProposal, Owner, Proposal, ProposalId, SubscriptionId,
+};
 use crate::protocol::negotiation::error::{
     CommitAgreementError, ProposeAgreementError, RejectProposalError,
 };
 
 use super::super::callback::CallbackMessage;
 use super::error::{
-    ApproveAgreementError, CounterProposalError, GsbAgreementError, TerminateAgreementError,
+    AmendAgreementError, ApproveAgreementError, CounterProposalError, GsbAgreementError,
+    TerminateAgreementError,
 };
 
 pub mod provider {
@@ -30,6 +33,14 @@ pub mod provider {
             PROTOCOL_VERSION!()
         )
     }
+
+    pub fn amendment_addr(prefix: &str) -> String {
+        format!(
+            "{}/protocol/{}/negotiation/provider/amendment",
+            prefix,
+            PROTOCOL_VERSION!()
+        )
+    }
 }
 
 pub mod requestor {
@@ -48,6 +59,14 @@ pub mod requestor {
             PROTOCOL_VERSION!()
         )
     }
+
+    pub fn amendment_addr(prefix: &str) -> String {
+        format!(
+            "{}/protocol/{}/negotiation/requestor/amendment",
+            prefix,
+            PROTOCOL_VERSION!()
+        )
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -205,6 +224,59 @@ impl RpcMessage for AgreementCommitted {
     type Error = CommitAgreementError;
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmendmentContent {
+    pub amendment_id: AmendmentId,
+    pub demand_properties: Option<String>,
+    pub offer_properties: Option<String>,
+
+    pub creation_ts: NaiveDateTime,
+    pub valid_to: NaiveDateTime,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmendmentProposed {
+    pub agreement_id: AgreementId,
+    pub amendment: AmendmentContent,
+}
+
+impl RpcMessage for AmendmentProposed {
+    const ID: &'static str = "AmendmentProposed";
+    type Item = ();
+    type Error = AmendAgreementError;
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmendmentApproved {
+    pub agreement_id: AgreementId,
+    pub amendment_id: AmendmentId,
+    pub approved_ts: NaiveDateTime,
+}
+
+impl RpcMessage for AmendmentApproved {
+    const ID: &'static str = "AmendmentApproved";
+    type Item = ();
+    type Error = AmendAgreementError;
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmendmentRejected {
+    pub agreement_id: AgreementId,
+    pub amendment_id: AmendmentId,
+    pub reason: Option<Reason>,
+    pub rejected_ts: NaiveDateTime,
+}
+
+impl RpcMessage for AmendmentRejected {
+    const ID: &'static str = "AmendmentRejected";
+    type Item = ();
+    type Error = AmendAgreementError;
+}
+
 /// The same messaged will be used on GSB and as messages in callbacks.
 impl<Message: RpcMessage> CallbackMessage for Message {
     type Ok = <Message as RpcMessage>::Item;
@@ -223,6 +295,18 @@ impl ProposalContent {
     }
 }
 
+impl AmendmentContent {
+    pub fn from(amendment: &Amendment) -> AmendmentContent {
+        AmendmentContent {
+            amendment_id: amendment.id.clone(),
+            demand_properties: amendment.demand_properties.clone(),
+            offer_properties: amendment.offer_properties.clone(),
+            creation_ts: amendment.creation_ts,
+            valid_to: amendment.valid_to,
+        }
+    }
+}
+
 impl ProposalReceived {
     pub fn translate(mut self, owner: Owner) -> Self {
         self.prev_proposal_id = self.prev_proposal_id.translate(owner);
@@ -273,3 +357,24 @@ impl AgreementCommitted {
         self
     }
 }
+
+impl AmendmentProposed {
+    pub fn translate(mut self, owner: Owner) -> Self {
+        self.agreement_id = self.agreement_id.translate(owner);
+        self
+    }
+}
+
+impl AmendmentApproved {
+    pub fn translate(mut self, owner: Owner) -> Self {
+        self.agreement_id = self.agreement_id.translate(owner);
+        self
+    }
+}
+
+impl AmendmentRejected {
+    pub fn translate(mut self, owner: Owner) -> Self {
+        self.agreement_id = self.agreement_id.translate(owner);
+        self
+    }
+}
@@ -12,13 +12,14 @@ use serde::Deserialize;
 use ya_client::model::ErrorMessage;
 
 use crate::db::model::{
-    AgreementId, AppSessionId, Owner, ProposalId, ProposalIdParseError, SubscriptionId,
+    AgreementId, AmendmentId, AppSessionId, Owner, ProposalId, ProposalIdParseError, SubscriptionId,
 };
 
 pub(crate) mod common;
 mod error;
 pub(crate) mod provider;
 pub(crate) mod requestor;
+pub(crate) mod stream;
 
 const DEFAULT_EVENT_TIMEOUT: f32 = 5.0; // seconds
 const DEFAULT_QUERY_TIMEOUT: f32 = 5.0;
@@ -48,6 +49,12 @@ pub struct PathAgreement {
     pub agreement_id: String,
 }
 
+#[derive(Deserialize, Clone)]
+pub struct PathAmendment {
+    pub agreement_id: String,
+    pub amendment_id: AmendmentId,
+}
+
 #[derive(Deserialize)]
 pub struct PathSubscription {
     pub subscription_id: SubscriptionId,
@@ -111,6 +118,40 @@ pub struct QueryAgreementEvents {
     pub after_timestamp: Option<DateTime<Utc>>,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct QueryMaxEvents {
+    /// maximum count of events sent in single batch
+    #[serde(rename = "maxEvents")]
+    pub max_events: Option<i32>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct QueryStreamAgreementEvents {
+    /// maximum count of events sent in single batch
+    #[serde(rename = "maxEvents")]
+    pub max_events: Option<i32>,
+    #[serde(rename = "appSessionId")]
+    pub app_session_id: AppSessionId,
+    #[serde(rename = "afterTimestamp")]
+    pub after_timestamp: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct QueryOffsetLimit {
+    /// number of matching Offers to skip
+    #[serde(rename = "offset", default)]
+    pub offset: usize,
+    /// maximum count of Offers to return
+    #[serde(rename = "limit")]
+    pub limit: Option<i32>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OfferScanQuery {
+    /// constraints expression evaluated against Offers properties
+    pub constraints: String,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct QueryTerminateAgreement {
     pub reason: Option<String>,
@@ -2,13 +2,15 @@ use actix_web::web::{Data, Json, Path, Query};
 use actix_web::{HttpResponse, Responder, Scope};
 use chrono::{TimeZone, Utc};
 use std::sync::Arc;
+use std::time::Duration;
 
 use ya_client::model::market::Reason;
+use ya_core_model::market::SetBcastIntervals;
 use ya_service_api_web::middleware::Identity;
 use ya_std_utils::LogErr;
 
-use super::PathAgreement;
-use crate::db::model::Owner;
+use super::{PathAgreement, PathAmendment};
+use crate::db::model::{NewAmendment, Owner};
 use crate::market::MarketService;
 use crate::negotiation::error::AgreementError;
 use crate::rest_api::QueryAgreementEvents;
@@ -18,6 +20,14 @@ pub fn register_endpoints(scope: Scope) -> Scope {
         .service(collect_agreement_events)
         .service(get_agreement)
         .service(terminate_agreement)
+        .service(collect_amendment_events)
+        .service(propose_amendment)
+        .service(list_amendments)
+        .service(get_amendment)
+        .service(approve_amendment)
+        .service(reject_amendment)
+        .service(get_settings)
+        .service(set_bcast_intervals)
 }
 
 #[actix_web::get("/agreements/{agreement_id}")]
@@ -86,3 +96,127 @@ async fn terminate_agreement(
         .log_err()
         .map(|_| HttpResponse::Ok().finish())
 }
+
+#[actix_web::get("/agreementAmendmentEvents")]
+async fn collect_amendment_events(
+    market: Data<Arc<MarketService>>,
+    query: Query<QueryAgreementEvents>,
+    id: Identity,
+) -> impl Responder {
+    let timeout: f32 = query.timeout;
+    let after_timestamp = query
+        .after_timestamp
+        .unwrap_or(Utc.ymd(2016, 11, 11).and_hms(15, 12, 0));
+
+    market
+        .query_amendment_events(
+            &query.app_session_id,
+            timeout,
+            query.max_events,
+            after_timestamp,
+            &id,
+        )
+        .await
+        .log_err()
+        .map(|events| HttpResponse::Ok().json(events))
+}
+
+#[actix_web::post("/agreements/{agreement_id}/amendments")]
+async fn propose_amendment(
+    market: Data<Arc<MarketService>>,
+    path: Path<PathAgreement>,
+    id: Identity,
+    body: Json<NewAmendment>,
+) -> impl Responder {
+    let client_agreement_id = path.into_inner().agreement_id;
+    market
+        .propose_amendment(&id, client_agreement_id, body.into_inner())
+        .await
+        .log_err()
+        .map(|amendment| HttpResponse::Created().json(amendment))
+}
+
+#[actix_web::get("/agreements/{agreement_id}/amendments")]
+async fn list_amendments(
+    market: Data<Arc<MarketService>>,
+    path: Path<PathAgreement>,
+    id: Identity,
+) -> impl Responder {
+    let client_agreement_id = path.into_inner().agreement_id;
+    market
+        .list_amendments(&id, client_agreement_id)
+        .await
+        .log_err()
+        .map(|amendments| HttpResponse::Ok().json(amendments))
+}
+
+#[actix_web::get("/agreements/{agreement_id}/amendments/{amendment_id}")]
+async fn get_amendment(
+    market: Data<Arc<MarketService>>,
+    path: Path<PathAmendment>,
+    id: Identity,
+) -> impl Responder {
+    let path = path.into_inner();
+    market
+        .get_amendment(&id, path.agreement_id, &path.amendment_id)
+        .await
+        .log_err()
+        .map(|amendment| HttpResponse::Ok().json(amendment))
+}
+
+#[actix_web::post("/agreements/{agreement_id}/amendments/{amendment_id}/approve")]
+async fn approve_amendment(
+    market: Data<Arc<MarketService>>,
+    path: Path<PathAmendment>,
+    id: Identity,
+) -> impl Responder {
+    let path = path.into_inner();
+    market
+        .approve_amendment(&id, path.agreement_id, &path.amendment_id)
+        .await
+        .log_err()
+        .map(|amendment| HttpResponse::Ok().json(amendment))
+}
+
+#[actix_web::post("/agreements/{agreement_id}/amendments/{amendment_id}/reject")]
+async fn reject_amendment(
+    market: Data<Arc<MarketService>>,
+    path: Path<PathAmendment>,
+    id: Identity,
+    body: Json<Option<Reason>>,
+) -> impl Responder {
+    let path = path.into_inner();
+    market
+        .reject_amendment(
+            &id,
+            path.agreement_id,
+            &path.amendment_id,
+            body.into_inner(),
+        )
+        .await
+        .log_err()
+        .map(|amendment| HttpResponse::Ok().json(amendment))
+}
+
+#[actix_web::get("/admin/settings")]
+async fn get_settings(market: Data<Arc<MarketService>>, _id: Identity) -> impl Responder {
+    HttpResponse::Ok().json(market.settings())
+}
+
+#[actix_web::put("/admin/settings/bcastIntervals")]
+async fn set_bcast_intervals(
+    market: Data<Arc<MarketService>>,
+    body: Json<SetBcastIntervals>,
+    _id: Identity,
+) -> impl Responder {
+    let body = body.into_inner();
+    market
+        .set_bcast_intervals(
+            body.mean_cyclic_bcast_interval_ms
+                .map(Duration::from_millis),
+            body.mean_cyclic_unsubscribes_interval_ms
+                .map(Duration::from_millis),
+        )
+        .log_err()
+        .map(|settings| HttpResponse::Ok().json(settings))
+}
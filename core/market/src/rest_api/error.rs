@@ -2,10 +2,11 @@ use actix_web::{HttpResponse, ResponseError};
 
 use ya_client::model::ErrorMessage;
 
-use crate::db::dao::{AgreementDaoError, SaveProposalError};
+use crate::config::ConfigError;
+use crate::db::dao::{AgreementDaoError, AmendmentDaoError, SaveProposalError};
 use crate::db::model::AgreementState;
-use crate::negotiation::error::{AgreementEventsError, ProposalValidationError};
-use crate::protocol::negotiation::error::RejectProposalError;
+use crate::negotiation::error::{AgreementEventsError, AmendmentError, ProposalValidationError};
+use crate::protocol::negotiation::error::{AmendAgreementError, RejectProposalError};
 use crate::{
     db::dao::TakeEventsError,
     market::MarketError,
@@ -38,6 +39,16 @@ impl ResponseError for MarketError {
     }
 }
 
+impl ResponseError for ConfigError {
+    fn error_response(&self) -> HttpResponse {
+        let msg = ErrorMessage::new(self.to_string());
+        match self {
+            ConfigError::Invalid(_) => HttpResponse::BadRequest().json(msg),
+            _ => HttpResponse::InternalServerError().json(msg),
+        }
+    }
+}
+
 impl ResponseError for MatcherError {
     fn error_response(&self) -> HttpResponse {
         match self {
@@ -79,7 +90,15 @@ impl ResponseError for QueryDemandsError {
 
 impl ResponseError for QueryOffersError {
     fn error_response(&self) -> HttpResponse {
-        HttpResponse::InternalServerError().json(ErrorMessage::new(self.to_string()))
+        let msg = ErrorMessage::new(self.to_string());
+        match self {
+            QueryOffersError::InvalidConstraints(_) | QueryOffersError::InvalidLimit(..) => {
+                HttpResponse::BadRequest().json(msg)
+            }
+            QueryOffersError::DbError(_) | QueryOffersError::IdentityError(_) => {
+                HttpResponse::InternalServerError().json(msg)
+            }
+        }
     }
 }
 
@@ -236,6 +255,46 @@ impl ResponseError for AgreementDaoError {
     }
 }
 
+impl ResponseError for AmendmentError {
+    fn error_response(&self) -> HttpResponse {
+        let msg = ErrorMessage::new(self.to_string());
+        match self {
+            AmendmentError::AgreementNotFound(_) | AmendmentError::NotFound(_) => {
+                HttpResponse::NotFound().json(msg)
+            }
+            AmendmentError::Invalid(_) => HttpResponse::BadRequest().json(msg),
+            AmendmentError::Dao(e) => e.error_response(),
+            AmendmentError::Protocol(AmendAgreementError::Remote(_)) => {
+                HttpResponse::Conflict().json(msg)
+            }
+            AmendmentError::GetAgreement(..)
+            | AmendmentError::Protocol(_)
+            | AmendmentError::Internal(_) => HttpResponse::InternalServerError().json(msg),
+        }
+    }
+}
+
+impl ResponseError for AmendmentDaoError {
+    fn error_response(&self) -> HttpResponse {
+        let msg = ErrorMessage::new(self.to_string());
+        match self {
+            AmendmentDaoError::AgreementNotFound(_) | AmendmentDaoError::NotFound(_) => {
+                HttpResponse::NotFound().json(msg)
+            }
+            AmendmentDaoError::Expired(_) | AmendmentDaoError::AgreementState(..) => {
+                HttpResponse::Gone().json(msg)
+            }
+            AmendmentDaoError::PendingExists(..) | AmendmentDaoError::AlreadyDecided(..) => {
+                HttpResponse::Conflict().json(msg)
+            }
+            AmendmentDaoError::OwnAmendment(_) => HttpResponse::BadRequest().json(msg),
+            AmendmentDaoError::Apply(..) | AmendmentDaoError::DbError(_) => {
+                HttpResponse::InternalServerError().json(msg)
+            }
+        }
+    }
+}
+
 impl ResponseError for WaitForApprovalError {
     fn error_response(&self) -> HttpResponse {
         let msg = ErrorMessage::new(self.to_string());
@@ -13,8 +13,8 @@ use crate::db::model::Owner;
 use crate::market::MarketService;
 
 use super::{
-    PathAgreement, PathSubscription, PathSubscriptionProposal, ProposalId, QueryTimeout,
-    QueryTimeoutMaxEvents,
+    OfferScanQuery, PathAgreement, PathSubscription, PathSubscriptionProposal, ProposalId,
+    QueryOffsetLimit, QueryTimeout, QueryTimeoutMaxEvents,
 };
 use crate::negotiation::ApprovalStatus;
 use crate::rest_api::QueryAppSessionId;
@@ -32,6 +32,7 @@ pub fn register_endpoints(scope: Scope) -> Scope {
         .service(confirm_agreement)
         .service(wait_for_approval)
         .service(cancel_agreement)
+        .service(scan_offers)
 }
 
 #[actix_web::post("/demands")]
@@ -87,6 +88,21 @@ async fn collect(
         .map(|events| HttpResponse::Ok().json(events))
 }
 
+#[actix_web::post("/offers/scan")]
+async fn scan_offers(
+    market: Data<Arc<MarketService>>,
+    body: Json<OfferScanQuery>,
+    query: Query<QueryOffsetLimit>,
+    _id: Identity,
+) -> impl Responder {
+    let query = query.into_inner();
+    market
+        .scan_offers(&body.constraints, query.offset, query.limit)
+        .await
+        .log_err()
+        .map(|page| HttpResponse::Ok().json(page))
+}
+
 #[actix_web::post("/demands/{subscription_id}/proposals/{proposal_id}")]
 async fn counter_proposal(
     market: Data<Arc<MarketService>>,
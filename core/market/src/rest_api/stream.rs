@@ -0,0 +1,200 @@
+//! Server-Sent Events variants of market events endpoints.
+//!
+//! Each stream repeats long-polling queries on the server side and pushes
+//! events to client as soon as they appear, so applications don't need to
+//! poll. Long-poll endpoints stay unchanged and both can be used interchangeably.
+use actix_web::http::header::CACHE_CONTROL;
+use actix_web::web::{Bytes, Data, Path, Query};
+use actix_web::{HttpResponse, Scope};
+use chrono::{TimeZone, Utc};
+use futures::{future, stream, Future, Stream, StreamExt};
+use serde::Serialize;
+use std::fmt::Display;
+use std::sync::Arc;
+
+use ya_client::model::ErrorMessage;
+use ya_service_api_web::middleware::Identity;
+use ya_std_utils::LogErr;
+
+use super::{PathSubscription, QueryMaxEvents, QueryStreamAgreementEvents};
+use crate::market::MarketService;
+use crate::negotiation::error::{AgreementEventsError, QueryEventsError};
+
+pub fn register_endpoints(scope: Scope) -> Scope {
+    scope
+        .service(stream_demand_events)
+        .service(stream_offer_events)
+        .service(stream_agreement_events)
+}
+
+#[actix_web::get("/demands/{subscription_id}/events/stream")]
+async fn stream_demand_events(
+    market: Data<Arc<MarketService>>,
+    path: Path<PathSubscription>,
+    query: Query<QueryMaxEvents>,
+    _id: Identity,
+) -> Result<HttpResponse, QueryEventsError> {
+    let subscription_id = path.into_inner().subscription_id;
+    let max_events = query.max_events;
+
+    // First query without waiting, to return proper http error code
+    // for not existing subscriptions or invalid parameters.
+    let events = market
+        .requestor_engine
+        .query_events(&subscription_id, 0.0, max_events)
+        .await
+        .log_err()?;
+
+    let timeout = heartbeat(&market);
+    let market = market.into_inner();
+    let next = stream_events((), move |_| {
+        let market = market.clone();
+        let subscription_id = subscription_id.clone();
+        async move {
+            market
+                .requestor_engine
+                .query_events(&subscription_id, timeout, max_events)
+                .await
+                .map(|events| ((), events))
+        }
+    });
+    Ok(sse_response(&events, next))
+}
+
+#[actix_web::get("/offers/{subscription_id}/events/stream")]
+async fn stream_offer_events(
+    market: Data<Arc<MarketService>>,
+    path: Path<PathSubscription>,
+    query: Query<QueryMaxEvents>,
+    _id: Identity,
+) -> Result<HttpResponse, QueryEventsError> {
+    let subscription_id = path.into_inner().subscription_id;
+    let max_events = query.max_events;
+
+    let events = market
+        .provider_engine
+        .query_events(&subscription_id, 0.0, max_events)
+        .await
+        .log_err()?;
+
+    let timeout = heartbeat(&market);
+    let market = market.into_inner();
+    let next = stream_events((), move |_| {
+        let market = market.clone();
+        let subscription_id = subscription_id.clone();
+        async move {
+            market
+                .provider_engine
+                .query_events(&subscription_id, timeout, max_events)
+                .await
+                .map(|events| ((), events))
+        }
+    });
+    Ok(sse_response(&events, next))
+}
+
+#[actix_web::get("/agreementEvents/stream")]
+async fn stream_agreement_events(
+    market: Data<Arc<MarketService>>,
+    query: Query<QueryStreamAgreementEvents>,
+    id: Identity,
+) -> Result<HttpResponse, AgreementEventsError> {
+    let query = query.into_inner();
+    let max_events = query.max_events;
+    let session_id = query.app_session_id;
+    let after_timestamp = query
+        .after_timestamp
+        .unwrap_or(Utc.ymd(2016, 11, 11).and_hms(15, 12, 0));
+
+    let events = market
+        .query_agreement_events(&session_id, 0.0, max_events, after_timestamp, &id)
+        .await
+        .log_err()?;
+    let after_timestamp = events
+        .last()
+        .map(|event| event.event_date)
+        .unwrap_or(after_timestamp);
+
+    let timeout = heartbeat(&market);
+    let market = market.into_inner();
+    let next = stream_events(after_timestamp, move |after_timestamp| {
+        let market = market.clone();
+        let session_id = session_id.clone();
+        let id = id.clone();
+        async move {
+            market
+                .query_agreement_events(&session_id, timeout, max_events, after_timestamp, &id)
+                .await
+                .map(|events| {
+                    let last = events.last().map(|event| event.event_date);
+                    (last.unwrap_or(after_timestamp), events)
+                })
+        }
+    });
+    Ok(sse_response(&events, next))
+}
+
+fn heartbeat(market: &MarketService) -> f32 {
+    market.config.events.stream_heartbeat.as_secs_f32()
+}
+
+/// Repeats `query` until it fails. State returned by each query is passed
+/// to the next one, which allows moving `after_timestamp` forward.
+/// Error is sent to the client as the last message in the stream.
+fn stream_events<S, T, E, F, Fut>(
+    init: S,
+    query: F,
+) -> impl Stream<Item = Result<Bytes, actix_web::Error>>
+where
+    S: 'static,
+    T: Serialize + 'static,
+    E: Display + 'static,
+    F: Fn(S) -> Fut + 'static,
+    Fut: Future<Output = Result<(S, Vec<T>), E>> + 'static,
+{
+    stream::unfold(Some(init), move |state| {
+        let next = state.map(&query);
+        async move {
+            match next?.await {
+                Ok((state, events)) => Some((Ok(encode_events(&events)), Some(state))),
+                Err(e) => {
+                    log::debug!("Closing market events stream. Reason: {}", e);
+                    Some((Ok(encode_error(e)), None))
+                }
+            }
+        }
+    })
+}
+
+fn sse_response<T: Serialize>(
+    first: &[T],
+    next: impl Stream<Item = Result<Bytes, actix_web::Error>> + 'static,
+) -> HttpResponse {
+    let first = stream::once(future::ok(encode_events(first)));
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .header(CACHE_CONTROL, "no-cache")
+        .streaming(Box::pin(first.chain(next)))
+}
+
+/// Encodes each event as separate SSE message. Empty batch is encoded
+/// as comment, which keeps idle connection alive.
+fn encode_events<T: Serialize>(events: &[T]) -> Bytes {
+    if events.is_empty() {
+        return Bytes::from_static(b": keep-alive\n\n");
+    }
+
+    let mut buffer = String::new();
+    for event in events {
+        match serde_json::to_string(event) {
+            Ok(json) => buffer.push_str(&format!("data: {}\n\n", json)),
+            Err(e) => log::warn!("Failed to serialize market event. Error: {}", e),
+        }
+    }
+    Bytes::from(buffer)
+}
+
+fn encode_error(e: impl Display) -> Bytes {
+    let msg = serde_json::to_string(&ErrorMessage::new(e.to_string())).unwrap_or_default();
+    Bytes::from(format!("event: error\ndata: {}\n\n", msg))
+}
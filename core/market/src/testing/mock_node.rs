@@ -62,6 +62,9 @@ pub enum MockNodeKind {
     /// Market implementation, but only Discovery interface.
     /// Necessary to emulate wrong nodes behavior.
     Discovery(Discovery),
+    /// Mock discovery node, that understands only `PROTOCOL_VERSION` messages.
+    /// Necessary to check compatibility with Nodes, that don't support Offers digests.
+    LegacyDiscovery(Discovery),
     /// Stores mock negotiation interfaces, that doesn't include full
     /// Market implementation.
     /// Necessary to emulate wrong nodes behavior.
@@ -79,6 +82,9 @@ impl MockNodeKind {
             MockNodeKind::Market(market) => market.bind_gsb(&public, &local).await?,
             MockNodeKind::Matcher { matcher, .. } => matcher.bind_gsb(&public, &local).await?,
             MockNodeKind::Discovery(discovery) => discovery.bind_gsb(&public, &local).await?,
+            MockNodeKind::LegacyDiscovery(discovery) => {
+                discovery.bind_gsb_legacy(&public, &local).await?
+            }
             MockNodeKind::Negotiation {
                 provider,
                 requestor,
@@ -226,11 +232,25 @@ impl MarketsNetwork {
             .await
     }
 
+    pub async fn add_legacy_discovery_instance(
+        self,
+        name: &str,
+        builder: DiscoveryBuilder,
+    ) -> Self {
+        let identity_api = MockIdentity::new(name);
+        let discovery = builder
+            .add_data(identity_api.clone() as Arc<dyn IdentityApi>)
+            .build();
+        self.add_node(name, identity_api, MockNodeKind::LegacyDiscovery(discovery))
+            .await
+    }
+
     pub fn discovery_builder() -> DiscoveryBuilder {
         DiscoveryBuilder::default()
             .add_handler(empty_on_offers_retrieved)
             .add_handler(empty_on_offers_bcast)
             .add_handler(empty_on_offer_unsubscribed_bcast)
+            .add_handler(empty_on_offers_digest_bcast)
             .add_handler(empty_on_retrieve_offers)
     }
 
@@ -376,6 +396,7 @@ impl MarketsNetwork {
             .find(|node| node.name == name)
             .map(|node| match &node.kind {
                 MockNodeKind::Discovery(discovery) => discovery.clone(),
+                MockNodeKind::LegacyDiscovery(discovery) => discovery.clone(),
                 _ => panic!("discovery expected"),
             })
             .unwrap()
@@ -625,6 +646,13 @@ pub mod default {
         Ok(vec![])
     }
 
+    pub async fn empty_on_offers_digest_bcast(
+        _caller: String,
+        _msg: OffersDigestBcast,
+    ) -> Result<Vec<SubscriptionId>, ()> {
+        Ok(vec![])
+    }
+
     pub async fn empty_on_initial_proposal(
         _caller: String,
         _msg: InitialProposalReceived,
@@ -45,6 +45,10 @@ pub fn sample_offer_with_expiration(expiration_ts: NaiveDateTime) -> Offer {
     .unwrap()
 }
 
+pub fn generate_offer_ids(count: usize) -> Vec<SubscriptionId> {
+    (0..count).map(|_| sample_offer().id).collect()
+}
+
 pub fn generate_offer(id: &str, expiration_ts: NaiveDateTime) -> Offer {
     Offer {
         id: SubscriptionId::from_str(id).unwrap(),
@@ -0,0 +1,321 @@
+use chrono::{Duration, Utc};
+use serde_json::json;
+
+use ya_market::testing::agreement_utils::{gen_reason, negotiate_agreement};
+use ya_market::testing::{
+    AmendmentDaoError, AmendmentError, AmendmentEventType, AmendmentIssuer, AmendmentState,
+    MarketsNetwork, NewAmendment,
+};
+
+const REQ_NAME: &str = "Node-1";
+const PROV_NAME: &str = "Node-2";
+
+fn sample_amendment() -> NewAmendment {
+    NewAmendment {
+        demand_properties: None,
+        offer_properties: Some(json!({"golem": {"srv": {"comp": {"expiration": 12345}}}})),
+        valid_to: Utc::now() + Duration::minutes(5),
+    }
+}
+
+/// Requestor proposes Amendment, Provider approves it and both sides
+/// should see changed Agreement properties.
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_amendment_approved() {
+    let network = MarketsNetwork::new(None)
+        .await
+        .add_market_instance(REQ_NAME)
+        .await
+        .add_market_instance(PROV_NAME)
+        .await;
+
+    let negotiation = negotiate_agreement(
+        &network,
+        REQ_NAME,
+        PROV_NAME,
+        "negotiation",
+        "r-session",
+        "p-session",
+    )
+    .await
+    .unwrap();
+    let req_market = network.get_market(REQ_NAME);
+    let prov_market = network.get_market(PROV_NAME);
+    let req_id = network.get_default_id(REQ_NAME);
+    let prov_id = network.get_default_id(PROV_NAME);
+    let agreement_id = negotiation.r_agreement.into_client();
+
+    let proposed = req_market
+        .propose_amendment(&req_id, agreement_id.clone(), sample_amendment())
+        .await
+        .unwrap();
+    assert_eq!(proposed.issuer, AmendmentIssuer::Requestor);
+    assert_eq!(proposed.state, AmendmentState::Pending);
+
+    // Provider should get the same Amendment.
+    let amendments = prov_market
+        .list_amendments(&prov_id, agreement_id.clone())
+        .await
+        .unwrap();
+    assert_eq!(amendments.len(), 1);
+    assert_eq!(amendments[0].amendment_id, proposed.amendment_id);
+    assert_eq!(amendments[0].offer_properties, proposed.offer_properties);
+
+    let events = prov_market
+        .query_amendment_events(
+            &None,
+            0.0,
+            Some(10),
+            negotiation.confirm_timestamp,
+            &prov_id,
+        )
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, AmendmentEventType::Proposed);
+    assert_eq!(events[0].issuer, AmendmentIssuer::Requestor);
+
+    let approved = prov_market
+        .approve_amendment(&prov_id, agreement_id.clone(), &proposed.amendment_id)
+        .await
+        .unwrap();
+    assert_eq!(approved.state, AmendmentState::Approved);
+
+    let r_amendment = req_market
+        .get_amendment(&req_id, agreement_id.clone(), &proposed.amendment_id)
+        .await
+        .unwrap();
+    assert_eq!(r_amendment.state, AmendmentState::Approved);
+
+    let r_agreement = req_market
+        .get_agreement(&negotiation.r_agreement, &req_id)
+        .await
+        .unwrap();
+    let p_agreement = prov_market
+        .get_agreement(&negotiation.p_agreement, &prov_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        r_agreement.offer.properties["golem.srv.comp.expiration"],
+        json!(12345)
+    );
+    assert_eq!(
+        p_agreement.offer.properties["golem.srv.comp.expiration"],
+        json!(12345)
+    );
+}
+
+/// Agreement can have only one pending Amendment and Agent
+/// can't decide about Amendment proposed by himself.
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_amendment_rejected() {
+    let network = MarketsNetwork::new(None)
+        .await
+        .add_market_instance(REQ_NAME)
+        .await
+        .add_market_instance(PROV_NAME)
+        .await;
+
+    let negotiation = negotiate_agreement(
+        &network,
+        REQ_NAME,
+        PROV_NAME,
+        "negotiation",
+        "r-session",
+        "p-session",
+    )
+    .await
+    .unwrap();
+    let req_market = network.get_market(REQ_NAME);
+    let prov_market = network.get_market(PROV_NAME);
+    let req_id = network.get_default_id(REQ_NAME);
+    let prov_id = network.get_default_id(PROV_NAME);
+    let agreement_id = negotiation.r_agreement.into_client();
+
+    let proposed = prov_market
+        .propose_amendment(&prov_id, agreement_id.clone(), sample_amendment())
+        .await
+        .unwrap();
+
+    match req_market
+        .propose_amendment(&req_id, agreement_id.clone(), sample_amendment())
+        .await
+    {
+        Err(AmendmentError::Dao(AmendmentDaoError::PendingExists(..))) => (),
+        result => panic!("Expected PendingExists error, got: {:?}", result),
+    }
+
+    match prov_market
+        .approve_amendment(&prov_id, agreement_id.clone(), &proposed.amendment_id)
+        .await
+    {
+        Err(AmendmentError::Dao(AmendmentDaoError::OwnAmendment(_))) => (),
+        result => panic!("Expected OwnAmendment error, got: {:?}", result),
+    }
+
+    let rejected = req_market
+        .reject_amendment(
+            &req_id,
+            agreement_id.clone(),
+            &proposed.amendment_id,
+            Some(gen_reason("Too expensive")),
+        )
+        .await
+        .unwrap();
+    assert_eq!(rejected.state, AmendmentState::Rejected);
+    assert_eq!(rejected.reason.unwrap().message, "Too expensive");
+
+    let p_amendment = prov_market
+        .get_amendment(&prov_id, agreement_id.clone(), &proposed.amendment_id)
+        .await
+        .unwrap();
+    assert_eq!(p_amendment.state, AmendmentState::Rejected);
+
+    // Rejected Amendment can't be approved later.
+    match req_market
+        .approve_amendment(&req_id, agreement_id.clone(), &proposed.amendment_id)
+        .await
+    {
+        Err(AmendmentError::Dao(AmendmentDaoError::AlreadyDecided(..))) => (),
+        result => panic!("Expected AlreadyDecided error, got: {:?}", result),
+    }
+
+    // Agreement wasn't changed.
+    let p_agreement = prov_market
+        .get_agreement(&negotiation.p_agreement, &prov_id)
+        .await
+        .unwrap();
+    assert_ne!(
+        p_agreement.offer.properties["golem.srv.comp.expiration"],
+        json!(12345)
+    );
+}
+
+/// Amendment, that couldn't be delivered to other party, shouldn't
+/// stay pending and block proposing next Amendments.
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_amendment_not_delivered() {
+    let network = MarketsNetwork::new(None)
+        .await
+        .add_market_instance(REQ_NAME)
+        .await
+        .add_market_instance(PROV_NAME)
+        .await;
+
+    let negotiation = negotiate_agreement(
+        &network,
+        REQ_NAME,
+        PROV_NAME,
+        "negotiation",
+        "r-session",
+        "p-session",
+    )
+    .await
+    .unwrap();
+    let req_market = network.get_market(REQ_NAME);
+    let req_id = network.get_default_id(REQ_NAME);
+    let agreement_id = negotiation.r_agreement.into_client();
+
+    network.break_networking_for(PROV_NAME).unwrap();
+    match req_market
+        .propose_amendment(&req_id, agreement_id.clone(), sample_amendment())
+        .await
+    {
+        Err(AmendmentError::Protocol(_)) => (),
+        result => panic!("Expected Protocol error, got: {:?}", result),
+    }
+    let amendments = req_market
+        .list_amendments(&req_id, agreement_id.clone())
+        .await
+        .unwrap();
+    assert!(amendments.is_empty());
+
+    network.enable_networking_for(PROV_NAME).unwrap();
+    req_market
+        .propose_amendment(&req_id, agreement_id.clone(), sample_amendment())
+        .await
+        .unwrap();
+}
+
+/// Approval, that couldn't be delivered to other party, is reverted,
+/// so both sides still have the same Agreement and can retry.
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_amendment_approval_not_delivered() {
+    let network = MarketsNetwork::new(None)
+        .await
+        .add_market_instance(REQ_NAME)
+        .await
+        .add_market_instance(PROV_NAME)
+        .await;
+
+    let negotiation = negotiate_agreement(
+        &network,
+        REQ_NAME,
+        PROV_NAME,
+        "negotiation",
+        "r-session",
+        "p-session",
+    )
+    .await
+    .unwrap();
+    let req_market = network.get_market(REQ_NAME);
+    let prov_market = network.get_market(PROV_NAME);
+    let req_id = network.get_default_id(REQ_NAME);
+    let prov_id = network.get_default_id(PROV_NAME);
+    let agreement_id = negotiation.r_agreement.into_client();
+
+    let proposed = req_market
+        .propose_amendment(&req_id, agreement_id.clone(), sample_amendment())
+        .await
+        .unwrap();
+
+    network.break_networking_for(REQ_NAME).unwrap();
+    match prov_market
+        .approve_amendment(&prov_id, agreement_id.clone(), &proposed.amendment_id)
+        .await
+    {
+        Err(AmendmentError::Protocol(_)) => (),
+        result => panic!("Expected Protocol error, got: {:?}", result),
+    }
+
+    let p_amendment = prov_market
+        .get_amendment(&prov_id, agreement_id.clone(), &proposed.amendment_id)
+        .await
+        .unwrap();
+    assert_eq!(p_amendment.state, AmendmentState::Pending);
+    let p_agreement = prov_market
+        .get_agreement(&negotiation.p_agreement, &prov_id)
+        .await
+        .unwrap();
+    assert_ne!(
+        p_agreement.offer.properties["golem.srv.comp.expiration"],
+        json!(12345)
+    );
+    let events = prov_market
+        .query_amendment_events(
+            &None,
+            0.0,
+            Some(10),
+            negotiation.confirm_timestamp,
+            &prov_id,
+        )
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, AmendmentEventType::Proposed);
+
+    network.enable_networking_for(REQ_NAME).unwrap();
+    prov_market
+        .approve_amendment(&prov_id, agreement_id.clone(), &proposed.amendment_id)
+        .await
+        .unwrap();
+    let r_amendment = req_market
+        .get_amendment(&req_id, agreement_id.clone(), &proposed.amendment_id)
+        .await
+        .unwrap();
+    assert_eq!(r_amendment.state, AmendmentState::Approved);
+}
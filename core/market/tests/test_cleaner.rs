@@ -7,7 +7,7 @@ use ya_market::testing::mock_offer::{generate_demand, generate_offer};
 use ya_market::testing::proposal_util::{generate_negotiation, generate_proposal};
 use ya_market::testing::{
     Agreement, AgreementDao, DbProposal, Demand, DemandDao, MarketsNetwork, Negotiation, Offer,
-    OfferDao,
+    OfferDao, RetentionConfig,
 };
 use ya_persistence::executor::PoolType;
 
@@ -29,7 +29,9 @@ async fn test_agreement() {
     let agreement_dao = db.as_dao::<AgreementDao>();
     agreement_dao.save(valid_agreement.clone()).await.unwrap();
     agreement_dao.save(expired_agreement.clone()).await.unwrap();
-    clean(db.clone()).await;
+    clean(db.clone(), &RetentionConfig::default(), false, None)
+        .await
+        .unwrap();
     assert_eq!(
         <PoolType as TestingDao<Agreement>>::exists(&db.clone().pool, valid_agreement.id).await,
         true
@@ -56,7 +58,9 @@ async fn test_demand() {
     let demand_dao = db.as_dao::<DemandDao>();
     demand_dao.insert(&valid_demand).await.unwrap();
     demand_dao.insert(&expired_demand).await.unwrap();
-    clean(db.clone()).await;
+    clean(db.clone(), &RetentionConfig::default(), false, None)
+        .await
+        .unwrap();
     assert_eq!(
         <PoolType as TestingDao<Demand>>::exists(&db.clone().pool, valid_demand.id).await,
         true
@@ -90,7 +94,9 @@ async fn test_offer() {
         .put(expired_offer.clone(), validation_ts.clone())
         .await
         .unwrap();
-    clean(db.clone()).await;
+    clean(db.clone(), &RetentionConfig::default(), false, None)
+        .await
+        .unwrap();
     assert_eq!(
         <PoolType as TestingDao<Offer>>::exists(&db.clone().pool, valid_offer.id).await,
         true
@@ -114,7 +120,9 @@ async fn test_events() {
     <PoolType as TestingDao<TestMarketEvent>>::raw_insert(&db.clone().pool, expired_event.clone())
         .await
         .unwrap();
-    clean(db.clone()).await;
+    clean(db.clone(), &RetentionConfig::default(), false, None)
+        .await
+        .unwrap();
     assert_eq!(
         <PoolType as TestingDao<TestMarketEvent>>::exists(&db.clone().pool, valid_event.id).await,
         true
@@ -164,7 +172,9 @@ async fn test_proposal() {
             .unwrap();
         expired_proposals.push(proposal.clone());
     }
-    clean(db.clone()).await;
+    clean(db.clone(), &RetentionConfig::default(), false, None)
+        .await
+        .unwrap();
     assert_eq!(
         <PoolType as TestingDao<Negotiation>>::exists(&db.clone().pool, valid_negotiation.id).await,
         true
@@ -210,7 +220,9 @@ async fn test_proposal_lotsa_negotiations() {
             .unwrap();
         expired_negotiations.push(expired_negotiation);
     }
-    clean(db.clone()).await;
+    clean(db.clone(), &RetentionConfig::default(), false, None)
+        .await
+        .unwrap();
     for n in expired_negotiations {
         assert_eq!(
             <PoolType as TestingDao<Negotiation>>::exists(&db.clone().pool, n.id).await,
@@ -218,3 +230,80 @@ async fn test_proposal_lotsa_negotiations() {
         );
     }
 }
+
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_dry_run() {
+    let _ = env_logger::builder().try_init();
+    let expired_agreement = generate_agreement(1, past());
+    let db = MarketsNetwork::new(None).await.init_database("testnode");
+    let agreement_dao = db.as_dao::<AgreementDao>();
+    agreement_dao.save(expired_agreement.clone()).await.unwrap();
+
+    let report = clean(db.clone(), &RetentionConfig::default(), true, None)
+        .await
+        .unwrap();
+    assert!(report.dry_run);
+    assert_eq!(report.agreements, 1);
+    assert_eq!(
+        <PoolType as TestingDao<Agreement>>::exists(&db.clone().pool, expired_agreement.id).await,
+        true
+    );
+}
+
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_agreement_retention() {
+    let _ = env_logger::builder().try_init();
+    let expired_agreement = generate_agreement(1, past());
+    let db = MarketsNetwork::new(None).await.init_database("testnode");
+    let agreement_dao = db.as_dao::<AgreementDao>();
+    agreement_dao.save(expired_agreement.clone()).await.unwrap();
+
+    let retention = RetentionConfig {
+        agreements: Duration::days(100),
+        ..Default::default()
+    };
+    let report = clean(db.clone(), &retention, false, None).await.unwrap();
+    assert_eq!(report.agreements, 0);
+    assert_eq!(
+        <PoolType as TestingDao<Agreement>>::exists(&db.clone().pool, expired_agreement.id).await,
+        true
+    );
+}
+
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_agreement_archive() {
+    let _ = env_logger::builder().try_init();
+    let valid_agreement = generate_agreement(1, future());
+    let expired_agreement = generate_agreement(2, past());
+    let db = MarketsNetwork::new(None).await.init_database("testnode");
+    let agreement_dao = db.as_dao::<AgreementDao>();
+    agreement_dao.save(valid_agreement.clone()).await.unwrap();
+    agreement_dao.save(expired_agreement.clone()).await.unwrap();
+
+    let archive_dir = tempdir::TempDir::new("market-archive").unwrap();
+    let report = clean(
+        db.clone(),
+        &RetentionConfig::default(),
+        false,
+        Some(archive_dir.path().to_path_buf()),
+    )
+    .await
+    .unwrap();
+    assert_eq!(report.agreements, 1);
+
+    let archive = std::fs::read_to_string(report.agreements_archive.unwrap()).unwrap();
+    let archive: serde_json::Value = serde_json::from_str(&archive).unwrap();
+    let archive = archive.as_array().unwrap();
+    assert_eq!(archive.len(), 1);
+    assert_eq!(
+        archive[0]["agreement"]["id"],
+        serde_json::json!(expired_agreement.id.to_string())
+    );
+    assert_eq!(
+        <PoolType as TestingDao<Agreement>>::exists(&db.clone().pool, expired_agreement.id).await,
+        false
+    );
+}
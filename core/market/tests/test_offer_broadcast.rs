@@ -6,10 +6,10 @@ use std::sync::Arc;
 use tokio::time::Duration;
 
 use ya_market::assert_err_eq;
-use ya_market::testing::discovery::{message::*, Discovery};
+use ya_market::testing::discovery::{digest::OfferIdsDigest, message::*, Discovery};
 use ya_market::testing::mock_offer::{client, sample_offer, sample_offer_with_expiration};
 use ya_market::testing::{wait_for_bcast, MarketServiceExt, MarketsNetwork};
-use ya_market::testing::{QueryOfferError, SubscriptionId};
+use ya_market::testing::{Config, QueryOfferError, SubscriptionId};
 
 /// Test adds offer. It should be broadcasted to other nodes in the network.
 /// Than sending unsubscribe should remove Offer from other nodes.
@@ -318,3 +318,166 @@ async fn test_discovery_get_offers() {
     assert_eq!(offers.len(), 1);
     assert_eq!(offers[0].id, subscription_id);
 }
+
+/// Market should respond to Offers digest with ids of Offers, that
+/// weren't included in digest. Offers included in digest shouldn't be sent.
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_offers_digest_response() {
+    let _ = env_logger::builder().try_init();
+
+    // Cyclic broadcasts would send additional Offers ids, so we make them rare.
+    let mut config = Config::default();
+    config.discovery.mean_cyclic_bcast_interval = Duration::from_secs(3600);
+    config.discovery.max_bcasted_offers = 50;
+
+    let network = MarketsNetwork::new(None)
+        .await
+        .with_config(Arc::new(config))
+        .add_market_instance("Node-1")
+        .await;
+
+    let market1 = network.get_market("Node-1");
+    let id1 = network.get_default_id("Node-1");
+
+    let known_id = market1
+        .subscribe_offer(&client::sample_offer(), &id1)
+        .await
+        .unwrap();
+    let missing_id = market1
+        .subscribe_offer(&client::sample_offer(), &id1)
+        .await
+        .unwrap();
+
+    let (tx, mut rx) = mpsc::channel::<Vec<SubscriptionId>>(1);
+    let discovery_builder =
+        MarketsNetwork::discovery_builder().add_handler(move |_: String, msg: OffersBcast| {
+            let mut tx = tx.clone();
+            async move {
+                // Only first message is checked, so we don't care about full channel.
+                tx.try_send(msg.offer_ids).ok();
+                Ok(vec![])
+            }
+        });
+    let network = network
+        .add_discovery_instance("Node-2", discovery_builder)
+        .await;
+
+    let discovery2: Discovery = network.get_discovery("Node-2");
+    discovery2
+        .bcast_offers_digest(OfferIdsDigest::new(&[known_id]))
+        .await
+        .unwrap();
+
+    let offer_ids = tokio::time::timeout(Duration::from_millis(500), rx.next())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(offer_ids, vec![missing_id]);
+}
+
+/// Nodes supporting only `PROTOCOL_VERSION` don't understand digests.
+/// They should still get Offers from new Nodes through cyclic broadcasts.
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_offers_sharing_with_legacy_node() {
+    let _ = env_logger::builder().try_init();
+
+    let mut config = Config::default();
+    config.discovery.mean_cyclic_bcast_interval = Duration::from_millis(100);
+    config.discovery.max_bcasted_offers = 50;
+
+    let network = MarketsNetwork::new(None)
+        .await
+        .with_config(Arc::new(config))
+        .add_market_instance("Node-1")
+        .await;
+
+    let market1 = network.get_market("Node-1");
+    let id1 = network.get_default_id("Node-1");
+
+    // Subscribe before legacy Node starts, so it won't get immediate broadcast.
+    let subscription_id = market1
+        .subscribe_offer(&client::sample_offer(), &id1)
+        .await
+        .unwrap();
+
+    let (tx, mut rx) = mpsc::channel::<Vec<SubscriptionId>>(10);
+    let discovery_builder =
+        MarketsNetwork::discovery_builder().add_handler(move |_: String, msg: OffersBcast| {
+            let mut tx = tx.clone();
+            async move {
+                // Only first message is checked, so we don't care about full channel.
+                tx.try_send(msg.offer_ids).ok();
+                Ok(vec![])
+            }
+        });
+    let network = network
+        .add_legacy_discovery_instance("Node-2", discovery_builder)
+        .await;
+
+    let offer_ids = tokio::time::timeout(Duration::from_millis(500), rx.next())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(offer_ids.contains(&subscription_id));
+
+    // Legacy Node broadcasts Offers ids the old way and market should accept them.
+    let offer = sample_offer();
+    let offer_id = offer.id.clone();
+    let network = network
+        .add_legacy_discovery_instance(
+            "Node-3",
+            MarketsNetwork::discovery_builder().add_handler(move |_: String, _: RetrieveOffers| {
+                let offer = offer.clone();
+                async move { Ok(vec![offer]) }
+            }),
+        )
+        .await;
+
+    let discovery3: Discovery = network.get_discovery("Node-3");
+    discovery3
+        .bcast_offers(vec![offer_id.clone()])
+        .await
+        .unwrap();
+    wait_for_bcast(1000, &market1, &offer_id, true).await;
+    market1.get_offer(&offer_id).await.unwrap();
+}
+
+/// Node, that joined network later, should get Offers through digests
+/// even from Nodes, that aren't Offer owners. Owner has broken networking,
+/// so the only way to get Offer is to ask Node-2.
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_offers_digest_sync_from_non_owner() {
+    let _ = env_logger::builder().try_init();
+
+    let mut config = Config::default();
+    config.discovery.mean_cyclic_bcast_interval = Duration::from_millis(100);
+    config.discovery.max_bcasted_offers = 50;
+
+    let network = MarketsNetwork::new(None)
+        .await
+        .with_config(Arc::new(config))
+        .add_market_instance("Node-1")
+        .await
+        .add_market_instance("Node-2")
+        .await;
+
+    let market1 = network.get_market("Node-1");
+    let id1 = network.get_default_id("Node-1");
+    let market2 = network.get_market("Node-2");
+
+    let subscription_id = market1
+        .subscribe_offer(&client::sample_offer(), &id1)
+        .await
+        .unwrap();
+    wait_for_bcast(1000, &market2, &subscription_id, true).await;
+
+    network.break_networking_for("Node-1").unwrap();
+    let network = network.add_market_instance("Node-3").await;
+    let market3 = network.get_market("Node-3");
+
+    wait_for_bcast(1000, &market3, &subscription_id, true).await;
+    market3.get_offer(&subscription_id).await.unwrap();
+}
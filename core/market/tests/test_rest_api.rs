@@ -1,7 +1,12 @@
 use actix_web::{
-    body::MessageBody, dev::ServiceResponse, error::PathError, http::StatusCode, test,
+    body::MessageBody,
+    dev::ServiceResponse,
+    error::PathError,
+    http::{header, StatusCode},
+    test,
 };
 use chrono::Utc;
+use futures::StreamExt;
 use serde::de::DeserializeOwned;
 use serde_json::json;
 
@@ -11,6 +16,7 @@ use ya_client::model::market::{
 };
 use ya_client::model::ErrorMessage;
 use ya_client::web::QueryParamsBuilder;
+use ya_core_model::market::MarketSettings;
 use ya_market::testing::agreement_utils::negotiate_agreement;
 use ya_market::testing::events_helper::requestor::expect_approve;
 use ya_market::testing::{
@@ -18,7 +24,8 @@ use ya_market::testing::{
     mock_node::{wait_for_bcast, MarketServiceExt},
     mock_offer::flatten_json,
     proposal_util::exchange_draft_proposals,
-    DemandError, MarketsNetwork, ModifyOfferError, Owner, SubscriptionId, SubscriptionParseError,
+    DemandError, MarketsNetwork, ModifyOfferError, OfferScanPage, Owner, QueryOffersError,
+    SubscriptionId, SubscriptionParseError,
 };
 
 const REQ_NAME: &str = "Node-1";
@@ -420,6 +427,76 @@ async fn test_rest_query_agreement_events() {
     expect_approve(events, "After agreementEvents").unwrap();
 }
 
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_rest_stream_agreement_events() {
+    let network = MarketsNetwork::new(None)
+        .await
+        .add_market_instance("Node-1")
+        .await
+        .add_market_instance("Node-2")
+        .await;
+
+    let negotiation = negotiate_agreement(
+        &network,
+        "Node-1",
+        "Node-2",
+        "negotiation",
+        "r-session",
+        "p-session",
+    )
+    .await
+    .unwrap();
+
+    let mut app = network.get_rest_app("Node-1").await;
+    let url = format!(
+        "/market-api/v1/agreementEvents/stream?{}",
+        QueryParamsBuilder::new()
+            .put("afterTimestamp", Some(negotiation.confirm_timestamp))
+            .put("appSessionId", Some("r-session"))
+            .build()
+    );
+    let req = test::TestRequest::get().uri(&url).to_request();
+    let mut resp = test::call_service(&mut app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        resp.headers().get(header::CONTENT_TYPE).unwrap(),
+        "text/event-stream"
+    );
+
+    // Stream never ends, so we can read only first chunk.
+    let chunk = resp.take_body().next().await.unwrap().unwrap();
+    let chunk = String::from_utf8(chunk.to_vec()).unwrap();
+    let events = chunk
+        .split("\n\n")
+        .filter_map(|message| message.strip_prefix("data: "))
+        .map(|json| serde_json::from_str(json).unwrap())
+        .collect::<Vec<AgreementOperationEvent>>();
+
+    expect_approve(events, "After agreementEvents/stream").unwrap();
+}
+
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_rest_stream_events_not_existing_subscription_should_return_404() {
+    let network = MarketsNetwork::new(None)
+        .await
+        .add_market_instance("Node-1")
+        .await;
+
+    let mut app = network.get_rest_app("Node-1").await;
+    let subscription_id = "c76161077d0343ab85ac986eb5f6ea38-edb0016d9f8bafb54540da34f05a8d510de8114488f23916276bdead05509a53";
+    for url in &[
+        format!("/market-api/v1/demands/{}/events/stream", subscription_id),
+        format!("/market-api/v1/offers/{}/events/stream", subscription_id),
+    ] {
+        let req = test::TestRequest::get().uri(url).to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+}
+
 #[cfg_attr(not(feature = "test-suite"), ignore)]
 #[serial_test::serial]
 async fn test_terminate_agreement() {
@@ -543,6 +620,131 @@ async fn test_terminate_agreement_without_reason() {
     );
 }
 
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_rest_scan_offers() {
+    let network = MarketsNetwork::new(None)
+        .await
+        .add_market_instance(REQ_NAME)
+        .await
+        .add_market_instance(PROV_NAME)
+        .await;
+
+    let market_prov = network.get_market(PROV_NAME);
+    let identity_prov = network.get_default_id(PROV_NAME);
+
+    let offer_small = NewOffer::new(json!({"golem.inf.mem.gib": 1}), "()".to_string());
+    let offer_big = NewOffer::new(json!({"golem.inf.mem.gib": 8}), "()".to_string());
+    let _small_id = market_prov
+        .subscribe_offer(&offer_small, &identity_prov)
+        .await
+        .unwrap();
+    let big_id = market_prov
+        .subscribe_offer(&offer_big, &identity_prov)
+        .await
+        .unwrap();
+
+    let market_req = network.get_market(REQ_NAME);
+    wait_for_bcast(1000, &market_req, &big_id, true).await;
+
+    let mut app = network.get_rest_app(REQ_NAME).await;
+    let req = test::TestRequest::post()
+        .uri("/market-api/v1/offers/scan")
+        .set_json(&json!({"constraints": "(golem.inf.mem.gib>4)"}))
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let page: OfferScanPage = read_response_json(resp).await;
+    assert_eq!(page.total, 1);
+    assert_eq!(page.offset, 0);
+    assert_eq!(page.offers.len(), 1);
+    assert_eq!(page.offers[0].offer_id, big_id.to_string());
+
+    // Scanning doesn't create any negotiation state on Requestor side.
+    assert!(market_req.get_demands(None).await.unwrap().is_empty());
+
+    let req = test::TestRequest::post()
+        .uri("/market-api/v1/offers/scan?offset=1")
+        .set_json(&json!({"constraints": "(golem.inf.mem.gib>0)"}))
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let page: OfferScanPage = read_response_json(resp).await;
+    assert_eq!(page.total, 2);
+    assert_eq!(page.offset, 1);
+    assert_eq!(page.offers.len(), 1);
+}
+
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_rest_scan_offers_invalid_query_should_return_400() {
+    let network = MarketsNetwork::new(None)
+        .await
+        .add_market_instance(REQ_NAME)
+        .await;
+    let mut app = network.get_rest_app(REQ_NAME).await;
+
+    let req = test::TestRequest::post()
+        .uri("/market-api/v1/offers/scan")
+        .set_json(&json!({"constraints": "werwer(golem.inf.mem.gib>4)"}))
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::post()
+        .uri("/market-api/v1/offers/scan?limit=0")
+        .set_json(&json!({"constraints": "()"}))
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let result: ErrorMessage = read_response_json(resp).await;
+    assert_eq!(
+        QueryOffersError::InvalidLimit(0, 500).to_string(),
+        result.message.unwrap()
+    );
+}
+
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_rest_settings_change_bcast_intervals() {
+    let network = MarketsNetwork::new(None)
+        .await
+        .add_market_instance(REQ_NAME)
+        .await;
+    let mut app = network.get_rest_app(REQ_NAME).await;
+
+    let req = test::TestRequest::get()
+        .uri("/market-api/v1/admin/settings")
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let settings: MarketSettings = read_response_json(resp).await;
+    assert_eq!(settings.mean_cyclic_bcast_interval_ms, 60_000);
+    assert_eq!(settings.max_events_max, 100);
+
+    let req = test::TestRequest::put()
+        .uri("/market-api/v1/admin/settings/bcastIntervals")
+        .set_json(&json!({"meanCyclicBcastIntervalMs": 500}))
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let updated: MarketSettings = read_response_json(resp).await;
+    assert_eq!(updated.mean_cyclic_bcast_interval_ms, 500);
+    assert_eq!(
+        updated.mean_cyclic_unsubscribes_interval_ms,
+        settings.mean_cyclic_unsubscribes_interval_ms
+    );
+
+    let req = test::TestRequest::put()
+        .uri("/market-api/v1/admin/settings/bcastIntervals")
+        .set_json(&json!({"meanCyclicUnsubscribesIntervalMs": 0}))
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
 // #[cfg_attr(not(feature = "test-suite"), ignore)]
 // #[actix_rt::test]
 // #[serial_test::serial]
@@ -0,0 +1,68 @@
+use structopt::StructOpt;
+
+use ya_core_model::metrics;
+use ya_service_api::{CliCtx, CommandOutput};
+use ya_service_bus::{typed as bus, RpcEndpoint};
+
+/// Metrics of the running service.
+///
+/// Prometheus can scrape them from `/metrics-api/v1/metrics` (or `/metrics-api/v1/expose`)
+/// using an app key with `metrics:read` scope. Note that `/expose` doesn't accept
+/// requests without the key anymore.
+#[derive(StructOpt, Debug)]
+pub enum MetricsCli {
+    /// Show current metrics in Prometheus text format.
+    Show {
+        /// Only metrics whose name starts with the given prefix, e.g. `market`
+        #[structopt(long)]
+        prefix: Option<String>,
+    },
+}
+
+impl MetricsCli {
+    pub async fn run_command(self, _ctx: &CliCtx) -> anyhow::Result<CommandOutput> {
+        match self {
+            MetricsCli::Show { prefix } => {
+                let text = bus::service(metrics::BUS_ID)
+                    .send(metrics::GetMetrics {})
+                    .await??;
+                CommandOutput::object(match prefix {
+                    Some(prefix) => filter_prefix(&text, &prefix),
+                    None => text,
+                })
+            }
+        }
+    }
+}
+
+/// Keeps samples, `# HELP` and `# TYPE` lines of metrics matching the prefix.
+/// Dots in the prefix match the underscores Prometheus uses in metric names.
+fn filter_prefix(text: &str, prefix: &str) -> String {
+    let prefix = prefix.replace('.', "_");
+    text.lines()
+        .filter(|line| {
+            let name = line
+                .trim_start_matches("# HELP ")
+                .trim_start_matches("# TYPE ");
+            name.starts_with(&prefix)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::filter_prefix;
+
+    #[test]
+    fn test_filter_prefix() {
+        let text = "# TYPE market_offers_incoming counter\n\
+                    market_offers_incoming 3\n\
+                    # TYPE net_calls_incoming counter\n\
+                    net_calls_incoming 5";
+        assert_eq!(
+            "# TYPE net_calls_incoming counter\nnet_calls_incoming 5",
+            filter_prefix(text, "net.calls")
+        );
+    }
+}
@@ -1,8 +1,10 @@
+mod cli;
 mod exporter;
 mod metrics;
 pub(crate) mod pusher;
 mod service;
 
+pub use cli::MetricsCli;
 pub use service::{MetricsPusherOpts, MetricsService};
 
 pub mod utils {
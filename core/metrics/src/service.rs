@@ -3,19 +3,26 @@ use lazy_static::lazy_static;
 use std::sync::Arc;
 use url::Url;
 
+use ya_core_model::metrics;
 use ya_service_api::{CliCtx, MetricsCtx};
-use ya_service_api_interfaces::Provider;
+use ya_service_api_interfaces::{Provider, Service};
+use ya_service_bus::typed as bus;
 
 use crate::metrics::Metrics;
 
 const YAGNA_METRICS_URL_ENV_VAR: &str = "YAGNA_METRICS_URL";
 const DEFAULT_YAGNA_METRICS_URL: &str = "https://metrics.golem.network:9092/";
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
 
-// TODO: enable showing metrics also via CLI
 #[derive(structopt::StructOpt, Debug)]
 pub struct MetricsPusherOpts {
-    /// Disable metrics pushing
+    /// Push metrics to `--metrics-push-url`. By default metrics stay local
+    /// and are only available via `yagna metrics show` and the metrics API.
     #[structopt(long)]
+    pub enable_metrics_push: bool,
+
+    /// Deprecated, metrics aren't pushed unless `--enable-metrics-push` is given.
+    #[structopt(long, hidden = true)]
     pub disable_metrics_push: bool,
 
     /// Metrics push host url
@@ -30,7 +37,7 @@ pub struct MetricsPusherOpts {
 impl From<&MetricsPusherOpts> for MetricsCtx {
     fn from(opts: &MetricsPusherOpts) -> Self {
         MetricsCtx {
-            push_enabled: !opts.disable_metrics_push,
+            push_enabled: opts.enable_metrics_push && !opts.disable_metrics_push,
             push_host_url: Some(opts.metrics_push_url.clone()),
         }
     }
@@ -38,6 +45,10 @@ impl From<&MetricsPusherOpts> for MetricsCtx {
 
 pub struct MetricsService;
 
+impl Service for MetricsService {
+    type Cli = crate::cli::MetricsCli;
+}
+
 lazy_static! {
     static ref METRICS: Arc<Mutex<Metrics>> = Metrics::new();
 }
@@ -47,6 +58,10 @@ impl MetricsService {
         // This should initialize Metrics. We need to do this before all other services will start.
         let _ = METRICS.clone();
 
+        let _ = bus::bind(metrics::BUS_ID, |_: metrics::GetMetrics| async {
+            Ok(export_metrics().await)
+        });
+
         crate::pusher::spawn(
             context
                 .component()
@@ -58,11 +73,19 @@ impl MetricsService {
 
     pub fn rest<C: Provider<Self, ()>>(_ctx: &C) -> actix_web::Scope {
         actix_web::Scope::new("metrics-api/v1")
-            // TODO:: add wrapper injecting Bearer to avoid hack in auth middleware
-            .route("/expose", actix_web::web::get().to(export_metrics))
+            .route("/expose", actix_web::web::get().to(scrape_metrics))
+            .route("/metrics", actix_web::web::get().to(scrape_metrics))
     }
 }
 
+/// Prometheus scrape target. Both `/expose` and `/metrics` require an application
+/// key with `metrics:read` scope; `/expose` used to be available without one.
+async fn scrape_metrics() -> actix_web::HttpResponse {
+    actix_web::HttpResponse::Ok()
+        .content_type(PROMETHEUS_CONTENT_TYPE)
+        .body(export_metrics().await)
+}
+
 pub async fn export_metrics() -> String {
     METRICS.lock().await.export()
 }
@@ -2,6 +2,7 @@
 //!
 //! Top level objects constitutes public activity API.
 //! Local and Exeunit are in dedicated submodules.
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::Role;
@@ -138,6 +139,21 @@ impl RpcMessage for GetUsage {
     type Error = RpcMessageError;
 }
 
+/// Get the history of activity usage counters, oldest first.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetUsageHistory {
+    pub activity_id: String,
+    pub since: Option<DateTime<Utc>>,
+    pub timeout: Option<f32>,
+}
+
+impl RpcMessage for GetUsageHistory {
+    const ID: &'static str = "GetActivityUsageHistory";
+    type Item = Vec<ActivityUsage>;
+    type Error = RpcMessageError;
+}
+
 pub mod sgx {
     use super::*;
 
@@ -220,13 +236,43 @@ impl RpcMessage for GetRunningCommand {
     type Error = RpcMessageError;
 }
 
+/// Input for a running command of an ExeScript batch.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CommandInput {
+    /// Write text to command's standard input.
+    Stdin { text: String },
+    /// Close command's standard input.
+    Eof {},
+    /// Change size of command's pseudo-terminal.
+    Resize { cols: u16, rows: u16 },
+}
+
+/// Send input to a running command. Supported only by ExeUnits, which provider
+/// allowed to run interactive commands.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendCommandInput {
+    pub activity_id: String,
+    pub batch_id: String,
+    pub command_index: usize,
+    pub input: CommandInput,
+    pub timeout: Option<f32>,
+}
+
+impl RpcMessage for SendCommandInput {
+    const ID: &'static str = "SendCommandInput";
+    type Item = ();
+    type Error = RpcMessageError;
+}
+
 /// Local activity bus API (used by ExeUnit).
 ///
 /// Should be accessible only from local service bus (not via net ie. from remote hosts).
 pub mod local {
     use super::*;
-    use chrono::{DateTime, Utc};
     use std::collections::BTreeMap;
+    use ya_client_model::activity::State;
 
     /// Local activity bus address.
     pub const BUS_ID: &str = "/local/activity";
@@ -252,6 +298,37 @@ pub mod local {
         type Error = RpcMessageError;
     }
 
+    /// List activities of the identity, newest first.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ListActivities {
+        pub identity: NodeId,
+        pub agreement_id: Option<String>,
+        pub state: Option<State>,
+        pub created_after: Option<DateTime<Utc>>,
+        pub role: Option<Role>,
+        pub offset: Option<u32>,
+        pub limit: Option<u32>,
+    }
+
+    impl RpcMessage for ListActivities {
+        const ID: &'static str = "ListActivities";
+        type Item = Vec<ActivityInfo>;
+        type Error = RpcMessageError;
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ActivityInfo {
+        pub activity_id: String,
+        pub agreement_id: String,
+        pub role: Role,
+        pub state: ActivityState,
+        /// Unknown for requestor's activities created by older versions. These are
+        /// listed only after their owner is recovered from the agreement.
+        pub created_date: Option<DateTime<Utc>>,
+    }
+
     /// Set state of the activity.
     #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
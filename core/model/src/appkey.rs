@@ -1,5 +1,8 @@
-use chrono::NaiveDateTime;
+use chrono::{NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
 use thiserror::Error;
 use ya_client_model::NodeId;
 use ya_service_bus::RpcMessage;
@@ -10,6 +13,14 @@ pub const DEFAULT_ROLE: &str = "manager";
 
 const DEFAULT_PAGE_SIZE: u32 = 20;
 
+/// Last use time of a key is updated at most once per this interval.
+pub const LAST_USED_INTERVAL: Duration = Duration::from_secs(60);
+
+/// REST APIs, which can be granted separately.
+pub const APIS: &[&str] = &[
+    "market", "activity", "payment", "net", "identity", "metrics",
+];
+
 #[derive(Clone, Error, Debug, Serialize, Deserialize)]
 #[error("appkey error [{code}]: {message}")]
 pub struct Error {
@@ -24,6 +35,90 @@ impl Error {
             message: e.to_string(),
         }
     }
+
+    pub fn bad_request(e: impl std::fmt::Display) -> Self {
+        Self {
+            code: 400,
+            message: e.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+    /// Running ExeScript commands on activity.
+    Exec,
+    /// Changing node's runtime settings via `/admin` endpoints. Isn't implied
+    /// by `write`, but keys with `*` access or without scopes have it.
+    Admin,
+    All,
+}
+
+/// Access to REST API of a single service, e.g. `market:read`, `activity:exec`
+/// or `payment:*`. `*` in place of API name grants given access to all APIs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scope {
+    pub api: String,
+    pub access: Access,
+}
+
+impl Scope {
+    pub fn new(api: impl ToString, access: Access) -> Self {
+        Scope {
+            api: api.to_string(),
+            access,
+        }
+    }
+
+    /// Checks, if this granted scope covers `required` one. Every kind of
+    /// access implies read access.
+    pub fn allows(&self, required: &Scope) -> bool {
+        let api = self.api == "*" || self.api == required.api;
+        let access = match (self.access, required.access) {
+            (Access::All, _) | (_, Access::Read) => true,
+            (granted, required) => granted == required,
+        };
+        api && access
+    }
+}
+
+impl FromStr for Scope {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::bad_request(format!("invalid scope: {}", s));
+        let (api, access) = match s.trim().split(':').collect::<Vec<_>>()[..] {
+            [api, access] => (api, access),
+            _ => return Err(invalid()),
+        };
+        if api != "*" && !APIS.contains(&api) {
+            return Err(invalid());
+        }
+        let access = match access {
+            "read" => Access::Read,
+            "write" => Access::Write,
+            "exec" => Access::Exec,
+            "admin" => Access::Admin,
+            "*" => Access::All,
+            _ => return Err(invalid()),
+        };
+        Ok(Scope::new(api, access))
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let access = match self.access {
+            Access::Read => "read",
+            Access::Write => "write",
+            Access::Exec => "exec",
+            Access::Admin => "admin",
+            Access::All => "*",
+        };
+        write!(f, "{}:{}", self.api, access)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -32,8 +127,14 @@ pub struct Create {
     pub name: String,
     pub role: String,
     pub identity: NodeId,
+    /// Key without scopes has access to all APIs.
+    #[serde(default)]
+    pub scopes: Option<Vec<String>>,
+    #[serde(default)]
+    pub expires_date: Option<NaiveDateTime>,
 }
 
+/// Resolves key used for authentication.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Get {
@@ -46,6 +147,14 @@ impl Get {
     }
 }
 
+/// Records use of the key. Sent by REST middleware for authenticated
+/// requests, at most once per `LAST_USED_INTERVAL` for given key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkUsed {
+    pub key: String,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct List {
@@ -79,6 +188,32 @@ pub struct AppKey {
     pub role: String,
     pub identity: NodeId,
     pub created_date: NaiveDateTime,
+    #[serde(default)]
+    pub scopes: Option<Vec<String>>,
+    #[serde(default)]
+    pub expires_date: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub last_used_date: Option<NaiveDateTime>,
+}
+
+impl AppKey {
+    pub fn is_expired(&self) -> bool {
+        self.expires_date
+            .map(|expires| expires <= Utc::now().naive_utc())
+            .unwrap_or(false)
+    }
+
+    /// Keys created without scopes have full access. Malformed scopes
+    /// don't grant anything.
+    pub fn allows(&self, required: &Scope) -> bool {
+        match &self.scopes {
+            None => true,
+            Some(scopes) => scopes
+                .iter()
+                .filter_map(|scope| scope.parse::<Scope>().ok())
+                .any(|scope| scope.allows(required)),
+        }
+    }
 }
 
 impl RpcMessage for Create {
@@ -93,6 +228,12 @@ impl RpcMessage for Get {
     type Error = Error;
 }
 
+impl RpcMessage for MarkUsed {
+    const ID: &'static str = "MarkUsed";
+    type Item = ();
+    type Error = Error;
+}
+
 impl RpcMessage for List {
     const ID: &'static str = "List";
     type Item = (Vec<AppKey>, u32);
@@ -135,3 +276,72 @@ pub mod event {
         type Error = Error;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(s: &str) -> Scope {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_scope() {
+        assert_eq!(scope("market:read"), Scope::new("market", Access::Read));
+        assert_eq!(scope("*:*"), Scope::new("*", Access::All));
+        assert_eq!(scope("activity:exec").to_string(), "activity:exec");
+        assert_eq!(scope("market:admin"), Scope::new("market", Access::Admin));
+        assert!("market".parse::<Scope>().is_err());
+        assert!("market:delete".parse::<Scope>().is_err());
+        assert!("wallet:read".parse::<Scope>().is_err());
+    }
+
+    #[test]
+    fn test_scope_allows() {
+        let required = Scope::new("payment", Access::Write);
+        assert!(scope("payment:write").allows(&required));
+        assert!(scope("payment:*").allows(&required));
+        assert!(scope("*:write").allows(&required));
+        assert!(!scope("payment:read").allows(&required));
+        assert!(!scope("market:write").allows(&required));
+
+        assert!(scope("activity:exec").allows(&Scope::new("activity", Access::Read)));
+        assert!(!scope("activity:exec").allows(&Scope::new("activity", Access::Write)));
+
+        let required = Scope::new("market", Access::Admin);
+        assert!(scope("market:admin").allows(&required));
+        assert!(scope("*:admin").allows(&required));
+        assert!(!scope("market:write").allows(&required));
+        assert!(scope("market:*").allows(&required));
+        assert!(scope("*:*").allows(&required));
+        assert!(scope("market:admin").allows(&Scope::new("market", Access::Read)));
+        assert!(!scope("market:admin").allows(&Scope::new("market", Access::Write)));
+    }
+
+    #[test]
+    fn test_app_key_access() {
+        let mut app_key = AppKey {
+            name: "test".to_string(),
+            key: "key".to_string(),
+            role: DEFAULT_ROLE.to_string(),
+            identity: NodeId::from(&[0u8; 20][..]),
+            created_date: Utc::now().naive_utc(),
+            scopes: None,
+            expires_date: None,
+            last_used_date: None,
+        };
+        assert!(app_key.allows(&Scope::new("payment", Access::Write)));
+        assert!(app_key.allows(&Scope::new("market", Access::Admin)));
+        assert!(!app_key.is_expired());
+
+        app_key.scopes = Some(vec!["market:read".to_string()]);
+        assert!(app_key.allows(&Scope::new("market", Access::Read)));
+        assert!(!app_key.allows(&Scope::new("payment", Access::Write)));
+
+        app_key.scopes = Some(vec!["market:write".to_string()]);
+        assert!(!app_key.allows(&Scope::new("market", Access::Admin)));
+
+        app_key.expires_date = Some(Utc::now().naive_utc() - chrono::Duration::seconds(1));
+        assert!(app_key.is_expired());
+    }
+}
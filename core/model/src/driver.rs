@@ -441,3 +441,53 @@ impl RpcMessage for Transfer {
     type Item = String; // Transaction Identifier
     type Error = GenericError;
 }
+
+// ************************** DRIVER STATUS **************************
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetDriverStatus {}
+
+impl RpcMessage for GetDriverStatus {
+    const ID: &'static str = "GetDriverStatus";
+    type Item = DriverStatus;
+    type Error = GenericError;
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriverStatus {
+    pub driver: String,
+    /// Last payment job run, which reported no errors.
+    pub last_payment_run: Option<DateTime<Utc>>,
+    /// Last confirmation job run, which reported no errors.
+    pub last_confirmation_run: Option<DateTime<Utc>>,
+    pub pending_payments: u64,
+    pub failed_payments: u64,
+    pub unconfirmed_txs: u64,
+    pub failed_txs: u64,
+    /// Submission time of the oldest transaction waiting for confirmation.
+    pub oldest_unconfirmed_tx: Option<DateTime<Utc>>,
+    pub last_error: Option<DriverError>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriverError {
+    pub message: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+// ************************** REQUEUE FAILED PAYMENTS **************************
+
+/// Makes failed payments pending again, so the driver retries them.
+/// Empty `order_ids` requeue all failed payments.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RequeueFailedPayments {
+    pub order_ids: Vec<String>,
+}
+
+impl RpcMessage for RequeueFailedPayments {
+    const ID: &'static str = "RequeueFailedPayments";
+    type Item = u64; // Number of requeued payments
+    type Error = GenericError;
+}
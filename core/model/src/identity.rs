@@ -20,6 +20,10 @@ pub enum Error {
     InternalErr(String),
     #[error("bad keystore format: {0}")]
     BadKeyStoreFormat(String),
+    #[error("invalid password")]
+    InvalidPassword,
+    #[error("invalid signature: {0}")]
+    InvalidSignature(String),
 }
 
 impl Error {
@@ -199,6 +203,47 @@ impl RpcMessage for Sign {
     type Error = Error;
 }
 
+/// Expected author of verified signature.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Signer {
+    /// Signature produced by `Sign`: 65 bytes (`v || r || s`) over 32 byte payload.
+    NodeId(NodeId),
+    /// DER signature of sha3-256 digest of the payload, produced by exe-unit
+    /// `Sign` command. Public key is SEC1 encoded secp256k1 key.
+    PublicKey(Vec<u8>),
+}
+
+/// Checks, that `signature` of `payload` was made by `signer`. Mismatched
+/// signature isn't an error; malformed one is.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Verify {
+    pub signer: Signer,
+    pub payload: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+impl RpcMessage for Verify {
+    const ID: &'static str = "Verify";
+    type Item = bool;
+    type Error = Error;
+}
+
+/// Returns node, which produced `signature` of `payload` with `Sign`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Recover {
+    pub payload: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+impl RpcMessage for Recover {
+    const ID: &'static str = "Recover";
+    type Item = NodeId;
+    type Error = Error;
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Subscribe {
@@ -211,6 +256,56 @@ impl RpcMessage for Subscribe {
     type Error = Error;
 }
 
+/// Derivation path of the first account in Ethereum wallets (BIP-44).
+pub const DEFAULT_DERIVATION_PATH: &str = "m/44'/60'/0'/0/0";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KeySource {
+    /// Ethereum JSON keystore (version 3).
+    KeyStore(String),
+    /// BIP-39 english mnemonic phrase.
+    Mnemonic {
+        phrase: String,
+        /// Defaults to `DEFAULT_DERIVATION_PATH`.
+        derivation_path: Option<String>,
+    },
+}
+
+/// Imports existing key as a new identity. Fails, when identity or alias
+/// already exists.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Import {
+    pub alias: Option<String>,
+    pub source: KeySource,
+    /// Password of imported keystore. Key derived from mnemonic is stored
+    /// encrypted with this password.
+    pub password: String,
+}
+
+impl RpcMessage for Import {
+    const ID: &'static str = "Import";
+    type Item = IdentityInfo;
+    type Error = Error;
+}
+
+/// Re-encrypts stored key file. Lock state of the identity doesn't change.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangePassword {
+    pub node_id: NodeId,
+    pub old_password: String,
+    pub new_password: String,
+}
+
+impl RpcMessage for ChangePassword {
+    const ID: &'static str = "ChangePassword";
+    type Item = IdentityInfo;
+    type Error = Error;
+}
+
+/// Returns key file in Ethereum JSON keystore format.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GetKeyFile(pub NodeId);
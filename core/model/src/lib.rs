@@ -16,6 +16,9 @@ pub mod identity;
 #[cfg(feature = "market")]
 pub mod market;
 
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
 #[cfg(feature = "net")]
 pub mod net;
 
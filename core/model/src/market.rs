@@ -1,5 +1,6 @@
 //! Market service bus API.
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 use crate::Role;
 pub use ya_client_model::market::Agreement;
@@ -41,6 +42,81 @@ impl RpcMessage for GetAgreement {
     type Error = RpcMessageError;
 }
 
+/// Returns current market settings. Bound only on local bus.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetSettings {}
+
+impl RpcMessage for GetSettings {
+    const ID: &'static str = "GetSettings";
+    type Item = MarketSettings;
+    type Error = RpcMessageError;
+}
+
+/// Changes intervals of cyclic Offers and unsubscribes broadcasts.
+/// Returns updated market settings. Bound only on local bus.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetBcastIntervals {
+    pub mean_cyclic_bcast_interval_ms: Option<u64>,
+    pub mean_cyclic_unsubscribes_interval_ms: Option<u64>,
+}
+
+impl RpcMessage for SetBcastIntervals {
+    const ID: &'static str = "SetBcastIntervals";
+    type Item = MarketSettings;
+    type Error = RpcMessageError;
+}
+
+/// Market settings used by running market service.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketSettings {
+    pub max_bcasted_offers: u32,
+    pub max_bcasted_unsubscribes: u32,
+    pub mean_cyclic_bcast_interval_ms: u64,
+    pub mean_cyclic_unsubscribes_interval_ms: u64,
+    pub subscription_ttl_secs: i64,
+    pub max_events_default: i32,
+    pub max_events_max: i32,
+    pub max_offers_default: i32,
+    pub max_offers_max: i32,
+}
+
+/// Removes outdated entries from market database according to retention
+/// settings. Bound only on local bus.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanDatabase {
+    /// Only count entries, that would be removed.
+    pub dry_run: bool,
+    /// Overrides directory, where removed Agreements are archived.
+    pub archive_dir: Option<PathBuf>,
+}
+
+impl RpcMessage for CleanDatabase {
+    const ID: &'static str = "CleanDatabase";
+    type Item = CleanReport;
+    type Error = RpcMessageError;
+}
+
+/// Number of entries removed (or to be removed in dry run) from market database.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanReport {
+    pub dry_run: bool,
+    pub demands: u64,
+    pub offers: u64,
+    pub offer_unsubscribes: u64,
+    pub negotiations: u64,
+    pub proposals: u64,
+    pub negotiation_events: u64,
+    pub agreements: u64,
+    pub agreement_events: u64,
+    /// File with removed Agreements, if they were archived.
+    pub agreements_archive: Option<PathBuf>,
+}
+
 /// Error message for market service bus API.
 #[derive(thiserror::Error, Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
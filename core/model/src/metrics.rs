@@ -0,0 +1,19 @@
+//! Metrics service bus API.
+
+use serde::{Deserialize, Serialize};
+
+use ya_client_model::ErrorMessage;
+use ya_service_bus::RpcMessage;
+
+pub const BUS_ID: &'static str = "/local/metrics";
+
+/// Current metrics in Prometheus text exposition format.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetMetrics {}
+
+impl RpcMessage for GetMetrics {
+    const ID: &'static str = "GetMetrics";
+    type Item = String;
+    type Error = ErrorMessage;
+}
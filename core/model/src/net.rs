@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use ya_client_model::node_id::ParseError;
 use ya_client_model::NodeId;
 use ya_service_bus::typed as bus;
@@ -10,10 +11,15 @@ pub const PUBLIC_PREFIX: &str = "/public";
 ///
 ///
 pub mod local {
+    use chrono::{DateTime, Utc};
     use serde::de::DeserializeOwned;
     use serde::{Deserialize, Serialize};
+    use std::time::Duration;
+    use ya_client_model::NodeId;
     use ya_service_bus::RpcMessage;
 
+    pub use super::DiagnosticsError;
+
     pub const BUS_ID: &str = "/local/net";
 
     pub trait BroadcastMessage: Serialize + DeserializeOwned {
@@ -105,6 +111,53 @@ pub mod local {
         RuntimeException(String),
     }
 
+    /// Returns state of connection with the hub.
+    #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Status {}
+
+    impl RpcMessage for Status {
+        const ID: &'static str = "Status";
+        type Item = NetStatus;
+        type Error = DiagnosticsError;
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct NetStatus {
+        pub connected: bool,
+        /// Hub we are connected to, or were connected to most recently.
+        pub hub_addr: Option<String>,
+        pub connected_since: Option<DateTime<Utc>>,
+        /// Number of successful connections after the first one.
+        pub reconnects: u64,
+        /// Node ids bound on the hub.
+        pub node_ids: Vec<NodeId>,
+        /// Broadcast topics subscribed on the hub.
+        pub topics: Vec<String>,
+    }
+
+    /// Measures round-trip time to remote node through the hub.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Ping {
+        pub node_id: NodeId,
+        pub timeout: Option<Duration>,
+    }
+
+    impl RpcMessage for Ping {
+        const ID: &'static str = "Ping";
+        type Item = PingResult;
+        type Error = DiagnosticsError;
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PingResult {
+        pub node_id: NodeId,
+        pub round_trip_ms: f64,
+    }
+
     #[derive(thiserror::Error, Debug)]
     pub enum BindBroadcastError {
         #[error(transparent)]
@@ -114,6 +167,72 @@ pub mod local {
     }
 }
 
+/// Messages bound by net service of each node, callable by other nodes.
+pub mod public {
+    use serde::{Deserialize, Serialize};
+    use ya_client_model::NodeId;
+    use ya_service_bus::RpcMessage;
+
+    pub use super::DiagnosticsError;
+
+    pub const BUS_ID: &str = "/public/net";
+
+    /// Answered immediately, used to measure latency between nodes.
+    #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Ping {}
+
+    impl RpcMessage for Ping {
+        const ID: &'static str = "Ping";
+        type Item = ();
+        type Error = DiagnosticsError;
+    }
+
+    /// Establishes session with `node_id` identity, before sending end-to-end
+    /// protected messages. Nodes, that don't answer it, get unprotected messages.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Handshake {
+        pub node_id: NodeId,
+        /// Ephemeral session public key of the caller.
+        #[serde(default)]
+        pub public_key: Vec<u8>,
+        /// Signature of `public_key` made with caller identity key.
+        #[serde(default)]
+        pub signature: Vec<u8>,
+    }
+
+    impl RpcMessage for Handshake {
+        const ID: &'static str = "Handshake";
+        type Item = HandshakeResponse;
+        type Error = HandshakeError;
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct HandshakeResponse {
+        /// Ephemeral session public key of `node_id`.
+        pub public_key: Vec<u8>,
+        /// Signature of both session public keys made with `node_id` identity key.
+        pub signature: Vec<u8>,
+    }
+
+    #[derive(thiserror::Error, Clone, Debug, Serialize, Deserialize)]
+    #[error("Handshake failed: {0}")]
+    pub struct HandshakeError(pub String);
+}
+
+#[derive(thiserror::Error, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiagnosticsError {
+    #[error("Node [{0}] unreachable: {1}")]
+    Unreachable(NodeId, String),
+    #[error("Node [{0}] didn't respond in {1:?}")]
+    Timeout(NodeId, std::time::Duration),
+    #[error("Net service error: {0}")]
+    Internal(String),
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum NetApiError {
     #[error("service bus address should have {} prefix: {0}", PUBLIC_PREFIX)]
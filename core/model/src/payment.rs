@@ -384,6 +384,83 @@ pub mod local {
         pub provider: InvoiceStatusNotes,
     }
 
+    /// Exports invoices, debit notes and payments of given period, grouped per agreement.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct ExportDocuments {
+        pub node_id: NodeId,
+        pub since: DateTime<Utc>,
+        pub until: DateTime<Utc>,
+    }
+
+    impl RpcMessage for ExportDocuments {
+        const ID: &'static str = "ExportDocuments";
+        type Item = AccountingReport;
+        type Error = GenericError;
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct AccountingReport {
+        pub node_id: NodeId,
+        pub since: DateTime<Utc>,
+        pub until: DateTime<Utc>,
+        pub agreements: Vec<AgreementReport>,
+    }
+
+    /// Documents and payments of the period, next to agreement totals
+    /// (which cover the whole agreement, not only the period).
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct AgreementReport {
+        pub agreement_id: String,
+        /// `provider` or `requestor`.
+        pub role: String,
+        pub peer_id: NodeId,
+        pub payment_platform: String,
+        pub payer_addr: String,
+        pub payee_addr: String,
+        pub total_amount_due: BigDecimal,
+        pub total_amount_accepted: BigDecimal,
+        pub total_amount_paid: BigDecimal,
+        pub invoices: Vec<DocumentEntry>,
+        pub activities: Vec<ActivityReport>,
+        pub payments: Vec<PaymentEntry>,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ActivityReport {
+        pub activity_id: String,
+        pub total_amount_due: BigDecimal,
+        pub total_amount_accepted: BigDecimal,
+        pub total_amount_paid: BigDecimal,
+        pub debit_notes: Vec<DocumentEntry>,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DocumentEntry {
+        pub id: String,
+        pub status: DocumentStatus,
+        pub amount: BigDecimal,
+        pub timestamp: DateTime<Utc>,
+    }
+
+    /// Share of a payment, which went to the agreement or one of its activities.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PaymentEntry {
+        pub payment_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub activity_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub allocation_id: Option<String>,
+        pub amount: BigDecimal,
+        /// Driver's payment confirmation (transaction hash for on-chain drivers), hex encoded.
+        pub transaction_id: String,
+        pub timestamp: DateTime<Utc>,
+    }
+
     #[derive(Clone, Debug, Serialize, Deserialize)]
     pub struct ValidateAllocation {
         pub platform: String,
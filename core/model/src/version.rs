@@ -19,6 +19,31 @@ impl RpcMessage for Skip {
     type Error = ErrorMessage;
 }
 
+/// Download, verify and install the latest release of configured channel,
+/// or the given version. Installed binaries are used after restart.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Upgrade {
+    pub version: Option<String>,
+}
+
+impl RpcMessage for Upgrade {
+    const ID: &'static str = "upgrade";
+    type Item = Release;
+    type Error = ErrorMessage;
+}
+
+/// Restore binaries replaced by the last upgrade. Returns restored version.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rollback();
+
+impl RpcMessage for Rollback {
+    const ID: &'static str = "rollback";
+    type Item = String;
+    type Error = ErrorMessage;
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Get {
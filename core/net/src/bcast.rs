@@ -43,4 +43,8 @@ impl BCastService {
             })
             .unwrap_or_default()
     }
+
+    pub fn topics(&self) -> Vec<String> {
+        self.inner.borrow().topics.keys().cloned().collect()
+    }
 }
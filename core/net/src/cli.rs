@@ -0,0 +1,80 @@
+use std::time::Duration;
+use structopt::StructOpt;
+
+use ya_core_model::net::local as local_net;
+use ya_core_model::NodeId;
+use ya_service_api::{CliCtx, CommandOutput, ResponseTable};
+use ya_service_bus::{typed as bus, RpcEndpoint};
+
+use crate::diagnostics::MAX_PING_TIMEOUT;
+
+/// Network diagnostics.
+#[derive(StructOpt, Debug)]
+pub enum NetCli {
+    /// Show state of connection with the hub.
+    Status,
+    /// Measure round-trip time to remote node through the hub.
+    Ping {
+        node_id: NodeId,
+        /// How long to wait for the response (at most 5min).
+        #[structopt(long, parse(try_from_str = humantime::parse_duration), default_value = "10s")]
+        timeout: Duration,
+    },
+}
+
+impl NetCli {
+    pub async fn run_command(self, ctx: &CliCtx) -> anyhow::Result<CommandOutput> {
+        match self {
+            NetCli::Status => {
+                let status = bus::service(local_net::BUS_ID)
+                    .send(local_net::Status {})
+                    .await??;
+                if ctx.json_output {
+                    return CommandOutput::object(status);
+                }
+
+                let connected_since = status
+                    .connected_since
+                    .map(|since| since.to_rfc3339())
+                    .unwrap_or_default();
+                Ok(ResponseTable {
+                    columns: vec![
+                        "connected".to_owned(),
+                        "hub".to_owned(),
+                        "since".to_owned(),
+                        "reconnects".to_owned(),
+                        "topics".to_owned(),
+                    ],
+                    values: vec![serde_json::json! {[
+                        status.connected,
+                        status.hub_addr.unwrap_or_default(),
+                        connected_since,
+                        status.reconnects,
+                        status.topics.join(", "),
+                    ]}],
+                }
+                .into())
+            }
+            NetCli::Ping { node_id, timeout } => {
+                let result = bus::service(local_net::BUS_ID)
+                    .send(local_net::Ping {
+                        node_id,
+                        timeout: Some(timeout.min(MAX_PING_TIMEOUT)),
+                    })
+                    .await??;
+                if ctx.json_output {
+                    return CommandOutput::object(result);
+                }
+
+                Ok(ResponseTable {
+                    columns: vec!["node".to_owned(), "round-trip [ms]".to_owned()],
+                    values: vec![serde_json::json! {[
+                        result.node_id,
+                        format!("{:.2}", result.round_trip_ms),
+                    ]}],
+                }
+                .into())
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+// Connection state reporting and latency measurement
+
+use chrono::{DateTime, Utc};
+use std::cell::RefCell;
+use std::net::SocketAddr;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use ya_core_model::net::local::{self as local_net, NetStatus, PingResult};
+use ya_core_model::net::{public as public_net, DiagnosticsError, RemoteEndpoint};
+use ya_core_model::NodeId;
+use ya_service_bus::{typed as bus, RpcEndpoint};
+
+use crate::bcast::BCastService;
+use crate::hub::HubSelector;
+
+const DEFAULT_PING_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longer timeouts are shortened to this value.
+pub const MAX_PING_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// State of the net service shared between consecutive hub connections.
+#[derive(Default)]
+pub struct NetState {
+    pub hubs: HubSelector,
+    hub_addr: Option<SocketAddr>,
+    connected_since: Option<DateTime<Utc>>,
+    connections: u64,
+    node_ids: Vec<NodeId>,
+    bcast: Option<BCastService>,
+}
+
+impl NetState {
+    pub fn on_connected(
+        &mut self,
+        hub_addr: SocketAddr,
+        node_ids: Vec<NodeId>,
+        bcast: BCastService,
+    ) {
+        self.hubs.mark_healthy(hub_addr);
+        self.hub_addr = Some(hub_addr);
+        self.connected_since = Some(Utc::now());
+        self.connections += 1;
+        self.node_ids = node_ids;
+        self.bcast = Some(bcast);
+    }
+
+    pub fn on_disconnected(&mut self) {
+        if let Some(hub_addr) = self.hub_addr {
+            self.hubs.mark_failed(hub_addr);
+        }
+        self.connected_since = None;
+        self.bcast = None;
+    }
+
+    pub fn status(&self) -> NetStatus {
+        NetStatus {
+            connected: self.connected_since.is_some(),
+            hub_addr: self.hub_addr.map(|addr| addr.to_string()),
+            connected_since: self.connected_since,
+            reconnects: self.connections.saturating_sub(1),
+            node_ids: self.node_ids.clone(),
+            topics: self
+                .bcast
+                .as_ref()
+                .map(|bcast| bcast.topics())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Diagnostic handlers are bound once and stay available between
+/// reconnections, so they can report disconnected state.
+pub fn bind_service(state: Rc<RefCell<NetState>>) {
+    let _ = bus::bind(local_net::BUS_ID, move |_: local_net::Status| {
+        let status = state.borrow().status();
+        async move { Ok(status) }
+    });
+    let _ = bus::bind(local_net::BUS_ID, |msg: local_net::Ping| ping(msg));
+    let _ = bus::bind(public_net::BUS_ID, |_: public_net::Ping| async { Ok(()) });
+}
+
+async fn ping(msg: local_net::Ping) -> Result<PingResult, DiagnosticsError> {
+    let node_id = msg.node_id;
+    let timeout = msg
+        .timeout
+        .unwrap_or(DEFAULT_PING_TIMEOUT)
+        .min(MAX_PING_TIMEOUT);
+    let start = Instant::now();
+
+    let response = node_id
+        .service(public_net::BUS_ID)
+        .send(public_net::Ping {});
+    match tokio::time::timeout(timeout, response).await {
+        Ok(Ok(Ok(()))) => Ok(PingResult {
+            node_id,
+            round_trip_ms: start.elapsed().as_secs_f64() * 1000.0,
+        }),
+        Ok(Ok(Err(e))) => Err(DiagnosticsError::Unreachable(node_id, e.to_string())),
+        Ok(Err(e)) => Err(DiagnosticsError::Unreachable(node_id, e.to_string())),
+        Err(_) => Err(DiagnosticsError::Timeout(node_id, timeout)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_reports_reconnects() {
+        let hub: SocketAddr = "127.0.0.1:7464".parse().unwrap();
+        let mut state = NetState::default();
+        assert!(!state.status().connected);
+
+        state.on_connected(hub, vec![], BCastService::default());
+        let status = state.status();
+        assert!(status.connected);
+        assert_eq!(status.reconnects, 0);
+        assert_eq!(status.hub_addr, Some(hub.to_string()));
+
+        state.on_disconnected();
+        let status = state.status();
+        assert!(!status.connected);
+        assert_eq!(status.hub_addr, Some(hub.to_string()));
+
+        state.on_connected(hub, vec![], BCastService::default());
+        assert_eq!(state.status().reconnects, 1);
+    }
+}
@@ -0,0 +1,268 @@
+// Central net hubs discovery, failover and health checking
+
+use futures::Future;
+use std::io::{Error as IoError, ErrorKind as IoErrorKind};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::time::Duration;
+
+use ya_utils_networking::srv_resolver;
+
+pub const CENTRAL_ADDR_ENV_VAR: &str = "CENTRAL_NET_HOST";
+const CENTRAL_ADDR_SRV_PREFIX: &str = "_net._tcp";
+
+/// Resolves all known hub addresses. `CENTRAL_NET_HOST` can contain comma
+/// separated list of hubs, otherwise all targets of SRV record are used.
+pub async fn resolve_hubs() -> std::io::Result<Vec<SocketAddr>> {
+    let hosts = match std::env::var(CENTRAL_ADDR_ENV_VAR) {
+        Ok(v) => split_hosts(&v),
+        Err(_) => srv_resolver::resolve_yagna_records(CENTRAL_ADDR_SRV_PREFIX).await?,
+    };
+    to_socket_addrs(hosts)
+}
+
+fn split_hosts(hosts: &str) -> Vec<String> {
+    hosts
+        .split(',')
+        .map(str::trim)
+        .filter(|host| !host.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Unresolvable hosts are skipped, so one misconfigured entry doesn't
+/// prevent connecting to the others.
+fn to_socket_addrs(hosts: Vec<String>) -> std::io::Result<Vec<SocketAddr>> {
+    let mut addrs = Vec::new();
+    for host in hosts {
+        match host.to_socket_addrs() {
+            Ok(resolved) => {
+                for addr in resolved {
+                    if !addrs.contains(&addr) {
+                        addrs.push(addr);
+                    }
+                }
+            }
+            Err(e) => log::warn!("Failed to resolve central net hub {}: {}", host, e),
+        }
+    }
+
+    if addrs.is_empty() {
+        return Err(IoError::new(
+            IoErrorKind::NotFound,
+            "central net hub address needed",
+        ));
+    }
+    Ok(addrs)
+}
+
+/// Decides in which order hubs should be tried. Hub that failed recently
+/// is moved to the end of the list, so we fail over to the next one.
+#[derive(Clone, Debug, Default)]
+pub struct HubSelector {
+    failed: Vec<SocketAddr>,
+}
+
+impl HubSelector {
+    pub fn order(&self, mut hubs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+        // Stable sort keeps original (priority) order among healthy hubs.
+        hubs.sort_by_key(|hub| self.failed.iter().position(|failed| failed == hub));
+        hubs
+    }
+
+    pub fn mark_failed(&mut self, hub: SocketAddr) {
+        self.failed.retain(|failed| failed != &hub);
+        self.failed.push(hub);
+    }
+
+    pub fn mark_healthy(&mut self, hub: SocketAddr) {
+        self.failed.retain(|failed| failed != &hub);
+    }
+}
+
+/// Connects to the first reachable hub.
+pub async fn connect_any<T, F, Fut>(
+    hubs: &[SocketAddr],
+    mut connect: F,
+) -> std::io::Result<(SocketAddr, T)>
+where
+    F: FnMut(SocketAddr) -> Fut,
+    Fut: Future<Output = std::io::Result<T>>,
+{
+    let mut last_error = IoError::new(IoErrorKind::NotFound, "no central net hub to connect");
+    for hub in hubs {
+        match connect(*hub).await {
+            Ok(conn) => return Ok((*hub, conn)),
+            Err(e) => {
+                log::warn!("Failed to connect to central net hub {}: {}", hub, e);
+                last_error = e;
+            }
+        }
+    }
+    Err(last_error)
+}
+
+#[derive(Clone, Debug)]
+pub struct HealthCheckConfig {
+    pub interval: Duration,
+    pub timeout: Duration,
+    /// Number of consecutive failed checks, after which hub is considered dead.
+    pub max_failures: u32,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        HealthCheckConfig {
+            interval: Duration::from_secs(30),
+            timeout: Duration::from_secs(10),
+            max_failures: 3,
+        }
+    }
+}
+
+/// Periodically pings hub. Resolves, when ping failed or hub didn't respond
+/// `max_failures` times in a row. Connection loss is detected independently,
+/// this check is meant for hubs, that keep connection open, but stopped routing messages.
+pub async fn monitor_health<F, Fut, T, E>(config: HealthCheckConfig, mut ping: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: std::fmt::Display,
+{
+    let mut failures = 0;
+    loop {
+        tokio::time::delay_for(config.interval).await;
+        let error = match tokio::time::timeout(config.timeout, ping()).await {
+            Ok(Ok(_)) => {
+                failures = 0;
+                continue;
+            }
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => "timeout".to_string(),
+        };
+
+        failures += 1;
+        log::debug!(
+            "Central net hub health check failed ({}/{}): {}",
+            failures,
+            config.max_failures,
+            error
+        );
+        if failures >= config.max_failures {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future;
+    use std::net::{TcpListener, TcpStream};
+
+    /// Stands in for a hub: accepts TCP connections (kernel completes the
+    /// handshake even without `accept` call).
+    fn hub_stand_in() -> (TcpListener, SocketAddr) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        (listener, addr)
+    }
+
+    fn dead_hub() -> SocketAddr {
+        let (_listener, addr) = hub_stand_in();
+        addr
+    }
+
+    fn fast_health_check() -> HealthCheckConfig {
+        HealthCheckConfig {
+            interval: Duration::from_millis(10),
+            timeout: Duration::from_millis(10),
+            max_failures: 3,
+        }
+    }
+
+    #[test]
+    fn test_hub_list_from_env_format() {
+        let hosts = split_hosts("127.0.0.1:7464, 127.0.0.2:7464,,127.0.0.1:7464");
+        let addrs = to_socket_addrs(hosts).unwrap();
+        assert_eq!(
+            addrs,
+            vec![
+                "127.0.0.1:7464".parse::<SocketAddr>().unwrap(),
+                "127.0.0.2:7464".parse().unwrap()
+            ]
+        );
+        assert!(to_socket_addrs(split_hosts(" , ")).is_err());
+    }
+
+    #[test]
+    fn test_failed_hub_is_tried_last() {
+        let hubs: Vec<SocketAddr> = vec![
+            "127.0.0.1:1".parse().unwrap(),
+            "127.0.0.1:2".parse().unwrap(),
+            "127.0.0.1:3".parse().unwrap(),
+        ];
+        let mut selector = HubSelector::default();
+        assert_eq!(selector.order(hubs.clone()), hubs);
+
+        selector.mark_failed(hubs[0]);
+        selector.mark_failed(hubs[1]);
+        assert_eq!(
+            selector.order(hubs.clone()),
+            vec![hubs[2], hubs[0], hubs[1]]
+        );
+
+        selector.mark_healthy(hubs[0]);
+        assert_eq!(
+            selector.order(hubs.clone()),
+            vec![hubs[0], hubs[2], hubs[1]]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_connect_fails_over_to_live_hub() {
+        let (_hub, live) = hub_stand_in();
+        let hubs = vec![dead_hub(), live];
+
+        let (connected, _) = connect_any(&hubs, |addr| future::ready(TcpStream::connect(addr)))
+            .await
+            .unwrap();
+        assert_eq!(connected, live);
+    }
+
+    #[actix_rt::test]
+    async fn test_connect_fails_when_all_hubs_down() {
+        let hubs = vec![dead_hub(), dead_hub()];
+        let result = connect_any(&hubs, |addr| future::ready(TcpStream::connect(addr))).await;
+        assert!(result.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_unresponsive_hub_is_detected() {
+        let check = monitor_health(fast_health_check(), || {
+            future::pending::<Result<(), std::io::Error>>()
+        });
+        tokio::time::timeout(Duration::from_secs(1), check)
+            .await
+            .unwrap();
+    }
+
+    #[actix_rt::test]
+    async fn test_responsive_hub_is_kept() {
+        let check = monitor_health(fast_health_check(), || {
+            future::ready(Ok::<_, std::io::Error>(()))
+        });
+        assert!(tokio::time::timeout(Duration::from_millis(200), check)
+            .await
+            .is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_failing_ping_is_detected() {
+        let check = monitor_health(fast_health_check(), || {
+            future::ready(Err::<(), _>("no route to node"))
+        });
+        tokio::time::timeout(Duration::from_secs(1), check)
+            .await
+            .unwrap();
+    }
+}
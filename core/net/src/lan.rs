@@ -0,0 +1,826 @@
+// Hub-less mode: peers discovered with UDP multicast and connected directly
+//
+// Each node runs embedded service bus router on `YA_NET_LAN_BIND` address
+// and connects to it the same way, as it would connect to a hub. Nodes
+// announce their ids and router port on multicast group, so outgoing
+// `/net/<node_id>` calls and broadcasts are sent directly to the router
+// of the peer owning given id.
+//
+// Announcements are signed with keys of all announced identities, so other
+// nodes can't take over their calls. Router listens only on loopback by
+// default, so announcements don't leave the host, until `YA_NET_LAN_BIND`
+// is set to address reachable from local network.
+//
+// Embedded router accepts any TCP client and trusts caller id, which client
+// claims. So router is bound to non-loopback address only, when envelopes
+// are mandatory for all services (`/` in `YA_NET_SIGNED_PREFIXES` or
+// `YA_NET_ENCRYPTED_PREFIXES`), and caller of a call is verified by its
+// signature. Envelopes don't cover broadcasts, so these are signed by the
+// caller separately and unsigned ones are dropped. Broadcast signature binds
+// caller, topic and payload only, so its copy can be replayed until it gets
+// older than allowed clock skew.
+
+use actix_rt::Arbiter;
+use futures::future::LocalBoxFuture;
+use futures::prelude::*;
+use futures::stream::LocalBoxStream;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{Error as IoError, ErrorKind as IoErrorKind};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use tokio::net::udp::{RecvHalf, SendHalf};
+
+use ya_core_model::NodeId;
+use ya_service_bus::connection::{self, ClientInfo, ConnectionRef, TcpTransport};
+use ya_service_bus::{serialization, Error, ResponseChunk};
+
+use crate::handler::CentralBusHandler;
+use crate::secure::{
+    recover_signer, sign, split_net_addr, SecureConfig, ENCRYPTED_PREFIXES_ENV_VAR,
+    SIGNED_PREFIXES_ENV_VAR,
+};
+
+pub const NET_MODE_ENV_VAR: &str = "YA_NET_MODE";
+pub const LAN_BIND_ENV_VAR: &str = "YA_NET_LAN_BIND";
+pub const LAN_MULTICAST_ENV_VAR: &str = "YA_NET_LAN_MULTICAST";
+
+const DEFAULT_LAN_BIND: &str = "127.0.0.1:7478";
+const DEFAULT_LAN_MULTICAST: &str = "239.255.42.99:7479";
+const PROTOCOL: &str = "ya-net-lan/3";
+/// Peer is forgotten, when it missed that many announcements.
+const MISSED_ANNOUNCEMENTS: u32 = 3;
+const MAX_CLOCK_SKEW_MILLIS: i64 = 60_000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LanConfig {
+    /// Address of embedded router, which accepts connections from peers.
+    pub bind_addr: SocketAddr,
+    pub multicast_addr: SocketAddrV4,
+    pub announce_interval: Duration,
+}
+
+impl LanConfig {
+    /// Returns `None`, unless `YA_NET_MODE=lan`.
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        Self::parse(
+            std::env::var(NET_MODE_ENV_VAR).ok(),
+            std::env::var(LAN_BIND_ENV_VAR).ok(),
+            std::env::var(LAN_MULTICAST_ENV_VAR).ok(),
+        )
+    }
+
+    fn parse(
+        mode: Option<String>,
+        bind_addr: Option<String>,
+        multicast_addr: Option<String>,
+    ) -> anyhow::Result<Option<Self>> {
+        match mode.as_deref().map(str::trim) {
+            None | Some("") | Some("hub") => return Ok(None),
+            Some("lan") => (),
+            Some(other) => anyhow::bail!("invalid {}: {}", NET_MODE_ENV_VAR, other),
+        }
+
+        let bind_addr: SocketAddr = bind_addr
+            .as_deref()
+            .unwrap_or(DEFAULT_LAN_BIND)
+            .parse()
+            .map_err(|e| anyhow::anyhow!("invalid {}: {}", LAN_BIND_ENV_VAR, e))?;
+        let multicast_addr: SocketAddrV4 = multicast_addr
+            .as_deref()
+            .unwrap_or(DEFAULT_LAN_MULTICAST)
+            .parse()
+            .map_err(|e| anyhow::anyhow!("invalid {}: {}", LAN_MULTICAST_ENV_VAR, e))?;
+        if !multicast_addr.ip().is_multicast() {
+            anyhow::bail!("{} is not a multicast address", multicast_addr);
+        }
+
+        Ok(Some(LanConfig {
+            bind_addr,
+            multicast_addr,
+            announce_interval: Duration::from_secs(5),
+        }))
+    }
+
+    /// Router reachable from other hosts can't rely on caller ids.
+    pub fn check_secure(&self, secure: &SecureConfig) -> anyhow::Result<()> {
+        if !self.bind_addr.ip().is_loopback() && !secure.protects_all() {
+            anyhow::bail!(
+                "{} {} is reachable from other hosts, so {} or {} has to include `/`",
+                LAN_BIND_ENV_VAR,
+                self.bind_addr,
+                SIGNED_PREFIXES_ENV_VAR,
+                ENCRYPTED_PREFIXES_ENV_VAR,
+            );
+        }
+        Ok(())
+    }
+
+    /// Address, on which this node connects to its own router.
+    pub fn local_addr(&self) -> SocketAddr {
+        match self.bind_addr.ip() {
+            ip if ip.is_unspecified() => (Ipv4Addr::LOCALHOST, self.bind_addr.port()).into(),
+            _ => self.bind_addr,
+        }
+    }
+
+    pub fn router_url(&self) -> url::Url {
+        url::Url::parse(&format!("tcp://{}", self.bind_addr)).unwrap()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Announcement {
+    protocol: String,
+    node_ids: Vec<NodeId>,
+    /// Router host, unless it listens on all interfaces. Then host is taken
+    /// from announcement source address.
+    host: Option<IpAddr>,
+    port: u16,
+    /// Milliseconds since epoch.
+    timestamp: i64,
+    /// Signatures of `digest` made with keys of `node_ids`, in the same order.
+    signatures: Vec<Vec<u8>>,
+}
+
+impl Announcement {
+    fn digest(&self) -> Vec<u8> {
+        let mut hasher = Sha256::new()
+            .chain(self.protocol.as_bytes())
+            .chain(&self.port.to_le_bytes())
+            .chain(&self.timestamp.to_le_bytes());
+        if let Some(host) = &self.host {
+            hasher.update(host.to_string().as_bytes());
+        }
+        for node_id in &self.node_ids {
+            hasher.update(node_id.to_string().as_bytes());
+        }
+        hasher.finalize().to_vec()
+    }
+
+    fn verify(&self) -> bool {
+        let digest = self.digest();
+        self.node_ids.len() == self.signatures.len()
+            && self
+                .node_ids
+                .iter()
+                .zip(&self.signatures)
+                .all(|(node_id, signature)| recover_signer(&digest, signature) == Some(*node_id))
+    }
+}
+
+/// Broadcast sent to LAN peers, signed with the key of its caller.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct SignedBroadcast {
+    /// Milliseconds since epoch.
+    timestamp: i64,
+    msg: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+fn broadcast_digest(caller: &str, topic: &str, timestamp: i64, msg: &[u8]) -> Vec<u8> {
+    Sha256::new()
+        .chain(PROTOCOL.as_bytes())
+        .chain(caller.as_bytes())
+        .chain(&(topic.len() as u64).to_le_bytes())
+        .chain(topic.as_bytes())
+        .chain(&timestamp.to_le_bytes())
+        .chain(msg)
+        .finalize()
+        .to_vec()
+}
+
+async fn seal_broadcast(caller: &str, topic: &str, msg: Vec<u8>) -> Result<Vec<u8>, Error> {
+    let node_id: NodeId = caller
+        .parse()
+        .map_err(|_| Error::GsbBadRequest(format!("invalid broadcast caller: {}", caller)))?;
+    let timestamp = chrono::Utc::now().timestamp_millis();
+    let digest = broadcast_digest(caller, topic, timestamp, &msg);
+    let signature = sign(node_id, digest).await?;
+    serialization::to_vec(&SignedBroadcast {
+        timestamp,
+        msg,
+        signature,
+    })
+    .map_err(|e| Error::GsbFailure(format!("serializing LAN broadcast: {}", e)))
+}
+
+/// Returns broadcast payload, if it's recent and signed by its caller.
+pub fn open_broadcast(caller: &str, topic: &str, data: &[u8]) -> Option<Vec<u8>> {
+    let broadcast: SignedBroadcast = serialization::from_slice(data).ok()?;
+    let age = chrono::Utc::now().timestamp_millis() - broadcast.timestamp;
+    if age.abs() > MAX_CLOCK_SKEW_MILLIS {
+        return None;
+    }
+    let digest = broadcast_digest(caller, topic, broadcast.timestamp, &broadcast.msg);
+    let signer = recover_signer(&digest, &broadcast.signature)?;
+    match caller.parse::<NodeId>() {
+        Ok(caller) if caller == signer => Some(broadcast.msg),
+        _ => None,
+    }
+}
+
+type PeerStream = stream::Once<future::Ready<Result<ResponseChunk, Error>>>;
+type PeerHandler = CentralBusHandler<
+    fn(String, String, String, Vec<u8>) -> PeerStream,
+    fn(String, String, Vec<u8>),
+>;
+type PeerConnection = ConnectionRef<TcpTransport, PeerHandler>;
+
+/// Outgoing connections are used only to send requests. Peers call us
+/// through our own router.
+fn reject_call(_: String, _: String, addr: String, _: Vec<u8>) -> PeerStream {
+    stream::once(future::err(Error::GsbBadRequest(format!(
+        "unexpected call to {} on outgoing peer connection",
+        addr
+    ))))
+}
+
+fn ignore_event(_: String, _: String, _: Vec<u8>) {}
+
+struct Peer {
+    addr: SocketAddr,
+    last_seen: Instant,
+    /// Timestamp of last accepted announcement. Older ones are replays.
+    timestamp: i64,
+}
+
+pub struct LanPeers {
+    config: LanConfig,
+    client_info: ClientInfo,
+    own_node_ids: RefCell<Vec<NodeId>>,
+    peers: RefCell<HashMap<NodeId, Peer>>,
+    connections: RefCell<HashMap<SocketAddr, PeerConnection>>,
+}
+
+impl LanPeers {
+    pub fn new(config: LanConfig, client_info: ClientInfo) -> Rc<Self> {
+        Rc::new(LanPeers {
+            config,
+            client_info,
+            own_node_ids: Default::default(),
+            peers: Default::default(),
+            connections: Default::default(),
+        })
+    }
+
+    pub fn config(&self) -> &LanConfig {
+        &self.config
+    }
+
+    /// Identities are listed on each rebind, so announced ids are updated too.
+    pub fn set_own_node_ids(&self, node_ids: Vec<NodeId>) {
+        self.own_node_ids.replace(node_ids);
+    }
+
+    fn ttl(&self) -> Duration {
+        self.config.announce_interval * MISSED_ANNOUNCEMENTS
+    }
+
+    fn on_announcement(&self, src: IpAddr, announcement: Announcement) {
+        if announcement.protocol != PROTOCOL {
+            return;
+        }
+        let own_node_ids = self.own_node_ids.borrow();
+        if announcement
+            .node_ids
+            .iter()
+            .any(|node_id| own_node_ids.contains(node_id))
+        {
+            return;
+        }
+        let age = chrono::Utc::now().timestamp_millis() - announcement.timestamp;
+        if age.abs() > MAX_CLOCK_SKEW_MILLIS {
+            log::trace!("Outdated LAN announcement from {}", src);
+            return;
+        }
+        if !announcement.verify() {
+            log::debug!("LAN announcement from {} isn't signed by its nodes", src);
+            return;
+        }
+
+        let addr = SocketAddr::new(announcement.host.unwrap_or(src), announcement.port);
+        let mut peers = self.peers.borrow_mut();
+        if announcement.node_ids.iter().any(|node_id| {
+            peers
+                .get(node_id)
+                .map(|peer| peer.timestamp >= announcement.timestamp)
+                .unwrap_or(false)
+        }) {
+            log::trace!("Replayed LAN announcement from {}", src);
+            return;
+        }
+        for node_id in announcement.node_ids {
+            if peers.get(&node_id).map(|peer| peer.addr) != Some(addr) {
+                log::debug!("Discovered LAN peer {} at {}", node_id, addr);
+            }
+            peers.insert(
+                node_id,
+                Peer {
+                    addr,
+                    last_seen: Instant::now(),
+                    timestamp: announcement.timestamp,
+                },
+            );
+        }
+    }
+
+    fn resolve(&self, node_id: &NodeId) -> Option<SocketAddr> {
+        let ttl = self.ttl();
+        self.peers
+            .borrow()
+            .get(node_id)
+            .filter(|peer| peer.last_seen.elapsed() < ttl)
+            .map(|peer| peer.addr)
+    }
+
+    fn peer_addrs(&self) -> Vec<SocketAddr> {
+        let ttl = self.ttl();
+        let mut addrs: Vec<_> = self
+            .peers
+            .borrow()
+            .values()
+            .filter(|peer| peer.last_seen.elapsed() < ttl)
+            .map(|peer| peer.addr)
+            .collect();
+        addrs.sort();
+        addrs.dedup();
+        addrs
+    }
+
+    async fn connection(self: Rc<Self>, addr: SocketAddr) -> Result<PeerConnection, Error> {
+        if let Some(conn) = self.connections.borrow().get(&addr) {
+            return Ok(conn.clone());
+        }
+
+        let transport = connection::tcp(addr)
+            .await
+            .map_err(|e| Error::GsbFailure(format!("connecting LAN peer {}: {}", addr, e)))?;
+        let (handler, done_rx) = CentralBusHandler::new(
+            reject_call as fn(String, String, String, Vec<u8>) -> PeerStream,
+            ignore_event as fn(String, String, Vec<u8>),
+        );
+        let conn = connection::connect_with_handler(self.client_info.clone(), transport, handler);
+        self.connections.borrow_mut().insert(addr, conn.clone());
+
+        let peers = self.clone();
+        Arbiter::spawn(async move {
+            let _ = done_rx.await;
+            log::debug!("Disconnected from LAN peer {}", addr);
+            peers.connections.borrow_mut().remove(&addr);
+        });
+        Ok(conn)
+    }
+
+    async fn route(self: Rc<Self>, addr: &str) -> Result<PeerConnection, Error> {
+        let (node_id, _) = split_net_addr(addr)?;
+        let peer_addr = self.resolve(&node_id).ok_or_else(|| {
+            Error::RemoteError(addr.to_string(), format!("unknown peer {}", node_id))
+        })?;
+        self.connection(peer_addr).await
+    }
+
+    pub fn call(
+        self: Rc<Self>,
+        caller: String,
+        addr: String,
+        msg: Vec<u8>,
+    ) -> LocalBoxFuture<'static, Result<Vec<u8>, Error>> {
+        async move {
+            let conn = self.route(&addr).await?;
+            conn.call(caller, addr.clone(), msg)
+                .await
+                .map_err(|e| Error::RemoteError(addr, e.to_string()))
+        }
+        .boxed_local()
+    }
+
+    pub fn call_streaming(
+        self: Rc<Self>,
+        caller: String,
+        addr: String,
+        msg: Vec<u8>,
+    ) -> LocalBoxStream<'static, Result<ResponseChunk, Error>> {
+        async move {
+            let conn = self.route(&addr).await?;
+            Ok::<_, Error>(conn.call_streaming(caller, addr, msg))
+        }
+        .try_flatten_stream()
+        .boxed_local()
+    }
+
+    /// Broadcasts are signed once and delivered to every live peer.
+    /// Unreachable peers are skipped, like nodes disconnected from the hub.
+    pub fn broadcast(
+        self: Rc<Self>,
+        caller: String,
+        topic: String,
+        msg: Vec<u8>,
+    ) -> LocalBoxFuture<'static, Result<(), Error>> {
+        async move {
+            let msg = seal_broadcast(&caller, &topic, msg).await?;
+            for addr in self.peer_addrs() {
+                let sent = match self.clone().connection(addr).await {
+                    Ok(conn) => conn
+                        .broadcast(caller.clone(), topic.clone(), msg.clone())
+                        .await
+                        .map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                };
+                if let Err(e) = sent {
+                    log::debug!("Failed to broadcast {} to LAN peer {}: {}", topic, addr, e);
+                }
+            }
+            Ok(())
+        }
+        .boxed_local()
+    }
+}
+
+fn multicast_socket(
+    group: &SocketAddrV4,
+    local_only: bool,
+) -> std::io::Result<tokio::net::UdpSocket> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(Domain::ipv4(), Type::dgram(), Some(Protocol::udp()))?;
+    // Allows running several nodes on one host.
+    socket.set_reuse_address(true)?;
+    #[cfg(unix)]
+    socket.set_reuse_port(true)?;
+    socket.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, group.port())).into())?;
+    socket.join_multicast_v4(group.ip(), &Ipv4Addr::UNSPECIFIED)?;
+    socket.set_multicast_loop_v4(true)?;
+    if local_only {
+        // Announcements of router bound to loopback are useless for other hosts.
+        socket.set_multicast_ttl_v4(0)?;
+    }
+    socket.set_nonblocking(true)?;
+    tokio::net::UdpSocket::from_std(socket.into_udp_socket())
+}
+
+/// Announces own identities and collects announcements of other nodes.
+/// Runs until multicast socket fails.
+pub async fn discover(peers: Rc<LanPeers>) -> std::io::Result<()> {
+    let group = peers.config.multicast_addr;
+    let local_only = peers.config.bind_addr.ip().is_loopback();
+    let (recv, send) = multicast_socket(&group, local_only)?.split();
+    log::info!("LAN peer discovery on multicast group {}", group);
+
+    future::try_join(announce(peers.clone(), send), listen(peers, recv))
+        .await
+        .map(|_| ())
+}
+
+async fn announce(peers: Rc<LanPeers>, mut send: SendHalf) -> std::io::Result<()> {
+    let group = SocketAddr::V4(peers.config.multicast_addr);
+    let bind_ip = peers.config.bind_addr.ip();
+    loop {
+        let mut announcement = Announcement {
+            protocol: PROTOCOL.to_string(),
+            node_ids: peers.own_node_ids.borrow().clone(),
+            host: Some(bind_ip).filter(|ip| !ip.is_unspecified()),
+            port: peers.config.bind_addr.port(),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            signatures: Vec::new(),
+        };
+        if !announcement.node_ids.is_empty() {
+            match sign_announcement(&mut announcement).await {
+                Ok(()) => {
+                    let data = serde_json::to_vec(&announcement)
+                        .map_err(|e| IoError::new(IoErrorKind::InvalidData, e))?;
+                    send.send_to(&data, &group).await?;
+                }
+                Err(e) => log::warn!("Failed to sign LAN announcement: {}", e),
+            }
+        }
+        tokio::time::delay_for(peers.config.announce_interval).await;
+    }
+}
+
+async fn sign_announcement(announcement: &mut Announcement) -> Result<(), Error> {
+    let digest = announcement.digest();
+    for node_id in &announcement.node_ids {
+        let signature = sign(*node_id, digest.clone()).await?;
+        announcement.signatures.push(signature);
+    }
+    Ok(())
+}
+
+async fn listen(peers: Rc<LanPeers>, mut recv: RecvHalf) -> std::io::Result<()> {
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let (len, src) = recv.recv_from(&mut buf).await?;
+        match serde_json::from_slice::<Announcement>(&buf[..len]) {
+            Ok(announcement) => peers.on_announcement(src.ip(), announcement),
+            Err(e) => log::trace!("Invalid LAN announcement from {}: {}", src, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use ya_core_model::identity;
+    use ya_service_bus::typed as bus;
+
+    fn node_id(n: u8) -> NodeId {
+        NodeId::from(&[n; 20][..])
+    }
+
+    fn identity() -> (ethsign::SecretKey, NodeId) {
+        let secret = ethsign::SecretKey::from_raw(&rand::thread_rng().gen::<[u8; 32]>()).unwrap();
+        let node_id = NodeId::from(secret.public().address().as_ref());
+        (secret, node_id)
+    }
+
+    fn sign_with(secret: &ethsign::SecretKey, digest: &[u8]) -> Vec<u8> {
+        let signature = secret.sign(digest).unwrap();
+        [&[signature.v][..], &signature.r[..], &signature.s[..]].concat()
+    }
+
+    /// Replaces identity service, which signs with given keys.
+    fn bind_signer(keys: Vec<ethsign::SecretKey>) {
+        let keys: HashMap<NodeId, ethsign::SecretKey> = keys
+            .into_iter()
+            .map(|key| (NodeId::from(key.public().address().as_ref()), key))
+            .collect();
+        let _ = bus::bind(identity::BUS_ID, move |msg: identity::Sign| {
+            let result = keys
+                .get(&msg.node_id)
+                .map(|key| sign_with(key, &msg.payload))
+                .ok_or_else(|| identity::Error::NodeNotFound(Box::new(msg.node_id)));
+            future::ready(result)
+        });
+    }
+
+    fn peers() -> Rc<LanPeers> {
+        let config = LanConfig::parse(Some("lan".to_string()), None, None)
+            .unwrap()
+            .unwrap();
+        let peers = LanPeers::new(config, ClientInfo::new("test"));
+        peers.set_own_node_ids(vec![node_id(0)]);
+        peers
+    }
+
+    fn announcement(keys: &[&ethsign::SecretKey], port: u16) -> Announcement {
+        let mut announcement = Announcement {
+            protocol: PROTOCOL.to_string(),
+            node_ids: keys
+                .iter()
+                .map(|key| NodeId::from(key.public().address().as_ref()))
+                .collect(),
+            host: None,
+            port,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            signatures: Vec::new(),
+        };
+        let digest = announcement.digest();
+        announcement.signatures = keys.iter().map(|key| sign_with(key, &digest)).collect();
+        announcement
+    }
+
+    fn free_port() -> u16 {
+        std::net::UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    #[test]
+    fn test_parse_config() {
+        assert_eq!(LanConfig::parse(None, None, None).unwrap(), None);
+        assert_eq!(
+            LanConfig::parse(Some("hub".to_string()), None, None).unwrap(),
+            None
+        );
+        assert!(LanConfig::parse(Some("p2p".to_string()), None, None).is_err());
+        assert!(LanConfig::parse(
+            Some("lan".to_string()),
+            None,
+            Some("192.168.0.1:7479".to_string())
+        )
+        .is_err());
+
+        let config = LanConfig::parse(Some("lan".to_string()), None, None)
+            .unwrap()
+            .unwrap();
+        assert!(config.bind_addr.ip().is_loopback());
+
+        let config = LanConfig::parse(
+            Some("lan".to_string()),
+            Some("0.0.0.0:7500".to_string()),
+            None,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(config.local_addr(), "127.0.0.1:7500".parse().unwrap());
+        assert_eq!(config.router_url().as_str(), "tcp://0.0.0.0:7500");
+    }
+
+    #[test]
+    fn test_exposed_router_requires_envelopes() {
+        let config = |bind_addr: &str| {
+            LanConfig::parse(Some("lan".to_string()), Some(bind_addr.to_string()), None)
+                .unwrap()
+                .unwrap()
+        };
+        let secure = |signed: &str, encrypted: &str| SecureConfig {
+            signed: vec![signed.to_string()],
+            encrypted: vec![encrypted.to_string()],
+        };
+
+        let loopback = config("127.0.0.1:7500");
+        assert!(loopback.check_secure(&SecureConfig::default()).is_ok());
+
+        let exposed = config("0.0.0.0:7500");
+        assert!(exposed.check_secure(&SecureConfig::default()).is_err());
+        assert!(exposed
+            .check_secure(&secure("/market", "/activity"))
+            .is_err());
+        assert!(exposed.check_secure(&secure("/", "/activity")).is_ok());
+        assert!(exposed.check_secure(&secure("/market", "/")).is_ok());
+    }
+
+    #[test]
+    fn test_announced_peers_are_resolved() {
+        let peers = peers();
+        let (key_1, node_1) = identity();
+        let (key_2, node_2) = identity();
+        let src: IpAddr = "192.168.0.2".parse().unwrap();
+        peers.on_announcement(src, announcement(&[&key_1, &key_2], 7478));
+
+        let addr = SocketAddr::new(src, 7478);
+        assert_eq!(peers.resolve(&node_1), Some(addr));
+        assert_eq!(peers.resolve(&node_2), Some(addr));
+        assert_eq!(peers.resolve(&node_id(3)), None);
+        assert_eq!(peers.peer_addrs(), vec![addr]);
+    }
+
+    #[test]
+    fn test_own_and_foreign_announcements_are_ignored() {
+        let peers = peers();
+        let (key, node) = identity();
+        peers.set_own_node_ids(vec![node]);
+        let src: IpAddr = "127.0.0.1".parse().unwrap();
+        peers.on_announcement(src, announcement(&[&key], 7478));
+
+        let (key, _) = identity();
+        let mut foreign = announcement(&[&key], 7478);
+        foreign.protocol = "other/1".to_string();
+        peers.on_announcement(src, foreign);
+
+        assert!(peers.peer_addrs().is_empty());
+    }
+
+    #[test]
+    fn test_unsigned_announcements_are_ignored() {
+        let peers = peers();
+        let (key, node) = identity();
+        let (other_key, _) = identity();
+        let src: IpAddr = "192.168.0.2".parse().unwrap();
+
+        let mut unsigned = announcement(&[&key], 7478);
+        unsigned.signatures.clear();
+        peers.on_announcement(src, unsigned);
+
+        // Other node can't claim our identity.
+        let mut forged = announcement(&[&other_key], 7478);
+        forged.node_ids = vec![node];
+        peers.on_announcement(src, forged);
+
+        let mut tampered = announcement(&[&key], 7478);
+        tampered.port = 7480;
+        peers.on_announcement(src, tampered);
+
+        let mut outdated = announcement(&[&key], 7478);
+        outdated.timestamp -= 2 * MAX_CLOCK_SKEW_MILLIS;
+        let digest = outdated.digest();
+        outdated.signatures = vec![sign_with(&key, &digest)];
+        peers.on_announcement(src, outdated);
+
+        assert_eq!(peers.resolve(&node), None);
+    }
+
+    #[test]
+    fn test_replayed_announcement_is_ignored() {
+        let peers = peers();
+        let (key, node) = identity();
+        let src: IpAddr = "192.168.0.2".parse().unwrap();
+        let announcement = announcement(&[&key], 7478);
+        peers.on_announcement(src, announcement.clone());
+
+        // Copy sent by other host doesn't redirect calls to it.
+        peers.on_announcement("192.168.0.3".parse().unwrap(), announcement);
+        assert_eq!(peers.resolve(&node), Some(SocketAddr::new(src, 7478)));
+    }
+
+    #[test]
+    fn test_silent_peers_expire() {
+        let peers = peers();
+        let (key, node) = identity();
+        let src: IpAddr = "192.168.0.2".parse().unwrap();
+        peers.on_announcement(src, announcement(&[&key], 7478));
+        peers.peers.borrow_mut().get_mut(&node).unwrap().last_seen -= peers.ttl();
+
+        assert_eq!(peers.resolve(&node), None);
+        assert!(peers.peer_addrs().is_empty());
+    }
+
+    fn signed_broadcast(key: &ethsign::SecretKey, caller: &str, topic: &str) -> SignedBroadcast {
+        let timestamp = chrono::Utc::now().timestamp_millis();
+        let msg = b"unsubscribe".to_vec();
+        let digest = broadcast_digest(caller, topic, timestamp, &msg);
+        SignedBroadcast {
+            timestamp,
+            signature: sign_with(key, &digest),
+            msg,
+        }
+    }
+
+    #[test]
+    fn test_broadcast_signed_by_caller() {
+        let (key, node) = identity();
+        let (_, other) = identity();
+        let (caller, topic) = (node.to_string(), "market-offers");
+        let sealed = |broadcast: &SignedBroadcast| serialization::to_vec(broadcast).unwrap();
+
+        let broadcast = signed_broadcast(&key, &caller, topic);
+        assert_eq!(
+            open_broadcast(&caller, topic, &sealed(&broadcast)),
+            Some(b"unsubscribe".to_vec())
+        );
+
+        // Broadcasts can't be attributed to other node nor moved to other topic.
+        assert_eq!(
+            open_broadcast(&other.to_string(), topic, &sealed(&broadcast)),
+            None
+        );
+        assert_eq!(open_broadcast(&caller, "other", &sealed(&broadcast)), None);
+
+        let mut tampered = broadcast.clone();
+        tampered.msg = b"subscribe".to_vec();
+        assert_eq!(open_broadcast(&caller, topic, &sealed(&tampered)), None);
+
+        let mut unsigned = broadcast.clone();
+        unsigned.signature.clear();
+        assert_eq!(open_broadcast(&caller, topic, &sealed(&unsigned)), None);
+
+        let mut outdated = broadcast;
+        outdated.timestamp -= 2 * MAX_CLOCK_SKEW_MILLIS;
+        let digest = broadcast_digest(&caller, topic, outdated.timestamp, &outdated.msg);
+        outdated.signature = sign_with(&key, &digest);
+        assert_eq!(open_broadcast(&caller, topic, &sealed(&outdated)), None);
+
+        // Plain payload, sent without signature.
+        assert_eq!(open_broadcast(&caller, topic, b"unsubscribe"), None);
+    }
+
+    #[actix_rt::test]
+    async fn test_two_nodes_discover_each_other() {
+        let (alice_key, alice) = identity();
+        let (bob_key, bob) = identity();
+        bind_signer(vec![alice_key, bob_key]);
+
+        let group = format!("239.255.42.99:{}", free_port());
+        let start = |node_id: NodeId| {
+            let bind_addr = format!("127.0.0.1:{}", free_port());
+            let mut config = LanConfig::parse(
+                Some("lan".to_string()),
+                Some(bind_addr),
+                Some(group.clone()),
+            )
+            .unwrap()
+            .unwrap();
+            config.announce_interval = Duration::from_millis(100);
+            let peers = LanPeers::new(config, ClientInfo::new("test"));
+            peers.set_own_node_ids(vec![node_id]);
+            let discovery = peers.clone();
+            Arbiter::spawn(async move {
+                if let Err(e) = discover(discovery).await {
+                    log::error!("LAN discovery failed: {}", e);
+                }
+            });
+            peers
+        };
+        let alice_peers = start(alice);
+        let bob_peers = start(bob);
+
+        let discovered = async {
+            while alice_peers.resolve(&bob) != Some(bob_peers.config.bind_addr)
+                || bob_peers.resolve(&alice) != Some(alice_peers.config.bind_addr)
+            {
+                tokio::time::delay_for(Duration::from_millis(50)).await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(5), discovered)
+            .await
+            .expect("nodes didn't discover each other");
+    }
+}
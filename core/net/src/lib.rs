@@ -1,8 +1,20 @@
 #[cfg(any(feature = "service", test))]
 mod bcast;
 #[cfg(any(feature = "service", test))]
+mod cli;
+#[cfg(any(feature = "service", test))]
+mod diagnostics;
+#[cfg(any(feature = "service", test))]
 mod handler;
 #[cfg(any(feature = "service", test))]
+mod hub;
+#[cfg(any(feature = "service", test))]
+mod lan;
+#[cfg(any(feature = "service", test))]
+mod rest;
+#[cfg(any(feature = "service", test))]
+mod secure;
+#[cfg(any(feature = "service", test))]
 mod service;
 
 #[cfg(feature = "service")]
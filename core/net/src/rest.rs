@@ -0,0 +1,66 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde::Deserialize;
+use std::time::Duration;
+
+use ya_client_model::ErrorMessage;
+use ya_core_model::net::local as local_net;
+use ya_core_model::net::DiagnosticsError;
+use ya_core_model::NodeId;
+use ya_service_bus::{typed as bus, RpcEndpoint};
+
+use crate::diagnostics::MAX_PING_TIMEOUT;
+
+pub const NET_API_PATH: &str = "/net-api/v1";
+
+pub fn web_scope() -> actix_web::Scope {
+    actix_web::web::scope(NET_API_PATH)
+        .service(get_status)
+        .service(ping)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QueryPing {
+    /// Seconds to wait for the response. Capped at `MAX_PING_TIMEOUT`.
+    timeout: Option<f32>,
+}
+
+#[actix_web::get("/status")]
+async fn get_status() -> impl Responder {
+    match bus::service(local_net::BUS_ID)
+        .send(local_net::Status {})
+        .await
+    {
+        Ok(Ok(status)) => HttpResponse::Ok().json(status),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(ErrorMessage::new(e.to_string())),
+        Err(e) => HttpResponse::InternalServerError().json(ErrorMessage::new(e.to_string())),
+    }
+}
+
+#[actix_web::get("/ping/{node_id}")]
+async fn ping(node_id: web::Path<NodeId>, query: web::Query<QueryPing>) -> impl Responder {
+    let timeout = match query.timeout {
+        Some(secs) if !secs.is_finite() || secs < 0.0 => {
+            return HttpResponse::BadRequest()
+                .json(ErrorMessage::new(format!("Invalid timeout: {}", secs)))
+        }
+        timeout => {
+            timeout.map(|secs| Duration::from_secs_f32(secs.min(MAX_PING_TIMEOUT.as_secs_f32())))
+        }
+    };
+    let msg = local_net::Ping {
+        node_id: node_id.into_inner(),
+        timeout,
+    };
+    match bus::service(local_net::BUS_ID).send(msg).await {
+        Ok(Ok(result)) => HttpResponse::Ok().json(result),
+        Ok(Err(e @ DiagnosticsError::Timeout(..))) => {
+            HttpResponse::GatewayTimeout().json(ErrorMessage::new(e.to_string()))
+        }
+        Ok(Err(e @ DiagnosticsError::Unreachable(..))) => {
+            HttpResponse::BadGateway().json(ErrorMessage::new(e.to_string()))
+        }
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(ErrorMessage::new(e.to_string())),
+        Err(e) => HttpResponse::InternalServerError().json(ErrorMessage::new(e.to_string())),
+    }
+}
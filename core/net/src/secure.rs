@@ -0,0 +1,1072 @@
+// End-to-end protection of messages routed through the hub
+//
+// Hub asserts caller id of each message, but otherwise forwards opaque
+// bytes, so it could forge, replay or read them. For service prefixes listed
+// in `YA_NET_SIGNED_PREFIXES` and `YA_NET_ENCRYPTED_PREFIXES` requests are
+// wrapped in `Envelope` signed with sender identity key. Replies to protected
+// requests are sealed the same way and bound to the request id.
+//
+// Before first protected message peers run `public::Handshake`. It proves,
+// that the peer understands envelopes, and establishes session key from
+// ephemeral x25519 keys, which are kept only in memory and rotated after
+// `SESSION_TTL`. Handshake isn't needed to sign messages, so peers, which
+// don't answer it (or hub dropped it), still get signed envelopes. Protected
+// messages are never sent without envelope, so hub can't downgrade them.
+//
+// Each envelope has random id. Ids seen within `MAX_CLOCK_SKEW_SECS` are
+// remembered, so replayed envelopes are rejected.
+//
+// Envelopes are recognized by magic prefix, so nodes accept them on any
+// prefix, but unprotected messages are rejected only on listed prefixes.
+
+use chacha20poly1305::aead::{Aead, NewAead};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use x25519_dalek::{EphemeralSecret, PublicKey};
+
+use ya_core_model::identity;
+use ya_core_model::net::public::{self as public_net, Handshake, HandshakeResponse};
+use ya_core_model::net::{self as net, RemoteEndpoint};
+use ya_core_model::NodeId;
+use ya_service_bus::{serialization, typed as bus, ResponseChunk, RpcEndpoint};
+
+pub const SIGNED_PREFIXES_ENV_VAR: &str = "YA_NET_SIGNED_PREFIXES";
+pub const ENCRYPTED_PREFIXES_ENV_VAR: &str = "YA_NET_ENCRYPTED_PREFIXES";
+
+const MAGIC: &[u8] = b"YA-NET-E2E/2";
+const SESSION_KEY_DOMAIN: &[u8] = b"ya-net session key";
+const HANDSHAKE_DOMAIN: &[u8] = b"ya-net handshake";
+const MAX_CLOCK_SKEW_SECS: i64 = 300;
+/// New session is negotiated after this time. Peer keeps old session
+/// twice as long, so messages in flight can still be opened.
+const SESSION_TTL: Duration = Duration::from_secs(3600);
+/// Peer, that didn't answer handshake, is asked again after this time.
+const LEGACY_PEER_TTL: Duration = Duration::from_secs(300);
+/// Limits memory used by sessions, which any node can open with handshake.
+const MAX_SESSIONS: usize = 4096;
+/// Sessions kept for one pair of identities. Peer keeps rotated sessions for
+/// a while, so there are at most two in use.
+const MAX_SESSIONS_PER_PEER: usize = 4;
+
+type EnvelopeId = [u8; 16];
+type SessionId = [u8; 16];
+
+#[derive(thiserror::Error, Debug)]
+pub enum SecureError {
+    #[error("invalid net address: {0}")]
+    InvalidAddress(String),
+    #[error("identity {0} can't sign: {1}")]
+    Identity(NodeId, String),
+    #[error("handshake with {0} failed: {1}")]
+    Handshake(NodeId, String),
+    #[error("{0} doesn't support encrypted messages")]
+    Unsupported(NodeId),
+    #[error("invalid envelope: {0}")]
+    InvalidEnvelope(String),
+    #[error("envelope signature doesn't match sender {0}")]
+    BadSignature(NodeId),
+    #[error("envelope from {0} is outdated")]
+    Outdated(NodeId),
+    #[error("envelope from {0} was already received")]
+    Replayed(NodeId),
+    #[error("unknown net session with {0}")]
+    UnknownSession(NodeId),
+    #[error("can't decrypt message from {0}")]
+    Decryption(NodeId),
+    #[error("unprotected message to secure service {0}")]
+    Unprotected(String),
+}
+
+impl From<SecureError> for ya_service_bus::Error {
+    fn from(e: SecureError) -> Self {
+        ya_service_bus::Error::GsbBadRequest(e.to_string())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Protection {
+    None,
+    Signed,
+    Encrypted,
+}
+
+/// Opt-in list of service prefixes (e.g. `/market`), which require
+/// end-to-end protection.
+#[derive(Clone, Debug, Default)]
+pub struct SecureConfig {
+    pub(crate) signed: Vec<String>,
+    pub(crate) encrypted: Vec<String>,
+}
+
+impl SecureConfig {
+    pub fn from_env() -> Self {
+        let prefixes = |var: &str| {
+            std::env::var(var)
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|prefix| !prefix.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        SecureConfig {
+            signed: prefixes(SIGNED_PREFIXES_ENV_VAR),
+            encrypted: prefixes(ENCRYPTED_PREFIXES_ENV_VAR),
+        }
+    }
+
+    /// Whether every service (except handshake) requires envelope, so caller
+    /// ids asserted by the router aren't trusted.
+    pub fn protects_all(&self) -> bool {
+        self.signed
+            .iter()
+            .chain(self.encrypted.iter())
+            .any(|prefix| prefix.trim_end_matches('/').is_empty())
+    }
+
+    /// `service` is address without `/net/<node_id>` part.
+    pub fn protection(&self, service: &str) -> Protection {
+        let matches = |prefix: &String| {
+            service == prefix || service.starts_with(&format!("{}/", prefix.trim_end_matches('/')))
+        };
+        // Handshake itself can't be protected.
+        if matches(&public_service_path()) {
+            Protection::None
+        } else if self.encrypted.iter().any(matches) {
+            Protection::Encrypted
+        } else if self.signed.iter().any(matches) {
+            Protection::Signed
+        } else {
+            Protection::None
+        }
+    }
+}
+
+fn public_service_path() -> String {
+    public_net::BUS_ID.replacen(net::PUBLIC_PREFIX, "", 1)
+}
+
+/// Splits `/net/<node_id>/<service>` address.
+pub(crate) fn split_net_addr(addr: &str) -> Result<(NodeId, &str), SecureError> {
+    let invalid = || SecureError::InvalidAddress(addr.to_string());
+    let rest = addr
+        .strip_prefix(net::BUS_ID)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .ok_or_else(invalid)?;
+    let (node_id, service) = match rest.find('/') {
+        Some(idx) => rest.split_at(idx),
+        None => (rest, ""),
+    };
+    Ok((node_id.parse().map_err(|_| invalid())?, service))
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Envelope {
+    sender: NodeId,
+    recipient: NodeId,
+    address: String,
+    timestamp: i64,
+    id: EnvelopeId,
+    /// Id of the request, which this envelope answers.
+    in_reply_to: Option<EnvelopeId>,
+    /// Present only in encrypted envelopes.
+    session_id: Option<SessionId>,
+    nonce: Option<[u8; 12]>,
+    payload: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+impl Envelope {
+    fn new(sender: NodeId, recipient: NodeId, address: String, payload: Vec<u8>) -> Self {
+        Envelope {
+            sender,
+            recipient,
+            address,
+            timestamp: chrono::Utc::now().timestamp(),
+            id: rand::thread_rng().gen(),
+            in_reply_to: None,
+            session_id: None,
+            nonce: None,
+            payload,
+            signature: Vec::new(),
+        }
+    }
+
+    fn digest(&self) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        let mut field = |data: &[u8]| {
+            hasher.update(&(data.len() as u64).to_le_bytes());
+            hasher.update(data);
+        };
+        field(MAGIC);
+        field(self.sender.to_string().as_bytes());
+        field(self.recipient.to_string().as_bytes());
+        field(self.address.as_bytes());
+        field(&self.timestamp.to_le_bytes());
+        field(&self.id);
+        field(
+            self.in_reply_to
+                .as_ref()
+                .map(|id| &id[..])
+                .unwrap_or_default(),
+        );
+        field(
+            self.session_id
+                .as_ref()
+                .map(|id| &id[..])
+                .unwrap_or_default(),
+        );
+        field(self.nonce.as_ref().map(|n| &n[..]).unwrap_or_default());
+        field(&self.payload);
+        hasher.finalize().to_vec()
+    }
+
+    fn verify(&self) -> Result<(), SecureError> {
+        match recover_signer(&self.digest(), &self.signature) {
+            Some(signer) if signer == self.sender => Ok(()),
+            _ => Err(SecureError::BadSignature(self.sender)),
+        }
+    }
+
+    fn encrypt(&mut self, session_id: SessionId, session: &Session) -> Result<(), SecureError> {
+        let nonce: [u8; 12] = rand::thread_rng().gen();
+        self.payload = session
+            .cipher()
+            .encrypt(Nonce::from_slice(&nonce), self.payload.as_ref())
+            .map_err(|_| SecureError::InvalidEnvelope("encryption failed".to_string()))?;
+        self.session_id = Some(session_id);
+        self.nonce = Some(nonce);
+        Ok(())
+    }
+
+    fn decrypt(&self, session: &Session) -> Result<Vec<u8>, SecureError> {
+        let nonce = self
+            .nonce
+            .ok_or_else(|| SecureError::InvalidEnvelope("missing nonce".to_string()))?;
+        session
+            .cipher()
+            .decrypt(Nonce::from_slice(&nonce), self.payload.as_ref())
+            .map_err(|_| SecureError::Decryption(self.sender))
+    }
+
+    fn encode(&self) -> Result<Vec<u8>, SecureError> {
+        let body =
+            serialization::to_vec(self).map_err(|e| SecureError::InvalidEnvelope(e.to_string()))?;
+        Ok([MAGIC, &body].concat())
+    }
+
+    /// Returns `None` for messages, which aren't envelopes.
+    fn decode(data: &[u8]) -> Result<Option<Envelope>, SecureError> {
+        match data.strip_prefix(MAGIC) {
+            Some(body) => serialization::from_slice(body)
+                .map(Some)
+                .map_err(|e| SecureError::InvalidEnvelope(e.to_string())),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Signature format produced by identity service: `v || r || s`.
+pub(crate) fn recover_signer(digest: &[u8], signature: &[u8]) -> Option<NodeId> {
+    if signature.len() != 65 {
+        return None;
+    }
+    let mut r = [0u8; 32];
+    let mut s = [0u8; 32];
+    r.copy_from_slice(&signature[1..33]);
+    s.copy_from_slice(&signature[33..65]);
+    let signature = ethsign::Signature {
+        v: signature[0],
+        r,
+        s,
+    };
+    let public = signature.recover(digest).ok()?;
+    Some(NodeId::from(public.address().as_ref()))
+}
+
+/// Binds session keys to both identities, so handshake messages can't be
+/// reused with other peer.
+fn handshake_digest(signer: &NodeId, peer: &NodeId, keys: &[&[u8]]) -> Vec<u8> {
+    let mut hasher = Sha256::new()
+        .chain(HANDSHAKE_DOMAIN)
+        .chain(signer.to_string().as_bytes())
+        .chain(peer.to_string().as_bytes());
+    for key in keys {
+        hasher.update(key);
+    }
+    hasher.finalize().to_vec()
+}
+
+fn to_public_key(key: &[u8], peer: NodeId) -> Result<PublicKey, SecureError> {
+    if key.len() != 32 {
+        return Err(SecureError::Handshake(
+            peer,
+            "invalid session key".to_string(),
+        ));
+    }
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(key);
+    Ok(PublicKey::from(bytes))
+}
+
+pub(crate) async fn sign(node_id: NodeId, digest: Vec<u8>) -> Result<Vec<u8>, SecureError> {
+    bus::service(identity::BUS_ID)
+        .send(identity::Sign {
+            node_id,
+            payload: digest,
+        })
+        .await
+        .map_err(|e| SecureError::Identity(node_id, e.to_string()))?
+        .map_err(|e| SecureError::Identity(node_id, e.to_string()))
+}
+
+/// Session between our identity and remote node, shared by both directions.
+#[derive(Clone)]
+struct Session {
+    local: NodeId,
+    peer: NodeId,
+    key: [u8; 32],
+    established: Instant,
+}
+
+impl Session {
+    /// Both sides derive the same key and session id from ephemeral keys.
+    fn derive(
+        local: NodeId,
+        peer: NodeId,
+        secret: EphemeralSecret,
+        initiator: (&NodeId, &PublicKey),
+        responder: (&NodeId, &PublicKey),
+    ) -> (SessionId, Session) {
+        let peer_key = match initiator.0 == &local {
+            true => responder.1,
+            false => initiator.1,
+        };
+        let shared = secret.diffie_hellman(peer_key);
+        let key = Sha256::new()
+            .chain(SESSION_KEY_DOMAIN)
+            .chain(shared.as_bytes())
+            .chain(initiator.0.to_string().as_bytes())
+            .chain(responder.0.to_string().as_bytes())
+            .chain(initiator.1.as_bytes())
+            .chain(responder.1.as_bytes())
+            .finalize();
+        let id = Sha256::new()
+            .chain(initiator.1.as_bytes())
+            .chain(responder.1.as_bytes())
+            .finalize();
+
+        let mut session_id = [0u8; 16];
+        session_id.copy_from_slice(&id[..16]);
+        let mut session_key = [0u8; 32];
+        session_key.copy_from_slice(&key);
+        let session = Session {
+            local,
+            peer,
+            key: session_key,
+            established: Instant::now(),
+        };
+        (session_id, session)
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(Key::from_slice(&self.key))
+    }
+
+    fn is_fresh(&self) -> bool {
+        self.established.elapsed() < SESSION_TTL
+    }
+}
+
+fn remove_oldest(sessions: &mut HashMap<SessionId, Session>, filter: impl Fn(&Session) -> bool) {
+    let oldest = sessions
+        .iter()
+        .filter(|(_, session)| filter(session))
+        .min_by_key(|(_, session)| session.established)
+        .map(|(id, _)| *id);
+    if let Some(id) = oldest {
+        sessions.remove(&id);
+    }
+}
+
+/// Remembers ids of recently opened envelopes.
+#[derive(Default)]
+struct ReplayCache {
+    seen: HashMap<EnvelopeId, i64>,
+}
+
+impl ReplayCache {
+    /// Returns false, if envelope was already seen.
+    fn insert(&mut self, envelope: &Envelope) -> bool {
+        let oldest = chrono::Utc::now().timestamp() - MAX_CLOCK_SKEW_SECS;
+        // Older envelopes are rejected by timestamp check anyway.
+        self.seen.retain(|_, timestamp| *timestamp >= oldest);
+        self.seen.insert(envelope.id, envelope.timestamp).is_none()
+    }
+}
+
+/// Sealed request, which reply has to match.
+#[derive(Clone, Debug)]
+pub struct PendingReply {
+    local: NodeId,
+    peer: NodeId,
+    address: String,
+    request_id: EnvelopeId,
+    session_id: Option<SessionId>,
+}
+
+/// Opened request, which reply should be sealed the same way.
+#[derive(Clone, Debug)]
+pub struct ReplyContext {
+    local: NodeId,
+    peer: NodeId,
+    address: String,
+    request_id: EnvelopeId,
+    session_id: Option<SessionId>,
+}
+
+#[derive(Default)]
+pub struct SecureChannel {
+    config: SecureConfig,
+    sessions: RefCell<HashMap<SessionId, Session>>,
+    /// Sessions initiated by our identities, by `(local, peer)`.
+    outgoing: RefCell<HashMap<(NodeId, NodeId), SessionId>>,
+    /// Peers, that didn't answer handshake, with time of the attempt.
+    legacy_peers: RefCell<HashMap<NodeId, Instant>>,
+    replay_cache: RefCell<ReplayCache>,
+}
+
+impl SecureChannel {
+    pub fn new(config: SecureConfig) -> Rc<Self> {
+        Rc::new(SecureChannel {
+            config,
+            ..Default::default()
+        })
+    }
+
+    fn insert_session(&self, id: SessionId, session: Session) {
+        let mut sessions = self.sessions.borrow_mut();
+        sessions.retain(|_, session| session.established.elapsed() < 2 * SESSION_TTL);
+
+        let (local, peer) = (session.local, session.peer);
+        let same_peer = |s: &Session| s.local == local && s.peer == peer;
+        if sessions.values().filter(|s| same_peer(s)).count() >= MAX_SESSIONS_PER_PEER {
+            remove_oldest(&mut sessions, same_peer);
+        }
+        if sessions.len() >= MAX_SESSIONS {
+            remove_oldest(&mut sessions, |_| true);
+        }
+        sessions.insert(id, session);
+    }
+
+    /// Answers `public::Handshake` sent by `caller` to our `msg.node_id` identity.
+    pub async fn handshake_response(
+        &self,
+        caller: NodeId,
+        msg: Handshake,
+    ) -> Result<HandshakeResponse, SecureError> {
+        let local = msg.node_id;
+        let digest = handshake_digest(&caller, &local, &[&msg.public_key[..]]);
+        if recover_signer(&digest, &msg.signature) != Some(caller) {
+            return Err(SecureError::Handshake(
+                caller,
+                "session key not signed by caller".to_string(),
+            ));
+        }
+        let caller_key = to_public_key(&msg.public_key, caller)?;
+
+        let secret = EphemeralSecret::new(rand::rngs::OsRng);
+        let public_key = PublicKey::from(&secret);
+        let signature = sign(
+            local,
+            handshake_digest(
+                &local,
+                &caller,
+                &[&public_key.as_bytes()[..], &caller_key.as_bytes()[..]],
+            ),
+        )
+        .await?;
+
+        let (session_id, session) = Session::derive(
+            local,
+            caller,
+            secret,
+            (&caller, &caller_key),
+            (&local, &public_key),
+        );
+        self.insert_session(session_id, session);
+
+        Ok(HandshakeResponse {
+            public_key: public_key.as_bytes().to_vec(),
+            signature,
+        })
+    }
+
+    /// Returns session with `peer` or `None`, if peer doesn't support envelopes.
+    async fn session(
+        &self,
+        local: NodeId,
+        peer: NodeId,
+    ) -> Result<Option<(SessionId, Session)>, SecureError> {
+        let current = self.outgoing.borrow().get(&(local, peer)).cloned();
+        if let Some(id) = current {
+            if let Some(session) = self.sessions.borrow().get(&id) {
+                if session.is_fresh() {
+                    return Ok(Some((id, session.clone())));
+                }
+            }
+        }
+        if let Some(attempt) = self.legacy_peers.borrow().get(&peer) {
+            if attempt.elapsed() < LEGACY_PEER_TTL {
+                return Ok(None);
+            }
+        }
+
+        let secret = EphemeralSecret::new(rand::rngs::OsRng);
+        let public_key = PublicKey::from(&secret);
+        let signature = sign(
+            local,
+            handshake_digest(&local, &peer, &[&public_key.as_bytes()[..]]),
+        )
+        .await?;
+
+        let response = net::from(local)
+            .to(peer)
+            .service(public_net::BUS_ID)
+            .send(Handshake {
+                node_id: peer,
+                public_key: public_key.as_bytes().to_vec(),
+                signature,
+            })
+            .await;
+        let response = match response {
+            Ok(Ok(response)) => response,
+            Ok(Err(e)) => return Err(SecureError::Handshake(peer, e.to_string())),
+            Err(e) => {
+                log::debug!(
+                    "Handshake with {} failed, treating it as node without envelopes support: {}",
+                    peer,
+                    e
+                );
+                self.legacy_peers.borrow_mut().insert(peer, Instant::now());
+                return Ok(None);
+            }
+        };
+
+        let peer_key = to_public_key(&response.public_key, peer)?;
+        let digest = handshake_digest(
+            &peer,
+            &local,
+            &[&peer_key.as_bytes()[..], &public_key.as_bytes()[..]],
+        );
+        if recover_signer(&digest, &response.signature) != Some(peer) {
+            return Err(SecureError::Handshake(
+                peer,
+                "session key not signed by peer".to_string(),
+            ));
+        }
+
+        let (session_id, session) = Session::derive(
+            local,
+            peer,
+            secret,
+            (&local, &public_key),
+            (&peer, &peer_key),
+        );
+        self.legacy_peers.borrow_mut().remove(&peer);
+        self.insert_session(session_id, session.clone());
+        self.outgoing.borrow_mut().insert((local, peer), session_id);
+        Ok(Some((session_id, session)))
+    }
+
+    /// Forgets session, which peer reported as unknown (e.g. after restart),
+    /// so next message negotiates new one.
+    pub fn on_remote_error(&self, pending: &PendingReply, error: &str) {
+        let unknown = SecureError::UnknownSession(pending.peer).to_string();
+        if pending.session_id.is_some() && error.contains(&unknown) {
+            self.outgoing
+                .borrow_mut()
+                .remove(&(pending.local, pending.peer));
+        }
+    }
+
+    /// Wraps outgoing request to `addr` (`/net/<node_id>/...`) in envelope,
+    /// if service requires it. Returns request to match the reply against.
+    pub async fn seal(
+        self: Rc<Self>,
+        sender: NodeId,
+        addr: String,
+        data: Vec<u8>,
+    ) -> Result<(Vec<u8>, Option<PendingReply>), SecureError> {
+        let (recipient, service) = split_net_addr(&addr)?;
+        let protection = self.config.protection(service);
+        if protection == Protection::None {
+            return Ok((data, None));
+        }
+
+        let session = match (self.session(sender, recipient).await?, protection) {
+            (Some(session), _) => Some(session),
+            // Signing doesn't need session key.
+            (None, Protection::Signed) => None,
+            (None, _) => return Err(SecureError::Unsupported(recipient)),
+        };
+
+        let mut envelope = Envelope::new(sender, recipient, addr.clone(), data);
+        let mut session_id = None;
+        if let (Protection::Encrypted, Some((id, session))) = (protection, &session) {
+            envelope.encrypt(*id, session)?;
+            session_id = Some(*id);
+        }
+        envelope.signature = sign(sender, envelope.digest()).await?;
+
+        let pending = PendingReply {
+            local: sender,
+            peer: recipient,
+            address: addr,
+            request_id: envelope.id,
+            session_id,
+        };
+        Ok((envelope.encode()?, Some(pending)))
+    }
+
+    /// Verifies incoming request, before it is forwarded to `/public`.
+    /// `caller` is node id asserted by the hub.
+    pub async fn open(
+        self: Rc<Self>,
+        caller: String,
+        addr: String,
+        data: Vec<u8>,
+    ) -> Result<(Vec<u8>, Option<ReplyContext>), SecureError> {
+        let (recipient, service) = split_net_addr(&addr)?;
+        let envelope = match Envelope::decode(&data)? {
+            Some(envelope) => envelope,
+            None => {
+                return match self.config.protection(service) {
+                    Protection::None => Ok((data, None)),
+                    _ => Err(SecureError::Unprotected(service.to_string())),
+                }
+            }
+        };
+
+        if envelope.recipient != recipient || envelope.address != addr {
+            return Err(SecureError::InvalidEnvelope(format!(
+                "addressed to {}, but received at {}",
+                envelope.address, addr
+            )));
+        }
+        if envelope.in_reply_to.is_some() {
+            return Err(SecureError::InvalidEnvelope(
+                "reply sent as request".to_string(),
+            ));
+        }
+        if caller.parse::<NodeId>().ok() != Some(envelope.sender) {
+            return Err(SecureError::BadSignature(envelope.sender));
+        }
+        let payload = self.verify_and_decrypt(&envelope, recipient)?;
+        let payload = match (payload, self.config.protection(service)) {
+            (None, Protection::Encrypted) => {
+                return Err(SecureError::Unprotected(service.to_string()))
+            }
+            (None, _) => envelope.payload,
+            (Some(payload), _) => payload,
+        };
+
+        let reply = ReplyContext {
+            local: recipient,
+            peer: envelope.sender,
+            address: addr,
+            request_id: envelope.id,
+            session_id: envelope.session_id,
+        };
+        Ok((payload, Some(reply)))
+    }
+
+    /// Seals reply to request opened with `open`. Replies to unprotected
+    /// requests are forwarded unchanged.
+    pub async fn seal_reply(
+        self: Rc<Self>,
+        reply: Option<ReplyContext>,
+        data: Vec<u8>,
+    ) -> Result<Vec<u8>, SecureError> {
+        let reply = match reply {
+            Some(reply) => reply,
+            None => return Ok(data),
+        };
+
+        let mut envelope = Envelope::new(reply.local, reply.peer, reply.address, data);
+        envelope.in_reply_to = Some(reply.request_id);
+        if let Some(session_id) = reply.session_id {
+            let session = self
+                .sessions
+                .borrow()
+                .get(&session_id)
+                .cloned()
+                .ok_or(SecureError::UnknownSession(reply.peer))?;
+            envelope.encrypt(session_id, &session)?;
+        }
+        envelope.signature = sign(reply.local, envelope.digest()).await?;
+        envelope.encode()
+    }
+
+    /// Same as `seal_reply` for chunks of streaming responses.
+    pub async fn seal_reply_chunk(
+        self: Rc<Self>,
+        reply: Option<ReplyContext>,
+        chunk: ResponseChunk,
+    ) -> Result<ResponseChunk, SecureError> {
+        Ok(match chunk {
+            ResponseChunk::Part(data) => ResponseChunk::Part(self.seal_reply(reply, data).await?),
+            ResponseChunk::Full(data) => ResponseChunk::Full(self.seal_reply(reply, data).await?),
+        })
+    }
+
+    /// Verifies reply to request sealed with `seal`.
+    pub fn open_reply(
+        &self,
+        pending: &Option<PendingReply>,
+        data: Vec<u8>,
+    ) -> Result<Vec<u8>, SecureError> {
+        let pending = match pending {
+            Some(pending) => pending,
+            None => return Ok(data),
+        };
+        let envelope = Envelope::decode(&data)?
+            .ok_or_else(|| SecureError::Unprotected(pending.address.clone()))?;
+
+        if envelope.sender != pending.peer
+            || envelope.recipient != pending.local
+            || envelope.address != pending.address
+            || envelope.in_reply_to != Some(pending.request_id)
+            || envelope.session_id != pending.session_id
+        {
+            return Err(SecureError::InvalidEnvelope(format!(
+                "reply doesn't match request to {}",
+                pending.address
+            )));
+        }
+        Ok(self
+            .verify_and_decrypt(&envelope, pending.local)?
+            .unwrap_or(envelope.payload))
+    }
+
+    /// Same as `open_reply` for chunks of streaming responses.
+    pub fn open_reply_chunk(
+        &self,
+        pending: &Option<PendingReply>,
+        chunk: ResponseChunk,
+    ) -> Result<ResponseChunk, SecureError> {
+        Ok(match chunk {
+            ResponseChunk::Part(data) => ResponseChunk::Part(self.open_reply(pending, data)?),
+            ResponseChunk::Full(data) => ResponseChunk::Full(self.open_reply(pending, data)?),
+        })
+    }
+
+    /// Checks signature, freshness and replays. Returns decrypted payload
+    /// of encrypted envelopes.
+    fn verify_and_decrypt(
+        &self,
+        envelope: &Envelope,
+        local: NodeId,
+    ) -> Result<Option<Vec<u8>>, SecureError> {
+        envelope.verify()?;
+        if (chrono::Utc::now().timestamp() - envelope.timestamp).abs() > MAX_CLOCK_SKEW_SECS {
+            return Err(SecureError::Outdated(envelope.sender));
+        }
+
+        let payload = match (envelope.session_id, envelope.nonce) {
+            (Some(session_id), Some(_)) => {
+                let session = self
+                    .sessions
+                    .borrow()
+                    .get(&session_id)
+                    .cloned()
+                    .filter(|session| session.local == local && session.peer == envelope.sender)
+                    .ok_or(SecureError::UnknownSession(local))?;
+                Some(envelope.decrypt(&session)?)
+            }
+            (None, None) => None,
+            _ => {
+                return Err(SecureError::InvalidEnvelope(
+                    "incomplete encryption parameters".to_string(),
+                ))
+            }
+        };
+
+        if !self.replay_cache.borrow_mut().insert(envelope) {
+            return Err(SecureError::Replayed(envelope.sender));
+        }
+        Ok(payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future;
+
+    fn identity() -> (ethsign::SecretKey, NodeId) {
+        let secret = ethsign::SecretKey::from_raw(&rand::thread_rng().gen::<[u8; 32]>()).unwrap();
+        let node_id = NodeId::from(secret.public().address().as_ref());
+        (secret, node_id)
+    }
+
+    fn sign_with(secret: &ethsign::SecretKey, digest: &[u8]) -> Vec<u8> {
+        let signature = secret.sign(digest).unwrap();
+        [&[signature.v][..], &signature.r[..], &signature.s[..]].concat()
+    }
+
+    fn signed_envelope(secret: &ethsign::SecretKey, sender: NodeId) -> Envelope {
+        let (_, recipient) = identity();
+        let address = format!("/net/{}/market/test", recipient);
+        let mut envelope = Envelope::new(sender, recipient, address, b"payload".to_vec());
+        envelope.signature = sign_with(secret, &envelope.digest());
+        envelope
+    }
+
+    /// Replaces identity service, which signs with given keys.
+    fn bind_signer(keys: Vec<ethsign::SecretKey>) {
+        let keys: HashMap<NodeId, ethsign::SecretKey> = keys
+            .into_iter()
+            .map(|key| (NodeId::from(key.public().address().as_ref()), key))
+            .collect();
+        let _ = bus::bind(identity::BUS_ID, move |msg: identity::Sign| {
+            let result = keys
+                .get(&msg.node_id)
+                .map(|key| sign_with(key, &msg.payload))
+                .ok_or_else(|| identity::Error::NodeNotFound(Box::new(msg.node_id)));
+            future::ready(result)
+        });
+    }
+
+    /// Routes handshakes from `from` to `to` directly to `channel`, instead of the hub.
+    fn bind_peer_handshake(from: NodeId, to: NodeId, channel: Rc<SecureChannel>) {
+        let addr = format!("/from/{}/to/{}{}", from, to, public_service_path());
+        let _ = bus::bind(&addr, move |msg: Handshake| {
+            let channel = channel.clone();
+            async move {
+                channel
+                    .handshake_response(from, msg)
+                    .await
+                    .map_err(|e| public_net::HandshakeError(e.to_string()))
+            }
+        });
+    }
+
+    #[test]
+    fn test_protection_by_prefix() {
+        let config = SecureConfig {
+            signed: vec!["/market".to_string()],
+            encrypted: vec!["/activity/".to_string()],
+        };
+        assert_eq!(config.protection("/market/protocol"), Protection::Signed);
+        assert_eq!(config.protection("/activity/exec"), Protection::Encrypted);
+        assert_eq!(config.protection("/marketplace"), Protection::None);
+        assert_eq!(config.protection("/net/Handshake"), Protection::None);
+    }
+
+    #[test]
+    fn test_split_net_addr() {
+        let (_, node_id) = identity();
+        let addr = format!("/net/{}/market/test", node_id);
+        assert_eq!(split_net_addr(&addr).unwrap(), (node_id, "/market/test"));
+        assert!(split_net_addr("/public/market").is_err());
+    }
+
+    #[test]
+    fn test_envelope_signature() {
+        let (secret, sender) = identity();
+        let envelope = signed_envelope(&secret, sender);
+        envelope.verify().unwrap();
+
+        let decoded = Envelope::decode(&envelope.encode().unwrap())
+            .unwrap()
+            .unwrap();
+        decoded.verify().unwrap();
+        assert!(Envelope::decode(b"plain message").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_forged_envelope_is_rejected() {
+        let (secret, sender) = identity();
+
+        let mut envelope = signed_envelope(&secret, sender);
+        envelope.payload = b"forged".to_vec();
+        assert!(envelope.verify().is_err());
+
+        // Hub can't claim, that message comes from other node.
+        let mut envelope = signed_envelope(&secret, sender);
+        envelope.sender = identity().1;
+        assert!(envelope.verify().is_err());
+
+        // Nor turn request into reply to other request.
+        let mut envelope = signed_envelope(&secret, sender);
+        envelope.in_reply_to = Some(rand::thread_rng().gen());
+        assert!(envelope.verify().is_err());
+    }
+
+    #[test]
+    fn test_session_key_is_symmetric() {
+        let (_, alice) = identity();
+        let (_, bob) = identity();
+        let alice_secret = EphemeralSecret::new(rand::rngs::OsRng);
+        let alice_pub = PublicKey::from(&alice_secret);
+        let bob_secret = EphemeralSecret::new(rand::rngs::OsRng);
+        let bob_pub = PublicKey::from(&bob_secret);
+
+        let (alice_id, alice_session) = Session::derive(
+            alice,
+            bob,
+            alice_secret,
+            (&alice, &alice_pub),
+            (&bob, &bob_pub),
+        );
+        let (bob_id, bob_session) = Session::derive(
+            bob,
+            alice,
+            bob_secret,
+            (&alice, &alice_pub),
+            (&bob, &bob_pub),
+        );
+        assert_eq!(alice_id, bob_id);
+        assert_eq!(alice_session.key, bob_session.key);
+
+        let mut envelope = Envelope::new(alice, bob, "/net/test".to_string(), b"secret".to_vec());
+        envelope.encrypt(alice_id, &alice_session).unwrap();
+        assert_ne!(envelope.payload, b"secret".to_vec());
+        assert_eq!(envelope.decrypt(&bob_session).unwrap(), b"secret".to_vec());
+
+        let eve_secret = EphemeralSecret::new(rand::rngs::OsRng);
+        let eve_pub = PublicKey::from(&eve_secret);
+        let (_, eve_session) = Session::derive(
+            bob,
+            alice,
+            eve_secret,
+            (&alice, &alice_pub),
+            (&bob, &eve_pub),
+        );
+        assert!(envelope.decrypt(&eve_session).is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_seal_open_round_trip() {
+        let (alice_key, alice) = identity();
+        let (bob_key, bob) = identity();
+        let (_, carol) = identity();
+        bind_signer(vec![alice_key, bob_key]);
+
+        let config = SecureConfig {
+            signed: vec!["/market".to_string()],
+            encrypted: vec!["/activity".to_string()],
+        };
+        let alice_channel = SecureChannel::new(config.clone());
+        let bob_channel = SecureChannel::new(config);
+        bind_peer_handshake(alice, bob, bob_channel.clone());
+
+        for (service, encrypted) in &[("/market/test", false), ("/activity/exec", true)] {
+            let addr = format!("/net/{}{}", bob, service);
+            let (sealed, pending) = alice_channel
+                .clone()
+                .seal(alice, addr.clone(), b"request".to_vec())
+                .await
+                .unwrap();
+            assert!(sealed.starts_with(MAGIC));
+            let visible = sealed.windows(7).any(|part| part == b"request");
+            assert_eq!(visible, !encrypted);
+
+            let (opened, reply) = bob_channel
+                .clone()
+                .open(alice.to_string(), addr.clone(), sealed.clone())
+                .await
+                .unwrap();
+            assert_eq!(opened, b"request".to_vec());
+            // Hub can't replay request, nor send it as other node.
+            let replayed = bob_channel
+                .clone()
+                .open(alice.to_string(), addr.clone(), sealed.clone())
+                .await;
+            assert!(matches!(replayed, Err(SecureError::Replayed(_))));
+            let forged = bob_channel
+                .clone()
+                .open(carol.to_string(), addr.clone(), sealed)
+                .await;
+            assert!(forged.is_err());
+
+            let reply = bob_channel
+                .clone()
+                .seal_reply(reply, b"reply".to_vec())
+                .await
+                .unwrap();
+            assert_eq!(
+                alice_channel.open_reply(&pending, reply.clone()).unwrap(),
+                b"reply".to_vec()
+            );
+            assert!(alice_channel.open_reply(&pending, reply).is_err());
+            // Hub can't strip protection from reply.
+            assert!(alice_channel
+                .open_reply(&pending, b"reply".to_vec())
+                .is_err());
+        }
+
+        let addr = format!("/net/{}/market/test", bob);
+        let unprotected = bob_channel
+            .clone()
+            .open(alice.to_string(), addr, b"request".to_vec())
+            .await;
+        assert!(matches!(unprotected, Err(SecureError::Unprotected(_))));
+
+        // Carol doesn't answer handshake (or hub drops it), so she gets only
+        // signed-only messages, but still in signed envelope.
+        let addr = format!("/net/{}/market/test", carol);
+        let (sealed, pending) = alice_channel
+            .clone()
+            .seal(alice, addr, b"request".to_vec())
+            .await
+            .unwrap();
+        assert!(sealed.starts_with(MAGIC));
+        assert!(pending.is_some());
+
+        let addr = format!("/net/{}/activity/exec", carol);
+        let sealed = alice_channel
+            .clone()
+            .seal(alice, addr, b"request".to_vec())
+            .await;
+        assert!(matches!(sealed, Err(SecureError::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_sessions_are_bounded() {
+        let channel = SecureChannel::new(SecureConfig::default());
+        let local = identity().1;
+        let session = |peer: NodeId| Session {
+            local,
+            peer,
+            key: [0u8; 32],
+            established: Instant::now(),
+        };
+        let id = |n: usize| {
+            let mut id = [0u8; 16];
+            id[..8].copy_from_slice(&(n as u64).to_le_bytes());
+            id
+        };
+
+        // Repeated handshakes of the same peer replace its oldest sessions.
+        let peer = identity().1;
+        for n in 0..2 * MAX_SESSIONS_PER_PEER {
+            channel.insert_session(id(n), session(peer));
+        }
+        assert_eq!(channel.sessions.borrow().len(), MAX_SESSIONS_PER_PEER);
+
+        for n in 0..MAX_SESSIONS + 10 {
+            let mut peer = [0u8; 20];
+            peer[..8].copy_from_slice(&(n as u64).to_le_bytes());
+            channel.insert_session(id(MAX_SESSIONS + n), session(NodeId::from(&peer[..])));
+        }
+        assert_eq!(channel.sessions.borrow().len(), MAX_SESSIONS);
+    }
+}
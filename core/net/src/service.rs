@@ -1,54 +1,87 @@
 use actix_rt::Arbiter;
-use anyhow::anyhow;
-use futures::channel::oneshot;
+use anyhow::{anyhow, Context};
+use futures::future::{Either, LocalBoxFuture};
 use futures::prelude::*;
-use std::net::{SocketAddr, ToSocketAddrs};
+use futures::stream::LocalBoxStream;
+use metrics::{counter, timing};
+use std::cell::RefCell;
 use std::rc::Rc;
+use std::time::Instant;
 
 use ya_core_model::identity::{self, IdentityInfo};
 use ya_core_model::net;
 use ya_core_model::net::local::{self as local_net, SendBroadcastMessage, SendBroadcastStub};
+use ya_core_model::net::public::{self as public_net, HandshakeError};
 use ya_core_model::NodeId;
+use ya_service_api_interfaces::Service;
 use ya_service_bus::connection::ClientInfo;
 use ya_service_bus::{
-    connection, serialization, typed as bus, untyped as local_bus, Error, RpcEndpoint, RpcMessage,
+    connection, serialization, typed as bus, untyped as local_bus, Error, ResponseChunk,
+    RpcEndpoint, RpcMessage,
 };
-use ya_utils_networking::srv_resolver;
 
 use crate::api::{net_service, parse_from_addr};
+use crate::diagnostics::{self, NetState};
 use crate::handler::{auto_rebind, CentralBusHandler};
-
-pub const CENTRAL_ADDR_ENV_VAR: &str = "CENTRAL_NET_HOST";
-
-async fn central_net_addr() -> std::io::Result<SocketAddr> {
-    Ok(match std::env::var(CENTRAL_ADDR_ENV_VAR) {
-        Ok(v) => v,
-        Err(_) => srv_resolver::resolve_yagna_record("_net._tcp").await?,
-    }
-    .to_socket_addrs()?
-    .next()
-    .expect("central net hub addr needed"))
-}
-
-/// Initialize net module on a hub.
+use crate::hub::{self, HealthCheckConfig};
+use crate::lan::{self, LanConfig, LanPeers};
+use crate::secure::{SecureChannel, SecureConfig};
+use crate::{cli, rest};
+
+pub use crate::hub::CENTRAL_ADDR_ENV_VAR;
+pub use crate::lan::{LAN_BIND_ENV_VAR, LAN_MULTICAST_ENV_VAR, NET_MODE_ENV_VAR};
+pub use crate::secure::{ENCRYPTED_PREFIXES_ENV_VAR, SIGNED_PREFIXES_ENV_VAR};
+
+/// Hub routes calls to this address back to us. Answering them proves,
+/// that hub still forwards messages in both directions.
+const HEALTH_CHECK_SERVICE: &str = "/_health";
+
+type CallFn =
+    Rc<dyn Fn(String, String, Vec<u8>) -> LocalBoxFuture<'static, Result<Vec<u8>, Error>>>;
+type CallStreamingFn =
+    Rc<dyn Fn(String, String, Vec<u8>) -> LocalBoxStream<'static, Result<ResponseChunk, Error>>>;
+type BroadcastFn =
+    Rc<dyn Fn(String, String, Vec<u8>) -> LocalBoxFuture<'static, Result<(), Error>>>;
+
+/// Initialize net module on the first reachable hub. Returned future resolves,
+/// when connection with the hub is lost or hub stops responding.
+///
+/// In LAN mode node connects to its own router instead of the hub
+/// and sends outgoing messages directly to discovered peers.
 pub async fn bind_remote(
     client_info: ClientInfo,
     default_node_id: NodeId,
     nodes: Vec<NodeId>,
-) -> std::io::Result<oneshot::Receiver<()>> {
-    let hub_addr = central_net_addr().await?;
-    let conn = connection::tcp(hub_addr).await?;
+    state: Rc<RefCell<NetState>>,
+    secure: Rc<SecureChannel>,
+    lan: Option<Rc<LanPeers>>,
+) -> std::io::Result<LocalBoxFuture<'static, Result<(), ()>>> {
+    let hub_addrs = match &lan {
+        Some(lan) => {
+            lan.set_own_node_ids(nodes.clone());
+            vec![lan.config().local_addr()]
+        }
+        None => state.borrow().hubs.order(hub::resolve_hubs().await?),
+    };
+    let (hub_addr, conn) = hub::connect_any(&hub_addrs, |addr| connection::tcp(addr)).await?;
     let bcast = super::bcast::BCastService::default();
     let bcast_service_id = <SendBroadcastMessage<()> as RpcMessage>::ID;
 
     // connect to hub with forwarding handler
     let own_net_nodes: Vec<_> = nodes.iter().map(|id| net_service(id)).collect();
+    let health_check_addr = format!("{}{}", net_service(&default_node_id), HEALTH_CHECK_SERVICE);
+    let secure_forward = secure.clone();
 
     let forward_call = move |request_id: String, caller: String, addr: String, data: Vec<u8>| {
+        if addr == health_check_addr {
+            return stream::once(future::ok(ResponseChunk::Full(Vec::new()))).boxed_local();
+        }
+
         let prefix = own_net_nodes
             .iter()
             .find(|&own_net_node_id| addr.starts_with(own_net_node_id));
         if let Some(prefix) = prefix {
+            counter!("net.calls.incoming", 1);
             // replaces  /net/<dest_node_id>/test/1 --> /public/test/1
             let local_addr: String = addr.replacen(prefix, net::PUBLIC_PREFIX, 1);
             log::trace!(
@@ -58,22 +91,55 @@ pub async fn bind_remote(
                 local_addr,
                 request_id
             );
-            // actual forwarding to my local bus
-            local_bus::call_stream(&local_addr, &caller, &data).right_stream()
+            // signature (and encryption) is checked before forwarding to my local bus
+            let secure = secure_forward.clone();
+            secure_forward
+                .clone()
+                .open(caller.clone(), addr, data)
+                .map_err(Error::from)
+                .map_ok(move |(data, reply)| {
+                    // replies are sealed the same way, as the request
+                    local_bus::call_stream(&local_addr, &caller, &data).and_then(move |chunk| {
+                        secure
+                            .clone()
+                            .seal_reply_chunk(reply.clone(), chunk)
+                            .map_err(Error::from)
+                    })
+                })
+                .try_flatten_stream()
+                .boxed_local()
         } else {
             return stream::once(future::err(Error::GsbBadRequest(format!(
                 "wrong routing: {}; I'll accept only addrs starting with: {:?}",
                 addr, own_net_nodes
             ))))
-            .left_stream();
+            .boxed_local();
         }
     };
 
     let broadcast_handler = {
         let bcast = bcast.clone();
+        // LAN router accepts broadcasts from any client, so caller has to sign them.
+        let signed_only = lan.is_some();
 
         move |caller: String, topic: String, msg: Vec<u8>| {
+            let msg = if signed_only {
+                match lan::open_broadcast(&caller, &topic, &msg) {
+                    Some(msg) => msg,
+                    None => {
+                        log::debug!(
+                            "Dropped broadcast to topic {} not signed by [{}].",
+                            topic,
+                            caller
+                        );
+                        return;
+                    }
+                }
+            } else {
+                msg
+            };
             let endpoints = bcast.resolve(&topic);
+            counter!("net.broadcasts.incoming", 1);
             let msg: Rc<[u8]> = msg.into();
             Arbiter::spawn(async move {
                 log::trace!("Received broadcast to topic {} from [{}].", &topic, &caller);
@@ -88,6 +154,53 @@ pub async fn bind_remote(
     let (handler, done_rx) = CentralBusHandler::new(forward_call, broadcast_handler);
     let central_bus = connection::connect_with_handler(client_info, conn, handler);
 
+    let (call, call_streaming, broadcast): (CallFn, CallStreamingFn, BroadcastFn) = match lan {
+        Some(lan) => {
+            let (lan_stream, lan_bcast) = (lan.clone(), lan.clone());
+            (
+                Rc::new(move |caller: String, addr: String, msg: Vec<u8>| {
+                    lan.clone().call(caller, addr, msg)
+                }),
+                Rc::new(move |caller: String, addr: String, msg: Vec<u8>| {
+                    lan_stream.clone().call_streaming(caller, addr, msg)
+                }),
+                Rc::new(move |caller: String, topic: String, msg: Vec<u8>| {
+                    lan_bcast.clone().broadcast(caller, topic, msg)
+                }),
+            )
+        }
+        None => {
+            let (bus_call, bus_stream, bus_bcast) = (
+                central_bus.clone(),
+                central_bus.clone(),
+                central_bus.clone(),
+            );
+            (
+                Rc::new(move |caller: String, addr: String, msg: Vec<u8>| {
+                    bus_call
+                        .call(caller, addr.clone(), msg)
+                        .map_err(|e| Error::RemoteError(addr, e.to_string()))
+                        .boxed_local()
+                }),
+                Rc::new(move |caller: String, addr: String, msg: Vec<u8>| {
+                    bus_stream.call_streaming(caller, addr, msg).boxed_local()
+                }),
+                Rc::new(move |caller: String, topic: String, msg: Vec<u8>| {
+                    bus_bcast
+                        .broadcast(caller, topic, msg)
+                        .map_err(|e| Error::GsbFailure(e.to_string()))
+                        .boxed_local()
+                }),
+            )
+        }
+    };
+
+    let (call, call_streaming, broadcast) = (
+        metered_call(call),
+        metered_call_streaming(call_streaming),
+        metered_broadcast(broadcast),
+    );
+
     // bind my local net service(s) on remote centralised bus under /net/<my_identity>
     for node in &nodes {
         let addr = net_service(node);
@@ -110,26 +223,35 @@ pub async fn bind_remote(
         };
 
         // `caller` is usually "local", so we replace it with our default node id
-        let central_bus_rpc = central_bus.clone();
-        let default_caller_rpc = default_node_id.to_string();
+        let call_rpc = call.clone();
+        let secure_rpc = secure.clone();
         let rpc = move |_caller: &str, addr: &str, msg: &[u8]| {
-            let caller = default_caller_rpc.clone();
-            log_message("rpc", &caller, addr);
-            let addr = addr.to_string();
-            central_bus_rpc
-                .call(caller, addr.clone(), Vec::from(msg))
-                .map_err(|e| Error::RemoteError(addr, e.to_string()))
+            log_message("rpc", &default_node_id.to_string(), addr);
+            secure_call(
+                secure_rpc.clone(),
+                call_rpc.clone(),
+                default_node_id,
+                addr.to_string(),
+                Vec::from(msg),
+            )
         };
 
-        let central_bus_stream = central_bus.clone();
-        let default_caller_stream = default_node_id.to_string();
+        let call_stream = call_streaming.clone();
+        let secure_stream = secure.clone();
         let stream = move |_caller: &str, addr: &str, msg: &[u8]| {
-            let caller = default_caller_stream.clone();
-            log_message("stream", &caller, addr);
-            let addr = addr.to_string();
-            central_bus_stream
-                .call_streaming(caller, addr.clone(), Vec::from(msg))
-                .map_err(move |e| Error::RemoteError(addr.clone(), e.to_string()))
+            log_message("stream", &default_node_id.to_string(), addr);
+            let call_streaming = call_stream.clone();
+            secure_call_streaming(
+                secure_stream.clone(),
+                Rc::new(move |caller: String, addr: String, msg: Vec<u8>| {
+                    call_streaming(caller, addr.clone(), msg)
+                        .map_err(move |e| Error::RemoteError(addr.clone(), e.to_string()))
+                        .boxed_local()
+                }),
+                default_node_id,
+                addr.to_string(),
+                Vec::from(msg),
+            )
         };
 
         local_bus::subscribe(net::BUS_ID, rpc, stream);
@@ -138,7 +260,8 @@ pub async fn bind_remote(
     // bind /from/<caller>/to/<addr> on my local bus and forward all calls to remote bus under /net
     {
         let nodes_rpc = nodes.clone();
-        let central_bus_rpc = central_bus.clone();
+        let call_rpc = call.clone();
+        let secure_rpc = secure.clone();
         let rpc = move |_caller: &str, addr: &str, msg: &[u8]| {
             let (from_node, to_addr) = match parse_from_addr(addr) {
                 Ok(v) => v,
@@ -153,14 +276,19 @@ pub async fn bind_remote(
                 .left_future();
             }
 
-            central_bus_rpc
-                .call(from_node.to_string(), to_addr.clone(), Vec::from(msg))
-                .map_err(|e| Error::RemoteError(to_addr, e.to_string()))
-                .right_future()
+            secure_call(
+                secure_rpc.clone(),
+                call_rpc.clone(),
+                from_node,
+                to_addr,
+                Vec::from(msg),
+            )
+            .right_future()
         };
 
         let nodes_stream = nodes.clone();
-        let central_bus_stream = central_bus.clone();
+        let call_stream = call_streaming.clone();
+        let secure_stream = secure.clone();
         let stream = move |_caller: &str, addr: &str, msg: &[u8]| {
             let (from_node, to_addr) = match parse_from_addr(addr) {
                 Ok(v) => v,
@@ -182,9 +310,14 @@ pub async fn bind_remote(
                     .left_stream();
             }
 
-            central_bus_stream
-                .call_streaming(from_node.to_string(), to_addr, Vec::from(msg))
-                .right_stream()
+            secure_call_streaming(
+                secure_stream.clone(),
+                call_stream.clone(),
+                from_node,
+                to_addr,
+                Vec::from(msg),
+            )
+            .right_stream()
         };
 
         local_bus::subscribe("/from", rpc, stream);
@@ -214,7 +347,6 @@ pub async fn bind_remote(
 
     // Send broadcast to remote
     {
-        let central_bus = central_bus.clone();
         let addr = format!("{}/{}", local_net::BUS_ID, bcast_service_id);
         let resp: Rc<[u8]> = serialization::to_vec(&Ok::<(), ()>(())).unwrap().into();
         let _ = local_bus::subscribe(
@@ -237,7 +369,7 @@ pub async fn bind_remote(
                     &caller
                 );
 
-                let fut = central_bus.broadcast(caller.to_owned(), stub.topic, msg.into());
+                let fut = broadcast(caller.to_owned(), stub.topic, msg.into());
                 let resp = resp.clone();
                 async move {
                     if let Err(e) = fut.await {
@@ -252,23 +384,75 @@ pub async fn bind_remote(
         );
     }
 
-    Ok(done_rx)
+    state
+        .borrow_mut()
+        .on_connected(hub_addr, nodes.clone(), bcast.clone());
+
+    let health_check = {
+        let central_bus = central_bus.clone();
+        let caller = default_node_id.to_string();
+        let addr = format!("{}{}", net_service(&default_node_id), HEALTH_CHECK_SERVICE);
+        hub::monitor_health(HealthCheckConfig::default(), move || {
+            central_bus.call(caller.clone(), addr.clone(), Vec::new())
+        })
+    };
+
+    Ok(async move {
+        match future::select(done_rx, health_check.boxed_local()).await {
+            Either::Left(_) => log::warn!("Lost connection with central net hub {}", hub_addr),
+            Either::Right(_) => log::warn!("Central net hub {} stopped responding", hub_addr),
+        }
+        state.borrow_mut().on_disconnected();
+        Ok(())
+    }
+    .boxed_local())
+}
+
+/// Counts outgoing calls and measures time until the response arrives.
+fn metered_call(call: CallFn) -> CallFn {
+    Rc::new(move |caller: String, addr: String, msg: Vec<u8>| {
+        let start = Instant::now();
+        counter!("net.calls.outgoing", 1);
+        call(caller, addr, msg)
+            .inspect(move |result| {
+                if result.is_err() {
+                    counter!("net.calls.outgoing.errors", 1);
+                }
+                timing!("net.calls.outgoing.time", start, Instant::now());
+            })
+            .boxed_local()
+    })
+}
+
+fn metered_call_streaming(call_streaming: CallStreamingFn) -> CallStreamingFn {
+    Rc::new(move |caller: String, addr: String, msg: Vec<u8>| {
+        counter!("net.calls.outgoing.streaming", 1);
+        call_streaming(caller, addr, msg)
+            .inspect_err(|_| counter!("net.calls.outgoing.errors", 1))
+            .boxed_local()
+    })
+}
+
+fn metered_broadcast(broadcast: BroadcastFn) -> BroadcastFn {
+    Rc::new(move |caller: String, topic: String, msg: Vec<u8>| {
+        counter!("net.broadcasts.outgoing", 1);
+        broadcast(caller, topic, msg)
+    })
 }
 
 async fn unbind_remote(nodes: Vec<NodeId>) {
     let addrs = nodes
         .into_iter()
         .map(|node_id| net_service(node_id))
-        .chain(std::iter::once(format!(
-            "{}/{}",
-            local_net::BUS_ID,
-            <SendBroadcastMessage<()> as RpcMessage>::ID
-        )))
         .chain(
-            [net::BUS_ID, local_net::BUS_ID, "/from"]
-                .iter()
-                .map(|s| s.to_string()),
+            [
+                <SendBroadcastMessage<()> as RpcMessage>::ID,
+                <local_net::Subscribe as RpcMessage>::ID,
+            ]
+            .iter()
+            .map(|id| format!("{}/{}", local_net::BUS_ID, id)),
         )
+        .chain([net::BUS_ID, "/from"].iter().map(|s| s.to_string()))
         .collect::<Vec<_>>();
 
     log::debug!("Unbinding remote handlers");
@@ -281,31 +465,312 @@ async fn unbind_remote(nodes: Vec<NodeId>) {
 
 pub struct Net;
 
+impl Service for Net {
+    type Cli = cli::NetCli;
+}
+
 impl Net {
     pub async fn gsb<Context>(_: Context) -> anyhow::Result<()> {
-        let ids: Vec<IdentityInfo> = bus::service(identity::BUS_ID)
-            .send(identity::List::default())
-            .await
-            .map_err(anyhow::Error::msg)??;
-
         let client_info = ClientInfo::new("sb-client-net");
-        let default_id = ids
-            .iter()
-            .find(|i| i.is_default)
-            .map(|i| i.node_id)
-            .ok_or_else(|| anyhow!("no default identity"))?;
-        log::info!("using default identity as network id: {:?}", default_id);
-        let ids = ids
-            .into_iter()
-            .map(|id| id.node_id)
-            .collect::<Vec<NodeId>>();
-        let ids_clone = ids.clone();
+        let state = Rc::new(RefCell::new(NetState::default()));
+        diagnostics::bind_service(state.clone());
+
+        // Initialize counters to 0 value. Otherwise they won't appear on metrics endpoint
+        // until first change to value will be made.
+        counter!("net.calls.incoming", 0);
+        counter!("net.calls.outgoing", 0);
+        counter!("net.calls.outgoing.streaming", 0);
+        counter!("net.calls.outgoing.errors", 0);
+        counter!("net.broadcasts.incoming", 0);
+        counter!("net.broadcasts.outgoing", 0);
+
+        let secure_config = SecureConfig::from_env();
+        let lan_config = LanConfig::from_env()?;
+        if let Some(lan_config) = &lan_config {
+            lan_config.check_secure(&secure_config)?;
+        }
+
+        let secure = SecureChannel::new(secure_config);
+        bind_handshake(secure.clone());
+
+        let lan = lan_config.map(|config| LanPeers::new(config, client_info.clone()));
+        if let Some(lan) = lan.clone() {
+            ya_sb_router::bind_gsb_router(Some(lan.config().router_url()))
+                .await
+                .context("binding LAN router")?;
+            Arbiter::spawn(async move {
+                if let Err(e) = lan::discover(lan).await {
+                    log::error!("LAN peer discovery failed: {}", e);
+                }
+            });
+        }
+
+        // Identities are listed on each reconnect, so we bind also
+        // the ones created since previous connection.
+        let bound_ids = Rc::new(RefCell::new(Vec::new()));
+        let bound_ids_clone = bound_ids.clone();
 
         auto_rebind(
-            move || bind_remote(client_info.clone(), default_id.clone(), ids.clone()),
-            move || unbind_remote(ids_clone.clone()),
+            move || {
+                let client_info = client_info.clone();
+                let state = state.clone();
+                let secure = secure.clone();
+                let lan = lan.clone();
+                let bound_ids = bound_ids.clone();
+                async move {
+                    let (default_id, ids) = list_identities().await.map_err(|e| {
+                        std::io::Error::new(std::io::ErrorKind::Other, e.to_string())
+                    })?;
+                    bound_ids.replace(ids.clone());
+                    bind_remote(client_info, default_id, ids, state, secure, lan).await
+                }
+            },
+            move || unbind_remote(bound_ids_clone.borrow().clone()),
         )
         .await;
         Ok(())
     }
+
+    pub fn rest<Context>(_: &Context) -> actix_web::Scope {
+        rest::web_scope()
+    }
+}
+
+/// Bound once, like diagnostics, since sessions don't depend on hub connection.
+fn bind_handshake(secure: Rc<SecureChannel>) {
+    let _ = bus::bind_with_caller(
+        public_net::BUS_ID,
+        move |caller: String, msg: public_net::Handshake| {
+            let secure = secure.clone();
+            async move {
+                let caller: NodeId = caller
+                    .parse()
+                    .map_err(|_| HandshakeError(format!("invalid caller: {}", caller)))?;
+                secure
+                    .handshake_response(caller, msg)
+                    .await
+                    .map_err(|e| HandshakeError(e.to_string()))
+            }
+        },
+    );
+}
+
+/// Seals request to remote node and verifies the reply.
+fn secure_call(
+    secure: Rc<SecureChannel>,
+    call: CallFn,
+    sender: NodeId,
+    addr: String,
+    msg: Vec<u8>,
+) -> LocalBoxFuture<'static, Result<Vec<u8>, Error>> {
+    async move {
+        let (msg, pending) = secure.clone().seal(sender, addr.clone(), msg).await?;
+        match call(sender.to_string(), addr, msg).await {
+            Ok(reply) => Ok(secure.open_reply(&pending, reply)?),
+            Err(e) => {
+                if let Some(pending) = &pending {
+                    secure.on_remote_error(pending, &e.to_string());
+                }
+                Err(e)
+            }
+        }
+    }
+    .boxed_local()
+}
+
+/// Streaming version of `secure_call`, which verifies each reply chunk.
+fn secure_call_streaming(
+    secure: Rc<SecureChannel>,
+    call_streaming: CallStreamingFn,
+    sender: NodeId,
+    addr: String,
+    msg: Vec<u8>,
+) -> LocalBoxStream<'static, Result<ResponseChunk, Error>> {
+    secure
+        .clone()
+        .seal(sender, addr.clone(), msg)
+        .map_err(Error::from)
+        .map_ok(move |(msg, pending)| {
+            call_streaming(sender.to_string(), addr, msg).map(move |chunk| match chunk {
+                Ok(chunk) => Ok(secure.open_reply_chunk(&pending, chunk)?),
+                Err(e) => {
+                    if let Some(pending) = &pending {
+                        secure.on_remote_error(pending, &e.to_string());
+                    }
+                    Err(e)
+                }
+            })
+        })
+        .try_flatten_stream()
+        .boxed_local()
+}
+
+async fn list_identities() -> anyhow::Result<(NodeId, Vec<NodeId>)> {
+    let ids: Vec<IdentityInfo> = bus::service(identity::BUS_ID)
+        .send(identity::List::default())
+        .await
+        .map_err(anyhow::Error::msg)??;
+
+    let default_id = ids
+        .iter()
+        .find(|i| i.is_default)
+        .map(|i| i.node_id)
+        .ok_or_else(|| anyhow!("no default identity"))?;
+    log::info!("using default identity as network id: {:?}", default_id);
+    let ids = ids
+        .into_iter()
+        .map(|id| id.node_id)
+        .collect::<Vec<NodeId>>();
+    Ok((default_id, ids))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    use std::net::{SocketAddr, TcpListener};
+    use std::time::Duration;
+
+    use ya_core_model::net::local::BroadcastMessage;
+
+    use crate::api::bind_broadcast_with_caller;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    struct TestBcast {
+        value: u32,
+    }
+
+    impl BroadcastMessage for TestBcast {
+        const TOPIC: &'static str = "test-net-rebind";
+    }
+
+    fn free_addr() -> SocketAddr {
+        TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+    }
+
+    /// Stands in for a hub: GSB router running in separate system, so it can be stopped.
+    fn start_hub(addr: SocketAddr) -> actix_rt::System {
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let mut sys = actix_rt::System::new("hub-stand-in");
+            let url = url::Url::parse(&format!("tcp://{}", addr)).unwrap();
+            sys.block_on(ya_sb_router::bind_gsb_router(Some(url)))
+                .unwrap();
+            tx.send(actix_rt::System::current()).unwrap();
+            sys.run().unwrap();
+        });
+        rx.recv().unwrap()
+    }
+
+    /// Other node connected to `hub` calls our health check service
+    /// and broadcasts `TestBcast` message.
+    async fn call_and_broadcast_via(hub: SocketAddr, node_id: NodeId) -> anyhow::Result<()> {
+        let conn = connection::tcp(hub).await?;
+        let (handler, _done_rx) = CentralBusHandler::new(
+            |_: String, _: String, addr: String, _: Vec<u8>| {
+                stream::once(future::err(Error::GsbBadRequest(addr))).boxed_local()
+            },
+            |_: String, _: String, _: Vec<u8>| (),
+        );
+        let observer =
+            connection::connect_with_handler(ClientInfo::new("test-observer"), conn, handler);
+        let caller = "0x0000000000000000000000000000000000000001".to_string();
+
+        let addr = format!("{}{}", net_service(&node_id), HEALTH_CHECK_SERVICE);
+        observer
+            .call(caller.clone(), addr, Vec::new())
+            .await
+            .map_err(|e| anyhow!("health check call failed: {}", e))?;
+
+        let msg = serialization::to_vec(&SendBroadcastMessage::new(TestBcast { value: 1 }))?;
+        observer
+            .broadcast(caller, TestBcast::TOPIC.to_string(), msg)
+            .await
+            .map_err(|e| anyhow!("broadcast failed: {}", e))?;
+
+        // Let connection flush broadcast, before it's dropped.
+        tokio::time::delay_for(Duration::from_millis(100)).await;
+        Ok(())
+    }
+
+    /// Retries until broadcast sent through `hub` reaches our handler.
+    async fn expect_reachable_via(
+        hub: SocketAddr,
+        node_id: NodeId,
+        received: &mut futures::channel::mpsc::UnboundedReceiver<u32>,
+    ) {
+        for _ in 0..50 {
+            if call_and_broadcast_via(hub, node_id).await.is_ok() {
+                let next = tokio::time::timeout(Duration::from_millis(200), received.next());
+                if let Ok(Some(1)) = next.await {
+                    return;
+                }
+            }
+            tokio::time::delay_for(Duration::from_millis(200)).await;
+        }
+        panic!("node {} not reachable via hub {}", node_id, hub);
+    }
+
+    #[actix_rt::test]
+    async fn test_rebind_after_hub_reconnect() {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let (hub_a, hub_b) = (free_addr(), free_addr());
+        let system_a = start_hub(hub_a);
+        let _system_b = start_hub(hub_b);
+        std::env::set_var(CENTRAL_ADDR_ENV_VAR, format!("{},{}", hub_a, hub_b));
+
+        let node_id: NodeId = "0xbabe000000000000000000000000000000000000"
+            .parse()
+            .unwrap();
+        let state = Rc::new(RefCell::new(NetState::default()));
+        let secure = SecureChannel::new(SecureConfig::default());
+        {
+            let state = state.clone();
+            Arbiter::spawn(auto_rebind(
+                move || {
+                    bind_remote(
+                        ClientInfo::new("test-net"),
+                        node_id,
+                        vec![node_id],
+                        state.clone(),
+                        secure.clone(),
+                        None,
+                    )
+                },
+                move || unbind_remote(vec![node_id]),
+            ));
+        }
+
+        let (tx, mut received) = futures::channel::mpsc::unbounded();
+        for _ in 0..50 {
+            if state.borrow().status().connected {
+                break;
+            }
+            tokio::time::delay_for(Duration::from_millis(100)).await;
+        }
+        bind_broadcast_with_caller(
+            "/test/net/rebind",
+            move |_caller: String, msg: SendBroadcastMessage<TestBcast>| {
+                tx.unbounded_send(msg.body().value).ok();
+                future::ok(())
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(state.borrow().status().hub_addr, Some(hub_a.to_string()));
+        expect_reachable_via(hub_a, node_id, &mut received).await;
+
+        // After hub A is gone, node should fail over to hub B and bind
+        // `/net/<node_id>` and broadcast topics there again.
+        system_a.stop();
+        expect_reachable_via(hub_b, node_id, &mut received).await;
+
+        let status = state.borrow().status();
+        assert_eq!(status.hub_addr, Some(hub_b.to_string()));
+        assert_eq!(status.reconnects, 1);
+    }
 }
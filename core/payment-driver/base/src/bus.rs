@@ -70,6 +70,12 @@ pub async fn bind_service<Driver: PaymentDriver + 'static>(
         )
         .bind_with_processor(
             move |db, dr, c, m| async move { dr.validate_allocation(db, c, m).await }
+        )
+        .bind_with_processor(
+            move |db, dr, c, m| async move { dr.get_status(db, c, m).await }
+        )
+        .bind_with_processor(
+            move |db, dr, c, m| async move { dr.requeue_failed_payments(db, c, m).await }
         );
 
     log::debug!("Successfully bound payment driver service to service bus.");
@@ -9,16 +9,22 @@ use actix::{
     prelude::{Addr, Context},
     Actor,
 };
+use chrono::Utc;
 use futures::lock::Mutex;
 use std::sync::Arc;
 use std::time::Duration;
 
 pub use async_trait::async_trait;
 
+// Local uses
+use crate::health::{DriverHealth, Job};
+
 #[async_trait(?Send)]
 pub trait PaymentDriverCron {
     async fn confirm_payments(&self);
     async fn process_payments(&self);
+    /// Jobs should record their errors here, to make them visible in driver status.
+    fn health(&self) -> &DriverHealth;
 }
 
 pub struct Cron<D: PaymentDriverCron> {
@@ -43,7 +49,9 @@ impl<D: PaymentDriverCron + 'static> Cron<D> {
                 match driver.try_lock() {
                     Some(driver) => {
                         log::trace!("Running payment confirmation job...");
+                        let started = Utc::now();
                         driver.confirm_payments().await;
+                        driver.health().run_finished(Job::Confirmations, started);
                         log::trace!("Confirmation job finished.");
                     }
                     None => {
@@ -61,7 +69,9 @@ impl<D: PaymentDriverCron + 'static> Cron<D> {
                 match driver.try_lock() {
                     Some(driver) => {
                         log::trace!("Running payment job...");
+                        let started = Utc::now();
                         driver.process_payments().await;
+                        driver.health().run_finished(Job::Payments, started);
                         log::trace!("Payment job finished.");
                     }
                     None => {
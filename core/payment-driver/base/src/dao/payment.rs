@@ -3,6 +3,7 @@
 */
 
 // External crates
+use chrono::Utc;
 use diesel::{self, ExpressionMethods, QueryDsl, RunQueryDsl};
 
 // Workspace uses
@@ -12,7 +13,10 @@ use ya_persistence::executor::{do_with_transaction, readonly_transaction, AsDao,
 use crate::{
     dao::DbResult,
     db::{
-        models::{Network, PaymentEntity, PAYMENT_STATUS_NOT_YET, PAYMENT_STATUS_OK},
+        models::{
+            Network, PaymentEntity, PAYMENT_STATUS_FAILED, PAYMENT_STATUS_NOT_YET,
+            PAYMENT_STATUS_OK,
+        },
         schema::{payment, payment::dsl, transaction},
     },
 };
@@ -96,4 +100,134 @@ impl<'c> PaymentDao<'c> {
         })
         .await
     }
+
+    pub async fn count_by_status(&self, status: i32) -> DbResult<i64> {
+        readonly_transaction(self.pool, move |conn| {
+            let count: i64 = dsl::payment
+                .filter(dsl::status.eq(status))
+                .count()
+                .get_result(conn)?;
+            Ok(count)
+        })
+        .await
+    }
+
+    /// Makes failed payments pending again and restarts their retry window.
+    /// Empty `order_ids` requeue all failed payments.
+    pub async fn requeue_failed(&self, order_ids: Vec<String>) -> DbResult<usize> {
+        do_with_transaction(self.pool, move |conn| {
+            let now = Utc::now().naive_utc();
+            let failed = dsl::payment.filter(dsl::status.eq(PAYMENT_STATUS_FAILED));
+            let values = (
+                dsl::status.eq(PAYMENT_STATUS_NOT_YET),
+                dsl::payment_due_date.eq(now),
+            );
+            let updated = if order_ids.is_empty() {
+                diesel::update(failed).set(values).execute(conn)?
+            } else {
+                diesel::update(failed.filter(dsl::order_id.eq_any(order_ids)))
+                    .set(values)
+                    .execute(conn)?
+            };
+            Ok(updated)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+    use ya_persistence::executor::DbExecutor;
+
+    use crate::db::models::PAYMENT_STATUS_OK;
+
+    async fn init_db(dir: &TempDir) -> DbExecutor {
+        let db = DbExecutor::from_data_dir(dir.path(), "driver").unwrap();
+        crate::dao::init(&db).await.unwrap();
+        let dao = db.as_dao::<PaymentDao>();
+        let payments = vec![
+            ("failed-1", PAYMENT_STATUS_FAILED),
+            ("failed-2", PAYMENT_STATUS_FAILED),
+            ("pending", PAYMENT_STATUS_NOT_YET),
+            ("paid", PAYMENT_STATUS_OK),
+        ];
+        for (order_id, status) in payments {
+            dao.insert(PaymentEntity {
+                order_id: order_id.to_string(),
+                amount: "0x01".to_string(),
+                gas: "0x01".to_string(),
+                sender: "0xbabe000000000000000000000000000000000000".to_string(),
+                recipient: "0xcafe000000000000000000000000000000000000".to_string(),
+                payment_due_date: Utc::now().naive_utc() - chrono::Duration::days(1),
+                status,
+                tx_id: None,
+                network: Network::Rinkeby,
+            })
+            .await
+            .unwrap();
+        }
+        db
+    }
+
+    fn statuses(db: &DbExecutor) -> Vec<(String, i32)> {
+        dsl::payment
+            .select((dsl::order_id, dsl::status))
+            .order(dsl::order_id.asc())
+            .load(&db.conn().unwrap())
+            .unwrap()
+    }
+
+    #[actix_rt::test]
+    async fn test_requeue_selected() {
+        let dir = TempDir::new("driver-dao").unwrap();
+        let db = init_db(&dir).await;
+        let dao = db.as_dao::<PaymentDao>();
+
+        let ids = vec![
+            "failed-2".to_string(),
+            "pending".to_string(),
+            "paid".to_string(),
+        ];
+        assert_eq!(dao.requeue_failed(ids).await.unwrap(), 1);
+        assert_eq!(
+            statuses(&db),
+            vec![
+                ("failed-1".to_string(), PAYMENT_STATUS_FAILED),
+                ("failed-2".to_string(), PAYMENT_STATUS_NOT_YET),
+                ("paid".to_string(), PAYMENT_STATUS_OK),
+                ("pending".to_string(), PAYMENT_STATUS_NOT_YET),
+            ]
+        );
+
+        // Retry window restarts, so the payment isn't failed right away again.
+        let requeued: PaymentEntity = dsl::payment
+            .find("failed-2")
+            .first(&db.conn().unwrap())
+            .unwrap();
+        assert!(requeued.payment_due_date > Utc::now().naive_utc() - chrono::Duration::hours(1));
+        let pending: PaymentEntity = dsl::payment
+            .find("pending")
+            .first(&db.conn().unwrap())
+            .unwrap();
+        assert!(pending.payment_due_date < Utc::now().naive_utc() - chrono::Duration::hours(1));
+    }
+
+    #[actix_rt::test]
+    async fn test_requeue_all() {
+        let dir = TempDir::new("driver-dao").unwrap();
+        let db = init_db(&dir).await;
+        let dao = db.as_dao::<PaymentDao>();
+
+        assert_eq!(dao.requeue_failed(vec![]).await.unwrap(), 2);
+        assert_eq!(dao.count_by_status(PAYMENT_STATUS_FAILED).await.unwrap(), 0);
+        assert_eq!(
+            dao.count_by_status(PAYMENT_STATUS_NOT_YET).await.unwrap(),
+            3
+        );
+        assert_eq!(dao.count_by_status(PAYMENT_STATUS_OK).await.unwrap(), 1);
+
+        assert_eq!(dao.requeue_failed(vec![]).await.unwrap(), 0);
+    }
 }
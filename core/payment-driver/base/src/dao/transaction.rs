@@ -3,6 +3,7 @@
 */
 
 // External crates
+use chrono::NaiveDateTime;
 use diesel::{self, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
 
 // Workspace uses
@@ -96,4 +97,30 @@ impl<'c> TransactionDao<'c> {
         })
         .await
     }
+
+    pub async fn count_by_status(&self, status: TransactionStatus) -> DbResult<i64> {
+        let status: i32 = status.into();
+        readonly_transaction(self.pool, move |conn| {
+            let count: i64 = dsl::transaction
+                .filter(dsl::status.eq(status))
+                .count()
+                .get_result(conn)?;
+            Ok(count)
+        })
+        .await
+    }
+
+    pub async fn get_oldest_unconfirmed_timestamp(&self) -> DbResult<Option<NaiveDateTime>> {
+        readonly_transaction(self.pool, move |conn| {
+            let sent_status: i32 = TransactionStatus::Sent.into();
+            let timestamp: Option<NaiveDateTime> = dsl::transaction
+                .filter(dsl::status.eq(sent_status))
+                .select(dsl::timestamp)
+                .order(dsl::timestamp.asc())
+                .first(conn)
+                .optional()?;
+            Ok(timestamp)
+        })
+        .await
+    }
 }
@@ -88,4 +88,20 @@ pub trait PaymentDriver {
         caller: String,
         msg: ValidateAllocation,
     ) -> Result<bool, GenericError>;
+
+    /// See `health::DriverHealth::status`.
+    async fn get_status(
+        &self,
+        db: DbExecutor,
+        caller: String,
+        msg: GetDriverStatus,
+    ) -> Result<DriverStatus, GenericError>;
+
+    /// See `health::requeue_failed_payments`.
+    async fn requeue_failed_payments(
+        &self,
+        db: DbExecutor,
+        caller: String,
+        msg: RequeueFailedPayments,
+    ) -> Result<u64, GenericError>;
 }
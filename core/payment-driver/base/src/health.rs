@@ -0,0 +1,172 @@
+/*
+    Tracks driver's job runs and errors for `GetDriverStatus`.
+*/
+
+// External crates
+use chrono::{DateTime, TimeZone, Utc};
+use std::cell::RefCell;
+use std::fmt::Display;
+
+// Local uses
+use crate::dao::{payment::PaymentDao, transaction::TransactionDao, DbExecutor};
+use crate::db::models::{TransactionStatus, PAYMENT_STATUS_FAILED, PAYMENT_STATUS_NOT_YET};
+use crate::model::{DriverError, DriverStatus, GenericError};
+
+#[derive(Clone, Copy, Debug)]
+pub enum Job {
+    Payments,
+    Confirmations,
+}
+
+#[derive(Default)]
+struct JobHealth {
+    last_run: Option<DateTime<Utc>>,
+    last_error: Option<DriverError>,
+}
+
+#[derive(Default)]
+pub struct DriverHealth {
+    payments: RefCell<JobHealth>,
+    confirmations: RefCell<JobHealth>,
+}
+
+impl DriverHealth {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn job(&self, job: Job) -> &RefCell<JobHealth> {
+        match job {
+            Job::Payments => &self.payments,
+            Job::Confirmations => &self.confirmations,
+        }
+    }
+
+    /// Remembers failed call to the network (or other error worth reporting) of the job.
+    pub fn record_error(&self, job: Job, error: impl Display) {
+        self.job(job).borrow_mut().last_error = Some(DriverError {
+            message: error.to_string(),
+            timestamp: Utc::now(),
+        });
+    }
+
+    /// Counts the run as successful, when the job recorded no error since it started.
+    pub fn run_finished(&self, job: Job, started: DateTime<Utc>) {
+        let mut health = self.job(job).borrow_mut();
+        let failed = match &health.last_error {
+            Some(error) => error.timestamp >= started,
+            None => false,
+        };
+        if !failed {
+            health.last_run = Some(started);
+        }
+    }
+
+    /// The latest error of any job.
+    fn last_error(&self) -> Option<DriverError> {
+        let payments = self.payments.borrow().last_error.clone();
+        let confirmations = self.confirmations.borrow().last_error.clone();
+        match (payments, confirmations) {
+            (Some(p), Some(c)) if c.timestamp > p.timestamp => Some(c),
+            (Some(p), _) => Some(p),
+            (None, c) => c,
+        }
+    }
+
+    pub async fn status(
+        &self,
+        db: &DbExecutor,
+        driver: String,
+    ) -> Result<DriverStatus, GenericError> {
+        let payments: PaymentDao = db.as_dao();
+        let transactions: TransactionDao = db.as_dao();
+        let count = |n: i64| n as u64;
+        Ok(DriverStatus {
+            driver,
+            last_payment_run: self.payments.borrow().last_run,
+            last_confirmation_run: self.confirmations.borrow().last_run,
+            pending_payments: count(
+                payments
+                    .count_by_status(PAYMENT_STATUS_NOT_YET)
+                    .await
+                    .map_err(GenericError::new)?,
+            ),
+            failed_payments: count(
+                payments
+                    .count_by_status(PAYMENT_STATUS_FAILED)
+                    .await
+                    .map_err(GenericError::new)?,
+            ),
+            unconfirmed_txs: count(
+                transactions
+                    .count_by_status(TransactionStatus::Sent)
+                    .await
+                    .map_err(GenericError::new)?,
+            ),
+            failed_txs: count(
+                transactions
+                    .count_by_status(TransactionStatus::Failed)
+                    .await
+                    .map_err(GenericError::new)?,
+            ),
+            oldest_unconfirmed_tx: transactions
+                .get_oldest_unconfirmed_timestamp()
+                .await
+                .map_err(GenericError::new)?
+                .map(|timestamp| Utc.from_utc_datetime(&timestamp)),
+            last_error: self.last_error(),
+        })
+    }
+}
+
+pub async fn requeue_failed_payments(
+    db: &DbExecutor,
+    order_ids: Vec<String>,
+) -> Result<u64, GenericError> {
+    let requeued = db
+        .as_dao::<PaymentDao>()
+        .requeue_failed(order_ids)
+        .await
+        .map_err(GenericError::new)?;
+    log::info!("Requeued {} failed payments", requeued);
+    Ok(requeued as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_errors_of_other_job_ignored() {
+        let health = DriverHealth::new();
+        let started = Utc::now() - Duration::seconds(1);
+
+        health.record_error(Job::Payments, "Insufficient funds");
+        health.run_finished(Job::Confirmations, started);
+        health.run_finished(Job::Payments, started);
+        assert_eq!(health.confirmations.borrow().last_run, Some(started));
+        assert_eq!(health.payments.borrow().last_run, None);
+
+        // Error recorded before the run doesn't fail it.
+        let started = Utc::now() + Duration::seconds(1);
+        health.run_finished(Job::Payments, started);
+        assert_eq!(health.payments.borrow().last_run, Some(started));
+    }
+
+    #[test]
+    fn test_latest_error_reported() {
+        let health = DriverHealth::new();
+        assert!(health.last_error().is_none());
+
+        health.record_error(Job::Confirmations, "Transaction reverted");
+        health.record_error(Job::Payments, "Insufficient funds");
+        let error = health.last_error().unwrap();
+        assert_eq!(error.message, "Insufficient funds");
+
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        health.record_error(Job::Confirmations, "Transaction dropped");
+        let error = health.last_error().unwrap();
+        assert_eq!(error.message, "Transaction dropped");
+    }
+}
@@ -15,6 +15,7 @@ pub mod cron;
 pub mod dao;
 pub mod db;
 pub mod driver;
+pub mod health;
 pub mod utils;
 
 pub use ya_core_model::driver as model;
@@ -0,0 +1,3 @@
+DROP INDEX `sim_transaction_status_idx`;
+DROP TABLE `sim_transaction`;
+DROP TABLE `sim_account`;
@@ -0,0 +1,24 @@
+CREATE TABLE `sim_account`
+(
+	address VARCHAR(50) NOT NULL PRIMARY KEY,
+	-- Decimal amount of tokens
+	balance VARCHAR(64) NOT NULL,
+	nonce INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE `sim_transaction`
+(
+	tx_hash VARCHAR(64) NOT NULL PRIMARY KEY,
+	sender VARCHAR(50) NOT NULL,
+	recipient VARCHAR(50) NOT NULL,
+	-- Decimal amounts of tokens
+	amount VARCHAR(64) NOT NULL,
+	fee VARCHAR(64) NOT NULL,
+	nonce INTEGER NOT NULL,
+	submitted_at DATETIME NOT NULL,
+	confirm_at DATETIME NOT NULL,
+	-- 1 pending, 2 confirmed, 3 reverted, 4 dropped
+	status INTEGER NOT NULL
+);
+
+CREATE INDEX `sim_transaction_status_idx` ON `sim_transaction` (status, confirm_at);
@@ -0,0 +1,116 @@
+/*
+    Random events of the simulated chain.
+*/
+
+// External crates
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::RefCell;
+
+// Local uses
+use crate::config::SimConfig;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    Confirmed,
+    Reverted,
+    Dropped,
+}
+
+pub struct Chain {
+    config: SimConfig,
+    rng: RefCell<StdRng>,
+}
+
+impl Chain {
+    pub fn new(config: SimConfig) -> Self {
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Self {
+            config,
+            rng: RefCell::new(rng),
+        }
+    }
+
+    pub fn config(&self) -> &SimConfig {
+        &self.config
+    }
+
+    fn roll(&self, probability: f64) -> bool {
+        probability > 0.0 && self.rng.borrow_mut().gen_bool(probability)
+    }
+
+    pub fn nonce_conflict(&self) -> bool {
+        self.roll(self.config.nonce_conflict_probability)
+    }
+
+    /// Decided when confirmation delay passes, so dropped transactions stay pending till then.
+    pub fn outcome(&self) -> Outcome {
+        if self.roll(self.config.drop_probability) {
+            Outcome::Dropped
+        } else if self.roll(self.config.revert_probability) {
+            Outcome::Reverted
+        } else {
+            Outcome::Confirmed
+        }
+    }
+
+    pub fn new_tx_hash(&self) -> String {
+        let hash: [u8; 32] = self.rng.borrow_mut().gen();
+        hex::encode(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_config;
+
+    fn outcomes(config: SimConfig, count: usize) -> Vec<Outcome> {
+        let chain = Chain::new(config);
+        (0..count).map(|_| chain.outcome()).collect()
+    }
+
+    #[test]
+    fn test_outcome_without_failures() {
+        let outcomes = outcomes(test_config(), 100);
+        assert!(outcomes.iter().all(|o| *o == Outcome::Confirmed));
+    }
+
+    #[test]
+    fn test_outcome_certain_failures() {
+        let config = SimConfig {
+            drop_probability: 1.0,
+            ..test_config()
+        };
+        assert!(outcomes(config, 10).iter().all(|o| *o == Outcome::Dropped));
+
+        let config = SimConfig {
+            revert_probability: 1.0,
+            ..test_config()
+        };
+        assert!(outcomes(config, 10).iter().all(|o| *o == Outcome::Reverted));
+    }
+
+    #[test]
+    fn test_outcome_seeded() {
+        let config = SimConfig {
+            drop_probability: 0.3,
+            revert_probability: 0.3,
+            ..test_config()
+        };
+        let first = outcomes(config.clone(), 100);
+        assert_eq!(first, outcomes(config.clone(), 100));
+        for outcome in &[Outcome::Confirmed, Outcome::Reverted, Outcome::Dropped] {
+            assert!(first.contains(outcome));
+        }
+
+        let other_seed = SimConfig {
+            seed: Some(7),
+            ..config
+        };
+        assert_ne!(first, outcomes(other_seed, 100));
+    }
+}
@@ -0,0 +1,87 @@
+/*
+    Simulation parameters, read from the environment.
+*/
+
+// External crates
+use anyhow::{anyhow, Context};
+use bigdecimal::BigDecimal;
+use std::env;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::Duration;
+
+#[derive(Clone, Debug)]
+pub struct SimConfig {
+    /// Time between submitting a transaction and its confirmation.
+    pub confirmation_delay: Duration,
+    /// Fee charged from sender for every mined transaction, also reverted ones.
+    pub fee: BigDecimal,
+    /// Amount credited by `yagna payment fund`.
+    pub faucet_amount: BigDecimal,
+    /// Chance, that submission fails on nonce conflict and has to be retried.
+    pub nonce_conflict_probability: f64,
+    /// Chance, that submitted transaction is dropped from the mempool.
+    /// Its payments are submitted again.
+    pub drop_probability: f64,
+    /// Chance, that mined transaction is reverted. Fee is charged, payments fail.
+    pub revert_probability: f64,
+    /// Seed for reproducible runs.
+    pub seed: Option<u64>,
+    /// Database of the simulated chain. Nodes pointing at the same file can verify
+    /// each other's payments. Defaults to the driver's own database.
+    pub chain_db: Option<String>,
+}
+
+impl SimConfig {
+    pub fn from_env() -> anyhow::Result<Self> {
+        Ok(Self {
+            confirmation_delay: humantime::parse_duration(&var_or("SIM_CONFIRMATION_DELAY", "10s"))
+                .context("SIM_CONFIRMATION_DELAY")?,
+            fee: parse("SIM_FEE", "0.0001")?,
+            faucet_amount: parse("SIM_FAUCET_AMOUNT", "1000")?,
+            nonce_conflict_probability: probability("SIM_NONCE_CONFLICT_PROBABILITY")?,
+            drop_probability: probability("SIM_DROP_PROBABILITY")?,
+            revert_probability: probability("SIM_REVERT_PROBABILITY")?,
+            seed: match env::var("SIM_SEED") {
+                Ok(seed) => Some(seed.parse().context("SIM_SEED")?),
+                Err(_) => None,
+            },
+            chain_db: env::var("SIM_CHAIN_DB").ok(),
+        })
+    }
+}
+
+fn var_or(name: &str, default: &str) -> String {
+    env::var(name).unwrap_or_else(|_| default.to_string())
+}
+
+fn parse<T: FromStr>(name: &str, default: &str) -> anyhow::Result<T>
+where
+    T::Err: Display,
+{
+    var_or(name, default)
+        .parse()
+        .map_err(|e| anyhow!("Invalid {}: {}", name, e))
+}
+
+fn probability(name: &str) -> anyhow::Result<f64> {
+    let p: f64 = parse(name, "0")?;
+    if !(0.0..=1.0).contains(&p) {
+        anyhow::bail!("Invalid {}: {} is not within [0, 1]", name, p);
+    }
+    Ok(p)
+}
+
+#[cfg(test)]
+pub(crate) fn test_config() -> SimConfig {
+    SimConfig {
+        confirmation_delay: Duration::from_secs(0),
+        fee: "0.0001".parse().unwrap(),
+        faucet_amount: "1000".parse().unwrap(),
+        nonce_conflict_probability: 0.0,
+        drop_probability: 0.0,
+        revert_probability: 0.0,
+        seed: Some(42),
+        chain_db: None,
+    }
+}
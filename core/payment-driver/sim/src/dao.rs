@@ -0,0 +1,490 @@
+/*
+    Database Access Object, all you need to interact with the database.
+*/
+
+// Extrernal crates
+use bigdecimal::{BigDecimal, Zero};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use diesel::{self, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use uuid::Uuid;
+
+// Workspace uses
+use ya_payment_driver::{
+    dao::{payment::PaymentDao, transaction::TransactionDao, DbError, DbExecutor, DbResult},
+    db::models::{
+        PaymentEntity, TransactionEntity, TransactionStatus, PAYMENT_STATUS_FAILED,
+        PAYMENT_STATUS_NOT_YET, TX_CREATED,
+    },
+    model::{GenericError, PaymentDetails, SchedulePayment},
+    utils,
+};
+use ya_persistence::executor::{do_with_transaction, readonly_transaction, ConnType};
+
+// Local uses
+use crate::chain::Outcome;
+use crate::db::{
+    models::{
+        AccountEntity, SimTransactionEntity, SIM_TX_CONFIRMED, SIM_TX_DROPPED, SIM_TX_PENDING,
+        SIM_TX_REVERTED,
+    },
+    schema::{sim_account::dsl as account_dsl, sim_transaction::dsl as tx_dsl},
+};
+use crate::network::DB_NETWORK;
+
+/// Orders live in node's database, while simulated chain state can be shared
+/// by all nodes of a local network.
+pub struct SimDao {
+    db: DbExecutor,
+    chain_db: DbExecutor,
+}
+
+fn parse_amount(amount: &str) -> DbResult<BigDecimal> {
+    amount
+        .parse()
+        .map_err(|e| DbError::InvalidData(format!("Invalid amount {}: {}", amount, e)))
+}
+
+fn get_account(address: &str, conn: &ConnType) -> DbResult<Option<AccountEntity>> {
+    Ok(account_dsl::sim_account
+        .find(address)
+        .first(conn)
+        .optional()?)
+}
+
+fn get_balance(address: &str, conn: &ConnType) -> DbResult<BigDecimal> {
+    match get_account(address, conn)? {
+        Some(account) => parse_amount(&account.balance),
+        None => Ok(BigDecimal::zero()),
+    }
+}
+
+fn set_balance(address: &str, balance: &BigDecimal, conn: &ConnType) -> DbResult<()> {
+    let updated = diesel::update(account_dsl::sim_account.find(address))
+        .set(account_dsl::balance.eq(balance.to_string()))
+        .execute(conn)?;
+    if updated == 0 {
+        diesel::insert_into(account_dsl::sim_account)
+            .values(AccountEntity {
+                address: address.to_string(),
+                balance: balance.to_string(),
+                nonce: 0,
+            })
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+impl SimDao {
+    pub fn new(db: DbExecutor, chain_db: DbExecutor) -> Self {
+        Self { db, chain_db }
+    }
+
+    fn payment(&self) -> PaymentDao {
+        self.db.as_dao::<PaymentDao>()
+    }
+
+    fn transaction(&self) -> TransactionDao {
+        self.db.as_dao::<TransactionDao>()
+    }
+
+    pub async fn get_pending_payments(&self, node_id: &str) -> Vec<PaymentEntity> {
+        match self
+            .payment()
+            .get_pending_payments(node_id.to_string(), DB_NETWORK)
+            .await
+        {
+            Ok(payments) => payments,
+            Err(e) => {
+                log::error!(
+                    "Failed to fetch pending payments for {:?} : {:?}",
+                    node_id,
+                    e
+                );
+                vec![]
+            }
+        }
+    }
+
+    pub async fn insert_payment(
+        &self,
+        order_id: &str,
+        msg: &SchedulePayment,
+        fee: &BigDecimal,
+    ) -> Result<(), GenericError> {
+        let payment = PaymentEntity {
+            amount: utils::u256_to_big_endian_hex(utils::big_dec_to_u256(msg.amount())),
+            gas: utils::u256_to_big_endian_hex(utils::big_dec_to_u256(fee.clone())),
+            order_id: order_id.to_string(),
+            payment_due_date: msg.due_date().naive_utc(),
+            sender: msg.sender(),
+            recipient: msg.recipient(),
+            status: PAYMENT_STATUS_NOT_YET,
+            tx_id: None,
+            network: DB_NETWORK,
+        };
+        if let Err(e) = self.payment().insert(payment).await {
+            log::error!(
+                "Failed to store transaction for {:?} , msg={:?}, err={:?}",
+                order_id,
+                msg,
+                e
+            );
+            return Err(GenericError::new(e));
+        }
+        Ok(())
+    }
+
+    pub async fn insert_transaction(
+        &self,
+        details: &PaymentDetails,
+        nonce: i32,
+        date: DateTime<Utc>,
+    ) -> String {
+        let tx_id = Uuid::new_v4().to_string();
+        let tx = TransactionEntity {
+            tx_id: tx_id.clone(),
+            sender: details.sender.clone(),
+            nonce: nonce.to_string(),
+            status: TX_CREATED,
+            timestamp: date.naive_utc(),
+            tx_type: 1, // Transfer
+            encoded: "".to_string(),
+            signature: "".to_string(),
+            tx_hash: None,
+        };
+        if let Err(e) = self.transaction().insert_transactions(vec![tx]).await {
+            log::error!("Failed to store transaction for {:?} : {:?}", details, e)
+        }
+        tx_id
+    }
+
+    pub async fn transaction_sent(&self, tx_id: &str, tx_hash: &str, order_id: &str) {
+        if let Err(e) = self
+            .payment()
+            .update_tx_id(order_id.to_string(), tx_id.to_string())
+            .await
+        {
+            log::error!("Failed to update for transaction {:?} : {:?}", tx_id, e)
+        }
+        if let Err(e) = self
+            .transaction()
+            .update_tx_sent(tx_id.to_string(), tx_hash.to_string())
+            .await
+        {
+            log::error!("Failed to update for transaction {:?} : {:?}", tx_id, e)
+        }
+    }
+
+    pub async fn transaction_confirmed(&self, tx_id: &str) -> Vec<PaymentEntity> {
+        if let Err(e) = self
+            .transaction()
+            .update_tx_status(tx_id.to_string(), TransactionStatus::Confirmed)
+            .await
+        {
+            log::error!("Failed to update tx status for {:?} : {:?}", tx_id, e)
+        }
+        self.get_payments(tx_id).await
+    }
+
+    pub async fn transaction_failed(&self, tx_id: &str) -> Vec<PaymentEntity> {
+        if let Err(e) = self
+            .transaction()
+            .update_tx_status(tx_id.to_string(), TransactionStatus::Failed)
+            .await
+        {
+            log::error!("Failed to update tx status for {:?} : {:?}", tx_id, e)
+        }
+        self.get_payments(tx_id).await
+    }
+
+    async fn get_payments(&self, tx_id: &str) -> Vec<PaymentEntity> {
+        match self.payment().get_by_tx_id(tx_id.to_string()).await {
+            Ok(payments) => payments,
+            Err(e) => {
+                log::error!("Failed to fetch `payments` for tx {:?} : {:?}", tx_id, e);
+                vec![]
+            }
+        }
+    }
+
+    pub async fn payment_failed(&self, order_id: &str) {
+        self.set_payment_status(order_id, PAYMENT_STATUS_FAILED)
+            .await
+    }
+
+    /// Makes payment pending again, so the payment job submits it in a new transaction.
+    pub async fn payment_retry(&self, order_id: &str) {
+        self.set_payment_status(order_id, PAYMENT_STATUS_NOT_YET)
+            .await
+    }
+
+    async fn set_payment_status(&self, order_id: &str, status: i32) {
+        if let Err(e) = self
+            .payment()
+            .update_status(order_id.to_string(), status)
+            .await
+        {
+            log::error!(
+                "Failed to update status of payment {:?} : {:?}",
+                order_id,
+                e
+            )
+        }
+    }
+
+    pub async fn get_unconfirmed_txs(&self) -> Vec<TransactionEntity> {
+        match self.transaction().get_unconfirmed_txs().await {
+            Ok(txs) => txs,
+            Err(e) => {
+                log::error!("Failed to fetch unconfirmed transactions : {:?}", e);
+                vec![]
+            }
+        }
+    }
+
+    pub async fn get_balance(&self, address: &str) -> DbResult<BigDecimal> {
+        let address = address.to_lowercase();
+        readonly_transaction(&self.chain_db.pool, move |conn| get_balance(&address, conn)).await
+    }
+
+    pub async fn fund(&self, address: &str, amount: BigDecimal) -> DbResult<BigDecimal> {
+        let address = address.to_lowercase();
+        do_with_transaction(&self.chain_db.pool, move |conn| {
+            let balance = get_balance(&address, conn)? + amount;
+            set_balance(&address, &balance, conn)?;
+            Ok(balance)
+        })
+        .await
+    }
+
+    /// Stores pending transaction with next nonce of the sender. Returns the nonce.
+    pub async fn submit(
+        &self,
+        tx_hash: &str,
+        details: &PaymentDetails,
+        fee: &BigDecimal,
+        confirm_at: NaiveDateTime,
+    ) -> DbResult<i32> {
+        let sender = details.sender.to_lowercase();
+        let tx_hash = tx_hash.to_string();
+        let recipient = details.recipient.to_lowercase();
+        let amount = details.amount.to_string();
+        let fee = fee.to_string();
+        do_with_transaction(&self.chain_db.pool, move |conn| {
+            let nonce = match get_account(&sender, conn)? {
+                Some(account) => account.nonce,
+                None => {
+                    set_balance(&sender, &BigDecimal::zero(), conn)?;
+                    0
+                }
+            };
+            diesel::update(account_dsl::sim_account.find(&sender))
+                .set(account_dsl::nonce.eq(nonce + 1))
+                .execute(conn)?;
+            diesel::insert_into(tx_dsl::sim_transaction)
+                .values(SimTransactionEntity {
+                    tx_hash,
+                    sender,
+                    recipient,
+                    amount,
+                    fee,
+                    nonce,
+                    submitted_at: Utc::now().naive_utc(),
+                    confirm_at,
+                    status: SIM_TX_PENDING,
+                })
+                .execute(conn)?;
+            Ok(nonce)
+        })
+        .await
+    }
+
+    pub async fn get_sim_transaction(
+        &self,
+        tx_hash: &str,
+    ) -> DbResult<Option<SimTransactionEntity>> {
+        let tx_hash = tx_hash.to_string();
+        readonly_transaction(&self.chain_db.pool, move |conn| {
+            Ok(tx_dsl::sim_transaction
+                .find(tx_hash)
+                .first(conn)
+                .optional()?)
+        })
+        .await
+    }
+
+    /// Applies outcome of pending transaction to balances. Transactions, which sender
+    /// can't cover at this point, are reverted. Returns the applied outcome.
+    pub async fn settle(&self, tx_hash: &str, outcome: Outcome) -> DbResult<Outcome> {
+        let tx_hash = tx_hash.to_string();
+        do_with_transaction(&self.chain_db.pool, move |conn| {
+            let tx: SimTransactionEntity = tx_dsl::sim_transaction.find(&tx_hash).first(conn)?;
+            let amount = parse_amount(&tx.amount)?;
+            let fee = parse_amount(&tx.fee)?;
+            let sender_balance = get_balance(&tx.sender, conn)?;
+
+            let outcome = match outcome {
+                Outcome::Confirmed if sender_balance < &amount + &fee => Outcome::Reverted,
+                outcome => outcome,
+            };
+            let status = match outcome {
+                Outcome::Confirmed => {
+                    set_balance(&tx.sender, &(sender_balance - &amount - &fee), conn)?;
+                    let recipient_balance = get_balance(&tx.recipient, conn)?;
+                    set_balance(&tx.recipient, &(recipient_balance + &amount), conn)?;
+                    SIM_TX_CONFIRMED
+                }
+                Outcome::Reverted => {
+                    let fee = if sender_balance < fee {
+                        sender_balance.clone()
+                    } else {
+                        fee
+                    };
+                    set_balance(&tx.sender, &(sender_balance - fee), conn)?;
+                    SIM_TX_REVERTED
+                }
+                Outcome::Dropped => SIM_TX_DROPPED,
+            };
+            diesel::update(tx_dsl::sim_transaction.find(&tx_hash))
+                .set(tx_dsl::status.eq(status))
+                .execute(conn)?;
+            Ok(outcome)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    pub(crate) const SENDER: &str = "0xbabe000000000000000000000000000000000000";
+    pub(crate) const RECIPIENT: &str = "0xcafe000000000000000000000000000000000000";
+
+    pub(crate) async fn init_db(dir: &TempDir) -> DbExecutor {
+        let db = DbExecutor::from_data_dir(dir.path(), "sim").unwrap();
+        ya_payment_driver::dao::init(&db).await.unwrap();
+        db.apply_migration(crate::db::migrations::run_with_output)
+            .unwrap();
+        db
+    }
+
+    fn amount(amount: &str) -> BigDecimal {
+        amount.parse().unwrap()
+    }
+
+    async fn submit(dao: &SimDao, tx_hash: &str, value: &str) -> i32 {
+        let details = PaymentDetails {
+            sender: SENDER.to_string(),
+            recipient: RECIPIENT.to_string(),
+            amount: amount(value),
+            date: None,
+        };
+        dao.submit(tx_hash, &details, &amount("0.0001"), Utc::now().naive_utc())
+            .await
+            .unwrap()
+    }
+
+    async fn status(dao: &SimDao, tx_hash: &str) -> i32 {
+        dao.get_sim_transaction(tx_hash)
+            .await
+            .unwrap()
+            .unwrap()
+            .status
+    }
+
+    #[actix_rt::test]
+    async fn test_fund() {
+        let dir = TempDir::new("sim-dao").unwrap();
+        let db = init_db(&dir).await;
+        let dao = SimDao::new(db.clone(), db);
+
+        assert_eq!(dao.get_balance(SENDER).await.unwrap(), amount("0"));
+        assert_eq!(dao.fund(SENDER, amount("10")).await.unwrap(), amount("10"));
+        assert_eq!(
+            dao.fund(&SENDER.to_uppercase(), amount("2.5"))
+                .await
+                .unwrap(),
+            amount("12.5")
+        );
+        assert_eq!(dao.get_balance(SENDER).await.unwrap(), amount("12.5"));
+        assert_eq!(dao.get_balance(RECIPIENT).await.unwrap(), amount("0"));
+    }
+
+    #[actix_rt::test]
+    async fn test_settle_confirmed() {
+        let dir = TempDir::new("sim-dao").unwrap();
+        let db = init_db(&dir).await;
+        let dao = SimDao::new(db.clone(), db);
+        dao.fund(SENDER, amount("10")).await.unwrap();
+
+        assert_eq!(submit(&dao, "tx-1", "1").await, 0);
+        assert_eq!(status(&dao, "tx-1").await, SIM_TX_PENDING);
+        let outcome = dao.settle("tx-1", Outcome::Confirmed).await.unwrap();
+        assert_eq!(outcome, Outcome::Confirmed);
+        assert_eq!(status(&dao, "tx-1").await, SIM_TX_CONFIRMED);
+        assert_eq!(dao.get_balance(SENDER).await.unwrap(), amount("8.9999"));
+        assert_eq!(dao.get_balance(RECIPIENT).await.unwrap(), amount("1"));
+    }
+
+    #[actix_rt::test]
+    async fn test_settle_insufficient_funds() {
+        let dir = TempDir::new("sim-dao").unwrap();
+        let db = init_db(&dir).await;
+        let dao = SimDao::new(db.clone(), db);
+        dao.fund(SENDER, amount("1")).await.unwrap();
+
+        // Amount is covered, but fee isn't.
+        submit(&dao, "tx-1", "1").await;
+        let outcome = dao.settle("tx-1", Outcome::Confirmed).await.unwrap();
+        assert_eq!(outcome, Outcome::Reverted);
+        assert_eq!(status(&dao, "tx-1").await, SIM_TX_REVERTED);
+        assert_eq!(dao.get_balance(SENDER).await.unwrap(), amount("0.9999"));
+        assert_eq!(dao.get_balance(RECIPIENT).await.unwrap(), amount("0"));
+    }
+
+    #[actix_rt::test]
+    async fn test_settle_reverted_charges_fee() {
+        let dir = TempDir::new("sim-dao").unwrap();
+        let db = init_db(&dir).await;
+        let dao = SimDao::new(db.clone(), db);
+        dao.fund(SENDER, amount("10")).await.unwrap();
+
+        submit(&dao, "tx-1", "1").await;
+        let outcome = dao.settle("tx-1", Outcome::Reverted).await.unwrap();
+        assert_eq!(outcome, Outcome::Reverted);
+        assert_eq!(dao.get_balance(SENDER).await.unwrap(), amount("9.9999"));
+        assert_eq!(dao.get_balance(RECIPIENT).await.unwrap(), amount("0"));
+    }
+
+    #[actix_rt::test]
+    async fn test_settle_reverted_fee_capped_at_balance() {
+        let dir = TempDir::new("sim-dao").unwrap();
+        let db = init_db(&dir).await;
+        let dao = SimDao::new(db.clone(), db);
+        dao.fund(SENDER, amount("0.00004")).await.unwrap();
+
+        submit(&dao, "tx-1", "1").await;
+        let outcome = dao.settle("tx-1", Outcome::Reverted).await.unwrap();
+        assert_eq!(outcome, Outcome::Reverted);
+        assert_eq!(dao.get_balance(SENDER).await.unwrap(), amount("0"));
+    }
+
+    #[actix_rt::test]
+    async fn test_settle_dropped() {
+        let dir = TempDir::new("sim-dao").unwrap();
+        let db = init_db(&dir).await;
+        let dao = SimDao::new(db.clone(), db);
+        dao.fund(SENDER, amount("10")).await.unwrap();
+
+        assert_eq!(submit(&dao, "tx-1", "1").await, 0);
+        let outcome = dao.settle("tx-1", Outcome::Dropped).await.unwrap();
+        assert_eq!(outcome, Outcome::Dropped);
+        assert_eq!(status(&dao, "tx-1").await, SIM_TX_DROPPED);
+        assert_eq!(dao.get_balance(SENDER).await.unwrap(), amount("10"));
+
+        // Nonce isn't reused by the next submission.
+        assert_eq!(submit(&dao, "tx-2", "1").await, 1);
+    }
+}
@@ -0,0 +1,11 @@
+/*
+    Simulated chain state, kept next to the base driver tables.
+*/
+
+pub mod migrations {
+    #[derive(diesel_migrations::EmbedMigrations)]
+    struct _Dummy;
+}
+
+pub mod models;
+pub mod schema;
@@ -0,0 +1,38 @@
+/*
+    Raw database models of the simulated chain.
+*/
+
+// External crates
+use chrono::NaiveDateTime;
+
+// Local uses
+use crate::db::schema::*;
+
+pub const SIM_TX_PENDING: i32 = 1;
+pub const SIM_TX_CONFIRMED: i32 = 2;
+pub const SIM_TX_REVERTED: i32 = 3;
+pub const SIM_TX_DROPPED: i32 = 4;
+
+#[derive(Queryable, Clone, Debug, Identifiable, Insertable, PartialEq)]
+#[primary_key(address)]
+#[table_name = "sim_account"]
+pub struct AccountEntity {
+    pub address: String,
+    pub balance: String,
+    pub nonce: i32,
+}
+
+#[derive(Queryable, Clone, Debug, Identifiable, Insertable, PartialEq)]
+#[primary_key(tx_hash)]
+#[table_name = "sim_transaction"]
+pub struct SimTransactionEntity {
+    pub tx_hash: String,
+    pub sender: String,
+    pub recipient: String,
+    pub amount: String,
+    pub fee: String,
+    pub nonce: i32,
+    pub submitted_at: NaiveDateTime,
+    pub confirm_at: NaiveDateTime,
+    pub status: i32,
+}
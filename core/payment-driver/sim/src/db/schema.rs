@@ -0,0 +1,23 @@
+table! {
+    sim_account (address) {
+        address -> Text,
+        balance -> Text,
+        nonce -> Integer,
+    }
+}
+
+table! {
+    sim_transaction (tx_hash) {
+        tx_hash -> Text,
+        sender -> Text,
+        recipient -> Text,
+        amount -> Text,
+        fee -> Text,
+        nonce -> Integer,
+        submitted_at -> Timestamp,
+        confirm_at -> Timestamp,
+        status -> Integer,
+    }
+}
+
+allow_tables_to_appear_in_same_query!(sim_account, sim_transaction,);
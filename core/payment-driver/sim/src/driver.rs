@@ -0,0 +1,520 @@
+/*
+    SimDriver to handle payments on the simulated chain.
+
+    Please limit the logic in this file, use local mods to handle the calls.
+*/
+// Extrnal crates
+use chrono::{Duration, TimeZone, Utc};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+// Workspace uses
+use ya_payment_driver::{
+    account::{Accounts, AccountsRc},
+    bus,
+    cron::PaymentDriverCron,
+    dao::DbExecutor,
+    db::models::{PaymentEntity, TransactionEntity},
+    driver::{async_trait, BigDecimal, IdentityError, IdentityEvent, Network, PaymentDriver},
+    health::{requeue_failed_payments, DriverHealth, Job},
+    model::*,
+    utils,
+};
+
+// Local uses
+use crate::{
+    chain::{Chain, Outcome},
+    config::SimConfig,
+    dao::SimDao,
+    db::models::SIM_TX_CONFIRMED,
+    network::{check_network, check_platform, SUPPORTED_NETWORKS},
+    DEFAULT_NETWORK, DEFAULT_PLATFORM, DEFAULT_TOKEN, DRIVER_NAME,
+};
+
+lazy_static! {
+    static ref TX_SUMBIT_TIMEOUT: Duration = Duration::minutes(15);
+}
+
+pub struct SimDriver {
+    active_accounts: AccountsRc,
+    health: DriverHealth,
+    dao: SimDao,
+    chain: Chain,
+}
+
+impl SimDriver {
+    pub fn new(db: DbExecutor, chain_db: DbExecutor, config: SimConfig) -> Self {
+        Self {
+            active_accounts: Accounts::new_rc(),
+            health: DriverHealth::new(),
+            dao: SimDao::new(db, chain_db),
+            chain: Chain::new(config),
+        }
+    }
+
+    pub async fn load_active_accounts(&self) {
+        log::debug!("load_active_accounts");
+        let mut accounts = self.active_accounts.borrow_mut();
+        let unlocked_accounts = bus::list_unlocked_identities().await.unwrap();
+        for account in unlocked_accounts {
+            log::debug!("account={}", account);
+            accounts.add_account(account)
+        }
+    }
+
+    fn is_account_active(&self, address: &str) -> bool {
+        self.active_accounts
+            .as_ref()
+            .borrow()
+            .get_node_id(address)
+            .is_some()
+    }
+
+    async fn process_payments_for_account(&self, node_id: &str) {
+        log::trace!("Processing payments for node_id={}", node_id);
+        let payments = self.dao.get_pending_payments(node_id).await;
+        if !payments.is_empty() {
+            log::info!(
+                "Processing payments. count={}, node_id={}",
+                payments.len(),
+                node_id
+            );
+        }
+        for payment in payments {
+            self.handle_payment(payment).await;
+        }
+    }
+
+    async fn handle_payment(&self, payment: PaymentEntity) {
+        let details = utils::db_to_payment_details(&payment);
+        let fee = self.chain.config().fee.clone();
+
+        let error = match self.dao.get_balance(&details.sender).await {
+            Err(e) => e.to_string(),
+            Ok(_) if self.chain.nonce_conflict() => "Nonce conflict".to_string(),
+            Ok(balance) if balance < &details.amount + &fee => format!(
+                "Insufficient funds. balance={}, needed={}",
+                balance,
+                &details.amount + &fee
+            ),
+            Ok(_) => {
+                let tx_hash = self.chain.new_tx_hash();
+                let confirm_at = Utc::now()
+                    + Duration::from_std(self.chain.config().confirmation_delay)
+                        .unwrap_or_else(|_| Duration::zero());
+                match self
+                    .dao
+                    .submit(&tx_hash, &details, &fee, confirm_at.naive_utc())
+                    .await
+                {
+                    Ok(nonce) => {
+                        let tx_id = self
+                            .dao
+                            .insert_transaction(&details, nonce, Utc::now())
+                            .await;
+                        self.dao
+                            .transaction_sent(&tx_id, &tx_hash, &payment.order_id)
+                            .await;
+                        log::info!(
+                            "Submitted simulated transaction. tx_hash={}, nonce={}, details={:?}",
+                            tx_hash,
+                            nonce,
+                            details
+                        );
+                        return;
+                    }
+                    Err(e) => e.to_string(),
+                }
+            }
+        };
+
+        self.health.record_error(Job::Payments, &error);
+        let deadline = Utc.from_utc_datetime(&payment.payment_due_date) + *TX_SUMBIT_TIMEOUT;
+        if Utc::now() > deadline {
+            log::error!("Failed to submit simulated transaction. Retry deadline reached. details={:?} error={}", payment, error);
+            self.dao.payment_failed(&payment.order_id).await;
+        } else {
+            log::warn!(
+                "Failed to submit simulated transaction. Payment will be retried until {}. details={:?} error={}",
+                deadline, payment, error
+            );
+        }
+    }
+
+    async fn confirm_transaction(&self, tx: TransactionEntity) {
+        let tx_hash = match &tx.tx_hash {
+            None => return,
+            Some(tx_hash) => tx_hash.clone(),
+        };
+        let sim_tx = match self.dao.get_sim_transaction(&tx_hash).await {
+            Ok(Some(sim_tx)) => sim_tx,
+            Ok(None) => return,
+            Err(e) => {
+                self.health.record_error(Job::Confirmations, &e);
+                log::error!("Failed to fetch simulated transaction {}: {}", tx_hash, e);
+                return;
+            }
+        };
+        if Utc::now().naive_utc() < sim_tx.confirm_at {
+            return;
+        }
+
+        let outcome = match self.dao.settle(&tx_hash, self.chain.outcome()).await {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                self.health.record_error(Job::Confirmations, &e);
+                log::error!("Failed to settle simulated transaction {}: {}", tx_hash, e);
+                return;
+            }
+        };
+        match outcome {
+            Outcome::Confirmed => {
+                let payments = self.dao.transaction_confirmed(&tx.tx_id).await;
+                let order_ids = payments.iter().map(|p| p.order_id.clone()).collect();
+                let details = PaymentDetails {
+                    sender: sim_tx.sender,
+                    recipient: sim_tx.recipient,
+                    amount: payments
+                        .into_iter()
+                        .map(|payment| utils::db_amount_to_big_dec(payment.amount))
+                        .sum(),
+                    date: Some(Utc::now()),
+                };
+                log::info!("Simulated transaction confirmed. tx_hash={}", tx_hash);
+                let tx_hash = hex::decode(&tx_hash).unwrap();
+                if let Err(e) =
+                    bus::notify_payment(DRIVER_NAME, DEFAULT_PLATFORM, order_ids, &details, tx_hash)
+                        .await
+                {
+                    self.health.record_error(Job::Confirmations, &e);
+                    log::error!("{}", e)
+                };
+            }
+            Outcome::Reverted => {
+                self.health.record_error(
+                    Job::Confirmations,
+                    format!("Transaction reverted. tx_hash={}", tx_hash),
+                );
+                log::error!("Simulated transaction reverted. tx_hash={}", tx_hash);
+                for payment in self.dao.transaction_failed(&tx.tx_id).await {
+                    self.dao.payment_failed(&payment.order_id).await;
+                }
+            }
+            Outcome::Dropped => {
+                log::warn!(
+                    "Simulated transaction dropped, resubmitting. tx_hash={}",
+                    tx_hash
+                );
+                for payment in self.dao.transaction_failed(&tx.tx_id).await {
+                    self.dao.payment_retry(&payment.order_id).await;
+                }
+            }
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl PaymentDriver for SimDriver {
+    async fn account_event(
+        &self,
+        _db: DbExecutor,
+        _caller: String,
+        msg: IdentityEvent,
+    ) -> Result<(), IdentityError> {
+        self.active_accounts.borrow_mut().handle_event(msg);
+        Ok(())
+    }
+
+    async fn enter(
+        &self,
+        _db: DbExecutor,
+        _caller: String,
+        msg: Enter,
+    ) -> Result<String, GenericError> {
+        log::info!("ENTER = Not Implemented: {:?}", msg);
+        Ok("NOT_IMPLEMENTED".to_string())
+    }
+
+    async fn exit(
+        &self,
+        _db: DbExecutor,
+        _caller: String,
+        msg: Exit,
+    ) -> Result<String, GenericError> {
+        log::info!("EXIT = Not Implemented: {:?}", msg);
+        Ok("NOT_IMPLEMENTED".to_string())
+    }
+
+    async fn get_account_balance(
+        &self,
+        _db: DbExecutor,
+        _caller: String,
+        msg: GetAccountBalance,
+    ) -> Result<BigDecimal, GenericError> {
+        log::debug!("get_account_balance: {:?}", msg);
+        check_platform(&msg.platform())?;
+        self.dao
+            .get_balance(&msg.address())
+            .await
+            .map_err(GenericError::new)
+    }
+
+    fn get_name(&self) -> String {
+        DRIVER_NAME.to_string()
+    }
+
+    fn get_default_network(&self) -> String {
+        DEFAULT_NETWORK.to_string()
+    }
+
+    fn get_networks(&self) -> HashMap<String, Network> {
+        SUPPORTED_NETWORKS.clone()
+    }
+
+    fn recv_init_required(&self) -> bool {
+        false
+    }
+
+    async fn get_transaction_balance(
+        &self,
+        _db: DbExecutor,
+        _caller: String,
+        msg: GetTransactionBalance,
+    ) -> Result<BigDecimal, GenericError> {
+        log::debug!("get_transaction_balance: {:?}", msg);
+        // TODO: Get real transaction balance
+        Ok(BigDecimal::from(1_000_000_000_000_000_000u64))
+    }
+
+    async fn init(&self, _db: DbExecutor, _caller: String, msg: Init) -> Result<Ack, GenericError> {
+        log::debug!("init: {:?}", msg);
+        let address = msg.address().clone();
+        let network = check_network(msg.network())?;
+        let token = msg.token().unwrap_or(DEFAULT_TOKEN.to_string());
+        let mode = msg.mode();
+        bus::register_account(self, &address, &network, &token, mode).await?;
+
+        log::info!(
+            "Initialised payment account. mode={:?}, address={}, driver={}, network={}, token={}",
+            mode,
+            &address,
+            DRIVER_NAME,
+            network,
+            token
+        );
+        Ok(Ack {})
+    }
+
+    async fn fund(
+        &self,
+        _db: DbExecutor,
+        _caller: String,
+        msg: Fund,
+    ) -> Result<String, GenericError> {
+        let address = msg.address();
+        check_network(msg.network())?;
+        let amount = self.chain.config().faucet_amount.clone();
+        let balance = self
+            .dao
+            .fund(&address, amount.clone())
+            .await
+            .map_err(GenericError::new)?;
+        Ok(format!(
+            "Received {} {} from the simulated faucet. address={}, balance={}",
+            amount, DEFAULT_TOKEN, &address, balance
+        ))
+    }
+
+    async fn transfer(
+        &self,
+        _db: DbExecutor,
+        _caller: String,
+        msg: Transfer,
+    ) -> Result<String, GenericError> {
+        log::info!("TRANSFER = Not Implemented: {:?}", msg);
+        Ok("NOT_IMPLEMENTED".to_string())
+    }
+
+    async fn schedule_payment(
+        &self,
+        _db: DbExecutor,
+        _caller: String,
+        msg: SchedulePayment,
+    ) -> Result<String, GenericError> {
+        log::debug!("schedule_payment: {:?}", msg);
+        check_platform(&msg.platform())?;
+
+        let sender = msg.sender().to_owned();
+        if !self.is_account_active(&sender) {
+            return Err(GenericError::new(
+                "Can not schedule_payment, account not active",
+            ));
+        }
+
+        let order_id = Uuid::new_v4().to_string();
+        self.dao
+            .insert_payment(&order_id, &msg, &self.chain.config().fee)
+            .await?;
+        Ok(order_id)
+    }
+
+    async fn verify_payment(
+        &self,
+        _db: DbExecutor,
+        _caller: String,
+        msg: VerifyPayment,
+    ) -> Result<PaymentDetails, GenericError> {
+        log::debug!("verify_payment: {:?}", msg);
+        check_platform(&msg.platform())?;
+        let tx_hash = hex::encode(msg.confirmation().confirmation);
+        log::info!("Verifying transaction: {}", tx_hash);
+        let tx = match self.dao.get_sim_transaction(&tx_hash).await {
+            Ok(Some(tx)) if tx.status == SIM_TX_CONFIRMED => tx,
+            Ok(_) => {
+                return Err(GenericError::new(format!(
+                    "Transaction {} not confirmed",
+                    tx_hash
+                )))
+            }
+            Err(e) => return Err(GenericError::new(e)),
+        };
+        Ok(PaymentDetails {
+            sender: tx.sender,
+            recipient: tx.recipient,
+            amount: tx.amount.parse().map_err(GenericError::new)?,
+            date: Some(Utc.from_utc_datetime(&tx.confirm_at)),
+        })
+    }
+
+    async fn validate_allocation(
+        &self,
+        _db: DbExecutor,
+        _caller: String,
+        msg: ValidateAllocation,
+    ) -> Result<bool, GenericError> {
+        check_platform(&msg.platform)?;
+        let account_balance = self
+            .dao
+            .get_balance(&msg.address)
+            .await
+            .map_err(GenericError::new)?;
+        let total_allocated_amount: BigDecimal = msg
+            .existing_allocations
+            .into_iter()
+            .map(|allocation| allocation.remaining_amount)
+            .sum();
+        Ok(msg.amount <= (account_balance - total_allocated_amount))
+    }
+
+    async fn get_status(
+        &self,
+        db: DbExecutor,
+        _caller: String,
+        _msg: GetDriverStatus,
+    ) -> Result<DriverStatus, GenericError> {
+        self.health.status(&db, self.get_name()).await
+    }
+
+    async fn requeue_failed_payments(
+        &self,
+        db: DbExecutor,
+        _caller: String,
+        msg: RequeueFailedPayments,
+    ) -> Result<u64, GenericError> {
+        requeue_failed_payments(&db, msg.order_ids).await
+    }
+}
+
+#[async_trait(?Send)]
+impl PaymentDriverCron for SimDriver {
+    async fn confirm_payments(&self) {
+        let txs = self.dao.get_unconfirmed_txs().await;
+        log::trace!("confirm_payments {:?}", txs);
+        for tx in txs {
+            self.confirm_transaction(tx).await;
+        }
+    }
+
+    async fn process_payments(&self) {
+        for node_id in self.active_accounts.borrow().list_accounts() {
+            self.process_payments_for_account(&node_id).await;
+        }
+    }
+    fn health(&self) -> &DriverHealth {
+        &self.health
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+    use ya_payment_driver::db::models::{Network as DbNetwork, PAYMENT_STATUS_NOT_YET};
+
+    use crate::config::test_config;
+    use crate::dao::tests::{init_db, RECIPIENT, SENDER};
+    use crate::db::models::SIM_TX_DROPPED;
+
+    #[actix_rt::test]
+    async fn test_dropped_payment_resubmitted() {
+        let dir = TempDir::new("sim-driver").unwrap();
+        let db = init_db(&dir).await;
+        let config = SimConfig {
+            drop_probability: 1.0,
+            ..test_config()
+        };
+        let driver = SimDriver::new(db.clone(), db.clone(), config);
+        driver
+            .dao
+            .fund(SENDER, "10".parse().unwrap())
+            .await
+            .unwrap();
+
+        let amount: BigDecimal = "1".parse().unwrap();
+        let payment = PaymentEntity {
+            order_id: "order-1".to_string(),
+            amount: utils::u256_to_big_endian_hex(utils::big_dec_to_u256(amount)),
+            gas: utils::u256_to_big_endian_hex(utils::big_dec_to_u256(
+                driver.chain.config().fee.clone(),
+            )),
+            sender: SENDER.to_string(),
+            recipient: RECIPIENT.to_string(),
+            payment_due_date: Utc::now().naive_utc(),
+            status: PAYMENT_STATUS_NOT_YET,
+            tx_id: None,
+            network: DbNetwork::Rinkeby,
+        };
+        db.as_dao::<ya_payment_driver::dao::payment::PaymentDao>()
+            .insert(payment)
+            .await
+            .unwrap();
+
+        driver
+            .active_accounts
+            .borrow_mut()
+            .add_account(SENDER.parse().unwrap());
+        driver.process_payments().await;
+        assert!(driver.dao.get_pending_payments(SENDER).await.is_empty());
+        let first = driver.dao.get_unconfirmed_txs().await;
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].nonce, "0");
+
+        driver.confirm_payments().await;
+        let first_hash = first[0].tx_hash.clone().unwrap();
+        let dropped = driver.dao.get_sim_transaction(&first_hash).await.unwrap();
+        assert_eq!(dropped.unwrap().status, SIM_TX_DROPPED);
+        assert!(driver.dao.get_unconfirmed_txs().await.is_empty());
+        assert_eq!(driver.dao.get_pending_payments(SENDER).await.len(), 1);
+
+        driver.process_payments().await;
+        let second = driver.dao.get_unconfirmed_txs().await;
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].nonce, "1");
+        assert_ne!(second[0].tx_hash, first[0].tx_hash);
+        assert_eq!(
+            driver.dao.get_balance(SENDER).await.unwrap(),
+            "10".parse::<BigDecimal>().unwrap()
+        );
+    }
+}
@@ -0,0 +1,29 @@
+/*
+    Simulated payment driver for yagna.
+
+    Keeps balances and transactions in its own tables and confirms them
+    with configurable delay, fees and failures. For offline testing only.
+
+    This file only contains constants and imports.
+*/
+
+// Public
+pub const DRIVER_NAME: &'static str = "sim";
+
+pub const DEFAULT_NETWORK: &'static str = "devnet";
+pub const DEFAULT_TOKEN: &'static str = "tGLM";
+pub const DEFAULT_PLATFORM: &'static str = "sim-devnet-tglm";
+
+pub use service::SimService as PaymentDriverService;
+
+// Private
+#[macro_use]
+extern crate diesel;
+
+mod chain;
+mod config;
+mod dao;
+mod db;
+mod driver;
+mod network;
+mod service;
@@ -0,0 +1,44 @@
+use maplit::hashmap;
+use std::collections::HashMap;
+
+// Workspace uses
+use ya_payment_driver::{db::models::Network as DbNetwork, driver::Network, model::GenericError};
+
+// Local uses
+use crate::{DEFAULT_NETWORK, DEFAULT_PLATFORM, DEFAULT_TOKEN};
+
+/// Base `payment` table requires a network id. The simulated devnet has none,
+/// so its payments are stored as test network ones.
+pub const DB_NETWORK: DbNetwork = DbNetwork::Rinkeby;
+
+lazy_static::lazy_static! {
+    pub static ref SUPPORTED_NETWORKS: HashMap<String, Network> = hashmap! {
+        DEFAULT_NETWORK.to_string() => Network {
+            default_token: DEFAULT_TOKEN.to_string(),
+            tokens: hashmap! {
+                DEFAULT_TOKEN.to_string() => DEFAULT_PLATFORM.to_string()
+            }
+        }
+    };
+}
+
+pub fn check_platform(platform: &str) -> Result<(), GenericError> {
+    match platform {
+        DEFAULT_PLATFORM => Ok(()),
+        _ => Err(GenericError::new(format!(
+            "Unsupported platform: {}",
+            platform
+        ))),
+    }
+}
+
+pub fn check_network(network: Option<String>) -> Result<String, GenericError> {
+    match network {
+        None => Ok(DEFAULT_NETWORK.to_string()),
+        Some(network) if network == DEFAULT_NETWORK => Ok(network),
+        Some(network) => Err(GenericError::new(format!(
+            "Unsupported network: {}",
+            network
+        ))),
+    }
+}
@@ -0,0 +1,56 @@
+/*
+    The service that binds this payment driver into yagna via GSB.
+*/
+
+// Extrernal crates
+use std::sync::Arc;
+
+// Workspace uses
+use ya_payment_driver::{
+    bus,
+    cron::Cron,
+    dao::{init, DbExecutor},
+    model::GenericError,
+};
+use ya_service_api_interfaces::Provider;
+
+// Local uses
+use crate::config::SimConfig;
+use crate::db::migrations;
+use crate::driver::SimDriver;
+
+pub struct SimService;
+
+impl SimService {
+    pub async fn gsb<Context: Provider<Self, DbExecutor>>(context: &Context) -> anyhow::Result<()> {
+        log::debug!("Connecting SimService to gsb...");
+
+        let config = SimConfig::from_env()?;
+        log::debug!("Environment variables validated. config={:?}", config);
+
+        // Init database
+        let db: DbExecutor = context.component();
+        init(&db).await.map_err(GenericError::new)?;
+        let chain_db = match &config.chain_db {
+            Some(path) => DbExecutor::new(path.clone())?,
+            None => db.clone(),
+        };
+        chain_db.apply_migration(migrations::run_with_output)?;
+        log::debug!("Database initialised");
+
+        // Load driver
+        let driver = SimDriver::new(db.clone(), chain_db, config);
+        driver.load_active_accounts().await;
+        let driver_rc = Arc::new(driver);
+        bus::bind_service(&db, driver_rc.clone()).await?;
+        log::debug!("Driver loaded");
+
+        // Start cron
+        Cron::new(driver_rc.clone());
+        log::debug!("Cron started");
+
+        log::warn!("Simulated payment driver enabled. Payments made with it have no value.");
+        log::info!("Succesfully connected SimService to gsb.");
+        Ok(())
+    }
+}
@@ -18,6 +18,7 @@ use ya_payment_driver::{
     dao::DbExecutor,
     db::models::{Network as DbNetwork, PaymentEntity},
     driver::{async_trait, BigDecimal, IdentityError, IdentityEvent, Network, PaymentDriver},
+    health::{requeue_failed_payments, DriverHealth, Job},
     model::*,
     utils,
 };
@@ -39,6 +40,7 @@ lazy_static! {
 
 pub struct ZksyncDriver {
     active_accounts: AccountsRc,
+    health: DriverHealth,
     dao: ZksyncDao,
 }
 
@@ -46,6 +48,7 @@ impl ZksyncDriver {
     pub fn new(db: DbExecutor) -> Self {
         Self {
             active_accounts: Accounts::new_rc(),
+            health: DriverHealth::new(),
             dao: ZksyncDao::new(db),
         }
     }
@@ -105,6 +108,7 @@ impl ZksyncDriver {
                 *nonce += 1;
             }
             Err(e) => {
+                self.health.record_error(Job::Payments, &e);
                 let deadline =
                     Utc.from_utc_datetime(&payment.payment_due_date) + *TX_SUMBIT_TIMEOUT;
                 if Utc::now() > deadline {
@@ -331,6 +335,24 @@ Mind that to be eligible you have to run your app at least once on testnet -
             .sum();
         Ok(msg.amount <= (account_balance - total_allocated_amount))
     }
+
+    async fn get_status(
+        &self,
+        db: DbExecutor,
+        _caller: String,
+        _msg: GetDriverStatus,
+    ) -> Result<DriverStatus, GenericError> {
+        self.health.status(&db, self.get_name()).await
+    }
+
+    async fn requeue_failed_payments(
+        &self,
+        db: DbExecutor,
+        _caller: String,
+        msg: RequeueFailedPayments,
+    ) -> Result<u64, GenericError> {
+        requeue_failed_payments(&db, msg.order_ids).await
+    }
 }
 
 #[async_trait(?Send)]
@@ -363,6 +385,7 @@ impl PaymentDriverCron for ZksyncDriver {
                 .collect();
 
             if let Err(err) = tx_success {
+                self.health.record_error(Job::Confirmations, &err);
                 log::error!(
                     "ZkSync transaction verification failed. tx_details={:?} error={}",
                     tx,
@@ -380,6 +403,7 @@ impl PaymentDriverCron for ZksyncDriver {
             let details = match wallet::verify_tx(&tx_hash, first_payment.network).await {
                 Ok(a) => a,
                 Err(e) => {
+                    self.health.record_error(Job::Confirmations, &e);
                     log::warn!("Failed to get transaction details from zksync, creating bespoke details. Error={}", e);
 
                     //Create bespoke payment details:
@@ -399,6 +423,7 @@ impl PaymentDriverCron for ZksyncDriver {
             if let Err(e) =
                 bus::notify_payment(&self.get_name(), &platform, order_ids, &details, tx_hash).await
             {
+                self.health.record_error(Job::Confirmations, &e);
                 log::error!("{}", e)
             };
         }
@@ -409,4 +434,7 @@ impl PaymentDriverCron for ZksyncDriver {
             self.process_payments_for_account(&node_id).await;
         }
     }
+    fn health(&self) -> &DriverHealth {
+        &self.health
+    }
 }
@@ -0,0 +1,32 @@
+-- HACK: removing columns 'driver_order_id' and 'due_date'
+
+PRAGMA foreign_keys=off;
+
+CREATE TABLE pay_order_tmp(
+    id VARCHAR(50) NOT NULL,
+    driver VARCHAR(50) NOT NULL,
+    amount VARCHAR(32) NOT NULL,
+    payee_id VARCHAR(50) NOT NULL,
+    payer_id VARCHAR(50) NOT NULL,
+    payee_addr VARCHAR(50) NOT NULL,
+    payer_addr VARCHAR(50) NOT NULL,
+    payment_platform VARCHAR(50) NOT NULL,
+    invoice_id VARCHAR(50) NULL UNIQUE,
+    debit_note_id VARCHAR(50) NULL UNIQUE,
+    allocation_id VARCHAR(50) NOT NULL,
+    is_paid BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY(id, driver),
+    FOREIGN KEY(payer_id, invoice_id) REFERENCES pay_invoice (owner_id, id),
+    FOREIGN KEY(payer_id, debit_note_id) REFERENCES pay_debit_note (owner_id, id),
+    FOREIGN KEY(allocation_id) REFERENCES pay_allocation (id),
+    CHECK ((invoice_id IS NULL) <> (debit_note_id IS NULL))
+);
+
+INSERT INTO pay_order_tmp(id, driver, amount, payee_id, payer_id, payee_addr, payer_addr, payment_platform, invoice_id, debit_note_id, allocation_id, is_paid)
+SELECT id, driver, amount, payee_id, payer_id, payee_addr, payer_addr, payment_platform, invoice_id, debit_note_id, allocation_id, is_paid FROM pay_order;
+
+DROP TABLE pay_order;
+
+ALTER TABLE pay_order_tmp RENAME TO pay_order;
+
+PRAGMA foreign_keys=on;
@@ -0,0 +1,9 @@
+-- Orders scheduled for the same (payer, payee, platform) can be sent to the driver as one
+-- transfer. Each row still describes a single document; `driver_order_id` links it to the
+-- transfer and stays NULL while the order is waiting for its batch.
+ALTER TABLE pay_order ADD COLUMN driver_order_id VARCHAR(50) NULL;
+ALTER TABLE pay_order ADD COLUMN due_date DATETIME NULL;
+
+UPDATE pay_order SET driver_order_id = id;
+
+CREATE INDEX pay_order_driver_order_id ON pay_order (driver_order_id, driver);
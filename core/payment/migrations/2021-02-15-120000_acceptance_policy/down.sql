@@ -0,0 +1,2 @@
+DROP TABLE pay_acceptance_decision;
+DROP TABLE pay_acceptance_policy;
@@ -0,0 +1,27 @@
+-- Rules for accepting debit notes and invoices without requestor app's involvement
+CREATE TABLE pay_acceptance_policy(
+    allocation_id VARCHAR(50) NOT NULL PRIMARY KEY,
+    owner_id VARCHAR(50) NOT NULL,
+    max_amount_per_agreement VARCHAR(32) NULL,
+    max_rate_per_hour VARCHAR(32) NULL,
+    accept_within_allocation BOOLEAN NOT NULL DEFAULT FALSE,
+    timestamp DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(allocation_id) REFERENCES pay_allocation (id) ON DELETE CASCADE
+);
+
+CREATE TABLE pay_acceptance_decision(
+    id VARCHAR(50) NOT NULL PRIMARY KEY,
+    owner_id VARCHAR(50) NOT NULL,
+    invoice_id VARCHAR(50) NULL,
+    debit_note_id VARCHAR(50) NULL,
+    allocation_id VARCHAR(50) NOT NULL,
+    accepted BOOLEAN NOT NULL,
+    rule VARCHAR(50) NOT NULL,
+    reason TEXT NOT NULL,
+    timestamp DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(owner_id, invoice_id),
+    UNIQUE(owner_id, debit_note_id),
+    FOREIGN KEY(owner_id, invoice_id) REFERENCES pay_invoice (owner_id, id),
+    FOREIGN KEY(owner_id, debit_note_id) REFERENCES pay_debit_note (owner_id, id),
+    CHECK ((invoice_id IS NULL) <> (debit_note_id IS NULL))
+);
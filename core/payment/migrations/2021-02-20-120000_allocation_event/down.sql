@@ -0,0 +1,2 @@
+DROP INDEX pay_allocation_event_owner_timestamp;
+DROP TABLE pay_allocation_event;
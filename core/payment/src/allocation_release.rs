@@ -0,0 +1,41 @@
+use chrono::Utc;
+use metrics::counter;
+use std::time::Duration;
+
+use ya_persistence::executor::DbExecutor;
+
+use crate::dao::AllocationDao;
+use crate::error::DbResult;
+
+/// Releases allocations past their timeout. Returns number of released allocations.
+pub async fn release_expired(db: &DbExecutor) -> DbResult<usize> {
+    let released = db
+        .as_dao::<AllocationDao>()
+        .release_expired(Utc::now().naive_utc())
+        .await?;
+    for (owner_id, allocation) in released.iter() {
+        log::info!(
+            "Allocation {} of {} expired at {:?}. Released remaining {} {}",
+            allocation.allocation_id,
+            owner_id,
+            allocation.timeout,
+            allocation.remaining_amount,
+            allocation.payment_platform
+        );
+        counter!("payment.allocations.requestor.expired", 1);
+    }
+    Ok(released.len())
+}
+
+/// Periodically releases expired allocations, so funds reserved by requestor
+/// apps, which didn't release them (e.g. crashed), become available again.
+pub fn start_release_job(db: DbExecutor, interval: Duration) {
+    actix_rt::spawn(async move {
+        loop {
+            if let Err(e) = release_expired(&db).await {
+                log::error!("Failed to release expired allocations: {}", e);
+            }
+            tokio::time::delay_for(interval).await;
+        }
+    });
+}
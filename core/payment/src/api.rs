@@ -6,16 +6,20 @@ use ya_service_api_web::scope::ExtendableScope;
 mod accounts;
 mod allocations;
 mod debit_notes;
+mod export;
 mod invoices;
 mod payments;
+mod policies;
 
 pub fn api_scope(scope: Scope) -> Scope {
     scope
         .extend(accounts::register_endpoints)
         .extend(allocations::register_endpoints)
         .extend(debit_notes::register_endpoints)
+        .extend(export::register_endpoints)
         .extend(invoices::register_endpoints)
         .extend(payments::register_endpoints)
+        .extend(policies::register_endpoints)
 }
 
 pub fn web_scope(db: &DbExecutor) -> Scope {
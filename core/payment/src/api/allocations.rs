@@ -1,6 +1,7 @@
 // Extrnal crates
 use actix_web::web::{delete, get, post, put, Data, Json, Path, Query};
 use actix_web::{HttpResponse, Scope};
+use chrono::Utc;
 use serde_json::value::Value::Null;
 
 // Workspace uses
@@ -17,7 +18,7 @@ use ya_service_bus::{typed as bus, RpcEndpoint};
 // Local uses
 use crate::dao::*;
 use crate::error::{DbError, Error};
-use crate::utils::response;
+use crate::utils::{listen_for_events, response};
 use crate::DEFAULT_PAYMENT_PLATFORM;
 
 pub fn register_endpoints(scope: Scope) -> Scope {
@@ -30,6 +31,7 @@ pub fn register_endpoints(scope: Scope) -> Scope {
             "/allocations/{allocation_id}",
             delete().to(release_allocation),
         )
+        .route("/allocationEvents", get().to(get_allocation_events))
         .route("/demandDecorations", get().to(get_demand_decorations))
 }
 
@@ -38,9 +40,17 @@ async fn create_allocation(
     body: Json<NewAllocation>,
     id: Identity,
 ) -> HttpResponse {
-    // TODO: Handle deposits & timeouts
     let allocation = body.into_inner();
     let node_id = id.identity;
+    if allocation.make_deposit {
+        return response::bad_request(&"Deposits are not supported");
+    }
+    // Expired allocations are released by `allocation_release` job
+    if let Some(timeout) = allocation.timeout {
+        if timeout <= Utc::now() {
+            return response::bad_request(&"Allocation timeout is in the past");
+        }
+    }
     let payment_platform = allocation
         .payment_platform
         .clone()
@@ -130,6 +140,28 @@ async fn release_allocation(
     }
 }
 
+async fn get_allocation_events(
+    db: Data<DbExecutor>,
+    query: Query<params::EventParams>,
+    id: Identity,
+) -> HttpResponse {
+    let node_id = id.identity;
+    let timeout_secs = query.poll_timeout.unwrap_or(params::DEFAULT_EVENT_TIMEOUT);
+    let after_timestamp = query.after_timestamp.map(|d| d.naive_utc());
+    let max_events = query.max_events;
+
+    let dao: AllocationEventDao = db.as_dao();
+    let getter = || async {
+        dao.get_for_node_id(node_id.clone(), after_timestamp.clone(), max_events.clone())
+            .await
+    };
+
+    match listen_for_events(getter, timeout_secs).await {
+        Ok(events) => response::ok(events),
+        Err(e) => response::server_error(&e),
+    }
+}
+
 async fn get_demand_decorations(
     db: Data<DbExecutor>,
     path: Query<params::AllocationIds>,
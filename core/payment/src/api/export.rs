@@ -0,0 +1,49 @@
+// Extrnal crates
+use actix_web::web::{get, Data, Query};
+use actix_web::{HttpResponse, Scope};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+// Workspace uses
+use ya_persistence::executor::DbExecutor;
+use ya_service_api_web::middleware::Identity;
+
+// Local uses
+use crate::export::{self, ExportFormat};
+use crate::utils::response;
+
+pub fn register_endpoints(scope: Scope) -> Scope {
+    scope.route("/export", get().to(export_documents))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportParams {
+    since: DateTime<Utc>,
+    #[serde(default)]
+    until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    format: Option<ExportFormat>,
+}
+
+async fn export_documents(
+    db: Data<DbExecutor>,
+    query: Query<ExportParams>,
+    id: Identity,
+) -> HttpResponse {
+    let node_id = id.identity;
+    let until = query.until.unwrap_or_else(Utc::now);
+    if until < query.since {
+        return response::bad_request(&"`until` is earlier than `since`");
+    }
+    let report = match export::export(&db, node_id, query.since, until).await {
+        Ok(report) => report,
+        Err(e) => return response::server_error(&e),
+    };
+    match query.format.unwrap_or(ExportFormat::Json) {
+        ExportFormat::Json => response::ok(report),
+        ExportFormat::Csv => HttpResponse::Ok()
+            .content_type("text/csv")
+            .body(export::to_csv(&report)),
+    }
+}
@@ -0,0 +1,115 @@
+// Extrnal crates
+use actix_web::web::{delete, get, put, Data, Json, Path};
+use actix_web::{HttpResponse, Scope};
+use serde_json::value::Value::Null;
+
+// Workspace uses
+use ya_client_model::payment::params;
+use ya_persistence::executor::DbExecutor;
+use ya_service_api_web::middleware::Identity;
+
+// Local uses
+use crate::dao::*;
+use crate::models::policy::AcceptancePolicy;
+use crate::utils::response;
+
+pub fn register_endpoints(scope: Scope) -> Scope {
+    scope
+        .route(
+            "/allocations/{allocation_id}/acceptancePolicy",
+            put().to(set_policy),
+        )
+        .route(
+            "/allocations/{allocation_id}/acceptancePolicy",
+            get().to(get_policy),
+        )
+        .route(
+            "/allocations/{allocation_id}/acceptancePolicy",
+            delete().to(delete_policy),
+        )
+        .route(
+            "/debitNotes/{debit_note_id}/acceptanceDecision",
+            get().to(get_debit_note_decision),
+        )
+        .route(
+            "/invoices/{invoice_id}/acceptanceDecision",
+            get().to(get_invoice_decision),
+        )
+}
+
+async fn set_policy(
+    db: Data<DbExecutor>,
+    path: Path<params::AllocationId>,
+    body: Json<AcceptancePolicy>,
+    id: Identity,
+) -> HttpResponse {
+    let allocation_id = path.allocation_id.clone();
+    let policy = body.into_inner();
+    let node_id = id.identity;
+    let dao: PolicyDao = db.as_dao();
+    match dao.set(allocation_id, node_id, policy.clone()).await {
+        Ok(true) => response::ok(policy),
+        Ok(false) => response::not_found(),
+        Err(e) => response::server_error(&e),
+    }
+}
+
+async fn get_policy(
+    db: Data<DbExecutor>,
+    path: Path<params::AllocationId>,
+    id: Identity,
+) -> HttpResponse {
+    let allocation_id = path.allocation_id.clone();
+    let node_id = id.identity;
+    let dao: PolicyDao = db.as_dao();
+    match dao.get(allocation_id, node_id).await {
+        Ok(Some(policy)) => response::ok(policy),
+        Ok(None) => response::not_found(),
+        Err(e) => response::server_error(&e),
+    }
+}
+
+async fn delete_policy(
+    db: Data<DbExecutor>,
+    path: Path<params::AllocationId>,
+    id: Identity,
+) -> HttpResponse {
+    let allocation_id = path.allocation_id.clone();
+    let node_id = id.identity;
+    let dao: PolicyDao = db.as_dao();
+    match dao.delete(allocation_id, node_id).await {
+        Ok(true) => response::ok(Null),
+        Ok(false) => response::not_found(),
+        Err(e) => response::server_error(&e),
+    }
+}
+
+async fn get_debit_note_decision(
+    db: Data<DbExecutor>,
+    path: Path<params::DebitNoteId>,
+    id: Identity,
+) -> HttpResponse {
+    let debit_note_id = path.debit_note_id.clone();
+    let node_id = id.identity;
+    let dao: PolicyDao = db.as_dao();
+    match dao.get_debit_note_decision(debit_note_id, node_id).await {
+        Ok(Some(decision)) => response::ok(decision),
+        Ok(None) => response::not_found(),
+        Err(e) => response::server_error(&e),
+    }
+}
+
+async fn get_invoice_decision(
+    db: Data<DbExecutor>,
+    path: Path<params::InvoiceId>,
+    id: Identity,
+) -> HttpResponse {
+    let invoice_id = path.invoice_id.clone();
+    let node_id = id.identity;
+    let dao: PolicyDao = db.as_dao();
+    match dao.get_invoice_decision(invoice_id, node_id).await {
+        Ok(Some(decision)) => response::ok(decision),
+        Ok(None) => response::not_found(),
+        Err(e) => response::server_error(&e),
+    }
+}
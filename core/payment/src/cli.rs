@@ -1,16 +1,17 @@
 // External crates
 use bigdecimal::BigDecimal;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use std::str::FromStr;
 use structopt::*;
 
 // Workspace uses
-use ya_core_model::{identity as id_api, payment::local as pay};
+use ya_core_model::{driver, identity as id_api, payment::local as pay};
 use ya_service_api::{CliCtx, CommandOutput, ResponseTable};
 use ya_service_bus::{typed as bus, RpcEndpoint};
 
 // Local uses
 use crate::accounts::{init_account, Account};
+use crate::export::{to_csv, ExportFormat};
 use crate::wallet;
 
 /// Payment management.
@@ -76,6 +77,43 @@ pub enum PaymentCli {
         #[structopt(subcommand)]
         command: InvoiceCommand,
     },
+
+    /// Inspect payment drivers
+    Driver {
+        #[structopt(subcommand)]
+        command: DriverCommand,
+    },
+
+    /// Export invoices, debit notes and payments of a period, grouped per agreement
+    Export {
+        #[structopt(long, help = "Optional identity [default: <DEFAULT_IDENTITY>]")]
+        address: Option<String>,
+        #[structopt(
+            long,
+            help = "Start of the period (RFC 3339, e.g. 2021-01-01T00:00:00Z)"
+        )]
+        since: DateTime<Utc>,
+        #[structopt(long, help = "End of the period (RFC 3339) [default: <NOW>]")]
+        until: Option<DateTime<Utc>>,
+        #[structopt(long, default_value = "csv", possible_values = &["csv", "json"])]
+        format: ExportFormat,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+pub enum DriverCommand {
+    /// Display driver's job runs, transaction counts and last error
+    Status {
+        #[structopt(long, default_value = pay::DEFAULT_PAYMENT_DRIVER)]
+        driver: String,
+    },
+    /// Make failed payments pending again, so the driver retries them
+    Requeue {
+        #[structopt(long, default_value = pay::DEFAULT_PAYMENT_DRIVER)]
+        driver: String,
+        /// Driver order ids to requeue [default: <ALL_FAILED>]
+        order_ids: Vec<String>,
+    },
 }
 
 #[derive(StructOpt, Debug)]
@@ -214,6 +252,71 @@ impl PaymentCli {
                         .await??,
                 )
             }
+            PaymentCli::Driver {
+                command: DriverCommand::Status { driver },
+            } => {
+                let status = bus::service(driver::driver_bus_id(&driver))
+                    .call(driver::GetDriverStatus {})
+                    .await??;
+                if ctx.json_output {
+                    return CommandOutput::object(status);
+                }
+
+                let since = |time: Option<DateTime<Utc>>| match time {
+                    Some(time) => format!("{} ({} ago)", time, format_age(time)),
+                    None => "never".to_string(),
+                };
+                let mut values = vec![
+                    serde_json::json! {["last payment run", since(status.last_payment_run)]},
+                    serde_json::json! {["last confirmation run", since(status.last_confirmation_run)]},
+                    serde_json::json! {["pending payments", status.pending_payments]},
+                    serde_json::json! {["failed payments", status.failed_payments]},
+                    serde_json::json! {["unconfirmed transactions", status.unconfirmed_txs]},
+                    serde_json::json! {["failed transactions", status.failed_txs]},
+                    serde_json::json! {[
+                        "oldest unconfirmed transaction",
+                        status.oldest_unconfirmed_tx.map(|time| since(Some(time))).unwrap_or_default()
+                    ]},
+                ];
+                if let Some(error) = status.last_error {
+                    values.push(serde_json::json! {[
+                        "last error",
+                        format!("{} ({} ago)", error.message, format_age(error.timestamp))
+                    ]});
+                }
+                Ok(ResponseTable {
+                    columns: vec!["".to_owned(), "".to_owned()],
+                    values,
+                }
+                .with_header(format!("\nStatus of driver: {}\n", status.driver)))
+            }
+            PaymentCli::Driver {
+                command: DriverCommand::Requeue { driver, order_ids },
+            } => {
+                let requeued = bus::service(driver::driver_bus_id(&driver))
+                    .call(driver::RequeueFailedPayments { order_ids })
+                    .await??;
+                CommandOutput::object(format!("Requeued {} failed payments", requeued))
+            }
+            PaymentCli::Export {
+                address,
+                since,
+                until,
+                format,
+            } => {
+                let address = resolve_address(address).await?;
+                let report = bus::service(pay::BUS_ID)
+                    .call(pay::ExportDocuments {
+                        node_id: address.parse()?,
+                        since,
+                        until: until.unwrap_or_else(Utc::now),
+                    })
+                    .await??;
+                match format {
+                    ExportFormat::Json => CommandOutput::object(report),
+                    ExportFormat::Csv => CommandOutput::object(to_csv(&report)),
+                }
+            }
             // TODO: Uncomment when operation is supported by drivers
             // PaymentCli::Enter {
             //     account,
@@ -261,6 +364,11 @@ impl PaymentCli {
     }
 }
 
+fn format_age(time: DateTime<Utc>) -> String {
+    let age = (Utc::now() - time).to_std().unwrap_or_default();
+    humantime::format_duration(std::time::Duration::from_secs(age.as_secs())).to_string()
+}
+
 async fn resolve_address(address: Option<String>) -> anyhow::Result<String> {
     if let Some(id) = address {
         return Ok(id);
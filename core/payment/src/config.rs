@@ -0,0 +1,31 @@
+use std::time::Duration;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Clone, Debug)]
+pub struct Config {
+    /// Time, for which payments scheduled for the same payee are collected
+    /// before being sent to the driver as a single transfer. Zero disables batching.
+    #[structopt(
+        long = "payment-batch-window",
+        env = "YAGNA_PAYMENT_BATCH_WINDOW",
+        parse(try_from_str = humantime::parse_duration),
+        default_value = "0s"
+    )]
+    pub batch_window: Duration,
+    /// Interval between checks for allocations past their timeout
+    #[structopt(
+        long = "payment-allocation-release-interval",
+        env = "YAGNA_PAYMENT_ALLOCATION_RELEASE_INTERVAL",
+        parse(try_from_str = humantime::parse_duration),
+        default_value = "1min"
+    )]
+    pub allocation_release_interval: Duration,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Config, structopt::clap::Error> {
+        // Empty command line arguments, because we want to use ENV fallback
+        // or default values if ENV variables are not set.
+        Config::from_iter_safe(&[""])
+    }
+}
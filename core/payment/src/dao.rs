@@ -1,19 +1,23 @@
 mod activity;
 mod agreement;
 mod allocation;
+mod allocation_event;
 mod debit_note;
 mod debit_note_event;
 mod invoice;
 mod invoice_event;
 mod order;
 mod payment;
+mod policy;
 
 pub use self::activity::ActivityDao;
 pub use self::agreement::AgreementDao;
 pub use self::allocation::AllocationDao;
+pub use self::allocation_event::AllocationEventDao;
 pub use self::debit_note::DebitNoteDao;
 pub use self::debit_note_event::DebitNoteEventDao;
 pub use self::invoice::InvoiceDao;
 pub use self::invoice_event::InvoiceEventDao;
 pub use self::order::OrderDao;
 pub use self::payment::PaymentDao;
+pub use self::policy::PolicyDao;
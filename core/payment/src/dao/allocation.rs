@@ -1,9 +1,13 @@
+use crate::dao::allocation_event;
 use crate::error::{DbError, DbResult};
 use crate::models::allocation::{ReadObj, WriteObj};
+use crate::models::allocation_event::AllocationEventType;
 use crate::schema::pay_allocation::dsl;
 use bigdecimal::BigDecimal;
-use chrono::NaiveDateTime;
-use diesel::{self, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use chrono::{NaiveDateTime, Utc};
+use diesel::{
+    self, BoolExpressionMethods, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl,
+};
 use ya_client_model::payment::{Allocation, NewAllocation};
 use ya_client_model::NodeId;
 use ya_persistence::executor::{
@@ -27,6 +31,12 @@ pub fn spend_from_allocation(
     conn: &ConnType,
 ) -> DbResult<()> {
     let allocation: ReadObj = dsl::pay_allocation.find(allocation_id).first(conn)?;
+    if allocation.released || is_expired(&allocation, Utc::now().naive_utc()) {
+        return Err(DbError::Query(format!(
+            "Allocation {} has expired or been released",
+            allocation_id
+        )));
+    }
     if amount > &allocation.remaining_amount {
         return Err(DbError::Query(format!(
             "Not enough funds in allocation. Needed: {} Remaining: {}",
@@ -44,6 +54,13 @@ pub fn spend_from_allocation(
     Ok(())
 }
 
+fn is_expired(allocation: &ReadObj, now: NaiveDateTime) -> bool {
+    match allocation.timeout {
+        Some(timeout) => timeout <= now,
+        None => false,
+    }
+}
+
 impl<'c> AllocationDao<'c> {
     pub async fn create(
         &self,
@@ -125,10 +142,12 @@ impl<'c> AllocationDao<'c> {
         address: String,
     ) -> DbResult<Vec<Allocation>> {
         readonly_transaction(self.pool, move |conn| {
+            let now = Utc::now().naive_utc();
             let allocations: Vec<ReadObj> = dsl::pay_allocation
                 .filter(dsl::payment_platform.eq(payment_platform))
                 .filter(dsl::address.eq(address))
                 .filter(dsl::released.eq(false))
+                .filter(dsl::timeout.is_null().or(dsl::timeout.gt(now)))
                 .load(conn)?;
             Ok(allocations.into_iter().map(Into::into).collect())
         })
@@ -150,17 +169,47 @@ impl<'c> AllocationDao<'c> {
         .await
     }
 
+    /// Releases allocations, which timed out before `now`. Returns them along with their owners.
+    pub async fn release_expired(&self, now: NaiveDateTime) -> DbResult<Vec<(NodeId, Allocation)>> {
+        do_with_transaction(self.pool, move |conn| {
+            let expired: Vec<ReadObj> = dsl::pay_allocation
+                .filter(dsl::released.eq(false))
+                .filter(dsl::timeout.le(now))
+                .load(conn)?;
+            let ids: Vec<String> = expired.iter().map(|a| a.id.clone()).collect();
+            diesel::update(dsl::pay_allocation.filter(dsl::id.eq_any(ids)))
+                .set(dsl::released.eq(true))
+                .execute(conn)?;
+            for allocation in expired.iter() {
+                allocation_event::create(
+                    allocation.id.clone(),
+                    allocation.owner_id,
+                    AllocationEventType::AllocationExpiredEvent,
+                    conn,
+                )?;
+            }
+            Ok(expired
+                .into_iter()
+                .map(|allocation| (allocation.owner_id, allocation.into()))
+                .collect())
+        })
+        .await
+    }
+
     pub async fn total_remaining_allocation(
         &self,
         platform: String,
         address: String,
     ) -> DbResult<BigDecimal> {
         readonly_transaction(self.pool, move |conn| {
+            // Expired allocations don't reserve funds, even before they get released
+            let now = Utc::now().naive_utc();
             let total_remaining_amount = dsl::pay_allocation
                 .select(dsl::remaining_amount)
                 .filter(dsl::payment_platform.eq(platform))
                 .filter(dsl::address.eq(address))
                 .filter(dsl::released.eq(false))
+                .filter(dsl::timeout.is_null().or(dsl::timeout.gt(now)))
                 .get_results::<BigDecimalField>(conn)?
                 .sum();
 
@@ -0,0 +1,56 @@
+use crate::error::DbResult;
+use crate::models::allocation_event::{AllocationEvent, AllocationEventType, ReadObj, WriteObj};
+use crate::schema::pay_allocation_event::dsl;
+use chrono::NaiveDateTime;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use std::convert::TryInto;
+use ya_client_model::NodeId;
+use ya_persistence::executor::{readonly_transaction, AsDao, ConnType, PoolType};
+
+pub fn create(
+    allocation_id: String,
+    owner_id: NodeId,
+    event_type: AllocationEventType,
+    conn: &ConnType,
+) -> DbResult<()> {
+    let event = WriteObj::new(allocation_id, owner_id, event_type);
+    diesel::insert_into(dsl::pay_allocation_event)
+        .values(event)
+        .execute(conn)?;
+    Ok(())
+}
+
+pub struct AllocationEventDao<'c> {
+    pool: &'c PoolType,
+}
+
+impl<'c> AsDao<'c> for AllocationEventDao<'c> {
+    fn as_dao(pool: &'c PoolType) -> Self {
+        Self { pool }
+    }
+}
+
+impl<'c> AllocationEventDao<'c> {
+    pub async fn get_for_node_id(
+        &self,
+        node_id: NodeId,
+        after_timestamp: Option<NaiveDateTime>,
+        max_events: Option<u32>,
+    ) -> DbResult<Vec<AllocationEvent>> {
+        readonly_transaction(self.pool, move |conn| {
+            let mut query = dsl::pay_allocation_event
+                .filter(dsl::owner_id.eq(node_id))
+                .order_by(dsl::timestamp.asc())
+                .into_boxed();
+            if let Some(timestamp) = after_timestamp {
+                query = query.filter(dsl::timestamp.gt(timestamp));
+            }
+            if let Some(limit) = max_events {
+                query = query.limit(limit.into());
+            }
+            let events: Vec<ReadObj> = query.load(conn)?;
+            events.into_iter().map(TryInto::try_into).collect()
+        })
+        .await
+    }
+}
@@ -221,11 +221,17 @@ impl<'c> DebitNoteDao<'c> {
         .await
     }
 
+    /// Recipient can accept or reject debit note, before sender marks it as received.
+    /// Only debit notes, which are still issued, are updated.
     pub async fn mark_received(&self, debit_note_id: String, owner_id: NodeId) -> DbResult<()> {
         do_with_transaction(self.pool, move |conn| {
-            diesel::update(dsl::pay_debit_note.find((debit_note_id, owner_id)))
-                .set(dsl::status.eq(DocumentStatus::Received.to_string()))
-                .execute(conn)?;
+            diesel::update(
+                dsl::pay_debit_note
+                    .find((debit_note_id, owner_id))
+                    .filter(dsl::status.eq(DocumentStatus::Issued.to_string())),
+            )
+            .set(dsl::status.eq(DocumentStatus::Received.to_string()))
+            .execute(conn)?;
             Ok(())
         })
         .await
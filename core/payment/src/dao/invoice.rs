@@ -220,9 +220,18 @@ impl<'c> InvoiceDao<'c> {
         Ok(stats)
     }
 
+    /// Recipient can accept or reject invoice, before sender marks it as received.
+    /// Only invoices, which are still issued, are updated.
     pub async fn mark_received(&self, invoice_id: String, owner_id: NodeId) -> DbResult<()> {
         do_with_transaction(self.pool, move |conn| {
-            update_status(&invoice_id, &owner_id, &DocumentStatus::Received, conn)
+            diesel::update(
+                dsl::pay_invoice
+                    .find((&invoice_id, &owner_id))
+                    .filter(dsl::status.eq(DocumentStatus::Issued.to_string())),
+            )
+            .set(dsl::status.eq(DocumentStatus::Received.to_string()))
+            .execute(conn)?;
+            Ok(())
         })
         .await
     }
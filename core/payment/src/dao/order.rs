@@ -6,13 +6,51 @@ use crate::schema::pay_invoice::dsl as invoice_dsl;
 use crate::schema::pay_order::dsl;
 use diesel::{
     self, BoolExpressionMethods, ExpressionMethods, JoinOnDsl, NullableExpressionMethods, QueryDsl,
-    RunQueryDsl,
+    RunQueryDsl, TextExpressionMethods,
 };
+use uuid::Uuid;
 use ya_core_model::payment::local::{
     DebitNotePayment, InvoicePayment, PaymentTitle, SchedulePayment,
 };
 use ya_persistence::executor::{do_with_transaction, readonly_transaction, AsDao, PoolType};
 
+/// Prefix of `driver_order_id` of orders claimed by a batch, which isn't scheduled yet.
+const BATCH_ID_PREFIX: &str = "batch:";
+
+macro_rules! query {
+    () => {
+        dsl::pay_order
+            .left_join(
+                invoice_dsl::pay_invoice.on(dsl::invoice_id
+                    .eq(invoice_dsl::id.nullable())
+                    .and(dsl::payer_id.eq(invoice_dsl::owner_id))),
+            )
+            .left_join(
+                debit_note_dsl::pay_debit_note.on(dsl::debit_note_id
+                    .eq(debit_note_dsl::id.nullable())
+                    .and(dsl::payer_id.eq(debit_note_dsl::owner_id))),
+            )
+            .select((
+                dsl::id,
+                dsl::driver,
+                dsl::amount,
+                dsl::payee_id,
+                dsl::payer_id,
+                dsl::payee_addr,
+                dsl::payer_addr,
+                dsl::payment_platform,
+                dsl::invoice_id,
+                dsl::debit_note_id,
+                dsl::allocation_id,
+                dsl::is_paid,
+                dsl::driver_order_id,
+                dsl::due_date,
+                invoice_dsl::agreement_id.nullable(),
+                debit_note_dsl::activity_id.nullable(),
+            ))
+    };
+}
+
 pub struct OrderDao<'c> {
     pool: &'c PoolType,
 }
@@ -24,7 +62,13 @@ impl<'c> AsDao<'c> for OrderDao<'c> {
 }
 
 impl<'c> OrderDao<'c> {
-    pub async fn create(&self, msg: SchedulePayment, id: String, driver: String) -> DbResult<()> {
+    /// Orders created without `driver_order_id` wait for `assign_driver_order`.
+    pub async fn create(
+        &self,
+        msg: SchedulePayment,
+        driver: String,
+        driver_order_id: Option<String>,
+    ) -> DbResult<()> {
         do_with_transaction(self.pool, move |conn| {
             match &msg.title {
                 PaymentTitle::DebitNote(DebitNotePayment { activity_id, .. }) => {
@@ -44,7 +88,7 @@ impl<'c> OrderDao<'c> {
                     )?
                 }
             };
-            let order = WriteObj::new(msg, id, driver);
+            let order = WriteObj::new(msg, driver, driver_order_id);
             allocation::spend_from_allocation(&order.allocation_id, &order.amount, conn)?;
             diesel::insert_into(dsl::pay_order)
                 .values(order)
@@ -54,40 +98,97 @@ impl<'c> OrderDao<'c> {
         .await
     }
 
+    /// Returns orders realized by given driver orders.
     pub async fn get_many(&self, ids: Vec<String>, driver: String) -> DbResult<Vec<ReadObj>> {
         readonly_transaction(self.pool, move |conn| {
-            let orders = dsl::pay_order
-                .left_join(
-                    invoice_dsl::pay_invoice.on(dsl::invoice_id
-                        .eq(invoice_dsl::id.nullable())
-                        .and(dsl::payer_id.eq(invoice_dsl::owner_id))),
-                )
-                .left_join(
-                    debit_note_dsl::pay_debit_note.on(dsl::debit_note_id
-                        .eq(debit_note_dsl::id.nullable())
-                        .and(dsl::payer_id.eq(debit_note_dsl::owner_id))),
-                )
-                .filter(dsl::id.eq_any(ids))
+            let orders = query!()
+                .filter(dsl::driver_order_id.eq_any(ids))
                 .filter(dsl::driver.eq(driver))
-                .select((
-                    dsl::id,
-                    dsl::driver,
-                    dsl::amount,
-                    dsl::payee_id,
-                    dsl::payer_id,
-                    dsl::payee_addr,
-                    dsl::payer_addr,
-                    dsl::payment_platform,
-                    dsl::invoice_id,
-                    dsl::debit_note_id,
-                    dsl::allocation_id,
-                    dsl::is_paid,
-                    invoice_dsl::agreement_id.nullable(),
-                    debit_note_dsl::activity_id.nullable(),
-                ))
                 .load(conn)?;
             Ok(orders)
         })
         .await
     }
+
+    /// Returns orders, which haven't been sent to the driver yet.
+    pub async fn get_pending(&self) -> DbResult<Vec<ReadObj>> {
+        readonly_transaction(self.pool, move |conn| {
+            let orders = query!().filter(dsl::driver_order_id.is_null()).load(conn)?;
+            Ok(orders)
+        })
+        .await
+    }
+
+    /// Marks pending orders with a provisional batch id, so they aren't sent again
+    /// while the batch is scheduled. Returns the batch id and the claimed orders.
+    pub async fn claim_pending(
+        &self,
+        ids: Vec<String>,
+        driver: String,
+    ) -> DbResult<(String, Vec<ReadObj>)> {
+        let batch_id = format!("{}{}", BATCH_ID_PREFIX, Uuid::new_v4());
+        do_with_transaction(self.pool, move |conn| {
+            diesel::update(
+                dsl::pay_order
+                    .filter(dsl::id.eq_any(ids))
+                    .filter(dsl::driver.eq(&driver))
+                    .filter(dsl::driver_order_id.is_null()),
+            )
+            .set(dsl::driver_order_id.eq(&batch_id))
+            .execute(conn)?;
+            let orders = query!()
+                .filter(dsl::driver_order_id.eq(&batch_id))
+                .filter(dsl::driver.eq(&driver))
+                .load(conn)?;
+            Ok((batch_id, orders))
+        })
+        .await
+    }
+
+    /// Returns orders claimed by batches, which were never scheduled nor released
+    /// (e.g. because the node was stopped in the meantime).
+    pub async fn get_claimed(&self) -> DbResult<Vec<ReadObj>> {
+        readonly_transaction(self.pool, move |conn| {
+            let orders = query!()
+                .filter(dsl::driver_order_id.like(format!("{}%", BATCH_ID_PREFIX)))
+                .load(conn)?;
+            Ok(orders)
+        })
+        .await
+    }
+
+    /// Links orders claimed by `batch_id` with the scheduled driver order.
+    pub async fn assign_driver_order(
+        &self,
+        batch_id: String,
+        driver: String,
+        driver_order_id: String,
+    ) -> DbResult<()> {
+        do_with_transaction(self.pool, move |conn| {
+            diesel::update(
+                dsl::pay_order
+                    .filter(dsl::driver_order_id.eq(batch_id))
+                    .filter(dsl::driver.eq(driver)),
+            )
+            .set(dsl::driver_order_id.eq(driver_order_id))
+            .execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Makes orders claimed by `batch_id` pending again.
+    pub async fn release_batch(&self, batch_id: String, driver: String) -> DbResult<()> {
+        do_with_transaction(self.pool, move |conn| {
+            diesel::update(
+                dsl::pay_order
+                    .filter(dsl::driver_order_id.eq(batch_id))
+                    .filter(dsl::driver.eq(driver)),
+            )
+            .set(dsl::driver_order_id.eq(None::<String>))
+            .execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
 }
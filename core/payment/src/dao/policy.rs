@@ -0,0 +1,207 @@
+use crate::error::DbResult;
+use crate::models::allocation::ReadObj as AllocationReadObj;
+use crate::models::policy::{
+    AcceptanceDecision, AcceptancePolicy, DecisionReadObj, DecisionWriteObj, PolicyReadObj,
+    PolicyWriteObj,
+};
+use crate::schema::pay_acceptance_decision::dsl as decision_dsl;
+use crate::schema::pay_acceptance_policy::dsl;
+use crate::schema::pay_activity::dsl as activity_dsl;
+use crate::schema::pay_allocation::dsl as allocation_dsl;
+use crate::schema::pay_debit_note::dsl as debit_note_dsl;
+use crate::schema::pay_invoice::dsl as invoice_dsl;
+use crate::schema::pay_order::dsl as order_dsl;
+use chrono::Utc;
+use diesel::{
+    self, BoolExpressionMethods, ExpressionMethods, JoinOnDsl, OptionalExtension, QueryDsl,
+    RunQueryDsl,
+};
+use ya_client_model::payment::Allocation;
+use ya_client_model::NodeId;
+use ya_persistence::executor::{do_with_transaction, readonly_transaction, AsDao, PoolType};
+
+pub struct PolicyDao<'c> {
+    pool: &'c PoolType,
+}
+
+impl<'c> AsDao<'c> for PolicyDao<'c> {
+    fn as_dao(pool: &'c PoolType) -> Self {
+        Self { pool }
+    }
+}
+
+impl<'c> PolicyDao<'c> {
+    /// Replaces policy of the allocation. Returns `false`, when there is no such allocation.
+    pub async fn set(
+        &self,
+        allocation_id: String,
+        owner_id: NodeId,
+        policy: AcceptancePolicy,
+    ) -> DbResult<bool> {
+        do_with_transaction(self.pool, move |conn| {
+            let allocations: i64 = allocation_dsl::pay_allocation
+                .filter(allocation_dsl::id.eq(&allocation_id))
+                .filter(allocation_dsl::owner_id.eq(owner_id))
+                .filter(allocation_dsl::released.eq(false))
+                .count()
+                .get_result(conn)?;
+            if allocations == 0 {
+                return Ok(false);
+            }
+            let policy = PolicyWriteObj::new(policy, allocation_id, owner_id);
+            diesel::replace_into(dsl::pay_acceptance_policy)
+                .values(policy)
+                .execute(conn)?;
+            Ok(true)
+        })
+        .await
+    }
+
+    pub async fn get(
+        &self,
+        allocation_id: String,
+        owner_id: NodeId,
+    ) -> DbResult<Option<AcceptancePolicy>> {
+        readonly_transaction(self.pool, move |conn| {
+            let policy: Option<PolicyReadObj> = dsl::pay_acceptance_policy
+                .filter(dsl::owner_id.eq(owner_id))
+                .find(allocation_id)
+                .first(conn)
+                .optional()?;
+            Ok(policy.map(Into::into))
+        })
+        .await
+    }
+
+    pub async fn delete(&self, allocation_id: String, owner_id: NodeId) -> DbResult<bool> {
+        do_with_transaction(self.pool, move |conn| {
+            let num_deleted = diesel::delete(
+                dsl::pay_acceptance_policy
+                    .filter(dsl::allocation_id.eq(allocation_id))
+                    .filter(dsl::owner_id.eq(owner_id)),
+            )
+            .execute(conn)?;
+            Ok(num_deleted > 0)
+        })
+        .await
+    }
+
+    /// Policy of the allocation, which the agreement is paid from, i.e. allocation of
+    /// orders for agreement's earlier documents. Before the first payment the agreement
+    /// can only be paid from the single active allocation for its platform and address.
+    /// Returns `None`, when allocation is ambiguous or has no policy.
+    pub async fn get_for_agreement(
+        &self,
+        agreement_id: String,
+        owner_id: NodeId,
+        payment_platform: String,
+        address: String,
+    ) -> DbResult<Option<(AcceptancePolicy, Allocation)>> {
+        readonly_transaction(self.pool, move |conn| {
+            let invoice_ids: Vec<String> = invoice_dsl::pay_invoice
+                .filter(invoice_dsl::owner_id.eq(owner_id))
+                .filter(invoice_dsl::agreement_id.eq(&agreement_id))
+                .select(invoice_dsl::id)
+                .load(conn)?;
+            let debit_note_ids: Vec<String> = debit_note_dsl::pay_debit_note
+                .inner_join(
+                    activity_dsl::pay_activity.on(activity_dsl::id
+                        .eq(debit_note_dsl::activity_id)
+                        .and(activity_dsl::owner_id.eq(debit_note_dsl::owner_id))),
+                )
+                .filter(debit_note_dsl::owner_id.eq(owner_id))
+                .filter(activity_dsl::agreement_id.eq(&agreement_id))
+                .select(debit_note_dsl::id)
+                .load(conn)?;
+            let paid_from: Option<String> = order_dsl::pay_order
+                .filter(order_dsl::payer_id.eq(owner_id))
+                .filter(
+                    order_dsl::invoice_id
+                        .eq_any(invoice_ids)
+                        .or(order_dsl::debit_note_id.eq_any(debit_note_ids)),
+                )
+                .select(order_dsl::allocation_id)
+                .first(conn)
+                .optional()?;
+
+            let allocation_id = match paid_from {
+                Some(allocation_id) => allocation_id,
+                None => {
+                    let now = Utc::now().naive_utc();
+                    let candidates: Vec<String> = allocation_dsl::pay_allocation
+                        .filter(allocation_dsl::owner_id.eq(owner_id))
+                        .filter(allocation_dsl::payment_platform.eq(payment_platform))
+                        .filter(allocation_dsl::address.eq(address))
+                        .filter(allocation_dsl::released.eq(false))
+                        .filter(
+                            allocation_dsl::timeout
+                                .is_null()
+                                .or(allocation_dsl::timeout.gt(now)),
+                        )
+                        .select(allocation_dsl::id)
+                        .load(conn)?;
+                    match candidates.as_slice() {
+                        [allocation_id] => allocation_id.clone(),
+                        _ => return Ok(None),
+                    }
+                }
+            };
+
+            let policy: Option<(PolicyReadObj, AllocationReadObj)> = dsl::pay_acceptance_policy
+                .inner_join(allocation_dsl::pay_allocation)
+                .filter(dsl::owner_id.eq(owner_id))
+                .filter(dsl::allocation_id.eq(allocation_id))
+                .filter(allocation_dsl::released.eq(false))
+                .first(conn)
+                .optional()?;
+            Ok(policy.map(|(policy, allocation)| (policy.into(), allocation.into())))
+        })
+        .await
+    }
+
+    pub async fn record_decision(
+        &self,
+        decision: AcceptanceDecision,
+        owner_id: NodeId,
+    ) -> DbResult<()> {
+        do_with_transaction(self.pool, move |conn| {
+            diesel::insert_into(decision_dsl::pay_acceptance_decision)
+                .values(DecisionWriteObj::new(decision, owner_id))
+                .execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn get_invoice_decision(
+        &self,
+        invoice_id: String,
+        owner_id: NodeId,
+    ) -> DbResult<Option<AcceptanceDecision>> {
+        readonly_transaction(self.pool, move |conn| {
+            let decision: Option<DecisionReadObj> = decision_dsl::pay_acceptance_decision
+                .filter(decision_dsl::owner_id.eq(owner_id))
+                .filter(decision_dsl::invoice_id.eq(invoice_id))
+                .first(conn)
+                .optional()?;
+            Ok(decision.map(Into::into))
+        })
+        .await
+    }
+
+    pub async fn get_debit_note_decision(
+        &self,
+        debit_note_id: String,
+        owner_id: NodeId,
+    ) -> DbResult<Option<AcceptanceDecision>> {
+        readonly_transaction(self.pool, move |conn| {
+            let decision: Option<DecisionReadObj> = decision_dsl::pay_acceptance_decision
+                .filter(decision_dsl::owner_id.eq(owner_id))
+                .filter(decision_dsl::debit_note_id.eq(debit_note_id))
+                .first(conn)
+                .optional()?;
+            Ok(decision.map(Into::into))
+        })
+        .await
+    }
+}
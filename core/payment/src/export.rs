@@ -0,0 +1,469 @@
+use crate::dao::{ActivityDao, AgreementDao, DebitNoteDao, InvoiceDao, PaymentDao};
+use crate::error::{DbError, DbResult};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use ya_client_model::NodeId;
+use ya_core_model::payment::local::{
+    AccountingReport, ActivityReport, AgreementReport, DocumentEntry, PaymentEntry,
+};
+use ya_persistence::executor::DbExecutor;
+use ya_persistence::types::Role;
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            _ => Err(format!("Unknown export format: {}", s)),
+        }
+    }
+}
+
+#[derive(Default)]
+struct AgreementDocuments {
+    invoices: Vec<DocumentEntry>,
+    debit_notes: BTreeMap<String, Vec<DocumentEntry>>,
+    payments: Vec<PaymentEntry>,
+}
+
+/// Collects documents and payments issued after `since` and not later than `until`.
+pub async fn export(
+    db: &DbExecutor,
+    node_id: NodeId,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> DbResult<AccountingReport> {
+    let after = Some(since.naive_utc());
+    let mut documents = BTreeMap::<String, AgreementDocuments>::new();
+
+    let invoices = db
+        .as_dao::<InvoiceDao>()
+        .get_for_node_id(node_id, after, None)
+        .await?;
+    for invoice in invoices.into_iter().filter(|i| i.timestamp <= until) {
+        documents
+            .entry(invoice.agreement_id)
+            .or_default()
+            .invoices
+            .push(DocumentEntry {
+                id: invoice.invoice_id,
+                status: invoice.status,
+                amount: invoice.amount,
+                timestamp: invoice.timestamp,
+            });
+    }
+
+    let debit_notes = db
+        .as_dao::<DebitNoteDao>()
+        .get_for_node_id(node_id, after, None)
+        .await?;
+    for debit_note in debit_notes.into_iter().filter(|d| d.timestamp <= until) {
+        documents
+            .entry(debit_note.agreement_id)
+            .or_default()
+            .debit_notes
+            .entry(debit_note.activity_id)
+            .or_default()
+            .push(DocumentEntry {
+                id: debit_note.debit_note_id,
+                status: debit_note.status,
+                amount: debit_note.total_amount_due,
+                timestamp: debit_note.timestamp,
+            });
+    }
+
+    let payments = db
+        .as_dao::<PaymentDao>()
+        .get_for_node_id(node_id, after, None, None)
+        .await?;
+    let activity_dao: ActivityDao = db.as_dao();
+    let mut activity_agreements = HashMap::<String, String>::new();
+    for payment in payments.into_iter().filter(|p| p.timestamp <= until) {
+        let transaction_id = match base64::decode(&payment.details) {
+            Ok(details) => format!("0x{}", hex::encode(details)),
+            Err(_) => payment.details.clone(),
+        };
+        for agreement_payment in payment.agreement_payments {
+            documents
+                .entry(agreement_payment.agreement_id)
+                .or_default()
+                .payments
+                .push(PaymentEntry {
+                    payment_id: payment.payment_id.clone(),
+                    activity_id: None,
+                    allocation_id: agreement_payment.allocation_id,
+                    amount: agreement_payment.amount,
+                    transaction_id: transaction_id.clone(),
+                    timestamp: payment.timestamp,
+                });
+        }
+        for activity_payment in payment.activity_payments {
+            let activity_id = activity_payment.activity_id;
+            let agreement_id = match activity_agreements.get(&activity_id).cloned() {
+                Some(agreement_id) => agreement_id,
+                None => match activity_dao.get(activity_id.clone(), node_id).await? {
+                    Some(activity) => {
+                        activity_agreements
+                            .insert(activity_id.clone(), activity.agreement_id.clone());
+                        activity.agreement_id
+                    }
+                    None => {
+                        log::warn!(
+                            "Payment {} refers to unknown activity {}",
+                            payment.payment_id,
+                            activity_id
+                        );
+                        continue;
+                    }
+                },
+            };
+            let agreement_documents = documents.entry(agreement_id).or_default();
+            agreement_documents
+                .debit_notes
+                .entry(activity_id.clone())
+                .or_default();
+            agreement_documents.payments.push(PaymentEntry {
+                payment_id: payment.payment_id.clone(),
+                activity_id: Some(activity_id),
+                allocation_id: activity_payment.allocation_id,
+                amount: activity_payment.amount,
+                transaction_id: transaction_id.clone(),
+                timestamp: payment.timestamp,
+            });
+        }
+    }
+
+    let agreement_dao: AgreementDao = db.as_dao();
+    let mut agreements = vec![];
+    for (agreement_id, documents) in documents {
+        let agreement = match agreement_dao.get(agreement_id.clone(), node_id).await? {
+            Some(agreement) => agreement,
+            None => {
+                log::warn!("Skipping documents of unknown agreement {}", agreement_id);
+                continue;
+            }
+        };
+        let mut activities = vec![];
+        for (activity_id, debit_notes) in documents.debit_notes {
+            let activity = activity_dao
+                .get(activity_id.clone(), node_id)
+                .await?
+                .ok_or_else(|| DbError::Query(format!("Unknown activity {}", activity_id)))?;
+            activities.push(ActivityReport {
+                activity_id,
+                total_amount_due: activity.total_amount_due.into(),
+                total_amount_accepted: activity.total_amount_accepted.into(),
+                total_amount_paid: activity.total_amount_paid.into(),
+                debit_notes,
+            });
+        }
+        agreements.push(AgreementReport {
+            agreement_id,
+            role: match agreement.role {
+                Role::Provider => "provider".to_string(),
+                Role::Requestor => "requestor".to_string(),
+            },
+            peer_id: agreement.peer_id,
+            payment_platform: agreement.payment_platform,
+            payer_addr: agreement.payer_addr,
+            payee_addr: agreement.payee_addr,
+            total_amount_due: agreement.total_amount_due.into(),
+            total_amount_accepted: agreement.total_amount_accepted.into(),
+            total_amount_paid: agreement.total_amount_paid.into(),
+            invoices: documents.invoices,
+            activities,
+            payments: documents.payments,
+        });
+    }
+
+    Ok(AccountingReport {
+        node_id,
+        since,
+        until,
+        agreements,
+    })
+}
+
+const CSV_COLUMNS: &[&str] = &[
+    "agreement_id",
+    "role",
+    "peer_id",
+    "payment_platform",
+    "payer_addr",
+    "payee_addr",
+    "activity_id",
+    "record_type",
+    "record_id",
+    "status",
+    "timestamp",
+    "amount",
+    "allocation_id",
+    "transaction_id",
+    "total_amount_due",
+    "total_amount_accepted",
+    "total_amount_paid",
+];
+
+/// One row per invoice, debit note and payment share. Totals are agreement's
+/// for invoices and agreement-level payments, and activity's otherwise.
+pub fn to_csv(report: &AccountingReport) -> String {
+    let mut csv = String::new();
+    push_row(
+        &mut csv,
+        CSV_COLUMNS.iter().map(ToString::to_string).collect(),
+    );
+
+    for agreement in &report.agreements {
+        let prefix = vec![
+            agreement.agreement_id.clone(),
+            agreement.role.clone(),
+            agreement.peer_id.to_string(),
+            agreement.payment_platform.clone(),
+            agreement.payer_addr.clone(),
+            agreement.payee_addr.clone(),
+        ];
+        let agreement_totals = totals(
+            &agreement.total_amount_due,
+            &agreement.total_amount_accepted,
+            &agreement.total_amount_paid,
+        );
+
+        for invoice in &agreement.invoices {
+            let record = vec![
+                String::new(),
+                "invoice".to_string(),
+                invoice.id.clone(),
+                String::from(invoice.status),
+                invoice.timestamp.to_rfc3339(),
+                invoice.amount.to_string(),
+                String::new(),
+                String::new(),
+            ];
+            push_row(&mut csv, [&prefix[..], &record, &agreement_totals].concat());
+        }
+        for activity in &agreement.activities {
+            let activity_totals = totals(
+                &activity.total_amount_due,
+                &activity.total_amount_accepted,
+                &activity.total_amount_paid,
+            );
+            for debit_note in &activity.debit_notes {
+                let record = vec![
+                    activity.activity_id.clone(),
+                    "debit_note".to_string(),
+                    debit_note.id.clone(),
+                    String::from(debit_note.status),
+                    debit_note.timestamp.to_rfc3339(),
+                    debit_note.amount.to_string(),
+                    String::new(),
+                    String::new(),
+                ];
+                push_row(&mut csv, [&prefix[..], &record, &activity_totals].concat());
+            }
+        }
+        for payment in &agreement.payments {
+            let payment_totals = payment
+                .activity_id
+                .as_ref()
+                .and_then(|id| agreement.activities.iter().find(|a| &a.activity_id == id))
+                .map(|a| {
+                    totals(
+                        &a.total_amount_due,
+                        &a.total_amount_accepted,
+                        &a.total_amount_paid,
+                    )
+                })
+                .unwrap_or_else(|| agreement_totals.clone());
+            let record = vec![
+                payment.activity_id.clone().unwrap_or_default(),
+                "payment".to_string(),
+                payment.payment_id.clone(),
+                String::new(),
+                payment.timestamp.to_rfc3339(),
+                payment.amount.to_string(),
+                payment.allocation_id.clone().unwrap_or_default(),
+                payment.transaction_id.clone(),
+            ];
+            push_row(&mut csv, [&prefix[..], &record, &payment_totals].concat());
+        }
+    }
+    csv
+}
+
+fn totals(due: &BigDecimal, accepted: &BigDecimal, paid: &BigDecimal) -> Vec<String> {
+    vec![due.to_string(), accepted.to_string(), paid.to_string()]
+}
+
+fn push_row(csv: &mut String, fields: Vec<String>) {
+    let fields: Vec<String> = fields.into_iter().map(escape).collect();
+    csv.push_str(&fields.join(","));
+    csv.push('\n');
+}
+
+fn escape(field: String) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ya_client_model::payment::DocumentStatus;
+
+    fn amount(s: &str) -> BigDecimal {
+        s.parse().unwrap()
+    }
+
+    fn timestamp() -> DateTime<Utc> {
+        "2021-01-10T12:00:00Z".parse().unwrap()
+    }
+
+    fn report() -> AccountingReport {
+        let document = |id: &str, value: &str| DocumentEntry {
+            id: id.to_string(),
+            status: DocumentStatus::Accepted,
+            amount: amount(value),
+            timestamp: timestamp(),
+        };
+        AccountingReport {
+            node_id: NodeId::from(&[0u8; 20][..]),
+            since: timestamp(),
+            until: timestamp(),
+            agreements: vec![AgreementReport {
+                agreement_id: "agreement-1".to_string(),
+                role: "provider".to_string(),
+                peer_id: NodeId::from(&[1u8; 20][..]),
+                payment_platform: "dummy-glm".to_string(),
+                payer_addr: "0xbabe".to_string(),
+                payee_addr: "0xcafe".to_string(),
+                total_amount_due: amount("10"),
+                total_amount_accepted: amount("10"),
+                total_amount_paid: amount("7"),
+                invoices: vec![document("invoice-1", "10")],
+                activities: vec![ActivityReport {
+                    activity_id: "activity-1".to_string(),
+                    total_amount_due: amount("6"),
+                    total_amount_accepted: amount("6"),
+                    total_amount_paid: amount("3"),
+                    debit_notes: vec![document("debit-note-1", "6")],
+                }],
+                payments: vec![
+                    PaymentEntry {
+                        payment_id: "payment-1".to_string(),
+                        activity_id: Some("activity-1".to_string()),
+                        allocation_id: None,
+                        amount: amount("3"),
+                        transaction_id: "tx \"1\", batch\n2".to_string(),
+                        timestamp: timestamp(),
+                    },
+                    PaymentEntry {
+                        payment_id: "payment-2".to_string(),
+                        activity_id: None,
+                        allocation_id: Some("allocation-1".to_string()),
+                        amount: amount("4"),
+                        transaction_id: "0x02".to_string(),
+                        timestamp: timestamp(),
+                    },
+                ],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("plain".to_string()), "plain");
+        assert_eq!(escape("a,b".to_string()), "\"a,b\"");
+        assert_eq!(escape("say \"hi\"".to_string()), "\"say \"\"hi\"\"\"");
+        assert_eq!(escape("a\nb".to_string()), "\"a\nb\"");
+        assert_eq!(escape("a\r\nb".to_string()), "\"a\r\nb\"");
+        assert_eq!(escape(String::new()), "");
+    }
+
+    /// Splits records and fields, unescaping quoted ones.
+    fn parse_csv(csv: &str) -> Vec<Vec<String>> {
+        let mut records = vec![];
+        let mut record = vec![];
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut chars = csv.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (c, quoted) {
+                ('"', true) if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                ('"', _) => quoted = !quoted,
+                (',', false) => record.push(std::mem::take(&mut field)),
+                ('\n', false) => {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+                (c, _) => field.push(c),
+            }
+        }
+        records
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let records = parse_csv(&to_csv(&report()));
+        assert_eq!(records.len(), 5);
+        assert!(records.iter().all(|r| r.len() == CSV_COLUMNS.len()));
+        assert_eq!(records[0], CSV_COLUMNS);
+
+        let accepted = String::from(DocumentStatus::Accepted);
+        let invoice = &records[1];
+        assert_eq!(invoice[0], "agreement-1");
+        assert_eq!(invoice[1], "provider");
+        assert_eq!(
+            invoice[6..10],
+            ["", "invoice", "invoice-1", accepted.as_str()]
+        );
+        assert_eq!(invoice[10], timestamp().to_rfc3339());
+        assert_eq!(invoice[11..], ["10", "", "", "10", "10", "7"]);
+
+        let debit_note = &records[2];
+        assert_eq!(
+            debit_note[6..10],
+            [
+                "activity-1",
+                "debit_note",
+                "debit-note-1",
+                accepted.as_str()
+            ]
+        );
+        assert_eq!(debit_note[11..], ["6", "", "", "6", "6", "3"]);
+
+        // Activity payment has activity's totals, agreement payment agreement's ones.
+        let activity_payment = &records[3];
+        assert_eq!(
+            activity_payment[6..10],
+            ["activity-1", "payment", "payment-1", ""]
+        );
+        assert_eq!(
+            activity_payment[11..],
+            ["3", "", "tx \"1\", batch\n2", "6", "6", "3"]
+        );
+        let agreement_payment = &records[4];
+        assert_eq!(agreement_payment[6..10], ["", "payment", "payment-2", ""]);
+        assert_eq!(
+            agreement_payment[11..],
+            ["4", "allocation-1", "0x02", "10", "10", "7"]
+        );
+    }
+}
@@ -8,11 +8,15 @@ use ya_service_api_interfaces::*;
 extern crate diesel;
 
 pub mod accounts;
+pub mod allocation_release;
 pub mod api;
 mod cli;
+pub mod config;
 pub mod dao;
 pub mod error;
+pub mod export;
 pub mod models;
+pub mod policy;
 pub mod processor;
 pub mod schema;
 pub mod service;
@@ -37,8 +41,11 @@ impl PaymentService {
     pub async fn gsb<Context: Provider<Self, DbExecutor>>(context: &Context) -> anyhow::Result<()> {
         let db: DbExecutor = context.component();
         db.apply_migration(migrations::run_with_output)?;
-        let processor = PaymentProcessor::new(db.clone());
-        self::service::bind_service(&db, processor);
+        let config = config::Config::from_env()?;
+        let processor = PaymentProcessor::new(db.clone()).with_batch_window(config.batch_window);
+        self::service::bind_service(&db, processor.clone());
+        processor.start_batching();
+        allocation_release::start_release_job(db.clone(), config.allocation_release_interval);
         Ok(())
     }
 
@@ -0,0 +1,82 @@
+use crate::error::{DbError, DbResult};
+use crate::schema::pay_allocation_event;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use ya_client_model::NodeId;
+
+/// Allocation events aren't part of the payment API model yet, so they're defined
+/// here, following `InvoiceEvent` and `DebitNoteEvent`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllocationEvent {
+    pub allocation_id: String,
+    pub event_date: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event_type: AllocationEventType,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "eventType")]
+pub enum AllocationEventType {
+    /// Allocation was released, because its timeout passed.
+    AllocationExpiredEvent,
+}
+
+impl AllocationEventType {
+    fn as_db_str(&self) -> &'static str {
+        match self {
+            AllocationEventType::AllocationExpiredEvent => "EXPIRED",
+        }
+    }
+
+    fn from_db_str(event_type: &str) -> DbResult<Self> {
+        match event_type {
+            "EXPIRED" => Ok(AllocationEventType::AllocationExpiredEvent),
+            other => Err(DbError::Integrity(format!(
+                "AllocationEvent type `{}` parsing failed",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "pay_allocation_event"]
+pub struct WriteObj {
+    pub allocation_id: String,
+    pub owner_id: NodeId,
+    pub event_type: String,
+}
+
+impl WriteObj {
+    pub fn new(allocation_id: String, owner_id: NodeId, event_type: AllocationEventType) -> Self {
+        Self {
+            allocation_id,
+            owner_id,
+            event_type: event_type.as_db_str().to_string(),
+        }
+    }
+}
+
+#[derive(Queryable, Debug, Identifiable)]
+#[table_name = "pay_allocation_event"]
+#[primary_key(allocation_id, event_type)]
+pub struct ReadObj {
+    pub allocation_id: String,
+    pub owner_id: NodeId,
+    pub event_type: String,
+    pub timestamp: NaiveDateTime,
+}
+
+impl TryFrom<ReadObj> for AllocationEvent {
+    type Error = DbError;
+
+    fn try_from(event: ReadObj) -> DbResult<Self> {
+        Ok(Self {
+            event_type: AllocationEventType::from_db_str(&event.event_type)?,
+            allocation_id: event.allocation_id,
+            event_date: Utc.from_utc_datetime(&event.timestamp),
+        })
+    }
+}
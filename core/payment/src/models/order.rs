@@ -1,4 +1,6 @@
 use crate::schema::pay_order;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
 use ya_client_model::NodeId;
 use ya_core_model::payment::local::{PaymentTitle, SchedulePayment};
 use ya_persistence::types::BigDecimalField;
@@ -18,6 +20,8 @@ pub struct WriteObj {
     pub debit_note_id: Option<String>,
     pub allocation_id: String,
     pub is_paid: bool,
+    pub driver_order_id: Option<String>,
+    pub due_date: Option<NaiveDateTime>,
 }
 
 #[derive(Queryable, Debug, Identifiable)]
@@ -35,19 +39,22 @@ pub struct ReadObj {
     pub debit_note_id: Option<String>,
     pub allocation_id: String,
     pub is_paid: bool,
+    pub driver_order_id: Option<String>, // None until sent to the driver
+    pub due_date: Option<NaiveDateTime>,
 
     pub agreement_id: Option<String>, // From invoice
     pub activity_id: Option<String>,  // From debit note
 }
 
 impl WriteObj {
-    pub fn new(msg: SchedulePayment, id: String, driver: String) -> Self {
+    /// `driver_order_id` is `None` for orders waiting to be sent in a batch.
+    pub fn new(msg: SchedulePayment, driver: String, driver_order_id: Option<String>) -> Self {
         let (invoice_id, debit_note_id) = match msg.title {
             PaymentTitle::DebitNote(title) => (None, Some(title.debit_note_id)),
             PaymentTitle::Invoice(title) => (Some(title.invoice_id), None),
         };
         Self {
-            id,
+            id: Uuid::new_v4().to_string(),
             driver,
             amount: msg.amount.into(),
             payee_id: msg.payee_id,
@@ -59,6 +66,8 @@ impl WriteObj {
             debit_note_id,
             allocation_id: msg.allocation_id,
             is_paid: false,
+            driver_order_id,
+            due_date: Some(msg.due_date.naive_utc()),
         }
     }
 }
@@ -0,0 +1,143 @@
+use crate::schema::{pay_acceptance_decision, pay_acceptance_policy};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use ya_client_model::NodeId;
+use ya_persistence::types::BigDecimalField;
+
+/// Rules for accepting debit notes and invoices paid from an allocation
+/// without requestor app's involvement.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AcceptancePolicy {
+    /// Debit notes and invoices are rejected, when total amount due for
+    /// their agreement exceeds this.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_amount_per_agreement: Option<BigDecimal>,
+    /// Debit notes and invoices are rejected, when they charge more than this
+    /// per hour since agreement approval (plus fixed price from agreement pricing).
+    /// Agreements, which pricing allows lower rate, are held to that rate instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_rate_per_hour: Option<BigDecimal>,
+    /// Accept anything, which can be paid from the allocation and passes
+    /// the other rules.
+    #[serde(default)]
+    pub accept_within_allocation: bool,
+}
+
+/// Outcome of applying `AcceptancePolicy` to a received debit note or invoice.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AcceptanceDecision {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invoice_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debit_note_id: Option<String>,
+    pub allocation_id: String,
+    pub accepted: bool,
+    /// Rule, which made the decision.
+    pub rule: String,
+    pub reason: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "pay_acceptance_policy"]
+pub struct PolicyWriteObj {
+    pub allocation_id: String,
+    pub owner_id: NodeId,
+    pub max_amount_per_agreement: Option<BigDecimalField>,
+    pub max_rate_per_hour: Option<BigDecimalField>,
+    pub accept_within_allocation: bool,
+}
+
+#[derive(Queryable, Debug, Identifiable)]
+#[table_name = "pay_acceptance_policy"]
+#[primary_key(allocation_id)]
+pub struct PolicyReadObj {
+    pub allocation_id: String,
+    pub owner_id: NodeId,
+    pub max_amount_per_agreement: Option<BigDecimalField>,
+    pub max_rate_per_hour: Option<BigDecimalField>,
+    pub accept_within_allocation: bool,
+    pub timestamp: NaiveDateTime,
+}
+
+impl PolicyWriteObj {
+    pub fn new(policy: AcceptancePolicy, allocation_id: String, owner_id: NodeId) -> Self {
+        Self {
+            allocation_id,
+            owner_id,
+            max_amount_per_agreement: policy.max_amount_per_agreement.map(Into::into),
+            max_rate_per_hour: policy.max_rate_per_hour.map(Into::into),
+            accept_within_allocation: policy.accept_within_allocation,
+        }
+    }
+}
+
+impl From<PolicyReadObj> for AcceptancePolicy {
+    fn from(policy: PolicyReadObj) -> Self {
+        Self {
+            max_amount_per_agreement: policy.max_amount_per_agreement.map(Into::into),
+            max_rate_per_hour: policy.max_rate_per_hour.map(Into::into),
+            accept_within_allocation: policy.accept_within_allocation,
+        }
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "pay_acceptance_decision"]
+pub struct DecisionWriteObj {
+    pub id: String,
+    pub owner_id: NodeId,
+    pub invoice_id: Option<String>,
+    pub debit_note_id: Option<String>,
+    pub allocation_id: String,
+    pub accepted: bool,
+    pub rule: String,
+    pub reason: String,
+}
+
+#[derive(Queryable, Debug, Identifiable)]
+#[table_name = "pay_acceptance_decision"]
+pub struct DecisionReadObj {
+    pub id: String,
+    pub owner_id: NodeId,
+    pub invoice_id: Option<String>,
+    pub debit_note_id: Option<String>,
+    pub allocation_id: String,
+    pub accepted: bool,
+    pub rule: String,
+    pub reason: String,
+    pub timestamp: NaiveDateTime,
+}
+
+impl DecisionWriteObj {
+    pub fn new(decision: AcceptanceDecision, owner_id: NodeId) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            owner_id,
+            invoice_id: decision.invoice_id,
+            debit_note_id: decision.debit_note_id,
+            allocation_id: decision.allocation_id,
+            accepted: decision.accepted,
+            rule: decision.rule,
+            reason: decision.reason,
+        }
+    }
+}
+
+impl From<DecisionReadObj> for AcceptanceDecision {
+    fn from(decision: DecisionReadObj) -> Self {
+        Self {
+            invoice_id: decision.invoice_id,
+            debit_note_id: decision.debit_note_id,
+            allocation_id: decision.allocation_id,
+            accepted: decision.accepted,
+            rule: decision.rule,
+            reason: decision.reason,
+            timestamp: Utc.from_utc_datetime(&decision.timestamp),
+        }
+    }
+}
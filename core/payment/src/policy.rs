@@ -0,0 +1,601 @@
+//! Automatic acceptance of debit notes and invoices according to `AcceptancePolicy`
+//! registered for an allocation. Policy applies to documents of agreements paid from
+//! the allocation (see `PolicyDao::get_for_agreement`).
+use bigdecimal::{BigDecimal, FromPrimitive};
+use chrono::{DateTime, TimeZone, Utc};
+use metrics::counter;
+use serde_json::Value;
+
+use ya_agreement_utils::agreement::expand;
+use ya_client_model::market::Agreement;
+use ya_client_model::payment::{Acceptance, DebitNote, Invoice};
+use ya_core_model::payment::local::{SchedulePayment, BUS_ID as LOCAL_SERVICE};
+use ya_core_model::payment::public::{AcceptDebitNote, AcceptInvoice, BUS_ID as PUBLIC_SERVICE};
+use ya_net::RemoteEndpoint;
+use ya_persistence::executor::DbExecutor;
+use ya_service_bus::{typed as bus, RpcEndpoint};
+
+use crate::dao::*;
+use crate::error::Error;
+use crate::models::policy::{AcceptanceDecision, AcceptancePolicy};
+
+pub const RULE_MAX_AMOUNT_PER_AGREEMENT: &str = "maxAmountPerAgreement";
+pub const RULE_MAX_RATE_PER_HOUR: &str = "maxRatePerHour";
+pub const RULE_ACCEPT_WITHIN_ALLOCATION: &str = "acceptWithinAllocation";
+pub const RULE_ALLOCATION_REMAINING_AMOUNT: &str = "allocationRemainingAmount";
+
+const AGREEMENT_EXPIRATION: &str = "/golem/srv/comp/expiration";
+const DURATION_USAGE: &str = "golem.usage.duration_sec";
+const CPU_USAGE: &str = "golem.usage.cpu_sec";
+
+/// Bounds of agreement cost derived from its linear pricing model.
+#[derive(Clone, Debug, Default)]
+pub struct Pricing {
+    pub fixed: BigDecimal,
+    /// `None`, when cost depends on counters other than duration and CPU time.
+    pub per_hour: Option<BigDecimal>,
+}
+
+impl Pricing {
+    pub fn from_agreement(agreement: &Agreement) -> Self {
+        Self::from_offer_properties(&expand(agreement.offer.properties.clone()))
+    }
+
+    pub fn from_offer_properties(properties: &Value) -> Self {
+        let value = |pointer: &str| properties.pointer(pointer).cloned();
+        let coeffs: Vec<f64> = value("/golem/com/pricing/model/linear/coeffs")
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default();
+        let usage: Vec<String> = value("/golem/com/usage/vector")
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default();
+        let threads: Option<f64> =
+            value("/golem/inf/cpu/threads").and_then(|v| serde_json::from_value(v).ok());
+
+        // Linear model has one coefficient per usage counter and fixed price at the end
+        if coeffs.len() != usage.len() + 1 {
+            return Pricing::default();
+        }
+        let fixed = coeffs[usage.len()];
+        let mut per_sec = Some(0.0);
+        for (counter, coeff) in usage.iter().zip(coeffs.iter()) {
+            per_sec = match (counter.as_str(), per_sec) {
+                (_, None) => None,
+                (_, _) if coeff.abs() < f64::EPSILON => per_sec,
+                (DURATION_USAGE, Some(rate)) => Some(rate + coeff),
+                (CPU_USAGE, Some(rate)) => threads.map(|threads| rate + coeff * threads),
+                _ => None,
+            };
+        }
+        Pricing {
+            fixed: BigDecimal::from_f64(fixed).unwrap_or_default(),
+            per_hour: per_sec.and_then(|rate| BigDecimal::from_f64(rate * 3600.0)),
+        }
+    }
+}
+
+/// Moment, until which agreement's cost is accounted. Document timestamps come from
+/// the provider, so receiver's clock is used, capped at agreement's expiration,
+/// after which the agreement is terminated.
+pub fn evaluation_time(agreement: &Agreement, now: DateTime<Utc>) -> DateTime<Utc> {
+    let expiration = expand(agreement.demand.properties.clone())
+        .pointer(AGREEMENT_EXPIRATION)
+        .and_then(|v| v.as_i64())
+        .map(|millis| Utc.timestamp_millis(millis));
+    match expiration {
+        Some(expiration) if expiration < now => expiration,
+        _ => now,
+    }
+}
+
+/// Amounts of a received document.
+pub struct Charge {
+    /// Amount due on the document. Cumulative for activity, in case of debit notes.
+    pub amount_due: BigDecimal,
+    /// Total amount due for the agreement, including the document.
+    pub agreement_amount_due: BigDecimal,
+    /// Amount, which would be scheduled for payment after acceptance.
+    pub amount_to_pay: BigDecimal,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Verdict {
+    pub accepted: bool,
+    pub rule: &'static str,
+    pub reason: String,
+}
+
+impl Verdict {
+    fn accept(rule: &'static str, reason: String) -> Self {
+        Self {
+            accepted: true,
+            rule,
+            reason,
+        }
+    }
+
+    fn reject(rule: &'static str, reason: String) -> Self {
+        Self {
+            accepted: false,
+            rule,
+            reason,
+        }
+    }
+}
+
+/// Rules are checked in order and the first violated one rejects the document.
+/// Returns `None`, when policy has no rules, i.e. document has to be accepted manually.
+pub fn evaluate(
+    policy: &AcceptancePolicy,
+    pricing: &Pricing,
+    remaining_amount: &BigDecimal,
+    charge: &Charge,
+    agreement_start: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Option<Verdict> {
+    let mut accepting_rule = None;
+
+    if let Some(max_amount) = &policy.max_amount_per_agreement {
+        if &charge.agreement_amount_due > max_amount {
+            return Some(Verdict::reject(
+                RULE_MAX_AMOUNT_PER_AGREEMENT,
+                format!(
+                    "Amount due for agreement {} exceeds {}",
+                    charge.agreement_amount_due, max_amount
+                ),
+            ));
+        }
+        accepting_rule = Some(Verdict::accept(
+            RULE_MAX_AMOUNT_PER_AGREEMENT,
+            format!("Amount due for agreement within {}", max_amount),
+        ));
+    }
+
+    if let Some(max_rate) = &policy.max_rate_per_hour {
+        let rate = match &pricing.per_hour {
+            Some(per_hour) if per_hour < max_rate => per_hour,
+            _ => max_rate,
+        };
+        let elapsed_secs = (now - agreement_start).num_seconds().max(0);
+        let hours = BigDecimal::from(elapsed_secs) / BigDecimal::from(3600);
+        let allowed = &pricing.fixed + rate * hours;
+        if charge.amount_due > allowed {
+            return Some(Verdict::reject(
+                RULE_MAX_RATE_PER_HOUR,
+                format!(
+                    "Amount due {} exceeds {} allowed after {}s at {} per hour",
+                    charge.amount_due, allowed, elapsed_secs, rate
+                ),
+            ));
+        }
+        accepting_rule = Some(Verdict::accept(
+            RULE_MAX_RATE_PER_HOUR,
+            format!("Amount due within {} per hour", rate),
+        ));
+    }
+
+    if policy.accept_within_allocation {
+        accepting_rule = Some(Verdict::accept(
+            RULE_ACCEPT_WITHIN_ALLOCATION,
+            "Amount due fits in allocation".to_string(),
+        ));
+    }
+
+    let verdict = accepting_rule?;
+    if &charge.amount_to_pay > remaining_amount {
+        return Some(Verdict::reject(
+            RULE_ALLOCATION_REMAINING_AMOUNT,
+            format!(
+                "Not enough funds. Allocated: {} Needed: {}",
+                remaining_amount, charge.amount_to_pay
+            ),
+        ));
+    }
+    Some(verdict)
+}
+
+fn decision(
+    verdict: Verdict,
+    allocation_id: String,
+    invoice_id: Option<String>,
+    debit_note_id: Option<String>,
+) -> AcceptanceDecision {
+    AcceptanceDecision {
+        invoice_id,
+        debit_note_id,
+        allocation_id,
+        accepted: verdict.accepted,
+        rule: verdict.rule.to_string(),
+        reason: verdict.reason,
+        timestamp: Utc::now(),
+    }
+}
+
+/// Accepts or rejects received debit note, if requestor registered a policy for it.
+pub async fn apply_to_debit_note(
+    db: DbExecutor,
+    debit_note: DebitNote,
+    agreement: Agreement,
+) -> Result<(), Error> {
+    let node_id = debit_note.recipient_id;
+    let pay_agreement = match db
+        .as_dao::<AgreementDao>()
+        .get(debit_note.agreement_id.clone(), node_id)
+        .await?
+    {
+        Some(agreement) => agreement,
+        None => return Ok(()),
+    };
+    let (policy, allocation) = match db
+        .as_dao::<PolicyDao>()
+        .get_for_agreement(
+            pay_agreement.id.clone(),
+            node_id,
+            pay_agreement.payment_platform.clone(),
+            pay_agreement.payer_addr.clone(),
+        )
+        .await?
+    {
+        Some(policy) => policy,
+        None => return Ok(()),
+    };
+    let activity = match db
+        .as_dao::<ActivityDao>()
+        .get(debit_note.activity_id.clone(), node_id)
+        .await?
+    {
+        Some(activity) => activity,
+        None => return Ok(()),
+    };
+
+    let charge = Charge {
+        amount_due: debit_note.total_amount_due.clone(),
+        agreement_amount_due: pay_agreement.total_amount_due.0.clone(),
+        amount_to_pay: &debit_note.total_amount_due - &activity.total_amount_scheduled.0,
+    };
+    let verdict = match evaluate(
+        &policy,
+        &Pricing::from_agreement(&agreement),
+        &allocation.remaining_amount,
+        &charge,
+        agreement.approved_date.unwrap_or(agreement.timestamp),
+        evaluation_time(&agreement, Utc::now()),
+    ) {
+        Some(verdict) => verdict,
+        None => return Ok(()),
+    };
+
+    let debit_note_id = debit_note.debit_note_id.clone();
+    let allocation_id = allocation.allocation_id;
+    if verdict.accepted {
+        let issuer_id = debit_note.issuer_id;
+        let acceptance = Acceptance {
+            total_amount_accepted: debit_note.total_amount_due.clone(),
+            allocation_id: allocation_id.clone(),
+        };
+        let accept_msg = AcceptDebitNote::new(debit_note_id.clone(), acceptance, issuer_id);
+        let schedule_msg = SchedulePayment::from_debit_note(
+            debit_note,
+            allocation_id.clone(),
+            charge.amount_to_pay,
+        );
+        ya_net::from(node_id)
+            .to(issuer_id)
+            .service(PUBLIC_SERVICE)
+            .call(accept_msg)
+            .await??;
+        if let Some(msg) = schedule_msg {
+            bus::service(LOCAL_SERVICE).send(msg).await??;
+        }
+        db.as_dao::<DebitNoteDao>()
+            .accept(debit_note_id.clone(), node_id)
+            .await?;
+        counter!("payment.debit_notes.requestor.accepted", 1);
+    }
+
+    log::info!(
+        "Debit note {} {} by rule {}: {}",
+        debit_note_id,
+        if verdict.accepted {
+            "accepted"
+        } else {
+            "rejected"
+        },
+        verdict.rule,
+        verdict.reason
+    );
+    let decision = decision(verdict, allocation_id, None, Some(debit_note_id));
+    db.as_dao::<PolicyDao>()
+        .record_decision(decision, node_id)
+        .await?;
+    Ok(())
+}
+
+/// Accepts or rejects received invoice, if requestor registered a policy for it.
+pub async fn apply_to_invoice(
+    db: DbExecutor,
+    invoice: Invoice,
+    agreement: Agreement,
+) -> Result<(), Error> {
+    let node_id = invoice.recipient_id;
+    let pay_agreement = match db
+        .as_dao::<AgreementDao>()
+        .get(invoice.agreement_id.clone(), node_id)
+        .await?
+    {
+        Some(agreement) => agreement,
+        None => return Ok(()),
+    };
+    let (policy, allocation) = match db
+        .as_dao::<PolicyDao>()
+        .get_for_agreement(
+            pay_agreement.id.clone(),
+            node_id,
+            pay_agreement.payment_platform.clone(),
+            pay_agreement.payer_addr.clone(),
+        )
+        .await?
+    {
+        Some(policy) => policy,
+        None => return Ok(()),
+    };
+
+    let charge = Charge {
+        amount_due: invoice.amount.clone(),
+        agreement_amount_due: invoice.amount.clone(),
+        amount_to_pay: &invoice.amount - &pay_agreement.total_amount_scheduled.0,
+    };
+    let verdict = match evaluate(
+        &policy,
+        &Pricing::from_agreement(&agreement),
+        &allocation.remaining_amount,
+        &charge,
+        agreement.approved_date.unwrap_or(agreement.timestamp),
+        evaluation_time(&agreement, Utc::now()),
+    ) {
+        Some(verdict) => verdict,
+        None => return Ok(()),
+    };
+
+    let invoice_id = invoice.invoice_id.clone();
+    let allocation_id = allocation.allocation_id;
+    if verdict.accepted {
+        let issuer_id = invoice.issuer_id;
+        let acceptance = Acceptance {
+            total_amount_accepted: invoice.amount.clone(),
+            allocation_id: allocation_id.clone(),
+        };
+        let accept_msg = AcceptInvoice::new(invoice_id.clone(), acceptance, issuer_id);
+        let schedule_msg =
+            SchedulePayment::from_invoice(invoice, allocation_id.clone(), charge.amount_to_pay);
+        ya_net::from(node_id)
+            .to(issuer_id)
+            .service(PUBLIC_SERVICE)
+            .call(accept_msg)
+            .await??;
+        if let Some(msg) = schedule_msg {
+            bus::service(LOCAL_SERVICE).send(msg).await??;
+        }
+        db.as_dao::<InvoiceDao>()
+            .accept(invoice_id.clone(), node_id)
+            .await?;
+        counter!("payment.invoices.requestor.accepted", 1);
+    }
+
+    log::info!(
+        "Invoice {} {} by rule {}: {}",
+        invoice_id,
+        if verdict.accepted {
+            "accepted"
+        } else {
+            "rejected"
+        },
+        verdict.rule,
+        verdict.reason
+    );
+    let decision = decision(verdict, allocation_id, Some(invoice_id), None);
+    db.as_dao::<PolicyDao>()
+        .record_decision(decision, node_id)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use serde_json::json;
+
+    fn pricing() -> Pricing {
+        // 0.1 per hour of duration, 0.36 per CPU hour, on 2 threads, 1.0 fixed
+        Pricing::from_offer_properties(&json!({
+            "golem": {
+                "com": {
+                    "pricing": { "model": { "linear": { "coeffs": [0.0001, 0.00002777777777777778, 1.0] } } },
+                    "usage": { "vector": [CPU_USAGE, DURATION_USAGE] },
+                },
+                "inf": { "cpu": { "threads": 2 } },
+            }
+        }))
+    }
+
+    fn approved_agreement(approved: DateTime<Utc>, expiration: DateTime<Utc>) -> Agreement {
+        use ya_client_model::market::{agreement::State, Demand, Offer};
+        Agreement {
+            agreement_id: "agreement-1".to_string(),
+            demand: Demand {
+                properties: json!({ "golem.srv.comp.expiration": expiration.timestamp_millis() }),
+                constraints: "".to_string(),
+                demand_id: "".to_string(),
+                requestor_id: "0xbabe000000000000000000000000000000000000"
+                    .parse()
+                    .unwrap(),
+                timestamp: approved,
+            },
+            offer: Offer {
+                properties: json!({}),
+                constraints: "".to_string(),
+                offer_id: "".to_string(),
+                provider_id: "0xcafe000000000000000000000000000000000000"
+                    .parse()
+                    .unwrap(),
+                timestamp: approved,
+            },
+            valid_to: approved,
+            approved_date: Some(approved),
+            state: State::Approved,
+            timestamp: approved,
+            app_session_id: None,
+            proposed_signature: None,
+            approved_signature: None,
+            committed_signature: None,
+        }
+    }
+
+    fn charge(amount_due: u32, agreement_amount_due: u32) -> Charge {
+        Charge {
+            amount_due: amount_due.into(),
+            agreement_amount_due: agreement_amount_due.into(),
+            amount_to_pay: amount_due.into(),
+        }
+    }
+
+    #[test]
+    fn test_pricing_per_hour() {
+        let pricing = pricing();
+        assert_eq!(pricing.fixed, BigDecimal::from(1));
+        let per_hour = pricing.per_hour.unwrap();
+        assert!(
+            (per_hour - BigDecimal::from_f64(0.82).unwrap()).abs()
+                < BigDecimal::from_f64(1e-6).unwrap()
+        );
+
+        let unknown_counter = Pricing::from_offer_properties(&json!({
+            "golem": { "com": {
+                "pricing": { "model": { "linear": { "coeffs": [0.1, 0.0] } } },
+                "usage": { "vector": ["golem.usage.storage_gib"] },
+            }}
+        }));
+        assert!(unknown_counter.per_hour.is_none());
+    }
+
+    #[test]
+    fn test_empty_policy_makes_no_decision() {
+        let now = Utc::now();
+        let policy = AcceptancePolicy::default();
+        let remaining = BigDecimal::from(100);
+        assert!(evaluate(&policy, &pricing(), &remaining, &charge(1, 1), now, now).is_none());
+    }
+
+    #[test]
+    fn test_rules_order() {
+        let now = Utc::now();
+        let started = now - Duration::hours(10);
+        let policy = AcceptancePolicy {
+            max_amount_per_agreement: Some(20.into()),
+            max_rate_per_hour: Some(1.into()),
+            accept_within_allocation: true,
+        };
+        let remaining = BigDecimal::from(8);
+
+        // Pricing allows 1 + 10h * 0.82
+        let verdict = evaluate(&policy, &pricing(), &remaining, &charge(5, 5), started, now);
+        assert_eq!(verdict.unwrap().rule, RULE_ACCEPT_WITHIN_ALLOCATION);
+        let verdict = evaluate(
+            &policy,
+            &pricing(),
+            &remaining,
+            &charge(10, 10),
+            started,
+            now,
+        );
+        assert_eq!(verdict.unwrap().rule, RULE_MAX_RATE_PER_HOUR);
+        let verdict = evaluate(
+            &policy,
+            &pricing(),
+            &remaining,
+            &charge(5, 21),
+            started,
+            now,
+        );
+        assert_eq!(verdict.unwrap().rule, RULE_MAX_AMOUNT_PER_AGREEMENT);
+
+        let verdict =
+            evaluate(&policy, &pricing(), &remaining, &charge(9, 9), started, now).unwrap();
+        assert!(!verdict.accepted);
+        assert_eq!(verdict.rule, RULE_ALLOCATION_REMAINING_AMOUNT);
+    }
+
+    #[test]
+    fn test_limit_without_accept_within_allocation() {
+        let now = Utc::now();
+        let policy = AcceptancePolicy {
+            max_amount_per_agreement: Some(20.into()),
+            ..Default::default()
+        };
+        let remaining = BigDecimal::from(100);
+        let verdict = evaluate(
+            &policy,
+            &Pricing::default(),
+            &remaining,
+            &charge(5, 5),
+            now,
+            now,
+        )
+        .unwrap();
+        assert!(verdict.accepted);
+        assert_eq!(verdict.rule, RULE_MAX_AMOUNT_PER_AGREEMENT);
+    }
+
+    /// Provider can't raise allowed amount by sending invoice with timestamp in the future.
+    #[test]
+    fn test_invoice_timestamp_in_future() {
+        let now = Utc::now();
+        let started = now - Duration::hours(10);
+        let agreement = approved_agreement(started, now + Duration::hours(200));
+        let invoice_timestamp = now + Duration::hours(100);
+        let policy = AcceptancePolicy {
+            max_rate_per_hour: Some(1.into()),
+            ..Default::default()
+        };
+        let remaining = BigDecimal::from(1000);
+
+        // Accepted, if provider's timestamp was trusted: 1 + 110h * 0.82
+        let verdict = evaluate(
+            &policy,
+            &pricing(),
+            &remaining,
+            &charge(50, 50),
+            started,
+            invoice_timestamp,
+        )
+        .unwrap();
+        assert!(verdict.accepted);
+
+        let verdict = evaluate(
+            &policy,
+            &pricing(),
+            &remaining,
+            &charge(50, 50),
+            started,
+            evaluation_time(&agreement, now),
+        )
+        .unwrap();
+        assert!(!verdict.accepted);
+        assert_eq!(verdict.rule, RULE_MAX_RATE_PER_HOUR);
+    }
+
+    #[test]
+    fn test_evaluation_time_capped_at_expiration() {
+        let now = Utc::now();
+        let expiration = now - Duration::hours(1);
+        let agreement = approved_agreement(now - Duration::hours(10), expiration);
+        assert_eq!(
+            evaluation_time(&agreement, now).timestamp_millis(),
+            expiration.timestamp_millis()
+        );
+
+        let agreement = approved_agreement(now - Duration::hours(10), now + Duration::hours(1));
+        assert_eq!(evaluation_time(&agreement, now), now);
+    }
+}
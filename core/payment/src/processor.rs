@@ -5,12 +5,15 @@ use crate::error::processor::{
 };
 use crate::models::order::ReadObj as DbOrder;
 use bigdecimal::{BigDecimal, Zero};
+use chrono::{DateTime, TimeZone, Utc};
 use futures::lock::Mutex;
-use metrics::counter;
+use metrics::{counter, timing};
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use ya_client_model::payment::{Account, ActivityPayment, AgreementPayment, Payment};
+use ya_client_model::NodeId;
 use ya_core_model::driver::{
     self, driver_bus_id, AccountMode, PaymentConfirmation, PaymentDetails, ValidateAllocation,
 };
@@ -263,10 +266,65 @@ impl DriverRegistry {
     }
 }
 
+/// Orders with the same key are paid with a single driver transfer. Batches don't mix
+/// allocations, so each activity and agreement paid by a transfer has a single allocation.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+struct BatchKey {
+    driver: String,
+    payer_id: NodeId,
+    payee_id: NodeId,
+    payer_addr: String,
+    payee_addr: String,
+    platform: String,
+    allocation_id: String,
+}
+
+impl From<&DbOrder> for BatchKey {
+    fn from(order: &DbOrder) -> Self {
+        Self {
+            driver: order.driver.clone(),
+            payer_id: order.payer_id,
+            payee_id: order.payee_id,
+            payer_addr: order.payer_addr.clone(),
+            payee_addr: order.payee_addr.clone(),
+            platform: order.payment_platform.clone(),
+            allocation_id: order.allocation_id.clone(),
+        }
+    }
+}
+
+fn group_batches(orders: Vec<DbOrder>) -> HashMap<BatchKey, Vec<DbOrder>> {
+    let mut batches: HashMap<BatchKey, Vec<DbOrder>> = HashMap::new();
+    for order in orders {
+        batches
+            .entry(BatchKey::from(&order))
+            .or_default()
+            .push(order);
+    }
+    batches
+}
+
+/// Total amount of the batch and its due date, which is the earliest due date of its orders.
+fn batch_payment(orders: &[DbOrder]) -> (BigDecimal, DateTime<Utc>) {
+    let amount = orders.iter().map(|order| &order.amount.0).sum();
+    let due_date = orders
+        .iter()
+        .filter_map(|order| order.due_date)
+        .min()
+        .map(|due_date| Utc.from_utc_datetime(&due_date))
+        .unwrap_or_else(Utc::now);
+    (amount, due_date)
+}
+
+/// Leftovers from previous runs are still sent, when batching is disabled.
+const PENDING_ORDERS_INTERVAL: Duration = Duration::from_secs(60);
+
 #[derive(Clone)]
 pub struct PaymentProcessor {
     db_executor: DbExecutor,
     registry: Arc<Mutex<DriverRegistry>>,
+    batch_window: Duration,
+    batch_lock: Arc<Mutex<()>>,
 }
 
 impl PaymentProcessor {
@@ -274,9 +332,56 @@ impl PaymentProcessor {
         Self {
             db_executor,
             registry: Default::default(),
+            batch_window: Duration::from_secs(0),
+            batch_lock: Default::default(),
         }
     }
 
+    pub fn with_batch_window(mut self, batch_window: Duration) -> Self {
+        self.batch_window = batch_window;
+        self
+    }
+
+    fn is_batching(&self) -> bool {
+        self.batch_window > Duration::from_secs(0)
+    }
+
+    /// Periodically sends orders waiting for their batch.
+    pub fn start_batching(&self) {
+        let processor = self.clone();
+        actix_rt::spawn(async move {
+            match processor
+                .db_executor
+                .as_dao::<OrderDao>()
+                .get_claimed()
+                .await
+            {
+                Ok(orders) if !orders.is_empty() => log::warn!(
+                    "{} orders were claimed by batches interrupted before they were scheduled. \
+                     They are not sent again automatically, check if they were paid: {:?}",
+                    orders.len(),
+                    orders.iter().map(|order| &order.id).collect::<Vec<_>>()
+                ),
+                Ok(_) => (),
+                Err(e) => log::error!("Failed to check interrupted payment batches: {}", e),
+            }
+        });
+
+        let processor = self.clone();
+        let interval = match processor.is_batching() {
+            true => processor.batch_window,
+            false => PENDING_ORDERS_INTERVAL,
+        };
+        actix_rt::spawn(async move {
+            loop {
+                tokio::time::delay_for(interval).await;
+                if let Err(e) = processor.send_batches().await {
+                    log::error!("Failed to send batched payments: {}", e);
+                }
+            }
+        });
+    }
+
     pub async fn register_driver(&self, msg: RegisterDriver) -> Result<(), RegisterDriverError> {
         self.registry.lock().await.register_driver(msg)
     }
@@ -342,25 +447,38 @@ impl PaymentProcessor {
         let mut activity_payments = vec![];
         let mut agreement_payments = vec![];
         for order in orders.iter() {
-            let amount = order.amount.clone().into();
+            let amount: BigDecimal = order.amount.clone().into();
+            // Batched orders may pay several documents of the same activity or agreement.
+            // They share the allocation (see `BatchKey`), so merged entries keep it.
             match (order.activity_id.clone(), order.agreement_id.clone()) {
-                (Some(activity_id), None) => activity_payments.push(ActivityPayment {
-                    activity_id,
-                    amount,
-                    allocation_id: Some(order.allocation_id.clone()),
-                }),
-                (None, Some(agreement_id)) => agreement_payments.push(AgreementPayment {
-                    agreement_id,
-                    amount,
-                    allocation_id: Some(order.allocation_id.clone()),
-                }),
+                (Some(activity_id), None) => match activity_payments
+                    .iter_mut()
+                    .find(|p| p.activity_id == activity_id)
+                {
+                    Some(payment) => payment.amount += amount,
+                    None => activity_payments.push(ActivityPayment {
+                        activity_id,
+                        amount,
+                        allocation_id: Some(order.allocation_id.clone()),
+                    }),
+                },
+                (None, Some(agreement_id)) => match agreement_payments
+                    .iter_mut()
+                    .find(|p| p.agreement_id == agreement_id)
+                {
+                    Some(payment) => payment.amount += amount,
+                    None => agreement_payments.push(AgreementPayment {
+                        agreement_id,
+                        amount,
+                        allocation_id: Some(order.allocation_id.clone()),
+                    }),
+                },
                 _ => return NotifyPaymentError::invalid_order(&order),
             }
         }
 
-        // FIXME: This is a hack. Payment orders realized by a single transaction are not guaranteed
-        //        to have the same payer and payee IDs. Fixing this requires a major redesign of the
-        //        data model. Payments can no longer by assigned to a single payer and payee.
+        // Orders are batched per payer and payee ID (see `BatchKey`), so all orders realized
+        // by a single transaction share them.
         let payer_id = orders.get(0).unwrap().payer_id;
         let payee_id = orders.get(0).unwrap().payee_id;
 
@@ -390,11 +508,18 @@ impl PaymentProcessor {
 
         counter!("payment.amount.sent", ya_metrics::utils::cryptocurrency_to_u64(&msg.amount), "driver" => msg.driver);
         let msg = SendPayment(payment);
+        let start = Instant::now();
         ya_net::from(payer_id)
             .to(payee_id)
             .service(BUS_ID)
             .call(msg)
             .await??;
+        timing!(
+            "payment.payments.requestor.send.time",
+            start,
+            Instant::now()
+        );
+        counter!("payment.payments.requestor.sent", 1);
 
         // TODO: Implement re-sending mechanism in case SendPayment fails
 
@@ -409,6 +534,15 @@ impl PaymentProcessor {
             &msg.payer_addr,
             AccountMode::SEND,
         )?;
+        if self.is_batching() {
+            // Sent to the driver by `send_batches`
+            self.db_executor
+                .as_dao::<OrderDao>()
+                .create(msg, driver, None)
+                .await?;
+            return Ok(());
+        }
+
         let order_id = driver_endpoint(&driver)
             .send(driver::SchedulePayment::new(
                 amount,
@@ -421,9 +555,67 @@ impl PaymentProcessor {
 
         self.db_executor
             .as_dao::<OrderDao>()
-            .create(msg, order_id, driver)
+            .create(msg, driver, Some(order_id))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Sends orders waiting for their batch. Each batch is a single driver transfer
+    /// due at the earliest due date of its orders.
+    pub async fn send_batches(&self) -> Result<(), SchedulePaymentError> {
+        let _guard = self.batch_lock.lock().await;
+        let orders = self.db_executor.as_dao::<OrderDao>().get_pending().await?;
+        for (key, orders) in group_batches(orders) {
+            // Batch refused by the driver stays pending and is retried with the next one
+            if let Err(e) = self.send_batch(&key, orders).await {
+                log::error!("Failed to send batched payment {:?}: {}", key, e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn send_batch(
+        &self,
+        key: &BatchKey,
+        orders: Vec<DbOrder>,
+    ) -> Result<(), SchedulePaymentError> {
+        let dao = self.db_executor.as_dao::<OrderDao>();
+        let ids = orders.into_iter().map(|order| order.id).collect();
+        // Claimed orders are sent again only if the driver refuses the batch, so an
+        // interrupted batch never pays them twice.
+        let (batch_id, orders) = dao.claim_pending(ids, key.driver.clone()).await?;
+        if orders.is_empty() {
+            return Ok(());
+        }
+
+        let (amount, due_date) = batch_payment(&orders);
+        let scheduled = driver_endpoint(&key.driver)
+            .send(driver::SchedulePayment::new(
+                amount.clone(),
+                key.payer_addr.clone(),
+                key.payee_addr.clone(),
+                key.platform.clone(),
+                due_date,
+            ))
             .await?;
+        let order_id = match scheduled {
+            Ok(order_id) => order_id,
+            Err(e) => {
+                dao.release_batch(batch_id, key.driver.clone()).await?;
+                return Err(e.into());
+            }
+        };
 
+        log::debug!(
+            "Scheduled batched payment {} of {} for {} orders to {}",
+            order_id,
+            amount,
+            orders.len(),
+            key.payee_addr
+        );
+        dao.assign_driver_order(batch_id, key.driver.clone(), order_id)
+            .await?;
         Ok(())
     }
 
@@ -574,3 +766,75 @@ impl PaymentProcessor {
         Ok(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+
+    fn order(id: &str, payee_addr: &str, allocation_id: &str, amount: u32) -> DbOrder {
+        DbOrder {
+            id: id.to_string(),
+            driver: "dummy".to_string(),
+            amount: BigDecimal::from(amount).into(),
+            payee_id: NodeId::from(&[2u8; 20][..]),
+            payer_id: NodeId::from(&[1u8; 20][..]),
+            payee_addr: payee_addr.to_string(),
+            payer_addr: "0x01".to_string(),
+            payment_platform: "dummy-glm".to_string(),
+            invoice_id: Some(format!("invoice-{}", id)),
+            debit_note_id: None,
+            allocation_id: allocation_id.to_string(),
+            is_paid: false,
+            driver_order_id: None,
+            due_date: None,
+            agreement_id: Some("agreement".to_string()),
+            activity_id: None,
+        }
+    }
+
+    fn ids(orders: &[DbOrder]) -> Vec<&str> {
+        let mut ids: Vec<_> = orders.iter().map(|order| order.id.as_str()).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn test_orders_are_batched_per_payee_and_allocation() {
+        let batches = group_batches(vec![
+            order("a", "0x02", "alloc-1", 1),
+            order("b", "0x02", "alloc-1", 2),
+            order("c", "0x03", "alloc-1", 3),
+            order("d", "0x02", "alloc-2", 4),
+        ]);
+        assert_eq!(batches.len(), 3);
+
+        let mut batches: Vec<_> = batches.values().map(|orders| ids(orders)).collect();
+        batches.sort();
+        assert_eq!(batches, vec![vec!["a", "b"], vec!["c"], vec!["d"]]);
+    }
+
+    #[test]
+    fn test_batch_is_due_at_earliest_due_date() {
+        let now = Utc::now();
+        let mut orders = vec![
+            order("a", "0x02", "alloc-1", 1),
+            order("b", "0x02", "alloc-1", 2),
+            order("c", "0x02", "alloc-1", 3),
+        ];
+        orders[0].due_date = Some((now + ChronoDuration::hours(2)).naive_utc());
+        orders[1].due_date = Some((now + ChronoDuration::hours(1)).naive_utc());
+
+        let (amount, due_date) = batch_payment(&orders);
+        assert_eq!(amount, BigDecimal::from(6u32));
+        assert_eq!(
+            due_date,
+            Utc.from_utc_datetime(&orders[1].due_date.unwrap())
+        );
+
+        // Orders without due date are paid right away
+        orders.iter_mut().for_each(|order| order.due_date = None);
+        let (_, due_date) = batch_payment(&orders);
+        assert!(due_date <= Utc::now());
+    }
+}
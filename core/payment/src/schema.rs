@@ -1,3 +1,28 @@
+table! {
+    pay_acceptance_decision (id) {
+        id -> Text,
+        owner_id -> Text,
+        invoice_id -> Nullable<Text>,
+        debit_note_id -> Nullable<Text>,
+        allocation_id -> Text,
+        accepted -> Bool,
+        rule -> Text,
+        reason -> Text,
+        timestamp -> Timestamp,
+    }
+}
+
+table! {
+    pay_acceptance_policy (allocation_id) {
+        allocation_id -> Text,
+        owner_id -> Text,
+        max_amount_per_agreement -> Nullable<Text>,
+        max_rate_per_hour -> Nullable<Text>,
+        accept_within_allocation -> Bool,
+        timestamp -> Timestamp,
+    }
+}
+
 table! {
     pay_activity (id, owner_id) {
         id -> Text,
@@ -64,6 +89,15 @@ table! {
     }
 }
 
+table! {
+    pay_allocation_event (allocation_id, event_type) {
+        allocation_id -> Text,
+        owner_id -> Text,
+        event_type -> Text,
+        timestamp -> Timestamp,
+    }
+}
+
 table! {
     pay_debit_note (id, owner_id) {
         id -> Text,
@@ -169,6 +203,8 @@ table! {
         debit_note_id -> Nullable<Text>,
         allocation_id -> Text,
         is_paid -> Bool,
+        driver_order_id -> Nullable<Text>,
+        due_date -> Nullable<Timestamp>,
     }
 }
 
@@ -187,8 +223,10 @@ table! {
     }
 }
 
+joinable!(pay_acceptance_policy -> pay_allocation (allocation_id));
 joinable!(pay_activity_payment -> pay_allocation (allocation_id));
 joinable!(pay_agreement_payment -> pay_allocation (allocation_id));
+joinable!(pay_allocation_event -> pay_allocation (allocation_id));
 joinable!(pay_debit_note -> pay_document_status (status));
 joinable!(pay_debit_note_event -> pay_event_type (event_type));
 joinable!(pay_invoice -> pay_document_status (status));
@@ -196,11 +234,14 @@ joinable!(pay_invoice_event -> pay_event_type (event_type));
 joinable!(pay_order -> pay_allocation (allocation_id));
 
 allow_tables_to_appear_in_same_query!(
+    pay_acceptance_decision,
+    pay_acceptance_policy,
     pay_activity,
     pay_activity_payment,
     pay_agreement,
     pay_agreement_payment,
     pay_allocation,
+    pay_allocation_event,
     pay_debit_note,
     pay_debit_note_event,
     pay_debit_note_event_read,
@@ -34,6 +34,7 @@ mod local {
             .bind_with_processor(notify_payment)
             .bind_with_processor(get_status)
             .bind_with_processor(get_invoice_stats)
+            .bind_with_processor(export_documents)
             .bind_with_processor(get_accounts)
             .bind_with_processor(validate_allocation);
 
@@ -52,6 +53,9 @@ mod local {
         counter!("payment.invoices.provider.cancelled", 0);
         counter!("payment.invoices.provider.paid", 0);
         counter!("payment.invoices.provider.accepted", 0);
+        counter!("payment.allocations.requestor.expired", 0);
+        counter!("payment.payments.requestor.sent", 0);
+        counter!("payment.payments.provider.received", 0);
 
         counter!("payment.amount.received", 0, "platform" => "erc20-rinkeby-tglm");
         counter!("payment.amount.received", 0, "platform" => "erc20-mainnet-glm");
@@ -253,6 +257,17 @@ mod local {
         Ok(output_stats)
     }
 
+    async fn export_documents(
+        db: DbExecutor,
+        processor: PaymentProcessor,
+        _caller: String,
+        msg: ExportDocuments,
+    ) -> Result<AccountingReport, GenericError> {
+        crate::export::export(&db, msg.node_id, msg.since, msg.until)
+            .map_err(GenericError::new)
+            .await
+    }
+
     async fn validate_allocation(
         db: DbExecutor,
         processor: PaymentProcessor,
@@ -269,7 +284,8 @@ mod public {
     use super::*;
 
     use crate::dao::*;
-    use crate::error::DbError;
+    use crate::error::{DbError, Error};
+    use crate::policy;
     use crate::utils::*;
 
     use crate::error::processor::VerifyPaymentError;
@@ -294,6 +310,18 @@ mod public {
         log::debug!("Successfully bound payment public service to service bus");
     }
 
+    /// Applies requestor's acceptance policy in background, since accepting
+    /// calls back the issuer, who is waiting for our `Ack`. Acceptance can reach
+    /// the issuer before it marks the document as received, so `mark_received`
+    /// doesn't change status of already accepted documents.
+    fn spawn_policy(apply: impl Future<Output = Result<(), Error>> + 'static) {
+        actix_rt::spawn(async move {
+            if let Err(e) = apply.await {
+                log::warn!("Failed to apply acceptance policy: {}", e);
+            }
+        });
+    }
+
     // ************************** DEBIT NOTE **************************
 
     async fn send_debit_note(
@@ -326,6 +354,7 @@ mod public {
         }
 
         let node_id = agreement.requestor_id().clone();
+        let policy_args = (db.clone(), debit_note.clone(), agreement.clone());
         match async move {
             db.as_dao::<AgreementDao>()
                 .create_if_not_exists(agreement, node_id, Role::Requestor)
@@ -342,7 +371,11 @@ mod public {
         }
         .await
         {
-            Ok(_) => Ok(Ack {}),
+            Ok(_) => {
+                let (db, debit_note, agreement) = policy_args;
+                spawn_policy(policy::apply_to_debit_note(db, debit_note, agreement));
+                Ok(Ack {})
+            }
             Err(DbError::Query(e)) => return Err(SendError::BadRequest(e.to_string())),
             Err(e) => return Err(SendError::ServiceError(e.to_string())),
         }
@@ -464,6 +497,7 @@ mod public {
         }
 
         let node_id = agreement.requestor_id().clone();
+        let policy_args = (db.clone(), invoice.clone(), agreement.clone());
         match async move {
             db.as_dao::<AgreementDao>()
                 .create_if_not_exists(agreement, node_id, Role::Requestor)
@@ -487,7 +521,11 @@ mod public {
         }
         .await
         {
-            Ok(_) => Ok(Ack {}),
+            Ok(_) => {
+                let (db, invoice, agreement) = policy_args;
+                spawn_policy(policy::apply_to_invoice(db, invoice, agreement));
+                Ok(Ack {})
+            }
             Err(DbError::Query(e)) => return Err(SendError::BadRequest(e.to_string())),
             Err(e) => return Err(SendError::ServiceError(e.to_string())),
         }
@@ -607,6 +645,7 @@ mod public {
             Ok(_) => {
                 counter!("payment.amount.received", ya_metrics::utils::cryptocurrency_to_u64(&amount), "platform" => platform);
                 counter!("payment.invoices.provider.paid", num_paid_invoices);
+                counter!("payment.payments.provider.received", 1);
                 Ok(Ack {})
             }
             Err(e) => match e {
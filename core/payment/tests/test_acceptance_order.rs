@@ -0,0 +1,136 @@
+use diesel::RunQueryDsl;
+use tempdir::TempDir;
+
+use ya_client_model::payment::DocumentStatus;
+use ya_client_model::NodeId;
+use ya_payment::dao::{DebitNoteDao, InvoiceDao};
+use ya_persistence::executor::DbExecutor;
+
+const AGREEMENT_ID: &str = "agreement-1";
+const ACTIVITY_ID: &str = "activity-1";
+const DEBIT_NOTE_ID: &str = "debit-note-1";
+const INVOICE_ID: &str = "invoice-1";
+
+fn init_db(dir: &TempDir) -> DbExecutor {
+    let db = DbExecutor::from_data_dir(dir.path(), "yagna").unwrap();
+    db.apply_migration(ya_payment::migrations::run_with_output)
+        .unwrap();
+    db
+}
+
+fn payer_id() -> NodeId {
+    "0xbabe000000000000000000000000000000000000"
+        .parse()
+        .unwrap()
+}
+
+fn payee_id() -> NodeId {
+    "0xcafe000000000000000000000000000000000000"
+        .parse()
+        .unwrap()
+}
+
+/// Issued documents on provider's side, not yet marked as received.
+fn insert_issued_documents(db: &DbExecutor) {
+    let conn = db.conn().unwrap();
+    let queries = vec![
+        format!(
+            "INSERT INTO pay_agreement (id, owner_id, role, peer_id, payee_addr, payer_addr, \
+             payment_platform, total_amount_due, total_amount_accepted, total_amount_scheduled, \
+             total_amount_paid) VALUES ('{}', '{}', 'P', '{}', '{}', '{}', 'dummy-glm', \
+             '10', '0', '0', '0')",
+            AGREEMENT_ID,
+            payee_id(),
+            payer_id(),
+            payee_id(),
+            payer_id()
+        ),
+        format!(
+            "INSERT INTO pay_activity (id, owner_id, role, agreement_id, total_amount_due, \
+             total_amount_accepted, total_amount_scheduled, total_amount_paid) \
+             VALUES ('{}', '{}', 'P', '{}', '5', '0', '0', '0')",
+            ACTIVITY_ID,
+            payee_id(),
+            AGREEMENT_ID
+        ),
+        format!(
+            "INSERT INTO pay_debit_note (id, owner_id, role, activity_id, status, total_amount_due) \
+             VALUES ('{}', '{}', 'P', '{}', 'ISSUED', '5')",
+            DEBIT_NOTE_ID,
+            payee_id(),
+            ACTIVITY_ID
+        ),
+        format!(
+            "INSERT INTO pay_invoice (id, owner_id, role, agreement_id, status, amount, \
+             payment_due_date) VALUES ('{}', '{}', 'P', '{}', 'ISSUED', '10', '{}')",
+            INVOICE_ID,
+            payee_id(),
+            AGREEMENT_ID,
+            chrono::Utc::now().naive_utc()
+        ),
+    ];
+    for query in queries {
+        diesel::sql_query(query).execute(&*conn).unwrap();
+    }
+}
+
+/// Requestor's acceptance policy can accept documents, before `SendInvoice` and
+/// `SendDebitNote` calls return to the issuer. Marking them as received afterwards
+/// mustn't revert the acceptance.
+#[actix_rt::test]
+async fn test_acceptance_before_mark_received() {
+    let dir = TempDir::new("acceptance-order").unwrap();
+    let db = init_db(&dir);
+    insert_issued_documents(&db);
+
+    let debit_note_dao: DebitNoteDao = db.as_dao();
+    debit_note_dao
+        .accept(DEBIT_NOTE_ID.to_string(), payee_id())
+        .await
+        .unwrap();
+    debit_note_dao
+        .mark_received(DEBIT_NOTE_ID.to_string(), payee_id())
+        .await
+        .unwrap();
+    let debit_note = debit_note_dao
+        .get(DEBIT_NOTE_ID.to_string(), payee_id())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(debit_note.status, DocumentStatus::Accepted);
+
+    let invoice_dao: InvoiceDao = db.as_dao();
+    invoice_dao
+        .accept(INVOICE_ID.to_string(), payee_id())
+        .await
+        .unwrap();
+    invoice_dao
+        .mark_received(INVOICE_ID.to_string(), payee_id())
+        .await
+        .unwrap();
+    let invoice = invoice_dao
+        .get(INVOICE_ID.to_string(), payee_id())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(invoice.status, DocumentStatus::Accepted);
+}
+
+#[actix_rt::test]
+async fn test_mark_received_issued() {
+    let dir = TempDir::new("acceptance-order").unwrap();
+    let db = init_db(&dir);
+    insert_issued_documents(&db);
+
+    let invoice_dao: InvoiceDao = db.as_dao();
+    invoice_dao
+        .mark_received(INVOICE_ID.to_string(), payee_id())
+        .await
+        .unwrap();
+    let invoice = invoice_dao
+        .get(INVOICE_ID.to_string(), payee_id())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(invoice.status, DocumentStatus::Received);
+}
@@ -0,0 +1,118 @@
+use bigdecimal::BigDecimal;
+use chrono::{Duration, Utc};
+use tempdir::TempDir;
+
+use ya_client_model::payment::NewAllocation;
+use ya_client_model::NodeId;
+use ya_payment::dao::{AllocationDao, AllocationEventDao};
+use ya_payment::models::allocation_event::AllocationEventType;
+use ya_persistence::executor::DbExecutor;
+
+const PLATFORM: &str = "dummy-glm";
+const ADDRESS: &str = "0xd39a168f0480b8502c2531b2ffd8588c592d713a";
+
+fn init_db(dir: &TempDir) -> DbExecutor {
+    let db = DbExecutor::from_data_dir(dir.path(), "yagna").unwrap();
+    db.apply_migration(ya_payment::migrations::run_with_output)
+        .unwrap();
+    db
+}
+
+async fn create(
+    db: &DbExecutor,
+    owner_id: NodeId,
+    amount: u64,
+    timeout: Option<Duration>,
+) -> String {
+    let allocation = NewAllocation {
+        address: Some(ADDRESS.to_string()),
+        payment_platform: Some(PLATFORM.to_string()),
+        total_amount: BigDecimal::from(amount),
+        timeout: timeout.map(|timeout| Utc::now() + timeout),
+        make_deposit: false,
+    };
+    db.as_dao::<AllocationDao>()
+        .create(
+            allocation,
+            owner_id,
+            PLATFORM.to_string(),
+            ADDRESS.to_string(),
+        )
+        .await
+        .unwrap()
+}
+
+#[actix_rt::test]
+async fn test_expired_allocations_are_released() {
+    let dir = TempDir::new("payment").unwrap();
+    let db = init_db(&dir);
+    let dao = db.as_dao::<AllocationDao>();
+    let owner_id: NodeId = "0xbabe000000000000000000000000000000000000"
+        .parse()
+        .unwrap();
+
+    let unlimited = create(&db, owner_id, 1, None).await;
+    let valid = create(&db, owner_id, 10, Some(Duration::days(1))).await;
+    let expired = create(&db, owner_id, 100, Some(Duration::seconds(-1))).await;
+
+    // Expired allocation doesn't reserve funds, even before it's released
+    let remaining = dao
+        .total_remaining_allocation(PLATFORM.to_string(), ADDRESS.to_string())
+        .await
+        .unwrap();
+    assert_eq!(remaining, BigDecimal::from(11u64));
+    let reserved = dao
+        .get_for_address(PLATFORM.to_string(), ADDRESS.to_string())
+        .await
+        .unwrap();
+    assert_eq!(reserved.len(), 2);
+
+    let released = dao.release_expired(Utc::now().naive_utc()).await.unwrap();
+    assert_eq!(released.len(), 1);
+    assert_eq!(released[0].0, owner_id);
+    assert_eq!(released[0].1.allocation_id, expired);
+
+    assert!(dao.get(expired.clone(), owner_id).await.unwrap().is_none());
+    assert!(dao.get(valid.clone(), owner_id).await.unwrap().is_some());
+    assert!(dao.get(unlimited, owner_id).await.unwrap().is_some());
+
+    // Requestor is notified about the expired allocation
+    let events = db
+        .as_dao::<AllocationEventDao>()
+        .get_for_node_id(owner_id, None, None)
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].allocation_id, expired);
+    assert_eq!(
+        events[0].event_type,
+        AllocationEventType::AllocationExpiredEvent
+    );
+    let other_id: NodeId = "0xcafe000000000000000000000000000000000000"
+        .parse()
+        .unwrap();
+    let events = db
+        .as_dao::<AllocationEventDao>()
+        .get_for_node_id(other_id, None, None)
+        .await
+        .unwrap();
+    assert!(events.is_empty());
+
+    // Released allocations aren't released again
+    let released = dao.release_expired(Utc::now().naive_utc()).await.unwrap();
+    assert!(released.is_empty());
+
+    // Allocation is released, once its timeout passes
+    let later = (Utc::now() + Duration::days(2)).naive_utc();
+    let released = dao.release_expired(later).await.unwrap();
+    assert_eq!(released.len(), 1);
+    assert_eq!(released[0].1.allocation_id, valid);
+
+    let events = db
+        .as_dao::<AllocationEventDao>()
+        .get_for_node_id(owner_id, None, None)
+        .await
+        .unwrap();
+    let ids: Vec<_> = events.iter().map(|e| e.allocation_id.clone()).collect();
+    assert_eq!(ids, vec![expired, valid]);
+}
@@ -0,0 +1,232 @@
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use diesel::RunQueryDsl;
+use tempdir::TempDir;
+
+use ya_client_model::NodeId;
+use ya_core_model::payment::local::AccountingReport;
+use ya_payment::export::export;
+use ya_persistence::executor::DbExecutor;
+
+fn init_db(dir: &TempDir) -> DbExecutor {
+    let db = DbExecutor::from_data_dir(dir.path(), "yagna").unwrap();
+    db.apply_migration(ya_payment::migrations::run_with_output)
+        .unwrap();
+    db
+}
+
+fn payer_id() -> NodeId {
+    "0xbabe000000000000000000000000000000000000"
+        .parse()
+        .unwrap()
+}
+
+fn payee_id() -> NodeId {
+    "0xcafe000000000000000000000000000000000000"
+        .parse()
+        .unwrap()
+}
+
+fn date(s: &str) -> DateTime<Utc> {
+    format!("{}T00:00:00Z", s).parse().unwrap()
+}
+
+fn amount(s: &str) -> BigDecimal {
+    s.parse().unwrap()
+}
+
+fn insert_agreement(db: &DbExecutor, agreement_id: &str, due: &str, paid: &str) {
+    let query = format!(
+        "INSERT INTO pay_agreement (id, owner_id, role, peer_id, payee_addr, payer_addr, \
+         payment_platform, total_amount_due, total_amount_accepted, total_amount_scheduled, \
+         total_amount_paid) VALUES ('{}', '{}', 'P', '{}', '{}', '{}', 'dummy-glm', \
+         '{}', '{}', '{}', '{}')",
+        agreement_id,
+        payee_id(),
+        payer_id(),
+        payee_id(),
+        payer_id(),
+        due,
+        due,
+        paid,
+        paid
+    );
+    diesel::sql_query(query)
+        .execute(&*db.conn().unwrap())
+        .unwrap();
+}
+
+/// Provider's documents and payments of three agreements. Only the first one
+/// has anything issued in January 2021.
+fn insert_documents(db: &DbExecutor) {
+    insert_agreement(db, "agreement-1", "16", "10");
+    insert_agreement(db, "agreement-2", "7", "0");
+    insert_agreement(db, "agreement-3", "9", "0");
+
+    let conn = db.conn().unwrap();
+    let queries = vec![
+        format!(
+            "INSERT INTO pay_activity (id, owner_id, role, agreement_id, total_amount_due, \
+             total_amount_accepted, total_amount_scheduled, total_amount_paid) \
+             VALUES ('activity-1', '{}', 'P', 'agreement-1', '6', '6', '6', '6')",
+            payee_id()
+        ),
+        format!(
+            "INSERT INTO pay_debit_note (id, owner_id, role, activity_id, status, timestamp, \
+             total_amount_due) VALUES ('debit-note-1', '{}', 'P', 'activity-1', 'ACCEPTED', \
+             '2021-01-05 00:00:00', '3')",
+            payee_id()
+        ),
+        format!(
+            "INSERT INTO pay_debit_note (id, owner_id, role, activity_id, status, timestamp, \
+             total_amount_due) VALUES ('debit-note-2', '{}', 'P', 'activity-1', 'ACCEPTED', \
+             '2021-01-31 00:00:00', '6')",
+            payee_id()
+        ),
+        format!(
+            "INSERT INTO pay_invoice (id, owner_id, role, agreement_id, status, timestamp, \
+             amount, payment_due_date) VALUES ('invoice-1', '{}', 'P', 'agreement-1', \
+             'ACCEPTED', '2021-01-10 00:00:00', '16', '2021-01-20 00:00:00')",
+            payee_id()
+        ),
+        format!(
+            "INSERT INTO pay_invoice (id, owner_id, role, agreement_id, status, timestamp, \
+             amount, payment_due_date) VALUES ('invoice-2', '{}', 'P', 'agreement-2', \
+             'ISSUED', '2020-12-31 00:00:00', '7', '2021-01-20 00:00:00')",
+            payee_id()
+        ),
+        format!(
+            "INSERT INTO pay_invoice (id, owner_id, role, agreement_id, status, timestamp, \
+             amount, payment_due_date) VALUES ('invoice-3', '{}', 'P', 'agreement-3', \
+             'ISSUED', '2021-02-01 00:00:00', '9', '2021-02-20 00:00:00')",
+            payee_id()
+        ),
+        format!(
+            "INSERT INTO pay_payment (id, owner_id, peer_id, payee_addr, payer_addr, \
+             payment_platform, role, amount, timestamp, details) VALUES ('payment-1', '{}', \
+             '{}', '{}', '{}', 'dummy-glm', 'P', '10', '2021-01-12 00:00:00', X'0102')",
+            payee_id(),
+            payer_id(),
+            payee_id(),
+            payer_id()
+        ),
+        format!(
+            "INSERT INTO pay_activity_payment (payment_id, activity_id, owner_id, amount) \
+             VALUES ('payment-1', 'activity-1', '{}', '6')",
+            payee_id()
+        ),
+        format!(
+            "INSERT INTO pay_agreement_payment (payment_id, agreement_id, owner_id, amount, \
+             allocation_id) VALUES ('payment-1', 'agreement-1', '{}', '4', 'allocation-1')",
+            payee_id()
+        ),
+    ];
+    for query in queries {
+        diesel::sql_query(query).execute(&*conn).unwrap();
+    }
+}
+
+#[actix_rt::test]
+async fn test_export_agreement_totals() {
+    let dir = TempDir::new("export").unwrap();
+    let db = init_db(&dir);
+    insert_documents(&db);
+
+    let report = export(&db, payee_id(), date("2021-01-01"), date("2021-01-31"))
+        .await
+        .unwrap();
+    assert_eq!(report.agreements.len(), 1);
+
+    let agreement = &report.agreements[0];
+    assert_eq!(agreement.agreement_id, "agreement-1");
+    assert_eq!(agreement.role, "provider");
+    assert_eq!(agreement.peer_id, payer_id());
+    assert_eq!(agreement.total_amount_due, amount("16"));
+    assert_eq!(agreement.total_amount_accepted, amount("16"));
+    assert_eq!(agreement.total_amount_paid, amount("10"));
+
+    let invoice_ids: Vec<_> = agreement.invoices.iter().map(|i| i.id.as_str()).collect();
+    assert_eq!(invoice_ids, vec!["invoice-1"]);
+
+    assert_eq!(agreement.activities.len(), 1);
+    let activity = &agreement.activities[0];
+    assert_eq!(activity.activity_id, "activity-1");
+    assert_eq!(activity.total_amount_due, amount("6"));
+    assert_eq!(activity.total_amount_paid, amount("6"));
+    let debit_note_ids: Vec<_> = activity.debit_notes.iter().map(|d| d.id.as_str()).collect();
+    assert_eq!(debit_note_ids, vec!["debit-note-1", "debit-note-2"]);
+
+    // Payment is split into its agreement and activity shares.
+    assert_eq!(agreement.payments.len(), 2);
+    let paid = agreement
+        .payments
+        .iter()
+        .fold(BigDecimal::from(0), |paid, p| paid + &p.amount);
+    assert_eq!(paid, agreement.total_amount_paid);
+    assert!(agreement
+        .payments
+        .iter()
+        .all(|p| p.payment_id == "payment-1" && p.transaction_id == "0x0102"));
+    let activity_payment = agreement
+        .payments
+        .iter()
+        .find(|p| p.activity_id.is_some())
+        .unwrap();
+    assert_eq!(activity_payment.activity_id.as_deref(), Some("activity-1"));
+    assert_eq!(activity_payment.amount, amount("6"));
+    let agreement_payment = agreement
+        .payments
+        .iter()
+        .find(|p| p.activity_id.is_none())
+        .unwrap();
+    assert_eq!(
+        agreement_payment.allocation_id.as_deref(),
+        Some("allocation-1")
+    );
+    assert_eq!(agreement_payment.amount, amount("4"));
+}
+
+#[actix_rt::test]
+async fn test_export_since_until() {
+    let dir = TempDir::new("export").unwrap();
+    let db = init_db(&dir);
+    insert_documents(&db);
+
+    let agreement_ids = |report: &AccountingReport| {
+        report
+            .agreements
+            .iter()
+            .map(|a| a.agreement_id.clone())
+            .collect::<Vec<_>>()
+    };
+
+    // Documents issued exactly at `since` are excluded, at `until` included.
+    let report = export(&db, payee_id(), date("2020-12-31"), date("2021-01-05"))
+        .await
+        .unwrap();
+    assert_eq!(agreement_ids(&report), vec!["agreement-1"]);
+    let activity = &report.agreements[0].activities[0];
+    assert_eq!(activity.debit_notes.len(), 1);
+    assert_eq!(activity.debit_notes[0].id, "debit-note-1");
+    assert!(report.agreements[0].invoices.is_empty());
+    assert!(report.agreements[0].payments.is_empty());
+
+    let report = export(&db, payee_id(), date("2020-12-01"), date("2021-03-01"))
+        .await
+        .unwrap();
+    assert_eq!(
+        agreement_ids(&report),
+        vec!["agreement-1", "agreement-2", "agreement-3"]
+    );
+
+    let report = export(&db, payee_id(), date("2021-02-02"), date("2021-03-01"))
+        .await
+        .unwrap();
+    assert!(report.agreements.is_empty());
+
+    // Other node's documents aren't exported.
+    let report = export(&db, payer_id(), date("2020-12-01"), date("2021-03-01"))
+        .await
+        .unwrap();
+    assert!(report.agreements.is_empty());
+}
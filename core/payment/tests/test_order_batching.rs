@@ -0,0 +1,196 @@
+use bigdecimal::BigDecimal;
+use chrono::{Duration, Utc};
+use diesel::RunQueryDsl;
+use tempdir::TempDir;
+
+use ya_client_model::payment::NewAllocation;
+use ya_client_model::NodeId;
+use ya_core_model::payment::local::{InvoicePayment, PaymentTitle, SchedulePayment};
+use ya_payment::dao::{AllocationDao, OrderDao};
+use ya_persistence::executor::DbExecutor;
+
+const DRIVER: &str = "dummy";
+const PLATFORM: &str = "dummy-glm";
+const PAYER_ADDR: &str = "0xd39a168f0480b8502c2531b2ffd8588c592d713a";
+const PAYEE_ADDR: &str = "0x3a2d6b2ef0e6ac5d1e0fa0a0a0f45e8c8b3b2a11";
+const AGREEMENT_ID: &str = "agreement-1";
+
+fn init_db(dir: &TempDir) -> DbExecutor {
+    let db = DbExecutor::from_data_dir(dir.path(), "yagna").unwrap();
+    db.apply_migration(ya_payment::migrations::run_with_output)
+        .unwrap();
+    db
+}
+
+fn payer_id() -> NodeId {
+    "0xbabe000000000000000000000000000000000000"
+        .parse()
+        .unwrap()
+}
+
+fn payee_id() -> NodeId {
+    "0xcafe000000000000000000000000000000000000"
+        .parse()
+        .unwrap()
+}
+
+/// Agreement and accepted invoices, which orders pay.
+fn insert_invoices(db: &DbExecutor, invoice_ids: &[&str]) {
+    let conn = db.conn().unwrap();
+    diesel::sql_query(format!(
+        "INSERT INTO pay_agreement (id, owner_id, role, peer_id, payee_addr, payer_addr, \
+         payment_platform, total_amount_due, total_amount_accepted, total_amount_scheduled, \
+         total_amount_paid) VALUES ('{}', '{}', 'R', '{}', '{}', '{}', '{}', '0', '0', '0', '0')",
+        AGREEMENT_ID,
+        payer_id(),
+        payee_id(),
+        PAYEE_ADDR,
+        PAYER_ADDR,
+        PLATFORM
+    ))
+    .execute(&*conn)
+    .unwrap();
+    for invoice_id in invoice_ids {
+        diesel::sql_query(format!(
+            "INSERT INTO pay_invoice (id, owner_id, role, agreement_id, status, amount, \
+             payment_due_date) VALUES ('{}', '{}', 'R', '{}', 'ACCEPTED', '10', '{}')",
+            invoice_id,
+            payer_id(),
+            AGREEMENT_ID,
+            Utc::now().naive_utc()
+        ))
+        .execute(&*conn)
+        .unwrap();
+    }
+}
+
+async fn create_allocation(db: &DbExecutor) -> String {
+    let allocation = NewAllocation {
+        address: Some(PAYER_ADDR.to_string()),
+        payment_platform: Some(PLATFORM.to_string()),
+        total_amount: BigDecimal::from(100u32),
+        timeout: None,
+        make_deposit: false,
+    };
+    db.as_dao::<AllocationDao>()
+        .create(
+            allocation,
+            payer_id(),
+            PLATFORM.to_string(),
+            PAYER_ADDR.to_string(),
+        )
+        .await
+        .unwrap()
+}
+
+async fn create_order(db: &DbExecutor, invoice_id: &str, allocation_id: &str, amount: u32) {
+    let msg = SchedulePayment {
+        title: PaymentTitle::Invoice(InvoicePayment {
+            invoice_id: invoice_id.to_string(),
+            agreement_id: AGREEMENT_ID.to_string(),
+        }),
+        payer_id: payer_id(),
+        payee_id: payee_id(),
+        payer_addr: PAYER_ADDR.to_string(),
+        payee_addr: PAYEE_ADDR.to_string(),
+        payment_platform: PLATFORM.to_string(),
+        allocation_id: allocation_id.to_string(),
+        amount: BigDecimal::from(amount),
+        due_date: Utc::now() + Duration::hours(1),
+    };
+    db.as_dao::<OrderDao>()
+        .create(msg, DRIVER.to_string(), None)
+        .await
+        .unwrap();
+}
+
+fn order_ids(orders: &[ya_payment::models::order::ReadObj]) -> Vec<String> {
+    let mut ids: Vec<_> = orders.iter().map(|order| order.id.clone()).collect();
+    ids.sort();
+    ids
+}
+
+#[actix_rt::test]
+async fn test_pending_orders_are_claimed_once() {
+    let dir = TempDir::new("payment").unwrap();
+    let db = init_db(&dir);
+    let dao = db.as_dao::<OrderDao>();
+    insert_invoices(&db, &["invoice-1", "invoice-2", "invoice-3"]);
+    let allocation_id = create_allocation(&db).await;
+    for invoice_id in &["invoice-1", "invoice-2", "invoice-3"] {
+        create_order(&db, invoice_id, &allocation_id, 10).await;
+    }
+
+    let pending = dao.get_pending().await.unwrap();
+    assert_eq!(pending.len(), 3);
+    assert!(pending.iter().all(|order| order.driver_order_id.is_none()));
+    assert!(pending
+        .iter()
+        .all(|order| order.agreement_id.as_deref() == Some(AGREEMENT_ID)));
+
+    let ids = order_ids(&pending[..2]);
+    let (batch_id, claimed) = dao
+        .claim_pending(ids.clone(), DRIVER.to_string())
+        .await
+        .unwrap();
+    assert_eq!(order_ids(&claimed), ids);
+    assert_eq!(dao.get_pending().await.unwrap().len(), 1);
+    assert_eq!(dao.get_claimed().await.unwrap().len(), 2);
+
+    // Orders claimed by a batch can't be claimed by another one
+    let (_, claimed) = dao
+        .claim_pending(ids.clone(), DRIVER.to_string())
+        .await
+        .unwrap();
+    assert!(claimed.is_empty());
+
+    // Orders of other driver aren't claimed
+    let (_, claimed) = dao
+        .claim_pending(order_ids(&pending), "other".to_string())
+        .await
+        .unwrap();
+    assert!(claimed.is_empty());
+
+    dao.assign_driver_order(batch_id, DRIVER.to_string(), "driver-order-1".to_string())
+        .await
+        .unwrap();
+    assert!(dao.get_claimed().await.unwrap().is_empty());
+    assert_eq!(dao.get_pending().await.unwrap().len(), 1);
+
+    let paid = dao
+        .get_many(vec!["driver-order-1".to_string()], DRIVER.to_string())
+        .await
+        .unwrap();
+    assert_eq!(order_ids(&paid), ids);
+}
+
+#[actix_rt::test]
+async fn test_released_batch_is_pending_again() {
+    let dir = TempDir::new("payment").unwrap();
+    let db = init_db(&dir);
+    let dao = db.as_dao::<OrderDao>();
+    insert_invoices(&db, &["invoice-1", "invoice-2"]);
+    let allocation_id = create_allocation(&db).await;
+    create_order(&db, "invoice-1", &allocation_id, 10).await;
+    create_order(&db, "invoice-2", &allocation_id, 20).await;
+
+    let ids = order_ids(&dao.get_pending().await.unwrap());
+    let (batch_id, claimed) = dao
+        .claim_pending(ids.clone(), DRIVER.to_string())
+        .await
+        .unwrap();
+    assert_eq!(claimed.len(), 2);
+    assert!(dao.get_pending().await.unwrap().is_empty());
+
+    dao.release_batch(batch_id.clone(), DRIVER.to_string())
+        .await
+        .unwrap();
+    assert_eq!(order_ids(&dao.get_pending().await.unwrap()), ids);
+    assert!(dao.get_claimed().await.unwrap().is_empty());
+
+    // Released batch id doesn't match any orders anymore
+    dao.assign_driver_order(batch_id, DRIVER.to_string(), "driver-order-1".to_string())
+        .await
+        .unwrap();
+    assert_eq!(dao.get_pending().await.unwrap().len(), 2);
+}
@@ -79,6 +79,8 @@ async fn main() -> anyhow::Result<()> {
                         name,
                         role: model::DEFAULT_ROLE.to_string(),
                         identity,
+                        scopes: None,
+                        expires_date: None,
                     };
 
                     let app_key = bus::service(model::BUS_ID)
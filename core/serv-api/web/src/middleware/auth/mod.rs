@@ -7,28 +7,35 @@ pub use crate::middleware::auth::ident::Identity;
 use crate::middleware::auth::resolver::AppKeyResolver;
 use actix_service::{Service, Transform};
 use actix_web::dev::{ServiceRequest, ServiceResponse};
-use actix_web::error::{Error, ErrorUnauthorized};
-use actix_web::{http::header::Header, HttpMessage};
+use actix_web::error::{Error, ErrorForbidden, ErrorUnauthorized};
+use actix_web::http::{header::Header, Method};
+use actix_web::HttpMessage;
 use actix_web_httpauth::headers::authorization::{Authorization, Bearer};
 use futures::future::{ok, Future, Ready};
 use futures::lock::Mutex;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::pin::Pin;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Instant;
+use ya_core_model::appkey::{self, Access, MarkUsed, Scope, LAST_USED_INTERVAL};
 use ya_service_api_cache::AutoResolveCache;
+use ya_service_bus::actix_rpc;
 
 pub type Cache = AutoResolveCache<AppKeyResolver>;
 
 pub struct Auth {
     cache: Arc<Mutex<Cache>>,
+    last_used: Arc<std::sync::Mutex<LastUsed>>,
 }
 
 impl Default for Auth {
     fn default() -> Self {
         let cache = Arc::new(Mutex::new(Cache::default()));
-        Auth { cache }
+        let last_used = Default::default();
+        Auth { cache, last_used }
     }
 }
 
@@ -49,6 +56,7 @@ where
         ok(AuthMiddleware {
             service: Rc::new(RefCell::new(service)),
             cache: self.cache.clone(),
+            last_used: self.last_used.clone(),
         })
     }
 }
@@ -56,6 +64,7 @@ where
 pub struct AuthMiddleware<S> {
     service: Rc<RefCell<S>>,
     cache: Arc<Mutex<Cache>>,
+    last_used: Arc<std::sync::Mutex<LastUsed>>,
 }
 
 impl<S, B> Service for AuthMiddleware<S>
@@ -78,12 +87,11 @@ where
             .map(|a| a.into_scheme().token().to_string());
 
         let cache = self.cache.clone();
+        let last_used = self.last_used.clone();
         let service = self.service.clone();
 
         // TODO: remove this hack; possibly by enabling creation of arbitrary appkey from CLI
-        if req.uri().to_string().starts_with("/metrics-api")
-            || req.uri().to_string().starts_with("/version")
-        {
+        if req.uri().to_string().starts_with("/version") {
             log::debug!("skipping authorization for uri={}", req.uri());
             return Box::pin(service.borrow_mut().call(req));
         }
@@ -98,7 +106,31 @@ where
                     };
 
                     match resolved {
+                        Some(app_key) if app_key.is_expired() => {
+                            log::debug!(
+                                "{} {} Expired application key: {}",
+                                req.method(),
+                                req.path(),
+                                app_key.name
+                            );
+                            Err(ErrorUnauthorized("Application key expired"))
+                        }
+                        Some(app_key) if !app_key.allows(&required_scope(&req)) => {
+                            let scope = required_scope(&req);
+                            log::debug!(
+                                "{} {} Application key {} lacks scope {}",
+                                req.method(),
+                                req.path(),
+                                app_key.name,
+                                scope
+                            );
+                            Err(ErrorForbidden(format!(
+                                "Application key lacks scope: {}",
+                                scope
+                            )))
+                        }
                         Some(app_key) => {
+                            mark_used(&last_used, &key);
                             req.extensions_mut().insert(Identity::from(app_key));
                             let fut = { service.borrow_mut().call(req) };
                             Ok(fut.await?)
@@ -122,3 +154,77 @@ where
         })
     }
 }
+
+/// Keys are resolved from cache, so use is tracked here for every
+/// authenticated request and reported to identity service in intervals.
+#[derive(Default)]
+struct LastUsed(HashMap<String, Instant>);
+
+impl LastUsed {
+    /// Returns true, when use of `key` should be reported again.
+    fn update(&mut self, key: &str, now: Instant) -> bool {
+        match self.0.get(key) {
+            Some(reported) if now.duration_since(*reported) < LAST_USED_INTERVAL => false,
+            _ => {
+                self.0.insert(key.to_string(), now);
+                true
+            }
+        }
+    }
+}
+
+fn mark_used(last_used: &std::sync::Mutex<LastUsed>, key: &str) {
+    let due = match last_used.lock() {
+        Ok(mut last_used) => last_used.update(key, Instant::now()),
+        Err(_) => false,
+    };
+    if !due {
+        return;
+    }
+    let key = key.to_string();
+    // Failing to record usage shouldn't delay nor prevent authentication.
+    actix_web::rt::spawn(async move {
+        match actix_rpc::service(appkey::BUS_ID)
+            .send(MarkUsed { key })
+            .await
+        {
+            Ok(Ok(())) => (),
+            Ok(Err(e)) => log::warn!("Failed to update last use of app key: {}", e),
+            Err(e) => log::warn!("Failed to update last use of app key: {}", e),
+        }
+    });
+}
+
+/// Scope needed to call given endpoint. API name is taken from the path
+/// prefix, e.g. `/market-api/v1/...` requires `market` scope.
+fn required_scope(req: &ServiceRequest) -> Scope {
+    let segments: Vec<&str> = req.path().trim_start_matches('/').split('/').collect();
+    let api = segments[0].trim_end_matches("-api");
+    let access = match *req.method() {
+        Method::GET | Method::HEAD | Method::OPTIONS => Access::Read,
+        _ if api == "activity" && segments.contains(&"exec") => Access::Exec,
+        // Signature checks don't change any state.
+        _ if api == "identity" && matches!(segments[..], [_, _, "verify"] | [_, _, "recover"]) => {
+            Access::Read
+        }
+        _ if matches!(segments[..], [_, _, "admin", ..]) => Access::Admin,
+        _ => Access::Write,
+    };
+    Scope::new(api, access)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_used_throttled() {
+        let mut last_used = LastUsed::default();
+        let now = Instant::now();
+        assert!(last_used.update("a", now));
+        assert!(!last_used.update("a", now + LAST_USED_INTERVAL / 2));
+        assert!(last_used.update("b", now + LAST_USED_INTERVAL / 2));
+        assert!(last_used.update("a", now + LAST_USED_INTERVAL));
+        assert!(!last_used.update("a", now + LAST_USED_INTERVAL));
+    }
+}
@@ -10,6 +10,7 @@ use std::{
     env,
     fmt::Debug,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 use structopt::{clap, StructOpt};
 use url::Url;
@@ -17,7 +18,7 @@ use url::Url;
 use ya_activity::service::Activity as ActivityService;
 use ya_file_logging::start_logger;
 use ya_identity::service::Identity as IdentityService;
-use ya_market::MarketService;
+use ya_market::{MarketConfig, MarketConfigOpts, MarketService};
 use ya_metrics::{MetricsPusherOpts, MetricsService};
 use ya_net::Net as NetService;
 use ya_payment::{accounts as payment_accounts, PaymentService};
@@ -128,6 +129,7 @@ struct ServiceContext {
     ctx: CliCtx,
     dbs: HashMap<TypeId, DbExecutor>,
     default_db: DbExecutor,
+    market_config: Arc<MarketConfig>,
 }
 
 impl<S: 'static> Provider<S, DbExecutor> for ServiceContext {
@@ -145,6 +147,12 @@ impl<S: 'static> Provider<S, CliCtx> for ServiceContext {
     }
 }
 
+impl Provider<MarketService, Arc<MarketConfig>> for ServiceContext {
+    fn component(&self) -> Arc<MarketConfig> {
+        self.market_config.clone()
+    }
+}
+
 impl<S: 'static> Provider<S, ()> for ServiceContext {
     fn component(&self) -> () {
         ()
@@ -159,6 +167,11 @@ impl ServiceContext {
     fn set_metrics_ctx(&mut self, metrics_opts: &MetricsPusherOpts) {
         self.ctx.metrics_ctx = Some(metrics_opts.into())
     }
+
+    fn set_market_config(&mut self, market_opts: &MarketConfigOpts) -> Result<()> {
+        self.market_config = Arc::new(market_opts.load()?);
+        Ok(())
+    }
 }
 
 impl TryFrom<CliCtx> for ServiceContext {
@@ -180,6 +193,7 @@ impl TryFrom<CliCtx> for ServiceContext {
             ctx,
             dbs,
             default_db,
+            market_config: Default::default(),
         })
     }
 }
@@ -189,15 +203,15 @@ enum Services {
     // Metrics service must be activated before all other services
     // to that will use it. Identity service is used by the Metrics,
     // so must be initialized before.
-    #[enable(gsb, cli(flatten))]
+    #[enable(gsb, rest, cli(flatten))]
     Identity(IdentityService),
-    #[enable(gsb, rest)]
+    #[enable(gsb, rest, cli)]
     Metrics(MetricsService),
     #[enable(gsb, rest, cli)]
     Version(VersionService),
-    #[enable(gsb)]
+    #[enable(gsb, rest, cli)]
     Net(NetService),
-    #[enable(gsb, rest)]
+    #[enable(gsb, rest, cli)]
     Market(MarketService),
     #[enable(gsb, rest, cli)]
     Activity(ActivityService),
@@ -210,6 +224,7 @@ enum Services {
 #[cfg(not(any(
     feature = "dummy-driver",
     feature = "gnt-driver",
+    feature = "sim-driver",
     feature = "zksync-driver"
 )))]
 compile_error!("At least one payment driver needs to be enabled in order to make payments.");
@@ -230,6 +245,13 @@ async fn start_payment_drivers(data_dir: &Path) -> anyhow::Result<Vec<String>> {
         PaymentDriverService::gsb(&db_executor).await?;
         drivers.push(DRIVER_NAME.to_owned());
     }
+    #[cfg(feature = "sim-driver")]
+    {
+        use ya_sim_driver::{PaymentDriverService, DRIVER_NAME};
+        let db_executor = DbExecutor::from_data_dir(data_dir, "sim-driver")?;
+        PaymentDriverService::gsb(&db_executor).await?;
+        drivers.push(DRIVER_NAME.to_owned());
+    }
     #[cfg(feature = "zksync-driver")]
     {
         use ya_zksync_driver::{PaymentDriverService, DRIVER_NAME};
@@ -289,6 +311,9 @@ struct ServiceCommandOpts {
     #[structopt(flatten)]
     metrics_opts: MetricsPusherOpts,
 
+    #[structopt(flatten)]
+    market_opts: MarketConfigOpts,
+
     #[structopt(long, env, default_value = "60")]
     max_rest_timeout: usize,
 
@@ -330,6 +355,7 @@ impl ServiceCommand {
             Self::Run(ServiceCommandOpts {
                 api_url,
                 metrics_opts,
+                market_opts,
                 max_rest_timeout,
                 log_dir,
             }) => {
@@ -376,6 +402,7 @@ impl ServiceCommand {
 
                 let mut context: ServiceContext = ctx.clone().try_into()?;
                 context.set_metrics_ctx(metrics_opts);
+                context.set_market_config(market_opts)?;
                 Services::gsb(&context).await?;
 
                 ya_compile_time_utils::report_version_to_metrics();
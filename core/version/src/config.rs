@@ -0,0 +1,138 @@
+use anyhow::{anyhow, bail};
+use std::env;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use ya_core_model::NodeId;
+
+pub const RELEASE_CHANNEL_ENV_VAR: &str = "YAGNA_RELEASE_CHANNEL";
+pub const RELEASES_URL_ENV_VAR: &str = "YAGNA_RELEASES_URL";
+pub const RELEASE_SIGNERS_ENV_VAR: &str = "YAGNA_RELEASE_SIGNERS";
+pub const RELEASE_PACKAGE_ENV_VAR: &str = "YAGNA_RELEASE_PACKAGE";
+
+const DEFAULT_RELEASES_URL: &str = "https://api.github.com/repos/golemfactory/yagna/releases";
+
+/// Which releases are considered when checking for updates and upgrading.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReleaseChannel {
+    /// Latest release, which is not marked as pre-release.
+    Stable,
+    /// Latest release, pre-releases included.
+    Beta,
+    /// Given release only, e.g. `pinned:0.6.1`.
+    Pinned(String),
+}
+
+impl Default for ReleaseChannel {
+    fn default() -> Self {
+        ReleaseChannel::Stable
+    }
+}
+
+impl FromStr for ReleaseChannel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().splitn(2, ':').collect::<Vec<_>>()[..] {
+            ["stable"] => Ok(ReleaseChannel::Stable),
+            ["beta"] => Ok(ReleaseChannel::Beta),
+            ["pinned", version] if !version.is_empty() => Ok(ReleaseChannel::Pinned(
+                ya_compile_time_utils::tag2semver(version).to_string(),
+            )),
+            _ => bail!(
+                "invalid release channel: '{}'; expected stable, beta or pinned:<version>",
+                s
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ReleaseChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReleaseChannel::Stable => write!(f, "stable"),
+            ReleaseChannel::Beta => write!(f, "beta"),
+            ReleaseChannel::Pinned(version) => write!(f, "pinned:{}", version),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ReleaseConfig {
+    pub channel: ReleaseChannel,
+    /// Endpoint listing releases in GitHub REST API format.
+    pub releases_url: String,
+    /// Nodes trusted to sign release artifacts. Upgrade is refused when empty.
+    pub signers: Vec<NodeId>,
+    /// Release artifact flavour, e.g. `provider` or `requestor`.
+    pub package: String,
+    /// Directory with currently installed binaries.
+    pub install_dir: PathBuf,
+    /// Downloads and backups of replaced binaries are kept here.
+    pub upgrade_dir: PathBuf,
+    pub current_version: String,
+}
+
+impl ReleaseConfig {
+    pub fn from_env(data_dir: &PathBuf) -> anyhow::Result<Self> {
+        let channel = match env::var(RELEASE_CHANNEL_ENV_VAR) {
+            Ok(channel) => channel.parse()?,
+            Err(_) => ReleaseChannel::default(),
+        };
+        let releases_url =
+            env::var(RELEASES_URL_ENV_VAR).unwrap_or_else(|_| DEFAULT_RELEASES_URL.to_string());
+        let signers = env::var(RELEASE_SIGNERS_ENV_VAR)
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                s.parse()
+                    .map_err(|e| anyhow!("invalid {}: {}", RELEASE_SIGNERS_ENV_VAR, e))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        let install_dir = env::current_exe()?
+            .parent()
+            .ok_or_else(|| anyhow!("unable to determine installation directory"))?
+            .to_path_buf();
+        let package = env::var(RELEASE_PACKAGE_ENV_VAR).unwrap_or_else(|_| {
+            match install_dir.join("ya-provider").exists() {
+                true => "provider".to_string(),
+                false => "requestor".to_string(),
+            }
+        });
+
+        Ok(ReleaseConfig {
+            channel,
+            releases_url,
+            signers,
+            package,
+            install_dir,
+            upgrade_dir: data_dir.join("upgrade"),
+            current_version: ya_compile_time_utils::semver_str().to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_channel() {
+        assert_eq!(ReleaseChannel::Stable, "stable".parse().unwrap());
+        assert_eq!(ReleaseChannel::Beta, " beta".parse().unwrap());
+        assert_eq!(
+            ReleaseChannel::Pinned("0.6.1".into()),
+            "pinned:v0.6.1".parse().unwrap()
+        );
+        assert!("pinned".parse::<ReleaseChannel>().is_err());
+        assert!("nightly".parse::<ReleaseChannel>().is_err());
+        assert_eq!(
+            "pinned:0.6.1",
+            ReleaseChannel::Pinned("0.6.1".into()).to_string()
+        );
+    }
+}
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 use crate::db::schema::version_release;
+use crate::github::GithubRelease;
 use ya_compile_time_utils::tag2semver;
 
 pub(crate) const DEFAULT_RELEASE_TS: &'static str = "2015-10-13T15:43:00GMT+2";
@@ -52,14 +53,16 @@ impl From<DBRelease> for ya_core_model::version::Release {
     }
 }
 
-impl TryFrom<self_update::update::Release> for DBRelease {
+impl TryFrom<&GithubRelease> for DBRelease {
     type Error = anyhow::Error;
-    fn try_from(rel: self_update::update::Release) -> Result<Self, Self::Error> {
+    fn try_from(rel: &GithubRelease) -> Result<Self, Self::Error> {
         Ok(Self {
-            version: tag2semver(&rel.version).into(),
-            name: rel.name.clone(),
+            version: tag2semver(&rel.tag_name).into(),
+            name: rel.name.clone().unwrap_or_else(|| rel.tag_name.clone()),
             seen: false,
-            release_ts: parse_release_ts(&rel.date)?,
+            release_ts: parse_release_ts(
+                rel.published_at.as_deref().unwrap_or(DEFAULT_RELEASE_TS),
+            )?,
             insertion_ts: None,
             update_ts: None,
         })
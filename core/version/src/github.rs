@@ -1,27 +1,169 @@
-use anyhow::anyhow;
+use actix_web::client::Client;
+use actix_web::http::header;
+use anyhow::{anyhow, bail};
 use metrics::counter;
-use self_update::backends::github::UpdateBuilder;
+use serde::Deserialize;
 use std::convert::TryFrom;
+use std::time::Duration;
 
+use ya_compile_time_utils::tag2semver;
 use ya_core_model::version::Release;
 use ya_persistence::executor::DbExecutor;
 
+use crate::config::{ReleaseChannel, ReleaseConfig};
 use crate::db::dao::ReleaseDAO;
 use crate::db::model::DBRelease;
 use crate::service::cli::ReleaseMessage;
 
-const REPO_OWNER: &'static str = "golemfactory";
-const REPO_NAME: &'static str = "yagna";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(600);
+const MAX_RELEASES_SIZE: usize = 8 * 1024 * 1024;
+/// Maximum allowed by GitHub API.
+const RELEASES_PER_PAGE: usize = 100;
+const MAX_RELEASES_PAGES: usize = 20;
+const MAX_DOWNLOAD_SIZE: usize = 512 * 1024 * 1024;
+const MAX_REDIRECTS: usize = 5;
 
-pub async fn check_latest_release(db: &DbExecutor) -> anyhow::Result<Release> {
-    log::debug!("Checking latest Yagna release");
-    let gh_rel = UpdateBuilder::new()
-        .repo_owner(REPO_OWNER)
-        .repo_name(REPO_NAME)
-        .bin_name("") // seems required by builder but unused
-        .current_version("") // similar as above
-        .build()?
-        .get_latest_release()?;
+/// Release as listed by GitHub REST API.
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct GithubRelease {
+    pub tag_name: String,
+    pub name: Option<String>,
+    pub published_at: Option<String>,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub assets: Vec<GithubAsset>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct GithubAsset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+impl GithubRelease {
+    pub fn version(&self) -> &str {
+        tag2semver(&self.tag_name)
+    }
+}
+
+fn client() -> Client {
+    Client::builder().timeout(REQUEST_TIMEOUT).finish()
+}
+
+/// Fetches all pages of the release list, since GitHub API returns only
+/// the most recent releases by default.
+pub(crate) async fn fetch_releases(url: &str) -> anyhow::Result<Vec<GithubRelease>> {
+    let separator = if url.contains('?') { '&' } else { '?' };
+    let mut releases = Vec::new();
+    for page in 1..=MAX_RELEASES_PAGES {
+        let page_url = format!(
+            "{}{}per_page={}&page={}",
+            url, separator, RELEASES_PER_PAGE, page
+        );
+        let page_releases = fetch_releases_page(&page_url).await?;
+        let last_page = page_releases.len() < RELEASES_PER_PAGE;
+        releases.extend(page_releases);
+        if last_page {
+            return Ok(releases);
+        }
+    }
+    log::warn!(
+        "Fetched only {} releases from {}, older are skipped",
+        releases.len(),
+        url
+    );
+    Ok(releases)
+}
+
+async fn fetch_releases_page(url: &str) -> anyhow::Result<Vec<GithubRelease>> {
+    let mut response = client()
+        .get(url)
+        .header(header::ACCEPT, "application/vnd.github.v3+json")
+        .send()
+        .await
+        .map_err(|e| anyhow!("Fetching releases from {}: {}", url, e))?;
+    if !response.status().is_success() {
+        bail!("Fetching releases from {}: {}", url, response.status());
+    }
+    response
+        .json()
+        .limit(MAX_RELEASES_SIZE)
+        .await
+        .map_err(|e| anyhow!("Parsing releases from {}: {}", url, e))
+}
+
+/// Downloads release artifact, following redirects to the storage it is served from.
+pub(crate) async fn download(url: &str) -> anyhow::Result<Vec<u8>> {
+    let client = client();
+    let mut url = url.to_string();
+    for _ in 0..MAX_REDIRECTS {
+        let mut response = client
+            .get(&url)
+            .header(header::ACCEPT, "application/octet-stream")
+            .send()
+            .await
+            .map_err(|e| anyhow!("Downloading {}: {}", url, e))?;
+        if response.status().is_redirection() {
+            url = response
+                .headers()
+                .get(header::LOCATION)
+                .and_then(|location| location.to_str().ok())
+                .ok_or_else(|| anyhow!("Downloading {}: redirect without location", url))?
+                .to_string();
+            continue;
+        }
+        if !response.status().is_success() {
+            bail!("Downloading {}: {}", url, response.status());
+        }
+        let body = response
+            .body()
+            .limit(MAX_DOWNLOAD_SIZE)
+            .await
+            .map_err(|e| anyhow!("Downloading {}: {}", url, e))?;
+        return Ok(body.to_vec());
+    }
+    bail!("Downloading {}: too many redirects", url)
+}
+
+/// Picks the release, which the channel currently points to.
+pub(crate) fn select_release<'a>(
+    releases: &'a [GithubRelease],
+    channel: &ReleaseChannel,
+) -> Option<&'a GithubRelease> {
+    let mut published = releases.iter().filter(|r| !r.draft);
+    match channel {
+        ReleaseChannel::Stable => latest(published.filter(|r| !r.prerelease)),
+        ReleaseChannel::Beta => latest(published),
+        ReleaseChannel::Pinned(version) => published.find(|r| r.version() == version.as_str()),
+    }
+}
+
+fn latest<'a>(releases: impl Iterator<Item = &'a GithubRelease>) -> Option<&'a GithubRelease> {
+    releases.fold(None, |latest, release| match latest {
+        Some(latest)
+            if !self_update::version::bump_is_greater(latest.version(), release.version())
+                .unwrap_or(false) =>
+        {
+            Some(latest)
+        }
+        _ => Some(release),
+    })
+}
+
+pub async fn check_latest_release(
+    db: &DbExecutor,
+    config: &ReleaseConfig,
+) -> anyhow::Result<Release> {
+    log::debug!(
+        "Checking latest Yagna release in {} channel",
+        config.channel
+    );
+    let releases = fetch_releases(&config.releases_url).await?;
+    let gh_rel = select_release(&releases, &config.channel)
+        .ok_or_else(|| anyhow!("No Yagna release found in {} channel", config.channel))?;
 
     log::trace!("Got latest Yagna release {:?}", gh_rel);
 
@@ -35,22 +177,25 @@ pub async fn check_latest_release(db: &DbExecutor) -> anyhow::Result<Release> {
         Ok(r) => r,
     };
 
-    if self_update::version::bump_is_greater(ya_compile_time_utils::semver_str(), &rel.version)
-        .map_err(|e| {
+    if self_update::version::bump_is_greater(&config.current_version, &rel.version).map_err(
+        |e| {
             anyhow!(
                 "Github release version `{}` parse error: {}",
                 rel.version,
                 e
             )
-        })?
-    {
+        },
+    )? {
         counter!("version.new", 1);
         log::warn!("{}", ReleaseMessage::Available(&rel));
     };
     Ok(rel)
 }
 
-pub(crate) async fn check_running_release(db: &DbExecutor) -> anyhow::Result<Release> {
+pub(crate) async fn check_running_release(
+    db: &DbExecutor,
+    config: &ReleaseConfig,
+) -> anyhow::Result<Release> {
     if let Some(release) = db.as_dao::<ReleaseDAO>().current_release().await? {
         return Ok(release);
     }
@@ -58,18 +203,21 @@ pub(crate) async fn check_running_release(db: &DbExecutor) -> anyhow::Result<Rel
     let running_tag = ya_compile_time_utils::git_tag();
     log::debug!("Checking release for running tag: {}", running_tag);
 
-    let db_rel = match UpdateBuilder::new()
-        .repo_owner(REPO_OWNER)
-        .repo_name(REPO_NAME)
-        .bin_name("") // seems required by builder but unused
-        .current_version("") // similar as above
-        .build()?
-        .get_release_version(running_tag)
-    {
-        Ok(gh_rel) => {
-            log::trace!("Got currently running release: {:?}", gh_rel);
-            DBRelease::try_from(gh_rel)?
-        }
+    let running = ReleaseChannel::Pinned(config.current_version.clone());
+    let db_rel = match fetch_releases(&config.releases_url).await {
+        Ok(releases) => match select_release(&releases, &running) {
+            Some(gh_rel) => {
+                log::trace!("Got currently running release: {:?}", gh_rel);
+                DBRelease::try_from(gh_rel)?
+            }
+            None => {
+                log::trace!(
+                    "No release for running tag: '{}'. Using current",
+                    running_tag
+                );
+                DBRelease::current()?
+            }
+        },
         Err(e) => {
             log::trace!(
                 "Failed to get release for running tag: '{}': {}. Using current",
@@ -93,3 +241,37 @@ pub(crate) async fn check_running_release(db: &DbExecutor) -> anyhow::Result<Rel
     };
     Ok(rel)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(tag: &str, prerelease: bool) -> GithubRelease {
+        GithubRelease {
+            tag_name: tag.into(),
+            name: None,
+            published_at: None,
+            draft: false,
+            prerelease,
+            assets: vec![],
+        }
+    }
+
+    #[test]
+    fn test_select_release() {
+        let releases = vec![
+            release("pre-rel-v0.7.0-rc1", true),
+            release("v0.6.1", false),
+            release("v0.6.0", false),
+        ];
+        let select = |channel| select_release(&releases, &channel).map(|r| r.version());
+
+        assert_eq!(Some("0.6.1"), select(ReleaseChannel::Stable));
+        assert_eq!(Some("0.7.0-rc1"), select(ReleaseChannel::Beta));
+        assert_eq!(
+            Some("0.6.0"),
+            select(ReleaseChannel::Pinned("0.6.0".into()))
+        );
+        assert_eq!(None, select(ReleaseChannel::Pinned("0.5.0".into())));
+    }
+}
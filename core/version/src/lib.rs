@@ -3,9 +3,11 @@ extern crate diesel;
 #[macro_use]
 extern crate diesel_migrations;
 
+mod config;
 mod db;
 mod github;
 mod notifier;
 mod service;
+mod upgrade;
 
 pub use service::VersionService;
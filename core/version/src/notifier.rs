@@ -2,32 +2,36 @@ use std::time::Duration;
 
 use ya_persistence::executor::DbExecutor;
 
+use crate::config::ReleaseConfig;
 use crate::db::dao::ReleaseDAO;
 use crate::github;
 use crate::github::check_running_release;
 use crate::service::cli::ReleaseMessage;
 
-pub async fn on_start(db: &DbExecutor) -> anyhow::Result<()> {
-    check_running_release(&db).await?;
+pub async fn on_start(db: &DbExecutor, config: &ReleaseConfig) -> anyhow::Result<()> {
+    check_running_release(&db, &config).await?;
 
-    if let Err(e) = github::check_latest_release(&db).await {
+    if let Err(e) = github::check_latest_release(&db, &config).await {
         log::error!("Failed to check for new Yagna release: {}", e);
     };
 
     let worker_db = db.clone();
-    tokio::task::spawn_local(async move { crate::notifier::worker(worker_db).await });
+    let worker_config = config.clone();
+    tokio::task::spawn_local(
+        async move { crate::notifier::worker(worker_db, worker_config).await },
+    );
     let pinger_db = db.clone();
     tokio::task::spawn_local(async move { crate::notifier::pinger(pinger_db).await });
 
     Ok(())
 }
 
-pub(crate) async fn worker(db: DbExecutor) {
+pub(crate) async fn worker(db: DbExecutor, config: ReleaseConfig) {
     // TODO: make interval configurable
     let mut interval = tokio::time::interval(Duration::from_secs(3600 * 24));
     loop {
         interval.tick().await;
-        if let Err(e) = github::check_latest_release(&db).await {
+        if let Err(e) = github::check_latest_release(&db, &config).await {
             log::error!("Failed to check for new Yagna release: {}", e);
         };
     }
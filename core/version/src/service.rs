@@ -1,6 +1,8 @@
 use ya_persistence::executor::DbExecutor;
+use ya_service_api::CliCtx;
 use ya_service_api_interfaces::{Provider, Service};
 
+use crate::config::ReleaseConfig;
 use crate::db::migrations;
 
 pub(crate) mod cli;
@@ -14,11 +16,16 @@ impl Service for VersionService {
 }
 
 impl VersionService {
-    pub async fn gsb<C: Provider<Self, DbExecutor>>(ctx: &C) -> anyhow::Result<()> {
-        let db = ctx.component();
+    pub async fn gsb<C>(ctx: &C) -> anyhow::Result<()>
+    where
+        C: Provider<Self, DbExecutor> + Provider<Self, CliCtx>,
+    {
+        let db = Provider::<Self, DbExecutor>::component(ctx);
+        let cli_ctx = Provider::<Self, CliCtx>::component(ctx);
+        let config = ReleaseConfig::from_env(&cli_ctx.data_dir)?;
         db.apply_migration(migrations::run_with_output)?;
-        crate::notifier::on_start(&db).await?;
-        gsb::bind_gsb(&db);
+        crate::notifier::on_start(&db, &config).await?;
+        gsb::bind_gsb(&db, config);
 
         Ok(())
     }
@@ -16,6 +16,10 @@ pub(crate) enum ReleaseMessage<'a> {
     Skipped(&'a version::Release),
     #[error("No pending release to skip")]
     NotSkipped,
+    #[error("Yagna {0} installed. Restart Yagna to use it")]
+    Installed(&'a version::Release),
+    #[error("Yagna {0} restored. Restart Yagna to use it")]
+    RolledBack(&'a str),
 }
 
 /// Yagna version management.
@@ -28,6 +32,15 @@ pub enum VersionCLI {
     /// Stop logging warnings about latest Yagna release availability.
     #[structopt(setting = AppSettings::Hidden)]
     Skip,
+    /// Download, verify and install the latest release of configured channel.
+    /// Takes effect after restart.
+    Upgrade {
+        /// Install given version instead, e.g. 0.6.1
+        #[structopt(long)]
+        version: Option<String>,
+    },
+    /// Restore binaries replaced by the last upgrade. Takes effect after restart.
+    Rollback,
 }
 
 impl VersionCLI {
@@ -44,6 +57,23 @@ impl VersionCLI {
                     None => ReleaseMessage::NotSkipped.to_string(),
                 },
             ),
+            VersionCLI::Upgrade { version: target } => {
+                let release = bus::service(version::BUS_ID)
+                    .send(version::Upgrade { version: target })
+                    .await??;
+                if ctx.json_output {
+                    return CommandOutput::object(release);
+                }
+                CommandOutput::object(ReleaseMessage::Installed(&release).to_string())
+            }
+            VersionCLI::Rollback => CommandOutput::object(
+                ReleaseMessage::RolledBack(
+                    &bus::service(version::BUS_ID)
+                        .send(version::Rollback())
+                        .await??,
+                )
+                .to_string(),
+            ),
         }
     }
 }
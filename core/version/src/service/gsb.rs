@@ -1,27 +1,35 @@
 use metrics::counter;
+use std::convert::TryFrom;
 
 use ya_core_model::version;
 use ya_persistence::executor::DbExecutor;
 use ya_service_bus::{typed as bus, RpcMessage};
 
+use crate::config::ReleaseConfig;
 use crate::db::dao::ReleaseDAO;
+use crate::db::model::DBRelease;
 use crate::service::cli::ReleaseMessage;
 
 pub type RpcMessageResult<T> = Result<<T as RpcMessage>::Item, <T as RpcMessage>::Error>;
 
-pub fn bind_gsb(db: &DbExecutor) {
-    bus::ServiceBinder::new(version::BUS_ID, db, ())
-        .bind(skip_version_gsb)
-        .bind(get_version_gsb);
+pub fn bind_gsb(db: &DbExecutor, config: ReleaseConfig) {
+    bus::ServiceBinder::new(version::BUS_ID, db, config)
+        .bind_with_processor(skip_version_gsb)
+        .bind_with_processor(get_version_gsb)
+        .bind_with_processor(upgrade_gsb)
+        .bind_with_processor(rollback_gsb);
 
     // Initialize counters to 0 value. Otherwise they won't appear on metrics endpoint
     // until first change to value will be made.
     counter!("version.new", 0);
     counter!("version.skip", 0);
+    counter!("version.upgrade", 0);
+    counter!("version.rollback", 0);
 }
 
 async fn skip_version_gsb(
     db: DbExecutor,
+    _config: ReleaseConfig,
     _caller: String,
     _msg: version::Skip,
 ) -> RpcMessageResult<version::Skip> {
@@ -38,11 +46,12 @@ async fn skip_version_gsb(
 
 async fn get_version_gsb(
     db: DbExecutor,
+    config: ReleaseConfig,
     _caller: String,
     msg: version::Get,
 ) -> RpcMessageResult<version::Get> {
     if msg.check {
-        crate::github::check_latest_release(&db)
+        crate::github::check_latest_release(&db, &config)
             .await
             .map_err(|e| e.to_string())?;
     }
@@ -52,3 +61,41 @@ async fn get_version_gsb(
         .await
         .map_err(|e| e.to_string().into())
 }
+
+async fn upgrade_gsb(
+    db: DbExecutor,
+    config: ReleaseConfig,
+    _caller: String,
+    msg: version::Upgrade,
+) -> RpcMessageResult<version::Upgrade> {
+    let gh_rel = crate::upgrade::upgrade(&config, msg.version)
+        .await
+        .map_err(|e| e.to_string())?;
+    let db_rel = DBRelease::try_from(&gh_rel).map_err(|e| e.to_string())?;
+    let release = match db.as_dao::<ReleaseDAO>().save_new(db_rel.clone()).await {
+        Ok(r) => r,
+        Err(e) => {
+            log::error!(
+                "Storing installed Yagna release {} to DB. {}",
+                db_rel.version,
+                e
+            );
+            db_rel.into()
+        }
+    };
+    counter!("version.upgrade", 1);
+    Ok(release)
+}
+
+async fn rollback_gsb(
+    _db: DbExecutor,
+    config: ReleaseConfig,
+    _caller: String,
+    _msg: version::Rollback,
+) -> RpcMessageResult<version::Rollback> {
+    let version = crate::upgrade::rollback(&config)
+        .await
+        .map_err(|e| e.to_string())?;
+    counter!("version.rollback", 1);
+    Ok(version)
+}
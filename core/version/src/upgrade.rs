@@ -0,0 +1,482 @@
+//! Opt-in replacement of installed binaries with a signed release.
+//!
+//! Binaries are replaced by renaming, so running processes keep using the old ones
+//! and the new release is started on the next restart. Replaced binaries are kept
+//! for a rollback.
+use anyhow::{anyhow, bail};
+use serde::{Deserialize, Serialize};
+use sha3::Digest;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use ya_core_model::NodeId;
+
+use crate::config::{ReleaseChannel, ReleaseConfig, RELEASE_SIGNERS_ENV_VAR};
+use crate::github::{self, GithubAsset, GithubRelease};
+
+const SIGNATURE_EXT: &str = ".sig";
+const RECOVERABLE_SIGNATURE_LEN: usize = 65;
+const BACKUP_DIR: &str = "backup";
+const BACKUP_MANIFEST: &str = "release.json";
+
+/// Binaries replaced by the last upgrade.
+#[derive(Serialize, Deserialize)]
+struct Backup {
+    version: String,
+    files: Vec<String>,
+}
+
+/// Installs the latest release of configured channel, or the given version.
+pub(crate) async fn upgrade(
+    config: &ReleaseConfig,
+    version: Option<String>,
+) -> anyhow::Result<GithubRelease> {
+    if config.signers.is_empty() {
+        bail!(
+            "No trusted release signers configured. Set {} to enable upgrades",
+            RELEASE_SIGNERS_ENV_VAR
+        );
+    }
+    let pinned = version.is_some();
+    let channel = match version {
+        Some(version) => {
+            ReleaseChannel::Pinned(ya_compile_time_utils::tag2semver(&version).to_string())
+        }
+        None => config.channel.clone(),
+    };
+
+    let releases = github::fetch_releases(&config.releases_url).await?;
+    let release = github::select_release(&releases, &channel)
+        .ok_or_else(|| anyhow!("No Yagna release found in {} channel", channel))?
+        .clone();
+    if release.version() == config.current_version {
+        bail!("Yagna {} is already installed", release.version());
+    }
+    let newer =
+        self_update::version::bump_is_greater(&config.current_version, release.version())
+            .map_err(|e| anyhow!("Release version `{}` parse error: {}", release.version(), e))?;
+    if !pinned && !newer {
+        bail!(
+            "No release newer than {} in {} channel",
+            config.current_version,
+            channel
+        );
+    }
+
+    let asset = find_asset(&release, &config.package)?;
+    let signature_name = format!("{}{}", asset.name, SIGNATURE_EXT);
+    let signature_asset = release
+        .assets
+        .iter()
+        .find(|a| a.name == signature_name)
+        .ok_or_else(|| {
+            anyhow!(
+                "Release {} has no signature {}",
+                release.tag_name,
+                signature_name
+            )
+        })?;
+
+    log::info!(
+        "Downloading Yagna {} from {}",
+        release.version(),
+        asset.browser_download_url
+    );
+    let archive = github::download(&asset.browser_download_url).await?;
+    let signature = github::download(&signature_asset.browser_download_url).await?;
+    let manifest = release_manifest(release.version(), &asset.name, &archive);
+    let signer = verify_signature(&manifest, &signature, &config.signers)?;
+    log::debug!("Release {} signed by {}", asset.name, signer);
+
+    let install_config = config.clone();
+    let version = release.version().to_string();
+    let files =
+        tokio::task::spawn_blocking(move || install(&install_config, &version, archive)).await??;
+    log::info!(
+        "Yagna {} installed ({}). Restart to use it.",
+        release.version(),
+        files.join(", ")
+    );
+    Ok(release)
+}
+
+/// Restores binaries replaced by the last upgrade.
+pub(crate) async fn rollback(config: &ReleaseConfig) -> anyhow::Result<String> {
+    let config = config.clone();
+    tokio::task::spawn_blocking(move || {
+        let backup_dir = config.upgrade_dir.join(BACKUP_DIR);
+        let backup = read_backup(&backup_dir).ok_or_else(|| anyhow!("No upgrade to roll back"))?;
+        for name in backup.files.iter() {
+            replace(&backup_dir.join(name), &config.install_dir.join(name))?;
+        }
+        fs::remove_dir_all(&backup_dir)?;
+        log::info!("Yagna {} restored. Restart to use it.", backup.version);
+        Ok(backup.version)
+    })
+    .await?
+}
+
+fn find_asset<'a>(release: &'a GithubRelease, package: &str) -> anyhow::Result<&'a GithubAsset> {
+    let prefix = format!("golem-{}-{}-", package, platform()?);
+    release
+        .assets
+        .iter()
+        .find(|a| a.name.starts_with(&prefix) && a.name.ends_with(".tar.gz"))
+        .ok_or_else(|| {
+            anyhow!(
+                "Release {} has no {}*.tar.gz artifact",
+                release.tag_name,
+                prefix
+            )
+        })
+}
+
+/// Platform name used in release artifacts.
+fn platform() -> anyhow::Result<&'static str> {
+    match std::env::consts::OS {
+        "linux" => Ok("linux"),
+        "macos" => Ok("osx"),
+        os => bail!("Upgrade is not supported on {}", os),
+    }
+}
+
+/// Signed description of the artifact. Binding version and name to the digest
+/// prevents serving an older signed artifact as a newer release.
+fn release_manifest(version: &str, asset_name: &str, artifact: &[u8]) -> String {
+    format!(
+        "version: {}\nasset: {}\nsha3-256: {}\n",
+        version,
+        asset_name,
+        hex::encode(sha3::Sha3_256::digest(artifact))
+    )
+}
+
+/// Signature is hex encoded `v || r || s` of sha3-256 digest of the release manifest,
+/// the same format as produced by identity `Sign`.
+fn verify_signature(
+    manifest: &str,
+    signature: &[u8],
+    signers: &[NodeId],
+) -> anyhow::Result<NodeId> {
+    let signature = String::from_utf8_lossy(signature);
+    let signature = hex::decode(signature.trim().trim_start_matches("0x"))
+        .map_err(|e| anyhow!("Invalid release signature: {}", e))?;
+    if signature.len() != RECOVERABLE_SIGNATURE_LEN {
+        bail!(
+            "Invalid release signature: expected {} bytes, got {}",
+            RECOVERABLE_SIGNATURE_LEN,
+            signature.len()
+        );
+    }
+    let mut r = [0u8; 32];
+    let mut s = [0u8; 32];
+    r.copy_from_slice(&signature[1..33]);
+    s.copy_from_slice(&signature[33..65]);
+    let digest = sha3::Sha3_256::digest(manifest.as_bytes());
+    let public = ethsign::Signature {
+        v: signature[0],
+        r,
+        s,
+    }
+    .recover(digest.as_slice())
+    .map_err(|e| anyhow!("Invalid release signature: {}", e))?;
+
+    let signer = NodeId::from(public.address().as_ref());
+    if !signers.contains(&signer) {
+        bail!("Release signed by untrusted node {}", signer);
+    }
+    Ok(signer)
+}
+
+/// Unpacks binaries already present in the install directory and swaps them,
+/// backing up the replaced ones. Returns names of installed binaries.
+fn install(config: &ReleaseConfig, version: &str, archive: Vec<u8>) -> anyhow::Result<Vec<String>> {
+    let staging_dir = config.upgrade_dir.join(version);
+    if staging_dir.exists() {
+        fs::remove_dir_all(&staging_dir)?;
+    }
+    fs::create_dir_all(&staging_dir)?;
+
+    // Binaries are placed in a top level directory named after the artifact.
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(archive.as_slice()));
+    let mut files = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = match entry.path()?.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => continue,
+        };
+        if !config.install_dir.join(&name).is_file() {
+            log::debug!("Skipping {}: not installed", name);
+            continue;
+        }
+        entry.unpack(staging_dir.join(&name))?;
+        files.push(name);
+    }
+    if files.is_empty() {
+        bail!(
+            "Release archive has none of binaries installed in {}",
+            config.install_dir.display()
+        );
+    }
+
+    let backup_dir = config.upgrade_dir.join(BACKUP_DIR);
+    let mut backup = match read_backup(&backup_dir) {
+        // Upgraded again without restart. Installed binaries aren't the running
+        // version anymore, so the backup of the running ones is kept.
+        Some(backup) if backup.version == config.current_version => backup,
+        _ => {
+            if backup_dir.exists() {
+                fs::remove_dir_all(&backup_dir)?;
+            }
+            fs::create_dir_all(&backup_dir)?;
+            Backup {
+                version: config.current_version.clone(),
+                files: vec![],
+            }
+        }
+    };
+    for name in files.iter() {
+        if !backup.files.contains(name) {
+            fs::copy(config.install_dir.join(name), backup_dir.join(name))?;
+            backup.files.push(name.clone());
+        }
+    }
+    fs::write(
+        backup_dir.join(BACKUP_MANIFEST),
+        serde_json::to_vec(&backup)?,
+    )?;
+
+    for name in files.iter() {
+        replace(&staging_dir.join(name), &config.install_dir.join(name))?;
+    }
+    fs::remove_dir_all(&staging_dir)?;
+    Ok(files)
+}
+
+fn read_backup(backup_dir: &Path) -> Option<Backup> {
+    let manifest = fs::read(backup_dir.join(BACKUP_MANIFEST)).ok()?;
+    serde_json::from_slice(&manifest).ok()
+}
+
+/// Copies next to the target first, so the target is swapped atomically.
+fn replace(source: &Path, target: &Path) -> io::Result<()> {
+    let name = target
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no file name"))?;
+    let tmp = target.with_file_name(format!(".{}.new", name.to_string_lossy()));
+    fs::copy(source, &tmp)?;
+    fs::rename(&tmp, target)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use actix_web::{web, App, HttpResponse, HttpServer};
+    use flate2::{write::GzEncoder, Compression};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use tempdir::TempDir;
+    use ya_compile_time_utils::tag2semver;
+
+    type Files = Arc<HashMap<String, Vec<u8>>>;
+
+    fn archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *content).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn sign(secret: &ethsign::SecretKey, manifest: &str) -> Vec<u8> {
+        let digest = sha3::Sha3_256::digest(manifest.as_bytes());
+        let signature = secret.sign(digest.as_slice()).unwrap();
+        hex::encode([&[signature.v][..], &signature.r[..], &signature.s[..]].concat()).into_bytes()
+    }
+
+    fn node_id(secret: &ethsign::SecretKey) -> NodeId {
+        NodeId::from(secret.public().address().as_ref())
+    }
+
+    async fn serve_file(
+        files: web::Data<Files>,
+        name: web::Path<String>,
+        query: web::Query<HashMap<String, String>>,
+    ) -> HttpResponse {
+        let page = query
+            .get("page")
+            .map(|page| format!("{}?page={}", name, page));
+        match page
+            .and_then(|page| files.get(&page))
+            .or_else(|| files.get(name.as_str()))
+        {
+            Some(content) => HttpResponse::Ok().body(content.clone()),
+            None => HttpResponse::NotFound().finish(),
+        }
+    }
+
+    /// Serves release list and artifacts of releases 0.7.0 and 0.8.0-rc1 on the second
+    /// page of the list. `replay_as` lists 0.7.0 artifact also as the given release.
+    fn serve_releases(secret: &ethsign::SecretKey, replay_as: Option<&str>) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let mut files = HashMap::new();
+        let mut releases = Vec::new();
+        for (tag, prerelease) in [("v0.7.0", false), ("pre-rel-v0.8.0-rc1", true)].iter() {
+            let name = format!("golem-provider-{}-{}", platform().unwrap(), tag);
+            let binary = format!("yagna {}", tag);
+            let artifact = archive(&[
+                (format!("{}/yagna", name).as_str(), binary.as_bytes()),
+                (format!("{}/golemsp", name).as_str(), &b"not installed"[..]),
+            ]);
+            let asset = format!("{}.tar.gz", name);
+            let signature = format!("{}{}", asset, SIGNATURE_EXT);
+            let manifest = release_manifest(tag2semver(tag), &asset, &artifact);
+            files.insert(signature.clone(), sign(secret, &manifest));
+            files.insert(asset.clone(), artifact);
+            releases.push(serde_json::json!({
+                "tag_name": tag,
+                "name": tag,
+                "published_at": "2021-03-01T12:00:00Z",
+                "prerelease": prerelease,
+                "assets": [
+                    {"name": asset, "browser_download_url": format!("{}/{}", url, asset)},
+                    {"name": signature, "browser_download_url": format!("{}/{}", url, signature)},
+                ]
+            }));
+        }
+        if let Some(tag) = replay_as {
+            let mut replayed = releases[0].clone();
+            replayed["tag_name"] = tag.into();
+            replayed["name"] = tag.into();
+            releases.push(replayed);
+        }
+        let old_releases: Vec<_> = (0..100)
+            .map(|n| serde_json::json!({"tag_name": format!("v0.0.{}", n), "assets": []}))
+            .collect();
+        files.insert(
+            "releases?page=1".into(),
+            serde_json::to_vec(&old_releases).unwrap(),
+        );
+        files.insert(
+            "releases?page=2".into(),
+            serde_json::to_vec(&releases).unwrap(),
+        );
+
+        let files: Files = Arc::new(files);
+        let _ = HttpServer::new(move || {
+            App::new()
+                .data(files.clone())
+                .route("/{name}", web::get().to(serve_file))
+        })
+        .listen(listener)
+        .unwrap()
+        .run();
+        url
+    }
+
+    fn test_config(dir: &TempDir, url: &str, signers: Vec<NodeId>) -> ReleaseConfig {
+        let install_dir = dir.path().join("bin");
+        fs::create_dir_all(&install_dir).unwrap();
+        fs::write(install_dir.join("yagna"), "yagna v0.6.0").unwrap();
+        ReleaseConfig {
+            channel: ReleaseChannel::Stable,
+            releases_url: format!("{}/releases", url),
+            signers,
+            package: "provider".into(),
+            install_dir,
+            upgrade_dir: dir.path().join("data").join("upgrade"),
+            current_version: "0.6.0".into(),
+        }
+    }
+
+    fn installed(config: &ReleaseConfig) -> String {
+        fs::read_to_string(config.install_dir.join("yagna")).unwrap()
+    }
+
+    fn secret(seed: u8) -> ethsign::SecretKey {
+        ethsign::SecretKey::from_raw(&[seed; 32]).unwrap()
+    }
+
+    #[actix_rt::test]
+    async fn test_upgrade_and_rollback() {
+        let dir = TempDir::new("upgrade").unwrap();
+        let secret = secret(1);
+        let url = serve_releases(&secret, None);
+        let mut config = test_config(&dir, &url, vec![node_id(&secret)]);
+
+        let release = upgrade(&config, None).await.unwrap();
+        assert_eq!("0.7.0", release.version());
+        assert_eq!("yagna v0.7.0", installed(&config));
+        assert!(!config.install_dir.join("golemsp").exists());
+
+        assert_eq!("0.6.0", rollback(&config).await.unwrap());
+        assert_eq!("yagna v0.6.0", installed(&config));
+        assert!(rollback(&config).await.is_err());
+
+        // Second upgrade before restart keeps binaries of the running version.
+        upgrade(&config, None).await.unwrap();
+        config.channel = ReleaseChannel::Beta;
+        upgrade(&config, None).await.unwrap();
+        assert_eq!("yagna pre-rel-v0.8.0-rc1", installed(&config));
+        assert_eq!("0.6.0", rollback(&config).await.unwrap());
+        assert_eq!("yagna v0.6.0", installed(&config));
+
+        config.channel = ReleaseChannel::Beta;
+        upgrade(&config, None).await.unwrap();
+        assert_eq!("yagna pre-rel-v0.8.0-rc1", installed(&config));
+
+        config.current_version = "0.8.0-rc1".into();
+        let error = upgrade(&config, None).await.unwrap_err();
+        assert!(error.to_string().contains("already installed"));
+    }
+
+    #[actix_rt::test]
+    async fn test_upgrade_pinned_version() {
+        let dir = TempDir::new("upgrade").unwrap();
+        let secret = secret(1);
+        let url = serve_releases(&secret, None);
+        let config = test_config(&dir, &url, vec![node_id(&secret)]);
+
+        upgrade(&config, Some("v0.8.0-rc1".into())).await.unwrap();
+        assert_eq!("yagna pre-rel-v0.8.0-rc1", installed(&config));
+        assert!(upgrade(&config, Some("0.5.0".into())).await.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_reject_untrusted_signature() {
+        let dir = TempDir::new("upgrade").unwrap();
+        let url = serve_releases(&secret(1), None);
+
+        let config = test_config(&dir, &url, vec![node_id(&secret(2))]);
+        let error = upgrade(&config, None).await.unwrap_err();
+        assert!(error.to_string().contains("untrusted"));
+        assert_eq!("yagna v0.6.0", installed(&config));
+
+        let config = test_config(&dir, &url, vec![]);
+        assert!(upgrade(&config, None).await.is_err());
+        assert!(!config.upgrade_dir.exists());
+    }
+
+    #[actix_rt::test]
+    async fn test_reject_replayed_release() {
+        let dir = TempDir::new("upgrade").unwrap();
+        let secret = secret(1);
+        let url = serve_releases(&secret, Some("v99.0.0"));
+        let config = test_config(&dir, &url, vec![node_id(&secret)]);
+
+        let error = upgrade(&config, None).await.unwrap_err();
+        assert!(error.to_string().contains("untrusted"));
+        assert_eq!("yagna v0.6.0", installed(&config));
+    }
+}
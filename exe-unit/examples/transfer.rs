@@ -215,6 +215,7 @@ async fn main() -> anyhow::Result<()> {
         work_dir: work_dir.clone(),
         cache_dir,
         runtime_args,
+        interactive: None,
         #[cfg(feature = "sgx")]
         crypto: init_crypto()?,
     };
@@ -173,6 +173,7 @@ async fn main() -> anyhow::Result<()> {
         work_dir,
         cache_dir,
         runtime_args,
+        interactive: None,
         #[cfg(feature = "sgx")]
         crypto: init_crypto()?,
     };
@@ -9,7 +9,7 @@ use ya_core_model::activity;
 use ya_exe_unit::agreement::Agreement;
 use ya_exe_unit::message::Register;
 use ya_exe_unit::runtime::process::RuntimeProcess;
-use ya_exe_unit::runtime::RuntimeArgs;
+use ya_exe_unit::runtime::{InteractiveMode, RuntimeArgs};
 use ya_exe_unit::service::metrics::MetricsService;
 use ya_exe_unit::service::signal::SignalMonitor;
 use ya_exe_unit::service::transfer::TransferService;
@@ -47,6 +47,14 @@ struct Cli {
         set = clap::ArgSettings::Global,
     )]
     requestor_pub_key: Option<String>,
+    /// Let requestors interact with running commands: "stdin" or "pty".
+    /// Offered only for runtimes declaring `golem.runtime.start_mode` "empty"
+    #[structopt(
+        long,
+        env = "EXE_UNIT_INTERACTIVE",
+        set = clap::ArgSettings::Global,
+    )]
+    interactive: Option<InteractiveMode>,
     #[structopt(subcommand)]
     command: Command,
 }
@@ -151,7 +159,8 @@ fn run() -> anyhow::Result<()> {
             args
         }
         Command::OfferTemplate => {
-            let offer_template = ExeUnit::<RuntimeProcess>::offer_template(cli.binary)?;
+            let offer_template =
+                ExeUnit::<RuntimeProcess>::offer_template(cli.binary, cli.interactive)?;
             println!("{}", serde_json::to_string(&offer_template)?);
             return Ok(());
         }
@@ -194,6 +203,7 @@ fn run() -> anyhow::Result<()> {
         work_dir,
         cache_dir,
         runtime_args,
+        interactive: cli.interactive,
         #[cfg(feature = "sgx")]
         crypto: init_crypto(
             cli.sec_key.replace("<hidden>".into()),
@@ -59,6 +59,8 @@ impl Crypto {
         EncryptionCtx::new(&self.requestor_pub_key, &self.sec_key)
     }
 
+    /// DER signature of sha3-256 digest. Verifiable with identity `Verify` and
+    /// `Signer::PublicKey`.
     pub fn sign<T: AsRef<[u8]>>(&self, data: T) -> Result<Vec<u8>, Error> {
         let ec = Secp256k1::new();
         let hash = sha3::Sha3_256::digest(data.as_ref());
@@ -1,5 +1,5 @@
 use crate::error::Error;
-use crate::message::{GetBatchResults, GetMetrics};
+use crate::message::{GetBatchResults, GetMetrics, WriteCommandInput};
 use crate::runtime::Runtime;
 use crate::{ExeUnit, RuntimeRef};
 use actix::prelude::*;
@@ -168,6 +168,40 @@ impl<R: Runtime> Handler<RpcEnvelope<GetExecBatchResults>> for ExeUnit<R> {
     }
 }
 
+impl<R: Runtime> Handler<RpcEnvelope<SendCommandInput>> for ExeUnit<R> {
+    type Result = ActorResponse<Self, (), RpcMessageError>;
+
+    fn handle(
+        &mut self,
+        msg: RpcEnvelope<SendCommandInput>,
+        _: &mut Self::Context,
+    ) -> Self::Result {
+        if let Err(e) = self.ctx.verify_activity_id(&msg.activity_id) {
+            return ActorResponse::reply(Err(e.into()));
+        }
+        if !self.state.batches.contains_key(&msg.batch_id) {
+            let err = RpcMessageError::NotFound(format!("batch_id = {}", msg.batch_id));
+            return ActorResponse::reply(Err(err));
+        }
+
+        let msg = msg.into_inner();
+        let runtime = self.runtime.clone();
+        let fut = async move {
+            let write = WriteCommandInput {
+                batch_id: msg.batch_id,
+                idx: msg.command_index,
+                input: msg.input,
+            };
+            match runtime.send(write).await {
+                Ok(result) => result.map_err(RpcMessageError::from),
+                Err(e) => Err(Error::from(e).into()),
+            }
+        };
+
+        ActorResponse::r#async(fut.into_actor(self))
+    }
+}
+
 impl<R: Runtime> Handler<RpcStreamCall<StreamExecBatchResults>> for ExeUnit<R> {
     type Result = ActorResponse<Self, (), RpcError>;
 
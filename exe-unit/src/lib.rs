@@ -76,14 +76,30 @@ impl<R: Runtime> ExeUnit<R> {
         }
     }
 
-    pub fn offer_template(binary: PathBuf) -> Result<OfferTemplate> {
+    pub fn offer_template(
+        binary: PathBuf,
+        interactive: Option<InteractiveMode>,
+    ) -> Result<OfferTemplate> {
         use crate::runtime::process::RuntimeProcess;
 
         let runtime_template = RuntimeProcess::offer_template(binary)?;
-        let supervisor_template = OfferTemplate::new(serde_json::json!({
+        let mut supervisor_template = serde_json::json!({
             "golem.com.usage.vector": MetricsService::usage_vector(),
             "golem.activity.caps.transfer.protocol": TransferService::schemes(),
-        }));
+        });
+        if let Some(mode) = interactive {
+            // Input is passed only to commands run as separate processes.
+            // Service runtimes (e.g. VMs) run commands themselves.
+            if runs_process_per_command(&runtime_template) {
+                supervisor_template["golem.activity.caps.interactive"] = mode.as_str().into();
+            } else {
+                log::warn!(
+                    "Runtime doesn't declare {} = \"empty\". Interactive mode won't be offered",
+                    START_MODE_PROPERTY
+                );
+            }
+        }
+        let supervisor_template = OfferTemplate::new(supervisor_template);
 
         Ok(supervisor_template.patch(runtime_template))
     }
@@ -329,6 +345,7 @@ impl<R: Runtime> Actor for ExeUnit<R> {
                 actix_rpc::bind::<activity::Exec>(&srv_id, addr.clone().recipient());
                 actix_rpc::bind::<activity::GetExecBatchResults>(&srv_id, addr.clone().recipient());
                 actix_rpc::bind::<activity::GetRunningCommand>(&srv_id, addr.clone().recipient());
+                actix_rpc::bind::<activity::SendCommandInput>(&srv_id, addr.clone().recipient());
                 actix_rpc::binds::<activity::StreamExecBatchResults>(
                     &srv_id,
                     addr.clone().recipient(),
@@ -379,6 +396,7 @@ pub struct ExeUnitContext {
     pub work_dir: PathBuf,
     pub cache_dir: PathBuf,
     pub runtime_args: RuntimeArgs,
+    pub interactive: Option<InteractiveMode>,
     #[cfg(feature = "sgx")]
     #[derivative(Debug = "ignore")]
     pub crypto: crate::crypto::Crypto,
@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use ya_client_model::activity::activity_state::{State, StatePair};
 use ya_client_model::activity::{ExeScriptCommand, ExeScriptCommandResult, RuntimeEvent};
+use ya_core_model::activity::CommandInput;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Message)]
 #[rtype(result = "Result<Vec<f64>>")]
@@ -76,6 +77,15 @@ pub struct ExecuteCommand {
     pub tx: mpsc::Sender<RuntimeEvent>,
 }
 
+/// Input for a running ExeScript command.
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "Result<()>")]
+pub struct WriteCommandInput {
+    pub batch_id: String,
+    pub idx: usize,
+    pub input: CommandInput,
+}
+
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub struct SetTaskPackagePath(pub PathBuf);
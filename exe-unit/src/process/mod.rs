@@ -1,8 +1,12 @@
 #[cfg(unix)]
+mod pty;
+#[cfg(unix)]
 mod unix;
 #[cfg(windows)]
 mod win;
 
+#[cfg(unix)]
+pub use self::pty::{spawn_pty, PtyProcess};
 #[cfg(unix)]
 pub use self::unix::*;
 #[cfg(windows)]
@@ -0,0 +1,193 @@
+use futures::channel::{mpsc, oneshot};
+use futures::StreamExt;
+use nix::libc;
+use nix::pty::{openpty, Winsize};
+use nix::sys::signal::{self, Signal};
+use nix::sys::termios::Termios;
+use nix::unistd::Pid;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use ya_core_model::activity::CommandInput;
+
+nix::ioctl_write_ptr_bad!(set_window_size, libc::TIOCSWINSZ, Winsize);
+
+const DEFAULT_COLS: u16 = 80;
+const DEFAULT_ROWS: u16 = 24;
+/// End of transmission, read as end of file by terminals in canonical mode
+const EOT: u8 = 0x04;
+
+/// Process attached to a pseudo-terminal. Killed on drop, unless already exited.
+pub struct PtyProcess {
+    pub pid: u32,
+    running: Arc<AtomicBool>,
+}
+
+impl Drop for PtyProcess {
+    fn drop(&mut self) {
+        if self.running.load(Ordering::SeqCst) {
+            let _ = signal::kill(Pid::from_raw(self.pid as i32), Signal::SIGKILL);
+        }
+    }
+}
+
+/// Spawns the command in a new session, with a pseudo-terminal as its controlling
+/// terminal and standard streams. Returns the process, terminal output, which ends
+/// when all processes close the terminal, and the exit code.
+pub fn spawn_pty(
+    mut command: Command,
+    mut input: mpsc::Receiver<CommandInput>,
+) -> io::Result<(
+    PtyProcess,
+    mpsc::UnboundedReceiver<Vec<u8>>,
+    oneshot::Receiver<i32>,
+)> {
+    let winsize = window_size(DEFAULT_COLS, DEFAULT_ROWS);
+    let pty = openpty(&winsize, None::<&Termios>).map_err(to_io_error)?;
+    let master = unsafe { File::from_raw_fd(pty.master) };
+    let slave = unsafe { File::from_raw_fd(pty.slave) };
+
+    command
+        .stdin(Stdio::from(slave.try_clone()?))
+        .stdout(Stdio::from(slave.try_clone()?))
+        .stderr(Stdio::from(slave));
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() < 0 || libc::ioctl(0, libc::TIOCSCTTY as _, 0) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    let mut child = command.spawn()?;
+    // Release parent's copies of the slave side
+    drop(command);
+
+    let running = Arc::new(AtomicBool::new(true));
+    let process = PtyProcess {
+        pid: child.id(),
+        running: running.clone(),
+    };
+
+    let (exit_tx, exited) = oneshot::channel();
+    std::thread::spawn(move || {
+        let code = match child.wait() {
+            Ok(status) => status.code().unwrap_or(-1),
+            Err(_) => -1,
+        };
+        running.store(false, Ordering::SeqCst);
+        let _ = exit_tx.send(code);
+    });
+
+    let (output_tx, output) = mpsc::unbounded();
+    let mut reader = master.try_clone()?;
+    std::thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        loop {
+            match reader.read(&mut buf) {
+                // EIO is reported when the slave side has been closed
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    if output_tx.unbounded_send(buf[..n].to_vec()).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+
+    let mut writer = master;
+    std::thread::spawn(move || {
+        while let Some(input) = futures::executor::block_on(input.next()) {
+            let result = match input {
+                CommandInput::Stdin { text } => writer.write_all(text.as_bytes()),
+                CommandInput::Eof {} => writer.write_all(&[EOT]),
+                CommandInput::Resize { cols, rows } => resize(writer.as_raw_fd(), cols, rows),
+            };
+            if let Err(e) = result {
+                log::warn!("Unable to write to pseudo-terminal: {}", e);
+            }
+        }
+    });
+
+    Ok((process, output, exited))
+}
+
+fn resize(fd: RawFd, cols: u16, rows: u16) -> io::Result<()> {
+    let winsize = window_size(cols, rows);
+    unsafe { set_window_size(fd, &winsize) }
+        .map(|_| ())
+        .map_err(to_io_error)
+}
+
+fn window_size(cols: u16, rows: u16) -> Winsize {
+    Winsize {
+        ws_row: rows,
+        ws_col: cols,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    }
+}
+
+fn to_io_error(e: nix::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    fn input(text: &str) -> CommandInput {
+        CommandInput::Stdin {
+            text: text.to_string(),
+        }
+    }
+
+    fn read_until(output: &mut mpsc::UnboundedReceiver<Vec<u8>>, expected: &str) -> String {
+        let mut received = String::new();
+        while !received.contains(expected) {
+            match block_on(output.next()) {
+                Some(chunk) => received.push_str(&String::from_utf8_lossy(&chunk)),
+                None => panic!("output ended before {:?}: {:?}", expected, received),
+            }
+        }
+        received
+    }
+
+    #[test]
+    fn test_pty_echo_and_eof() {
+        let (mut tx, rx) = mpsc::channel(8);
+        let (_process, mut output, exited) = spawn_pty(Command::new("cat"), rx).unwrap();
+
+        tx.try_send(input("hello\n")).unwrap();
+        // Terminal echoes the input, then `cat` writes it back
+        let received = read_until(&mut output, "hello\r\nhello\r\n");
+        assert!(received.starts_with("hello"));
+
+        tx.try_send(CommandInput::Eof {}).unwrap();
+        assert_eq!(block_on(exited).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_pty_resize() {
+        let (mut tx, rx) = mpsc::channel(8);
+        let mut command = Command::new("sh");
+        command.args(&["-c", "stty size; read line; stty size"]);
+        let (_process, mut output, exited) = spawn_pty(command, rx).unwrap();
+
+        read_until(&mut output, &format!("{} {}", DEFAULT_ROWS, DEFAULT_COLS));
+        tx.try_send(CommandInput::Resize {
+            cols: 120,
+            rows: 40,
+        })
+        .unwrap();
+        tx.try_send(input("\n")).unwrap();
+        read_until(&mut output, "40 120");
+        assert_eq!(block_on(exited).unwrap(), 0);
+    }
+}
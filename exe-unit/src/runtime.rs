@@ -3,6 +3,8 @@ use crate::message::*;
 use actix::prelude::*;
 use std::ffi::OsString;
 use std::path::PathBuf;
+use std::str::FromStr;
+use ya_agreement_utils::agreement::{flatten, OfferTemplate};
 use ya_runtime_api::deploy::StartMode;
 
 mod event;
@@ -14,6 +16,7 @@ pub trait Runtime:
     + Handler<ExecuteCommand>
     + Handler<SetTaskPackagePath>
     + Handler<SetRuntimeMode>
+    + Handler<WriteCommandInput>
 {
 }
 
@@ -38,6 +41,52 @@ impl From<StartMode> for RuntimeMode {
     }
 }
 
+/// Offer template property, in which runtime declares `StartMode` of its deployments.
+pub const START_MODE_PROPERTY: &str = "golem.runtime.start_mode";
+
+/// Whether runtime declared, that it runs each command as a separate process.
+/// Runtimes started in `StartMode::Blocking` run commands themselves.
+pub fn runs_process_per_command(runtime_template: &OfferTemplate) -> bool {
+    let properties = flatten(runtime_template.properties.clone());
+    match properties.get(START_MODE_PROPERTY).cloned() {
+        Some(mode) => serde_json::from_value::<StartMode>(mode)
+            .map(|mode| mode == StartMode::Empty)
+            .unwrap_or(false),
+        None => false,
+    }
+}
+
+/// Provider's consent to run ExeScript `Run` commands interactively.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InteractiveMode {
+    /// Requestors may write to commands' standard input
+    Stdin,
+    /// Commands are run in a pseudo-terminal (unix only)
+    Pty,
+}
+
+impl InteractiveMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InteractiveMode::Stdin => "stdin",
+            InteractiveMode::Pty => "pty",
+        }
+    }
+}
+
+impl FromStr for InteractiveMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stdin" => Ok(InteractiveMode::Stdin),
+            "pty" if cfg!(unix) => Ok(InteractiveMode::Pty),
+            "pty" => Err("pseudo-terminals are not supported on this platform".to_string()),
+            _ => Err(format!("invalid interactive mode: {}", s)),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct RuntimeArgs {
     workdir: PathBuf,
@@ -1,29 +1,34 @@
-use crate::error::Error;
-use crate::message::{ExecuteCommand, SetRuntimeMode, SetTaskPackagePath, Shutdown};
+use crate::error::{ChannelError, Error};
+use crate::message::{
+    ExecuteCommand, SetRuntimeMode, SetTaskPackagePath, Shutdown, WriteCommandInput,
+};
 use crate::output::{forward_output, vec_to_string};
 use crate::process::{kill, ProcessTree, SystemError};
 use crate::runtime::event::EventMonitor;
-use crate::runtime::{Runtime, RuntimeArgs, RuntimeMode};
+use crate::runtime::{InteractiveMode, Runtime, RuntimeArgs, RuntimeMode};
 use crate::ExeUnitContext;
 use actix::prelude::*;
+use futures::channel::mpsc;
 use futures::future::{self, LocalBoxFuture};
 use futures::prelude::*;
 use futures::{FutureExt, SinkExt, TryFutureExt};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsString;
 use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
-use tokio::process::Command;
+use tokio::process::{ChildStdin, Command};
 use ya_agreement_utils::agreement::OfferTemplate;
 use ya_client_model::activity::{CommandOutput, ExeScriptCommand, RuntimeEvent};
+use ya_core_model::activity::CommandInput;
 use ya_runtime_api::server::{spawn, ProcessControl, RunProcess, RuntimeService, RuntimeStatus};
 
 const PROCESS_KILL_TIMEOUT_SECONDS_ENV_VAR: &str = "PROCESS_KILL_TIMEOUT_SECONDS";
 const DEFAULT_PROCESS_KILL_TIMEOUT_SECONDS: i64 = 5;
 const MIN_PROCESS_KILL_TIMEOUT_SECONDS: i64 = 1;
 const SERVICE_PROTOCOL_VERSION: &str = "0.1.0";
+const COMMAND_INPUT_BUFFER_SIZE: usize = 16;
 
 fn process_kill_timeout_seconds() -> i64 {
     let limit = std::env::var(PROCESS_KILL_TIMEOUT_SECONDS_ENV_VAR)
@@ -40,6 +45,8 @@ pub struct RuntimeProcess {
     children: HashSet<ChildProcess>,
     service: Option<ProcessService>,
     monitor: Option<EventMonitor>,
+    interactive: Option<InteractiveMode>,
+    inputs: HashMap<CommandKey, CommandInputSender>,
 }
 
 impl RuntimeProcess {
@@ -52,6 +59,8 @@ impl RuntimeProcess {
             children: HashSet::new(),
             service: None,
             monitor: None,
+            interactive: ctx.interactive,
+            inputs: HashMap::new(),
         }
     }
 
@@ -111,6 +120,10 @@ impl RuntimeProcess {
     ) -> LocalBoxFuture<'f, Result<i32, Error>> {
         let idx = cmd.idx;
         let evt_tx = cmd.tx.clone();
+        let interactive = match &cmd.command {
+            ExeScriptCommand::Run { .. } => self.interactive,
+            _ => None,
+        };
 
         let cmd_args = match cmd.command {
             ExeScriptCommand::Deploy {} => {
@@ -148,12 +161,24 @@ impl RuntimeProcess {
 
         let batch_id = cmd.batch_id.clone();
         async move {
-            let mut child = Command::new(binary)
+            #[cfg(unix)]
+            {
+                if let Some(InteractiveMode::Pty) = interactive {
+                    return run_in_pty(binary, args?, batch_id, idx, evt_tx, address).await;
+                }
+            }
+
+            let mut command = Command::new(binary);
+            command
                 .kill_on_drop(true)
                 .args(args?)
                 .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn()?;
+                .stderr(Stdio::piped());
+            if interactive.is_some() {
+                command.stdin(Stdio::piped());
+            }
+            let mut child = command.spawn()?;
+            let child_stdin = child.stdin.take();
 
             let id = batch_id.clone();
             let stdout = forward_output(child.stdout.take().unwrap(), &evt_tx, move |out| {
@@ -172,7 +197,17 @@ impl RuntimeProcess {
             };
             let _guard = ChildProcessGuard::new(proc, address.clone());
 
-            let result = future::join3(child, stdout, stderr).await;
+            let output = future::join3(child, stdout, stderr);
+            let result = match child_stdin {
+                Some(stdin) => {
+                    let (tx, rx) = mpsc::channel(COMMAND_INPUT_BUFFER_SIZE);
+                    let sender = CommandInputSender { tx, pty: false };
+                    let _input_guard =
+                        CommandInputGuard::new((batch_id, idx), sender, address.clone());
+                    with_input(output, write_stdin(stdin, rx)).await
+                }
+                None => output.await,
+            };
             Ok(result.0?.code().unwrap_or(-1))
         }
         .boxed_local()
@@ -323,6 +358,30 @@ impl Handler<ExecuteCommand> for RuntimeProcess {
     }
 }
 
+impl Handler<WriteCommandInput> for RuntimeProcess {
+    type Result = <WriteCommandInput as Message>::Result;
+
+    fn handle(&mut self, msg: WriteCommandInput, _: &mut Self::Context) -> Self::Result {
+        let sender = self
+            .inputs
+            .get_mut(&(msg.batch_id.clone(), msg.idx))
+            .ok_or_else(|| {
+                Error::CommandError(format!(
+                    "command {} of batch {} is not running interactively",
+                    msg.idx, msg.batch_id
+                ))
+            })?;
+        if let (CommandInput::Resize { .. }, false) = (&msg.input, sender.pty) {
+            let err = "command is not running in a pseudo-terminal";
+            return Err(Error::CommandError(err.to_string()));
+        }
+        sender
+            .tx
+            .try_send(msg.input)
+            .map_err(|e| ChannelError::TrySendError(e.to_string()).into())
+    }
+}
+
 impl Handler<SetTaskPackagePath> for RuntimeProcess {
     type Result = <SetTaskPackagePath as Message>::Result;
 
@@ -336,6 +395,10 @@ impl Handler<SetRuntimeMode> for RuntimeProcess {
 
     fn handle(&mut self, msg: SetRuntimeMode, _: &mut Self::Context) -> Self::Result {
         log::info!("Setting runtime mode to: {:?}", msg.0);
+        if let (RuntimeMode::Service, Some(_)) = (&msg.0, self.interactive) {
+            log::warn!("Commands of service runtime can't be run interactively");
+            self.interactive = None;
+        }
         self.mode = msg.0;
         Ok(())
     }
@@ -491,3 +554,156 @@ struct AddChildProcess(ChildProcess);
 #[derive(Message)]
 #[rtype("()")]
 struct RemoveChildProcess(ChildProcess);
+
+#[derive(Message)]
+#[rtype("()")]
+struct AddCommandInput(CommandKey, CommandInputSender);
+
+#[derive(Message)]
+#[rtype("()")]
+struct RemoveCommandInput(CommandKey);
+
+impl Handler<AddCommandInput> for RuntimeProcess {
+    type Result = <AddCommandInput as Message>::Result;
+
+    fn handle(&mut self, msg: AddCommandInput, _: &mut Self::Context) -> Self::Result {
+        self.inputs.insert(msg.0, msg.1);
+    }
+}
+
+impl Handler<RemoveCommandInput> for RuntimeProcess {
+    type Result = <RemoveCommandInput as Message>::Result;
+
+    fn handle(&mut self, msg: RemoveCommandInput, _: &mut Self::Context) -> Self::Result {
+        self.inputs.remove(&msg.0);
+    }
+}
+
+/// Batch id and command index
+type CommandKey = (String, usize);
+
+struct CommandInputSender {
+    tx: mpsc::Sender<CommandInput>,
+    pty: bool,
+}
+
+/// Accepts input for the command while alive.
+struct CommandInputGuard {
+    key: CommandKey,
+    addr: Addr<RuntimeProcess>,
+}
+
+impl CommandInputGuard {
+    fn new(key: CommandKey, sender: CommandInputSender, addr: Addr<RuntimeProcess>) -> Self {
+        addr.do_send(AddCommandInput(key.clone(), sender));
+        CommandInputGuard { key, addr }
+    }
+}
+
+impl Drop for CommandInputGuard {
+    fn drop(&mut self) {
+        self.addr.do_send(RemoveCommandInput(self.key.clone()));
+    }
+}
+
+/// Drives command's output until the command exits. The input writer is dropped then.
+async fn with_input<T>(output: impl Future<Output = T>, writer: impl Future<Output = ()>) -> T {
+    futures::pin_mut!(output);
+    futures::pin_mut!(writer);
+    match future::select(output, writer).await {
+        future::Either::Left((result, _)) => result,
+        future::Either::Right((_, output)) => output.await,
+    }
+}
+
+async fn write_stdin(mut stdin: ChildStdin, mut input: mpsc::Receiver<CommandInput>) {
+    use tokio::io::AsyncWriteExt;
+
+    while let Some(input) = input.next().await {
+        let result = match input {
+            CommandInput::Stdin { text } => stdin.write_all(text.as_bytes()).await,
+            // Dropping the pipe closes it
+            CommandInput::Eof {} => return,
+            CommandInput::Resize { .. } => continue,
+        };
+        if let Err(e) = result {
+            log::warn!("Unable to write to command's stdin: {}", e);
+            return;
+        }
+    }
+}
+
+#[cfg(unix)]
+async fn run_in_pty(
+    binary: PathBuf,
+    args: Vec<OsString>,
+    batch_id: String,
+    idx: usize,
+    evt_tx: mpsc::Sender<RuntimeEvent>,
+    address: Addr<RuntimeProcess>,
+) -> Result<i32, Error> {
+    use crate::process::spawn_pty;
+
+    let mut command = std::process::Command::new(binary);
+    command.args(args);
+
+    let (tx, rx) = mpsc::channel(COMMAND_INPUT_BUFFER_SIZE);
+    let (process, output, exited) = spawn_pty(command, rx)?;
+    let tree = ProcessTree::try_new(process.pid).map_err(Error::runtime)?;
+    let _guard = ChildProcessGuard::new(ChildProcess::from(tree), address.clone());
+    let sender = CommandInputSender { tx, pty: true };
+    let _input_guard = CommandInputGuard::new((batch_id.clone(), idx), sender, address);
+
+    // Terminal merges stdout and stderr
+    let output = output
+        .map(move |out| {
+            let evt = RuntimeEvent::stdout(batch_id.clone(), idx, CommandOutput::Bin(out));
+            Ok::<_, mpsc::SendError>(evt)
+        })
+        .forward(evt_tx);
+    let (code, result) = future::join(exited, output).await;
+    if let Err(e) = result {
+        log::error!("Error forwarding output: {:?}", e);
+    }
+    Ok(code.unwrap_or(-1))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    #[actix_rt::test]
+    async fn test_write_stdin() {
+        let mut child = Command::new("cat")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let stdin = child.stdin.take().unwrap();
+        let mut stdout = child.stdout.take().unwrap();
+
+        let (mut tx, rx) = mpsc::channel(COMMAND_INPUT_BUFFER_SIZE);
+        tx.try_send(CommandInput::Stdin {
+            text: "hello\n".to_string(),
+        })
+        .unwrap();
+        // Not a terminal, so resizing is ignored
+        tx.try_send(CommandInput::Resize {
+            cols: 120,
+            rows: 40,
+        })
+        .unwrap();
+        tx.try_send(CommandInput::Stdin {
+            text: "world\n".to_string(),
+        })
+        .unwrap();
+        tx.try_send(CommandInput::Eof {}).unwrap();
+        write_stdin(stdin, rx).await;
+
+        let mut output = String::new();
+        stdout.read_to_string(&mut output).await.unwrap();
+        assert_eq!(output, "hello\nworld\n");
+        assert!(child.await.unwrap().success());
+    }
+}